@@ -42,15 +42,23 @@ fn main() {
         .unwrap();
     world.set_function_import_weight(function_index, 3).unwrap();
 
+    // Add the queries that let the genetic code look at the game before deciding what to do
+    world
+        .add_function_import("cards_remaining", cards_remaining)
+        .unwrap();
+    world
+        .add_function_import("top_card_rank", top_card_rank)
+        .unwrap();
+
     // Setup the various islands that apply different kinds of pressure to the genetic algorithm.
     world.create_island(Box::new(IslandOne {}));
 
-    // Run the world for 10_000 generations
-    let mut generations_complete = 0;
+    // Run the world for up to 10_000 generations, stopping early once an individual has played every card
+    let mut stop = StopCriteria::new(10_000);
+    stop.set_target_score(52);
     world
         .run_generations_while(|world| {
-            generations_complete += 1;
-            println!("Generation {} is complete", generations_complete);
+            println!("Generation {} is complete", world.generations_complete());
             let most_fit_island_one = world.get_island(0).unwrap().most_fit_individual().unwrap();
             let cards_played = most_fit_island_one.get_run_result().unwrap().cards_played();
             println!("  island one:   {} cards played", cards_played);
@@ -66,9 +74,10 @@ fn main() {
             code.print_for_rust(&mut output, &mut indentation).unwrap();
             println!("  code: {}", output);
 
-            generations_complete < 10_000 && cards_played < 52
+            stop.keep_running(world)
         })
         .unwrap();
+    println!("Stopped because of {:?}", stop.reason().unwrap());
 }
 
 // Draws the next card and returns true if there are still cards to play
@@ -86,3 +95,18 @@ fn move_top_play_pile_card_to_finish(mut caller: Caller<'_, GameState>) -> i32 {
         0
     }
 }
+
+// Returns the number of cards that have not been played to the finished piles
+fn cards_remaining(caller: Caller<'_, GameState>) -> i32 {
+    let game: &GameState = caller.data();
+    (52 - game.number_of_finished_cards()) as i32
+}
+
+// Returns the rank of the top card of the play pile, from 1 for an ace to 13 for a king, or 0 if the pile is empty
+fn top_card_rank(caller: Caller<'_, GameState>) -> i32 {
+    let game: &GameState = caller.data();
+    match game.top_card_of_play_pile() {
+        Some(card) => card.index_in_suit() as i32 + 1,
+        None => 0,
+    }
+}
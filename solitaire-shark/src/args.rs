@@ -0,0 +1,130 @@
+use std::path::PathBuf;
+
+pub const USAGE: &str = "usage: solitaire-shark [options]
+
+options:
+  --generations <n>     the number of generations to run (default 10000)
+  --seed <n>            seeds the world so that a run can be repeated
+  --individuals <n>     the number of individuals on each island (default 100)
+  --max-points <n>      the largest amount of code an individual may have (default 20)
+  --export-wasm <path>  saves the most fit individual of island one as a .wasm module at the end of the run
+  --report <path>       writes a Markdown report of the run at the end of the run
+  --checkpoint <dir>    writes a checkpoint to the directory every 100 generations, keeping the last three
+  --resume <path>       continues the run saved in a checkpoint file, with the same options it was started with
+  --help                shows this message";
+
+/// The settings of one run, from the command line
+#[derive(Clone, Debug, PartialEq)]
+pub struct Args {
+    pub generations: usize,
+    pub seed: Option<u64>,
+    pub individuals_per_island: usize,
+    pub max_points: usize,
+    pub export_wasm: Option<PathBuf>,
+    pub report: Option<PathBuf>,
+    pub checkpoint: Option<PathBuf>,
+    pub resume: Option<PathBuf>,
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Args {
+            generations: 10_000,
+            seed: None,
+            individuals_per_island: 100,
+            max_points: 20,
+            export_wasm: None,
+            report: None,
+            checkpoint: None,
+            resume: None,
+        }
+    }
+}
+
+impl Args {
+    /// Parses the arguments that follow the name of the program. Returns Ok(None) when the usage was asked for, and an
+    /// error message for anything that is not understood.
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Option<Args>, String> {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg == "--help" {
+                return Ok(None);
+            }
+            let value = args.next().ok_or(format!("{} needs a value", arg))?;
+            match arg.as_str() {
+                "--generations" => parsed.generations = number(&arg, &value)?,
+                "--seed" => parsed.seed = Some(number(&arg, &value)?),
+                "--individuals" => parsed.individuals_per_island = number(&arg, &value)?,
+                "--max-points" => parsed.max_points = number(&arg, &value)?,
+                "--export-wasm" => parsed.export_wasm = Some(PathBuf::from(value)),
+                "--report" => parsed.report = Some(PathBuf::from(value)),
+                "--checkpoint" => parsed.checkpoint = Some(PathBuf::from(value)),
+                "--resume" => parsed.resume = Some(PathBuf::from(value)),
+                _ => return Err(format!("unknown option {}", arg)),
+            }
+        }
+        Ok(Some(parsed))
+    }
+}
+
+fn number<N: std::str::FromStr>(arg: &str, value: &str) -> Result<N, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} needs a number, not '{}'", arg, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<Args>, String> {
+        Args::parse(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn parses_every_option() {
+        let args = parse(&[
+            "--generations",
+            "50",
+            "--seed",
+            "7",
+            "--individuals",
+            "20",
+            "--max-points",
+            "30",
+            "--export-wasm",
+            "best.wasm",
+            "--report",
+            "run.md",
+            "--checkpoint",
+            "checkpoints",
+            "--resume",
+            "checkpoints/checkpoint-0000000100.txt",
+        ])
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            Args {
+                generations: 50,
+                seed: Some(7),
+                individuals_per_island: 20,
+                max_points: 30,
+                export_wasm: Some(PathBuf::from("best.wasm")),
+                report: Some(PathBuf::from("run.md")),
+                checkpoint: Some(PathBuf::from("checkpoints")),
+                resume: Some(PathBuf::from("checkpoints/checkpoint-0000000100.txt")),
+            },
+            args
+        );
+        assert_eq!(Some(Args::default()), parse(&[]).unwrap());
+        assert_eq!(None, parse(&["--help"]).unwrap());
+    }
+
+    #[test]
+    fn rejects_bad_options() {
+        assert!(parse(&["--generations"]).is_err());
+        assert!(parse(&["--generations", "many"]).is_err());
+        assert!(parse(&["--colour", "red"]).is_err());
+    }
+}
@@ -289,7 +289,7 @@ impl GameState {
             return None;
         }
 
-        if number_of_cards_down > work_pile.len() {
+        if number_of_cards_down >= work_pile.len() {
             number_of_cards_down = work_pile.len() - 1;
         }
 
@@ -673,6 +673,10 @@ mod tests {
             state.face_up_card_in_work_pile(0, 2)
         );
         // No matter how high it goes, always returns the last
+        assert_eq!(
+            Some(Card::ThreeOfHearts),
+            state.face_up_card_in_work_pile(0, 3)
+        );
         assert_eq!(
             Some(Card::ThreeOfHearts),
             state.face_up_card_in_work_pile(0, 500)
@@ -0,0 +1,68 @@
+use wasmgp::{FitnessCombinator, Goal, Individual, IslandCallbacks, WeightedFitness};
+
+use crate::{game_result::GameResult, game_state::GameState, island_common::*};
+
+pub struct IslandSix {
+    common: IslandCommon,
+    fitness: WeightedFitness<GameResult>,
+}
+
+impl IslandSix {
+    pub fn new() -> IslandSix {
+        // island_six_fitness_score_fn: the pressures of the other five islands in one weighted score
+        let mut fitness = WeightedFitness::new(FitnessCombinator::WeightedSum);
+        fitness.add_metric("games_won", 4.0, Goal::Maximize, |r: &GameResult| {
+            r.games_won() as f64
+        });
+        fitness.add_metric("finished_cards", 2.0, Goal::Maximize, |r: &GameResult| {
+            r.number_of_finished_cards() as f64
+        });
+        fitness.add_metric("draw_stack_cards", 1.0, Goal::Minimize, |r: &GameResult| {
+            r.number_of_draw_stack_cards() as f64
+        });
+        fitness.add_metric("face_down_cards", 1.0, Goal::Minimize, |r: &GameResult| {
+            r.number_of_face_down_cards() as f64
+        });
+        fitness.add_metric("face_up_cards", 1.0, Goal::Minimize, |r: &GameResult| {
+            r.number_of_face_up_cards() as f64
+        });
+
+        IslandSix {
+            common: IslandCommon::new(),
+            fitness,
+        }
+    }
+}
+
+impl IslandCallbacks<GameState, GameResult> for IslandSix {
+    fn pre_generation_run(&mut self, _individuals: &[Individual<GameState, GameResult>]) {
+        self.common.generate_game_seeds();
+    }
+
+    fn run_individual(&mut self, individual: &mut Individual<GameState, GameResult>) {
+        self.common.run_individual(individual);
+    }
+
+    fn post_generation_run(&mut self, individuals: &[Individual<GameState, GameResult>]) {
+        self.fitness.learn_normalization(individuals);
+    }
+
+    fn sort_individuals(
+        &self,
+        a: &Individual<GameState, GameResult>,
+        b: &Individual<GameState, GameResult>,
+    ) -> std::cmp::Ordering {
+        self.fitness.compare(a, b)
+    }
+
+    fn score_individual(&self, i: &Individual<GameState, GameResult>) -> u64 {
+        self.fitness.score(i)
+    }
+
+    fn clone(&self) -> Box<dyn IslandCallbacks<GameState, GameResult>> {
+        Box::new(IslandSix {
+            common: self.common.clone(),
+            fitness: self.fitness.clone(),
+        })
+    }
+}
@@ -1,3 +1,4 @@
+mod args;
 mod card;
 mod game_result;
 mod game_state;
@@ -5,20 +6,35 @@ mod island_common;
 mod island_five;
 mod island_four;
 mod island_one;
+mod island_six;
 mod island_three;
 mod island_two;
 mod suit;
 
+use args::{Args, USAGE};
 use game_result::GameResult;
 use game_state::GameState;
 use island_five::IslandFive;
 use island_four::IslandFour;
 use island_one::IslandOne;
+use island_six::IslandSix;
 use island_three::IslandThree;
 use island_two::IslandTwo;
 use wasmgp::*;
 
 fn main() {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            std::process::exit(2);
+        }
+    };
+
     // Configure the world
     let mut config = WorldConfiguration::default();
     config.main_entry_point = FunctionSignature::new("play_game", [], []);
@@ -28,8 +44,14 @@ fn main() {
         f32: 0,
         f64: 0,
     };
-    config.individual_max_points = 20;
+    config.individual_max_points = args.max_points;
+    config.individuals_per_island = args.individuals_per_island;
     config.individual_run_time_ms = 2;
+    config.seed = args.seed;
+    config.checkpoints = args
+        .checkpoint
+        .as_ref()
+        .map(|directory| CheckpointSchedule::new(directory, 100, 3));
 
     // Create the world with the configuration we specified
     let mut world = World::<GameState, GameResult>::new(config).unwrap();
@@ -53,18 +75,55 @@ fn main() {
         .add_function_import("move_all_piles", move_all_piles)
         .unwrap();
 
+    // Finer-grained actions and queries take their arguments from work slots. Any value names a pile, by wrapping it
+    // around the seven work piles. The single moves are favored over the macro-actions above.
+    let single_moves = [
+        world
+            .add_function_import("move_pile_to_pile", move_pile_to_other_pile)
+            .unwrap(),
+        world
+            .add_function_import("move_play_to_pile", move_play_to_pile)
+            .unwrap(),
+        world
+            .add_function_import("move_pile_to_finish", move_pile_to_finish)
+            .unwrap(),
+    ];
+    for index in single_moves {
+        world.set_function_import_weight(index, 2).unwrap();
+    }
+    world
+        .add_function_import("peek_top_card", peek_top_card)
+        .unwrap();
+    world
+        .add_function_import("peek_play_card", peek_play_card)
+        .unwrap();
+    world
+        .add_function_import("cards_left_in_draw", cards_left_in_draw)
+        .unwrap();
+
     // Setup the various islands that apply different kinds of pressure to the genetic algorithm.
     world.create_island(Box::new(IslandOne::new()));
     world.create_island(Box::new(IslandTwo::new()));
     world.create_island(Box::new(IslandThree::new()));
     world.create_island(Box::new(IslandFour::new()));
     world.create_island(Box::new(IslandFive::new()));
+    world.create_island(Box::new(IslandSix::new()));
+
+    // Pick up where an earlier run left off. The functions and islands above must be in place first, since the
+    // checkpoint only holds the code of the individuals.
+    if let Some(path) = args.resume.as_ref() {
+        world.restore_checkpoint(path).unwrap();
+        println!(
+            "Resumed the run from {} after generation {}",
+            path.display(),
+            world.generations_complete()
+        );
+    }
 
-    // Run the world for 10_000 generations
-    let mut generations_complete = 0;
+    // Run the world until it has completed the requested number of generations
     world
         .run_generations_while(|world| {
-            generations_complete += 1;
+            let generations_complete = world.generations_complete();
             println!("Generation {} is complete", generations_complete);
             let most_fit_island_one = world.get_island(0).unwrap().most_fit_individual().unwrap();
             println!(
@@ -107,6 +166,16 @@ fn main() {
                     .number_of_face_up_cards() as f64
                     / 100.0f64
             );
+            let most_fit_island_six = world.get_island(5).unwrap().most_fit_individual().unwrap();
+            println!(
+                "  island six:   {:.04}% games won, {:.04} avg finished cards",
+                most_fit_island_six.get_run_result().unwrap().games_won() as f64,
+                most_fit_island_six
+                    .get_run_result()
+                    .unwrap()
+                    .number_of_finished_cards() as f64
+                    / 100.0f64
+            );
 
             let mut indentation = Indentation::new(4, 0);
             let mut output = std::string::String::new();
@@ -119,9 +188,38 @@ fn main() {
             code.print_for_rust(&mut output, &mut indentation).unwrap();
             println!("  code: {}", output);
 
-            generations_complete < 10_000
+            // Every so often, show which instructions the elites rely on so the code weights can be adjusted
+            if generations_complete % 100 == 0 {
+                println!(
+                    "  island one opcode usage:\n{}",
+                    world.opcode_usage(0).unwrap()
+                );
+            }
+
+            generations_complete < args.generations
         })
         .unwrap();
+
+    // Save what the run produced
+    if let Some(path) = args.export_wasm.as_ref() {
+        let code = world
+            .get_island(0)
+            .unwrap()
+            .most_fit_individual()
+            .unwrap()
+            .get_code()
+            .to_vec();
+        let binary = world.wasm_binary(&code[..]).unwrap();
+        std::fs::write(path, binary).unwrap();
+        println!(
+            "Saved the most fit individual of island one to {}",
+            path.display()
+        );
+    }
+    if let Some(path) = args.report.as_ref() {
+        world.write_run_report(path).unwrap();
+        println!("Wrote the run report to {}", path.display());
+    }
 }
 
 // Draws the next card and returns true if there are still cards to play
@@ -176,3 +274,56 @@ fn move_all_piles(mut caller: Caller<'_, GameState>) -> i32 {
 
     times_moved
 }
+
+// Turns the value of a work slot into the index of a work pile
+fn work_pile(value: i32) -> usize {
+    value.rem_euclid(7) as usize
+}
+
+// Moves every face up card of one work pile onto another, and returns 1 if they moved
+fn move_pile_to_other_pile(
+    mut caller: Caller<'_, GameState>,
+    source: i32,
+    destination: i32,
+) -> i32 {
+    let (source, destination) = (work_pile(source), work_pile(destination));
+    if source == destination {
+        return 0;
+    }
+    let state: &mut GameState = caller.data_mut();
+    state.move_work_pile_cards_to_another_work_pile(source, 13, destination) as i32
+}
+
+fn move_play_to_pile(mut caller: Caller<'_, GameState>, pile: i32) -> i32 {
+    let state: &mut GameState = caller.data_mut();
+    state.move_top_play_pile_card_to_work_pile(work_pile(pile)) as i32
+}
+
+fn move_pile_to_finish(mut caller: Caller<'_, GameState>, pile: i32) -> i32 {
+    let state: &mut GameState = caller.data_mut();
+    state.move_top_work_pile_card_to_finish(work_pile(pile)) as i32
+}
+
+// Returns the top face up card of the work pile (0 for the ace of spades through 51 for the king of hearts), or -1 if
+// the pile is empty
+fn peek_top_card(caller: Caller<'_, GameState>, pile: i32) -> i32 {
+    let state: &GameState = caller.data();
+    match state.face_up_card_in_work_pile(work_pile(pile), 0) {
+        Some(card) => card as i32,
+        None => -1,
+    }
+}
+
+// Returns the top card of the play pile in the same way as `peek_top_card`
+fn peek_play_card(caller: Caller<'_, GameState>) -> i32 {
+    let state: &GameState = caller.data();
+    match state.top_card_of_play_pile() {
+        Some(card) => card as i32,
+        None => -1,
+    }
+}
+
+fn cards_left_in_draw(caller: Caller<'_, GameState>) -> i32 {
+    let state: &GameState = caller.data();
+    state.number_of_cards_in_draw_pile() as i32
+}
@@ -0,0 +1,93 @@
+//! Measures the three stages that every individual goes through: generating its code, compiling the code into a Wasm
+//! module, and running it. Run with `cargo bench -p wasmgp`, and compare the results before and after a change that is
+//! meant to make the evaluation pipeline faster.
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use wasmgp::*;
+
+// The sizes of code that are measured, in points
+const CODE_SIZES: [usize; 3] = [10, 50, 100];
+
+// The number of individuals on the island when measuring evaluations
+const INDIVIDUALS: usize = 100;
+
+fn code_generation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("code_generation");
+    for points in CODE_SIZES {
+        let mut engine = GeneticEngine::new(GeneticEngineConfiguration::new(Some(1), 10));
+        group.throughput(Throughput::Elements(1));
+        group.bench_with_input(BenchmarkId::from_parameter(points), &points, |b, points| {
+            b.iter(|| engine.random_code_list(*points))
+        });
+    }
+    group.finish();
+}
+
+fn compile(c: &mut Criterion) {
+    let mut group = c.benchmark_group("compile");
+    for points in CODE_SIZES {
+        let mut world = World::<(), EmptyRunResult>::new(WorldConfiguration::default()).unwrap();
+        let mut engine = GeneticEngine::new(GeneticEngineConfiguration::new(Some(1), 10));
+        let programs: Vec<Vec<Code>> = (0..16).map(|_| engine.random_code_list(points)).collect();
+        let mut next = 0;
+        group.throughput(Throughput::Elements(1));
+        group.bench_with_input(BenchmarkId::from_parameter(points), &programs, |b, programs| {
+            b.iter(|| {
+                next = (next + 1) % programs.len();
+                world.instanciate_pre(&programs[next][..]).unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+// Runs each individual once, with nothing to score
+#[derive(Clone)]
+struct RunOnce {}
+
+impl IslandCallbacks<(), EmptyRunResult> for RunOnce {
+    fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+        Box::new(Clone::clone(self))
+    }
+
+    fn run_individual(&mut self, individual: &mut Individual<(), EmptyRunResult>) {
+        let ((), result) = individual.execute::<(), ()>((), ());
+        result.unwrap();
+    }
+}
+
+fn evaluation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("evaluation");
+    for points in CODE_SIZES {
+        let config = WorldConfiguration {
+            individuals_per_island: INDIVIDUALS,
+            individual_max_points: points,
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+
+        // Loops are left out so that every evaluation does a similar amount of work
+        world.reset_all_code_weights(0);
+        world.set_code_weight(Code::ConstI32(ConstI32::default()), 1);
+        world.set_code_weight(Code::Add(Add::default()), 1);
+        world.set_code_weight(Code::Multiply(Multiply::default()), 1);
+        world.set_code_weight(Code::IsLessThan(IsLessThan::default()), 1);
+        world.set_code_weight(Code::If(If::default()), 1);
+        world.create_island(Box::new(RunOnce {}));
+        world.fill_all_islands().unwrap();
+
+        group.throughput(Throughput::Elements(INDIVIDUALS as u64));
+        group.bench_function(BenchmarkId::from_parameter(points), |b| {
+            b.iter(|| {
+                let island = world.get_island_mut(0).unwrap();
+                #[cfg(not(feature = "async"))]
+                island.run_one_generation();
+                #[cfg(feature = "async")]
+                futures::executor::block_on(island.run_one_generation());
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, code_generation, compile, evaluation);
+criterion_main!(benches);
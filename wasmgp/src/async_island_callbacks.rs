@@ -0,0 +1,113 @@
+use crate::{CallbackState, EvaluationContext, Individual, RunResult};
+
+/// The async counterpart to `IslandCallbacks`. Islands created with `World::create_async_island` await these callbacks,
+/// so evaluations that talk to external services (simulators, databases) do not block a thread per individual.
+#[async_trait::async_trait]
+pub trait AsyncIslandCallbacks<T, R: RunResult>: Send {
+    fn clone(&self) -> Box<dyn AsyncIslandCallbacks<T, R>>;
+
+    /// Called before `pre_generation_run` with the indices of the fitness cases that this generation should be
+    /// evaluated on, when `WorldConfiguration::fitness_cases` is set. The indices are sorted and every island receives
+    /// the same ones. The default implementation does nothing.
+    fn use_fitness_cases(&mut self, _cases: &[usize]) {}
+
+    /// Called before `pre_generation_run` with the island's difficulty level. The level is always zero unless the island
+    /// has a `Curriculum`. The default implementation does nothing.
+    fn use_difficulty_level(&mut self, _level: usize) {}
+
+    /// Called before `pre_generation_run` with the generation that is about to run, the island running it, a seed for
+    /// the generation and the stats of the last generation of every island. This is the place to build a
+    /// `SharedFixture` that every individual of the generation is evaluated against. The default implementation does
+    /// nothing.
+    fn use_evaluation_context(&mut self, _context: &EvaluationContext) {}
+
+    /// Trait implementations can use this callback to configure any data that will apply to all individuals in this
+    /// generation. Called once before any individuals are run. The default implementation does nothing.
+    async fn pre_generation_run(&mut self, _individuals: &[Individual<T, R>]) {}
+
+    /// Trait implementations can use this callback to perform any cleanup for this generation. Called once after all
+    /// individuals are run. The default implementation does nothing.
+    async fn post_generation_run(&mut self, _individuals: &[Individual<T, R>]) {}
+
+    /// Run the virtual machine for a single individual. Called once for each individual on the island.
+    ///
+    /// A typical implementation might look like the following:
+    /// ```ignore
+    /// async fn run_individual(&mut self, individual: &mut Individual<MyRunResult>) {
+    ///     // Perform any simulation setup. This may wait on an external simulator or database without blocking the
+    ///     // thread.
+    ///     let state = MyState::connect().await;
+    ///
+    ///     // Execute the individual's code. Note that the individual temporarily owns the state.
+    ///     let (state, result) = individual.execute_async(state, (param1, param2)).await;
+    ///
+    ///     // Calculate how fit this individual is, and store that value. This is the where each island will emphasize
+    ///     // a different feature of an individual. One island may place a higher value on code size, another on
+    ///     // 'winning' at any cost, another on 'not losing', etc
+    ///     individual.set_run_result(Some(my_calculate_fitness_for_island_x(state, result)))
+    /// }
+    /// ```
+    ///
+    /// In a simulation where the inputs do not vary from generation to generation, the implementation may wish to check
+    /// to see if a RunResult has already been saved for each individual, and skipping the function if already
+    /// calculated in a previous run.
+    async fn run_individual(&mut self, individual: &mut Individual<T, R>);
+
+    /// Called as soon as the island has run each individual, with the RunResult that `run_individual` set, so that
+    /// results can be streamed to a dashboard or log without waiting for the end of the generation. Not called when
+    /// `run_individual` left no RunResult. When individuals are run concurrently, it is called on the clone of the
+    /// callbacks that ran the individual, so clones should share where they send results, such as a channel. The
+    /// default implementation does nothing.
+    fn on_individual_evaluated(&mut self, _individual: &Individual<T, R>, _result: &R) {}
+
+    /// Called after each individual is run. Return true when the individual is good enough that the rest of the
+    /// generation does not need to be evaluated, such as when it has a perfect score. The individuals that have not been
+    /// run yet are skipped, and `Individual::was_skipped` returns true for them until they are run again. The default
+    /// implementation always returns false.
+    fn is_good_enough(&self, _individual: &Individual<T, R>) -> bool {
+        false
+    }
+
+    /// Compare two individuals. The sort order is least fit to most fit. Called multiple times by the sorting algorithm
+    /// after all individuals have been run. The default implementation sorts based on the score of the two individuals.
+    /// You should implement your own sorting function if the order of individual is based upon multiple criteria or a
+    /// simple score is impossible to calculate.
+    fn sort_individuals(&self, a: &Individual<T, R>, b: &Individual<T, R>) -> std::cmp::Ordering {
+        self.score_individual(a).cmp(&self.score_individual(b))
+    }
+
+    /// Score the effectiveness of one individual. The default implementation returns zero, indicating the worst
+    /// fitness possible. You should either implement score_individual or sort_individuals. (You may also implement
+    /// both). Use the score if it is easy to boil down the run results to a single number.
+    ///
+    /// The score is also used by the algorithm to determine the best instruction weights, so it can be useful to write
+    /// a score function for use with that algorithm, even if your primary method of choosing individual is by
+    /// implementing sort_individuals.
+    fn score_individual(&self, _i: &Individual<T, R>) -> u64 {
+        0
+    }
+
+    /// Called when the World writes a checkpoint, to save any state that the callbacks carry from one generation to the
+    /// next and that a resumed run needs to continue exactly, such as a `League` with `League::save_state`. The default
+    /// implementation saves nothing.
+    fn save_state(&mut self, _state: &mut CallbackState<T, R>) {}
+
+    /// Called when the World resumes from a checkpoint, with the state that `save_state` saved. The callbacks are the
+    /// ones the resumed World was given, so only what `save_state` saved has to be restored. The default implementation
+    /// restores nothing.
+    fn restore_state(&mut self, _state: &mut CallbackState<T, R>) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+impl<T, R: RunResult> Clone for Box<dyn AsyncIslandCallbacks<T, R>> {
+    fn clone(&self) -> Self {
+        self.as_ref().clone()
+    }
+}
+
+impl<T, R: RunResult> std::fmt::Debug for Box<dyn AsyncIslandCallbacks<T, R>> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:p}", self.as_ref())
+    }
+}
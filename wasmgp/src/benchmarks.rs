@@ -0,0 +1,634 @@
+//! Classic genetic programming problems with known baselines. Each problem implements `Benchmark`, which provides a
+//! `WorldConfiguration` for the problem and sets up a World with one or more islands that are scored on it. Running the
+//! same benchmark before and after a change to an operator or a selection curve shows whether the change helped.
+//!
+//! ```
+//! use wasmgp::benchmarks::*;
+//! use wasmgp::*;
+//!
+//! let benchmark = EvenParity::new(3).unwrap();
+//! let mut config = benchmark.configuration();
+//! config.individuals_per_island = 10;
+//! let mut world = benchmark.create_world(config, 1).unwrap();
+//! world.run_generations_while(|world| world.generations_complete() < 2).unwrap();
+//! let best = world.get_island(0).unwrap().most_fit_individual().unwrap();
+//! assert!(best.get_run_result().unwrap().hits <= benchmark.perfect_hits());
+//! ```
+use crate::*;
+use anyhow::Result;
+use std::cmp::Ordering;
+
+/// The outcome of running an individual on all the cases of a benchmark. `hits` counts the cases that were solved, and
+/// `error` is the total distance from the right answers. An individual that fails to run has no result at all.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BenchmarkResult {
+    pub hits: usize,
+    pub error: f64,
+}
+
+impl RunResult for BenchmarkResult {}
+
+/// A problem that can be used to measure the genetic algorithm. Islands are sorted by hits, and then by the smallest
+/// error. The score of an individual is its number of hits.
+pub trait Benchmark<T: Default>: IslandCallbacks<T, BenchmarkResult> {
+    /// Returns the default configuration with the entry point and the work slots that the problem needs. Most other
+    /// fields may be changed before calling `create_world`.
+    fn configuration(&self) -> WorldConfiguration;
+
+    /// Sets the code weights and adds any function imports that the problem uses. The default implementation does
+    /// nothing.
+    fn prepare_world(&self, _world: &mut World<T, BenchmarkResult>) -> Result<()> {
+        Ok(())
+    }
+
+    /// The number of hits scored by an individual that solves every case
+    fn perfect_hits(&self) -> usize;
+
+    /// Creates a World from the configuration and adds the number of islands, each of which runs this benchmark
+    fn create_world(&self, config: WorldConfiguration, islands: usize) -> Result<World<T, BenchmarkResult>> {
+        let mut world = World::new(config)?;
+        self.prepare_world(&mut world)?;
+        for _ in 0..islands {
+            world.create_island(IslandCallbacks::clone(self));
+        }
+        Ok(world)
+    }
+}
+
+// Orders results by hits and then by the smallest error. Individuals without a result are the least fit.
+fn compare_results(a: Option<&BenchmarkResult>, b: Option<&BenchmarkResult>) -> Ordering {
+    match (a, b) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Less,
+        (Some(_), None) => Ordering::Greater,
+        (Some(a), Some(b)) => a
+            .hits
+            .cmp(&b.hits)
+            .then_with(|| b.error.partial_cmp(&a.error).unwrap_or(Ordering::Equal)),
+    }
+}
+
+fn hits_of<T>(individual: &Individual<T, BenchmarkResult>) -> u64 {
+    individual.get_run_result().map(|r| r.hits as u64).unwrap_or(0)
+}
+
+/// Fits a function of one variable: the individual is passed `x` and returns its guess for `target(x)`. A case is a hit
+/// when the guess is within 0.01 of the target, and the error is the sum of the absolute differences.
+#[derive(Clone)]
+pub struct SymbolicRegression {
+    target: fn(f64) -> f64,
+    cases: Vec<f64>,
+}
+
+impl SymbolicRegression {
+    /// Creates a regression of `target` over the values of `x` in `cases`
+    pub fn new(target: fn(f64) -> f64, cases: Vec<f64>) -> SymbolicRegression {
+        SymbolicRegression { target, cases }
+    }
+
+    /// The quartic polynomial `x^4 + x^3 + x^2 + x`, sampled at twenty evenly spaced points in `[-1, 1)`
+    pub fn quartic() -> SymbolicRegression {
+        SymbolicRegression::new(
+            |x| x * x * x * x + x * x * x + x * x + x,
+            (0..20).map(|i| -1.0 + i as f64 * 0.1).collect(),
+        )
+    }
+}
+
+impl Benchmark<()> for SymbolicRegression {
+    fn configuration(&self) -> WorldConfiguration {
+        WorldConfiguration {
+            main_entry_point: FunctionSignature::new("regress", [ValueType::F64], [ValueType::F64]),
+            work_slots: SlotCount {
+                i32: 0,
+                i64: 0,
+                f32: 0,
+                f64: 4,
+            },
+            individual_max_points: 20,
+            ..WorldConfiguration::default()
+        }
+    }
+
+    fn prepare_world(&self, world: &mut World<(), BenchmarkResult>) -> Result<()> {
+        world.reset_all_code_weights(0);
+        world.set_code_weight(Code::Add(Add::default()), 1);
+        world.set_code_weight(Code::Subtract(Subtract::default()), 1);
+        world.set_code_weight(Code::Multiply(Multiply::default()), 1);
+        world.set_code_weight(Code::Divide(Divide::default()), 1);
+        world.set_code_weight(Code::ConstOne(ConstOne::default()), 1);
+        Ok(())
+    }
+
+    fn perfect_hits(&self) -> usize {
+        self.cases.len()
+    }
+}
+
+impl IslandCallbacks<(), BenchmarkResult> for SymbolicRegression {
+    fn clone(&self) -> Box<dyn IslandCallbacks<(), BenchmarkResult>> {
+        Box::new(Clone::clone(self))
+    }
+
+    fn run_individual(&mut self, individual: &mut Individual<(), BenchmarkResult>) {
+        let mut result = BenchmarkResult { hits: 0, error: 0.0 };
+        for x in self.cases.iter() {
+            let ((), guess) = individual.execute::<(f64,), f64>((), (*x,));
+            let guess = match guess {
+                Ok(guess) => guess,
+                Err(_) => return individual.set_run_result(None),
+            };
+            let error = (guess - (self.target)(*x)).abs();
+            let error = if error.is_nan() { f64::INFINITY } else { error };
+            if error < 0.01 {
+                result.hits += 1;
+            }
+            result.error += error;
+        }
+        individual.set_run_result(Some(result));
+    }
+
+    fn sort_individuals(&self, a: &Individual<(), BenchmarkResult>, b: &Individual<(), BenchmarkResult>) -> Ordering {
+        compare_results(a.get_run_result(), b.get_run_result())
+    }
+
+    fn score_individual(&self, i: &Individual<(), BenchmarkResult>) -> u64 {
+        hits_of(i)
+    }
+}
+
+// The most inputs that a boolean benchmark can pass to the entry point
+const MAX_INPUTS: usize = 11;
+
+// Runs the individual with each bit as a separate i32 parameter, and returns whether the result was non-zero
+fn execute_bits(individual: &mut Individual<(), BenchmarkResult>, b: &[i32]) -> Result<bool> {
+    let ((), result) = match b.len() {
+        1 => individual.execute::<(i32,), i32>((), (b[0],)),
+        2 => individual.execute::<(i32, i32), i32>((), (b[0], b[1])),
+        3 => individual.execute::<(i32, i32, i32), i32>((), (b[0], b[1], b[2])),
+        4 => individual.execute::<(i32, i32, i32, i32), i32>((), (b[0], b[1], b[2], b[3])),
+        5 => individual.execute::<(i32, i32, i32, i32, i32), i32>((), (b[0], b[1], b[2], b[3], b[4])),
+        6 => individual.execute::<(i32, i32, i32, i32, i32, i32), i32>((), (b[0], b[1], b[2], b[3], b[4], b[5])),
+        7 => individual
+            .execute::<(i32, i32, i32, i32, i32, i32, i32), i32>((), (b[0], b[1], b[2], b[3], b[4], b[5], b[6])),
+        8 => individual.execute::<(i32, i32, i32, i32, i32, i32, i32, i32), i32>(
+            (),
+            (b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]),
+        ),
+        9 => individual.execute::<(i32, i32, i32, i32, i32, i32, i32, i32, i32), i32>(
+            (),
+            (b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7], b[8]),
+        ),
+        10 => individual.execute::<(i32, i32, i32, i32, i32, i32, i32, i32, i32, i32), i32>(
+            (),
+            (b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7], b[8], b[9]),
+        ),
+        11 => individual.execute::<(i32, i32, i32, i32, i32, i32, i32, i32, i32, i32, i32), i32>(
+            (),
+            (b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7], b[8], b[9], b[10]),
+        ),
+        _ => unreachable!("the benchmark constructors limit the number of inputs"),
+    };
+    Ok(result? != 0)
+}
+
+// Runs every combination of `inputs` bits and counts the cases where the individual matches `expected`
+fn run_boolean_cases(
+    individual: &mut Individual<(), BenchmarkResult>,
+    inputs: usize,
+    expected: impl Fn(&[i32]) -> bool,
+) {
+    let mut hits = 0;
+    for case in 0..(1u32 << inputs) {
+        let bits: Vec<i32> = (0..inputs).map(|bit| ((case >> bit) & 1) as i32).collect();
+        match execute_bits(individual, &bits[..]) {
+            Ok(output) if output == expected(&bits[..]) => hits += 1,
+            Ok(_) => {}
+            Err(_) => return individual.set_run_result(None),
+        }
+    }
+    let cases = 1usize << inputs;
+    individual.set_run_result(Some(BenchmarkResult {
+        hits,
+        error: (cases - hits) as f64,
+    }))
+}
+
+// Uses the boolean functions And, Or, the negation IsEqualZero, and IfElse. Xor is left out because it makes parity
+// trivial.
+fn prepare_boolean_world(world: &mut World<(), BenchmarkResult>) {
+    world.reset_all_code_weights(0);
+    world.set_code_weight(Code::And(And::default()), 1);
+    world.set_code_weight(Code::Or(Or::default()), 1);
+    world.set_code_weight(Code::IsEqualZero(IsEqualZero::default()), 1);
+    world.set_code_weight(Code::IfElse(IfElse::default()), 1);
+}
+
+fn boolean_configuration(name: &str, inputs: usize) -> WorldConfiguration {
+    WorldConfiguration {
+        main_entry_point: FunctionSignature::new(name, vec![ValueType::I32; inputs], [ValueType::I32]),
+        work_slots: SlotCount {
+            i32: 4,
+            i64: 0,
+            f32: 0,
+            f64: 0,
+        },
+        individual_max_points: 50,
+        ..WorldConfiguration::default()
+    }
+}
+
+/// Returns true when an even number of the inputs are set. Each of the inputs is a separate i32 parameter that is zero
+/// or one, and any non-zero result counts as true. Every combination of inputs is one case.
+#[derive(Clone)]
+pub struct EvenParity {
+    bits: usize,
+}
+
+impl EvenParity {
+    /// Creates an even-parity problem with between two and eleven inputs
+    pub fn new(bits: usize) -> Result<EvenParity> {
+        if !(2..=MAX_INPUTS).contains(&bits) {
+            return Err(WasmgpError::InvalidConfiguration(format!(
+                "even-parity needs between 2 and {} bits",
+                MAX_INPUTS
+            ))
+            .into());
+        }
+        Ok(EvenParity { bits })
+    }
+}
+
+impl Benchmark<()> for EvenParity {
+    fn configuration(&self) -> WorldConfiguration {
+        boolean_configuration("even_parity", self.bits)
+    }
+
+    fn prepare_world(&self, world: &mut World<(), BenchmarkResult>) -> Result<()> {
+        prepare_boolean_world(world);
+        Ok(())
+    }
+
+    fn perfect_hits(&self) -> usize {
+        1 << self.bits
+    }
+}
+
+impl IslandCallbacks<(), BenchmarkResult> for EvenParity {
+    fn clone(&self) -> Box<dyn IslandCallbacks<(), BenchmarkResult>> {
+        Box::new(Clone::clone(self))
+    }
+
+    fn run_individual(&mut self, individual: &mut Individual<(), BenchmarkResult>) {
+        run_boolean_cases(individual, self.bits, |bits| {
+            bits.iter().filter(|bit| **bit != 0).count() % 2 == 0
+        });
+    }
+
+    fn sort_individuals(&self, a: &Individual<(), BenchmarkResult>, b: &Individual<(), BenchmarkResult>) -> Ordering {
+        compare_results(a.get_run_result(), b.get_run_result())
+    }
+
+    fn score_individual(&self, i: &Individual<(), BenchmarkResult>) -> u64 {
+        hits_of(i)
+    }
+}
+
+/// Returns the data input picked by the address inputs. The first `address_bits` parameters are the address, with the
+/// lowest bit first, and the `2^address_bits` parameters after them are the data. The 6-multiplexer has two address
+/// bits and the 11-multiplexer has three.
+#[derive(Clone)]
+pub struct Multiplexer {
+    address_bits: usize,
+}
+
+impl Multiplexer {
+    /// Creates a multiplexer with one, two or three address bits
+    pub fn new(address_bits: usize) -> Result<Multiplexer> {
+        if !(1..=3).contains(&address_bits) {
+            return Err(WasmgpError::InvalidConfiguration("a multiplexer needs 1, 2 or 3 address bits".into()).into());
+        }
+        Ok(Multiplexer { address_bits })
+    }
+
+    /// The 6-multiplexer
+    pub fn six() -> Multiplexer {
+        Multiplexer { address_bits: 2 }
+    }
+
+    /// The 11-multiplexer
+    pub fn eleven() -> Multiplexer {
+        Multiplexer { address_bits: 3 }
+    }
+
+    fn inputs(&self) -> usize {
+        self.address_bits + (1 << self.address_bits)
+    }
+}
+
+impl Benchmark<()> for Multiplexer {
+    fn configuration(&self) -> WorldConfiguration {
+        boolean_configuration("multiplexer", self.inputs())
+    }
+
+    fn prepare_world(&self, world: &mut World<(), BenchmarkResult>) -> Result<()> {
+        prepare_boolean_world(world);
+        Ok(())
+    }
+
+    fn perfect_hits(&self) -> usize {
+        1 << self.inputs()
+    }
+}
+
+impl IslandCallbacks<(), BenchmarkResult> for Multiplexer {
+    fn clone(&self) -> Box<dyn IslandCallbacks<(), BenchmarkResult>> {
+        Box::new(Clone::clone(self))
+    }
+
+    fn run_individual(&mut self, individual: &mut Individual<(), BenchmarkResult>) {
+        let address_bits = self.address_bits;
+        run_boolean_cases(individual, self.inputs(), |bits| {
+            let address = (0..address_bits).fold(0, |address, bit| address | ((bits[bit] as usize) << bit));
+            bits[address_bits + address] != 0
+        });
+    }
+
+    fn sort_individuals(&self, a: &Individual<(), BenchmarkResult>, b: &Individual<(), BenchmarkResult>) -> Ordering {
+        compare_results(a.get_run_result(), b.get_run_result())
+    }
+
+    fn score_individual(&self, i: &Individual<(), BenchmarkResult>) -> u64 {
+        hits_of(i)
+    }
+}
+
+/// The state of the grid that an artificial ant forages on
+#[derive(Clone, Debug, Default)]
+pub struct AntState {
+    food: Vec<Vec<bool>>,
+    row: usize,
+    column: usize,
+    direction: usize,
+    steps_left: usize,
+    eaten: usize,
+}
+
+impl AntState {
+    // Directions are east, south, west and north, in the order that the ant turns right
+    const MOVES: [(isize, isize); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+
+    fn new(food: Vec<Vec<bool>>, max_steps: usize) -> AntState {
+        AntState {
+            food,
+            row: 0,
+            column: 0,
+            direction: 0,
+            steps_left: max_steps,
+            eaten: 0,
+        }
+    }
+
+    /// The number of pieces of food the ant has eaten
+    pub fn eaten(&self) -> usize {
+        self.eaten
+    }
+
+    /// The number of moves and turns the ant has left
+    pub fn steps_left(&self) -> usize {
+        self.steps_left
+    }
+
+    // The square in front of the ant. The grid wraps around at the edges.
+    fn ahead(&self) -> (usize, usize) {
+        let rows = self.food.len() as isize;
+        let columns = self.food[0].len() as isize;
+        let (dr, dc) = Self::MOVES[self.direction];
+        (
+            (self.row as isize + dr).rem_euclid(rows) as usize,
+            (self.column as isize + dc).rem_euclid(columns) as usize,
+        )
+    }
+
+    fn food_ahead(&self) -> bool {
+        let (row, column) = self.ahead();
+        self.food[row][column]
+    }
+
+    fn step(&mut self) {
+        if self.steps_left > 0 {
+            self.steps_left -= 1;
+            let (row, column) = self.ahead();
+            self.row = row;
+            self.column = column;
+            if self.food[row][column] {
+                self.food[row][column] = false;
+                self.eaten += 1;
+            }
+        }
+    }
+
+    fn turn(&mut self, quarters: usize) {
+        if self.steps_left > 0 {
+            self.steps_left -= 1;
+            self.direction = (self.direction + quarters) % 4;
+        }
+    }
+}
+
+fn ant_move(mut caller: Caller<'_, AntState>) {
+    caller.data_mut().step();
+}
+
+fn ant_left(mut caller: Caller<'_, AntState>) {
+    caller.data_mut().turn(3);
+}
+
+fn ant_right(mut caller: Caller<'_, AntState>) {
+    caller.data_mut().turn(1);
+}
+
+fn ant_food_ahead(caller: Caller<'_, AntState>) -> i32 {
+    caller.data().food_ahead() as i32
+}
+
+/// An ant starts in the top left corner of a grid facing east, and eats the food along a trail that has gaps and turns
+/// in it. The ant can call `move`, `left` and `right`, each of which uses one step, and `food_ahead`, which is free.
+/// The entry point is run again and again until the ant is out of steps, or until a run takes no steps. The hits are
+/// the pieces of food that were eaten.
+#[derive(Clone)]
+pub struct ArtificialAnt {
+    food: Vec<Vec<bool>>,
+    max_steps: usize,
+}
+
+impl ArtificialAnt {
+    /// A short trail with three gaps and four turns, which has seventeen pieces of food
+    pub const SAMPLE_TRAIL: &'static str = "\
+.###.##...
+......#...
+......#...
+..........
+......#...
+...####...
+...#......
+..........
+...#......
+...###....";
+
+    /// Creates the problem from a trail where '#' is food and any other character is an empty square. Each line is a
+    /// row of the grid, and all the rows must be the same length.
+    pub fn new(trail: &str, max_steps: usize) -> Result<ArtificialAnt> {
+        let food: Vec<Vec<bool>> = trail
+            .lines()
+            .map(|line| line.chars().map(|c| c == '#').collect())
+            .collect();
+        if food.is_empty() || food[0].is_empty() || food.iter().any(|row| row.len() != food[0].len()) {
+            return Err(WasmgpError::InvalidConfiguration("the trail must be a rectangle of squares".into()).into());
+        }
+        Ok(ArtificialAnt { food, max_steps })
+    }
+
+    /// The sample trail with enough steps to eat all of the food
+    pub fn sample() -> ArtificialAnt {
+        ArtificialAnt::new(ArtificialAnt::SAMPLE_TRAIL, 100).unwrap()
+    }
+}
+
+impl Benchmark<AntState> for ArtificialAnt {
+    fn configuration(&self) -> WorldConfiguration {
+        WorldConfiguration {
+            main_entry_point: FunctionSignature::new("forage", [], []),
+            work_slots: SlotCount {
+                i32: 2,
+                i64: 0,
+                f32: 0,
+                f64: 0,
+            },
+            individual_max_points: 30,
+            ..WorldConfiguration::default()
+        }
+    }
+
+    fn prepare_world(&self, world: &mut World<AntState, BenchmarkResult>) -> Result<()> {
+        world.reset_all_code_weights(0);
+        world.set_code_weight(Code::IfElse(IfElse::default()), 1);
+        for index in [
+            world.add_function_import("move", ant_move)?,
+            world.add_function_import("left", ant_left)?,
+            world.add_function_import("right", ant_right)?,
+            world.add_function_import("food_ahead", ant_food_ahead)?,
+        ] {
+            world.set_function_import_weight(index, 1)?;
+        }
+        Ok(())
+    }
+
+    fn perfect_hits(&self) -> usize {
+        self.food.iter().flatten().filter(|food| **food).count()
+    }
+}
+
+impl IslandCallbacks<AntState, BenchmarkResult> for ArtificialAnt {
+    fn clone(&self) -> Box<dyn IslandCallbacks<AntState, BenchmarkResult>> {
+        Box::new(Clone::clone(self))
+    }
+
+    fn run_individual(&mut self, individual: &mut Individual<AntState, BenchmarkResult>) {
+        let mut state = AntState::new(self.food.clone(), self.max_steps);
+        while state.steps_left() > 0 {
+            let steps_left = state.steps_left();
+            let (returned_state, result) = individual.execute::<(), ()>(state, ());
+            state = returned_state;
+            if result.is_err() {
+                return individual.set_run_result(None);
+            }
+            if state.steps_left() == steps_left {
+                break;
+            }
+        }
+        individual.set_run_result(Some(BenchmarkResult {
+            hits: state.eaten(),
+            error: (self.perfect_hits() - state.eaten()) as f64,
+        }));
+    }
+
+    fn sort_individuals(
+        &self,
+        a: &Individual<AntState, BenchmarkResult>,
+        b: &Individual<AntState, BenchmarkResult>,
+    ) -> Ordering {
+        compare_results(a.get_run_result(), b.get_run_result())
+    }
+
+    fn score_individual(&self, i: &Individual<AntState, BenchmarkResult>) -> u64 {
+        hits_of(i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Runs the code as the only individual of a world for the benchmark, and returns the result
+    fn run<T: Default, B: Benchmark<T> + Clone>(benchmark: &B, code: Vec<Code>) -> Option<BenchmarkResult> {
+        let mut world = benchmark.create_world(benchmark.configuration(), 0).unwrap();
+        let name = benchmark.configuration().main_entry_point.name().clone();
+        let instance_pre = world.instanciate_pre(&code[..]).unwrap();
+        let mut individual = Individual::new(0, code, name, instance_pre, 1000, 1, 0);
+        let mut benchmark = Clone::clone(benchmark);
+        benchmark.run_individual(&mut individual);
+        individual.get_run_result().cloned()
+    }
+
+    #[test]
+    fn regression_rewards_the_exact_function() {
+        // Slot 0 is x, slot 1 is the result and slots 2 and up are work slots
+        let square_plus_x = SymbolicRegression::new(|x| x * x + x, vec![-1.0, 0.0, 0.5, 2.0]);
+        let result = run(&square_plus_x, vec![Multiply::new(0, 0, 2), Add::new(2, 0, 1)]).unwrap();
+        assert_eq!(BenchmarkResult { hits: 4, error: 0.0 }, result);
+
+        let result = run(&square_plus_x, vec![CopySlot::new(0, 1)]).unwrap();
+        assert_eq!(1, result.hits);
+        assert_eq!(1.0 + 0.25 + 4.0, result.error);
+    }
+
+    #[test]
+    fn boolean_problems_count_every_combination() {
+        // Even parity of two bits is the negation of their exclusive or
+        let parity = EvenParity::new(2).unwrap();
+        let result = run(&parity, vec![Xor::new(0, 1, 3), IsEqualZero::new(3, 2)]).unwrap();
+        assert_eq!(4, result.hits);
+        assert!(EvenParity::new(12).is_err());
+
+        // A constant answer is right for half of the cases
+        let multiplexer = Multiplexer::six();
+        assert_eq!(64, multiplexer.perfect_hits());
+        let result = run(&multiplexer, vec![ConstZero::new(6)]).unwrap();
+        assert_eq!(BenchmarkResult { hits: 32, error: 32.0 }, result);
+
+        // With one address bit, the data inputs are parameters one and two
+        let result = run(
+            &Multiplexer::new(1).unwrap(),
+            vec![IfElse::new(0, vec![CopySlot::new(2, 3)], vec![CopySlot::new(1, 3)])],
+        )
+        .unwrap();
+        assert_eq!(8, result.hits);
+    }
+
+    #[test]
+    fn the_ant_stops_when_it_is_out_of_steps() {
+        let ant = ArtificialAnt::sample();
+        assert_eq!(17, ant.perfect_hits());
+
+        // Import 0 is 'move'. Moving straight ahead eats the food on the first row.
+        let result = run(&ant, vec![Call::new(0, vec![], vec![])]).unwrap();
+        assert_eq!(5, result.hits);
+
+        // A program that never moves or turns is only run once
+        let result = run(&ant, vec![]).unwrap();
+        assert_eq!(0, result.hits);
+
+        assert!(ArtificialAnt::new("#.\n#", 10).is_err());
+    }
+}
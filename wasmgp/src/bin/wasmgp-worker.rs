@@ -0,0 +1,26 @@
+use wasmgp::*;
+
+// Rewards programs for having fewer points. It does not run the code, which makes it a cheap way to check that the
+// islands of a run can reach their workers.
+#[derive(Clone)]
+struct Smallest;
+
+impl IslandCallbacks<(), EmptyRunResult> for Smallest {
+    fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+        Box::new(Clone::clone(self))
+    }
+
+    fn run_individual(&mut self, _individual: &mut Individual<(), EmptyRunResult>) {}
+
+    fn score_individual(&self, individual: &Individual<(), EmptyRunResult>) -> u64 {
+        let points: usize = individual.get_code().iter().map(|code| code.points()).sum();
+        u64::MAX - points as u64
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let world = World::<(), EmptyRunResult>::new(WorldConfiguration::default())?;
+    let mut worker = Worker::new(world);
+    worker.register_fitness("smallest", Box::new(Smallest));
+    worker.serve_stdio()
+}
@@ -0,0 +1,103 @@
+use crate::CodeStream;
+use std::ops::AddAssign;
+
+// The most buffers an engine keeps for reuse. A crossover needs three at once, and the rest cover a mutation that is
+// bred while the engine still holds the streams of its parents.
+const MAX_KEPT_BUFFERS: usize = 8;
+
+/// How many code stream buffers a `GeneticEngine` needed while breeding. Every mutation and crossover turns code into
+/// streams, and the engine keeps the buffers of those streams to use again for the next child. A buffer is only
+/// allocated when none is free, so once the first few children have been bred almost every buffer is reused.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BufferCounts {
+    /// The number of buffers that had to be allocated
+    pub allocated: usize,
+
+    /// The number of buffers that were reused from an earlier child
+    pub reused: usize,
+}
+
+impl AddAssign for BufferCounts {
+    fn add_assign(&mut self, other: BufferCounts) {
+        self.allocated += other.allocated;
+        self.reused += other.reused;
+    }
+}
+
+// The stream buffers kept by a GeneticEngine between children, along with the counts of how they were used
+#[derive(Default)]
+pub(crate) struct BreedingBuffers {
+    free: Vec<Vec<CodeStream>>,
+    counts: BufferCounts,
+}
+
+impl BreedingBuffers {
+    // Returns an empty buffer, reusing a free one if there is any
+    pub fn take(&mut self) -> Vec<CodeStream> {
+        match self.free.pop() {
+            Some(buffer) => {
+                self.counts.reused += 1;
+                buffer
+            }
+            None => {
+                self.counts.allocated += 1;
+                vec![]
+            }
+        }
+    }
+
+    // Keeps the buffer for the next child, unless enough are already kept
+    pub fn give_back(&mut self, mut buffer: Vec<CodeStream>) {
+        if self.free.len() < MAX_KEPT_BUFFERS {
+            buffer.clear();
+            self.free.push(buffer);
+        }
+    }
+
+    pub fn counts(&self) -> BufferCounts {
+        self.counts
+    }
+
+    pub fn take_counts(&mut self) -> BufferCounts {
+        std::mem::take(&mut self.counts)
+    }
+
+    // Drops the free buffers, so that their memory is returned
+    pub fn clear(&mut self) {
+        self.free.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn buffers_are_reused_once_given_back() {
+        let mut buffers = BreedingBuffers::default();
+        let mut first = buffers.take();
+        CodeStream::extend_stream(&mut first, &[ConstOne::new(0), If::new(0, vec![ConstZero::new(1)])]);
+        let capacity = first.capacity();
+        buffers.give_back(first);
+
+        let second = buffers.take();
+        assert!(second.is_empty());
+        assert_eq!(capacity, second.capacity());
+        let third = buffers.take();
+        assert_eq!(
+            BufferCounts {
+                allocated: 2,
+                reused: 1
+            },
+            buffers.take_counts()
+        );
+        assert_eq!(BufferCounts::default(), buffers.counts());
+
+        buffers.give_back(second);
+        buffers.give_back(third);
+        buffers.clear();
+        buffers.take();
+        assert_eq!(1, buffers.counts().allocated);
+    }
+}
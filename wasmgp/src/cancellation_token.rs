@@ -0,0 +1,35 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A CancellationToken is shared between a running World and any other thread or task that may want to stop it.
+/// Every clone refers to the same flag, so cancelling any clone cancels them all.
+/// ```
+/// use wasmgp::*;
+///
+/// let token = CancellationToken::new();
+/// let other_task = token.clone();
+/// assert!(!token.is_cancelled());
+///
+/// other_task.cancel();
+/// assert!(token.is_cancelled());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    /// Requests that the work watching this token stops at the next opportunity
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns true if `cancel` has been called on this token or any of its clones
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
@@ -0,0 +1,19 @@
+use crate::{IndividualId, IslandId};
+
+/// The code of the most fit individual of an island, as printed by `Individual::get_code_string`, recorded every
+/// `champion_listing_interval` generations. Reading the listings of one island in order shows how its best program
+/// changed over the run.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChampionListing {
+    pub island: IslandId,
+
+    /// The generation that had just completed when the listing was recorded
+    pub generation: usize,
+
+    pub individual_id: IndividualId,
+
+    /// The score of the individual from `IslandCallbacks::score_individual`
+    pub score: u64,
+
+    pub listing: String,
+}
@@ -0,0 +1,725 @@
+use crate::code_builder::CodeBuilder;
+use crate::indentation::Indentation;
+use crate::{
+    parse_code_listing, Code, Individual, IndividualId, InstructionSet, IslandId, NumericEvents, RunResult, WasmgpError,
+};
+use anyhow::{Context, Result};
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Settings for writing checkpoints automatically while `World::run_generations_while` runs, so that a crash costs no
+/// more than one checkpoint interval. A checkpoint is written after every `every_generations` generations, and after
+/// the first generation to end `every_minutes` or more after the last checkpoint. Only the `keep` most recent
+/// checkpoints are kept in the `directory`.
+///
+/// After a restart, create the world and its islands just as before and pass `latest_checkpoint` to
+/// `World::restore_checkpoint`, or pass it to `World::resume` with the configuration and the callbacks of the islands.
+/// ```no_run
+/// use wasmgp::*;
+///
+/// let schedule = CheckpointSchedule::new("checkpoints", 50, 3);
+/// let config = WorldConfiguration {
+///     checkpoints: Some(schedule.clone()),
+///     ..WorldConfiguration::default()
+/// };
+/// let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+/// // ... create the islands ...
+/// if let Some(path) = schedule.latest_checkpoint().unwrap() {
+///     world.restore_checkpoint(path).unwrap();
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CheckpointSchedule {
+    /// The directory where the checkpoint files are written. It is created if it does not exist.
+    pub directory: PathBuf,
+
+    /// The number of generations between checkpoints, or zero to only write them by time
+    pub every_generations: usize,
+
+    /// The number of minutes between checkpoints, or zero to only write them by generation
+    pub every_minutes: u64,
+
+    /// The number of checkpoints to keep. Older checkpoint files in the directory are removed. Must be at least one.
+    pub keep: usize,
+}
+
+impl CheckpointSchedule {
+    /// Creates a schedule that writes a checkpoint every `every_generations` generations and keeps the last `keep`
+    pub fn new<P: Into<PathBuf>>(directory: P, every_generations: usize, keep: usize) -> CheckpointSchedule {
+        CheckpointSchedule {
+            directory: directory.into(),
+            every_generations,
+            every_minutes: 0,
+            keep,
+        }
+    }
+
+    /// Returns the paths of the checkpoint files in the directory, oldest first. A directory that does not exist has no
+    /// checkpoints.
+    pub fn checkpoint_files(&self) -> Result<Vec<PathBuf>> {
+        if !self.directory.exists() {
+            return Ok(vec![]);
+        }
+        let mut files = vec![];
+        let entries = std::fs::read_dir(&self.directory)
+            .with_context(|| format!("failed to list the checkpoints in {}", self.directory.display()))?;
+        for entry in entries {
+            let path = entry?.path();
+            if let Some(generation) = checkpoint_generation(&path) {
+                files.push((generation, path));
+            }
+        }
+        files.sort();
+        Ok(files.into_iter().map(|(_, path)| path).collect())
+    }
+
+    /// Returns the path of the most recent checkpoint, or None if none have been written
+    pub fn latest_checkpoint(&self) -> Result<Option<PathBuf>> {
+        Ok(self.checkpoint_files()?.pop())
+    }
+
+    // The path of the checkpoint for the end of `generation`
+    pub(crate) fn checkpoint_path(&self, generation: usize) -> PathBuf {
+        self.directory.join(format!("checkpoint-{:010}.txt", generation))
+    }
+
+    pub(crate) fn is_due(&self, generations_complete: usize, since_last_checkpoint: Duration) -> bool {
+        (self.every_generations > 0 && generations_complete % self.every_generations == 0)
+            || (self.every_minutes > 0 && since_last_checkpoint >= Duration::from_secs(self.every_minutes * 60))
+    }
+
+    // Removes all but the `keep` most recent checkpoints
+    pub(crate) fn remove_old_checkpoints(&self) -> Result<()> {
+        let files = self.checkpoint_files()?;
+        for path in files.iter().take(files.len().saturating_sub(self.keep)) {
+            std::fs::remove_file(path)
+                .with_context(|| format!("failed to remove the checkpoint {}", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+/// The state that island callbacks carry from one generation to the next, such as the members and the random number
+/// generator of a `League`. The World passes it to `IslandCallbacks::save_state` when it writes a checkpoint, and hands
+/// what was saved back to `IslandCallbacks::restore_state` when a run resumes from the checkpoint. Numbers and
+/// individuals are each taken back in the order they were saved. An individual comes back with its ID and code, and
+/// with its RunResult when `RunResult::save_result` can write it.
+/// ```
+/// use wasmgp::*;
+///
+/// let mut state = CallbackState::<(), EmptyRunResult>::default();
+/// state.push_number(7);
+/// state.push_number(11);
+/// assert_eq!(7, state.pop_number().unwrap());
+/// assert_eq!(11, state.pop_number().unwrap());
+/// assert!(state.pop_number().is_err());
+/// assert!(state.pop_individual().is_err());
+/// ```
+pub struct CallbackState<T, R: RunResult> {
+    numbers: VecDeque<u64>,
+    individuals: VecDeque<Individual<T, R>>,
+}
+
+impl<T, R: RunResult> Default for CallbackState<T, R> {
+    fn default() -> Self {
+        CallbackState {
+            numbers: VecDeque::new(),
+            individuals: VecDeque::new(),
+        }
+    }
+}
+
+impl<T, R: RunResult> CallbackState<T, R> {
+    /// Saves a number, such as the state from `League::capture_rng_state`
+    pub fn push_number(&mut self, number: u64) {
+        self.numbers.push_back(number);
+    }
+
+    /// Takes back the oldest number that has not been taken yet. Returns `WasmgpError::InvalidCheckpoint` if every
+    /// number has been taken, such as when the checkpoint was written by callbacks that saved less.
+    pub fn pop_number(&mut self) -> Result<u64> {
+        self.numbers
+            .pop_front()
+            .ok_or_else(|| checkpoint_error("the island callbacks saved fewer numbers than they restore"))
+    }
+
+    /// Saves a clone of the individual, such as a member of a `League`
+    pub fn push_individual(&mut self, individual: &Individual<T, R>) {
+        self.individuals.push_back(individual.clone());
+    }
+
+    /// Takes back the oldest individual that has not been taken yet. Returns `WasmgpError::InvalidCheckpoint` if every
+    /// individual has been taken.
+    pub fn pop_individual(&mut self) -> Result<Individual<T, R>> {
+        self.individuals
+            .pop_front()
+            .ok_or_else(|| checkpoint_error("the island callbacks saved fewer individuals than they restore"))
+    }
+
+    pub(crate) fn numbers(&self) -> Vec<u64> {
+        self.numbers.iter().copied().collect()
+    }
+
+    pub(crate) fn individuals(&self) -> impl Iterator<Item = &Individual<T, R>> {
+        self.individuals.iter()
+    }
+
+    pub(crate) fn from_parts(numbers: Vec<u64>, individuals: Vec<Individual<T, R>>) -> CallbackState<T, R> {
+        CallbackState {
+            numbers: numbers.into(),
+            individuals: individuals.into(),
+        }
+    }
+}
+
+// Returns the generation of a checkpoint file from its name, or None if it is not a checkpoint
+fn checkpoint_generation(path: &Path) -> Option<usize> {
+    let name = path.file_name()?.to_str()?;
+    name.strip_prefix("checkpoint-")?.strip_suffix(".txt")?.parse().ok()
+}
+
+// The state of a World that is saved in a checkpoint. Checkpoints written before the instruction set, the countdowns,
+// the code weights, the subtree archive or the hall of fame were recorded have none.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Checkpoint {
+    pub instruction_set: Option<InstructionSet>,
+    pub generations_complete: usize,
+    pub rng_state: u64,
+    pub next_individual_id: IndividualId,
+    pub generations_remaining_before_migration: Option<usize>,
+    pub fitness_cases: Option<FitnessCaseCountdown>,
+    pub code_weight_levels: Option<Vec<f64>>,
+    pub subtree_archive: Option<Vec<Code>>,
+    pub hall_of_fame: Vec<(u64, IndividualId, IslandId)>,
+    pub islands: Vec<CheckpointIsland>,
+}
+
+// One island of a checkpoint. The current generation is only saved when it was sorted and the results of all of its
+// individuals could be saved; otherwise it is saved with the future generation, to be run again. Checkpoints written
+// before the rest of the island was recorded have only the future generation.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct CheckpointIsland {
+    pub difficulty_level: Option<usize>,
+    pub generations: Option<usize>,
+    pub target_size: Option<usize>,
+    pub subtree_archive: Option<Vec<Code>>,
+    pub callback_numbers: Vec<u64>,
+    pub callback_teams: Vec<CheckpointTeam>,
+    pub current: Vec<CheckpointTeam>,
+    pub future: Vec<CheckpointTeam>,
+}
+
+impl CheckpointIsland {
+    fn teams_mut(&mut self, section: Section) -> &mut Vec<CheckpointTeam> {
+        match section {
+            Section::CallbackIndividuals => &mut self.callback_teams,
+            Section::Current => &mut self.current,
+            Section::Future => &mut self.future,
+        }
+    }
+}
+
+// The ID and the code of every member of a team, and how the team did in its last evaluation. Checkpoints written
+// before the IDs were recorded have none, and a team whose RunResult cannot be saved has no evaluation.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct CheckpointTeam {
+    pub members: Vec<(Option<IndividualId>, Vec<Code>)>,
+    pub evaluation: Option<CheckpointEvaluation>,
+}
+
+impl CheckpointTeam {
+    // Saves the members of the team, and the evaluation of its lead when its RunResult can be written on one line
+    pub fn from_individual<T, R: RunResult>(individual: &Individual<T, R>) -> CheckpointTeam {
+        let members = (0..individual.team_size())
+            .filter_map(|member| individual.get_team_member(member))
+            .map(|member| (Some(member.get_id()), member.get_code().to_vec()))
+            .collect();
+        let run_result = match individual.get_run_result() {
+            Some(result) => match result.save_result().filter(|text| !text.contains('\n')) {
+                Some(text) => Some(text),
+                None => {
+                    return CheckpointTeam {
+                        members,
+                        evaluation: None,
+                    }
+                }
+            },
+            None => None,
+        };
+        CheckpointTeam {
+            members,
+            evaluation: Some(CheckpointEvaluation {
+                stage: individual.get_evaluation_stage(),
+                skipped: individual.was_skipped(),
+                numeric_events: individual.get_numeric_events(),
+                run_result,
+            }),
+        }
+    }
+}
+
+// The evaluation stage the team reached, whether it was skipped, its numeric events and its RunResult as written by
+// `RunResult::save_result`
+#[derive(Debug, PartialEq)]
+pub(crate) struct CheckpointEvaluation {
+    pub stage: usize,
+    pub skipped: bool,
+    pub numeric_events: NumericEvents,
+    pub run_result: Option<String>,
+}
+
+// The fitness cases in use and the number of generations until new ones are drawn
+#[derive(Debug, PartialEq)]
+pub(crate) struct FitnessCaseCountdown {
+    pub generations_until_new_cases: usize,
+    pub cases: Vec<usize>,
+}
+
+// The part of an island that the teams which follow belong to. Teams before the first section belong to the future
+// generation.
+#[derive(Clone, Copy)]
+enum Section {
+    CallbackIndividuals,
+    Current,
+    Future,
+}
+
+const CHECKPOINT_HEADER: &str = "wasmgp checkpoint";
+
+impl Checkpoint {
+    // Writes the checkpoint as text. Code is printed by `print_for_rust`, so a top-level list ends with the only
+    // unindented `]` line.
+    pub fn write_text(&self, f: &mut String) -> std::fmt::Result {
+        writeln!(f, "{}", CHECKPOINT_HEADER)?;
+        if let Some(instruction_set) = self.instruction_set {
+            writeln!(f, "instruction_set {}", instruction_set)?;
+        }
+        writeln!(f, "generations_complete {}", self.generations_complete)?;
+        writeln!(f, "rng_state {}", self.rng_state)?;
+        writeln!(f, "next_individual_id {}", self.next_individual_id)?;
+        if let Some(generations) = self.generations_remaining_before_migration {
+            writeln!(f, "generations_remaining_before_migration {}", generations)?;
+        }
+        if let Some(countdown) = &self.fitness_cases {
+            write!(f, "fitness_cases {}", countdown.generations_until_new_cases)?;
+            for case in countdown.cases.iter() {
+                write!(f, " {}", case)?;
+            }
+            writeln!(f)?;
+        }
+        if let Some(levels) = &self.code_weight_levels {
+            write!(f, "code_weights")?;
+            for level in levels.iter() {
+                write!(f, " {}", level)?;
+            }
+            writeln!(f)?;
+        }
+        for (score, individual, island) in self.hall_of_fame.iter() {
+            writeln!(f, "hall_of_fame {} {} {}", score, individual, island)?;
+        }
+        if let Some(subtrees) = &self.subtree_archive {
+            write_listing(f, "subtree_archive", subtrees)?;
+        }
+        for (id, island) in self.islands.iter().enumerate() {
+            writeln!(f, "island {}", id)?;
+            if let Some(level) = island.difficulty_level {
+                writeln!(f, "difficulty_level {}", level)?;
+            }
+            if let Some(generations) = island.generations {
+                writeln!(f, "generations {}", generations)?;
+            }
+            if let Some(target_size) = island.target_size {
+                writeln!(f, "target_size {}", target_size)?;
+            }
+            if !island.callback_numbers.is_empty() {
+                write!(f, "callback_state")?;
+                for number in island.callback_numbers.iter() {
+                    write!(f, " {}", number)?;
+                }
+                writeln!(f)?;
+            }
+            if let Some(subtrees) = &island.subtree_archive {
+                write_listing(f, "subtree_archive", subtrees)?;
+            }
+            let sections = [
+                ("callback_individuals", &island.callback_teams),
+                ("current", &island.current),
+                ("future", &island.future),
+            ];
+            for (name, teams) in sections {
+                if teams.is_empty() {
+                    continue;
+                }
+                writeln!(f, "{}", name)?;
+                for team in teams.iter() {
+                    write_team(f, team)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn from_text(text: &str) -> Result<Checkpoint> {
+        let mut lines = text.lines();
+        if lines.next() != Some(CHECKPOINT_HEADER) {
+            return Err(checkpoint_error("the file does not start with the checkpoint header"));
+        }
+        let mut line = lines.next();
+        let instruction_set = match line.and_then(|line| line.strip_prefix("instruction_set ")) {
+            Some(text) => {
+                line = lines.next();
+                Some(
+                    text.parse()
+                        .map_err(|_| checkpoint_error("the instruction set could not be read"))?,
+                )
+            }
+            None => None,
+        };
+        let mut checkpoint = Checkpoint {
+            instruction_set,
+            generations_complete: header_value(line, "generations_complete")?,
+            rng_state: header_value(lines.next(), "rng_state")?,
+            next_individual_id: header_value(lines.next(), "next_individual_id")?,
+            generations_remaining_before_migration: None,
+            fitness_cases: None,
+            code_weight_levels: None,
+            subtree_archive: None,
+            hall_of_fame: vec![],
+            islands: vec![],
+        };
+
+        let mut section = Section::Future;
+        while let Some(line) = lines.next() {
+            let (name, value) = line.split_once(' ').unwrap_or((line, ""));
+            match line {
+                _ if name == "individual" || name == "member" => {
+                    let id = match value {
+                        "" => None,
+                        id => Some(
+                            id.parse()
+                                .map_err(|_| checkpoint_error(format!("the ID of '{}' could not be read", line)))?,
+                        ),
+                    };
+                    let code = read_listing(&mut lines)?;
+                    let teams = checkpoint
+                        .islands
+                        .last_mut()
+                        .ok_or_else(|| checkpoint_error("an individual comes before the first island"))?
+                        .teams_mut(section);
+                    if name == "individual" {
+                        teams.push(CheckpointTeam {
+                            members: vec![(id, code)],
+                            evaluation: None,
+                        });
+                    } else {
+                        teams
+                            .last_mut()
+                            .ok_or_else(|| checkpoint_error("a team member comes before its individual"))?
+                            .members
+                            .push((id, code));
+                    }
+                }
+                _ if name == "evaluated" => {
+                    let evaluation = read_evaluation(value)
+                        .ok_or_else(|| checkpoint_error(format!("the evaluation '{}' could not be read", line)))?;
+                    last_team(&mut checkpoint, section)?.evaluation = Some(evaluation);
+                }
+                _ if name == "run_result" => {
+                    last_team(&mut checkpoint, section)?
+                        .evaluation
+                        .as_mut()
+                        .ok_or_else(|| checkpoint_error("a run result comes before its evaluation"))?
+                        .run_result = Some(value.to_string());
+                }
+                "callback_individuals" | "current" | "future" => {
+                    last_island(&mut checkpoint, "a list of individuals")?;
+                    section = match line {
+                        "callback_individuals" => Section::CallbackIndividuals,
+                        "current" => Section::Current,
+                        _ => Section::Future,
+                    };
+                }
+                "subtree_archive" => {
+                    let subtrees = Some(read_listing(&mut lines)?);
+                    match checkpoint.islands.last_mut() {
+                        Some(island) => island.subtree_archive = subtrees,
+                        None => checkpoint.subtree_archive = subtrees,
+                    }
+                }
+                line if line.starts_with("generations_remaining_before_migration ") => {
+                    checkpoint.generations_remaining_before_migration =
+                        Some(header_value(Some(line), "generations_remaining_before_migration")?);
+                }
+                line if line.starts_with("fitness_cases ") => {
+                    let numbers = parse_numbers(value)
+                        .filter(|numbers: &Vec<usize>| !numbers.is_empty())
+                        .ok_or_else(|| checkpoint_error("the fitness cases could not be read"))?;
+                    checkpoint.fitness_cases = Some(FitnessCaseCountdown {
+                        generations_until_new_cases: numbers[0],
+                        cases: numbers[1..].to_vec(),
+                    });
+                }
+                line if line.starts_with("code_weights ") => {
+                    checkpoint.code_weight_levels = Some(
+                        parse_numbers(value).ok_or_else(|| checkpoint_error("the code weights could not be read"))?,
+                    );
+                }
+                line if line.starts_with("hall_of_fame ") => {
+                    let entry = match parse_numbers::<u64>(value).as_deref() {
+                        Some([score, individual, island]) => (*score, *individual, *island as IslandId),
+                        _ => return Err(checkpoint_error(format!("the entry '{}' could not be read", line))),
+                    };
+                    checkpoint.hall_of_fame.push(entry);
+                }
+                line if line.starts_with("island ") => {
+                    let id: usize = header_value(Some(line), "island")?;
+                    if id != checkpoint.islands.len() {
+                        return Err(checkpoint_error(format!("island {} is out of order", id)));
+                    }
+                    checkpoint.islands.push(CheckpointIsland::default());
+                    section = Section::Future;
+                }
+                line if line.starts_with("callback_state ") => {
+                    let numbers = parse_numbers(value)
+                        .ok_or_else(|| checkpoint_error("the state of the island callbacks could not be read"))?;
+                    last_island(&mut checkpoint, "the state of the callbacks")?.callback_numbers = numbers;
+                }
+                line if line.starts_with("difficulty_level ") => {
+                    last_island(&mut checkpoint, "a difficulty level")?.difficulty_level =
+                        Some(header_value(Some(line), "difficulty_level")?);
+                }
+                line if line.starts_with("generations ") => {
+                    last_island(&mut checkpoint, "a generation count")?.generations =
+                        Some(header_value(Some(line), "generations")?);
+                }
+                line if line.starts_with("target_size ") => {
+                    last_island(&mut checkpoint, "a target size")?.target_size =
+                        Some(header_value(Some(line), "target_size")?);
+                }
+                "" => {}
+                line => return Err(checkpoint_error(format!("unexpected line '{}'", line))),
+            }
+        }
+        Ok(checkpoint)
+    }
+}
+
+// Writes a line with the name, and then the code on the lines that follow
+fn write_listing(f: &mut String, name: &str, code: &Vec<Code>) -> std::fmt::Result {
+    writeln!(f, "{}", name)?;
+    code.print_for_rust(f, &mut Indentation::new(2, 0))?;
+    writeln!(f)
+}
+
+// Writes every member of the team, and then its evaluation
+fn write_team(f: &mut String, team: &CheckpointTeam) -> std::fmt::Result {
+    for (member, (id, code)) in team.members.iter().enumerate() {
+        let mut name = if member == 0 { "individual" } else { "member" }.to_string();
+        if let Some(id) = id {
+            write!(name, " {}", id)?;
+        }
+        write_listing(f, &name, code)?;
+    }
+    if let Some(evaluation) = &team.evaluation {
+        writeln!(
+            f,
+            "evaluated {} {} {} {}",
+            evaluation.stage,
+            if evaluation.skipped { "skipped" } else { "ran" },
+            evaluation.numeric_events.divisions_by_zero,
+            evaluation.numeric_events.saturated_conversions
+        )?;
+        if let Some(run_result) = &evaluation.run_result {
+            writeln!(f, "run_result {}", run_result)?;
+        }
+    }
+    Ok(())
+}
+
+// Reads the lines of a code listing up to the `]` that ends it
+fn read_listing(lines: &mut std::str::Lines) -> Result<Vec<Code>> {
+    let mut listing = String::new();
+    for listing_line in lines.by_ref() {
+        listing.push_str(listing_line);
+        listing.push('\n');
+        if listing_line == "]" {
+            break;
+        }
+    }
+    parse_code_listing(&listing)
+}
+
+// Reads the stage, `skipped` or `ran`, and the numeric events of an evaluation
+fn read_evaluation(text: &str) -> Option<CheckpointEvaluation> {
+    let fields: Vec<&str> = text.split_whitespace().collect();
+    match fields[..] {
+        [stage, skipped @ ("skipped" | "ran"), divisions_by_zero, saturated_conversions] => {
+            Some(CheckpointEvaluation {
+                stage: stage.parse().ok()?,
+                skipped: skipped == "skipped",
+                numeric_events: NumericEvents {
+                    divisions_by_zero: divisions_by_zero.parse().ok()?,
+                    saturated_conversions: saturated_conversions.parse().ok()?,
+                },
+                run_result: None,
+            })
+        }
+        _ => None,
+    }
+}
+
+// Reads numbers separated by spaces, or returns None if any of them cannot be read
+fn parse_numbers<N: std::str::FromStr>(text: &str) -> Option<Vec<N>> {
+    text.split_whitespace().map(|number| number.parse().ok()).collect()
+}
+
+// The island that the line being read belongs to
+fn last_island<'a>(checkpoint: &'a mut Checkpoint, what: &str) -> Result<&'a mut CheckpointIsland> {
+    checkpoint
+        .islands
+        .last_mut()
+        .ok_or_else(|| checkpoint_error(format!("{} comes before the first island", what)))
+}
+
+// The team that the evaluation being read belongs to
+fn last_team(checkpoint: &mut Checkpoint, section: Section) -> Result<&mut CheckpointTeam> {
+    last_island(checkpoint, "an evaluation")?
+        .teams_mut(section)
+        .last_mut()
+        .ok_or_else(|| checkpoint_error("an evaluation comes before its individual"))
+}
+
+fn checkpoint_error<S: Into<String>>(message: S) -> anyhow::Error {
+    WasmgpError::InvalidCheckpoint(message.into()).into()
+}
+
+// Reads a `name value` line
+fn header_value<N: std::str::FromStr>(line: Option<&str>, name: &str) -> Result<N> {
+    line.and_then(|line| line.strip_prefix(name))
+        .and_then(|value| value.trim().parse().ok())
+        .ok_or_else(|| checkpoint_error(format!("expected a line with the {}", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn checkpoints_read_back_what_was_written() {
+        let team = |id, code| CheckpointTeam {
+            members: vec![(Some(id), code)],
+            evaluation: None,
+        };
+        let checkpoint = Checkpoint {
+            instruction_set: Some(InstructionSet::current()),
+            generations_complete: 212,
+            rng_state: u64::MAX,
+            next_individual_id: 9000,
+            generations_remaining_before_migration: Some(3),
+            fitness_cases: Some(FitnessCaseCountdown {
+                generations_until_new_cases: 1,
+                cases: vec![0, 4, 7],
+            }),
+            code_weight_levels: Some(vec![1.0, 0.1 + 0.2, 0.0]),
+            subtree_archive: Some(vec![DoFor::new(2, vec![Return::new()])]),
+            hall_of_fame: vec![(70, 41, 0), (50, 12, 1)],
+            islands: vec![
+                CheckpointIsland {
+                    difficulty_level: Some(2),
+                    generations: Some(12),
+                    target_size: Some(6),
+                    subtree_archive: Some(vec![]),
+                    callback_numbers: vec![u64::MAX, 3],
+                    callback_teams: vec![team(12, vec![ConstOne::new(0)])],
+                    current: vec![
+                        CheckpointTeam {
+                            members: vec![(Some(40), vec![Add::new(0, 1, 2), DoFor::new(2, vec![Return::new()])])],
+                            evaluation: Some(CheckpointEvaluation {
+                                stage: 1,
+                                skipped: false,
+                                numeric_events: NumericEvents {
+                                    divisions_by_zero: 2,
+                                    saturated_conversions: 0,
+                                },
+                                run_result: Some("won 3 of 4".to_string()),
+                            }),
+                        },
+                        CheckpointTeam {
+                            members: vec![(Some(41), vec![]), (Some(42), vec![ConstOne::new(0)])],
+                            evaluation: Some(CheckpointEvaluation {
+                                stage: 0,
+                                skipped: true,
+                                numeric_events: NumericEvents::default(),
+                                run_result: None,
+                            }),
+                        },
+                    ],
+                    future: vec![team(43, vec![ConstZero::new(1)])],
+                },
+                CheckpointIsland::default(),
+            ],
+        };
+        let mut text = String::new();
+        checkpoint.write_text(&mut text).unwrap();
+        assert_eq!(checkpoint, Checkpoint::from_text(&text).unwrap());
+
+        assert!(Checkpoint::from_text("generations_complete 1").is_err());
+        let unreadable = text.replace(&InstructionSet::current().to_string(), "unknown");
+        assert!(Checkpoint::from_text(&unreadable).is_err());
+        assert!(Checkpoint::from_text(&text.replace("island 1", "island 7")).is_err());
+        let misplaced = text.replace("island 0\ndifficulty_level 2", "difficulty_level 2\nisland 0");
+        assert!(Checkpoint::from_text(&misplaced).is_err());
+        assert!(Checkpoint::from_text(&text.replace("member 42", "member forty-two")).is_err());
+        assert!(Checkpoint::from_text(&text.replace("evaluated 1 ran", "evaluated 1 lost")).is_err());
+        assert!(Checkpoint::from_text(&text.replace("evaluated 1 ran 2 0\n", "")).is_err());
+        assert!(Checkpoint::from_text(&text.replace("hall_of_fame 70 41 0", "hall_of_fame 70 41")).is_err());
+
+        // Older checkpoints have no instruction set
+        let mut lines: Vec<&str> = text.lines().collect();
+        lines.remove(1);
+        let older = Checkpoint::from_text(&lines.join("\n")).unwrap();
+        assert_eq!(None, older.instruction_set);
+        assert_eq!(checkpoint.islands, older.islands);
+
+        // Before the rest of the world was saved, a checkpoint only had the code of each team, as the next generation
+        let oldest = Checkpoint {
+            instruction_set: None,
+            generations_remaining_before_migration: None,
+            fitness_cases: None,
+            code_weight_levels: None,
+            subtree_archive: None,
+            hall_of_fame: vec![],
+            islands: vec![CheckpointIsland {
+                future: vec![
+                    CheckpointTeam {
+                        members: vec![(None, vec![ConstOne::new(0)]), (None, vec![])],
+                        evaluation: None,
+                    },
+                    CheckpointTeam {
+                        members: vec![(None, vec![Add::new(0, 1, 2)])],
+                        evaluation: None,
+                    },
+                ],
+                ..CheckpointIsland::default()
+            }],
+            ..checkpoint
+        };
+        let mut text = String::new();
+        oldest.write_text(&mut text).unwrap();
+        let text = text.replace("future\n", "");
+        assert_eq!(oldest, Checkpoint::from_text(&text).unwrap());
+    }
+
+    #[test]
+    fn checkpoints_are_due_by_generation_or_time() {
+        let mut schedule = CheckpointSchedule::new("unused", 5, 1);
+        assert!(schedule.is_due(10, Duration::ZERO));
+        assert!(!schedule.is_due(11, Duration::from_secs(3600)));
+        schedule.every_minutes = 2;
+        assert!(schedule.is_due(11, Duration::from_secs(120)));
+        assert!(!schedule.is_due(11, Duration::from_secs(119)));
+    }
+}
@@ -3,10 +3,13 @@ use crate::indentation::Indentation;
 use crate::CodeContext;
 use crate::*;
 use anyhow::Result;
-use strum_macros::EnumIter;
+use strum_macros::{EnumIter, IntoStaticStr};
 use wasm_ast::Instruction;
 
-#[derive(Clone, Debug, EnumIter, PartialEq)]
+/// Every instruction that can appear in evolved code. The bit operations and the float operations are behind the
+/// `bit_ops` and `float_ops` features, which are on by default. Turning them off removes those instructions from the
+/// enum, from the weight table and from code listings, for builds that only need integer arithmetic.
+#[derive(Clone, Debug, EnumIter, IntoStaticStr, PartialEq)]
 pub enum Code {
     // Const
     ConstI32(ConstI32),
@@ -17,15 +20,29 @@ pub enum Code {
     ConstZero(ConstZero),
 
     // Bitwise
+    #[cfg(feature = "bit_ops")]
     CountLeadingZeros(CountLeadingZeros),
+    #[cfg(feature = "bit_ops")]
     CountTrailingZeros(CountTrailingZeros),
+    #[cfg(feature = "bit_ops")]
     PopulationCount(PopulationCount),
+    #[cfg(feature = "bit_ops")]
+    PowerOfTwo(PowerOfTwo),
+    #[cfg(feature = "bit_ops")]
+    IntegerLog2(IntegerLog2),
+    #[cfg(feature = "bit_ops")]
     And(And),
+    #[cfg(feature = "bit_ops")]
     Or(Or),
+    #[cfg(feature = "bit_ops")]
     Xor(Xor),
+    #[cfg(feature = "bit_ops")]
     ShiftLeft(ShiftLeft),
+    #[cfg(feature = "bit_ops")]
     ShiftRight(ShiftRight),
+    #[cfg(feature = "bit_ops")]
     RotateLeft(RotateLeft),
+    #[cfg(feature = "bit_ops")]
     RotateRight(RotateRight),
 
     // Arithmetic
@@ -34,16 +51,26 @@ pub enum Code {
     Multiply(Multiply),
     Divide(Divide),
     Remainder(Remainder),
+    Power(Power),
 
     // Float
+    #[cfg(feature = "float_ops")]
     AbsoluteValue(AbsoluteValue),
+    #[cfg(feature = "float_ops")]
     Negate(Negate),
+    #[cfg(feature = "float_ops")]
     SquareRoot(SquareRoot),
+    #[cfg(feature = "float_ops")]
     Ceiling(Ceiling),
+    #[cfg(feature = "float_ops")]
     Floor(Floor),
+    #[cfg(feature = "float_ops")]
     Nearest(Nearest),
+    #[cfg(feature = "float_ops")]
     Min(Min),
+    #[cfg(feature = "float_ops")]
     Max(Max),
+    #[cfg(feature = "float_ops")]
     CopySign(CopySign),
 
     // Comparison
@@ -55,6 +82,11 @@ pub enum Code {
     IsLessThanOrEqual(IsLessThanOrEqual),
     IsGreaterThanOrEqual(IsGreaterThanOrEqual),
 
+    // Slot ranges
+    FillSlots(FillSlots),
+    SumSlots(SumSlots),
+    CopySlots(CopySlots),
+
     /// LoadI8(offset_slot, result_slot): Loads the i8 value at the memory index indicated by the offset into the result
     /// slot. The memory index will be cast into an integer and the calculation `offset % mem_size` applied before
     /// attempting to read the memory. The i8 value will be cast into the result slot type.
@@ -90,9 +122,17 @@ pub enum Code {
     DoFor(DoFor),
     Break(Break),
     BreakIf(BreakIf),
+
+    // Provenance
+    Provenance(Provenance),
 }
 
 impl Code {
+    /// Returns the name of the Code variant, such as "DoUntil"
+    pub fn name(&self) -> &'static str {
+        self.into()
+    }
+
     /// Returns the number of places where this code item could be mutated.
     pub fn points(&self) -> usize {
         match self {
@@ -101,10 +141,157 @@ impl Code {
             Code::DoUntil(instructions) => instructions.points(),
             Code::DoWhile(instructions) => instructions.points(),
             Code::DoFor(instructions) => instructions.points(),
+            Code::Provenance(instructions) => instructions.points(),
             _ => 1,
         }
     }
 
+    /// Returns the number of blocks nested inside of each other in this code item, counting itself. Zero for code that
+    /// is not a block. Provenance is not counted because it does not add a block to the module.
+    pub fn nesting_depth(&self) -> usize {
+        let (is_block, children): (bool, &[&[Code]]) = match self {
+            Code::If(instructions) => (true, &[instructions.do_this()]),
+            Code::IfElse(instructions) => (true, &[instructions.do_this(), instructions.else_do_this()]),
+            Code::DoUntil(instructions) => (true, &[instructions.do_this()]),
+            Code::DoWhile(instructions) => (true, &[instructions.do_this()]),
+            Code::DoFor(instructions) => (true, &[instructions.do_this()]),
+            Code::Provenance(instructions) => (false, &[instructions.do_this()]),
+            _ => (false, &[]),
+        };
+        let deepest_child = children
+            .iter()
+            .flat_map(|list| list.iter())
+            .map(|child| child.nesting_depth())
+            .max()
+            .unwrap_or(0);
+        deepest_child + is_block as usize
+    }
+
+    /// Calls `visit` for this code item and then for every code item nested inside of it, in the order they appear
+    pub fn walk<'a>(&'a self, visit: &mut dyn FnMut(&'a Code)) {
+        visit(self);
+        let children: &[&'a [Code]] = match self {
+            Code::If(instructions) => &[instructions.do_this()],
+            Code::IfElse(instructions) => &[instructions.do_this(), instructions.else_do_this()],
+            Code::DoUntil(instructions) => &[instructions.do_this()],
+            Code::DoWhile(instructions) => &[instructions.do_this()],
+            Code::DoFor(instructions) => &[instructions.do_this()],
+            Code::Provenance(instructions) => &[instructions.do_this()],
+            _ => &[],
+        };
+        for child in children.iter().flat_map(|list| list.iter()) {
+            child.walk(visit);
+        }
+    }
+
+    /// Replaces every slot used by this code item, and by the code nested inside of it, with the slot returned by
+    /// `map`. Constants, loop counts, range counts and function indexes are unchanged.
+    pub fn remap_slots(&mut self, map: &dyn Fn(Slot) -> Slot) {
+        let remap_list = |list: &[Code]| -> Vec<Code> {
+            let mut list = list.to_vec();
+            for c in list.iter_mut() {
+                c.remap_slots(map);
+            }
+            list
+        };
+        *self = match &*self {
+            Code::ConstI32(c) => ConstI32::new(map(c.slot()), c.value()),
+            Code::ConstI64(c) => ConstI64::new(map(c.slot()), c.value()),
+            Code::ConstF32(c) => ConstF32::new(map(c.slot()), c.value()),
+            Code::ConstF64(c) => ConstF64::new(map(c.slot()), c.value()),
+            Code::ConstOne(c) => ConstOne::new(map(c.destination())),
+            Code::ConstZero(c) => ConstZero::new(map(c.destination())),
+            #[cfg(feature = "bit_ops")]
+            Code::CountLeadingZeros(c) => CountLeadingZeros::new(map(c.source()), map(c.destination())),
+            #[cfg(feature = "bit_ops")]
+            Code::CountTrailingZeros(c) => CountTrailingZeros::new(map(c.source()), map(c.destination())),
+            #[cfg(feature = "bit_ops")]
+            Code::PopulationCount(c) => PopulationCount::new(map(c.source()), map(c.destination())),
+            #[cfg(feature = "bit_ops")]
+            Code::PowerOfTwo(c) => PowerOfTwo::new(map(c.exponent()), map(c.destination())),
+            #[cfg(feature = "bit_ops")]
+            Code::IntegerLog2(c) => IntegerLog2::new(map(c.source()), map(c.destination())),
+            #[cfg(feature = "bit_ops")]
+            Code::And(c) => And::new(map(c.left()), map(c.right()), map(c.destination())),
+            #[cfg(feature = "bit_ops")]
+            Code::Or(c) => Or::new(map(c.left()), map(c.right()), map(c.destination())),
+            #[cfg(feature = "bit_ops")]
+            Code::Xor(c) => Xor::new(map(c.left()), map(c.right()), map(c.destination())),
+            #[cfg(feature = "bit_ops")]
+            Code::ShiftLeft(c) => ShiftLeft::new(map(c.source()), map(c.bits()), map(c.destination())),
+            #[cfg(feature = "bit_ops")]
+            Code::ShiftRight(c) => ShiftRight::new(map(c.source()), map(c.bits()), map(c.destination())),
+            #[cfg(feature = "bit_ops")]
+            Code::RotateLeft(c) => RotateLeft::new(map(c.source()), map(c.bits()), map(c.destination())),
+            #[cfg(feature = "bit_ops")]
+            Code::RotateRight(c) => RotateRight::new(map(c.source()), map(c.bits()), map(c.destination())),
+            Code::Add(c) => Add::new(map(c.left()), map(c.right()), map(c.destination())),
+            Code::Subtract(c) => Subtract::new(map(c.left()), map(c.right()), map(c.destination())),
+            Code::Multiply(c) => Multiply::new(map(c.left()), map(c.right()), map(c.destination())),
+            Code::Divide(c) => Divide::new(map(c.dividend()), map(c.divisor()), map(c.destination())),
+            Code::Remainder(c) => Remainder::new(map(c.dividend()), map(c.divisor()), map(c.destination())),
+            Code::Power(c) => Power::new(map(c.base()), map(c.exponent()), map(c.destination())),
+            #[cfg(feature = "float_ops")]
+            Code::AbsoluteValue(c) => AbsoluteValue::new(map(c.source()), map(c.destination())),
+            #[cfg(feature = "float_ops")]
+            Code::Negate(c) => Negate::new(map(c.source()), map(c.destination())),
+            #[cfg(feature = "float_ops")]
+            Code::SquareRoot(c) => SquareRoot::new(map(c.source()), map(c.destination())),
+            #[cfg(feature = "float_ops")]
+            Code::Ceiling(c) => Ceiling::new(map(c.source()), map(c.destination())),
+            #[cfg(feature = "float_ops")]
+            Code::Floor(c) => Floor::new(map(c.source()), map(c.destination())),
+            #[cfg(feature = "float_ops")]
+            Code::Nearest(c) => Nearest::new(map(c.source()), map(c.destination())),
+            #[cfg(feature = "float_ops")]
+            Code::Min(c) => Min::new(map(c.left()), map(c.right()), map(c.destination())),
+            #[cfg(feature = "float_ops")]
+            Code::Max(c) => Max::new(map(c.left()), map(c.right()), map(c.destination())),
+            #[cfg(feature = "float_ops")]
+            Code::CopySign(c) => CopySign::new(map(c.left()), map(c.right()), map(c.destination())),
+            Code::IsEqualZero(c) => IsEqualZero::new(map(c.source()), map(c.destination())),
+            Code::AreEqual(c) => AreEqual::new(map(c.left()), map(c.right()), map(c.destination())),
+            Code::AreNotEqual(c) => AreNotEqual::new(map(c.left()), map(c.right()), map(c.destination())),
+            Code::IsLessThan(c) => IsLessThan::new(map(c.left()), map(c.right()), map(c.destination())),
+            Code::IsGreaterThan(c) => IsGreaterThan::new(map(c.left()), map(c.right()), map(c.destination())),
+            Code::IsLessThanOrEqual(c) => IsLessThanOrEqual::new(map(c.left()), map(c.right()), map(c.destination())),
+            Code::IsGreaterThanOrEqual(c) => {
+                IsGreaterThanOrEqual::new(map(c.left()), map(c.right()), map(c.destination()))
+            }
+            Code::FillSlots(c) => FillSlots::new(map(c.start()), c.count(), c.value()),
+            Code::SumSlots(c) => SumSlots::new(map(c.start()), c.count(), map(c.destination())),
+            Code::CopySlots(c) => CopySlots::new(map(c.source_start()), map(c.destination_start()), c.count()),
+            Code::CopySlot(c) => CopySlot::new(map(c.source()), map(c.destination())),
+            Code::Return(_) | Code::Break(_) => return,
+            Code::Call(c) => Call::new(
+                c.function_index(),
+                c.params().iter().map(|slot| map(*slot)).collect(),
+                c.results().iter().map(|slot| map(*slot)).collect(),
+            ),
+            Code::If(c) => If::new(map(c.if_not_zero()), remap_list(c.do_this())),
+            Code::IfElse(c) => IfElse::new(
+                map(c.if_not_zero()),
+                remap_list(c.do_this()),
+                remap_list(c.else_do_this()),
+            ),
+            Code::DoUntil(c) => DoUntil::new(map(c.until_not_zero()), remap_list(c.do_this())),
+            Code::DoWhile(c) => DoWhile::new(map(c.while_not_zero()), remap_list(c.do_this())),
+            Code::DoFor(c) => DoFor::new(c.times(), remap_list(c.do_this())),
+            Code::Provenance(c) => Provenance::new(c.tag(), remap_list(c.do_this())),
+            Code::BreakIf(c) => BreakIf::new(map(c.break_if_not_zero())),
+        };
+    }
+
+    /// Wraps every slot into the range `0..slot_count`, so that code evolved with more slots can be used where there
+    /// are fewer. A slot past the end is replaced by `slot % slot_count`, which may change the type of the slot; values
+    /// are converted as they are for any other slot of the wrong type. Does nothing if `slot_count` is zero.
+    pub fn wrap_slots(&mut self, slot_count: usize) {
+        if slot_count == 0 {
+            return;
+        }
+        self.remap_slots(&|slot| (slot as usize % slot_count) as Slot);
+    }
+
     /// Returns the minimum number of points consumed when generating this Code randomly
     pub fn minimum_points(&self) -> usize {
         match self {
@@ -113,6 +300,7 @@ impl Code {
             Code::DoUntil(_) => 2,
             Code::DoWhile(_) => 2,
             Code::DoFor(_) => 2,
+            Code::Provenance(_) => 2,
             _ => 1,
         }
     }
@@ -126,29 +314,53 @@ impl Code {
             Code::ConstF64(_) => Code::ConstF64(ConstF64::default()),
             Code::ConstOne(_) => Code::ConstOne(ConstOne::default()),
             Code::ConstZero(_) => Code::ConstZero(ConstZero::default()),
+            #[cfg(feature = "bit_ops")]
             Code::CountLeadingZeros(_) => Code::CountLeadingZeros(CountLeadingZeros::default()),
+            #[cfg(feature = "bit_ops")]
             Code::CountTrailingZeros(_) => Code::CountTrailingZeros(CountTrailingZeros::default()),
+            #[cfg(feature = "bit_ops")]
             Code::PopulationCount(_) => Code::PopulationCount(PopulationCount::default()),
+            #[cfg(feature = "bit_ops")]
+            Code::PowerOfTwo(_) => Code::PowerOfTwo(PowerOfTwo::default()),
+            #[cfg(feature = "bit_ops")]
+            Code::IntegerLog2(_) => Code::IntegerLog2(IntegerLog2::default()),
+            #[cfg(feature = "bit_ops")]
             Code::And(_) => Code::And(And::default()),
+            #[cfg(feature = "bit_ops")]
             Code::Or(_) => Code::Or(Or::default()),
+            #[cfg(feature = "bit_ops")]
             Code::Xor(_) => Code::Xor(Xor::default()),
+            #[cfg(feature = "bit_ops")]
             Code::ShiftLeft(_) => Code::ShiftLeft(ShiftLeft::default()),
+            #[cfg(feature = "bit_ops")]
             Code::ShiftRight(_) => Code::ShiftRight(ShiftRight::default()),
+            #[cfg(feature = "bit_ops")]
             Code::RotateLeft(_) => Code::RotateLeft(RotateLeft::default()),
+            #[cfg(feature = "bit_ops")]
             Code::RotateRight(_) => Code::RotateRight(RotateRight::default()),
             Code::Add(_) => Code::Add(Add::default()),
             Code::Subtract(_) => Code::Subtract(Subtract::default()),
             Code::Multiply(_) => Code::Multiply(Multiply::default()),
             Code::Divide(_) => Code::Divide(Divide::default()),
             Code::Remainder(_) => Code::Remainder(Remainder::default()),
+            Code::Power(_) => Code::Power(Power::default()),
+            #[cfg(feature = "float_ops")]
             Code::AbsoluteValue(_) => Code::AbsoluteValue(AbsoluteValue::default()),
+            #[cfg(feature = "float_ops")]
             Code::Negate(_) => Code::Negate(Negate::default()),
+            #[cfg(feature = "float_ops")]
             Code::SquareRoot(_) => Code::SquareRoot(SquareRoot::default()),
+            #[cfg(feature = "float_ops")]
             Code::Ceiling(_) => Code::Ceiling(Ceiling::default()),
+            #[cfg(feature = "float_ops")]
             Code::Floor(_) => Code::Floor(Floor::default()),
+            #[cfg(feature = "float_ops")]
             Code::Nearest(_) => Code::Nearest(Nearest::default()),
+            #[cfg(feature = "float_ops")]
             Code::Min(_) => Code::Min(Min::default()),
+            #[cfg(feature = "float_ops")]
             Code::Max(_) => Code::Max(Max::default()),
+            #[cfg(feature = "float_ops")]
             Code::CopySign(_) => Code::CopySign(CopySign::default()),
             Code::IsEqualZero(_) => Code::IsEqualZero(IsEqualZero::default()),
             Code::AreEqual(_) => Code::AreEqual(AreEqual::default()),
@@ -158,6 +370,9 @@ impl Code {
             Code::IsLessThanOrEqual(_) => Code::IsLessThanOrEqual(IsLessThanOrEqual::default()),
             Code::IsGreaterThanOrEqual(_) => Code::IsGreaterThanOrEqual(IsGreaterThanOrEqual::default()),
             Code::Return(_) => Code::Return(Return::default()),
+            Code::FillSlots(_) => Code::FillSlots(FillSlots::default()),
+            Code::SumSlots(_) => Code::SumSlots(SumSlots::default()),
+            Code::CopySlots(_) => Code::CopySlots(CopySlots::default()),
             Code::CopySlot(_) => Code::CopySlot(CopySlot::default()),
             Code::Call(_) => Code::Call(Call::default()),
             Code::If(_) => Code::If(If::default()),
@@ -167,6 +382,7 @@ impl Code {
             Code::DoFor(_) => Code::DoFor(DoFor::default()),
             Code::Break(_) => Code::Break(Break::default()),
             Code::BreakIf(_) => Code::BreakIf(BreakIf::default()),
+            Code::Provenance(_) => Code::Provenance(Provenance::default()),
         }
     }
 }
@@ -180,29 +396,53 @@ impl CodeBuilder for Code {
             Code::ConstF64(instruction) => instruction.append_code(context, instruction_list)?,
             Code::ConstOne(instruction) => instruction.append_code(context, instruction_list)?,
             Code::ConstZero(instruction) => instruction.append_code(context, instruction_list)?,
+            #[cfg(feature = "bit_ops")]
             Code::CountLeadingZeros(instruction) => instruction.append_code(context, instruction_list)?,
+            #[cfg(feature = "bit_ops")]
             Code::CountTrailingZeros(instruction) => instruction.append_code(context, instruction_list)?,
+            #[cfg(feature = "bit_ops")]
             Code::PopulationCount(instruction) => instruction.append_code(context, instruction_list)?,
+            #[cfg(feature = "bit_ops")]
+            Code::PowerOfTwo(instruction) => instruction.append_code(context, instruction_list)?,
+            #[cfg(feature = "bit_ops")]
+            Code::IntegerLog2(instruction) => instruction.append_code(context, instruction_list)?,
+            #[cfg(feature = "bit_ops")]
             Code::And(instruction) => instruction.append_code(context, instruction_list)?,
+            #[cfg(feature = "bit_ops")]
             Code::Or(instruction) => instruction.append_code(context, instruction_list)?,
+            #[cfg(feature = "bit_ops")]
             Code::Xor(instruction) => instruction.append_code(context, instruction_list)?,
+            #[cfg(feature = "bit_ops")]
             Code::ShiftLeft(instruction) => instruction.append_code(context, instruction_list)?,
+            #[cfg(feature = "bit_ops")]
             Code::ShiftRight(instruction) => instruction.append_code(context, instruction_list)?,
+            #[cfg(feature = "bit_ops")]
             Code::RotateLeft(instruction) => instruction.append_code(context, instruction_list)?,
+            #[cfg(feature = "bit_ops")]
             Code::RotateRight(instruction) => instruction.append_code(context, instruction_list)?,
             Code::Add(instruction) => instruction.append_code(context, instruction_list)?,
             Code::Subtract(instruction) => instruction.append_code(context, instruction_list)?,
             Code::Multiply(instruction) => instruction.append_code(context, instruction_list)?,
             Code::Divide(instruction) => instruction.append_code(context, instruction_list)?,
             Code::Remainder(instruction) => instruction.append_code(context, instruction_list)?,
+            Code::Power(instruction) => instruction.append_code(context, instruction_list)?,
+            #[cfg(feature = "float_ops")]
             Code::AbsoluteValue(instruction) => instruction.append_code(context, instruction_list)?,
+            #[cfg(feature = "float_ops")]
             Code::Negate(instruction) => instruction.append_code(context, instruction_list)?,
+            #[cfg(feature = "float_ops")]
             Code::SquareRoot(instruction) => instruction.append_code(context, instruction_list)?,
+            #[cfg(feature = "float_ops")]
             Code::Ceiling(instruction) => instruction.append_code(context, instruction_list)?,
+            #[cfg(feature = "float_ops")]
             Code::Floor(instruction) => instruction.append_code(context, instruction_list)?,
+            #[cfg(feature = "float_ops")]
             Code::Nearest(instruction) => instruction.append_code(context, instruction_list)?,
+            #[cfg(feature = "float_ops")]
             Code::Min(instruction) => instruction.append_code(context, instruction_list)?,
+            #[cfg(feature = "float_ops")]
             Code::Max(instruction) => instruction.append_code(context, instruction_list)?,
+            #[cfg(feature = "float_ops")]
             Code::CopySign(instruction) => instruction.append_code(context, instruction_list)?,
             Code::IsEqualZero(instruction) => instruction.append_code(context, instruction_list)?,
             Code::AreEqual(instruction) => instruction.append_code(context, instruction_list)?,
@@ -212,6 +452,9 @@ impl CodeBuilder for Code {
             Code::IsLessThanOrEqual(instruction) => instruction.append_code(context, instruction_list)?,
             Code::IsGreaterThanOrEqual(instruction) => instruction.append_code(context, instruction_list)?,
             Code::Return(instruction) => instruction.append_code(context, instruction_list)?,
+            Code::FillSlots(instruction) => instruction.append_code(context, instruction_list)?,
+            Code::SumSlots(instruction) => instruction.append_code(context, instruction_list)?,
+            Code::CopySlots(instruction) => instruction.append_code(context, instruction_list)?,
             Code::CopySlot(instruction) => instruction.append_code(context, instruction_list)?,
             Code::Call(instruction) => instruction.append_code(context, instruction_list)?,
             Code::If(instruction) => instruction.append_code(context, instruction_list)?,
@@ -221,6 +464,7 @@ impl CodeBuilder for Code {
             Code::DoFor(instruction) => instruction.append_code(context, instruction_list)?,
             Code::Break(instruction) => instruction.append_code(context, instruction_list)?,
             Code::BreakIf(instruction) => instruction.append_code(context, instruction_list)?,
+            Code::Provenance(instruction) => instruction.append_code(context, instruction_list)?,
         }
 
         Ok(())
@@ -234,29 +478,53 @@ impl CodeBuilder for Code {
             Code::ConstF64(instruction) => instruction.make_random_code(engine, max_points),
             Code::ConstOne(instruction) => instruction.make_random_code(engine, max_points),
             Code::ConstZero(instruction) => instruction.make_random_code(engine, max_points),
+            #[cfg(feature = "bit_ops")]
             Code::CountLeadingZeros(instruction) => instruction.make_random_code(engine, max_points),
+            #[cfg(feature = "bit_ops")]
             Code::CountTrailingZeros(instruction) => instruction.make_random_code(engine, max_points),
+            #[cfg(feature = "bit_ops")]
             Code::PopulationCount(instruction) => instruction.make_random_code(engine, max_points),
+            #[cfg(feature = "bit_ops")]
+            Code::PowerOfTwo(instruction) => instruction.make_random_code(engine, max_points),
+            #[cfg(feature = "bit_ops")]
+            Code::IntegerLog2(instruction) => instruction.make_random_code(engine, max_points),
+            #[cfg(feature = "bit_ops")]
             Code::And(instruction) => instruction.make_random_code(engine, max_points),
+            #[cfg(feature = "bit_ops")]
             Code::Or(instruction) => instruction.make_random_code(engine, max_points),
+            #[cfg(feature = "bit_ops")]
             Code::Xor(instruction) => instruction.make_random_code(engine, max_points),
+            #[cfg(feature = "bit_ops")]
             Code::ShiftLeft(instruction) => instruction.make_random_code(engine, max_points),
+            #[cfg(feature = "bit_ops")]
             Code::ShiftRight(instruction) => instruction.make_random_code(engine, max_points),
+            #[cfg(feature = "bit_ops")]
             Code::RotateLeft(instruction) => instruction.make_random_code(engine, max_points),
+            #[cfg(feature = "bit_ops")]
             Code::RotateRight(instruction) => instruction.make_random_code(engine, max_points),
             Code::Add(instruction) => instruction.make_random_code(engine, max_points),
             Code::Subtract(instruction) => instruction.make_random_code(engine, max_points),
             Code::Multiply(instruction) => instruction.make_random_code(engine, max_points),
             Code::Divide(instruction) => instruction.make_random_code(engine, max_points),
             Code::Remainder(instruction) => instruction.make_random_code(engine, max_points),
+            Code::Power(instruction) => instruction.make_random_code(engine, max_points),
+            #[cfg(feature = "float_ops")]
             Code::AbsoluteValue(instruction) => instruction.make_random_code(engine, max_points),
+            #[cfg(feature = "float_ops")]
             Code::Negate(instruction) => instruction.make_random_code(engine, max_points),
+            #[cfg(feature = "float_ops")]
             Code::SquareRoot(instruction) => instruction.make_random_code(engine, max_points),
+            #[cfg(feature = "float_ops")]
             Code::Ceiling(instruction) => instruction.make_random_code(engine, max_points),
+            #[cfg(feature = "float_ops")]
             Code::Floor(instruction) => instruction.make_random_code(engine, max_points),
+            #[cfg(feature = "float_ops")]
             Code::Nearest(instruction) => instruction.make_random_code(engine, max_points),
+            #[cfg(feature = "float_ops")]
             Code::Min(instruction) => instruction.make_random_code(engine, max_points),
+            #[cfg(feature = "float_ops")]
             Code::Max(instruction) => instruction.make_random_code(engine, max_points),
+            #[cfg(feature = "float_ops")]
             Code::CopySign(instruction) => instruction.make_random_code(engine, max_points),
             Code::IsEqualZero(instruction) => instruction.make_random_code(engine, max_points),
             Code::AreEqual(instruction) => instruction.make_random_code(engine, max_points),
@@ -266,6 +534,9 @@ impl CodeBuilder for Code {
             Code::IsLessThanOrEqual(instruction) => instruction.make_random_code(engine, max_points),
             Code::IsGreaterThanOrEqual(instruction) => instruction.make_random_code(engine, max_points),
             Code::Return(instruction) => instruction.make_random_code(engine, max_points),
+            Code::FillSlots(instruction) => instruction.make_random_code(engine, max_points),
+            Code::SumSlots(instruction) => instruction.make_random_code(engine, max_points),
+            Code::CopySlots(instruction) => instruction.make_random_code(engine, max_points),
             Code::CopySlot(instruction) => instruction.make_random_code(engine, max_points),
             Code::Call(instruction) => instruction.make_random_code(engine, max_points),
             Code::If(instruction) => instruction.make_random_code(engine, max_points),
@@ -275,6 +546,7 @@ impl CodeBuilder for Code {
             Code::DoFor(instruction) => instruction.make_random_code(engine, max_points),
             Code::Break(instruction) => instruction.make_random_code(engine, max_points),
             Code::BreakIf(instruction) => instruction.make_random_code(engine, max_points),
+            Code::Provenance(instruction) => instruction.make_random_code(engine, max_points),
         }
     }
 
@@ -286,29 +558,53 @@ impl CodeBuilder for Code {
             Code::ConstF64(instruction) => instruction.print_for_rust(f, indentation),
             Code::ConstOne(instruction) => instruction.print_for_rust(f, indentation),
             Code::ConstZero(instruction) => instruction.print_for_rust(f, indentation),
+            #[cfg(feature = "bit_ops")]
             Code::CountLeadingZeros(instruction) => instruction.print_for_rust(f, indentation),
+            #[cfg(feature = "bit_ops")]
             Code::CountTrailingZeros(instruction) => instruction.print_for_rust(f, indentation),
+            #[cfg(feature = "bit_ops")]
             Code::PopulationCount(instruction) => instruction.print_for_rust(f, indentation),
+            #[cfg(feature = "bit_ops")]
+            Code::PowerOfTwo(instruction) => instruction.print_for_rust(f, indentation),
+            #[cfg(feature = "bit_ops")]
+            Code::IntegerLog2(instruction) => instruction.print_for_rust(f, indentation),
+            #[cfg(feature = "bit_ops")]
             Code::And(instruction) => instruction.print_for_rust(f, indentation),
+            #[cfg(feature = "bit_ops")]
             Code::Or(instruction) => instruction.print_for_rust(f, indentation),
+            #[cfg(feature = "bit_ops")]
             Code::Xor(instruction) => instruction.print_for_rust(f, indentation),
+            #[cfg(feature = "bit_ops")]
             Code::ShiftLeft(instruction) => instruction.print_for_rust(f, indentation),
+            #[cfg(feature = "bit_ops")]
             Code::ShiftRight(instruction) => instruction.print_for_rust(f, indentation),
+            #[cfg(feature = "bit_ops")]
             Code::RotateLeft(instruction) => instruction.print_for_rust(f, indentation),
+            #[cfg(feature = "bit_ops")]
             Code::RotateRight(instruction) => instruction.print_for_rust(f, indentation),
             Code::Add(instruction) => instruction.print_for_rust(f, indentation),
             Code::Subtract(instruction) => instruction.print_for_rust(f, indentation),
             Code::Multiply(instruction) => instruction.print_for_rust(f, indentation),
             Code::Divide(instruction) => instruction.print_for_rust(f, indentation),
             Code::Remainder(instruction) => instruction.print_for_rust(f, indentation),
+            Code::Power(instruction) => instruction.print_for_rust(f, indentation),
+            #[cfg(feature = "float_ops")]
             Code::AbsoluteValue(instruction) => instruction.print_for_rust(f, indentation),
+            #[cfg(feature = "float_ops")]
             Code::Negate(instruction) => instruction.print_for_rust(f, indentation),
+            #[cfg(feature = "float_ops")]
             Code::SquareRoot(instruction) => instruction.print_for_rust(f, indentation),
+            #[cfg(feature = "float_ops")]
             Code::Ceiling(instruction) => instruction.print_for_rust(f, indentation),
+            #[cfg(feature = "float_ops")]
             Code::Floor(instruction) => instruction.print_for_rust(f, indentation),
+            #[cfg(feature = "float_ops")]
             Code::Nearest(instruction) => instruction.print_for_rust(f, indentation),
+            #[cfg(feature = "float_ops")]
             Code::Min(instruction) => instruction.print_for_rust(f, indentation),
+            #[cfg(feature = "float_ops")]
             Code::Max(instruction) => instruction.print_for_rust(f, indentation),
+            #[cfg(feature = "float_ops")]
             Code::CopySign(instruction) => instruction.print_for_rust(f, indentation),
             Code::IsEqualZero(instruction) => instruction.print_for_rust(f, indentation),
             Code::AreEqual(instruction) => instruction.print_for_rust(f, indentation),
@@ -318,6 +614,9 @@ impl CodeBuilder for Code {
             Code::IsLessThanOrEqual(instruction) => instruction.print_for_rust(f, indentation),
             Code::IsGreaterThanOrEqual(instruction) => instruction.print_for_rust(f, indentation),
             Code::Return(instruction) => instruction.print_for_rust(f, indentation),
+            Code::FillSlots(instruction) => instruction.print_for_rust(f, indentation),
+            Code::SumSlots(instruction) => instruction.print_for_rust(f, indentation),
+            Code::CopySlots(instruction) => instruction.print_for_rust(f, indentation),
             Code::CopySlot(instruction) => instruction.print_for_rust(f, indentation),
             Code::Call(instruction) => instruction.print_for_rust(f, indentation),
             Code::If(instruction) => instruction.print_for_rust(f, indentation),
@@ -327,6 +626,7 @@ impl CodeBuilder for Code {
             Code::DoFor(instruction) => instruction.print_for_rust(f, indentation),
             Code::Break(instruction) => instruction.print_for_rust(f, indentation),
             Code::BreakIf(instruction) => instruction.print_for_rust(f, indentation),
+            Code::Provenance(instruction) => instruction.print_for_rust(f, indentation),
         }
     }
 }
@@ -361,6 +661,54 @@ mod tests {
         (store, instance)
     }
 
+    #[test]
+    fn remap_slots_reaches_nested_code() {
+        let mut code = DoUntil::new(
+            9,
+            vec![
+                Call::new(0, vec![7, 1], vec![8]),
+                IfElse::new(6, vec![ConstI32::new(5, 42)], vec![BreakIf::new(4), Return::new()]),
+                DoFor::new(3, vec![Add::new(2, 1, 0)]),
+            ],
+        );
+        code.wrap_slots(6);
+        assert_eq!(
+            DoUntil::new(
+                3,
+                vec![
+                    Call::new(0, vec![1, 1], vec![2]),
+                    IfElse::new(0, vec![ConstI32::new(5, 42)], vec![BreakIf::new(4), Return::new()]),
+                    DoFor::new(3, vec![Add::new(2, 1, 0)]),
+                ],
+            ),
+            code
+        );
+
+        code.remap_slots(&|slot| slot + 10);
+        let mut slots = vec![];
+        code.walk(&mut |c| {
+            if let Code::Add(c) = c {
+                slots.extend([c.left(), c.right(), c.destination()]);
+            }
+        });
+        assert_eq!(vec![12, 11, 10], slots);
+    }
+
+    #[test]
+    fn nesting_depth_counts_blocks_but_not_provenance() {
+        assert_eq!(0, Return::new().nesting_depth());
+        assert_eq!(1, DoWhile::new(0, vec![]).nesting_depth());
+        let code = IfElse::new(
+            0,
+            vec![ConstOne::new(1)],
+            vec![Provenance::new(
+                "elite",
+                vec![DoFor::new(2, vec![If::new(1, vec![Break::new()])])],
+            )],
+        );
+        assert_eq!(3, code.nesting_depth());
+    }
+
     #[test]
     fn return_init_to_zero() {
         // Context
@@ -413,6 +761,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(all(feature = "bit_ops", feature = "float_ops"))]
     fn print() {
         let to_print = vec![
             ConstI32::new(0, 1),
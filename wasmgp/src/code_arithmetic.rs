@@ -4,7 +4,9 @@ use crate::indentation::Indentation;
 use crate::*;
 use anyhow::Result;
 use std::fmt::Write;
-use wasm_ast::{BlockType, ControlInstruction, Expression, Instruction, NumericInstruction};
+use wasm_ast::{
+    BlockType, ControlInstruction, Expression, Instruction, NumericInstruction, SignExtension, VariableInstruction,
+};
 
 /// Adds the values in the `left` and `right` slots, placing the results in the `destination` slot. All operands are
 /// converted to the type of the result before the operation.
@@ -53,6 +55,18 @@ impl Add {
             destination,
         })
     }
+
+    pub fn left(&self) -> Slot {
+        self.left
+    }
+
+    pub fn right(&self) -> Slot {
+        self.right
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for Add {
@@ -125,6 +139,18 @@ impl Subtract {
             destination,
         })
     }
+
+    pub fn left(&self) -> Slot {
+        self.left
+    }
+
+    pub fn right(&self) -> Slot {
+        self.right
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for Subtract {
@@ -197,6 +223,18 @@ impl Multiply {
             destination,
         })
     }
+
+    pub fn left(&self) -> Slot {
+        self.left
+    }
+
+    pub fn right(&self) -> Slot {
+        self.right
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for Multiply {
@@ -289,6 +327,18 @@ impl Divide {
             destination,
         })
     }
+
+    pub fn dividend(&self) -> Slot {
+        self.dividend
+    }
+
+    pub fn divisor(&self) -> Slot {
+        self.divisor
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for Divide {
@@ -310,7 +360,7 @@ impl CodeBuilder for Divide {
             }
             _ => inner_instructions.push(NumericInstruction::EqualToZero(operate_as.into()).into()),
         }
-        inner_instructions.push(ControlInstruction::BranchIf(0).into());
+        skip_division_by_zero(context, &mut inner_instructions);
 
         // Now that the div/0 check is done, perform the real division
         GetSlotConvert::convert(self.dividend, operate_as, context, &mut inner_instructions)?;
@@ -389,6 +439,18 @@ impl Remainder {
             destination,
         })
     }
+
+    pub fn dividend(&self) -> Slot {
+        self.dividend
+    }
+
+    pub fn divisor(&self) -> Slot {
+        self.divisor
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for Remainder {
@@ -405,7 +467,7 @@ impl CodeBuilder for Remainder {
         let mut inner_instructions: Vec<Instruction> = vec![];
         GetSlotConvert::convert(self.divisor, operate_as, context, &mut inner_instructions)?;
         inner_instructions.push(NumericInstruction::EqualToZero(operate_as.into()).into());
-        inner_instructions.push(ControlInstruction::BranchIf(0).into());
+        skip_division_by_zero(context, &mut inner_instructions);
 
         // Now that the div/0 check is done, perform the real division
         GetSlotConvert::convert(self.dividend, operate_as, context, &mut inner_instructions)?;
@@ -432,6 +494,145 @@ impl CodeBuilder for Remainder {
     }
 }
 
+// Leaves the enclosing block when the zero check on top of the stack is true. With numeric telemetry, the division by
+// zero is counted on the way out.
+fn skip_division_by_zero(context: &CodeContext, instruction_list: &mut Vec<Instruction>) {
+    let mut count = vec![];
+    context.count_division_by_zero(&mut count);
+    if count.is_empty() {
+        instruction_list.push(ControlInstruction::BranchIf(0).into());
+    } else {
+        count.push(ControlInstruction::Branch(1).into());
+        instruction_list.push(ControlInstruction::If(BlockType::None, Expression::new(count), None).into());
+    }
+}
+
+/// Raises the `base` to the power of the `exponent` and places the result in the `destination` slot. It works on
+/// integers of the type of the destination (i64 for float destinations), with the exponent read as an unsigned integer,
+/// and wraps around on overflow in the same way as `Multiply`. The power is found by repeated squaring in a loop, so it
+/// takes at most 32 or 64 steps however large the exponent is.
+///
+/// ```
+/// use wasmgp::*;
+/// use wasmgp_macros::wasm_code;
+///
+/// #[wasm_code(signed)]
+/// fn power_i32(base: i32, exponent: i32) -> i32 {
+///     [Power::new(0, 1, 2), Return::new()]
+/// }
+/// let func = PowerI32::new().unwrap();
+/// assert_eq!(1, func.call(7, 0).unwrap());
+/// assert_eq!(81, func.call(3, 4).unwrap());
+/// assert_eq!(-8, func.call(-2, 3).unwrap());
+/// assert_eq!(0, func.call(2, 32).unwrap());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Power {
+    base: Slot,
+    exponent: Slot,
+    destination: Slot,
+}
+
+impl Power {
+    pub fn new(base: Slot, exponent: Slot, destination: Slot) -> Code {
+        Code::Power(Power {
+            base,
+            exponent,
+            destination,
+        })
+    }
+
+    pub fn base(&self) -> Slot {
+        self.base
+    }
+
+    pub fn exponent(&self) -> Slot {
+        self.exponent
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
+}
+
+impl CodeBuilder for Power {
+    fn append_code(&self, context: &CodeContext, instruction_list: &mut Vec<Instruction>) -> Result<()> {
+        let operate_as = match context.get_slot_value_type(self.destination)? {
+            ValueType::I32 => ValueType::I32,
+            _ => ValueType::I64,
+        };
+        let one = match operate_as {
+            ValueType::I32 => NumericInstruction::I32Constant(1),
+            _ => NumericInstruction::I64Constant(1),
+        };
+
+        // Copy the operands into locals, and start the result at one
+        let result = context.get_unused_local(operate_as);
+        let base = context.get_unused_local(operate_as);
+        let exponent = context.get_unused_local(operate_as);
+        instruction_list.push(one.clone().into());
+        instruction_list.push(VariableInstruction::LocalSet(*result).into());
+        GetSlotConvert::convert(self.base, operate_as, context, instruction_list)?;
+        instruction_list.push(VariableInstruction::LocalSet(*base).into());
+        GetSlotConvert::convert(self.exponent, operate_as, context, instruction_list)?;
+        instruction_list.push(VariableInstruction::LocalSet(*exponent).into());
+
+        // Leave the loop once the exponent has no bits left
+        let mut inner_instructions: Vec<Instruction> = vec![];
+        inner_instructions.push(VariableInstruction::LocalGet(*exponent).into());
+        inner_instructions.push(NumericInstruction::EqualToZero(operate_as.into()).into());
+        inner_instructions.push(ControlInstruction::BranchIf(1).into());
+
+        // When the lowest bit of the exponent is set, multiply the result by the base
+        let multiply = vec![
+            VariableInstruction::LocalGet(*result).into(),
+            VariableInstruction::LocalGet(*base).into(),
+            NumericInstruction::Multiply(operate_as.into()).into(),
+            VariableInstruction::LocalSet(*result).into(),
+        ];
+        inner_instructions.push(VariableInstruction::LocalGet(*exponent).into());
+        inner_instructions.push(one.clone().into());
+        inner_instructions.push(NumericInstruction::And(operate_as.into()).into());
+        inner_instructions.push(NumericInstruction::EqualToZero(operate_as.into()).into());
+        inner_instructions.push(NumericInstruction::EqualToZero(ValueType::I32.into()).into());
+        inner_instructions.push(ControlInstruction::If(BlockType::None, Expression::new(multiply), None).into());
+
+        // Square the base, and move on to the next bit of the exponent
+        inner_instructions.push(VariableInstruction::LocalGet(*base).into());
+        inner_instructions.push(VariableInstruction::LocalGet(*base).into());
+        inner_instructions.push(NumericInstruction::Multiply(operate_as.into()).into());
+        inner_instructions.push(VariableInstruction::LocalSet(*base).into());
+        inner_instructions.push(VariableInstruction::LocalGet(*exponent).into());
+        inner_instructions.push(one.into());
+        inner_instructions.push(NumericInstruction::ShiftRight(operate_as.into(), SignExtension::Unsigned).into());
+        inner_instructions.push(VariableInstruction::LocalSet(*exponent).into());
+        inner_instructions.push(ControlInstruction::Branch(0).into());
+
+        let loop_expression = Expression::new(vec![ControlInstruction::Loop(
+            BlockType::None,
+            Expression::new(inner_instructions),
+        )
+        .into()]);
+        instruction_list.push(ControlInstruction::Block(BlockType::None, loop_expression).into());
+
+        instruction_list.push(VariableInstruction::LocalGet(*result).into());
+        SetSlotConvert::convert(self.destination, operate_as, context, instruction_list)?;
+        Ok(())
+    }
+
+    fn make_random_code(&self, engine: &mut GeneticEngine, _max_points: usize) -> Code {
+        Power::new(engine.random_slot(), engine.random_slot(), engine.random_slot())
+    }
+
+    fn print_for_rust(&self, f: &mut std::string::String, indentation: &mut Indentation) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{}Power::new({}, {}, {}),",
+            indentation, self.base, self.exponent, self.destination
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::*;
@@ -43,6 +43,14 @@ impl CountLeadingZeros {
     pub fn new(source: Slot, destination: Slot) -> Code {
         Code::CountLeadingZeros(CountLeadingZeros { source, destination })
     }
+
+    pub fn source(&self) -> Slot {
+        self.source
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for CountLeadingZeros {
@@ -108,6 +116,14 @@ impl CountTrailingZeros {
     pub fn new(source: Slot, destination: Slot) -> Code {
         Code::CountTrailingZeros(CountTrailingZeros { source, destination })
     }
+
+    pub fn source(&self) -> Slot {
+        self.source
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for CountTrailingZeros {
@@ -172,6 +188,14 @@ impl PopulationCount {
     pub fn new(source: Slot, destination: Slot) -> Code {
         Code::PopulationCount(PopulationCount { source, destination })
     }
+
+    pub fn source(&self) -> Slot {
+        self.source
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for PopulationCount {
@@ -200,6 +224,142 @@ impl CodeBuilder for PopulationCount {
     }
 }
 
+/// PowerOfTwo places two raised to the power of the `exponent` slot into the destination slot. It works on integers of
+/// the type of the destination (i64 for float destinations), and like `ShiftLeft` only the low bits of the exponent
+/// are used, so the exponent is taken modulo 32 or 64.
+///
+/// ```
+/// use wasmgp::*;
+/// use wasmgp_macros::wasm_code;
+///
+/// #[wasm_code(unsigned)]
+/// fn power_of_two_i32(exponent: u32) -> u32 {
+///     [PowerOfTwo::new(0, 1), Return::new()]
+/// }
+/// let func = PowerOfTwoI32::new().unwrap();
+/// assert_eq!(1, func.call(0).unwrap());
+/// assert_eq!(1024, func.call(10).unwrap());
+/// assert_eq!(2, func.call(33).unwrap());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PowerOfTwo {
+    exponent: Slot,
+    destination: Slot,
+}
+
+impl PowerOfTwo {
+    pub fn new(exponent: Slot, destination: Slot) -> Code {
+        Code::PowerOfTwo(PowerOfTwo { exponent, destination })
+    }
+
+    pub fn exponent(&self) -> Slot {
+        self.exponent
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
+}
+
+impl CodeBuilder for PowerOfTwo {
+    fn append_code(&self, context: &CodeContext, instruction_list: &mut Vec<Instruction>) -> Result<()> {
+        let operate_as = match context.get_slot_value_type(self.destination)? {
+            ValueType::I32 => ValueType::I32,
+            _ => ValueType::I64,
+        };
+        instruction_list.push(match operate_as {
+            ValueType::I32 => NumericInstruction::I32Constant(1).into(),
+            _ => NumericInstruction::I64Constant(1).into(),
+        });
+        GetSlotConvert::convert(self.exponent, operate_as, context, instruction_list)?;
+        instruction_list.push(NumericInstruction::ShiftLeft(operate_as.into()).into());
+        SetSlotConvert::convert(self.destination, operate_as, context, instruction_list)?;
+        Ok(())
+    }
+
+    fn make_random_code(&self, engine: &mut GeneticEngine, _max_points: usize) -> Code {
+        PowerOfTwo::new(engine.random_slot(), engine.random_slot())
+    }
+
+    fn print_for_rust(&self, f: &mut std::string::String, indentation: &mut Indentation) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{}PowerOfTwo::new({}, {}),",
+            indentation, self.exponent, self.destination
+        )
+    }
+}
+
+/// IntegerLog2 places the base two logarithm of the source slot, rounded down, into the destination slot. It is found
+/// from the count of leading zeros, so the source is read as an unsigned integer of its own type (i64 for floats), and
+/// a source of zero gives -1.
+///
+/// ```
+/// use wasmgp::*;
+/// use wasmgp_macros::wasm_code;
+///
+/// #[wasm_code(signed)]
+/// fn integer_log2_i32(value: i32) -> i32 {
+///     [IntegerLog2::new(0, 1), Return::new()]
+/// }
+/// let func = IntegerLog2I32::new().unwrap();
+/// assert_eq!(0, func.call(1).unwrap());
+/// assert_eq!(3, func.call(15).unwrap());
+/// assert_eq!(4, func.call(16).unwrap());
+/// assert_eq!(31, func.call(-1).unwrap());
+/// assert_eq!(-1, func.call(0).unwrap());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IntegerLog2 {
+    source: Slot,
+    destination: Slot,
+}
+
+impl IntegerLog2 {
+    pub fn new(source: Slot, destination: Slot) -> Code {
+        Code::IntegerLog2(IntegerLog2 { source, destination })
+    }
+
+    pub fn source(&self) -> Slot {
+        self.source
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
+}
+
+impl CodeBuilder for IntegerLog2 {
+    fn append_code(&self, context: &CodeContext, instruction_list: &mut Vec<Instruction>) -> Result<()> {
+        // (bits - 1) - clz(source)
+        let operate_as = match context.get_slot_value_type(self.source)? {
+            ValueType::I32 => ValueType::I32,
+            _ => ValueType::I64,
+        };
+        instruction_list.push(match operate_as {
+            ValueType::I32 => NumericInstruction::I32Constant(31).into(),
+            _ => NumericInstruction::I64Constant(63).into(),
+        });
+        GetSlotConvert::convert(self.source, operate_as, context, instruction_list)?;
+        instruction_list.push(NumericInstruction::CountLeadingZeros(operate_as.into()).into());
+        instruction_list.push(NumericInstruction::Subtract(operate_as.into()).into());
+        SetSlotConvert::convert(self.destination, operate_as, context, instruction_list)?;
+        Ok(())
+    }
+
+    fn make_random_code(&self, engine: &mut GeneticEngine, _max_points: usize) -> Code {
+        IntegerLog2::new(engine.random_slot(), engine.random_slot())
+    }
+
+    fn print_for_rust(&self, f: &mut std::string::String, indentation: &mut Indentation) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{}IntegerLog2::new({}, {}),",
+            indentation, self.source, self.destination
+        )
+    }
+}
+
 /// And performs the bitwise AND of two source integers and places the result in the destination slot
 ///
 /// ```
@@ -230,6 +390,18 @@ impl And {
             destination,
         })
     }
+
+    pub fn left(&self) -> Slot {
+        self.left
+    }
+
+    pub fn right(&self) -> Slot {
+        self.right
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for And {
@@ -291,6 +463,18 @@ impl Or {
             destination,
         })
     }
+
+    pub fn left(&self) -> Slot {
+        self.left
+    }
+
+    pub fn right(&self) -> Slot {
+        self.right
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for Or {
@@ -352,6 +536,18 @@ impl Xor {
             destination,
         })
     }
+
+    pub fn left(&self) -> Slot {
+        self.left
+    }
+
+    pub fn right(&self) -> Slot {
+        self.right
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for Xor {
@@ -418,6 +614,18 @@ impl ShiftLeft {
             destination,
         })
     }
+
+    pub fn source(&self) -> Slot {
+        self.source
+    }
+
+    pub fn bits(&self) -> Slot {
+        self.bits
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for ShiftLeft {
@@ -503,6 +711,18 @@ impl ShiftRight {
             destination,
         })
     }
+
+    pub fn source(&self) -> Slot {
+        self.source
+    }
+
+    pub fn bits(&self) -> Slot {
+        self.bits
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for ShiftRight {
@@ -564,6 +784,18 @@ impl RotateLeft {
             destination,
         })
     }
+
+    pub fn source(&self) -> Slot {
+        self.source
+    }
+
+    pub fn bits(&self) -> Slot {
+        self.bits
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for RotateLeft {
@@ -625,6 +857,18 @@ impl RotateRight {
             destination,
         })
     }
+
+    pub fn source(&self) -> Slot {
+        self.source
+    }
+
+    pub fn bits(&self) -> Slot {
+        self.bits
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for RotateRight {
@@ -1,6 +1,6 @@
 use crate::indentation::Indentation;
-use crate::GeneticEngine;
 use crate::{code_context::CodeContext, Code};
+use crate::{CodeDiagnostic, GeneticEngine};
 use anyhow::Result;
 use wasm_ast::Instruction;
 
@@ -39,3 +39,19 @@ impl CodeBuilder for Vec<Code> {
         write!(f, "{}]", indentation)
     }
 }
+
+// Appends the code for a list nested inside another Code (such as the body of a loop). Any error is tagged with the name
+// of the list and the index of the failing Code so that the problem can be found in large programs.
+pub(crate) fn append_code_list(
+    list: &[Code],
+    list_name: &str,
+    context: &CodeContext,
+    instruction_list: &mut Vec<Instruction>,
+) -> Result<()> {
+    for (index, code) in list.iter().enumerate() {
+        code.append_code(context, instruction_list)
+            .map_err(|err| CodeDiagnostic::wrap(err, format!("{}[{}]", list_name, index)))?;
+    }
+
+    Ok(())
+}
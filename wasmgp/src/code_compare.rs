@@ -45,6 +45,14 @@ impl IsEqualZero {
     pub fn new(source: Slot, destination: Slot) -> Code {
         Code::IsEqualZero(IsEqualZero { source, destination })
     }
+
+    pub fn source(&self) -> Slot {
+        self.source
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for IsEqualZero {
@@ -125,6 +133,18 @@ impl AreEqual {
             destination,
         })
     }
+
+    pub fn left(&self) -> Slot {
+        self.left
+    }
+
+    pub fn right(&self) -> Slot {
+        self.right
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for AreEqual {
@@ -206,6 +226,18 @@ impl AreNotEqual {
             destination,
         })
     }
+
+    pub fn left(&self) -> Slot {
+        self.left
+    }
+
+    pub fn right(&self) -> Slot {
+        self.right
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for AreNotEqual {
@@ -287,6 +319,18 @@ impl IsLessThan {
             destination,
         })
     }
+
+    pub fn left(&self) -> Slot {
+        self.left
+    }
+
+    pub fn right(&self) -> Slot {
+        self.right
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for IsLessThan {
@@ -373,6 +417,18 @@ impl IsGreaterThan {
             destination,
         })
     }
+
+    pub fn left(&self) -> Slot {
+        self.left
+    }
+
+    pub fn right(&self) -> Slot {
+        self.right
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for IsGreaterThan {
@@ -459,6 +515,18 @@ impl IsLessThanOrEqual {
             destination,
         })
     }
+
+    pub fn left(&self) -> Slot {
+        self.left
+    }
+
+    pub fn right(&self) -> Slot {
+        self.right
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for IsLessThanOrEqual {
@@ -545,6 +613,18 @@ impl IsGreaterThanOrEqual {
             destination,
         })
     }
+
+    pub fn left(&self) -> Slot {
+        self.left
+    }
+
+    pub fn right(&self) -> Slot {
+        self.right
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for IsGreaterThanOrEqual {
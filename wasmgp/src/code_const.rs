@@ -18,6 +18,14 @@ impl ConstI32 {
     pub fn new(slot: Slot, value: i32) -> Code {
         Code::ConstI32(ConstI32 { slot, value })
     }
+
+    pub fn slot(&self) -> Slot {
+        self.slot
+    }
+
+    pub fn value(&self) -> i32 {
+        self.value
+    }
 }
 
 impl CodeBuilder for ConstI32 {
@@ -45,6 +53,14 @@ impl ConstI64 {
     pub fn new(slot: Slot, value: i64) -> Code {
         Code::ConstI64(ConstI64 { slot, value })
     }
+
+    pub fn slot(&self) -> Slot {
+        self.slot
+    }
+
+    pub fn value(&self) -> i64 {
+        self.value
+    }
 }
 
 impl CodeBuilder for ConstI64 {
@@ -72,6 +88,14 @@ impl ConstF32 {
     pub fn new(slot: Slot, value: f32) -> Code {
         Code::ConstF32(ConstF32 { slot, value })
     }
+
+    pub fn slot(&self) -> Slot {
+        self.slot
+    }
+
+    pub fn value(&self) -> f32 {
+        self.value
+    }
 }
 
 impl CodeBuilder for ConstF32 {
@@ -99,6 +123,14 @@ impl ConstF64 {
     pub fn new(slot: Slot, value: f64) -> Code {
         Code::ConstF64(ConstF64 { slot, value })
     }
+
+    pub fn slot(&self) -> Slot {
+        self.slot
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
 }
 
 impl CodeBuilder for ConstF64 {
@@ -138,6 +170,10 @@ impl ConstOne {
     pub fn new(destination: Slot) -> Code {
         Code::ConstOne(ConstOne { destination })
     }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for ConstOne {
@@ -178,6 +214,10 @@ impl ConstZero {
     pub fn new(destination: Slot) -> Code {
         Code::ConstZero(ConstZero { destination })
     }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for ConstZero {
@@ -1,21 +1,44 @@
 use crate::code_builder::CodeBuilder;
+use crate::slot_init::{RANDOM_F32_SCALE, RANDOM_F64_SCALE};
+use crate::world::{
+    CHECK_F32_CONVERSION_IMPORT, CHECK_F64_CONVERSION_IMPORT, DIVISION_BY_ZERO_IMPORT, RANDOM_SLOT_IMPORT,
+};
 use crate::{
-    Code, ConstF32, ConstF64, ConstI32, ConstI64, FunctionSignature, Return, Slot, SlotCount, SlotInit, ValueType,
-    WasmgpError,
+    Code, CodeDiagnostic, ConstF32, ConstF64, ConstI32, ConstI64, ConversionProfile, FunctionSignature, Return, Slot,
+    SlotCount, SlotInit, ValueType, WasmgpError, MODULE_NAME,
 };
 use anyhow::Result;
 use std::{cell::RefCell, ops::Deref};
-use wasm_ast::{Export, Function, FunctionType, LabelIndex, LocalIndex, ModuleBuilder, ResultType, SignExtension};
+use wasm_ast::{
+    ControlInstruction, Export, FloatType, Function, FunctionIndex, FunctionType, Import, Instruction, IntegerType,
+    LabelIndex, LocalIndex, ModuleBuilder, Name, NumberType, NumericInstruction, ResultType, SignExtension,
+    VariableInstruction,
+};
 
 pub struct CodeContext {
     signature: FunctionSignature,
     is_signed: bool,
     locals: RefCell<Vec<SlotInfo>>,
+    imports: Vec<FunctionSignature>,
 
     // A stack of the looping constructs that have been entered. If at least one loop is involved, then the 'Break'
     // instructions are valid and will produce code. The LabelIndex on the stack is how far we need to branch to exit
     // the loop.
     break_stack: RefCell<Vec<LabelIndex>>,
+
+    // When set, `build` imports the functions that count the numeric events of the code, and the protected operations
+    // call them
+    numeric_telemetry: bool,
+    numeric_imports: RefCell<Option<NumericImports>>,
+
+    conversion_profile: ConversionProfile,
+}
+
+#[derive(Clone, Copy)]
+struct NumericImports {
+    division_by_zero: FunctionIndex,
+    check_f32_conversion: FunctionIndex,
+    check_f64_conversion: FunctionIndex,
 }
 
 impl CodeContext {
@@ -69,20 +92,100 @@ impl CodeContext {
             signature: signature.clone(),
             is_signed,
             locals: RefCell::new(locals),
+            imports: vec![],
             break_stack: RefCell::new(vec![]),
+            numeric_telemetry: false,
+            numeric_imports: RefCell::new(None),
+            conversion_profile: ConversionProfile::Saturating,
         })
     }
 
+    /// Makes `build` count the divisions by zero and saturated conversions of the code, as described by
+    /// `WorldConfiguration::numeric_telemetry`
+    pub fn set_numeric_telemetry(&mut self, numeric_telemetry: bool) {
+        self.numeric_telemetry = numeric_telemetry;
+    }
+
+    /// Sets how values are converted between integers and floats, as described by
+    /// `WorldConfiguration::conversion_profile`
+    pub fn set_conversion_profile(&mut self, conversion_profile: ConversionProfile) {
+        self.conversion_profile = conversion_profile;
+    }
+
+    pub fn conversion_profile(&self) -> ConversionProfile {
+        self.conversion_profile
+    }
+
+    /// Declares the signature of the next function import, in the same order as `World::add_function_import`, and
+    /// returns the index that `Call` uses for it. A Call to a declared import may list more or fewer slots than the
+    /// function has parameters and results.
+    pub fn add_function_import(&mut self, signature: FunctionSignature) -> FunctionIndex {
+        self.imports.push(signature);
+        (self.imports.len() - 1) as FunctionIndex
+    }
+
+    /// Returns the signature of a declared function import, or None if it was not declared
+    pub fn function_import(&self, function_index: FunctionIndex) -> Option<&FunctionSignature> {
+        self.imports.get(function_index as usize)
+    }
+
     /// Adds a function to the specified builder. This adds three components to the WASM: a function type using the
     /// signature held by the context, the function body using the specified Code, and a function export using the name
-    /// from the signature.
-    pub fn build<R: rand::Rng>(&self, builder: &mut ModuleBuilder, code: &[Code], rng: &mut R) -> Result<()> {
+    /// from the signature. Returns the index of the new function.
+    pub fn build<R: rand::Rng>(
+        &self,
+        builder: &mut ModuleBuilder,
+        code: &[Code],
+        rng: &mut R,
+    ) -> Result<FunctionIndex> {
         // Add the function type
         let params = self.signature.params_ast();
         let results = self.signature.results_ast();
         let function_type = FunctionType::new(ResultType::from(params), ResultType::from(results));
         let function_type_index = builder.add_function_type(function_type)?;
 
+        // Slots that are randomized on every call get their values from the built-in random import
+        let random_import = if self
+            .locals_needing_init()
+            .iter()
+            .any(|i| i.init == Some(SlotInit::RandomPerCall))
+        {
+            let random_type = FunctionType::new(ResultType::empty(), ResultType::from(vec![wasm_ast::ValueType::I64]));
+            let type_index = builder.add_function_type(random_type)?;
+            let import = Import::function(
+                Name::new(String::from(MODULE_NAME)),
+                Name::new(String::from(RANDOM_SLOT_IMPORT)),
+                type_index,
+            );
+            Some(builder.add_import(import)?)
+        } else {
+            None
+        };
+
+        // The telemetry imports follow any random import, after all of the imports of the World
+        if self.numeric_telemetry {
+            let mut import_function = |name: &str,
+                                       params: Vec<wasm_ast::ValueType>,
+                                       results: Vec<wasm_ast::ValueType>|
+             -> Result<FunctionIndex> {
+                let type_index = builder
+                    .add_function_type(FunctionType::new(ResultType::from(params), ResultType::from(results)))?;
+                let import = Import::function(
+                    Name::new(String::from(MODULE_NAME)),
+                    Name::new(String::from(name)),
+                    type_index,
+                );
+                Ok(builder.add_import(import)?)
+            };
+            let (f32, f64) = (wasm_ast::ValueType::F32, wasm_ast::ValueType::F64);
+            let imports = NumericImports {
+                division_by_zero: import_function(DIVISION_BY_ZERO_IMPORT, vec![], vec![])?,
+                check_f32_conversion: import_function(CHECK_F32_CONVERSION_IMPORT, vec![f32, f64, f64], vec![f32])?,
+                check_f64_conversion: import_function(CHECK_F64_CONVERSION_IMPORT, vec![f64, f64, f64], vec![f64])?,
+            };
+            *self.numeric_imports.borrow_mut() = Some(imports);
+        }
+
         // Find all slots that require initialization and init them
         let mut instruction_list = vec![];
         for slot_info in self.locals_needing_init() {
@@ -118,13 +221,39 @@ impl CodeContext {
                         ConstF64::new(slot_info.index as u8, rng.gen()).append_code(&self, &mut instruction_list)?
                     }
                 },
+                Some(SlotInit::RandomPerCall) => {
+                    let random_import = random_import.expect("the random import is added for RandomPerCall");
+                    instruction_list.push(ControlInstruction::Call(random_import).into());
+                    match slot_info.value_type {
+                        ValueType::I32 => instruction_list.push(NumericInstruction::Wrap.into()),
+                        ValueType::I64 => {}
+                        ValueType::F32 => {
+                            instruction_list.push(
+                                NumericInstruction::Convert(FloatType::F32, IntegerType::I64, SignExtension::Unsigned)
+                                    .into(),
+                            );
+                            instruction_list.push(NumericInstruction::F32Constant(RANDOM_F32_SCALE).into());
+                            instruction_list.push(NumericInstruction::Multiply(NumberType::F32).into());
+                        }
+                        ValueType::F64 => {
+                            instruction_list.push(
+                                NumericInstruction::Convert(FloatType::F64, IntegerType::I64, SignExtension::Unsigned)
+                                    .into(),
+                            );
+                            instruction_list.push(NumericInstruction::F64Constant(RANDOM_F64_SCALE).into());
+                            instruction_list.push(NumericInstruction::Multiply(NumberType::F64).into());
+                        }
+                    }
+                    instruction_list.push(VariableInstruction::LocalSet(slot_info.index as u32).into());
+                }
                 None => panic!("should never have not-init in this list"),
             }
         }
 
         // Build the code. Some instructions may create more local variables for internal processing
-        for c in code.iter() {
-            c.append_code(&self, &mut instruction_list)?;
+        for (index, c) in code.iter().enumerate() {
+            c.append_code(&self, &mut instruction_list)
+                .map_err(|err| CodeDiagnostic::wrap(err, format!("{}[{}]", c.name(), index)))?;
         }
 
         // If the code does not already end in a Return, add one onto the end
@@ -147,7 +276,7 @@ impl CodeContext {
         let export = Export::function(self.signature.name().clone().into(), function_index);
         builder.add_export(export);
 
-        Ok(())
+        Ok(function_index)
     }
 
     pub fn is_signed(&self) -> bool {
@@ -162,6 +291,49 @@ impl CodeContext {
         }
     }
 
+    /// Adds a call that counts a division by zero, when the context has numeric telemetry
+    pub fn count_division_by_zero(&self, instruction_list: &mut Vec<Instruction>) {
+        if let Some(imports) = *self.numeric_imports.borrow() {
+            instruction_list.push(ControlInstruction::Call(imports.division_by_zero).into());
+        }
+    }
+
+    /// Adds a call that counts the float on top of the stack as saturated if it is NaN or out of the range of the
+    /// integer type it is about to be converted to, when the context has numeric telemetry. The float is left on the
+    /// stack. Only the `ConversionProfile::Saturating` conversions are checked, because the others never saturate.
+    pub fn check_conversion(&self, source: ValueType, destination: ValueType, instruction_list: &mut Vec<Instruction>) {
+        if self.conversion_profile != ConversionProfile::Saturating {
+            return;
+        }
+        let imports = match *self.numeric_imports.borrow() {
+            Some(imports) => imports,
+            None => return,
+        };
+        let check = match source {
+            ValueType::F32 => imports.check_f32_conversion,
+            ValueType::F64 => imports.check_f64_conversion,
+            _ => return,
+        };
+
+        // The range is from `min` up to but not including `max`
+        let (min, max) = match (destination, self.is_signed) {
+            (ValueType::I32, true) => (-2147483648.0, 2147483648.0),
+            (ValueType::I32, false) => (0.0, 4294967296.0),
+            (ValueType::I64, true) => (-9223372036854775808.0, 9223372036854775808.0),
+            (ValueType::I64, false) => (0.0, 18446744073709551616.0),
+            _ => return,
+        };
+        instruction_list.push(NumericInstruction::F64Constant(min).into());
+        instruction_list.push(NumericInstruction::F64Constant(max).into());
+        instruction_list.push(ControlInstruction::Call(check).into());
+    }
+
+    /// Returns the number of slots defined in the function: the parameters, returns and working slots
+    pub fn slot_count(&self) -> usize {
+        let locals = self.locals.borrow();
+        locals.iter().filter(|i| i.purpose != SlotPurpose::Instruction).count()
+    }
+
     /// Returns a list of all the local variable types suitable for passing to wasm_ast::Function::new. Specifically,
     /// this list does NOT include the parameters as part of the list
     pub fn local_types(&self) -> Vec<wasm_ast::ValueType> {
@@ -190,7 +362,8 @@ impl CodeContext {
                 Ok(slot_info.value_type)
             }
         } else {
-            Err(WasmgpError::InvalidSlot(slot).into())
+            let defined = locals.iter().filter(|i| i.purpose != SlotPurpose::Instruction).count();
+            Err(WasmgpError::SlotOutOfRange(slot, defined).into())
         }
     }
 
@@ -386,6 +559,37 @@ mod tests {
         assert!(context.get_slot_value_type(200).is_err());
     }
 
+    #[test]
+    fn build_errors_name_the_offending_code() {
+        use crate::*;
+        use rand::SeedableRng;
+
+        let fs = FunctionSignature::new("main", vec![], vec![]);
+        let slots = SlotCount {
+            i32: 12,
+            i64: 0,
+            f32: 0,
+            f64: 0,
+        };
+        let context = CodeContext::new(&fs, slots, false, SlotInit::Zero).unwrap();
+        let code = [
+            ConstOne::new(0),
+            ConstOne::new(1),
+            Add::new(0, 1, 2),
+            DoUntil::new(2, vec![ConstZero::new(3), Add::new(3, 17, 4)]),
+        ];
+        let mut builder = wasm_ast::ModuleBuilder::new();
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(1);
+        let err = context.build(&mut builder, &code[..], &mut rng).unwrap_err();
+
+        let diagnostic = err.downcast_ref::<CodeDiagnostic>().unwrap();
+        assert_eq!(["DoUntil[3]", "body[1]"], diagnostic.path[..]);
+        assert_eq!(
+            "DoUntil[3].body[1]: slot 17 out of range (12 slots defined)",
+            err.to_string()
+        );
+    }
+
     #[test]
     fn get_unused_local() {
         let fs = FunctionSignature::new("test", vec![], vec![]);
@@ -1,4 +1,4 @@
-use crate::code_builder::CodeBuilder;
+use crate::code_builder::{append_code_list, CodeBuilder};
 use crate::convert::{GetSlotConvert, SetSlotConvert};
 use crate::indentation::Indentation;
 use crate::*;
@@ -6,7 +6,8 @@ use anyhow::Result;
 use rand::Rng;
 use std::fmt::Write;
 use wasm_ast::{
-    BlockType, ControlInstruction, Expression, FunctionIndex, Instruction, NumericInstruction, VariableInstruction,
+    BlockType, ControlInstruction, Expression, FunctionIndex, Instruction, NumericInstruction, ParametricInstruction,
+    VariableInstruction,
 };
 
 /// Copies the value from one slot to another. The type will be converted if necessary
@@ -46,6 +47,14 @@ impl CopySlot {
     pub fn new(source: Slot, destination: Slot) -> Code {
         Code::CopySlot(CopySlot { source, destination })
     }
+
+    pub fn source(&self) -> Slot {
+        self.source
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for CopySlot {
@@ -99,10 +108,12 @@ impl CodeBuilder for Return {
 }
 
 /// Call(function_index, parameter_slots, return_slots): Calls the host or code function with the specified index
-/// (remainder the number of functions) and uses the specified list of work variables as parameters. If more work
-/// variables are specified than are needed, they will be ignored. If more work variables are needed than are
-/// supplied, the works 0..x will be used until all parameters are satisfied. The returns values from the function
-/// will be placed into the work variables specified by 'return_slots'.
+/// (remainder the number of functions) and uses the specified list of work variables as parameters. The returns values
+/// from the function will be placed into the work variables specified by 'return_slots'.
+///
+/// When the CodeContext knows the signature of the import (the World always tells it), the number of slots does not
+/// have to match the function. Extra parameter slots are ignored and missing parameters are passed as zero. Extra
+/// results are dropped, keeping the first ones, and extra return slots are set to zero.
 ///
 /// ```
 /// use wasmgp::*;
@@ -142,21 +153,64 @@ impl Call {
             results,
         })
     }
+
+    pub fn function_index(&self) -> FunctionIndex {
+        self.function_index
+    }
+
+    pub fn params(&self) -> &[Slot] {
+        &self.params[..]
+    }
+
+    pub fn results(&self) -> &[Slot] {
+        &self.results[..]
+    }
 }
 
 impl CodeBuilder for Call {
-    fn append_code(&self, _context: &CodeContext, instruction_list: &mut Vec<Instruction>) -> Result<()> {
-        // Load each parameter slot onto the stack
-        for &slot in self.params.iter() {
+    fn append_code(&self, context: &CodeContext, instruction_list: &mut Vec<Instruction>) -> Result<()> {
+        // Without the signature of the import, the slots are taken to match it
+        let import = context.function_import(self.function_index);
+        let param_types = match import {
+            Some(import) => import.params(),
+            None => &[],
+        };
+        let result_count = match import {
+            Some(import) => import.results().len(),
+            None => self.results.len(),
+        };
+
+        // Load each parameter slot onto the stack, leaving out extra slots and passing zero for missing ones
+        let param_count = if import.is_some() {
+            self.params.len().min(param_types.len())
+        } else {
+            self.params.len()
+        };
+        for &slot in self.params[..param_count].iter() {
             instruction_list.push(VariableInstruction::LocalGet(slot as u32).into());
         }
+        for value_type in param_types.iter().skip(param_count) {
+            instruction_list.push(zero_constant(*value_type));
+        }
 
         // Call the host function
         instruction_list.push(ControlInstruction::Call(self.function_index).into());
 
+        // Drop the results that have no slot, which are the last ones and so on the top of the stack
+        for _ in self.results.len()..result_count {
+            instruction_list.push(ParametricInstruction::Drop.into());
+        }
+
         // Put the results in the slot where they go (the top of the stack is the last result returned, so we need to
         // process our slots in reverse)
-        for &slot in self.results.iter().rev() {
+        let kept = self.results.len().min(result_count);
+        for &slot in self.results[..kept].iter().rev() {
+            instruction_list.push(VariableInstruction::LocalSet(slot as u32).into());
+        }
+
+        // Return slots without a result are set to zero
+        for &slot in self.results[kept..].iter() {
+            instruction_list.push(zero_constant(context.get_slot_value_type(slot)?));
             instruction_list.push(VariableInstruction::LocalSet(slot as u32).into());
         }
 
@@ -199,6 +253,16 @@ impl CodeBuilder for Call {
     }
 }
 
+// Pushes the zero of a type onto the stack
+fn zero_constant(value_type: ValueType) -> Instruction {
+    match value_type {
+        ValueType::I32 => NumericInstruction::I32Constant(0).into(),
+        ValueType::I64 => NumericInstruction::I64Constant(0).into(),
+        ValueType::F32 => NumericInstruction::F32Constant(0.0).into(),
+        ValueType::F64 => NumericInstruction::F64Constant(0.0).into(),
+    }
+}
+
 /// If the value in the compare_slot is not zero, than the code listed in 'do' will execute.
 ///
 /// ```
@@ -249,7 +313,7 @@ impl If {
 impl CodeBuilder for If {
     fn append_code(&self, context: &CodeContext, instruction_list: &mut Vec<Instruction>) -> Result<()> {
         let mut inner_instructions: Vec<Instruction> = vec![];
-        self.do_this.append_code(context, &mut inner_instructions)?;
+        append_code_list(&self.do_this, "body", context, &mut inner_instructions)?;
 
         GetSlotConvert::convert(self.if_not_zero, ValueType::I32, context, instruction_list)?;
         instruction_list
@@ -332,9 +396,9 @@ impl IfElse {
 impl CodeBuilder for IfElse {
     fn append_code(&self, context: &CodeContext, instruction_list: &mut Vec<Instruction>) -> Result<()> {
         let mut if_instructions: Vec<Instruction> = vec![];
-        self.do_this.append_code(context, &mut if_instructions)?;
+        append_code_list(&self.do_this, "body", context, &mut if_instructions)?;
         let mut else_instructions: Vec<Instruction> = vec![];
-        self.else_do_this.append_code(context, &mut else_instructions)?;
+        append_code_list(&self.else_do_this, "else_body", context, &mut else_instructions)?;
 
         GetSlotConvert::convert(self.if_not_zero, ValueType::I32, context, instruction_list)?;
         instruction_list.push(
@@ -435,7 +499,7 @@ impl CodeBuilder for DoUntil {
         // 'Do' the code. When the `loop_label` is dropped, it indicates we can't break from that loop anymore
         {
             let loop_label = context.entering_loop(1);
-            self.do_this.append_code(context, &mut inner_instructions)?;
+            append_code_list(&self.do_this, "body", context, &mut inner_instructions)?;
             drop(loop_label);
         }
 
@@ -554,7 +618,7 @@ impl CodeBuilder for DoWhile {
         // 'Do' the code. When the `loop_label` is dropped, it indicates we can't break from that loop anymore
         {
             let loop_label = context.entering_loop(1);
-            self.do_this.append_code(context, &mut inner_instructions)?;
+            append_code_list(&self.do_this, "body", context, &mut inner_instructions)?;
             drop(loop_label);
         }
 
@@ -658,7 +722,7 @@ impl CodeBuilder for DoFor {
         // 'Do' the code. When the `loop_label` is dropped, it indicates we can't break from that loop anymore
         {
             let loop_label = context.entering_loop(1);
-            self.do_this.append_code(context, &mut inner_instructions)?;
+            append_code_list(&self.do_this, "body", context, &mut inner_instructions)?;
             drop(loop_label);
         }
 
@@ -830,6 +894,10 @@ impl BreakIf {
     pub fn new(break_if_not_zero: Slot) -> Code {
         Code::BreakIf(BreakIf { break_if_not_zero })
     }
+
+    pub fn break_if_not_zero(&self) -> Slot {
+        self.break_if_not_zero
+    }
 }
 
 impl CodeBuilder for BreakIf {
@@ -880,6 +948,73 @@ mod tests {
         assert_eq!((3, 1, -2), func.call(1, 3).unwrap());
     }
 
+    #[test]
+    fn call_slots_may_not_match_the_import() {
+        let config = WorldConfiguration {
+            main_entry_point: FunctionSignature::new(
+                "main",
+                vec![ValueType::I32, ValueType::I32],
+                vec![ValueType::I32, ValueType::I32, ValueType::I32],
+            ),
+            ..WorldConfiguration::default()
+        };
+        let mut world: World<(), EmptyRunResult> = World::new(config).unwrap();
+        let three = world
+            .add_function_import("three", |v1: i32, v2: i32| (v2, v1, v1 - v2))
+            .unwrap();
+        let combine = world
+            .add_function_import("combine", |v1: i32, v2: i32| v1 * 10 + v2)
+            .unwrap();
+        let mut evaluator = world.reference_evaluator().unwrap();
+        evaluator
+            .set_host_function(three, |params| match (params[0], params[1]) {
+                (SlotValue::I32(v1), SlotValue::I32(v2)) => Ok(vec![v2.into(), v1.into(), (v1 - v2).into()]),
+                _ => unreachable!(),
+            })
+            .unwrap();
+        evaluator
+            .set_host_function(combine, |params| match (params[0], params[1]) {
+                (SlotValue::I32(v1), SlotValue::I32(v2)) => Ok(vec![(v1 * 10 + v2).into()]),
+                _ => unreachable!(),
+            })
+            .unwrap();
+
+        // The return slots start at seven so that a slot left alone can be told from one set to zero
+        let cases = [
+            // Extra results are dropped, keeping the first
+            (Call::new(three, vec![0, 1], vec![2]), (3, 7, 7)),
+            // Return slots without a result are set to zero
+            (Call::new(combine, vec![0, 1], vec![2, 3]), (13, 0, 7)),
+            // Missing parameters are zero
+            (Call::new(combine, vec![1], vec![2]), (30, 7, 7)),
+            (Call::new(three, vec![], vec![2, 3, 4]), (0, 0, 0)),
+            // Extra parameter slots are ignored
+            (Call::new(combine, vec![0, 1, 0], vec![2]), (13, 7, 7)),
+            (Call::new(three, vec![0, 1], vec![2, 3, 4]), (3, 1, -2)),
+        ];
+        let mut rng = rand::thread_rng();
+        for (call, expected) in cases {
+            let code = vec![
+                ConstI32::new(2, 7),
+                ConstI32::new(3, 7),
+                ConstI32::new(4, 7),
+                call,
+                Return::new(),
+            ];
+            let mut store = world.store(());
+            store.set_epoch_deadline(1_000);
+            let instance = world.instanciate(&mut store, &code[..]).unwrap();
+            let func = instance
+                .get_typed_func::<(i32, i32), (i32, i32, i32)>(&mut store, "main")
+                .unwrap();
+            assert_eq!(expected, func.call(&mut store, (1, 3)).unwrap(), "{:?}", code);
+
+            let reference = evaluator.evaluate(&code, &[1.into(), 3.into()], &mut rng).unwrap();
+            let expected: Vec<SlotValue> = vec![expected.0.into(), expected.1.into(), expected.2.into()];
+            assert_eq!(expected, reference, "{:?}", code);
+        }
+    }
+
     #[test]
     fn test_random_call() {
         let mut ge = GeneticEngine::new(GeneticEngineConfiguration::new(Some(1), 5));
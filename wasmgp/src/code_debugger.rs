@@ -0,0 +1,229 @@
+use crate::{Code, ReferenceEvaluator, SlotValue};
+use anyhow::Result;
+use std::fmt::Display;
+use wasm_ast::FunctionIndex;
+
+/// Steps through a program one Code at a time, showing the slot values, the loops that are running and the arguments
+/// of any host call at each step. This is useful for understanding why an evolved program behaves the way it does.
+///
+/// The program is run by a `ReferenceEvaluator` when the debugger is created, so host functions are called before the
+/// first step is shown. The steps can then be walked forwards and backwards as often as needed.
+///
+/// ```
+/// use wasmgp::*;
+///
+/// let signature = FunctionSignature::new("main", vec![ValueType::I32], vec![ValueType::I32]);
+/// let slots = SlotCount { i32: 1, i64: 0, f32: 0, f64: 0 };
+/// let mut evaluator = ReferenceEvaluator::new(&signature, slots, true, SlotInit::Zero).unwrap();
+/// let code = vec![
+///     ConstOne::new(2),
+///     DoFor::new(2, vec![Add::new(0, 2, 1), Add::new(1, 2, 2)]),
+///     Return::new(),
+/// ];
+/// let mut rng = rand::thread_rng();
+/// let mut debugger = CodeDebugger::new(&mut evaluator, &code, &[SlotValue::I32(10)], &mut rng).unwrap();
+///
+/// // Stop on the first Add during the second time through the loop
+/// let step = debugger
+///     .run_until(|step| step.path == ["DoFor[1]", "body[0]"] && step.loops[0].iteration == 1)
+///     .unwrap();
+/// assert_eq!(vec![SlotValue::I32(10), SlotValue::I32(11), SlotValue::I32(12)], step.slots);
+/// assert_eq!(Some(1), step.loops[0].remaining);
+///
+/// assert_eq!(&[SlotValue::I32(22)], debugger.result().unwrap());
+/// ```
+pub struct CodeDebugger {
+    steps: Vec<DebugStep>,
+    position: Option<usize>,
+    outcome: Result<Vec<SlotValue>>,
+}
+
+impl CodeDebugger {
+    /// Runs the code and records every step. Returns an error if the code could not be run at all; an error part way
+    /// through the run is available from `result` once the steps leading up to it have been recorded.
+    pub fn new<R: rand::Rng>(
+        evaluator: &mut ReferenceEvaluator,
+        code: &[Code],
+        params: &[SlotValue],
+        rng: &mut R,
+    ) -> Result<CodeDebugger> {
+        let (outcome, steps) = evaluator.evaluate_traced(code, params, rng)?;
+        Ok(CodeDebugger {
+            steps,
+            position: None,
+            outcome,
+        })
+    }
+
+    /// Moves to the next step and returns it, or returns None once the program has finished
+    pub fn step(&mut self) -> Option<&DebugStep> {
+        let next = self.position.map_or(0, |p| p + 1);
+        if next < self.steps.len() {
+            self.position = Some(next);
+            self.steps.get(next)
+        } else {
+            None
+        }
+    }
+
+    /// Moves to the previous step and returns it, or returns None if already at the first step
+    pub fn step_back(&mut self) -> Option<&DebugStep> {
+        match self.position {
+            Some(p) if p > 0 => {
+                self.position = Some(p - 1);
+                self.steps.get(p - 1)
+            }
+            _ => None,
+        }
+    }
+
+    /// Steps forward until the `breakpoint` returns true for a step, and returns that step. Returns None if the program
+    /// finishes first.
+    pub fn run_until<F>(&mut self, mut breakpoint: F) -> Option<&DebugStep>
+    where
+        F: FnMut(&DebugStep) -> bool,
+    {
+        let start = self.position.map_or(0, |p| p + 1);
+        let found = self.steps[start.min(self.steps.len())..]
+            .iter()
+            .position(&mut breakpoint)
+            .map(|offset| start + offset);
+        self.position = found.or(self.position);
+        found.and_then(|p| self.steps.get(p))
+    }
+
+    /// Returns to the state before the first step
+    pub fn restart(&mut self) {
+        self.position = None;
+    }
+
+    /// The step that was moved to most recently
+    pub fn current(&self) -> Option<&DebugStep> {
+        self.position.and_then(|p| self.steps.get(p))
+    }
+
+    pub fn steps(&self) -> &[DebugStep] {
+        &self.steps[..]
+    }
+
+    /// The values of the result slots, or the error that stopped the program
+    pub fn result(&self) -> Result<&[SlotValue], &anyhow::Error> {
+        self.outcome.as_ref().map(|results| &results[..])
+    }
+}
+
+/// The state of the program just before one Code runs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DebugStep {
+    /// Counts each Code and each time through a loop, the same way as `ReferenceEvaluator::max_steps`
+    pub step: u64,
+
+    /// Where the Code is in the program, in the same form as `CodeDiagnostic`, such as `["DoUntil[3]", "body[1]"]`
+    pub path: Vec<String>,
+
+    pub code: Code,
+
+    /// The value of every slot, starting with the parameters, then the results and then the work slots
+    pub slots: Vec<SlotValue>,
+
+    /// The loops that contain the Code, outermost first. A `Break` here exits the last one.
+    pub loops: Vec<LoopFrame>,
+
+    /// When the Code is a `Call`, the values that went to and came back from the host
+    pub host_call: Option<HostCall>,
+}
+
+impl Display for DebugStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:>6} {} {:?}", self.step, self.path.join("."), self.slots)
+    }
+}
+
+/// One of the loops that contain the Code being run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LoopFrame {
+    /// The name of the loop Code, such as "DoFor"
+    pub code_name: &'static str,
+
+    /// Starts at 0 and counts each time the loop has gone back to its top
+    pub iteration: u64,
+
+    /// For `DoFor`, the count of runs left including this one
+    pub remaining: Option<i32>,
+}
+
+impl LoopFrame {
+    pub(crate) fn new(code: &Code, iteration: u64, remaining: Option<i32>) -> LoopFrame {
+        LoopFrame {
+            code_name: code.name(),
+            iteration,
+            remaining,
+        }
+    }
+}
+
+/// The arguments and results of a call to a host function.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HostCall {
+    pub function_index: FunctionIndex,
+    pub params: Vec<SlotValue>,
+    pub results: Vec<SlotValue>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn steps_show_host_calls_and_runtime_errors() {
+        let signature = FunctionSignature::new("main", vec![ValueType::I32], vec![ValueType::I32]);
+        let slots = SlotCount {
+            i32: 1,
+            i64: 0,
+            f32: 0,
+            f64: 0,
+        };
+        let mut evaluator = ReferenceEvaluator::new(&signature, slots, true, SlotInit::Zero).unwrap();
+        let negate = evaluator.add_function_import(FunctionSignature::new(
+            "negate",
+            vec![ValueType::I32],
+            vec![ValueType::I32],
+        ));
+        evaluator
+            .set_host_function(negate, |params| match params[0] {
+                SlotValue::I32(v) => Ok(vec![SlotValue::I32(v.wrapping_neg())]),
+                _ => unreachable!(),
+            })
+            .unwrap();
+
+        // i32::MIN / -1 traps on the last Code
+        let code = vec![
+            Call::new(negate, vec![0], vec![1]),
+            ConstI32::new(2, -1),
+            Divide::new(0, 2, 1),
+            Return::new(),
+        ];
+        let mut rng = SmallRng::seed_from_u64(1);
+        let mut debugger = CodeDebugger::new(&mut evaluator, &code, &[SlotValue::I32(i32::MIN)], &mut rng).unwrap();
+        assert!(debugger.result().is_err());
+        assert_eq!(3, debugger.steps().len());
+
+        let step = debugger.step().unwrap();
+        assert_eq!(vec!["Call[0]"], step.path);
+        let host_call = step.host_call.as_ref().unwrap();
+        assert_eq!(vec![SlotValue::I32(i32::MIN)], host_call.params);
+        assert_eq!(vec![SlotValue::I32(i32::MIN)], host_call.results);
+
+        let step = debugger.run_until(|step| step.code.name() == "Divide").unwrap();
+        assert_eq!(vec!["Divide[2]"], step.path);
+        assert_eq!(SlotValue::I32(-1), step.slots[2]);
+        assert!(debugger.step().is_none());
+
+        assert_eq!(vec!["ConstI32[1]"], debugger.step_back().unwrap().path);
+        debugger.restart();
+        assert!(debugger.current().is_none());
+        assert!(debugger.step_back().is_none());
+    }
+}
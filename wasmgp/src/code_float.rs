@@ -43,6 +43,14 @@ impl AbsoluteValue {
     pub fn new(source: Slot, destination: Slot) -> Code {
         Code::AbsoluteValue(AbsoluteValue { source, destination })
     }
+
+    pub fn source(&self) -> Slot {
+        self.source
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for AbsoluteValue {
@@ -107,6 +115,14 @@ impl Negate {
     pub fn new(source: Slot, destination: Slot) -> Code {
         Code::Negate(Negate { source, destination })
     }
+
+    pub fn source(&self) -> Slot {
+        self.source
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for Negate {
@@ -170,6 +186,14 @@ impl SquareRoot {
     pub fn new(source: Slot, destination: Slot) -> Code {
         Code::SquareRoot(SquareRoot { source, destination })
     }
+
+    pub fn source(&self) -> Slot {
+        self.source
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for SquareRoot {
@@ -224,6 +248,14 @@ impl Ceiling {
     pub fn new(source: Slot, destination: Slot) -> Code {
         Code::Ceiling(Ceiling { source, destination })
     }
+
+    pub fn source(&self) -> Slot {
+        self.source
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for Ceiling {
@@ -273,6 +305,14 @@ impl Floor {
     pub fn new(source: Slot, destination: Slot) -> Code {
         Code::Floor(Floor { source, destination })
     }
+
+    pub fn source(&self) -> Slot {
+        self.source
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for Floor {
@@ -325,6 +365,14 @@ impl Nearest {
     pub fn new(source: Slot, destination: Slot) -> Code {
         Code::Nearest(Nearest { source, destination })
     }
+
+    pub fn source(&self) -> Slot {
+        self.source
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for Nearest {
@@ -391,6 +439,18 @@ impl Min {
             destination,
         })
     }
+
+    pub fn left(&self) -> Slot {
+        self.left
+    }
+
+    pub fn right(&self) -> Slot {
+        self.right
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for Min {
@@ -465,6 +525,18 @@ impl Max {
             destination,
         })
     }
+
+    pub fn left(&self) -> Slot {
+        self.left
+    }
+
+    pub fn right(&self) -> Slot {
+        self.right
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for Max {
@@ -540,6 +612,18 @@ impl CopySign {
             destination,
         })
     }
+
+    pub fn left(&self) -> Slot {
+        self.left
+    }
+
+    pub fn right(&self) -> Slot {
+        self.right
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
 }
 
 impl CodeBuilder for CopySign {
@@ -0,0 +1,448 @@
+use crate::*;
+use anyhow::Result;
+use std::str::FromStr;
+
+/// Reads code back from the listing printed by `print_for_rust`, such as the listing of `Individual::get_code_string`
+/// or of a `ChampionListing`. The listing is a list of Code constructors, like `[Add::new(0, 1, 2), Return::new()]`,
+/// and may be laid out with any whitespace.
+///
+/// ```
+/// use wasmgp::*;
+///
+/// let code = parse_code_listing("[
+///     ConstF32::new(3, -0.5f32),
+///     DoFor::new(2, vec![
+///         Add::new(0, 3, 1),
+///     ]),
+///     Provenance::new(\"seeded\", vec![Call::new(0, vec![1], vec![])]),
+/// ]").unwrap();
+/// assert_eq!(
+///     vec![
+///         ConstF32::new(3, -0.5),
+///         DoFor::new(2, vec![Add::new(0, 3, 1)]),
+///         Provenance::new("seeded", vec![Call::new(0, vec![1], vec![])]),
+///     ],
+///     code
+/// );
+/// ```
+pub fn parse_code_listing(listing: &str) -> Result<Vec<Code>> {
+    let tokens = tokenize(listing)?;
+    let mut parser = Parser { tokens, next: 0 };
+    let code = parser.code_list()?;
+    if parser.next < parser.tokens.len() {
+        return Err(listing_error("unexpected text after the end of the list"));
+    }
+    Ok(code)
+}
+
+fn listing_error<S: Into<String>>(message: S) -> anyhow::Error {
+    WasmgpError::InvalidCodeListing(message.into()).into()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    // A name or a number, such as `Add`, `12` or `-0.5f32`
+    Word(String),
+    Text(String),
+    PathSeparator,
+    Punctuation(char),
+}
+
+fn tokenize(listing: &str) -> Result<Vec<Token>> {
+    let mut tokens = vec![];
+    let mut chars = listing.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '(' | ')' | '[' | ']' | ',' | '!' => tokens.push(Token::Punctuation(c)),
+            ':' if chars.next_if_eq(&':').is_some() => tokens.push(Token::PathSeparator),
+            '"' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => text.push(unescape(&mut chars)?),
+                        Some(c) => text.push(c),
+                        None => return Err(listing_error("a string is missing its closing quote")),
+                    }
+                }
+                tokens.push(Token::Text(text));
+            }
+            c if is_word_char(c) => {
+                let mut word = String::from(c);
+                while let Some(c) = chars.next_if(|c| is_word_char(*c)) {
+                    word.push(c);
+                }
+                tokens.push(Token::Word(word));
+            }
+            c => return Err(listing_error(format!("unexpected character '{}'", c))),
+        }
+    }
+    Ok(tokens)
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.' || c == '-' || c == '+'
+}
+
+// Reads the rest of an escape sequence as written by `{:?}`
+fn unescape(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<char> {
+    match chars.next() {
+        Some('n') => Ok('\n'),
+        Some('r') => Ok('\r'),
+        Some('t') => Ok('\t'),
+        Some('0') => Ok('\0'),
+        Some('u') => {
+            let mut hex = String::new();
+            if chars.next() == Some('{') {
+                while let Some(c) = chars.next_if(|c| *c != '}') {
+                    hex.push(c);
+                }
+                chars.next();
+            }
+            u32::from_str_radix(&hex, 16)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or_else(|| listing_error(format!("'\\u{{{}}}' is not a character", hex)))
+        }
+        Some(c) => Ok(c),
+        None => Err(listing_error("a string ends in the middle of an escape")),
+    }
+}
+
+// An argument of a Code constructor
+enum Arg {
+    Word(String),
+    Text(String),
+    List(Vec<Arg>),
+    Code(Code),
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    next: usize,
+}
+
+impl Parser {
+    fn peek(&self, ahead: usize) -> Option<&Token> {
+        self.tokens.get(self.next + ahead)
+    }
+
+    fn take(&mut self) -> Result<Token> {
+        let token = self
+            .tokens
+            .get(self.next)
+            .cloned()
+            .ok_or_else(|| listing_error("the listing ends too soon"))?;
+        self.next += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<()> {
+        let token = self.take()?;
+        if token == expected {
+            Ok(())
+        } else {
+            Err(listing_error(format!("expected {:?} but found {:?}", expected, token)))
+        }
+    }
+
+    // Skips the comma that may follow an item of a list
+    fn skip_comma(&mut self) {
+        if self.peek(0) == Some(&Token::Punctuation(',')) {
+            self.next += 1;
+        }
+    }
+
+    fn code_list(&mut self) -> Result<Vec<Code>> {
+        self.expect(Token::Punctuation('['))?;
+        let mut code = vec![];
+        while self.peek(0) != Some(&Token::Punctuation(']')) {
+            code.push(self.code()?);
+            self.skip_comma();
+        }
+        self.expect(Token::Punctuation(']'))?;
+        Ok(code)
+    }
+
+    fn code(&mut self) -> Result<Code> {
+        let name = match self.take()? {
+            Token::Word(name) => name,
+            token => {
+                return Err(listing_error(format!(
+                    "expected the name of a Code but found {:?}",
+                    token
+                )))
+            }
+        };
+        self.expect(Token::PathSeparator)?;
+        self.expect(Token::Word("new".into()))?;
+        self.expect(Token::Punctuation('('))?;
+        let mut args = vec![];
+        while self.peek(0) != Some(&Token::Punctuation(')')) {
+            args.push(self.arg()?);
+            self.skip_comma();
+        }
+        self.expect(Token::Punctuation(')'))?;
+        build_code(&name, Args { name: &name, args })
+    }
+
+    fn arg(&mut self) -> Result<Arg> {
+        match self.take()? {
+            Token::Word(word) if word == "vec" => {
+                self.expect(Token::Punctuation('!'))?;
+                self.expect(Token::Punctuation('['))?;
+                let mut items = vec![];
+                while self.peek(0) != Some(&Token::Punctuation(']')) {
+                    // An item followed by `::` is a Code constructor, otherwise it is a slot
+                    if self.peek(1) == Some(&Token::PathSeparator) {
+                        items.push(Arg::Code(self.code()?));
+                    } else {
+                        items.push(self.arg()?);
+                    }
+                    self.skip_comma();
+                }
+                self.expect(Token::Punctuation(']'))?;
+                Ok(Arg::List(items))
+            }
+            Token::Word(word) => Ok(Arg::Word(word)),
+            Token::Text(text) => Ok(Arg::Text(text)),
+            token => Err(listing_error(format!("expected an argument but found {:?}", token))),
+        }
+    }
+}
+
+struct Args<'a> {
+    name: &'a str,
+    args: Vec<Arg>,
+}
+
+impl<'a> Args<'a> {
+    fn arity(&self, expected: usize) -> Result<()> {
+        if self.args.len() == expected {
+            Ok(())
+        } else {
+            Err(listing_error(format!(
+                "{} takes {} arguments but has {}",
+                self.name,
+                expected,
+                self.args.len()
+            )))
+        }
+    }
+
+    fn number<N: FromStr>(&self, index: usize) -> Result<N> {
+        match &self.args[index] {
+            Arg::Word(word) => parse_number(word),
+            _ => Err(listing_error(format!(
+                "argument {} of {} is not a number",
+                index, self.name
+            ))),
+        }
+    }
+
+    // A float is printed with its type as a suffix, such as `1.5f32`
+    fn float<N: FromStr>(&self, index: usize, suffix: &str) -> Result<N> {
+        match &self.args[index] {
+            Arg::Word(word) => parse_number(word.strip_suffix(suffix).unwrap_or(word)),
+            _ => Err(listing_error(format!(
+                "argument {} of {} is not a number",
+                index, self.name
+            ))),
+        }
+    }
+
+    fn text(&self, index: usize) -> Result<&str> {
+        match &self.args[index] {
+            Arg::Text(text) => Ok(text),
+            _ => Err(listing_error(format!(
+                "argument {} of {} is not a string",
+                index, self.name
+            ))),
+        }
+    }
+
+    fn slots(&self, index: usize) -> Result<Vec<Slot>> {
+        match &self.args[index] {
+            Arg::List(items) => items
+                .iter()
+                .map(|item| match item {
+                    Arg::Word(word) => parse_number(word),
+                    _ => Err(listing_error(format!(
+                        "argument {} of {} is not a list of slots",
+                        index, self.name
+                    ))),
+                })
+                .collect(),
+            _ => Err(listing_error(format!(
+                "argument {} of {} is not a list",
+                index, self.name
+            ))),
+        }
+    }
+
+    fn code(&mut self, index: usize) -> Result<Vec<Code>> {
+        match &mut self.args[index] {
+            Arg::List(items) => std::mem::take(items)
+                .into_iter()
+                .map(|item| match item {
+                    Arg::Code(code) => Ok(code),
+                    _ => Err(listing_error(format!(
+                        "argument {} of {} is not a list of code",
+                        index, self.name
+                    ))),
+                })
+                .collect(),
+            _ => Err(listing_error(format!(
+                "argument {} of {} is not a list",
+                index, self.name
+            ))),
+        }
+    }
+}
+
+fn parse_number<N: FromStr>(word: &str) -> Result<N> {
+    word.parse()
+        .map_err(|_| listing_error(format!("'{}' is not a number of the right type", word)))
+}
+
+const ONE_SLOT: &[(&str, fn(Slot) -> Code)] = &[
+    ("ConstOne", ConstOne::new),
+    ("ConstZero", ConstZero::new),
+    ("BreakIf", BreakIf::new),
+];
+
+const TWO_SLOTS: &[(&str, fn(Slot, Slot) -> Code)] = &[
+    #[cfg(feature = "bit_ops")]
+    ("CountLeadingZeros", CountLeadingZeros::new),
+    #[cfg(feature = "bit_ops")]
+    ("CountTrailingZeros", CountTrailingZeros::new),
+    #[cfg(feature = "bit_ops")]
+    ("PopulationCount", PopulationCount::new),
+    #[cfg(feature = "bit_ops")]
+    ("PowerOfTwo", PowerOfTwo::new),
+    #[cfg(feature = "bit_ops")]
+    ("IntegerLog2", IntegerLog2::new),
+    #[cfg(feature = "float_ops")]
+    ("AbsoluteValue", AbsoluteValue::new),
+    #[cfg(feature = "float_ops")]
+    ("Negate", Negate::new),
+    #[cfg(feature = "float_ops")]
+    ("SquareRoot", SquareRoot::new),
+    #[cfg(feature = "float_ops")]
+    ("Ceiling", Ceiling::new),
+    #[cfg(feature = "float_ops")]
+    ("Floor", Floor::new),
+    #[cfg(feature = "float_ops")]
+    ("Nearest", Nearest::new),
+    ("IsEqualZero", IsEqualZero::new),
+    ("CopySlot", CopySlot::new),
+];
+
+const THREE_SLOTS: &[(&str, fn(Slot, Slot, Slot) -> Code)] = &[
+    #[cfg(feature = "bit_ops")]
+    ("And", And::new),
+    #[cfg(feature = "bit_ops")]
+    ("Or", Or::new),
+    #[cfg(feature = "bit_ops")]
+    ("Xor", Xor::new),
+    #[cfg(feature = "bit_ops")]
+    ("ShiftLeft", ShiftLeft::new),
+    #[cfg(feature = "bit_ops")]
+    ("ShiftRight", ShiftRight::new),
+    #[cfg(feature = "bit_ops")]
+    ("RotateLeft", RotateLeft::new),
+    #[cfg(feature = "bit_ops")]
+    ("RotateRight", RotateRight::new),
+    ("Add", Add::new),
+    ("Subtract", Subtract::new),
+    ("Multiply", Multiply::new),
+    ("Divide", Divide::new),
+    ("Remainder", Remainder::new),
+    ("Power", Power::new),
+    #[cfg(feature = "float_ops")]
+    ("Min", Min::new),
+    #[cfg(feature = "float_ops")]
+    ("Max", Max::new),
+    #[cfg(feature = "float_ops")]
+    ("CopySign", CopySign::new),
+    ("AreEqual", AreEqual::new),
+    ("AreNotEqual", AreNotEqual::new),
+    ("IsLessThan", IsLessThan::new),
+    ("IsGreaterThan", IsGreaterThan::new),
+    ("IsLessThanOrEqual", IsLessThanOrEqual::new),
+    ("IsGreaterThanOrEqual", IsGreaterThanOrEqual::new),
+];
+
+fn build_code(name: &str, mut a: Args) -> Result<Code> {
+    if let Some((_, new)) = ONE_SLOT.iter().find(|(n, _)| *n == name) {
+        a.arity(1)?;
+        return Ok(new(a.number(0)?));
+    }
+    if let Some((_, new)) = TWO_SLOTS.iter().find(|(n, _)| *n == name) {
+        a.arity(2)?;
+        return Ok(new(a.number(0)?, a.number(1)?));
+    }
+    if let Some((_, new)) = THREE_SLOTS.iter().find(|(n, _)| *n == name) {
+        a.arity(3)?;
+        return Ok(new(a.number(0)?, a.number(1)?, a.number(2)?));
+    }
+
+    let arity = match name {
+        "Return" | "Break" => 0,
+        "ConstI32" | "ConstI64" | "ConstF32" | "ConstF64" | "If" | "DoUntil" | "DoWhile" | "DoFor" | "Provenance" => 2,
+        "IfElse" | "Call" | "FillSlots" | "SumSlots" | "CopySlots" => 3,
+        _ => return Err(listing_error(format!("'{}' is not a kind of Code", name))),
+    };
+    a.arity(arity)?;
+    Ok(match name {
+        "Return" => Return::new(),
+        "Break" => Break::new(),
+        "ConstI32" => ConstI32::new(a.number(0)?, a.number(1)?),
+        "ConstI64" => ConstI64::new(a.number(0)?, a.number(1)?),
+        "ConstF32" => ConstF32::new(a.number(0)?, a.float(1, "f32")?),
+        "ConstF64" => ConstF64::new(a.number(0)?, a.float(1, "f64")?),
+        "If" => If::new(a.number(0)?, a.code(1)?),
+        "DoUntil" => DoUntil::new(a.number(0)?, a.code(1)?),
+        "DoWhile" => DoWhile::new(a.number(0)?, a.code(1)?),
+        "DoFor" => DoFor::new(a.number(0)?, a.code(1)?),
+        "Provenance" => Provenance::new(a.text(0)?, a.code(1)?),
+        "IfElse" => IfElse::new(a.number(0)?, a.code(1)?, a.code(2)?),
+        "Call" => Call::new(a.number(0)?, a.slots(1)?, a.slots(2)?),
+        "FillSlots" => FillSlots::new(a.number(0)?, a.number(1)?, a.number(2)?),
+        "SumSlots" => SumSlots::new(a.number(0)?, a.number(1)?, a.number(2)?),
+        _ => CopySlots::new(a.number(0)?, a.number(1)?, a.number(2)?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::code_builder::CodeBuilder;
+    use crate::indentation::Indentation;
+    use crate::*;
+
+    #[test]
+    fn printed_code_parses_back_to_the_same_code() {
+        let mut engine = GeneticEngine::new(GeneticEngineConfiguration::new(Some(11), 8));
+        engine.set_host_call_weight(0, 2, 1, 1);
+        for _ in 0..50 {
+            let mut code = engine.random_code_list(40);
+            code.push(Provenance::new(
+                "from island 3 \"gen\" 212\n",
+                vec![ConstF64::new(1, f64::MIN_POSITIVE)],
+            ));
+            let mut listing = std::string::String::new();
+            code.print_for_rust(&mut listing, &mut Indentation::new(2, 0)).unwrap();
+            assert_eq!(code, parse_code_listing(&listing).unwrap());
+        }
+    }
+
+    #[test]
+    fn mistakes_are_reported() {
+        assert!(parse_code_listing("[Add::new(0, 1)]").is_err());
+        assert!(parse_code_listing("[Launch::new()]").is_err());
+        assert!(parse_code_listing("[ConstI32::new(0, 1.5)]").is_err());
+        assert!(parse_code_listing("[Return::new(),").is_err());
+        assert!(parse_code_listing("[Return::new()] Break::new()").is_err());
+    }
+}
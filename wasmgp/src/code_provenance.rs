@@ -0,0 +1,125 @@
+use crate::code_builder::{append_code_list, CodeBuilder};
+use crate::indentation::Indentation;
+use crate::*;
+use anyhow::Result;
+use std::fmt::Write;
+use wasm_ast::Instruction;
+
+/// Provenance(tag, do): Marks where the code listed in 'do' came from, such as "seeded" or "from island 3 gen 212".
+/// The code runs exactly as if it were not wrapped, and no instructions are added for the tag.
+///
+/// Mutation and crossover carry the tag along with whatever part of the wrapped code survives, and the tag is printed
+/// with the code, so the parts of a champion that came from a seed can be told apart from the parts that evolved. The
+/// genetic algorithm never creates a Provenance on its own.
+///
+/// ```
+/// use wasmgp::*;
+/// use wasmgp_macros::wasm_code;
+///
+/// #[wasm_code]
+/// fn double(value: u32) -> u32 {
+///     [
+///         Provenance::new("seeded", vec![
+///             Add::new(0, 0, 1),
+///         ]),
+///         Return::new(),
+///     ]
+/// }
+/// let func = Double::new().unwrap();
+/// assert_eq!(4, func.call(2).unwrap());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Provenance {
+    tag: String,
+    do_this: Vec<Code>,
+}
+
+impl Provenance {
+    pub fn new(tag: &str, do_this: Vec<Code>) -> Code {
+        Code::Provenance(Provenance {
+            tag: tag.to_owned(),
+            do_this,
+        })
+    }
+
+    pub fn points(&self) -> usize {
+        1 + self.do_this.iter().map(|code| code.points()).sum::<usize>()
+    }
+
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    pub fn do_this(&self) -> &[Code] {
+        &self.do_this[..]
+    }
+}
+
+impl CodeBuilder for Provenance {
+    fn append_code(&self, context: &CodeContext, instruction_list: &mut Vec<Instruction>) -> Result<()> {
+        append_code_list(&self.do_this, "body", context, instruction_list)
+    }
+
+    fn make_random_code(&self, engine: &mut GeneticEngine, max_points: usize) -> Code {
+        assert!(
+            max_points >= 2,
+            "internal error: `Provenance::make_random_code` called with too few points"
+        );
+        let children = engine.random_code_list(max_points - 1);
+        Provenance::new("random", children)
+    }
+
+    fn print_for_rust(&self, f: &mut std::string::String, indentation: &mut Indentation) -> std::fmt::Result {
+        write!(f, "{}Provenance::new({:?}, vec!", indentation, self.tag)?;
+        self.do_this.print_for_rust(f, indentation)?;
+        writeln!(f, "),")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::code_builder::CodeBuilder;
+    use crate::indentation::Indentation;
+    use crate::*;
+
+    #[test]
+    fn tags_survive_mutation_and_crossover() {
+        let seeded = vec![Provenance::new(
+            "seeded",
+            vec![Add::new(0, 1, 2), Subtract::new(2, 1, 0), Multiply::new(0, 0, 1)],
+        )];
+        let evolved = vec![Divide::new(0, 1, 2), Remainder::new(2, 1, 0)];
+
+        let mut engine = GeneticEngine::new(GeneticEngineConfiguration::new(Some(5), 3));
+        assert_eq!(0, engine.get_code_weight(&Code::Provenance(Provenance::default())));
+        let mut tagged = 0;
+        for _ in 0..20 {
+            let child = engine.crossover(&seeded, &evolved, 1).unwrap();
+            let mutant = engine.mutate(&seeded, 1).unwrap();
+            for code in child.iter().chain(mutant.iter()) {
+                code.walk(&mut |c| {
+                    if let Code::Provenance(p) = c {
+                        assert_eq!("seeded", p.tag());
+                        tagged += 1;
+                    }
+                });
+            }
+        }
+        assert!(tagged > 0);
+    }
+
+    #[test]
+    fn tags_are_printed() {
+        let code = vec![Provenance::new("from island 3 gen 212", vec![ConstOne::new(0)])];
+        let mut output = std::string::String::new();
+        code.print_for_rust(&mut output, &mut Indentation::new(4, 0)).unwrap();
+        assert_eq!(
+            "[
+    Provenance::new(\"from island 3 gen 212\", vec![
+        ConstOne::new(0),
+    ]),
+]",
+            output
+        );
+    }
+}
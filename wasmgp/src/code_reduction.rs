@@ -0,0 +1,184 @@
+use crate::*;
+use anyhow::Result;
+
+/// Shrinks the code to a smaller program that `keep` still accepts, such as a champion that still wins a third of its
+/// games, or a program that still shows a bug. Runs of top-level code are removed first, in halves, then quarters, and
+/// so on. After that, every code item at any depth is removed, or replaced by its body when it is a block, one at a
+/// time until no more of them can go. Each candidate is passed to `keep`, and it replaces the code when `keep` returns
+/// true.
+///
+/// The result cannot lose any single item or block and still pass, but a smaller program may exist that needs several
+/// items changed at once. `World::reduce_champion` runs the candidates through an island's callbacks. Returns
+/// `WasmgpError::ReductionPredicateFailed` if the code does not pass before it is reduced.
+/// ```
+/// use wasmgp::*;
+///
+/// let code = vec![
+///     ConstOne::new(0),
+///     DoFor::new(2, vec![ConstOne::new(1), Add::new(0, 1, 2)]),
+///     Subtract::new(0, 1, 2),
+/// ];
+///
+/// // Keeps any program that still has the Add in it
+/// let has_add = |code: &[Code]| {
+///     let mut found = false;
+///     for c in code.iter() {
+///         c.walk(&mut |c| found |= *c == Add::new(0, 1, 2));
+///     }
+///     found
+/// };
+/// assert_eq!(vec![Add::new(0, 1, 2)], reduce_code(&code, has_add).unwrap());
+/// ```
+pub fn reduce_code<K>(code: &[Code], mut keep: K) -> Result<Vec<Code>>
+where
+    K: FnMut(&[Code]) -> bool,
+{
+    if !keep(code) {
+        return Err(WasmgpError::ReductionPredicateFailed.into());
+    }
+
+    // Remove runs of top-level code, halving the length of the runs down to single items
+    let mut reduced = code.to_vec();
+    let mut run = reduced.len() / 2;
+    while run > 0 {
+        let mut start = 0;
+        while start < reduced.len() {
+            let end = (start + run).min(reduced.len());
+            let mut candidate = reduced[..start].to_vec();
+            candidate.extend_from_slice(&reduced[end..]);
+            if keep(&candidate) {
+                reduced = candidate;
+            } else {
+                start = end;
+            }
+        }
+        run /= 2;
+    }
+
+    // Remove single items at any depth, or replace blocks by their bodies, until a whole pass changes nothing
+    let mut changed = true;
+    while changed {
+        changed = false;
+        let mut index = 0;
+        while let Some(item) = item_at(&reduced, index) {
+            let mut edits = vec![Edit::Remove];
+            if body(item).is_some() {
+                edits.push(Edit::Hoist);
+            }
+            let candidate = edits
+                .into_iter()
+                .map(|edit| edit_at(&reduced, &mut Some(index), edit))
+                .find(|candidate| keep(candidate));
+            match candidate {
+                Some(candidate) => {
+                    reduced = candidate;
+                    changed = true;
+                }
+                None => index += 1,
+            }
+        }
+    }
+    Ok(reduced)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Edit {
+    // Drops the item and everything in it
+    Remove,
+
+    // Puts the body of a block in place of the block
+    Hoist,
+}
+
+// Returns the item that is `index` items into the code, in the order that `walk` visits them
+fn item_at(code: &[Code], index: usize) -> Option<&Code> {
+    let mut remaining = index;
+    let mut found = None;
+    for c in code.iter() {
+        c.walk(&mut |c| {
+            if remaining == 0 && found.is_none() {
+                found = Some(c);
+            }
+            remaining = remaining.wrapping_sub(1);
+        });
+    }
+    found
+}
+
+// Returns the code inside of a block, with both branches of an IfElse one after the other, or None for other code
+fn body(code: &Code) -> Option<Vec<Code>> {
+    match code {
+        Code::If(c) => Some(c.do_this().to_vec()),
+        Code::IfElse(c) => Some([c.do_this(), c.else_do_this()].concat()),
+        Code::DoUntil(c) => Some(c.do_this().to_vec()),
+        Code::DoWhile(c) => Some(c.do_this().to_vec()),
+        Code::DoFor(c) => Some(c.do_this().to_vec()),
+        Code::Provenance(c) => Some(c.do_this().to_vec()),
+        _ => None,
+    }
+}
+
+// Rebuilds the code, making the edit to the item that is `target` items further on. The target is set to None once the
+// edit has been made.
+fn edit_at(code: &[Code], target: &mut Option<usize>, edit: Edit) -> Vec<Code> {
+    let mut edited = vec![];
+    for c in code.iter() {
+        if *target == Some(0) {
+            *target = None;
+            if edit == Edit::Hoist {
+                edited.extend(body(c).unwrap_or_default());
+            }
+            continue;
+        }
+        if let Some(remaining) = target.as_mut() {
+            *remaining -= 1;
+        }
+        let c = match c {
+            Code::If(c) => If::new(c.if_not_zero(), edit_at(c.do_this(), target, edit)),
+            Code::IfElse(c) => {
+                let do_this = edit_at(c.do_this(), target, edit);
+                IfElse::new(c.if_not_zero(), do_this, edit_at(c.else_do_this(), target, edit))
+            }
+            Code::DoUntil(c) => DoUntil::new(c.until_not_zero(), edit_at(c.do_this(), target, edit)),
+            Code::DoWhile(c) => DoWhile::new(c.while_not_zero(), edit_at(c.do_this(), target, edit)),
+            Code::DoFor(c) => DoFor::new(c.times(), edit_at(c.do_this(), target, edit)),
+            Code::Provenance(c) => Provenance::new(c.tag(), edit_at(c.do_this(), target, edit)),
+            c => c.clone(),
+        };
+        edited.push(c);
+    }
+    edited
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_item_can_be_removed_or_hoisted() {
+        let code = vec![
+            ConstOne::new(0),
+            IfElse::new(1, vec![Add::new(0, 1, 2)], vec![Multiply::new(0, 1, 2)]),
+        ];
+        assert_eq!(Some(&Add::new(0, 1, 2)), item_at(&code, 2));
+        assert_eq!(None, item_at(&code, 4));
+        assert_eq!(
+            vec![ConstOne::new(0), IfElse::new(1, vec![Add::new(0, 1, 2)], vec![])],
+            edit_at(&code, &mut Some(3), Edit::Remove)
+        );
+        assert_eq!(
+            vec![ConstOne::new(0), Add::new(0, 1, 2), Multiply::new(0, 1, 2)],
+            edit_at(&code, &mut Some(1), Edit::Hoist)
+        );
+    }
+
+    #[test]
+    fn code_that_does_not_pass_is_not_reduced() {
+        assert_eq!(0, reduce_code(&[ConstOne::new(0)], |_| true).unwrap().len());
+        assert_eq!(
+            1,
+            reduce_code(&[ConstOne::new(0)], |code| !code.is_empty()).unwrap().len()
+        );
+        assert!(reduce_code(&[ConstOne::new(0)], |code| code.len() > 1).is_err());
+    }
+}
@@ -0,0 +1,264 @@
+use crate::code_builder::CodeBuilder;
+use crate::convert::{GetSlotConvert, SetSlotConvert, StackConvert};
+use crate::indentation::Indentation;
+use crate::*;
+use anyhow::Result;
+use rand::Rng;
+use std::fmt::Write;
+use std::ops::Range;
+use wasm_ast::{Instruction, NumericInstruction};
+
+/// Sets each of the `count` slots beginning with `start` to `value`. The value is converted to the type of each slot.
+/// Slots past the end of the function are skipped. The instruction is unrolled into one store per slot.
+///
+/// ```
+/// use wasmgp::*;
+/// use wasmgp_macros::wasm_code;
+///
+/// #[wasm_code(signed, 2)]
+/// fn fill_then_sum(value: i32) -> i32 {
+///     [FillSlots::new(2, 2, 7), Add::new(2, 3, 1), Return::new()]
+/// }
+/// let func = FillThenSum::new().unwrap();
+/// assert_eq!(14, func.call(0).unwrap());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FillSlots {
+    start: Slot,
+    count: u8,
+    value: i32,
+}
+
+impl FillSlots {
+    pub fn new(start: Slot, count: u8, value: i32) -> Code {
+        Code::FillSlots(FillSlots { start, count, value })
+    }
+
+    pub fn start(&self) -> Slot {
+        self.start
+    }
+
+    pub fn count(&self) -> u8 {
+        self.count
+    }
+
+    pub fn value(&self) -> i32 {
+        self.value
+    }
+}
+
+impl CodeBuilder for FillSlots {
+    fn append_code(&self, context: &CodeContext, instruction_list: &mut Vec<Instruction>) -> Result<()> {
+        for slot in slot_range(self.start, self.count, context.slot_count()) {
+            instruction_list.push(NumericInstruction::I32Constant(self.value).into());
+            SetSlotConvert::convert(slot as Slot, ValueType::I32, context, instruction_list)?;
+        }
+        Ok(())
+    }
+
+    fn make_random_code(&self, engine: &mut GeneticEngine, _max_points: usize) -> Code {
+        let start = engine.random_slot();
+        FillSlots::new(start, engine.random_slot_count(start), engine.rng().gen())
+    }
+
+    fn print_for_rust(&self, f: &mut std::string::String, indentation: &mut Indentation) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{}FillSlots::new({}, {}, {}),",
+            indentation, self.start, self.count, self.value
+        )
+    }
+}
+
+/// Adds together the `count` slots beginning with `start`, placing the result in the `destination` slot. All operands
+/// are converted to the type of the result before the operation. Slots past the end of the function are skipped, and
+/// an empty range sets the destination to zero.
+///
+/// ```
+/// use wasmgp::*;
+/// use wasmgp_macros::wasm_code;
+///
+/// #[wasm_code(signed)]
+/// fn sum_three(v1: i32, v2: i32, v3: i32) -> i32 {
+///     [SumSlots::new(0, 3, 3), Return::new()]
+/// }
+/// let func = SumThree::new().unwrap();
+/// assert_eq!(6, func.call(1, 2, 3).unwrap());
+/// assert_eq!(-4, func.call(1, -2, -3).unwrap());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SumSlots {
+    start: Slot,
+    count: u8,
+    destination: Slot,
+}
+
+impl SumSlots {
+    pub fn new(start: Slot, count: u8, destination: Slot) -> Code {
+        Code::SumSlots(SumSlots {
+            start,
+            count,
+            destination,
+        })
+    }
+
+    pub fn start(&self) -> Slot {
+        self.start
+    }
+
+    pub fn count(&self) -> u8 {
+        self.count
+    }
+
+    pub fn destination(&self) -> Slot {
+        self.destination
+    }
+}
+
+impl CodeBuilder for SumSlots {
+    fn append_code(&self, context: &CodeContext, instruction_list: &mut Vec<Instruction>) -> Result<()> {
+        let operate_as = context.get_slot_value_type(self.destination)?;
+        instruction_list.push(NumericInstruction::I32Constant(0).into());
+        StackConvert::convert(ValueType::I32, operate_as, context, instruction_list)?;
+        for slot in slot_range(self.start, self.count, context.slot_count()) {
+            GetSlotConvert::convert(slot as Slot, operate_as, context, instruction_list)?;
+            instruction_list.push(NumericInstruction::Add(operate_as.into()).into());
+        }
+        SetSlotConvert::convert(self.destination, operate_as, context, instruction_list)?;
+        Ok(())
+    }
+
+    fn make_random_code(&self, engine: &mut GeneticEngine, _max_points: usize) -> Code {
+        let start = engine.random_slot();
+        SumSlots::new(start, engine.random_slot_count(start), engine.random_slot())
+    }
+
+    fn print_for_rust(&self, f: &mut std::string::String, indentation: &mut Indentation) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{}SumSlots::new({}, {}, {}),",
+            indentation, self.start, self.count, self.destination
+        )
+    }
+}
+
+/// Copies the `count` slots beginning with `source_start` into the slots beginning with `destination_start`. Each value
+/// is converted to the type of the slot it is copied into. The ranges may overlap: every source is read before any
+/// destination is written. Pairs of slots that fall past the end of the function are skipped.
+///
+/// ```
+/// use wasmgp::*;
+/// use wasmgp_macros::wasm_code;
+///
+/// #[wasm_code(signed)]
+/// fn shift_up(v1: i32, v2: i32) -> i32 {
+///     [CopySlots::new(0, 1, 2), Subtract::new(1, 2, 2), Return::new()]
+/// }
+/// let func = ShiftUp::new().unwrap();
+/// assert_eq!(-4, func.call(5, 9).unwrap());
+/// assert_eq!(0, func.call(3, 3).unwrap());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CopySlots {
+    source_start: Slot,
+    destination_start: Slot,
+    count: u8,
+}
+
+impl CopySlots {
+    pub fn new(source_start: Slot, destination_start: Slot, count: u8) -> Code {
+        Code::CopySlots(CopySlots {
+            source_start,
+            destination_start,
+            count,
+        })
+    }
+
+    pub fn source_start(&self) -> Slot {
+        self.source_start
+    }
+
+    pub fn destination_start(&self) -> Slot {
+        self.destination_start
+    }
+
+    pub fn count(&self) -> u8 {
+        self.count
+    }
+
+    /// Returns the (source, destination) pairs that are copied in a function with `slot_count` slots
+    pub fn pairs(&self, slot_count: usize) -> Vec<(Slot, Slot)> {
+        slot_range(self.source_start, self.count, slot_count)
+            .zip(slot_range(self.destination_start, self.count, slot_count))
+            .map(|(source, destination)| (source as Slot, destination as Slot))
+            .collect()
+    }
+}
+
+impl CodeBuilder for CopySlots {
+    fn append_code(&self, context: &CodeContext, instruction_list: &mut Vec<Instruction>) -> Result<()> {
+        // Load every source before storing, so that overlapping ranges copy the original values
+        let pairs = self.pairs(context.slot_count());
+        for (source, destination) in pairs.iter() {
+            let operate_as = context.get_slot_value_type(*destination)?;
+            GetSlotConvert::convert(*source, operate_as, context, instruction_list)?;
+        }
+        for (_, destination) in pairs.iter().rev() {
+            let operate_as = context.get_slot_value_type(*destination)?;
+            SetSlotConvert::convert(*destination, operate_as, context, instruction_list)?;
+        }
+        Ok(())
+    }
+
+    fn make_random_code(&self, engine: &mut GeneticEngine, _max_points: usize) -> Code {
+        let source_start = engine.random_slot();
+        let destination_start = engine.random_slot();
+        let count = engine.random_slot_count(source_start.max(destination_start));
+        CopySlots::new(source_start, destination_start, count)
+    }
+
+    fn print_for_rust(&self, f: &mut std::string::String, indentation: &mut Indentation) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{}CopySlots::new({}, {}, {}),",
+            indentation, self.source_start, self.destination_start, self.count
+        )
+    }
+}
+
+/// Returns the slot indexes of a range, clipped to the `slot_count` slots of the function
+pub(crate) fn slot_range(start: Slot, count: u8, slot_count: usize) -> Range<usize> {
+    let start = (start as usize).min(slot_count);
+    start..(start + count as usize).min(slot_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use wasmgp_macros::wasm_code;
+
+    #[test]
+    fn ranges_are_clipped_to_the_slots() {
+        #[wasm_code(signed)]
+        fn fill_past_the_end(value: i32) -> i32 {
+            [FillSlots::new(1, 200, 3), SumSlots::new(0, 255, 1), Return::new()]
+        }
+        let func = FillPastTheEnd::new().unwrap();
+        assert_eq!(7, func.call(4).unwrap());
+    }
+
+    #[test]
+    fn overlapping_copies_keep_the_original_values() {
+        #[wasm_code(signed)]
+        fn shift_up(v1: i32, v2: i32, v3: i32) -> i32 {
+            [CopySlots::new(1, 2, 2), Return::new()]
+        }
+        let func = ShiftUp::new().unwrap();
+        assert_eq!(3, func.call(1, 2, 3).unwrap());
+
+        let copy = CopySlots::new(0, 1, 4);
+        if let Code::CopySlots(copy) = copy {
+            assert_eq!(vec![(0, 1), (1, 2), (2, 3)], copy.pairs(4));
+        }
+    }
+}
@@ -13,12 +13,21 @@ impl CodeStream {
         I: IntoIterator<Item = &'a Code>,
     {
         let mut stream = vec![];
+        CodeStream::extend_stream(&mut stream, code);
+        stream
+    }
+
+    /// Appends the stream of the code to the end of `stream`, so that one buffer can hold the streams of many pieces of
+    /// code in turn
+    pub fn extend_stream<'a, I>(stream: &mut Vec<CodeStream>, code: I)
+    where
+        I: IntoIterator<Item = &'a Code>,
+    {
         for item in code.into_iter() {
             match item {
                 Code::If(instructions) => {
                     stream.push(CodeStream::Begin(If::new(instructions.if_not_zero(), vec![])));
-                    let mut branch = CodeStream::to_stream(instructions.do_this());
-                    stream.append(&mut branch);
+                    CodeStream::extend_stream(stream, instructions.do_this());
                     stream.push(CodeStream::End);
                 }
                 Code::IfElse(instructions) => {
@@ -27,38 +36,37 @@ impl CodeStream {
                         vec![],
                         vec![],
                     )));
-                    let mut branch = CodeStream::to_stream(instructions.do_this());
-                    stream.append(&mut branch);
+                    CodeStream::extend_stream(stream, instructions.do_this());
                     stream.push(CodeStream::End);
-                    let mut branch = CodeStream::to_stream(instructions.else_do_this());
-                    stream.append(&mut branch);
+                    CodeStream::extend_stream(stream, instructions.else_do_this());
                     stream.push(CodeStream::End);
                 }
                 Code::DoUntil(instructions) => {
                     stream.push(CodeStream::Begin(DoUntil::new(instructions.until_not_zero(), vec![])));
-                    let mut branch = CodeStream::to_stream(instructions.do_this());
-                    stream.append(&mut branch);
+                    CodeStream::extend_stream(stream, instructions.do_this());
                     stream.push(CodeStream::End);
                 }
                 Code::DoWhile(instructions) => {
                     stream.push(CodeStream::Begin(DoWhile::new(instructions.while_not_zero(), vec![])));
-                    let mut branch = CodeStream::to_stream(instructions.do_this());
-                    stream.append(&mut branch);
+                    CodeStream::extend_stream(stream, instructions.do_this());
                     stream.push(CodeStream::End);
                 }
                 Code::DoFor(instructions) => {
                     stream.push(CodeStream::Begin(DoFor::new(instructions.times(), vec![])));
-                    let mut branch = CodeStream::to_stream(instructions.do_this());
-                    stream.append(&mut branch);
+                    CodeStream::extend_stream(stream, instructions.do_this());
+                    stream.push(CodeStream::End);
+                }
+                Code::Provenance(instructions) => {
+                    stream.push(CodeStream::Begin(Provenance::new(instructions.tag(), vec![])));
+                    CodeStream::extend_stream(stream, instructions.do_this());
                     stream.push(CodeStream::End);
                 }
                 _ => stream.push(CodeStream::Simple(item.clone())),
             }
         }
-        stream
     }
 
-    pub fn from_stream(stream: &mut std::vec::IntoIter<CodeStream>) -> Vec<Code> {
+    pub fn from_stream<I: Iterator<Item = CodeStream>>(stream: &mut I) -> Vec<Code> {
         let mut code = vec![];
         while let Some(item) = stream.next() {
             match item {
@@ -85,6 +93,10 @@ impl CodeStream {
                         let branch = CodeStream::from_stream(stream);
                         code.push(DoFor::new(instructions.times(), branch));
                     }
+                    Code::Provenance(instructions) => {
+                        let branch = CodeStream::from_stream(stream);
+                        code.push(Provenance::new(instructions.tag(), branch));
+                    }
                     _ => {
                         panic!("getting here indicates a logical flaw in the code")
                     }
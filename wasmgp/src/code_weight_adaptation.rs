@@ -0,0 +1,21 @@
+/// Settings for slowly moving the code weights of the GeneticEngine toward how often each Code variant and host import
+/// is used by the elites of every island. The variants that the elites rely on become more likely in new code, while the
+/// `floor` keeps every variant in play so that the search does not stop exploring.
+///
+/// Only variants with a weight above zero are adapted; a variant that was turned off with a weight of zero stays off.
+/// The total weight of the adapted variants stays about the same from generation to generation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CodeWeightAdaptation {
+    /// How far each weight moves toward its target after every generation, between 0.0 (not at all) and 1.0 (all the
+    /// way)
+    pub rate: f64,
+
+    /// The lowest weight that a variant can be adapted down to. Must be at least one.
+    pub floor: u8,
+}
+
+impl CodeWeightAdaptation {
+    pub fn new(rate: f64, floor: u8) -> CodeWeightAdaptation {
+        CodeWeightAdaptation { rate, floor }
+    }
+}
@@ -0,0 +1,64 @@
+/// How a value is converted between an integer and a float when an instruction reads or writes a slot of another type.
+/// Conversions between two integers or two floats are the same in every profile: integers are extended using the
+/// signedness of the code or wrapped into fewer bits, and floats are promoted or demoted. Converting an integer to a
+/// float keeps its value in the `Saturating` and `Wrapping` profiles. No profile ever traps.
+///
+/// The profile changes what programs can evolve. Saturation keeps numbers close to their value, while wrapping and
+/// bitcasts give evolution a cheap way to reach the low bits of a float or to hash one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConversionProfile {
+    /// Floats are truncated toward zero into integers, clamping values that are out of range to the smallest or largest
+    /// integer, and NaN to zero (default)
+    Saturating,
+
+    /// Floats are truncated toward zero into integers, keeping only the low bits of values that are out of range as if
+    /// the integer had infinite bits. NaN and infinities become zero. The signedness of the code does not matter.
+    Wrapping,
+
+    /// The bits of a float become the bits of an integer of the same width, and the other way around. A value is
+    /// first extended or wrapped as an integer when the widths differ, such as an I32 that is extended to an I64
+    /// before its bits become an F64. Wasm does not promise which NaN an operation produces, so the bits of a NaN that
+    /// was calculated may differ from one machine to the next.
+    Bitcast,
+}
+
+impl Default for ConversionProfile {
+    fn default() -> Self {
+        ConversionProfile::Saturating
+    }
+}
+
+// 2^63 and 2^64, the bounds used to wrap a float into the range of an I64
+pub(crate) const TWO_TO_THE_63: f64 = 9223372036854775808.0;
+pub(crate) const TWO_TO_THE_64: f64 = 18446744073709551616.0;
+
+// Truncates the float toward zero and keeps the low 64 bits, in the same way as the code emitted for
+// `ConversionProfile::Wrapping`. Narrower integers keep the low bits of the result.
+pub(crate) fn wrap_float(value: f64) -> u64 {
+    if !value.is_finite() {
+        return 0;
+    }
+    let remainder = value.trunc() % TWO_TO_THE_64;
+    if remainder < 0.0 {
+        ((-remainder) as u64).wrapping_neg()
+    } else {
+        remainder as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn floats_wrap_into_the_low_bits() {
+        assert_eq!(7, wrap_float(7.9));
+        assert_eq!(u64::MAX, wrap_float(-1.5));
+        assert_eq!(1 << 63, wrap_float(TWO_TO_THE_63));
+        assert_eq!(0, wrap_float(TWO_TO_THE_64 * 3.0));
+        assert_eq!(4096, wrap_float(TWO_TO_THE_64 + 4096.0));
+        assert_eq!((-4096i64) as u64, wrap_float(-TWO_TO_THE_64 - 4096.0));
+        assert_eq!(0, wrap_float(f64::NAN));
+        assert_eq!(0, wrap_float(f64::NEG_INFINITY));
+    }
+}
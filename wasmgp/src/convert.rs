@@ -1,8 +1,12 @@
 use crate::code_builder::CodeBuilder;
+use crate::conversion_profile::{TWO_TO_THE_63, TWO_TO_THE_64};
 use crate::indentation::Indentation;
-use crate::{Code, CodeContext, GeneticEngine, Slot, ValueType};
+use crate::{Code, CodeContext, ConversionProfile, GeneticEngine, Slot, ValueType};
 use anyhow::Result;
-use wasm_ast::{FloatType, Instruction, IntegerType, NumericInstruction, VariableInstruction};
+use wasm_ast::{
+    BlockType, ControlInstruction, Expression, FloatType, Instruction, IntegerType, NumericInstruction, SignExtension,
+    VariableInstruction,
+};
 
 /// Used to convert a slot value to the value expected for a stack operation
 pub struct GetSlotConvert {
@@ -106,6 +110,9 @@ impl StackConvert {
 
 impl CodeBuilder for StackConvert {
     fn append_code(&self, context: &CodeContext, instruction_list: &mut Vec<Instruction>) -> Result<()> {
+        if self.source_type.is_float() && !self.destination_type.is_float() {
+            context.check_conversion(self.source_type, self.destination_type, instruction_list);
+        }
         match (self.source_type, self.destination_type) {
             // None of these need converting
             (ValueType::I32, ValueType::I32) => {}
@@ -113,77 +120,50 @@ impl CodeBuilder for StackConvert {
             (ValueType::F32, ValueType::F32) => {}
             (ValueType::F64, ValueType::F64) => {}
 
-            // Convert I32 to...
+            // Between integers, or between floats
             (ValueType::I32, ValueType::I64) => {
                 instruction_list.push(NumericInstruction::ExtendWithSignExtension(context.sign_extension()).into());
             }
-            (ValueType::I32, ValueType::F32) => {
-                instruction_list.push(
-                    NumericInstruction::Convert(FloatType::F32, IntegerType::I32, context.sign_extension()).into(),
-                );
-            }
-            (ValueType::I32, ValueType::F64) => {
-                instruction_list.push(
-                    NumericInstruction::Convert(FloatType::F64, IntegerType::I32, context.sign_extension()).into(),
-                );
-            }
-
-            // Convert I64 to...
             (ValueType::I64, ValueType::I32) => {
                 instruction_list.push(NumericInstruction::Wrap.into());
             }
-            (ValueType::I64, ValueType::F32) => {
-                instruction_list.push(
-                    NumericInstruction::Convert(FloatType::F32, IntegerType::I64, context.sign_extension()).into(),
-                );
-            }
-            (ValueType::I64, ValueType::F64) => {
-                instruction_list.push(
-                    NumericInstruction::Convert(FloatType::F64, IntegerType::I64, context.sign_extension()).into(),
-                );
-            }
-
-            // Convert F32 to...
-            (ValueType::F32, ValueType::I32) => instruction_list.push(
-                NumericInstruction::ConvertAndTruncateWithSaturation(
-                    IntegerType::I32,
-                    FloatType::F32,
-                    context.sign_extension(),
-                )
-                .into(),
-            ),
-            (ValueType::F32, ValueType::I64) => instruction_list.push(
-                NumericInstruction::ConvertAndTruncateWithSaturation(
-                    IntegerType::I64,
-                    FloatType::F32,
-                    context.sign_extension(),
-                )
-                .into(),
-            ),
             (ValueType::F32, ValueType::F64) => {
                 instruction_list.push(NumericInstruction::Promote.into());
             }
-
-            // Convert F64 to...
-            (ValueType::F64, ValueType::I32) => instruction_list.push(
-                NumericInstruction::ConvertAndTruncateWithSaturation(
-                    IntegerType::I32,
-                    FloatType::F64,
-                    context.sign_extension(),
-                )
-                .into(),
-            ),
-            (ValueType::F64, ValueType::I64) => instruction_list.push(
-                NumericInstruction::ConvertAndTruncateWithSaturation(
-                    IntegerType::I64,
-                    FloatType::F64,
-                    context.sign_extension(),
-                )
-                .into(),
-            ),
             (ValueType::F64, ValueType::F32) => {
                 instruction_list.push(NumericInstruction::Demote.into());
             }
+
+            // From an integer to a float, or from a float to an integer
+            (integer, float) if float.is_float() => match context.conversion_profile() {
+                ConversionProfile::Saturating | ConversionProfile::Wrapping => instruction_list
+                    .push(NumericInstruction::Convert(float.into(), integer.into(), context.sign_extension()).into()),
+                ConversionProfile::Bitcast => {
+                    let same_width = bitcast_width(float);
+                    StackConvert::convert(integer, same_width, context, instruction_list)?;
+                    instruction_list.push(NumericInstruction::ReinterpretInteger(float.into()).into());
+                }
+            },
+            (float, integer) => match context.conversion_profile() {
+                ConversionProfile::Saturating => instruction_list.push(
+                    NumericInstruction::ConvertAndTruncateWithSaturation(
+                        integer.into(),
+                        float.into(),
+                        context.sign_extension(),
+                    )
+                    .into(),
+                ),
+                ConversionProfile::Wrapping => {
+                    StackConvert::convert(float, ValueType::F64, context, instruction_list)?;
+                    wrap_f64_into_i64(context, instruction_list);
+                    StackConvert::convert(ValueType::I64, integer, context, instruction_list)?;
+                }
+                ConversionProfile::Bitcast => {
+                    let same_width = bitcast_width(float);
+                    instruction_list.push(NumericInstruction::ReinterpretFloat(same_width.into()).into());
+                    StackConvert::convert(same_width, integer, context, instruction_list)?;
+                }
+            },
         }
         Ok(())
     }
@@ -196,3 +176,70 @@ impl CodeBuilder for StackConvert {
         Ok(())
     }
 }
+
+// The integer type with the same number of bits as the float type
+fn bitcast_width(float: ValueType) -> ValueType {
+    match float {
+        ValueType::F32 => ValueType::I32,
+        _ => ValueType::I64,
+    }
+}
+
+// Replaces the F64 on top of the stack with an I64 holding its low 64 bits after truncating toward zero, in the same
+// way as `conversion_profile::wrap_float`. Every step is exact: a float of 2^64 or more is a multiple of 2^12, so
+// removing multiples of 2^64 from it leaves a value that can be held exactly, and the same is true when moving a value
+// of 2^63 or more into the range of an I64. NaN and the infinities end up as NaN, which the saturating truncation turns
+// into zero.
+fn wrap_f64_into_i64(context: &CodeContext, instruction_list: &mut Vec<Instruction>) {
+    let value = context.get_unused_local(ValueType::F64);
+    instruction_list.push(VariableInstruction::LocalSet(*value).into());
+
+    // Remove the multiples of 2^64 from a float that is too large for any 64-bit integer, leaving 0..2^64
+    instruction_list.push(VariableInstruction::LocalGet(*value).into());
+    instruction_list.push(NumericInstruction::AbsoluteValue(ValueType::F64.into()).into());
+    instruction_list.push(NumericInstruction::F64Constant(TWO_TO_THE_64).into());
+    instruction_list.push(NumericInstruction::GreaterThanOrEqualToFloat(ValueType::F64.into()).into());
+    let reduce = vec![
+        VariableInstruction::LocalGet(*value).into(),
+        VariableInstruction::LocalGet(*value).into(),
+        NumericInstruction::F64Constant(TWO_TO_THE_64).into(),
+        NumericInstruction::DivideFloat(ValueType::F64.into()).into(),
+        NumericInstruction::Floor(ValueType::F64.into()).into(),
+        NumericInstruction::F64Constant(TWO_TO_THE_64).into(),
+        NumericInstruction::Multiply(ValueType::F64.into()).into(),
+        NumericInstruction::Subtract(ValueType::F64.into()).into(),
+        VariableInstruction::LocalSet(*value).into(),
+    ];
+    instruction_list.push(ControlInstruction::If(BlockType::None, Expression::new(reduce), None).into());
+
+    // Move a float from 2^63 up to 2^64, or from -2^64 down to -2^63, by 2^64 into the range of an I64
+    for (compare, bound, adjust) in [
+        (
+            NumericInstruction::GreaterThanOrEqualToFloat(ValueType::F64.into()),
+            TWO_TO_THE_63,
+            NumericInstruction::Subtract(ValueType::F64.into()),
+        ),
+        (
+            NumericInstruction::LessThanFloat(ValueType::F64.into()),
+            -TWO_TO_THE_63,
+            NumericInstruction::Add(ValueType::F64.into()),
+        ),
+    ] {
+        instruction_list.push(VariableInstruction::LocalGet(*value).into());
+        instruction_list.push(NumericInstruction::F64Constant(bound).into());
+        instruction_list.push(compare.into());
+        let shift = vec![
+            VariableInstruction::LocalGet(*value).into(),
+            NumericInstruction::F64Constant(TWO_TO_THE_64).into(),
+            adjust.into(),
+            VariableInstruction::LocalSet(*value).into(),
+        ];
+        instruction_list.push(ControlInstruction::If(BlockType::None, Expression::new(shift), None).into());
+    }
+
+    instruction_list.push(VariableInstruction::LocalGet(*value).into());
+    instruction_list.push(
+        NumericInstruction::ConvertAndTruncateWithSaturation(IntegerType::I64, FloatType::F64, SignExtension::Signed)
+            .into(),
+    );
+}
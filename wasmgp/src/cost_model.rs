@@ -0,0 +1,140 @@
+use crate::Code;
+use strum::IntoEnumIterator;
+
+/// Estimates how expensive code is to run, as opposed to how much code there is. Each Code variant has a cost, and
+/// code nested in a loop costs as many times over as the loop is expected to run, so a few points of nested loops can
+/// cost far more than the same number of points of straight-line code. A World limits the cost of its individuals with
+/// `WorldConfiguration::individual_max_cost`.
+///
+/// By default every variant costs one, except for `Code::Call` and `Code::Power`, which runs a loop, at ten each, and
+/// `Code::Provenance`, which adds no instructions of its own and costs nothing. The
+/// body of a `DoFor` runs its `times` and the bodies of `DoUntil` and `DoWhile` run `loop_iterations` times. An
+/// `IfElse` costs its more expensive branch. The slot range instructions, such as `FillSlots`, cost once per slot in
+/// their range.
+/// ```
+/// use wasmgp::*;
+///
+/// let mut model = CostModel::default();
+/// let straight = [Add::new(0, 1, 2), Add::new(0, 1, 2), Add::new(0, 1, 2)];
+/// let looped = [DoFor::new(100, vec![Add::new(0, 1, 2), Add::new(0, 1, 2)])];
+/// assert_eq!(3, model.code_cost(&straight));
+/// assert_eq!(201, model.code_cost(&looped));
+///
+/// model.set_cost(&Code::Add(Add::default()), 5);
+/// assert_eq!(15, model.code_cost(&straight));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct CostModel {
+    // The cost of each Code variant, by name
+    costs: Vec<(&'static str, u64)>,
+    loop_iterations: u64,
+}
+
+impl CostModel {
+    /// Creates a model where every Code variant costs `cost`, and the body of a `DoUntil` or `DoWhile` is expected to
+    /// run `loop_iterations` times
+    pub fn new(cost: u64, loop_iterations: u64) -> CostModel {
+        CostModel {
+            costs: Code::iter().map(|code| (code.name(), cost)).collect(),
+            loop_iterations,
+        }
+    }
+
+    /// Sets the cost of one run of the Code variant, not counting any code nested inside of it
+    pub fn set_cost(&mut self, code: &Code, cost: u64) {
+        let name = code.name();
+        if let Some(entry) = self.costs.iter_mut().find(|entry| entry.0 == name) {
+            entry.1 = cost;
+        }
+    }
+
+    /// Returns the cost of one run of the Code variant, not counting any code nested inside of it
+    pub fn get_cost(&self, code: &Code) -> u64 {
+        let name = code.name();
+        self.costs
+            .iter()
+            .find(|entry| entry.0 == name)
+            .map_or(0, |entry| entry.1)
+    }
+
+    /// Sets the number of times the body of a `DoUntil` or `DoWhile` is expected to run
+    pub fn set_loop_iterations(&mut self, loop_iterations: u64) {
+        self.loop_iterations = loop_iterations;
+    }
+
+    /// Returns the number of times the body of a `DoUntil` or `DoWhile` is expected to run
+    pub fn loop_iterations(&self) -> u64 {
+        self.loop_iterations
+    }
+
+    /// Returns the cost of the code item, including the code nested inside of it
+    pub fn cost(&self, code: &Code) -> u64 {
+        let own = match code {
+            Code::FillSlots(c) => self.get_cost(code).saturating_mul(c.count() as u64),
+            Code::SumSlots(c) => self.get_cost(code).saturating_mul(c.count() as u64),
+            Code::CopySlots(c) => self.get_cost(code).saturating_mul(c.count() as u64),
+            _ => self.get_cost(code),
+        };
+        let nested = match code {
+            Code::If(instructions) => self.code_cost(instructions.do_this()),
+            Code::IfElse(instructions) => u64::max(
+                self.code_cost(instructions.do_this()),
+                self.code_cost(instructions.else_do_this()),
+            ),
+            Code::DoUntil(instructions) => self
+                .code_cost(instructions.do_this())
+                .saturating_mul(self.loop_iterations),
+            Code::DoWhile(instructions) => self
+                .code_cost(instructions.do_this())
+                .saturating_mul(self.loop_iterations),
+            Code::DoFor(instructions) => self
+                .code_cost(instructions.do_this())
+                .saturating_mul(instructions.times() as u64),
+            Code::Provenance(instructions) => self.code_cost(instructions.do_this()),
+            _ => 0,
+        };
+        own.saturating_add(nested)
+    }
+
+    /// Returns the total cost of a list of code
+    pub fn code_cost(&self, code: &[Code]) -> u64 {
+        code.iter().fold(0, |sum, item| sum.saturating_add(self.cost(item)))
+    }
+}
+
+impl Default for CostModel {
+    fn default() -> Self {
+        let mut model = CostModel::new(1, 10);
+        model.set_cost(&Code::Call(Default::default()), 10);
+        model.set_cost(&Code::Power(Default::default()), 10);
+        model.set_cost(&Code::Provenance(Default::default()), 0);
+        model
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn nested_loops_multiply_their_cost() {
+        let model = CostModel::default();
+        let code = [
+            Call::new(0, vec![], vec![]),
+            DoWhile::new(0, vec![DoFor::new(3, vec![Add::new(0, 1, 2)])]),
+            IfElse::new(0, vec![Add::new(0, 1, 2)], vec![ConstOne::new(0), ConstOne::new(1)]),
+        ];
+        assert_eq!(10, model.cost(&code[0]));
+        assert_eq!(1 + 10 * (1 + 3), model.cost(&code[1]));
+        assert_eq!(3, model.cost(&code[2]));
+        assert_eq!(54, model.code_cost(&code));
+        assert_eq!(4, model.cost(&SumSlots::new(0, 4, 5)));
+
+        let runaway = DoFor::new(u16::MAX, vec![DoFor::new(u16::MAX, vec![DoFor::new(u16::MAX, vec![])])]);
+        assert!(model.cost(&runaway) > 1_000_000_000);
+        let runaway = DoUntil::new(0, vec![runaway.clone(), runaway]);
+        let mut model = model;
+        model.set_loop_iterations(u64::MAX);
+        assert_eq!(u64::MAX, model.cost(&runaway));
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{Code, CodeStream};
+use std::fmt;
+use std::sync::Arc;
+
+/// Where a crossover may cut the code of its parents. The code of each parent is turned into a `CodeStream`, and a cut
+/// is made at a point between two items of the stream: point zero is before the first item, and the last point is
+/// after the last item. Restricting the cuts keeps scaffolding that was seeded with care, such as the loop of a game,
+/// from being torn apart by crossovers.
+///
+/// A `Crossover` cuts both parents at the same points, so a point is only used when both parents allow it. A
+/// `HomologousCrossover` only replaces segments of the first parent that begin and end at allowed points.
+/// ```
+/// use wasmgp::*;
+///
+/// let code = [ConstOne::new(0), DoFor::new(3, vec![Add::new(0, 0, 0), Add::new(0, 0, 0)]), ConstOne::new(1)];
+/// let stream = CodeStream::to_stream(&code);
+/// assert_eq!(vec![0, 1, 2, 3, 4, 5, 6], CrossoverCuts::Anywhere.allowed_points(&stream));
+/// assert_eq!(vec![0, 1, 5, 6], CrossoverCuts::OutsideLoops.allowed_points(&stream));
+///
+/// // Only cut before a constant
+/// let cuts = CrossoverCuts::custom(|stream, point| {
+///     matches!(stream.get(point), Some(CodeStream::Simple(Code::ConstOne(_))))
+/// });
+/// assert_eq!(vec![0, 5], cuts.allowed_points(&stream));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum CrossoverCuts {
+    /// At any point
+    Anywhere,
+
+    /// At any point that is not inside the body of a `DoUntil`, `DoWhile` or `DoFor`, so loops are always copied whole
+    OutsideLoops,
+
+    /// Only between the top-level statements of the code, so every block is copied whole
+    TopLevel,
+
+    /// Only at the points where the predicate returns true. The predicate is given the stream of one parent and a
+    /// point. See `CrossoverCuts::custom`
+    Custom(CutPredicate),
+}
+
+impl CrossoverCuts {
+    /// Creates a `CrossoverCuts::Custom` from the predicate
+    pub fn custom<F>(predicate: F) -> CrossoverCuts
+    where
+        F: Fn(&[CodeStream], usize) -> bool + Send + Sync + 'static,
+    {
+        CrossoverCuts::Custom(CutPredicate(Arc::new(predicate)))
+    }
+
+    /// Returns every point of the stream where a cut is allowed, in order
+    pub fn allowed_points(&self, stream: &[CodeStream]) -> Vec<usize> {
+        match self {
+            CrossoverCuts::Anywhere => (0..=stream.len()).collect(),
+            CrossoverCuts::OutsideLoops => (0..=stream.len())
+                .filter(|point| !enclosing_blocks(stream, *point).iter().any(is_loop))
+                .collect(),
+            CrossoverCuts::TopLevel => (0..=stream.len())
+                .filter(|point| enclosing_blocks(stream, *point).is_empty())
+                .collect(),
+            CrossoverCuts::Custom(predicate) => (0..=stream.len())
+                .filter(|point| (predicate.0)(stream, *point))
+                .collect(),
+        }
+    }
+}
+
+impl Default for CrossoverCuts {
+    fn default() -> Self {
+        CrossoverCuts::Anywhere
+    }
+}
+
+/// The predicate of a `CrossoverCuts::Custom`. Two predicates are only equal if they share the same function.
+#[derive(Clone)]
+pub struct CutPredicate(Arc<dyn Fn(&[CodeStream], usize) -> bool + Send + Sync>);
+
+impl fmt::Debug for CutPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CutPredicate")
+    }
+}
+
+impl PartialEq for CutPredicate {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+// Returns the blocks that are open at the point, outermost first. An IfElse is open until the end of its else branch.
+fn enclosing_blocks(stream: &[CodeStream], point: usize) -> Vec<&Code> {
+    let mut open = vec![];
+    for item in stream[..point].iter() {
+        match item {
+            CodeStream::Simple(_) => {}
+            CodeStream::Begin(code @ Code::IfElse(_)) => {
+                open.push(code);
+                open.push(code);
+            }
+            CodeStream::Begin(code) => open.push(code),
+            CodeStream::End => {
+                open.pop();
+            }
+        }
+    }
+    open
+}
+
+fn is_loop(code: &&Code) -> bool {
+    matches!(code, Code::DoUntil(_) | Code::DoWhile(_) | Code::DoFor(_))
+}
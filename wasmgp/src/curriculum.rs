@@ -0,0 +1,46 @@
+/// Raises the difficulty level of an island as its individuals get better. The level starts at zero, and moves up by
+/// one after a generation whose most fit individual has a score (from `IslandCallbacks::score_individual`) of at least
+/// the threshold for the current level. The last level is the number of thresholds.
+///
+/// The island passes its level to `IslandCallbacks::use_difficulty_level` before each generation runs, so that the
+/// callbacks can make the environment harder.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Curriculum {
+    thresholds: Vec<u64>,
+}
+
+impl Curriculum {
+    /// Creates a curriculum where level `n` is passed by scoring at least `thresholds[n]`. Scores from different levels
+    /// do not have to be comparable, so the thresholds may go down as well as up.
+    pub fn new(thresholds: Vec<u64>) -> Curriculum {
+        Curriculum { thresholds }
+    }
+
+    /// The highest level of the curriculum
+    pub fn max_level(&self) -> usize {
+        self.thresholds.len()
+    }
+
+    /// Returns the level that follows a generation at `level` whose most fit individual had `best_score`
+    pub fn next_level(&self, level: usize, best_score: u64) -> usize {
+        match self.thresholds.get(level) {
+            Some(threshold) if best_score >= *threshold => level + 1,
+            _ => level,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_go_up_one_threshold_at_a_time() {
+        let curriculum = Curriculum::new(vec![10, 5]);
+        assert_eq!(2, curriculum.max_level());
+        assert_eq!(0, curriculum.next_level(0, 9));
+        assert_eq!(1, curriculum.next_level(0, 100));
+        assert_eq!(2, curriculum.next_level(1, 5));
+        assert_eq!(2, curriculum.next_level(2, 1000));
+    }
+}
@@ -0,0 +1,276 @@
+use crate::{Code, SlotValue, ValueType, WasmgpError};
+use anyhow::Result;
+use rand::Rng;
+use std::fmt::Display;
+use wasmtime::Trap;
+
+/// Controls how much random Code `World::differential_test` generates and runs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DifferentialOptions {
+    /// The number of random programs to generate
+    pub programs: usize,
+
+    /// The number of random sets of parameters to run each program with
+    pub inputs_per_program: usize,
+
+    /// The maximum number of points in each random program
+    pub max_points: usize,
+
+    /// Seeds the random parameters and any randomly initialized slots. The programs come from the World's own
+    /// `GeneticEngine`, so they follow its code weights and seed.
+    pub seed: Option<u64>,
+}
+
+impl Default for DifferentialOptions {
+    fn default() -> Self {
+        DifferentialOptions {
+            programs: 100,
+            inputs_per_program: 4,
+            max_points: 20,
+            seed: None,
+        }
+    }
+}
+
+/// A program and parameters that produced a different outcome in Wasm than in the `ReferenceEvaluator`.
+#[derive(Clone, Debug)]
+pub struct Divergence {
+    pub code: Vec<Code>,
+    pub params: Vec<SlotValue>,
+
+    /// The results of each path, or the error that stopped it
+    pub wasm: std::result::Result<Vec<SlotValue>, String>,
+    pub reference: std::result::Result<Vec<SlotValue>, String>,
+}
+
+impl Display for Divergence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "params: {:?}", self.params)?;
+        writeln!(f, "wasm: {:?}", self.wasm)?;
+        writeln!(f, "reference: {:?}", self.reference)?;
+        write!(f, "code: {:?}", self.code)
+    }
+}
+
+/// The outcome of `World::differential_test`.
+#[derive(Clone, Debug, Default)]
+pub struct DifferentialReport {
+    /// The number of times a program was run on both paths
+    pub runs: usize,
+
+    /// Runs where both paths returned the same results, or both failed
+    pub agreed: usize,
+
+    /// Runs that could not be compared because one path ran out of time or steps
+    pub inconclusive: usize,
+
+    pub divergences: Vec<Divergence>,
+}
+
+impl DifferentialReport {
+    /// True when no run diverged
+    pub fn is_clean(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+// Creates parameters that favor the values where conversions and arithmetic tend to surprise: zero, the extremes, and
+// for floats the non-finite values.
+pub(crate) fn random_params<R: Rng>(types: &[ValueType], rng: &mut R) -> Vec<SlotValue> {
+    types
+        .iter()
+        .map(|value_type| {
+            let use_edge = rng.gen_ratio(1, 4);
+            match value_type {
+                ValueType::I32 if use_edge => SlotValue::I32(pick(&[0, 1, -1, i32::MIN, i32::MAX], rng)),
+                ValueType::I32 => SlotValue::I32(rng.gen()),
+                ValueType::I64 if use_edge => SlotValue::I64(pick(&[0, 1, -1, i64::MIN, i64::MAX], rng)),
+                ValueType::I64 => SlotValue::I64(rng.gen()),
+                ValueType::F32 if use_edge => SlotValue::F32(pick(
+                    &[
+                        0.0,
+                        -0.0,
+                        0.5,
+                        -1.5,
+                        f32::MIN,
+                        f32::MAX,
+                        f32::INFINITY,
+                        f32::NEG_INFINITY,
+                        f32::NAN,
+                    ],
+                    rng,
+                )),
+                ValueType::F32 => SlotValue::F32(rng.gen_range(-1.0e6..1.0e6)),
+                ValueType::F64 if use_edge => SlotValue::F64(pick(
+                    &[
+                        0.0,
+                        -0.0,
+                        0.5,
+                        -1.5,
+                        f64::MIN,
+                        f64::MAX,
+                        f64::INFINITY,
+                        f64::NEG_INFINITY,
+                        f64::NAN,
+                    ],
+                    rng,
+                )),
+                ValueType::F64 => SlotValue::F64(rng.gen_range(-1.0e12..1.0e12)),
+            }
+        })
+        .collect()
+}
+
+fn pick<V: Copy, R: Rng>(values: &[V], rng: &mut R) -> V {
+    values[rng.gen_range(0..values.len())]
+}
+
+// Wasm does not promise which NaN an operation produces, so any two NaN values are treated as the same result
+pub(crate) fn same_results(wasm: &[SlotValue], reference: &[SlotValue]) -> bool {
+    wasm.len() == reference.len()
+        && wasm.iter().zip(reference.iter()).all(|pair| match pair {
+            (SlotValue::F32(w), SlotValue::F32(r)) => w.to_bits() == r.to_bits() || (w.is_nan() && r.is_nan()),
+            (SlotValue::F64(w), SlotValue::F64(r)) => w.to_bits() == r.to_bits() || (w.is_nan() && r.is_nan()),
+            (w, r) => w == r,
+        })
+}
+
+// Running out of time, fuel or stack in Wasm, or out of steps in the reference evaluator, says nothing about whether
+// the paths agree
+pub(crate) fn is_inconclusive(error: &anyhow::Error) -> bool {
+    matches!(
+        error.downcast_ref::<Trap>(),
+        Some(Trap::Interrupt | Trap::OutOfFuel | Trap::StackOverflow)
+    ) || matches!(
+        error.downcast_ref::<WasmgpError>(),
+        Some(WasmgpError::StepLimitExceeded(_))
+    )
+}
+
+// Records the outcome of one run in the report
+pub(crate) fn compare(
+    report: &mut DifferentialReport,
+    code: &[Code],
+    params: Vec<SlotValue>,
+    wasm: Result<Vec<SlotValue>>,
+    reference: Result<Vec<SlotValue>>,
+) {
+    report.runs += 1;
+    match (&wasm, &reference) {
+        (Err(err), _) | (_, Err(err)) if is_inconclusive(err) => report.inconclusive += 1,
+        (Ok(w), Ok(r)) if same_results(w, r) => report.agreed += 1,
+        (Err(_), Err(_)) => report.agreed += 1,
+        _ => report.divergences.push(Divergence {
+            code: code.to_vec(),
+            params,
+            wasm: wasm.map_err(|err| format!("{:#}", err)),
+            reference: reference.map_err(|err| format!("{:#}", err)),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn wasm_and_reference_agree_on_random_code() {
+        let config = WorldConfiguration {
+            main_entry_point: FunctionSignature::new(
+                "main",
+                vec![ValueType::I32, ValueType::F32, ValueType::I64],
+                vec![ValueType::F64, ValueType::I32],
+            ),
+            work_slots: SlotCount {
+                i32: 2,
+                i64: 1,
+                f32: 1,
+                f64: 1,
+            },
+            work_slot_initialization: SlotInit::Random,
+            is_signed: false,
+            ..Default::default()
+        };
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+        let mut evaluator = world.reference_evaluator().unwrap();
+        evaluator.set_max_steps(10_000);
+
+        let options = DifferentialOptions {
+            programs: 50,
+            seed: Some(11),
+            ..Default::default()
+        };
+        let report = world.differential_test(&mut evaluator, &options).unwrap();
+        assert_eq!(200, report.runs);
+        if let Some(divergence) = report.divergences.first() {
+            panic!("{}", divergence);
+        }
+        assert!(report.agreed > 100);
+    }
+
+    #[test]
+    fn wasm_and_reference_agree_on_slots_randomized_per_call() {
+        let config = WorldConfiguration {
+            main_entry_point: FunctionSignature::new(
+                "main",
+                vec![ValueType::I32],
+                vec![ValueType::F32, ValueType::I32],
+            ),
+            work_slots: SlotCount {
+                i32: 2,
+                i64: 1,
+                f32: 1,
+                f64: 1,
+            },
+            work_slot_initialization: SlotInit::RandomPerCall,
+            ..Default::default()
+        };
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+        let mut evaluator = world.reference_evaluator().unwrap();
+        evaluator.set_max_steps(10_000);
+
+        let options = DifferentialOptions {
+            programs: 25,
+            seed: Some(12),
+            ..Default::default()
+        };
+        let report = world.differential_test(&mut evaluator, &options).unwrap();
+        if let Some(divergence) = report.divergences.first() {
+            panic!("{}", divergence);
+        }
+        assert!(report.agreed > 50);
+    }
+
+    #[test]
+    fn wasm_and_reference_agree_on_wrapping_conversions() {
+        let config = WorldConfiguration {
+            main_entry_point: FunctionSignature::new(
+                "main",
+                vec![ValueType::F32, ValueType::F64],
+                vec![ValueType::I32, ValueType::I64],
+            ),
+            work_slots: SlotCount {
+                i32: 1,
+                i64: 1,
+                f32: 1,
+                f64: 1,
+            },
+            conversion_profile: ConversionProfile::Wrapping,
+            ..Default::default()
+        };
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+        let mut evaluator = world.reference_evaluator().unwrap();
+        evaluator.set_max_steps(10_000);
+
+        let options = DifferentialOptions {
+            programs: 50,
+            seed: Some(13),
+            ..Default::default()
+        };
+        let report = world.differential_test(&mut evaluator, &options).unwrap();
+        if let Some(divergence) = report.divergences.first() {
+            panic!("{}", divergence);
+        }
+        assert!(report.agreed > 100);
+    }
+}
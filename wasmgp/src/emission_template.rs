@@ -0,0 +1,135 @@
+use crate::WasmgpError;
+use anyhow::Result;
+
+// The id of the code section, which holds the bodies of the functions defined by a module
+const CODE_SECTION_ID: u8 = 10;
+
+// The magic number and version at the start of every Wasm binary
+const HEADER_LENGTH: usize = 8;
+
+/// The binary of a World's module with the body of its main function cut out. Everything in the module except that
+/// body is the same for every individual: the types, the imports, the function and the export. The template is emitted
+/// once, and the binary of an individual is put together from it and the body of its own main function, so that the
+/// imports of the World are not encoded again for every child. See `WorldConfiguration::incremental_emission`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct EmissionTemplate {
+    prefix: Vec<u8>,
+    suffix: Vec<u8>,
+}
+
+impl EmissionTemplate {
+    /// Creates the template from the binary of a module that defines exactly one function
+    pub fn new(module: &[u8]) -> Result<EmissionTemplate> {
+        let (start, end) = only_code_section(module)?;
+        Ok(EmissionTemplate {
+            prefix: module[..start].to_vec(),
+            suffix: module[end..].to_vec(),
+        })
+    }
+
+    /// Returns the binary of the template's module with the function body taken from the binary of another module that
+    /// defines exactly one function. A body only calls the World's imports, by the indices the `CodeContext` gave them,
+    /// so it can be taken from a module that was built without the imports.
+    pub fn assemble(&self, module: &[u8]) -> Result<Vec<u8>> {
+        let (start, end) = only_code_section(module)?;
+        let code_section = &module[start..end];
+        let mut binary = Vec::with_capacity(self.prefix.len() + code_section.len() + self.suffix.len());
+        binary.extend_from_slice(&self.prefix);
+        binary.extend_from_slice(code_section);
+        binary.extend_from_slice(&self.suffix);
+        Ok(binary)
+    }
+}
+
+// Returns the range of the code section within the binary, from its id to the end of its contents. The section must
+// hold exactly one function body.
+fn only_code_section(module: &[u8]) -> Result<(usize, usize)> {
+    let mut offset = HEADER_LENGTH;
+    while offset < module.len() {
+        let start = offset;
+        let id = module[offset];
+        let (size, length) = read_u32(module, offset + 1)?;
+        offset += 1 + length;
+        let end = offset + size as usize;
+        if end > module.len() {
+            return Err(malformed("a section runs past the end of the binary"));
+        }
+        if id == CODE_SECTION_ID {
+            let (count, _) = read_u32(module, offset)?;
+            if count != 1 {
+                return Err(malformed("the code section must hold exactly one function body"));
+            }
+            return Ok((start, end));
+        }
+        offset = end;
+    }
+    Err(malformed("the binary has no code section"))
+}
+
+// Reads an unsigned LEB128 number, returning it along with the number of bytes it took
+fn read_u32(bytes: &[u8], offset: usize) -> Result<(u32, usize)> {
+    let mut value = 0u32;
+    for length in 0..5 {
+        let byte = *bytes
+            .get(offset + length)
+            .ok_or_else(|| malformed("a number runs past the end of the binary"))?;
+        value |= ((byte & 0x7f) as u32) << (7 * length);
+        if byte & 0x80 == 0 {
+            return Ok((value, length + 1));
+        }
+    }
+    Err(malformed("a number is longer than five bytes"))
+}
+
+fn malformed(reason: &str) -> anyhow::Error {
+    WasmgpError::InvalidConfiguration(format!(
+        "the module cannot be used for incremental emission: {}",
+        reason
+    ))
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_function_body_is_spliced_between_the_rest_of_the_module() {
+        // A header, a type section, a code section with one body of three bytes, and an export section
+        let header = [0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+        let types = [0x01, 0x04, 0x01, 0x60, 0x00, 0x00];
+        let exports = [0x07, 0x05, 0x01, 0x01, b'm', 0x00, 0x00];
+        let module_with_body = |body: &[u8]| {
+            let mut module = header.to_vec();
+            module.extend_from_slice(&types);
+            module.extend_from_slice(&[CODE_SECTION_ID, body.len() as u8 + 2, 0x01, body.len() as u8]);
+            module.extend_from_slice(body);
+            module.extend_from_slice(&exports);
+            module
+        };
+
+        let template = EmissionTemplate::new(&module_with_body(&[0x00, 0x01, 0x0b])).unwrap();
+        let longer = module_with_body(&[0x01, 0x01, 0x7f, 0x01, 0x0b]);
+        assert_eq!(longer, template.assemble(&longer).unwrap());
+
+        // A module from another builder only gives up its code section
+        let mut other = header.to_vec();
+        other.extend_from_slice(&[CODE_SECTION_ID, 0x05, 0x01, 0x03, 0x00, 0x01, 0x0b]);
+        assert_eq!(
+            module_with_body(&[0x00, 0x01, 0x0b]),
+            template.assemble(&other).unwrap()
+        );
+
+        assert!(EmissionTemplate::new(&header).is_err());
+        let mut two_bodies = header.to_vec();
+        two_bodies.extend_from_slice(&[CODE_SECTION_ID, 0x05, 0x02, 0x01, 0x0b, 0x01, 0x0b]);
+        assert!(EmissionTemplate::new(&two_bodies).is_err());
+    }
+
+    #[test]
+    fn numbers_are_read_as_leb128() {
+        assert_eq!((3, 1), read_u32(&[0x03], 0).unwrap());
+        assert_eq!((624485, 3), read_u32(&[0x00, 0xe5, 0x8e, 0x26], 1).unwrap());
+        assert!(read_u32(&[0x80, 0x80], 0).is_err());
+    }
+}
@@ -0,0 +1,145 @@
+use crate::{Individual, Island, RunResult, WasmgpError};
+use anyhow::Result;
+use wasmtime::{WasmParams, WasmResults};
+
+/// A group of champions that answer together. Every call runs each member with the same parameters and hands all of
+/// their outputs to a combining function, which decides the answer of the ensemble, such as `majority_vote` or
+/// `mean_output`. A team of diverse champions often makes fewer mistakes than its best member alone.
+///
+/// `World::ensemble_wasm_binary` builds the same ensemble as a single Wasm module, with the combining step written as
+/// Code.
+/// ```
+/// use wasmgp::*;
+///
+/// #[derive(Clone)]
+/// struct DoNothing;
+/// impl IslandCallbacks<(), EmptyRunResult> for DoNothing {
+///     fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+///         Box::new(Clone::clone(self))
+///     }
+///     fn run_individual(&mut self, _individual: &mut Individual<(), EmptyRunResult>) {}
+/// }
+///
+/// let config = WorldConfiguration {
+///     main_entry_point: FunctionSignature::new("main", vec![ValueType::I32, ValueType::I32], vec![ValueType::I32]),
+///     ..WorldConfiguration::default()
+/// };
+/// let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+/// let mut members = vec![];
+/// for code in [Add::new(0, 1, 2), Add::new(1, 0, 2), Subtract::new(0, 1, 2)] {
+///     members.push(world.evaluate(&[code], &mut DoNothing).unwrap().0);
+/// }
+///
+/// // Two of the three members add, so the ensemble adds
+/// let mut ensemble = Ensemble::new(members);
+/// let ((), sum) = ensemble.execute::<(i32, i32), i32, _>((), (5, 3), majority_vote);
+/// assert_eq!(8, sum.unwrap());
+/// ```
+pub struct Ensemble<T, R: RunResult> {
+    members: Vec<Individual<T, R>>,
+}
+
+impl<T, R: RunResult> Ensemble<T, R> {
+    /// Creates an ensemble of the individuals. The order of the members is the order of the outputs given to the
+    /// combining function.
+    pub fn new(members: Vec<Individual<T, R>>) -> Ensemble<T, R> {
+        Ensemble { members }
+    }
+
+    /// Creates an ensemble of clones of the `count` most fit individuals of the island, most fit first. The ensemble is
+    /// empty if the island has no sorted generation.
+    pub fn from_island(island: &Island<T, R>, count: usize) -> Ensemble<T, R> {
+        if island.most_fit_individual().is_none() {
+            return Ensemble::new(vec![]);
+        }
+        let members = (0..island.len())
+            .rev()
+            .take(count)
+            .filter_map(|index| island.get_one_individual(index).cloned())
+            .collect();
+        Ensemble::new(members)
+    }
+
+    /// Borrows the members, in the order their outputs are combined
+    pub fn members(&self) -> &[Individual<T, R>] {
+        &self.members[..]
+    }
+
+    /// Returns the number of members
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Returns true if the ensemble has no members
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Runs every member with `Individual::execute` and returns the result of `combine` on their outputs. The state is
+    /// passed from one member to the next in order, and passed back at the end.
+    ///
+    /// A member whose call fails is left out, so `combine` is given the outputs of the members that succeeded, in
+    /// order. If every member fails, the error of the last one is returned without calling `combine`.
+    pub fn execute<Params, Results, C>(&mut self, state: T, params: Params, combine: C) -> (T, Result<Results>)
+    where
+        Params: WasmParams + Clone,
+        Results: WasmResults,
+        C: FnOnce(Vec<Results>) -> Results,
+    {
+        let mut state = state;
+        let mut outputs = vec![];
+        let mut last_error = None;
+        for member in self.members.iter_mut() {
+            let (returned_state, result) = member.execute(state, params.clone());
+            state = returned_state;
+            match result {
+                Ok(output) => outputs.push(output),
+                Err(error) => last_error = Some(error),
+            }
+        }
+
+        if !outputs.is_empty() {
+            (state, Ok(combine(outputs)))
+        } else if let Some(error) = last_error {
+            (state, Err(error))
+        } else {
+            (state, Err(WasmgpError::EmptyEnsemble.into()))
+        }
+    }
+}
+
+/// Combines the outputs of an `Ensemble` by returning the one given most often. A tie goes to the output of the
+/// earliest member, which for an ensemble from `Ensemble::from_island` is the most fit. Panics if there are no outputs,
+/// which `Ensemble::execute` never passes.
+pub fn majority_vote<V: PartialEq>(outputs: Vec<V>) -> V {
+    let votes: Vec<usize> = outputs
+        .iter()
+        .map(|output| outputs.iter().filter(|other| *other == output).count())
+        .collect();
+    let most = votes
+        .iter()
+        .copied()
+        .max()
+        .expect("majority_vote needs at least one output");
+    let winner = votes.iter().position(|count| *count == most).unwrap();
+    outputs.into_iter().nth(winner).unwrap()
+}
+
+/// Combines the numeric outputs of an `Ensemble` by returning their mean. Panics if there are no outputs, which
+/// `Ensemble::execute` never passes.
+pub fn mean_output(outputs: Vec<f64>) -> f64 {
+    assert!(!outputs.is_empty(), "mean_output needs at least one output");
+    outputs.iter().sum::<f64>() / outputs.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn majority_vote_prefers_the_earliest_of_a_tie() {
+        assert_eq!(3, majority_vote(vec![1, 3, 3, 2]));
+        assert_eq!(2, majority_vote(vec![2, 1, 1, 2]));
+        assert_eq!(2.5, mean_output(vec![1.0, 4.0]));
+    }
+}
@@ -11,6 +11,9 @@ pub enum WasmgpError {
     #[error("Invalid slot: {0}")]
     InvalidSlot(Slot),
 
+    #[error("slot {0} out of range ({1} slots defined)")]
+    SlotOutOfRange(Slot, usize),
+
     #[error(
         "The total number of slots used across all parameters, return and locals must be 256 or fewer, but got {0}"
     )]
@@ -18,4 +21,77 @@ pub enum WasmgpError {
 
     #[error("Configuration is not valid ({0})")]
     InvalidConfiguration(String),
+
+    #[error("The run was cancelled")]
+    Cancelled,
+
+    #[error("Return must be the final Code or be discarded by a loop")]
+    MisplacedReturn,
+
+    #[error("The call to function {0} does not match its signature")]
+    CallSignatureMismatch(FunctionIndex),
+
+    #[error("The parameters do not match the function signature")]
+    ParameterMismatch,
+
+    #[error("Integer overflow")]
+    IntegerOverflow,
+
+    #[error("The evaluation did not finish within {0} steps")]
+    StepLimitExceeded(u64),
+
+    #[error("The call limit for '{0}' was exceeded")]
+    CallLimitExceeded(String),
+
+    #[error("The budget of {0} host calls was exceeded")]
+    HostCallBudgetExceeded(usize),
+
+    #[error("The ensemble has no members")]
+    EmptyEnsemble,
+
+    #[error("The code listing could not be read ({0})")]
+    InvalidCodeListing(String),
+
+    #[error("The run policy could not be read ({0})")]
+    InvalidRunPolicy(String),
+
+    #[error("The checkpoint could not be read ({0})")]
+    InvalidCheckpoint(String),
+
+    #[error("The code was saved with instruction set {0}, but this build has instruction set {1}")]
+    InstructionSetMismatch(String, String),
+
+    #[error("The code does not pass the test it is being reduced against")]
+    ReductionPredicateFailed,
+}
+
+/// Identifies the Code that could not be turned into Wasm. The path starts with the top-level Code and its index, and
+/// then names each nested code list and index, for example "DoUntil[3].body[1]: slot 17 out of range (12 slots
+/// defined)". The original error remains available as the source.
+#[derive(Error, Debug)]
+#[error("{}: {message}", path.join("."))]
+pub struct CodeDiagnostic {
+    pub path: Vec<String>,
+    pub message: String,
+    #[source]
+    source: anyhow::Error,
+}
+
+impl CodeDiagnostic {
+    // Adds a segment to the front of the path of an existing diagnostic, or creates the diagnostic for an error that
+    // was raised by the Code at this segment.
+    pub(crate) fn wrap(error: anyhow::Error, segment: String) -> anyhow::Error {
+        match error.downcast::<CodeDiagnostic>() {
+            Ok(mut diagnostic) => {
+                diagnostic.path.insert(0, segment);
+                diagnostic.into()
+            }
+            Err(error) => CodeDiagnostic {
+                path: vec![segment],
+                message: format!("{:#}", error),
+                source: error,
+            }
+            .into(),
+        }
+    }
 }
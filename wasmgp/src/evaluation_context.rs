@@ -0,0 +1,21 @@
+use crate::{GenerationStats, IslandId};
+
+/// What the callbacks of an island know about the generation that is about to run. The World passes it to
+/// `IslandCallbacks::use_evaluation_context` before `pre_generation_run`, so that a fitness function can change with the
+/// generation, such as a penalty that is annealed or noise that is drawn anew each generation.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EvaluationContext {
+    /// The generation that is about to run, counting from zero
+    pub generation: usize,
+
+    /// The island that is running the generation
+    pub island: IslandId,
+
+    /// A seed for any randomness in the fitness function. It comes from the World's seed and the generation, so every
+    /// island sees the same seed in a generation, and a World with the same seed sees the same seeds again.
+    pub seed: u64,
+
+    /// The stats of every island from the generation that last completed, indexed by island. Empty before the first
+    /// generation has completed.
+    pub stats: Vec<GenerationStats>,
+}
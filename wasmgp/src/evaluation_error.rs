@@ -0,0 +1,51 @@
+use wasmtime::Trap;
+
+/// Describes why the most recent evaluation of an individual failed. Failures are captured on the Individual so that an
+/// island can continue running the rest of the generation and decide later how to treat the failed individual.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EvaluationError {
+    /// The full error message, including any context added by wasmtime
+    pub message: String,
+
+    /// The trap raised by the code, if the failure was a trap. Running out of time is reported as `Trap::Interrupt`,
+    /// running out of fuel as `Trap::OutOfFuel`, and calling too deep as `Trap::StackOverflow`.
+    pub trap: Option<Trap>,
+
+    /// The number of times the code was instantiated, or tried to be. Only a failed instantiation is tried again.
+    pub attempts: usize,
+}
+
+/// The limits that can stop an evaluation. All can be active at once: the run time protects the host from code that
+/// runs too long, fuel stops the code after the same amount of work on every machine, and the call depth stops code
+/// that calls itself too deeply.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EvaluationLimit {
+    /// The code ran for longer than `WorldConfiguration::individual_run_time_ms`
+    RunTime,
+
+    /// The code used all of the `WorldConfiguration::fuel_per_evaluation`
+    Fuel,
+
+    /// The code called deeper than the `WorldConfiguration::max_wasm_stack`, or the stack wasmtime allows by default
+    CallDepth,
+}
+
+impl EvaluationError {
+    pub(crate) fn new(error: &anyhow::Error, attempts: usize) -> EvaluationError {
+        EvaluationError {
+            message: format!("{:#}", error),
+            trap: error.downcast_ref::<Trap>().copied(),
+            attempts,
+        }
+    }
+
+    /// Returns the limit on the evaluation that stopped the code, or None if the failure was not caused by a limit
+    pub fn exceeded_limit(&self) -> Option<EvaluationLimit> {
+        match self.trap {
+            Some(Trap::Interrupt) => Some(EvaluationLimit::RunTime),
+            Some(Trap::OutOfFuel) => Some(EvaluationLimit::Fuel),
+            Some(Trap::StackOverflow) => Some(EvaluationLimit::CallDepth),
+            _ => None,
+        }
+    }
+}
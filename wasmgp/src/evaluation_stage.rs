@@ -0,0 +1,29 @@
+/// One step of a staged evaluation. An island with evaluation stages runs every individual through the first stage,
+/// keeps only the most fit `survivors` for the next stage, and so on. Cheap screening stages (few fitness cases, a small
+/// time budget) can then weed out most of the population before the expensive full evaluation.
+///
+/// `IslandCallbacks::run_individual` is called once for each stage an individual reaches, and can find out which stage
+/// it is running with `Individual::get_evaluation_stage`. The time limit of the stage is already applied to the
+/// individual. After the last stage, the individuals are sorted by the stage they reached and then by the callbacks, so
+/// an individual that was screened out is always less fit than one that went further.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EvaluationStage {
+    /// Describes the stage in reports
+    pub name: String,
+
+    /// The number of the most fit individuals that go on to the next stage. Ignored for the last stage.
+    pub survivors: usize,
+
+    /// Replaces the `individual_run_time_ms` of the WorldConfiguration during this stage, or None to keep it
+    pub run_time_ms: Option<u64>,
+}
+
+impl EvaluationStage {
+    pub fn new(name: &str, survivors: usize, run_time_ms: Option<u64>) -> EvaluationStage {
+        EvaluationStage {
+            name: name.to_string(),
+            survivors,
+            run_time_ms,
+        }
+    }
+}
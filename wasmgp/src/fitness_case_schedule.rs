@@ -0,0 +1,12 @@
+/// Decides which of the `WorldConfiguration::fitness_cases` each generation is evaluated on. The World passes the
+/// chosen cases to every island through `IslandCallbacks::use_fitness_cases` before the generation runs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FitnessCaseSchedule {
+    /// Every generation is evaluated on every case
+    All,
+
+    /// Each generation is evaluated on a random subset of `size` cases. A new subset is drawn every `generations`
+    /// generations. Because fitness is only comparable between individuals that were evaluated on the same cases,
+    /// RunResults from an earlier subset should not be reused.
+    RandomSubset { size: usize, generations: usize },
+}
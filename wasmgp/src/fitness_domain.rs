@@ -0,0 +1,135 @@
+use crate::{FunctionSignature, IslandCallbacks, IslandId, RunResult, World, WorldConfiguration};
+use anyhow::Result;
+
+/// Bundles everything a problem needs from a World, so that the problem can be packaged as its own crate and attached
+/// with one call to `World::attach_domain`. A domain names the entry point that its programs implement, adds the host
+/// functions that its programs may call, and creates the islands that run and score them. The callbacks of each island
+/// create the state `T` for each run, just as they do when islands are created by hand.
+/// ```
+/// use wasmgp::*;
+/// use wasmtime::*;
+///
+/// fn count_up(mut caller: Caller<'_, u64>) -> u64 {
+///     *caller.data_mut() += 1;
+///     *caller.data()
+/// }
+///
+/// #[derive(Clone)]
+/// struct CountUpIsland;
+/// impl IslandCallbacks<u64, EmptyRunResult> for CountUpIsland {
+///     fn clone(&self) -> Box<dyn IslandCallbacks<u64, EmptyRunResult>> {
+///         Box::new(Clone::clone(self))
+///     }
+///     fn run_individual(&mut self, _individual: &mut Individual<u64, EmptyRunResult>) {}
+/// }
+///
+/// struct CountingDomain;
+/// impl FitnessDomain<u64, EmptyRunResult> for CountingDomain {
+///     fn name(&self) -> &str {
+///         "counting"
+///     }
+///     fn entry_point(&self) -> FunctionSignature {
+///         FunctionSignature::empty()
+///     }
+///     fn add_imports(&self, world: &mut World<u64, EmptyRunResult>) -> Result<(), Error> {
+///         world.add_function_import("count_up", count_up)?;
+///         Ok(())
+///     }
+///     fn create_islands(&self) -> Vec<Box<dyn IslandCallbacks<u64, EmptyRunResult>>> {
+///         vec![Box::new(CountUpIsland), Box::new(CountUpIsland)]
+///     }
+/// }
+///
+/// let mut world = World::<u64, EmptyRunResult>::new(CountingDomain.configure(WorldConfiguration::default())).unwrap();
+/// assert_eq!(vec![0, 1], world.attach_domain(CountingDomain).unwrap());
+/// ```
+pub trait FitnessDomain<T, R: RunResult> {
+    /// The name that the domain is registered under in a `DomainRegistry`
+    fn name(&self) -> &str;
+
+    /// The signature of the function that the programs of this domain implement. It must match the
+    /// `main_entry_point` of the World that the domain is attached to.
+    fn entry_point(&self) -> FunctionSignature;
+
+    /// Adds the host functions that the programs of this domain may call
+    fn add_imports(&self, world: &mut World<T, R>) -> Result<()>;
+
+    /// Creates the callbacks of each island that the domain runs on. Each island may score the same problem
+    /// differently.
+    fn create_islands(&self) -> Vec<Box<dyn IslandCallbacks<T, R>>>;
+
+    /// Returns the configuration with the settings that the domain requires, such as its entry point and work slots.
+    /// Call this before `World::new`. The default implementation only sets the entry point.
+    fn configure(&self, config: WorldConfiguration) -> WorldConfiguration {
+        WorldConfiguration {
+            main_entry_point: self.entry_point(),
+            ..config
+        }
+    }
+}
+
+/// A set of domains that a program can choose between by name, such as from a command line argument.
+/// ```
+/// use wasmgp::*;
+///
+/// # struct EmptyDomain(&'static str);
+/// # impl FitnessDomain<(), EmptyRunResult> for EmptyDomain {
+/// #     fn name(&self) -> &str { self.0 }
+/// #     fn entry_point(&self) -> FunctionSignature { FunctionSignature::empty() }
+/// #     fn add_imports(&self, _world: &mut World<(), EmptyRunResult>) -> Result<(), Error> { Ok(()) }
+/// #     fn create_islands(&self) -> Vec<Box<dyn IslandCallbacks<(), EmptyRunResult>>> { vec![] }
+/// # }
+/// let mut registry = DomainRegistry::<(), EmptyRunResult>::new();
+/// registry.register(Box::new(EmptyDomain("solitaire")));
+/// registry.register(Box::new(EmptyDomain("checkers")));
+/// assert_eq!(vec!["checkers", "solitaire"], registry.names());
+///
+/// let domain = registry.get("checkers").unwrap();
+/// let mut world = World::<(), EmptyRunResult>::new(domain.configure(WorldConfiguration::default())).unwrap();
+/// registry.attach("checkers", &mut world).unwrap();
+/// assert!(registry.attach("chess", &mut world).is_err());
+/// ```
+pub struct DomainRegistry<T, R: RunResult> {
+    domains: Vec<Box<dyn FitnessDomain<T, R>>>,
+}
+
+impl<T: Default, R: RunResult> DomainRegistry<T, R> {
+    pub fn new() -> DomainRegistry<T, R> {
+        DomainRegistry { domains: vec![] }
+    }
+
+    /// Adds a domain. A domain with the same name as one already registered replaces it.
+    pub fn register(&mut self, domain: Box<dyn FitnessDomain<T, R>>) {
+        self.domains.retain(|existing| existing.name() != domain.name());
+        self.domains.push(domain);
+    }
+
+    /// Returns the domain registered under the name
+    pub fn get(&self, name: &str) -> Option<&dyn FitnessDomain<T, R>> {
+        self.domains
+            .iter()
+            .find(|domain| domain.name() == name)
+            .map(|domain| domain.as_ref())
+    }
+
+    /// Returns the names of every registered domain in sorted order
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.domains.iter().map(|domain| domain.name()).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Attaches the domain registered under the name to the world, and returns the IDs of the islands it created
+    pub fn attach(&self, name: &str, world: &mut World<T, R>) -> Result<Vec<IslandId>> {
+        let domain = self
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("there is no fitness domain named '{}'", name))?;
+        world.attach_domain_ref(domain)
+    }
+}
+
+impl<T: Default, R: RunResult> Default for DomainRegistry<T, R> {
+    fn default() -> Self {
+        DomainRegistry::new()
+    }
+}
@@ -0,0 +1,190 @@
+use crate::{BufferCounts, Code, Individual, IslandId, OperatorStats, RunResult};
+use rand::seq::index::sample;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+/// The largest number of individuals that are compared pairwise to find the `mean_edit_distance`.
+pub const EDIT_DISTANCE_SAMPLE_SIZE: usize = 32;
+
+/// Statistics about the current generation of one island. The diversity measures are filled in once the generation has
+/// been run and sorted.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GenerationStats {
+    /// The number of individuals in this generation that arrived from another island
+    pub immigrants: usize,
+
+    /// The number of individuals from this generation that were selected to migrate to another island
+    pub emigrants: usize,
+
+    /// The difficulty level of the island's `Curriculum` that this generation was run at
+    pub difficulty_level: usize,
+
+    /// The number of distinct programs in this generation. Two individuals share a structure when their code is
+    /// identical, down to the slots and constants.
+    pub unique_structures: usize,
+
+    /// The mean edit distance between the instruction sequences of every pair in a random sample of at most
+    /// `EDIT_DISTANCE_SAMPLE_SIZE` individuals. Each insertion, deletion or substitution of an instruction counts as
+    /// one edit.
+    pub mean_edit_distance: f64,
+
+    /// The Shannon entropy, in bits, of how often each Code variant is used across the whole generation. Falls towards
+    /// zero as the population comes to rely on a few instructions.
+    pub opcode_entropy: f64,
+
+    /// The score from `IslandCallbacks::score_individual` of the most fit individual
+    pub best_score: u64,
+
+    /// The mean of the individuals' scores
+    pub mean_score: f64,
+
+    /// The population variance of the individuals' scores from `IslandCallbacks::score_individual`
+    pub fitness_variance: f64,
+
+    /// The mean score that this island's callbacks gave to the most fit `cross_evaluation_elites` of each island,
+    /// indexed by island. The entry for this island is the mean score of its own elites, without running them again.
+    /// The entry is None for an island without a sorted generation. Empty unless the WorldConfiguration turns on cross
+    /// evaluation.
+    pub cross_evaluation: Vec<Option<f64>>,
+
+    /// The number of children bred for this generation that could not be built into a Wasm module, and were replaced
+    /// by other children
+    pub failed_children: usize,
+
+    /// The error of the last child that could not be built, if any
+    pub last_child_error: Option<String>,
+
+    /// How many code stream buffers breeding the children of this generation allocated, and how many it reused from
+    /// earlier children. See `GeneticEngine::buffer_counts`.
+    pub breeding_buffers: BufferCounts,
+
+    /// For each operator that produced individuals of this generation, how many it produced and how many of them
+    /// outperformed their parents, in the order of `Operator`
+    pub operators: Vec<OperatorStats>,
+}
+
+/// The stats of one island for one generation, as kept in the World's `generation_history`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GenerationRecord {
+    pub island: IslandId,
+
+    /// The generation that had just completed when the stats were recorded
+    pub generation: usize,
+
+    pub stats: GenerationStats,
+}
+
+impl GenerationStats {
+    // Measures the diversity of a generation that has been run. The scores are in the same order as the individuals.
+    pub(crate) fn measure_diversity<T, R: RunResult>(&mut self, individuals: &[Individual<T, R>], scores: &[u64]) {
+        let sequences: Vec<Vec<&'static str>> = individuals.iter().map(|i| opcode_sequence(i.get_code())).collect();
+
+        self.unique_structures = individuals
+            .iter()
+            .map(|i| structural_hash(i.get_code()))
+            .collect::<HashSet<u64>>()
+            .len();
+
+        let mut rng = rand::thread_rng();
+        let sampled = sample(
+            &mut rng,
+            sequences.len(),
+            sequences.len().min(EDIT_DISTANCE_SAMPLE_SIZE),
+        )
+        .into_vec();
+        let mut total_distance = 0;
+        let mut pairs = 0;
+        for (n, a) in sampled.iter().enumerate() {
+            for b in sampled[n + 1..].iter() {
+                total_distance += edit_distance(&sequences[*a], &sequences[*b]);
+                pairs += 1;
+            }
+        }
+        self.mean_edit_distance = if pairs > 0 {
+            total_distance as f64 / pairs as f64
+        } else {
+            0.0
+        };
+
+        let mut counts: HashMap<&'static str, usize> = HashMap::new();
+        for name in sequences.iter().flatten() {
+            *counts.entry(name).or_default() += 1;
+        }
+        let total: usize = counts.values().sum();
+        self.opcode_entropy = counts
+            .values()
+            .map(|count| {
+                let p = *count as f64 / total as f64;
+                -p * p.log2()
+            })
+            .sum();
+
+        // The individuals have been sorted, so the most fit is last
+        self.best_score = scores.last().copied().unwrap_or_default();
+        self.mean_score = if scores.is_empty() {
+            0.0
+        } else {
+            scores.iter().map(|s| *s as f64).sum::<f64>() / scores.len() as f64
+        };
+        self.fitness_variance = if scores.is_empty() {
+            0.0
+        } else {
+            let mean = self.mean_score;
+            scores.iter().map(|s| (*s as f64 - mean).powi(2)).sum::<f64>() / scores.len() as f64
+        };
+    }
+}
+
+// Lists the name of every instruction in the code, with the contents of each block following the block itself
+fn opcode_sequence(code: &[Code]) -> Vec<&'static str> {
+    let mut names = vec![];
+    for c in code.iter() {
+        c.walk(&mut |c| names.push(c.name()));
+    }
+    names
+}
+
+pub(crate) fn structural_hash(code: &[Code]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    format!("{:?}", code).hash(&mut hasher);
+    hasher.finish()
+}
+
+// The number of single instruction edits that turn one program into the other
+pub(crate) fn structural_distance(a: &[Code], b: &[Code]) -> usize {
+    edit_distance(&opcode_sequence(a), &opcode_sequence(b))
+}
+
+// The Levenshtein distance between two instruction sequences
+fn edit_distance(a: &[&str], b: &[&str]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, x) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, y) in b.iter().enumerate() {
+            let substitution = previous[j] + if x == y { 0 } else { 1 };
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_distance_counts_single_instruction_edits() {
+        assert_eq!(0, edit_distance(&["Add", "Return"], &["Add", "Return"]));
+        assert_eq!(1, edit_distance(&["Add", "Return"], &["Subtract", "Return"]));
+        assert_eq!(2, edit_distance(&["Add"], &["Add", "If", "Return"]));
+        assert_eq!(3, edit_distance(&[], &["Add", "If", "Return"]));
+    }
+
+    #[test]
+    fn opcode_sequence_includes_nested_code() {
+        let code = vec![crate::ConstOne::new(0), crate::If::new(0, vec![crate::Return::new()])];
+        assert_eq!(vec!["ConstOne", "If", "Return"], opcode_sequence(&code));
+    }
+}
@@ -1,43 +1,173 @@
+use crate::breeding_buffers::BreedingBuffers;
 use crate::*;
 use anyhow::Result;
 use rand::rngs::SmallRng;
 use rand::{Rng, SeedableRng};
+use std::cell::Cell;
+use std::sync::Arc;
 use strum::IntoEnumIterator;
 use wasm_ast::FunctionIndex;
 
+// The number of children bred by a mutation or crossover before the last one is cut down to the `individual_max_cost`
+const COST_ATTEMPTS: usize = 10;
+
+/// Generates random code and breeds new code from existing code. A World uses its engine to fill its islands, but the
+/// engine can also be used on its own, to generate code for an evolutionary loop that lives outside of wasmgp. The code
+/// can be turned into Wasm with a `CodeContext`, or with `World::instanciate`.
+///
+/// Each Code variant is picked in proportion to its weight. The slots used by the code are between zero and the
+/// `slot_count` of the configuration: the parameters of the entry point, then its results, then the work slots.
+///
+/// An engine is used through `&mut self`, so it cannot be shared between threads. Instead, each thread can be given its
+/// own engine from `handle`, which shares the weight table but has a separate stream of random numbers.
+///
+/// Breeding turns the parents into code streams, and the engine keeps the buffers of those streams to reuse for the
+/// next child instead of allocating new ones. `buffer_counts` shows how well that works, and `clear_buffers` lets the
+/// memory go, such as at the end of a generation.
+/// ```
+/// use wasmgp::*;
+///
+/// let mut engine = GeneticEngine::with_weights(
+///     4,
+///     &[
+///         (Code::Add(Add::default()), 2),
+///         (Code::Multiply(Multiply::default()), 1),
+///         (Code::ConstOne(ConstOne::default()), 1),
+///     ],
+/// );
+/// let mother = engine.random_code_list(10);
+/// let father = engine.random_code_list(10);
+/// let child = engine.crossover(&mother[..], &father[..], 2).unwrap();
+/// let mutant = engine.mutate(&child[..], 1).unwrap();
+/// for code in mutant.iter() {
+///     assert!(matches!(code, Code::Add(_) | Code::Multiply(_) | Code::ConstOne(_)));
+/// }
+/// ```
 pub struct GeneticEngine {
     rng: SmallRng,
     config: GeneticEngineConfiguration,
-    weights: Vec<WeightEntry>,
-    sum_of_weights: Option<usize>,
+    // Shared with the engine's handles until one of them changes a weight
+    weights: Arc<Vec<WeightEntry>>,
+    archive: Arc<SubtreeArchive>,
+    buffers: BreedingBuffers,
 }
 
 impl GeneticEngine {
+    /// Creates an engine where every Code variant has a weight of one, except for `Code::Call`, which has no weight
+    /// until `set_host_call_weight` is called, and `Code::Provenance`, which only marks code that was supplied
     pub fn new(config: GeneticEngineConfiguration) -> GeneticEngine {
         let rng = small_rng_from_optional_seed(config.seed);
-        let mut engine = GeneticEngine {
-            rng: rng,
-            config,
-            weights: vec![],
-            sum_of_weights: None,
-        };
 
-        // Set the default weight of every instruction except for Call to be one. The Call instructions will be added
-        // when there is a host function to call.
+        // Set the default weight of every instruction except for Call and Provenance to be one. The Call instructions
+        // will be added when there is a host function to call.
+        let mut weights = vec![];
         let test_for_call = Code::Call(Call::default());
+        let test_for_provenance = Code::Provenance(Provenance::default());
         for code in Code::iter() {
-            if code != test_for_call {
-                engine.weights.push(WeightEntry {
+            if code != test_for_call && code != test_for_provenance {
+                weights.push(WeightEntry {
                     code,
                     weight: 1,
+                    level: 1.0,
                     combined_weight: 0,
                 });
             }
         }
+        update_combined_weights(&mut weights);
+
+        GeneticEngine {
+            rng: rng,
+            config,
+            weights: Arc::new(weights),
+            archive: Arc::new(SubtreeArchive::default()),
+            buffers: BreedingBuffers::default(),
+        }
+    }
+
+    /// Creates an engine with the same configuration, code weights and subtree archive, whose random number generator
+    /// is seeded from this one. The handle can be moved to another thread. The weight table is shared until either
+    /// engine changes a weight, after which each has its own copy. The handle starts without any buffers.
+    pub fn handle(&mut self) -> GeneticEngine {
+        GeneticEngine {
+            rng: SmallRng::from_rng(&mut self.rng).expect("SmallRng can always be seeded from another SmallRng"),
+            config: self.config.clone(),
+            weights: self.weights.clone(),
+            archive: self.archive.clone(),
+            buffers: BreedingBuffers::default(),
+        }
+    }
+
+    /// Returns how many code stream buffers breeding has allocated and reused since the counts were last taken
+    pub fn buffer_counts(&self) -> BufferCounts {
+        self.buffers.counts()
+    }
+
+    /// Returns the counts of `buffer_counts` and starts counting again from zero
+    pub fn take_buffer_counts(&mut self) -> BufferCounts {
+        self.buffers.take_counts()
+    }
+
+    /// Drops the buffers that are kept for the next child. The World calls this once every island has been filled, so
+    /// that the buffers of a generation of unusually long code are not held on to. The counts are kept.
+    pub fn clear_buffers(&mut self) {
+        self.buffers.clear();
+    }
+
+    // Returns a buffer that holds the stream of the code
+    fn stream_of(&mut self, code: &[Code]) -> Vec<CodeStream> {
+        let mut stream = self.buffers.take();
+        CodeStream::extend_stream(&mut stream, code);
+        stream
+    }
+
+    // Turns the stream back into code, and keeps its buffer for the next child
+    fn code_from(&mut self, mut stream: Vec<CodeStream>) -> Vec<Code> {
+        let code = CodeStream::from_stream(&mut stream.drain(..));
+        self.buffers.give_back(stream);
+        code
+    }
 
+    /// Replaces the configuration, such as after the slot count has changed. The code weights and the random number
+    /// generator are kept, so the seed of the new configuration is ignored.
+    pub fn reconfigure(&mut self, config: GeneticEngineConfiguration) {
+        self.config = config;
+    }
+
+    /// Creates an engine for code that uses `slot_count` slots, where only the listed Code variants have a weight. The
+    /// rest of the configuration is the default from `GeneticEngineConfiguration::new`, with a random seed. A
+    /// `Code::Call` in the list gives a weight to calls of its `function_index`, with as many parameters and results as
+    /// the call has.
+    pub fn with_weights(slot_count: Slot, weights: &[(Code, u8)]) -> GeneticEngine {
+        let mut engine = GeneticEngine::new(GeneticEngineConfiguration::new(None, slot_count));
+        engine.reset_all_code_weights(0);
+        for (code, weight) in weights.iter() {
+            match code {
+                Code::Call(call) => engine.set_host_call_weight(
+                    call.function_index(),
+                    call.params().len() as u8,
+                    call.results().len() as u8,
+                    *weight,
+                ),
+                _ => engine.set_code_weight(code.clone(), *weight),
+            }
+        }
         engine
     }
 
+    /// Captures the state of the random number generator as a single number that can be saved with a checkpoint. The
+    /// generator is reseeded from the returned state, so an engine that is later given the state through
+    /// `restore_rng_state` draws the same random numbers that this engine draws from here on.
+    pub fn capture_rng_state(&mut self) -> u64 {
+        let state = self.rng.gen();
+        self.rng = SmallRng::seed_from_u64(state);
+        state
+    }
+
+    /// Resumes the random numbers from a state returned by `capture_rng_state`
+    pub fn restore_rng_state(&mut self, state: u64) {
+        self.rng = SmallRng::seed_from_u64(state);
+    }
+
     /// Mutably borrows the random number generator
     pub fn rng(&mut self) -> &mut SmallRng {
         &mut self.rng
@@ -48,16 +178,30 @@ impl GeneticEngine {
         self.rng.gen_range(0..self.config.slot_count)
     }
 
-    /// Creates a random list of code up to the specified number of max_points
+    /// Returns a random number of slots, at least one, for a range that begins at `start` and ends within the slots
+    /// defined in the function
+    pub fn random_slot_count(&mut self, start: Slot) -> u8 {
+        self.rng
+            .gen_range(1..=self.config.slot_count.saturating_sub(start).max(1))
+    }
+
+    /// Creates a random list of code with between one and `max_points` points. Code with children, such as `DoUntil`,
+    /// counts its children toward its points.
     pub fn random_code_list(&mut self, max_points: usize) -> Vec<Code> {
+        let points = self.rng.gen_range(1..=max_points);
+        self.full_code_list(points)
+    }
+
+    /// Creates a random list of code with exactly `points` points. If the configuration has an `individual_max_cost`,
+    /// the items that do not fit within it are left out, so the list may have fewer points.
+    pub fn full_code_list(&mut self, mut points: usize) -> Vec<Code> {
         let mut code = vec![];
-        let mut points = self.rng.gen_range(1..=max_points);
         while points > 0 {
             let child = self.random_code(points);
             points -= child.points();
             code.push(child);
         }
-        code
+        self.fit_to_cost(code)
     }
 
     /// Creates a single random piece of code. `max_points` defines how many child Code elements items such as `IfElse`
@@ -76,24 +220,39 @@ impl GeneticEngine {
         weighted_code.make_random_code(self, max_points)
     }
 
-    /// Randomly selects either a crossover or mutation as the genetic operation to perform.
+    /// Randomly selects a crossover, a homologous crossover, a mutation, an archive mutation or an operand mutation as
+    /// the genetic operation to perform.
     pub fn select_genetic_operation(&mut self) -> GeneticOperation {
         let mutation_rate = self.config.mutation_rate as usize;
-        let total = self.config.crossover_rate as usize + mutation_rate;
+        let archive_mutation_rate = self.config.archive_mutation_rate as usize;
+        let operand_mutation_rate = self.config.operand_mutation_rate as usize;
+        let all_mutation_rates = mutation_rate + archive_mutation_rate + operand_mutation_rate;
+        let crossover_rate = self.config.crossover_rate as usize;
+        let total = all_mutation_rates + crossover_rate + self.config.homologous_crossover_rate as usize;
         let pick = self.rng.gen_range(0..total);
-        if pick < mutation_rate as usize {
-            if self.config.max_mutation_points == 1 {
-                GeneticOperation::Mutation(1)
+        if pick < all_mutation_rates {
+            let count = if self.config.max_mutation_points <= 1 {
+                1
             } else {
-                let count = self.rng.gen_range(1..self.config.max_mutation_points);
+                self.rng.gen_range(1..self.config.max_mutation_points)
+            };
+            if pick < mutation_rate {
                 GeneticOperation::Mutation(count)
+            } else if pick < mutation_rate + archive_mutation_rate {
+                GeneticOperation::ArchiveMutation(count)
+            } else {
+                GeneticOperation::OperandMutation(count)
             }
         } else {
-            if self.config.max_crossover_points == 1 {
-                GeneticOperation::Crossover(1)
+            let count = if self.config.max_crossover_points == 1 {
+                1
             } else {
-                let count = self.rng.gen_range(1..self.config.max_crossover_points);
+                self.rng.gen_range(1..self.config.max_crossover_points)
+            };
+            if pick < all_mutation_rates + crossover_rate {
                 GeneticOperation::Crossover(count)
+            } else {
+                GeneticOperation::HomologousCrossover(count)
             }
         }
     }
@@ -104,19 +263,158 @@ impl GeneticEngine {
     /// The defined_names of the child will only include the code that is specifically named in the child's code. If
     /// both parents have the same defined_name, the value for that will come from the left individual.
     pub fn rand_child(&mut self, left: &[Code], right: &[Code]) -> Result<Vec<Code>> {
-        match self.select_genetic_operation() {
+        let operation = self.select_genetic_operation();
+        self.apply_genetic_operation(&operation, left, right)
+    }
+
+    /// Produces a child of the two individuals with the operation. A mutation only uses the left individual.
+    pub fn apply_genetic_operation(
+        &mut self,
+        operation: &GeneticOperation,
+        left: &[Code],
+        right: &[Code],
+    ) -> Result<Vec<Code>> {
+        match *operation {
             GeneticOperation::Mutation(count) => self.mutate(left, count),
+            GeneticOperation::ArchiveMutation(count) => self.archive_mutate(left, count),
+            GeneticOperation::OperandMutation(count) => self.mutate_operands(left, count),
             GeneticOperation::Crossover(count) => self.crossover(left, right, count),
+            GeneticOperation::HomologousCrossover(count) => self.homologous_crossover(left, right, count),
         }
     }
 
+    /// Produces a random mutation of the parent, with up to `max_mutation_points` points mutated. This is the same as the
+    /// mutation chosen by `rand_child`, but is never a crossover.
+    pub fn rand_mutation(&mut self, parent: &[Code]) -> Result<Vec<Code>> {
+        let count = if self.config.max_mutation_points <= 1 {
+            1
+        } else {
+            self.rng.gen_range(1..self.config.max_mutation_points)
+        };
+        self.mutate(parent, count)
+    }
+
     /// Mutates the parent by randomly selecting a point in the code, generating a new random code item and replacing
-    /// the selected point with the new code.
-    pub fn mutate(&mut self, parent: &[Code], mut count: u8) -> Result<Vec<Code>> {
+    /// the selected point with the new code. This is repeated `count` times, or until the child reaches the
+    /// `individual_max_points` of the configuration. The parent must have at least one point.
+    ///
+    /// A child that costs more than the `individual_max_cost` of the configuration is mutated again from the parent, and
+    /// after several tries the last child is cut down to fit.
+    pub fn mutate(&mut self, parent: &[Code], count: u8) -> Result<Vec<Code>> {
+        self.within_cost(|engine| engine.mutate_once(parent, count, false))
+    }
+
+    /// Mutates the parent in the same way as `mutate`, except that each point is replaced by a subtree from the
+    /// `subtree_archive` that fits within the points left. A point is replaced by random code when no subtree fits.
+    pub fn archive_mutate(&mut self, parent: &[Code], count: u8) -> Result<Vec<Code>> {
+        self.within_cost(|engine| engine.mutate_once(parent, count, true))
+    }
+
+    /// Borrows the archive of subtrees that `archive_mutate` inserts
+    pub fn subtree_archive(&self) -> &SubtreeArchive {
+        &self.archive
+    }
+
+    /// Replaces the archive of subtrees that `archive_mutate` inserts. Handles created before the call keep the archive
+    /// they were created with.
+    pub fn set_subtree_archive(&mut self, archive: SubtreeArchive) {
+        self.archive = Arc::new(archive);
+    }
+
+    /// Mutates the parent by picking one operand of one code item and giving it a new random value, `count` times. The
+    /// instructions stay the same, and so does the shape of the code: an operand is a slot that the item reads or
+    /// writes, a constant, the number of times a `DoFor` loops, or the number of slots in a range. This is a much
+    /// smaller step than `mutate`, which replaces whole items, so it suits fine tuning a program that is nearly right.
+    ///
+    /// A new slot is always different from the old one when there is more than one slot. Code without any operands,
+    /// such as a lone `Return`, is returned unchanged.
+    pub fn mutate_operands(&mut self, parent: &[Code], count: u8) -> Result<Vec<Code>> {
+        self.within_cost(|engine| Ok(engine.mutate_operands_once(parent, count)))
+    }
+
+    fn mutate_operands_once(&mut self, parent: &[Code], count: u8) -> Vec<Code> {
+        // The stream holds blocks without their children, so each item only has its own operands
+        let mut stream = self.stream_of(parent);
+        for _ in 0..count {
+            let mut operands = vec![];
+            for (index, item) in stream.iter().enumerate() {
+                if let CodeStream::Simple(code) | CodeStream::Begin(code) = item {
+                    let (slots, constants) = count_operands(code);
+                    operands.extend((0..slots + constants).map(|operand| (index, operand)));
+                }
+            }
+            if operands.is_empty() {
+                break;
+            }
+
+            let (index, operand) = operands[self.rng.gen_range(0..operands.len())];
+            stream[index] = match &stream[index] {
+                CodeStream::Simple(code) => CodeStream::Simple(self.mutate_operand(code, operand)),
+                CodeStream::Begin(code) => CodeStream::Begin(self.mutate_operand(code, operand)),
+                CodeStream::End => unreachable!("an End has no operands"),
+            };
+        }
+        self.code_from(stream)
+    }
+
+    // Gives the operand a new random value. The slots come first, in the order that `remap_slots` visits them, followed
+    // by the constants.
+    fn mutate_operand(&mut self, code: &Code, operand: usize) -> Code {
+        let (slots, _) = count_operands(code);
+        if operand < slots {
+            let old = Cell::new(0);
+            let seen = Cell::new(0);
+            let mut probe = code.clone();
+            probe.remap_slots(&|slot| {
+                if seen.get() == operand {
+                    old.set(slot);
+                }
+                seen.set(seen.get() + 1);
+                slot
+            });
+            let mut new = self.random_slot();
+            while new == old.get() && self.config.slot_count > 1 {
+                new = self.random_slot();
+            }
+
+            let mut mutated = code.clone();
+            seen.set(0);
+            mutated.remap_slots(&|slot| {
+                let replace = seen.get() == operand;
+                seen.set(seen.get() + 1);
+                if replace {
+                    new
+                } else {
+                    slot
+                }
+            });
+            return mutated;
+        }
+
+        match code {
+            Code::ConstI32(c) => ConstI32::new(c.slot(), self.rng.gen()),
+            Code::ConstI64(c) => ConstI64::new(c.slot(), self.rng.gen()),
+            Code::ConstF32(c) => ConstF32::new(c.slot(), self.rng.gen()),
+            Code::ConstF64(c) => ConstF64::new(c.slot(), self.rng.gen()),
+            Code::DoFor(c) => DoFor::new(self.rng.gen(), c.do_this().to_vec()),
+            Code::FillSlots(c) if operand == slots => {
+                FillSlots::new(c.start(), self.random_slot_count(c.start()), c.value())
+            }
+            Code::FillSlots(c) => FillSlots::new(c.start(), c.count(), self.rng.gen()),
+            Code::SumSlots(c) => SumSlots::new(c.start(), self.random_slot_count(c.start()), c.destination()),
+            Code::CopySlots(c) => {
+                let start = c.source_start().max(c.destination_start());
+                CopySlots::new(c.source_start(), c.destination_start(), self.random_slot_count(start))
+            }
+            _ => unreachable!("the code has no constant operands"),
+        }
+    }
+
+    fn mutate_once(&mut self, parent: &[Code], mut count: u8, from_archive: bool) -> Result<Vec<Code>> {
         // Most code will replace a single item, but if we replace an item with an IfElse, we could be in for a lot of
         // code. Make sure it doesn't get larger than the allowed amount
         let parent_points: usize = parent.iter().map(|v| v.points()).sum();
-        let max_additional_points = self.config.individual_max_points - parent_points;
+        let max_additional_points = self.config.individual_max_points.saturating_sub(parent_points);
         let mut additional_points = if max_additional_points > 1 && self.config.individual_max_points > parent_points {
             self.rng.gen_range(1..max_additional_points)
         } else {
@@ -124,34 +422,26 @@ impl GeneticEngine {
         };
 
         // Turn the parent into a stream
-        let mut stream = CodeStream::to_stream(parent);
+        let mut stream = self.stream_of(parent);
 
         // Mutate a single point in the stream the specified number of times
         while count > 0 {
             count -= 1;
 
             let mutation_point = self.rng.gen_range(0..stream.len());
-            let replace_with_code = vec![self.random_code(additional_points)];
+            let archived = match from_archive {
+                true => self.archive.pick(&mut self.rng, additional_points),
+                false => None,
+            };
+            let replace_with_code = vec![archived.unwrap_or_else(|| self.random_code(additional_points))];
             let random_code_points = replace_with_code[0].points();
             assert!(random_code_points <= additional_points);
             assert!(random_code_points > 0);
 
-            // Turn the new code into a stream as well
-            let replace_stream = CodeStream::to_stream(&replace_with_code);
-
-            // Make a new stream with the new code in place of that one element
-            let mut next_stream = vec![];
-            if mutation_point > 0 {
-                next_stream.extend(&stream[0..mutation_point]);
-            }
-            next_stream.extend(&replace_stream[..]);
-            if mutation_point + 1 < stream.len() {
-                next_stream.extend(&stream[mutation_point + 1..]);
-            }
-
-            // We have a list of borrowed items, clone them to turn them into our real stream. We can't use `.cloned()`
-            // because the iterator items are `&&CodeStream`
-            stream = next_stream.iter().map(|&x| x.clone()).collect();
+            // Turn the new code into a stream as well, and put it in place of that one element
+            let mut replace_stream = self.stream_of(&replace_with_code);
+            stream.splice(mutation_point..mutation_point + 1, replace_stream.drain(..));
+            self.buffers.give_back(replace_stream);
 
             // If we got code larger than one point, we need to adjust the additional_points downward
             additional_points -= random_code_points - 1;
@@ -161,17 +451,25 @@ impl GeneticEngine {
         }
 
         // Turn the stream back into code
-        Ok(CodeStream::from_stream(&mut stream.into_iter()))
+        Ok(self.code_from(stream))
     }
 
     /// Produces a random child that is a crossover of both parents. `count` random points along the shortest of the
-    /// two code streams will be selected to swap the streams.
-    pub fn crossover(&mut self, left_parent: &[Code], right_parent: &[Code], mut count: u8) -> Result<Vec<Code>> {
+    /// two code streams will be selected to swap the streams. The child starts with the code of the left parent. The
+    /// size of the child is not limited by `individual_max_points`, and `count` must be at least one. The points are
+    /// limited to those that the `crossover_cuts` of the configuration allows in both parents.
+    ///
+    /// The cost of the child is limited by the `individual_max_cost` of the configuration in the same way as `mutate`.
+    pub fn crossover(&mut self, left_parent: &[Code], right_parent: &[Code], count: u8) -> Result<Vec<Code>> {
+        self.within_cost(|engine| engine.crossover_once(left_parent, right_parent, count))
+    }
+
+    fn crossover_once(&mut self, left_parent: &[Code], right_parent: &[Code], mut count: u8) -> Result<Vec<Code>> {
         assert!(count > 0);
 
         // Turn each parent into a stream
-        let mut left_stream = CodeStream::to_stream(left_parent);
-        let mut right_stream = CodeStream::to_stream(right_parent);
+        let mut left_stream = self.stream_of(left_parent);
+        let mut right_stream = self.stream_of(right_parent);
 
         // Determine the shortest stream
         let max_crossover_point = if left_stream.len() > right_stream.len() {
@@ -184,15 +482,36 @@ impl GeneticEngine {
         // than `count` points, but that's typically with very short streams and a lot of crossovers in a short space is
         // not typically desired behavior
         let mut crossover_points = vec![];
-        while count > 0 {
-            count -= 1;
-            crossover_points.push(self.rng.gen_range(0..=max_crossover_point));
+        if self.config.crossover_cuts == CrossoverCuts::Anywhere {
+            while count > 0 {
+                count -= 1;
+                crossover_points.push(self.rng.gen_range(0..=max_crossover_point));
+            }
+        } else {
+            // Point zero is allowed in most cases, but a custom predicate may leave no point where both parents agree
+            let right_points = self.config.crossover_cuts.allowed_points(&right_stream);
+            let allowed: Vec<usize> = self
+                .config
+                .crossover_cuts
+                .allowed_points(&left_stream)
+                .into_iter()
+                .filter(|point| *point <= max_crossover_point && right_points.binary_search(point).is_ok())
+                .collect();
+            if allowed.is_empty() {
+                self.buffers.give_back(left_stream);
+                self.buffers.give_back(right_stream);
+                return Ok(left_parent.to_vec());
+            }
+            while count > 0 {
+                count -= 1;
+                crossover_points.push(allowed[self.rng.gen_range(0..allowed.len())]);
+            }
         }
         crossover_points.sort();
         crossover_points.dedup();
 
         // Assemble the child stream as slices of left and right
-        let mut child_stream: Vec<CodeStream> = vec![];
+        let mut child_stream = self.buffers.take();
         let mut last_crossover = 0;
         for &crossover in crossover_points.iter() {
             // In the case of the first point being zero, we can have a situation of duplicate crossover points.
@@ -211,17 +530,138 @@ impl GeneticEngine {
         if left_stream.len() > last_crossover {
             child_stream.extend((&left_stream[last_crossover..]).iter().map(|x| x.clone()));
         }
+        self.buffers.give_back(left_stream);
+        self.buffers.give_back(right_stream);
 
         // Turn the stream back into code
-        Ok(CodeStream::from_stream(&mut child_stream.into_iter()))
+        Ok(self.code_from(child_stream))
     }
 
-    fn pick_random_weighted_code(&mut self) -> Code {
-        if self.sum_of_weights.is_none() {
-            self.update_sum_of_weights();
+    /// Produces a child by replacing `count` segments of the left parent with segments of the right parent that are
+    /// about the same size and start at about the same position. This is the size-fair crossover of linear GP: each
+    /// segment from the right parent has a length within half of the length of the segment it replaces, so on average
+    /// the child is the same size as the left parent, and code does not bloat from one generation to the next.
+    ///
+    /// The segments are runs of the code streams that hold only whole blocks, so a block is never torn in two. When the
+    /// right parent has no segment of a fair size, the segment closest in size is used. `count` must be at least one.
+    /// Only the segments of the left parent that begin and end at points the `crossover_cuts` of the configuration
+    /// allows are replaced.
+    ///
+    /// The cost of the child is limited by the `individual_max_cost` of the configuration in the same way as `mutate`.
+    pub fn homologous_crossover(
+        &mut self,
+        left_parent: &[Code],
+        right_parent: &[Code],
+        count: u8,
+    ) -> Result<Vec<Code>> {
+        self.within_cost(|engine| engine.homologous_crossover_once(left_parent, right_parent, count))
+    }
+
+    fn homologous_crossover_once(
+        &mut self,
+        left_parent: &[Code],
+        right_parent: &[Code],
+        count: u8,
+    ) -> Result<Vec<Code>> {
+        assert!(count > 0);
+        let mut child_stream = self.stream_of(left_parent);
+        let right_stream = self.stream_of(right_parent);
+        let right_segments = whole_segments(&right_stream);
+
+        for _ in 0..count {
+            // Pick a segment of the child. Each segment is equally likely, so short and long ones are both replaced.
+            let mut child_segments = whole_segments(&child_stream);
+            if self.config.crossover_cuts != CrossoverCuts::Anywhere {
+                let allowed = self.config.crossover_cuts.allowed_points(&child_stream);
+                let is_allowed = |point: usize| allowed.binary_search(&point).is_ok();
+                child_segments.retain(|(start, len)| is_allowed(*start) && is_allowed(start + len));
+            }
+            if child_segments.is_empty() || right_segments.is_empty() {
+                break;
+            }
+            let (start, len) = child_segments[self.rng.gen_range(0..child_segments.len())];
+
+            // The right segment closest in position among those of a fair size. When there is none, the closest in
+            // size, and then in position.
+            let max_difference = len / 2;
+            let distance = |&(right_start, right_len): &(usize, usize)| {
+                let size = right_len.abs_diff(len);
+                let position = right_start.abs_diff(start);
+                if size <= max_difference {
+                    (0, position)
+                } else {
+                    (size, position)
+                }
+            };
+            let best = right_segments.iter().map(distance).min().unwrap();
+            let closest: Vec<&(usize, usize)> = right_segments.iter().filter(|s| distance(s) == best).collect();
+            let (right_start, right_len) = *closest[self.rng.gen_range(0..closest.len())];
+
+            child_stream.splice(
+                start..start + len,
+                right_stream[right_start..right_start + right_len].iter().cloned(),
+            );
         }
+        self.buffers.give_back(right_stream);
 
-        let pick = self.rng.gen_range(1..=self.sum_of_weights.unwrap());
+        // Turn the stream back into code
+        Ok(self.code_from(child_stream))
+    }
+
+    /// Returns the cost of the code under the `cost_model` of the configuration
+    pub fn code_cost(&self, code: &[Code]) -> u64 {
+        self.config.cost_model.code_cost(code)
+    }
+
+    // Breeds a child with `operation` until one is within the `individual_max_cost`. After `COST_ATTEMPTS` children,
+    // the last one is cut down to fit.
+    fn within_cost<F>(&mut self, mut operation: F) -> Result<Vec<Code>>
+    where
+        F: FnMut(&mut GeneticEngine) -> Result<Vec<Code>>,
+    {
+        let max_cost = match self.config.individual_max_cost {
+            Some(max_cost) => max_cost,
+            None => return operation(self),
+        };
+        let mut child = operation(self)?;
+        for _ in 1..COST_ATTEMPTS {
+            if self.code_cost(&child) <= max_cost {
+                return Ok(child);
+            }
+            child = operation(self)?;
+        }
+        Ok(self.fit_to_cost(child))
+    }
+
+    // Keeps the top-level items of the code that fit within the `individual_max_cost`, in order. If none of them fit,
+    // the code becomes a single random item with nothing nested inside of it.
+    fn fit_to_cost(&mut self, code: Vec<Code>) -> Vec<Code> {
+        let max_cost = match self.config.individual_max_cost {
+            Some(max_cost) => max_cost,
+            None => return code,
+        };
+        if self.code_cost(&code) <= max_cost {
+            return code;
+        }
+
+        let mut remaining = max_cost;
+        let mut fitted = vec![];
+        for item in code {
+            let cost = self.config.cost_model.cost(&item);
+            if cost <= remaining {
+                remaining -= cost;
+                fitted.push(item);
+            }
+        }
+        if fitted.is_empty() {
+            fitted.push(self.random_code(1));
+        }
+        fitted
+    }
+
+    fn pick_random_weighted_code(&mut self) -> Code {
+        let sum_of_weights = self.weights.last().map_or(0, |entry| entry.combined_weight);
+        let pick = self.rng.gen_range(1..=sum_of_weights);
         let index = self.weights.partition_point(|entry| entry.combined_weight < pick);
         let entry = self.weights.get(index).unwrap();
         entry.code.clone()
@@ -252,45 +692,113 @@ impl GeneticEngine {
 
     /// Sets the weight of every Code variant to the specified value (reset with a default)
     pub fn reset_all_code_weights(&mut self, weight: u8) {
-        for entry in self.weights.iter_mut() {
+        let weights = Arc::make_mut(&mut self.weights);
+        for entry in weights.iter_mut() {
             entry.weight = weight;
+            entry.level = weight as f64;
         }
-        self.sum_of_weights = None;
+        update_combined_weights(weights);
+    }
+
+    /// Returns the weight of the specified Code variant. For a `Code::Call`, the weight is for the function index of the
+    /// call. Returns zero for a host function that has no weight set.
+    pub fn get_code_weight(&self, code: &Code) -> u8 {
+        let code = match code {
+            Code::Call(call) => self.weight_key_for_call(call.function_index()),
+            _ => Some(code.get_default()),
+        };
+        code.and_then(|code| self.weights.iter().find(|entry| entry.code == code))
+            .map_or(0, |entry| entry.weight)
+    }
+
+    /// Moves the weight of every Code variant that has a weight above zero a `rate` of the way toward its `share` of
+    /// the total weight, never going below `floor`. The `share` is between 0.0 and 1.0 and is called with the default
+    /// of each variant, or with a `Code::Call` whose `function_index` identifies the host function.
+    pub fn adapt_code_weights<F>(&mut self, share: F, rate: f64, floor: u8)
+    where
+        F: Fn(&Code) -> f64,
+    {
+        let total: f64 = self
+            .weights
+            .iter()
+            .filter(|entry| entry.weight > 0)
+            .map(|entry| entry.level)
+            .sum();
+        let weights = Arc::make_mut(&mut self.weights);
+        for entry in weights.iter_mut().filter(|entry| entry.weight > 0) {
+            let target = f64::max(floor as f64, share(&entry.code) * total);
+            entry.level = (entry.level + rate * (target - entry.level)).clamp(floor as f64, u8::MAX as f64);
+            entry.weight = entry.level.round() as u8;
+        }
+        update_combined_weights(weights);
+    }
+
+    // The exact weight of every entry of the weight table, in the order of the table, for a checkpoint
+    pub(crate) fn code_weight_levels(&self) -> Vec<f64> {
+        self.weights.iter().map(|entry| entry.level).collect()
+    }
+
+    // Restores the exact weights returned by `code_weight_levels`, which must have one for every entry of the table
+    pub(crate) fn set_code_weight_levels(&mut self, levels: &[f64]) {
+        let weights = Arc::make_mut(&mut self.weights);
+        for (entry, level) in weights.iter_mut().zip(levels.iter()) {
+            entry.level = *level;
+            entry.weight = level.round() as u8;
+        }
+        update_combined_weights(weights);
+    }
+
+    // The calls to a host function are weighted by an entry that has the function's parameter and result counts in
+    // place of slots
+    fn weight_key_for_call(&self, function_index: FunctionIndex) -> Option<Code> {
+        self.weights
+            .iter()
+            .map(|entry| &entry.code)
+            .find_map(|code| match code {
+                Code::Call(call) if call.function_index() == function_index => Some(code.clone()),
+                _ => None,
+            })
     }
 
     fn internal_set_code_weight(&mut self, code: Code, weight: u8) {
         // Update the existing entry for the weight or add a new entry. Setting weights should happen infrequently
         // enough that a list scan shouldn't impact performance.
-        let existing_index = self.weights.iter().position(|entry| entry.code == code);
+        let weights = Arc::make_mut(&mut self.weights);
+        let existing_index = weights.iter().position(|entry| entry.code == code);
         if let Some(index) = existing_index {
-            self.weights[index].weight = weight;
+            weights[index].weight = weight;
+            weights[index].level = weight as f64;
         } else {
-            self.weights.push(WeightEntry {
+            weights.push(WeightEntry {
                 code,
                 weight,
+                level: weight as f64,
                 combined_weight: 0,
             });
         }
 
-        // The combined weight of all items is now probably wrong and needs to be recalculated
-        self.sum_of_weights = None
+        // The combined weight of all items is now wrong and needs to be recalculated
+        update_combined_weights(weights);
     }
+}
 
-    fn update_sum_of_weights(&mut self) {
-        // Set the combined_weight field to the sum of all entries up to and including this one. The `partition_point`
-        // function will then be able to find the correct entry with a minimum number of lookups.
-        let mut sum = 0;
-        for entry in self.weights.iter_mut() {
-            sum += entry.weight as usize;
-            entry.combined_weight = sum;
-        }
-        self.sum_of_weights = Some(sum);
+// Sets the combined_weight field to the sum of all entries up to and including this one. The `partition_point` function
+// will then be able to find the correct entry with a minimum number of lookups, and the last entry holds the sum of all
+// weights.
+fn update_combined_weights(weights: &mut [WeightEntry]) {
+    let mut sum = 0;
+    for entry in weights.iter_mut() {
+        sum += entry.weight as usize;
+        entry.combined_weight = sum;
     }
 }
 
+#[derive(Clone)]
 struct WeightEntry {
     code: Code,
     weight: u8,
+    // The exact weight, which adaptation moves in steps too small to change the rounded `weight` right away
+    level: f64,
     combined_weight: usize,
 }
 
@@ -302,6 +810,54 @@ fn small_rng_from_optional_seed(rng_seed: Option<u64>) -> SmallRng {
     }
 }
 
+// Returns the number of slots and the number of constants that are operands of the code item itself, not counting the
+// code nested inside of it
+fn count_operands(code: &Code) -> (usize, usize) {
+    let slots = Cell::new(0);
+    let mut probe = match code {
+        Code::If(c) => If::new(c.if_not_zero(), vec![]),
+        Code::IfElse(c) => IfElse::new(c.if_not_zero(), vec![], vec![]),
+        Code::DoUntil(c) => DoUntil::new(c.until_not_zero(), vec![]),
+        Code::DoWhile(c) => DoWhile::new(c.while_not_zero(), vec![]),
+        Code::DoFor(c) => DoFor::new(c.times(), vec![]),
+        Code::Provenance(c) => Provenance::new(c.tag(), vec![]),
+        c => c.clone(),
+    };
+    probe.remap_slots(&|slot| {
+        slots.set(slots.get() + 1);
+        slot
+    });
+    let constants = match code {
+        Code::ConstI32(_) | Code::ConstI64(_) | Code::ConstF32(_) | Code::ConstF64(_) => 1,
+        Code::DoFor(_) | Code::SumSlots(_) | Code::CopySlots(_) => 1,
+        Code::FillSlots(_) => 2,
+        _ => 0,
+    };
+    (slots.get(), constants)
+}
+
+// Returns the start and length of every run of the stream that holds only whole blocks: each block that begins in the
+// run also ends in it, and the run does not end a block that began before it
+fn whole_segments(stream: &[CodeStream]) -> Vec<(usize, usize)> {
+    let mut segments = vec![];
+    for start in 0..stream.len() {
+        let mut depth = 0usize;
+        for (offset, item) in stream[start..].iter().enumerate() {
+            match item {
+                CodeStream::Simple(_) => {}
+                CodeStream::Begin(Code::IfElse(_)) => depth += 2,
+                CodeStream::Begin(_) => depth += 1,
+                CodeStream::End if depth == 0 => break,
+                CodeStream::End => depth -= 1,
+            }
+            if depth == 0 {
+                segments.push((start, offset + 1));
+            }
+        }
+    }
+    segments
+}
+
 #[cfg(test)]
 mod tests {
     use crate::*;
@@ -333,6 +889,85 @@ mod tests {
         assert_eq!(4, entries.partition_point(|&x| x < 10));
     }
 
+    #[test]
+    fn with_weights_only_picks_the_listed_code() {
+        let mut engine = GeneticEngine::with_weights(
+            3,
+            &[(Code::Add(Add::default()), 1), (Call::new(7, vec![0, 0], vec![0]), 1)],
+        );
+        for _ in 0..50 {
+            match engine.random_code(1) {
+                Code::Add(add) => assert!(add.destination() < 3),
+                Code::Call(call) => {
+                    assert_eq!(7, call.function_index());
+                    assert_eq!(2, call.params().len());
+                    assert_eq!(1, call.results().len());
+                }
+                code => panic!("{} has no weight", code.name()),
+            }
+        }
+    }
+
+    #[test]
+    fn full_code_lists_have_exactly_the_points() {
+        let mut engine = GeneticEngine::new(GeneticEngineConfiguration::new(Some(1), 10));
+        for points in 1..30 {
+            let code = engine.full_code_list(points);
+            assert_eq!(points, code.iter().map(|c| c.points()).sum::<usize>());
+        }
+    }
+
+    #[test]
+    fn bred_code_stays_within_the_max_cost() {
+        // Without a limit, the loops of random code soon cost far more than the points suggest
+        let mut engine = GeneticEngine::new(GeneticEngineConfiguration::new(Some(1), 10));
+        assert!((0..50).any(|_| {
+            let code = engine.random_code_list(20);
+            engine.code_cost(&code) > 30
+        }));
+
+        let mut config = GeneticEngineConfiguration::new(Some(1), 10);
+        config.individual_max_cost = Some(30);
+        let mut engine = GeneticEngine::new(config);
+        let parents: Vec<Vec<Code>> = (0..50).map(|_| engine.random_code_list(20)).collect();
+        for pair in parents.windows(2) {
+            assert!(engine.code_cost(&pair[0]) <= 30);
+            let child = engine.crossover(&pair[0], &pair[1], 2).unwrap();
+            assert!(engine.code_cost(&child) <= 30);
+            let mutant = engine.mutate(&pair[0], 3).unwrap();
+            assert!(!mutant.is_empty());
+            assert!(engine.code_cost(&mutant) <= 30);
+        }
+    }
+
+    #[test]
+    fn restored_rng_state_draws_the_same_code() {
+        let mut engine = GeneticEngine::new(GeneticEngineConfiguration::new(Some(1), 10));
+        let state = engine.capture_rng_state();
+        let expected: Vec<Vec<Code>> = (0..10).map(|_| engine.random_code_list(20)).collect();
+
+        let mut resumed = GeneticEngine::new(GeneticEngineConfiguration::new(Some(2), 10));
+        resumed.restore_rng_state(state);
+        let code: Vec<Vec<Code>> = (0..10).map(|_| resumed.random_code_list(20)).collect();
+        assert_eq!(expected, code);
+    }
+
+    #[test]
+    fn handles_share_the_weights_but_not_the_random_numbers() {
+        let mut engine = GeneticEngine::with_weights(3, &[(Code::Add(Add::default()), 1)]);
+        let mut first = engine.handle();
+        let mut second = engine.handle();
+        assert!(first.random_code_list(20).iter().all(|code| code.name() == "Add"));
+        assert_ne!(first.random_code_list(20), second.random_code_list(20));
+
+        // Changing a weight on one handle leaves the others alone
+        first.reset_all_code_weights(0);
+        first.set_code_weight(Code::Subtract(Subtract::default()), 1);
+        assert_eq!(0, first.get_code_weight(&Code::Add(Add::default())));
+        assert_eq!(1, second.get_code_weight(&Code::Add(Add::default())));
+        assert_eq!(1, engine.get_code_weight(&Code::Add(Add::default())));
+    }
+
     #[test]
     fn test_select_genetic_operation() {
         let mut config = GeneticEngineConfiguration::new(Some(1), 10);
@@ -350,6 +985,29 @@ mod tests {
     }
 
     #[test]
+    fn archive_mutations_insert_archived_subtrees() {
+        let mut engine = GeneticEngine::with_weights(4, &[(Code::ConstOne(ConstOne::default()), 1)]);
+        let archived = If::new(1, vec![Add::new(0, 1, 2)]);
+        let mut archive = SubtreeArchive::new(4, 2);
+        archive.add_subtrees(&[archived.clone()]);
+        engine.set_subtree_archive(archive);
+
+        let parent = vec![ConstOne::new(0), ConstOne::new(1), ConstOne::new(2)];
+        let mut inserted = 0;
+        for _ in 0..20 {
+            let child = engine.archive_mutate(&parent, 1).unwrap();
+            inserted += child.iter().filter(|c| **c == archived).count();
+        }
+        assert!(inserted > 0);
+
+        // Without an archive, the points are replaced with random code
+        engine.set_subtree_archive(SubtreeArchive::default());
+        let child = engine.archive_mutate(&parent, 1).unwrap();
+        assert!(child.iter().all(|c| matches!(c, Code::ConstOne(_))));
+    }
+
+    #[test]
+    #[cfg(all(feature = "bit_ops", feature = "float_ops"))]
     fn test_mutation() {
         let config = GeneticEngineConfiguration::new(Some(1), 10);
         let mut engine = GeneticEngine::new(config);
@@ -460,4 +1118,180 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn operands_are_counted_without_the_nested_code() {
+        assert_eq!((3, 0), count_operands(&Add::new(0, 1, 2)));
+        assert_eq!((1, 0), count_operands(&If::new(0, vec![Add::new(0, 1, 2)])));
+        assert_eq!((0, 1), count_operands(&DoFor::new(3, vec![Add::new(0, 1, 2)])));
+        assert_eq!((1, 2), count_operands(&FillSlots::new(0, 2, 7)));
+        assert_eq!((3, 0), count_operands(&Call::new(0, vec![1, 2], vec![3])));
+        assert_eq!((0, 0), count_operands(&Return::new()));
+    }
+
+    #[test]
+    fn operand_mutations_keep_the_instructions() {
+        let mut engine = GeneticEngine::new(GeneticEngineConfiguration::new(Some(1), 10));
+        let parent = vec![
+            ConstI32::new(0, 5),
+            DoFor::new(3, vec![Add::new(0, 1, 2), IsLessThan::new(2, 3, 4)]),
+            Return::new(),
+        ];
+        let flatten = |code: &[Code]| {
+            let mut items = vec![];
+            for c in code.iter() {
+                c.walk(&mut |c| items.push(c.clone()));
+            }
+            items
+        };
+        for _ in 0..50 {
+            let child = engine.mutate_operands(&parent[..], 1).unwrap();
+            let (before, after) = (flatten(&parent), flatten(&child));
+            assert_eq!(before.len(), after.len());
+            for (b, a) in before.iter().zip(after.iter()) {
+                assert_eq!(std::mem::discriminant(b), std::mem::discriminant(a));
+            }
+
+            // Only one operand changed, so only one of the items that are not blocks is different
+            let changed = before
+                .iter()
+                .zip(after.iter())
+                .filter(|(b, a)| b != a && !matches!(b, Code::DoFor(_)))
+                .count();
+            assert!(changed <= 1);
+        }
+        assert_eq!(
+            vec![Return::new()],
+            engine.mutate_operands(&[Return::new()], 3).unwrap()
+        );
+    }
+
+    #[test]
+    fn segments_hold_whole_blocks() {
+        let stream = CodeStream::to_stream(&[ConstOne::new(0), IfElse::new(1, vec![ConstOne::new(2)], vec![])]);
+        assert_eq!(vec![(0, 1), (0, 5), (1, 4), (2, 1)], whole_segments(&stream));
+    }
+
+    #[test]
+    fn crossovers_only_cut_where_the_configuration_allows() {
+        let mut config = GeneticEngineConfiguration::new(Some(5), 4);
+        config.crossover_cuts = CrossoverCuts::OutsideLoops;
+        let mut engine = GeneticEngine::new(config);
+        let left_loop = DoFor::new(3, vec![ConstOne::new(0), ConstOne::new(1), ConstOne::new(2)]);
+        let right_loop = DoFor::new(4, vec![ConstZero::new(0), ConstZero::new(1)]);
+        let left = [ConstOne::new(3), left_loop.clone(), ConstOne::new(3), ConstOne::new(3)];
+        let right = [
+            ConstZero::new(3),
+            ConstZero::new(3),
+            right_loop.clone(),
+            ConstZero::new(3),
+        ];
+        for _ in 0..100 {
+            for child in [
+                engine.crossover(&left, &right, 3).unwrap(),
+                engine.homologous_crossover(&left, &right, 2).unwrap(),
+            ] {
+                for code in child.iter() {
+                    if let Code::DoFor(_) = code {
+                        assert!(*code == left_loop || *code == right_loop);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn breeding_reuses_the_stream_buffers() {
+        let mut engine = GeneticEngine::new(GeneticEngineConfiguration::new(Some(1), 4));
+        let left = engine.full_code_list(20);
+        let right = engine.full_code_list(20);
+
+        // A crossover holds both parents and the child at once
+        engine.crossover(&left[..], &right[..], 2).unwrap();
+        assert_eq!(
+            BufferCounts {
+                allocated: 3,
+                reused: 0
+            },
+            engine.take_buffer_counts()
+        );
+
+        // A mutation holds the parent and the replacement
+        for _ in 0..10 {
+            engine.crossover(&left[..], &right[..], 2).unwrap();
+            engine.mutate(&left[..], 1).unwrap();
+        }
+        assert_eq!(
+            BufferCounts {
+                allocated: 0,
+                reused: 50
+            },
+            engine.buffer_counts()
+        );
+
+        engine.clear_buffers();
+        engine.mutate(&left[..], 1).unwrap();
+        assert_eq!(2, engine.take_buffer_counts().allocated);
+    }
+
+    #[test]
+    fn homologous_crossover_keeps_children_near_the_size_of_the_left_parent() {
+        let mut engine = GeneticEngine::new(GeneticEngineConfiguration::new(Some(1), 10));
+        let left: Vec<Code> = (0..10).map(ConstOne::new).collect();
+        let right: Vec<Code> = (0..10).map(ConstZero::new).collect();
+        for _ in 0..20 {
+            let child = engine.homologous_crossover(&left[..], &right[..], 1).unwrap();
+            assert!(child.len() >= 5 && child.len() <= 15);
+
+            // The replaced segment comes from the same place in the right parent
+            let from_right: Vec<usize> = (0..child.len())
+                .filter(|i| matches!(child[*i], Code::ConstZero(_)))
+                .collect();
+            if let Some(first) = from_right.first() {
+                assert_eq!(ConstZero::new(*first as Slot), child[*first]);
+            }
+        }
+
+        // Blocks are swapped whole, so the If keeps its body
+        let block = vec![If::new(0, vec![ConstOne::new(1), ConstOne::new(2)]), ConstOne::new(3)];
+        let simple = vec![ConstZero::new(4), ConstZero::new(5), ConstZero::new(6)];
+        for _ in 0..20 {
+            let child = engine.homologous_crossover(&simple[..], &block[..], 2).unwrap();
+            for code in child.iter() {
+                if let Code::If(c) = code {
+                    assert_eq!(2, c.do_this().len());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn code_weights_adapt_toward_their_share_above_a_floor() {
+        let mut engine = GeneticEngine::new(GeneticEngineConfiguration::new(Some(1), 10));
+        engine.reset_all_code_weights(0);
+        engine.set_code_weight(Code::Add(Add::default()), 10);
+        engine.set_code_weight(Code::Subtract(Subtract::default()), 10);
+        engine.set_host_call_weight(0, 1, 1, 10);
+        engine.set_host_call_weight(1, 0, 0, 10);
+
+        // Add is all of the elite code, with calls to function one in second place
+        let share = |code: &Code| match code {
+            Code::Add(_) => 0.75,
+            Code::Call(call) if call.function_index() == 1 => 0.25,
+            _ => 0.0,
+        };
+        engine.adapt_code_weights(share, 0.5, 2);
+        assert_eq!(20, engine.get_code_weight(&Code::Add(Add::default())));
+        assert_eq!(6, engine.get_code_weight(&Code::Subtract(Subtract::default())));
+        assert_eq!(6, engine.get_code_weight(&Call::new(0, vec![3], vec![])));
+        assert_eq!(10, engine.get_code_weight(&Call::new(1, vec![], vec![])));
+
+        // Repeated adaptation settles at the floor, but variants that were turned off stay off
+        for _ in 0..20 {
+            engine.adapt_code_weights(share, 0.5, 2);
+        }
+        assert_eq!(2, engine.get_code_weight(&Code::Subtract(Subtract::default())));
+        assert_eq!(0, engine.get_code_weight(&Code::Multiply(Multiply::default())));
+        assert_eq!(0, engine.get_code_weight(&Call::new(2, vec![], vec![])));
+    }
 }
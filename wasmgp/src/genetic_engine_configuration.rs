@@ -1,14 +1,49 @@
-use crate::Slot;
+use crate::{CostModel, CrossoverCuts, Slot};
 
+/// The settings of a `GeneticEngine`. A World creates these from its `WorldConfiguration`.
 #[derive(Clone, Debug, PartialEq)]
 pub struct GeneticEngineConfiguration {
+    /// Seeds the random number generator, or None for a random seed
     pub seed: Option<u64>,
+
+    /// The number of slots the generated code may use
     pub slot_count: Slot,
+
+    /// The most points that a mutation may grow code to
     pub individual_max_points: usize,
+
+    /// The most that the code of a generated, mutated or crossed over child may cost under the `cost_model`, or None
+    /// for no limit
+    pub individual_max_cost: Option<u64>,
+
+    /// How the cost of code is estimated for `individual_max_cost`
+    pub cost_model: CostModel,
+
+    /// How often `rand_child` mutates, relative to the `crossover_rate` and `archive_mutation_rate`
     pub mutation_rate: u8,
+
+    /// How often `rand_child` performs a crossover, relative to the `mutation_rate` and `archive_mutation_rate`
     pub crossover_rate: u8,
+
+    /// How often `rand_child` performs an archive mutation, relative to the other rates
+    pub archive_mutation_rate: u8,
+
+    /// How often `rand_child` performs an operand mutation, relative to the other rates
+    pub operand_mutation_rate: u8,
+
+    /// How often `rand_child` performs a homologous crossover, relative to the other rates
+    pub homologous_crossover_rate: u8,
+
+    /// The upper bound on the number of points changed by one mutation or archive mutation, and on the number of
+    /// operands changed by one operand mutation
     pub max_mutation_points: u8,
+
+    /// The upper bound on the number of crossover points in one crossover, and on the number of segments swapped by one
+    /// homologous crossover
     pub max_crossover_points: u8,
+
+    /// Where the crossovers may cut the code of the parents
+    pub crossover_cuts: CrossoverCuts,
 }
 
 impl GeneticEngineConfiguration {
@@ -17,10 +52,16 @@ impl GeneticEngineConfiguration {
             seed,
             slot_count,
             individual_max_points: 100,
+            individual_max_cost: None,
+            cost_model: CostModel::default(),
             mutation_rate: 1,
             crossover_rate: 9,
+            archive_mutation_rate: 0,
+            operand_mutation_rate: 0,
+            homologous_crossover_rate: 0,
             max_mutation_points: 1,
             max_crossover_points: 2,
+            crossover_cuts: CrossoverCuts::Anywhere,
         }
     }
 }
@@ -3,6 +3,17 @@ pub enum GeneticOperation {
     /// A single point of code will be mutated the specified number of times
     Mutation(u8),
 
+    /// A single point of code will be replaced by a subtree from the archive the specified number of times
+    ArchiveMutation(u8),
+
+    /// A single operand of the code, such as a slot or a constant, will be given a new random value the specified
+    /// number of times
+    OperandMutation(u8),
+
     /// The code from the two parents will be swapped at random positions the specified number of times
     Crossover(u8),
+
+    /// Segments of the first parent will be replaced by segments of a similar size and position from the second parent
+    /// the specified number of times
+    HomologousCrossover(u8),
 }
@@ -0,0 +1,34 @@
+use crate::{IndividualId, RunResult};
+
+/// How one individual did on the holdout runs of `World::evaluate_on_holdout`
+#[derive(Clone, Debug, PartialEq)]
+pub struct HoldoutResult<R: RunResult> {
+    pub individual_id: IndividualId,
+
+    /// The RunResult that the holdout callbacks left on the individual after each run, in the order of the runs
+    pub run_results: Vec<Option<R>>,
+
+    /// The score from the holdout callbacks' `score_individual` after each run, in the order of the runs
+    pub scores: Vec<u64>,
+}
+
+impl<R: RunResult> HoldoutResult<R> {
+    /// Returns the mean of the scores, or zero if there were no runs
+    pub fn mean_score(&self) -> f64 {
+        if self.scores.is_empty() {
+            0.0
+        } else {
+            self.scores.iter().map(|score| *score as f64).sum::<f64>() / self.scores.len() as f64
+        }
+    }
+
+    /// Returns the lowest score of any run, or None if there were no runs
+    pub fn min_score(&self) -> Option<u64> {
+        self.scores.iter().copied().min()
+    }
+
+    /// Returns the highest score of any run, or None if there were no runs
+    pub fn max_score(&self) -> Option<u64> {
+        self.scores.iter().copied().max()
+    }
+}
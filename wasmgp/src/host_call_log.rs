@@ -0,0 +1,195 @@
+use crate::{NumericEvents, SlotValue};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Display;
+use wasmtime::Val;
+
+/// One call from an individual's code to a host function, recorded when `WorldConfiguration::trace_host_calls` is set.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HostCallRecord {
+    /// The name the function was imported with
+    pub name: String,
+
+    pub params: Vec<SlotValue>,
+
+    /// The values the host function returned, or the error it failed with
+    pub results: std::result::Result<Vec<SlotValue>, String>,
+}
+
+impl Display for HostCallRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{:?}", self.name, self.params)?;
+        match &self.results {
+            Ok(results) => write!(f, " -> {:?}", results),
+            Err(message) => write!(f, " failed: {}", message),
+        }
+    }
+}
+
+/// What the host calls of one evaluation left behind: the trace, how often each limited import was called, and the
+/// results of memoized imports.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct HostCallLog {
+    pub trace: Vec<HostCallRecord>,
+    pub calls: HashMap<String, usize>,
+
+    /// The imports that were called more often than their `CallLimit`, in the order their limits were first exceeded
+    pub exceeded_limits: Vec<String>,
+
+    /// The number of calls to all imports, and whether that went over the `host_call_budget`
+    pub total_calls: usize,
+    pub exceeded_budget: bool,
+
+    /// The results of each memoized import, keyed by the bits of its arguments
+    pub memos: HashMap<String, HashMap<Vec<u64>, Vec<Val>>>,
+
+    /// The state of the generator behind the built-in random imports. It is not cleared between calls, so each call
+    /// continues where the previous one stopped until the individual restarts it for its next evaluation.
+    pub random_state: u64,
+
+    /// The numeric events counted by the telemetry imports. Like the random state, they add up over every call until
+    /// the individual begins its next evaluation.
+    pub numeric_events: NumericEvents,
+}
+
+impl HostCallLog {
+    pub fn clear(&mut self) {
+        self.trace.clear();
+        self.calls.clear();
+        self.exceeded_limits.clear();
+        self.total_calls = 0;
+        self.exceeded_budget = false;
+        self.memos.clear();
+    }
+}
+
+// Host functions are shared by every Store, so the calls are logged into whichever log is active on the current thread.
+// The individual makes its own log active only while its code is running.
+thread_local! {
+    static ACTIVE_LOG: RefCell<Option<HostCallLog>> = const { RefCell::new(None) };
+}
+
+// Runs `f` with `log` as the active log. Any log that was already active is put back afterwards.
+pub(crate) fn with_log<O, F: FnOnce() -> O>(log: &mut HostCallLog, f: F) -> O {
+    let previous = ACTIVE_LOG.with(|active| active.replace(Some(std::mem::take(log))));
+    let output = f();
+    *log = ACTIVE_LOG.with(|active| active.replace(previous)).unwrap_or_default();
+    output
+}
+
+pub(crate) fn record(call: HostCallRecord) {
+    ACTIVE_LOG.with(|active| {
+        if let Some(log) = active.borrow_mut().as_mut() {
+            log.trace.push(call);
+        }
+    });
+}
+
+// Counts a call to the named import and returns false once there have been more than `max_calls` of them
+pub(crate) fn count_call(name: &str, max_calls: usize) -> bool {
+    ACTIVE_LOG.with(|active| {
+        let mut active = active.borrow_mut();
+        let log = match active.as_mut() {
+            Some(log) => log,
+            None => return true,
+        };
+        let calls = match log.calls.get_mut(name) {
+            Some(calls) => calls,
+            None => log.calls.entry(name.to_string()).or_default(),
+        };
+        *calls += 1;
+        if *calls == max_calls + 1 {
+            log.exceeded_limits.push(name.to_string());
+        }
+        *calls <= max_calls
+    })
+}
+
+// Counts a call to any import and returns false once there have been more than `max_calls` of them
+pub(crate) fn count_budgeted_call(max_calls: usize) -> bool {
+    ACTIVE_LOG.with(|active| match active.borrow_mut().as_mut() {
+        Some(log) => {
+            log.total_calls += 1;
+            if log.total_calls > max_calls {
+                log.exceeded_budget = true;
+            }
+            log.total_calls <= max_calls
+        }
+        None => true,
+    })
+}
+
+// Counts a Divide or Remainder whose divisor was zero, for the numeric telemetry import
+pub(crate) fn count_division_by_zero() {
+    ACTIVE_LOG.with(|active| {
+        if let Some(log) = active.borrow_mut().as_mut() {
+            log.numeric_events.divisions_by_zero += 1;
+        }
+    })
+}
+
+// Counts a conversion of `value` to an integer in the range `min..max` that will saturate, for the numeric telemetry
+// imports
+pub(crate) fn check_conversion(value: f64, min: f64, max: f64) {
+    let truncated = value.trunc();
+    if value.is_nan() || truncated < min || truncated >= max {
+        ACTIVE_LOG.with(|active| {
+            if let Some(log) = active.borrow_mut().as_mut() {
+                log.numeric_events.saturated_conversions += 1;
+            }
+        })
+    }
+}
+
+// Returns the next value of the active log's generator, for the built-in random import
+pub(crate) fn next_random() -> i64 {
+    ACTIVE_LOG.with(|active| match active.borrow_mut().as_mut() {
+        Some(log) => splitmix64(&mut log.random_state) as i64,
+        None => 0,
+    })
+}
+
+// A small generator whose whole state is one u64, so that it can be stored on each individual and copied with it.
+// ref https://prng.di.unimi.it/splitmix64.c
+pub(crate) fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+// Returns the results of an earlier call to the named import with the same arguments
+pub(crate) fn memoized(name: &str, params: &[Val]) -> Option<Vec<Val>> {
+    ACTIVE_LOG.with(|active| {
+        let active = active.borrow();
+        let memo = active.as_ref()?.memos.get(name)?;
+        memo.get(&memo_key(params)[..]).cloned()
+    })
+}
+
+pub(crate) fn memoize(name: &str, params: &[Val], results: &[Val]) {
+    ACTIVE_LOG.with(|active| {
+        if let Some(log) = active.borrow_mut().as_mut() {
+            let memo = match log.memos.get_mut(name) {
+                Some(memo) => memo,
+                None => log.memos.entry(name.to_string()).or_default(),
+            };
+            memo.insert(memo_key(params), results.to_vec());
+        }
+    });
+}
+
+// The arguments of an import always have the same types, so their bits are enough to tell two calls apart
+fn memo_key(params: &[Val]) -> Vec<u64> {
+    params
+        .iter()
+        .map(|param| match param {
+            Val::I32(v) => *v as u32 as u64,
+            Val::I64(v) => *v as u64,
+            Val::F32(bits) => *bits as u64,
+            Val::F64(bits) => *bits,
+            _ => panic!("unsupported wasmtime::Val {:?}", param),
+        })
+        .collect()
+}
@@ -0,0 +1,44 @@
+/// Options for a function import that are applied while individuals run. Use with
+/// `World::add_function_import_with_options`. Options other than the default cannot be used in a World configured with
+/// `async_epoch_yielding`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ImportOptions {
+    /// Limits how many times the import may be called during one evaluation. The default is no limit.
+    pub call_limit: Option<CallLimit>,
+
+    /// When true, the results of each call are kept for the rest of the evaluation, and a later call with the same
+    /// arguments returns them without calling the host function again. Only use this for imports whose results depend
+    /// on nothing but their arguments; an import that reads or changes the state must not be memoized.
+    ///
+    /// The default is false
+    pub memoize: bool,
+}
+
+/// The most times an import may be called during one evaluation of an individual, and what happens to the calls after
+/// that.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CallLimit {
+    pub max_calls: usize,
+    pub policy: CallLimitPolicy,
+}
+
+impl CallLimit {
+    pub fn new(max_calls: usize, policy: CallLimitPolicy) -> CallLimit {
+        CallLimit { max_calls, policy }
+    }
+}
+
+/// What happens when an individual calls an import more times than its `CallLimit` allows. Whatever the policy, the
+/// name of the import is listed by `Individual::get_exceeded_call_limits`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CallLimitPolicy {
+    /// The call fails with `WasmgpError::CallLimitExceeded`, which ends the evaluation
+    Trap,
+
+    /// The host function is not called and every result is zero
+    NoOp,
+
+    /// The host function is still called. The fitness function can check `Individual::get_exceeded_call_limits` and
+    /// penalize the individual.
+    Flag,
+}
@@ -1,32 +1,93 @@
 use anyhow::Result;
 use wasmtime::{InstancePre, Store, WasmParams, WasmResults};
 
-use crate::{Code, CodeBuilder, Indentation, RunResult};
+use crate::host_call_log::{self, HostCallLog};
+use crate::{
+    Code, CodeBuilder, EvaluationError, EvaluationLimit, HostCallRecord, Indentation, ModuleMetadata, NumericEvents,
+    Operator, RunResult,
+};
+
+/// Uniquely identifies an individual within a World. Clones of an individual (elites, cloned migrants) share the ID.
+pub type IndividualId = u64;
+
+// A copy of the state of an evaluation, with the function that copies it again, so that an individual can be cloned
+// whether or not T is Clone
+struct StateSnapshot<T> {
+    state: T,
+    clone: fn(&T) -> T,
+}
 
 pub struct Individual<T, R: RunResult> {
+    id: IndividualId,
     code: Vec<Code>,
     function_name: String,
     instance_pre: InstancePre<T>,
     deadline: u64,
+    ticks_per_ms: u64,
+    slice_ms: u64,
+    max_turns: usize,
+    fuel: Option<u64>,
+    max_retries: usize,
+    random_seed: u64,
     run_result: Option<R>,
+    state_snapshot: Option<StateSnapshot<T>>,
+    error: Option<EvaluationError>,
+    host_calls: HostCallLog,
+    evaluation_stage: usize,
+    stage_deadline: Option<u64>,
+    skipped: bool,
+    parents: Vec<IndividualId>,
+    origin: Option<(Operator, u64)>,
+    teammates: Vec<Individual<T, R>>,
+    module_metadata: ModuleMetadata,
 }
 
 impl<T, R: RunResult> Individual<T, R> {
     pub(crate) fn new(
+        id: IndividualId,
         code: Vec<Code>,
         function_name: String,
         instance_pre: InstancePre<T>,
         deadline: u64,
+        ticks_per_ms: u64,
+        max_retries: usize,
     ) -> Individual<T, R> {
+        let random_seed = random_seed(0, id);
+        let host_calls = HostCallLog {
+            random_state: random_seed,
+            ..HostCallLog::default()
+        };
         Individual {
+            id,
             code,
             function_name,
             instance_pre,
             deadline,
+            ticks_per_ms,
+            slice_ms: 1,
+            max_turns: 1000,
+            fuel: None,
+            max_retries,
+            random_seed,
             run_result: None,
+            state_snapshot: None,
+            error: None,
+            host_calls,
+            evaluation_stage: 0,
+            stage_deadline: None,
+            skipped: false,
+            parents: vec![],
+            origin: None,
+            teammates: vec![],
+            module_metadata: ModuleMetadata::default(),
         }
     }
 
+    /// Returns the ID assigned to the Individual when it was created
+    pub fn get_id(&self) -> IndividualId {
+        self.id
+    }
+
     /// Borrows the Individual's code
     pub fn get_code(&self) -> &[Code] {
         &self.code[..]
@@ -47,6 +108,196 @@ impl<T, R: RunResult> Individual<T, R> {
         self.run_result = run_result;
     }
 
+    /// Borrows the copy of the state kept by the most recent call to `snapshot_state`, or None if there is no copy
+    pub fn get_state_snapshot(&self) -> Option<&T> {
+        self.state_snapshot.as_ref().map(|snapshot| &snapshot.state)
+    }
+
+    /// Removes the copy of the state kept by `snapshot_state` and returns it
+    pub fn take_state_snapshot(&mut self) -> Option<T> {
+        self.state_snapshot.take().map(|snapshot| snapshot.state)
+    }
+
+    /// Borrows the error from the most recent call to `execute`, or None if that call succeeded
+    pub fn get_error(&self) -> Option<&EvaluationError> {
+        self.error.as_ref()
+    }
+
+    /// Borrows the host calls made during the most recent attempt of the most recent call to `execute`, in the order
+    /// they were made. Always empty unless the World was configured with `trace_host_calls`.
+    pub fn get_host_call_trace(&self) -> &[HostCallRecord] {
+        &self.host_calls.trace[..]
+    }
+
+    /// Returns the names of the imports that the most recent attempt of the most recent call to `execute` called more
+    /// often than their `CallLimit` allows
+    pub fn get_exceeded_call_limits(&self) -> &[String] {
+        &self.host_calls.exceeded_limits[..]
+    }
+
+    /// Returns true if the most recent attempt of the most recent call to `execute` made more host calls than the
+    /// `host_call_budget` of the WorldConfiguration allows
+    pub fn exceeded_host_call_budget(&self) -> bool {
+        self.host_calls.exceeded_budget
+    }
+
+    /// Returns how often the protected numeric operations stepped in during every call to `execute` of the individual's
+    /// most recent evaluation. Always zero unless the World was configured with `numeric_telemetry`. The events of
+    /// teammates are counted on each teammate.
+    pub fn get_numeric_events(&self) -> NumericEvents {
+        self.host_calls.numeric_events
+    }
+
+    /// Returns the size, local count and nesting depth of the Wasm module compiled for the individual's code
+    pub fn get_module_metadata(&self) -> ModuleMetadata {
+        self.module_metadata
+    }
+
+    pub(crate) fn set_module_metadata(&mut self, module_metadata: ModuleMetadata) {
+        self.module_metadata = module_metadata;
+    }
+
+    /// Returns the limit that stopped the individual's most recent evaluation, if its time or fuel ran out
+    pub fn get_exceeded_limit(&self) -> Option<EvaluationLimit> {
+        self.error.as_ref().and_then(|error| error.exceeded_limit())
+    }
+
+    /// Returns the index of the last `EvaluationStage` the individual ran in this generation. Always zero on an island
+    /// without evaluation stages.
+    pub fn get_evaluation_stage(&self) -> usize {
+        self.evaluation_stage
+    }
+
+    /// Returns true if the individual was not run in this generation, because the island's callbacks found another
+    /// individual that was good enough to end the generation early. A skipped individual keeps the RunResult of its
+    /// last evaluation, which is None for a new individual, and is sorted as less fit than every individual that ran.
+    pub fn was_skipped(&self) -> bool {
+        self.skipped
+    }
+
+    // Marks the individual as skipped until it is run again
+    pub(crate) fn skip(&mut self) {
+        self.skipped = true;
+    }
+
+    // Gives the individual the results of an evaluation saved in a checkpoint
+    pub(crate) fn restore_evaluation(
+        &mut self,
+        run_result: Option<R>,
+        stage: usize,
+        skipped: bool,
+        numeric_events: NumericEvents,
+    ) {
+        self.run_result = run_result;
+        self.evaluation_stage = stage;
+        self.skipped = skipped;
+        self.host_calls.numeric_events = numeric_events;
+    }
+
+    // Moves the individual into a stage, which may run with a different time limit
+    pub(crate) fn begin_stage(&mut self, stage: usize, run_time_ms: Option<u64>) {
+        self.evaluation_stage = stage;
+        self.stage_deadline = run_time_ms;
+    }
+
+    // Sets how long `execute_async` runs before it yields back to the executor
+    pub(crate) fn set_time_slice(&mut self, slice_ms: u64) {
+        self.slice_ms = slice_ms;
+    }
+
+    // Sets the most calls that `execute_turns` makes during one evaluation
+    pub(crate) fn set_max_turns(&mut self, max_turns: usize) {
+        self.max_turns = max_turns;
+    }
+
+    // Sets the fuel that each call to `execute` may use, which must be None unless the engine consumes fuel
+    pub(crate) fn set_fuel(&mut self, fuel: Option<u64>) {
+        self.fuel = fuel;
+    }
+
+    fn deadline(&self) -> u64 {
+        self.stage_deadline.unwrap_or(self.deadline)
+    }
+
+    // The run time in epoch ticks, plus the tick in progress when the deadline is set, which may come at any moment
+    fn deadline_ticks(&self) -> u64 {
+        self.deadline() * self.ticks_per_ms + 1
+    }
+
+    /// Returns the number of programs in the individual's team. An individual from an island without teams is a team of
+    /// one.
+    pub fn team_size(&self) -> usize {
+        1 + self.teammates.len()
+    }
+
+    /// Borrows one member of the team, or None if there is no such member. Member zero is the individual itself, which
+    /// leads the team and holds the team's RunResult.
+    pub fn get_team_member(&self, member: usize) -> Option<&Individual<T, R>> {
+        match member {
+            0 => Some(self),
+            _ => self.teammates.get(member - 1),
+        }
+    }
+
+    /// Mutably borrows one member of the team, so that it can be executed. Member zero is the individual itself.
+    pub fn get_team_member_mut(&mut self, member: usize) -> Option<&mut Individual<T, R>> {
+        match member {
+            0 => Some(self),
+            _ => self.teammates.get_mut(member - 1),
+        }
+    }
+
+    /// Returns the IDs of the individuals that were chosen as parents of this one. The child starts from the code of the
+    /// first, and the second is the mate that it may have been crossed with. A child bred during a mutation-only warm-up
+    /// has one parent, and random code has none. Teammates are listed on the lead of the team only.
+    pub fn get_parents(&self) -> &[IndividualId] {
+        &self.parents[..]
+    }
+
+    pub(crate) fn set_parents(&mut self, parents: Vec<IndividualId>) {
+        self.parents = parents;
+    }
+
+    /// Returns the operator that produced this individual for its current generation, or None for random code, an
+    /// immigrant or an individual created outside of breeding. The operator of a team is that of its lead.
+    pub fn get_operator(&self) -> Option<Operator> {
+        self.origin.map(|(operator, _)| operator)
+    }
+
+    // The operator that produced the individual and the score of its most fit parent
+    pub(crate) fn get_origin(&self) -> Option<(Operator, u64)> {
+        self.origin
+    }
+
+    pub(crate) fn set_origin(&mut self, origin: Option<(Operator, u64)>) {
+        self.origin = origin;
+    }
+
+    pub(crate) fn teammates(&self) -> &[Individual<T, R>] {
+        &self.teammates[..]
+    }
+
+    pub(crate) fn set_teammates(&mut self, teammates: Vec<Individual<T, R>>) {
+        self.teammates = teammates;
+    }
+
+    // Seeds the built-in random values from the World's seed and the ID
+    pub(crate) fn seed_random(&mut self, world_seed: u64) {
+        self.random_seed = random_seed(world_seed, self.id);
+        self.host_calls.random_state = self.random_seed;
+    }
+
+    // Starts the built-in random values and the numeric events over for the individual and its teammates, as each
+    // evaluation begins
+    pub(crate) fn begin_evaluation(&mut self) {
+        self.skipped = false;
+        self.host_calls.random_state = self.random_seed;
+        self.host_calls.numeric_events = NumericEvents::default();
+        for teammate in self.teammates.iter_mut() {
+            teammate.begin_evaluation();
+        }
+    }
+
     /// Returns the code as a string
     pub fn get_code_string(&self) -> String {
         let mut indentation = Indentation::new(2, 0);
@@ -61,50 +312,244 @@ impl<T, R: RunResult> Individual<T, R> {
     ///
     /// Note that `execute` temporarily owns the state, but will pass it back no matter whether the execution of the
     /// code succeeds or not.
+    ///
+    /// A failed instantiation, such as when the memory of the instance cannot be allocated, is tried again up to
+    /// `evaluation_retries` times (see `WorldConfiguration`). No code has run when instantiation fails, so every
+    /// attempt starts from the same state. A failure while the code runs is never retried: a trap would happen again,
+    /// and the host functions that were called may already have changed the state. The error is both returned and kept
+    /// on the individual, where `get_error` can find it later.
     pub fn execute<Params, Results>(&mut self, state: T, params: Params) -> (T, Result<Results>)
     where
         Params: WasmParams,
         Results: WasmResults,
     {
-        // Create a new instance that references the state. If this fails, we need to unpack the state to be able to
-        // pass it back to the caller
-        let engine = self.instance_pre.module().engine();
-        let mut store = Store::new(engine, state);
-        let result = self.instance_pre.instantiate(&mut store);
-        if result.is_err() {
-            let state = store.into_data();
-            return (state, Err(result.unwrap_err()));
-        }
-        let instance = result.unwrap();
+        // Create a new instance that references the state, trying again if that fails. No code has run yet, so the
+        // state unpacked from the store of a failed attempt is the state that was passed in.
+        self.host_calls.clear();
+        let mut state = state;
+        let mut attempts = 0;
+        let (mut store, instance) = loop {
+            attempts += 1;
+            let mut store = self.new_store(state);
+            match self.instance_pre.instantiate(&mut store) {
+                Ok(instance) => break (store, instance),
+                Err(err) if attempts > self.max_retries => return self.finish(store.into_data(), Err(err), attempts),
+                Err(_) => state = store.into_data(),
+            }
+        };
 
         // Get the typed function from the instance. If this fails, we need to unpack the state to be able to pass it
         // back to the caller.
-        let result: Result<wasmtime::TypedFunc<Params, Results>, anyhow::Error> =
-            instance.get_typed_func(&mut store, &self.function_name);
-        if result.is_err() {
-            let state = store.into_data();
-            let err = result.err().unwrap();
-            return (state, Err(err));
-        }
-        let func = result.unwrap();
+        let func = match instance.get_typed_func::<Params, Results>(&mut store, &self.function_name) {
+            Ok(func) => func,
+            Err(err) => return self.finish(store.into_data(), Err(err), attempts),
+        };
 
         // Call the function. Unpack the state from the store and return the state and whatever the results of the
         // function were. This will run for the specified number of milliseconds at most.
-        store.set_epoch_deadline(self.deadline);
-        let result = func.call(&mut store, params);
-        let state = store.into_data();
+        store.set_epoch_deadline(self.deadline_ticks());
+        let result = host_call_log::with_log(&mut self.host_calls, || func.call(&mut store, params));
+        self.finish(store.into_data(), result, attempts)
+    }
+
+    // Keeps the error of an evaluation that failed on the individual, or clears the last one if it succeeded
+    fn finish<Results>(&mut self, state: T, result: Result<Results>, attempts: usize) -> (T, Result<Results>) {
+        self.error = result.as_ref().err().map(|err| EvaluationError::new(err, attempts));
         (state, result)
     }
+
+    /// Instantiates the individual's code once and calls it again and again on the same instance, such as once for
+    /// every move of a turn-based game, instead of playing the whole episode inside of one call. `turn` is called with
+    /// the state before the first call, with None, and after each call with its results. It returns the params of the
+    /// next call, or None when the episode is over. The episode also ends after `max_turns` calls (see
+    /// `WorldConfiguration`), once `turn` has seen the results of the last one.
+    ///
+    /// Returns the state and the number of calls that were made. The time limit covers the whole episode, including
+    /// the time spent in `turn`. An episode that fails is not retried, because the calls before the failure may have
+    /// changed the state; the error is both returned and kept on the individual.
+    pub fn execute_turns<Params, Results, F>(&mut self, state: T, mut turn: F) -> (T, Result<usize>)
+    where
+        Params: WasmParams,
+        Results: WasmResults,
+        F: FnMut(&mut T, Option<Results>) -> Option<Params>,
+    {
+        self.host_calls.clear();
+        let mut store = self.new_store(state);
+        let func = match self
+            .instance_pre
+            .instantiate(&mut store)
+            .and_then(|instance| instance.get_typed_func::<Params, Results>(&mut store, &self.function_name))
+        {
+            Ok(func) => func,
+            Err(err) => {
+                self.error = Some(EvaluationError::new(&err, 1));
+                return (store.into_data(), Err(err));
+            }
+        };
+
+        store.set_epoch_deadline(self.deadline_ticks());
+        let mut results = None;
+        let mut calls = 0;
+        while let Some(params) = turn(store.data_mut(), results.take()) {
+            if calls == self.max_turns {
+                break;
+            }
+            calls += 1;
+            match host_call_log::with_log(&mut self.host_calls, || func.call(&mut store, params)) {
+                Ok(returned) => results = Some(returned),
+                Err(err) => {
+                    self.error = Some(EvaluationError::new(&err, 1));
+                    return (store.into_data(), Err(err));
+                }
+            }
+        }
+        self.error = None;
+        (store.into_data(), Ok(calls))
+    }
+
+    // Creates a store for one evaluation, holding the individual's fuel
+    fn new_store(&self, state: T) -> Store<T> {
+        let engine = self.instance_pre.module().engine();
+        let mut store = Store::new(engine, state);
+        if let Some(fuel) = self.fuel {
+            store.add_fuel(fuel).expect("the engine of the World consumes fuel");
+        }
+        store
+    }
+}
+
+impl<T: Clone, R: RunResult> Individual<T, R> {
+    /// Keeps a copy of the state alongside the RunResult, so that the final state an evaluation produced, such as the
+    /// board at the end of a game, can be inspected once the run is over. Call it from `run_individual` with the state
+    /// that `execute` passed back. The copy is kept until it is replaced, and clones of the individual, such as elites
+    /// and migrants, keep a copy of their own.
+    /// ```
+    /// use wasmgp::*;
+    ///
+    /// #[derive(Clone)]
+    /// struct KeepsTheState;
+    /// impl IslandCallbacks<u64, EmptyRunResult> for KeepsTheState {
+    ///     fn clone(&self) -> Box<dyn IslandCallbacks<u64, EmptyRunResult>> {
+    ///         Box::new(Clone::clone(self))
+    ///     }
+    ///     fn run_individual(&mut self, individual: &mut Individual<u64, EmptyRunResult>) {
+    ///         let (state, _) = individual.execute::<(), ()>(41, ());
+    ///         individual.snapshot_state(&(state + 1));
+    ///         individual.set_run_result(Some(EmptyRunResult {}));
+    ///     }
+    /// }
+    ///
+    /// let mut world = World::<u64, EmptyRunResult>::new(WorldConfiguration::default()).unwrap();
+    /// let (individual, _) = world.evaluate(&[ConstZero::new(0)], &mut KeepsTheState).unwrap();
+    /// assert_eq!(Some(&42), individual.get_state_snapshot());
+    ///
+    /// // Start a new evaluation from the state the last one ended with
+    /// assert_eq!(Some(42), individual.restore_state());
+    /// ```
+    pub fn snapshot_state(&mut self, state: &T) {
+        self.state_snapshot = Some(StateSnapshot {
+            state: state.clone(),
+            clone: T::clone,
+        });
+    }
+
+    /// Returns a copy of the state kept by `snapshot_state`, such as to pass it to `execute` again
+    pub fn restore_state(&self) -> Option<T> {
+        self.get_state_snapshot().cloned()
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T: Send, R: RunResult> Individual<T, R> {
+    /// Executes the individual's code in the same way as `execute`, but yields back to the executor every millisecond
+    /// (or every `evaluation_slice_ms` of the WorldConfiguration) while the code is running. The World must have been
+    /// configured with `async_epoch_yielding`.
+    ///
+    /// If the code runs longer than the individual's time limit, the result is a `Trap::Interrupt` error, just as it
+    /// would be from `execute`. Failed instantiations are retried, and failures captured on the individual, just as
+    /// they are by `execute`.
+    pub async fn execute_async<Params, Results>(&mut self, state: T, params: Params) -> (T, Result<Results>)
+    where
+        Params: WasmParams + Send + Sync,
+        Results: WasmResults + Send + Sync,
+    {
+        use std::future::Future;
+        use std::task::Poll;
+        use std::time::{Duration, Instant};
+
+        self.host_calls.clear();
+        let mut state = state;
+        let mut attempts = 0;
+        let (mut store, instance) = loop {
+            attempts += 1;
+            let mut store = self.new_store(state);
+            match self.instance_pre.instantiate_async(&mut store).await {
+                Ok(instance) => break (store, instance),
+                Err(err) if attempts > self.max_retries => return self.finish(store.into_data(), Err(err), attempts),
+                Err(_) => state = store.into_data(),
+            }
+        };
+        let func = match instance.get_typed_func::<Params, Results>(&mut store, &self.function_name) {
+            Ok(func) => func,
+            Err(err) => return self.finish(store.into_data(), Err(err), attempts),
+        };
+
+        // Yield once every time slice of epoch ticks. The call is abandoned once the time limit has passed, because
+        // yielding on the epoch replaces the trap that `execute` relies upon.
+        store.epoch_deadline_async_yield_and_update(self.slice_ms * self.ticks_per_ms);
+        let time_limit = Duration::from_millis(self.deadline());
+        let started = Instant::now();
+        let host_calls = &mut self.host_calls;
+        let result = {
+            let mut call = Box::pin(func.call_async(&mut store, params));
+            futures::future::poll_fn(
+                |cx| match host_call_log::with_log(host_calls, || call.as_mut().poll(cx)) {
+                    Poll::Pending if started.elapsed() >= time_limit => {
+                        Poll::Ready(Err(wasmtime::Trap::Interrupt.into()))
+                    }
+                    other => other,
+                },
+            )
+            .await
+        };
+        self.finish(store.into_data(), result, attempts)
+    }
+}
+
+// Mixes the ID into the seed, so that individuals with neighbouring IDs do not share a stretch of random values
+fn random_seed(world_seed: u64, id: IndividualId) -> u64 {
+    let mut id = id;
+    world_seed ^ host_call_log::splitmix64(&mut id)
 }
 
 impl<T, R: RunResult> Clone for Individual<T, R> {
     fn clone(&self) -> Self {
         Self {
+            id: self.id,
             code: self.code.clone(),
             function_name: self.function_name.clone(),
             instance_pre: self.instance_pre.clone(),
             deadline: self.deadline.clone(),
+            ticks_per_ms: self.ticks_per_ms,
+            slice_ms: self.slice_ms,
+            max_turns: self.max_turns,
+            fuel: self.fuel,
+            max_retries: self.max_retries,
+            random_seed: self.random_seed,
             run_result: self.run_result.clone(),
+            state_snapshot: self.state_snapshot.as_ref().map(|snapshot| StateSnapshot {
+                state: (snapshot.clone)(&snapshot.state),
+                clone: snapshot.clone,
+            }),
+            error: self.error.clone(),
+            host_calls: self.host_calls.clone(),
+            evaluation_stage: self.evaluation_stage,
+            stage_deadline: self.stage_deadline,
+            skipped: self.skipped,
+            parents: self.parents.clone(),
+            origin: self.origin,
+            teammates: self.teammates.clone(),
+            module_metadata: self.module_metadata,
         }
     }
 }
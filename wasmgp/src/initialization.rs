@@ -0,0 +1,41 @@
+/// How the code of the first generation of an island is generated. The size of a program is measured in points, which
+/// take the place that tree depth has in tree-based genetic programming.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Initialization {
+    /// Every individual has a random size between one point and the `initial_max_points`
+    Uniform,
+
+    /// The classic ramped half-and-half scheme. Each individual is given a target size, ramped evenly across the
+    /// population from `min_points` to the `initial_max_points`. Half of the individuals are "full" and have exactly
+    /// their target size, and the other half "grow" to a random size up to their target.
+    RampedHalfAndHalf { min_points: usize },
+}
+
+impl Initialization {
+    /// Returns the target size of the individual at `index` in a first generation of `count` individuals, and whether
+    /// it is grown to exactly that size. The Uniform initialization grows every individual up to `max_points`.
+    pub fn target(&self, index: usize, count: usize, max_points: usize) -> (usize, bool) {
+        match *self {
+            Initialization::Uniform => (max_points, false),
+            Initialization::RampedHalfAndHalf { min_points } => {
+                let steps = count.saturating_sub(1).max(1);
+                let ramp = max_points.saturating_sub(min_points) * index.min(steps) / steps;
+                (min_points + ramp, index & 1 == 0)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn ramped_half_and_half_targets() {
+        let ramped = Initialization::RampedHalfAndHalf { min_points: 2 };
+        let targets: Vec<(usize, bool)> = (0..5).map(|index| ramped.target(index, 5, 10)).collect();
+        assert_eq!([(2, true), (4, false), (6, true), (8, false), (10, true)], targets[..]);
+        assert_eq!((2, true), ramped.target(0, 1, 10));
+        assert_eq!((10, false), Initialization::Uniform.target(3, 5, 10));
+    }
+}
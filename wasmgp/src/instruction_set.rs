@@ -0,0 +1,99 @@
+use crate::{Code, WasmgpError};
+use anyhow::Result;
+use strum::IntoEnumIterator;
+
+/// Increased whenever an instruction changes what it does, such as how it converts a value between types, so that code
+/// saved by an older build is not run as if it meant something else.
+pub const INSTRUCTION_SEMANTICS_VERSION: u32 = 1;
+
+/// Identifies the instructions of `Code` and what they do. Code that is saved, such as in a checkpoint, is saved with
+/// the instruction set of the build that wrote it, and is only read back by a build with the same instruction set. The
+/// instruction set changes when a variant of `Code` is added, removed or renamed, including by the `bit_ops` and
+/// `float_ops` features, and when `INSTRUCTION_SEMANTICS_VERSION` is increased.
+///
+/// It is written as text in the form `1:0123456789abcdef`, the semantics version and then a hash of the variants.
+/// ```
+/// use wasmgp::*;
+///
+/// let current = InstructionSet::current();
+/// let text = current.to_string();
+/// assert_eq!(current, text.parse().unwrap());
+/// assert!(current.check().is_ok());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InstructionSet {
+    /// The `INSTRUCTION_SEMANTICS_VERSION` of the build
+    pub semantics_version: u32,
+
+    /// A hash of the names of the variants of `Code`, in order
+    pub variants: u64,
+}
+
+impl InstructionSet {
+    /// Returns the instruction set of this build
+    pub fn current() -> InstructionSet {
+        // FNV-1a, which unlike the hashers of the standard library is the same in every build
+        let mut variants: u64 = 0xcbf29ce484222325;
+        for code in Code::iter() {
+            for byte in code.name().bytes().chain([b',']) {
+                variants ^= byte as u64;
+                variants = variants.wrapping_mul(0x100000001b3);
+            }
+        }
+        InstructionSet {
+            semantics_version: INSTRUCTION_SEMANTICS_VERSION,
+            variants,
+        }
+    }
+
+    /// Returns an error if code saved with this instruction set would not mean the same thing in this build
+    pub fn check(&self) -> Result<()> {
+        let current = InstructionSet::current();
+        if *self != current {
+            return Err(WasmgpError::InstructionSetMismatch(self.to_string(), current.to_string()).into());
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for InstructionSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{:016x}", self.semantics_version, self.variants)
+    }
+}
+
+impl std::str::FromStr for InstructionSet {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<InstructionSet> {
+        let (semantics_version, variants) = text
+            .split_once(':')
+            .and_then(|(version, variants)| Some((version.parse().ok()?, u64::from_str_radix(variants, 16).ok()?)))
+            .ok_or_else(|| WasmgpError::InvalidCodeListing(format!("'{}' is not an instruction set", text)))?;
+        Ok(InstructionSet {
+            semantics_version,
+            variants,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn other_instruction_sets_fail_the_check() {
+        let current = InstructionSet::current();
+        let older = InstructionSet {
+            semantics_version: current.semantics_version - 1,
+            ..current
+        };
+        assert!(older.check().is_err());
+        let other_variants = InstructionSet {
+            variants: current.variants ^ 1,
+            ..current
+        };
+        assert!(other_variants.check().is_err());
+        assert!("1-abc".parse::<InstructionSet>().is_err());
+    }
+}
@@ -1,19 +1,88 @@
-use crate::{Individual, IslandCallbacks, RunResult, SelectionCurve};
+use crate::generation_stats::structural_hash;
+use crate::island_functions::IslandFunctions;
+use crate::operator_stats;
+#[cfg(feature = "async")]
+use crate::CancellationToken;
+use crate::{
+    BufferCounts, CallbackState, Curriculum, EvaluationContext, EvaluationStage, GenerationStats, Individual,
+    OpcodeUsage, RunResult, SelectionStrategy, SubtreeArchive, WarmUp,
+};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(not(feature = "async"))]
+use std::sync::Mutex;
+#[cfg(not(feature = "async"))]
+use std::thread;
 
 pub struct Island<T, R: RunResult> {
-    functions: Box<dyn IslandCallbacks<T, R>>,
+    functions: IslandFunctions<T, R>,
     individuals: Vec<Individual<T, R>>,
     individuals_are_sorted: bool,
     future: Vec<Individual<T, R>>,
+    stats: GenerationStats,
+    future_stats: GenerationStats,
+    number_of_individuals_migrating: Option<usize>,
+    clone_migrated_individuals: Option<bool>,
+    evaluation_stages: Vec<EvaluationStage>,
+    team_size: usize,
+    curriculum: Option<Curriculum>,
+    difficulty_level: usize,
+    generations: usize,
+    warm_up: Option<WarmUp>,
+    numeric_event_penalty: u64,
+
+    // The share of the individuals given to the island by `island_sizing`
+    target_size: Option<usize>,
+
+    // The island's own archive, when the World migrates fragments
+    subtree_archive: Option<SubtreeArchive>,
+}
+
+// Clones the callbacks, settings and individuals, such as for `World::fork`
+impl<T, R: RunResult> Clone for Island<T, R> {
+    fn clone(&self) -> Self {
+        Island {
+            functions: self.functions.clone(),
+            individuals: self.individuals.clone(),
+            individuals_are_sorted: self.individuals_are_sorted,
+            future: self.future.clone(),
+            stats: self.stats.clone(),
+            future_stats: self.future_stats.clone(),
+            number_of_individuals_migrating: self.number_of_individuals_migrating,
+            clone_migrated_individuals: self.clone_migrated_individuals,
+            evaluation_stages: self.evaluation_stages.clone(),
+            team_size: self.team_size,
+            curriculum: self.curriculum.clone(),
+            difficulty_level: self.difficulty_level,
+            generations: self.generations,
+            warm_up: self.warm_up,
+            numeric_event_penalty: self.numeric_event_penalty,
+            target_size: self.target_size,
+            subtree_archive: self.subtree_archive.clone(),
+        }
+    }
 }
 
 impl<T, R: RunResult> Island<T, R> {
-    pub(crate) fn new(callbacks: Box<dyn IslandCallbacks<T, R>>) -> Island<T, R> {
+    pub(crate) fn new(functions: IslandFunctions<T, R>) -> Island<T, R> {
         Island {
-            functions: callbacks,
+            functions,
             individuals: vec![],
             individuals_are_sorted: false,
             future: vec![],
+            stats: GenerationStats::default(),
+            future_stats: GenerationStats::default(),
+            number_of_individuals_migrating: None,
+            clone_migrated_individuals: None,
+            evaluation_stages: vec![],
+            team_size: 1,
+            curriculum: None,
+            difficulty_level: 0,
+            generations: 0,
+            warm_up: None,
+            numeric_event_penalty: 0,
+            target_size: None,
+            subtree_archive: None,
         }
     }
 
@@ -22,6 +91,27 @@ impl<T, R: RunResult> Island<T, R> {
         self.individuals.clear();
         self.individuals_are_sorted = false;
         self.future.clear();
+        self.stats = GenerationStats::default();
+        self.future_stats = GenerationStats::default();
+        self.generations = 0;
+    }
+
+    /// Starts the island over while keeping its `keep_top_n` most fit individuals. The kept individuals join the future
+    /// generation, and the rest of the current generation is removed, so the next `World::fill_all_islands` tops the
+    /// island up with random individuals instead of breeding them. Nothing is kept from an island that has not been
+    /// sorted. Any migrants already in the future generation stay, and the island's settings, curriculum level and
+    /// generation count are kept.
+    pub fn reseed(&mut self, keep_top_n: usize) {
+        let individuals = std::mem::take(&mut self.individuals);
+        if self.individuals_are_sorted {
+            let keep = keep_top_n.min(individuals.len());
+            self.future
+                .extend(individuals.into_iter().rev().take(keep).map(|mut individual| {
+                    individual.set_origin(None);
+                    individual
+                }));
+        }
+        self.individuals_are_sorted = false;
     }
 
     /// Returns the most fit of all the individuals (the one sorted to the tail by the sorting algorithm). Returns None
@@ -47,54 +137,493 @@ impl<T, R: RunResult> Island<T, R> {
         self.individuals.get(index)
     }
 
+    // The individuals of the current generation, in their current order
+    pub(crate) fn individuals(&self) -> impl Iterator<Item = &Individual<T, R>> {
+        self.individuals.iter()
+    }
+
+    // The `count` most fit individuals, most fit first, or none if the island has not been sorted
+    pub(crate) fn elites(&self, count: usize) -> impl Iterator<Item = &Individual<T, R>> {
+        let count = if self.individuals_are_sorted { count } else { 0 };
+        self.individuals.iter().rev().take(count)
+    }
+
+    // The individuals that have already joined the next generation, such as migrants
+    pub(crate) fn future_individuals(&self) -> impl Iterator<Item = &Individual<T, R>> {
+        self.future.iter()
+    }
+
+    // Replaces the individuals with the ones from a checkpoint. A current generation that is not empty was saved after
+    // it was sorted, along with the results of its evaluation, so it is not run again. Checkpoints written before the
+    // generation count was saved leave it as it is.
+    pub(crate) fn restore_individuals(
+        &mut self,
+        current: Vec<Individual<T, R>>,
+        future: Vec<Individual<T, R>>,
+        generations: Option<usize>,
+    ) {
+        self.individuals_are_sorted = !current.is_empty();
+        self.individuals = current;
+        self.future = future;
+        self.stats = GenerationStats::default();
+        self.future_stats = GenerationStats::default();
+        self.generations = generations.unwrap_or(self.generations);
+    }
+
+    // Saves the state of the island's callbacks for a checkpoint
+    pub(crate) fn save_callback_state(&mut self) -> CallbackState<T, R> {
+        let mut state = CallbackState::default();
+        self.functions.save_state(&mut state);
+        state
+    }
+
+    // Returns a copy of the island's callbacks with the state restored into it. The island keeps its own callbacks
+    // until `set_functions` replaces them.
+    pub(crate) fn restored_functions(&self, mut state: CallbackState<T, R>) -> anyhow::Result<IslandFunctions<T, R>> {
+        let mut functions = self.functions.clone();
+        functions.restore_state(&mut state)?;
+        Ok(functions)
+    }
+
+    pub(crate) fn set_functions(&mut self, functions: IslandFunctions<T, R>) {
+        self.functions = functions;
+    }
+
+    pub(crate) fn use_fitness_cases(&mut self, cases: &[usize]) {
+        self.functions.use_fitness_cases(cases);
+    }
+
+    pub(crate) fn use_evaluation_context(&mut self, context: &EvaluationContext) {
+        self.functions.use_evaluation_context(context);
+    }
+
     /// Uses the specified VM to run one generation of individuals. Calls all of the user-supplied functions from the
     /// `Island` trait.
     #[cfg(not(feature = "async"))]
     pub fn run_one_generation(&mut self) {
         // Allow the island to set up for all runs
+        self.functions.use_difficulty_level(self.difficulty_level);
         self.functions.pre_generation_run(&self.individuals);
 
-        // Run each individual
-        for individual in self.individuals.iter_mut() {
-            self.functions.run_individual(individual);
+        // Run each individual, or run each stage on the individuals that qualified for it, until one is good enough
+        let mut qualified = self.begin_evaluation();
+        if self.evaluation_stages.is_empty() {
+            for individual in self.individuals.iter_mut() {
+                self.functions.run_individual(individual);
+                if self.functions.is_good_enough(individual) {
+                    break;
+                }
+            }
+        } else {
+            'stages: for index in 0..self.evaluation_stages.len() {
+                self.begin_stage(index, &qualified);
+                for (individual, _) in self.individuals.iter_mut().zip(qualified.iter()).filter(|(_, q)| **q) {
+                    self.functions.run_individual(individual);
+                    if self.functions.is_good_enough(individual) {
+                        break 'stages;
+                    }
+                }
+                qualified = self.stage_survivors(index, &qualified);
+            }
+            self.end_evaluation();
+        }
+
+        // Allow the island to before any cleanup or group analysis tasks
+        self.functions.post_generation_run(&self.individuals);
+
+        // Sort the individuals and measure how diverse they are
+        self.sort_individuals();
+        self.measure_diversity();
+        self.advance_curriculum();
+    }
+
+    /// Runs one generation of individuals in the same way as `run_one_generation`, but on `workers` threads. Each
+    /// thread takes the next individual that has not been run until there are none left, and runs it with its own
+    /// clone of the island callbacks, made after `pre_generation_run`. Once one is good enough, no more are started.
+    #[cfg(not(feature = "async"))]
+    pub fn run_one_generation_on_threads(&mut self, workers: usize)
+    where
+        T: Send,
+        R: Send,
+    {
+        if workers <= 1 {
+            return self.run_one_generation();
+        }
+
+        // Allow the island to set up for all runs
+        self.functions.use_difficulty_level(self.difficulty_level);
+        self.functions.pre_generation_run(&self.individuals);
+
+        // Run each individual, or run each stage on the individuals that qualified for it, until one is good enough
+        let mut qualified = self.begin_evaluation();
+        let good_enough = AtomicBool::new(false);
+        if self.evaluation_stages.is_empty() {
+            self.run_qualified_individuals_on_threads(&qualified, workers, &good_enough);
+        } else {
+            for index in 0..self.evaluation_stages.len() {
+                self.begin_stage(index, &qualified);
+                self.run_qualified_individuals_on_threads(&qualified, workers, &good_enough);
+                if good_enough.load(Ordering::Relaxed) {
+                    break;
+                }
+                qualified = self.stage_survivors(index, &qualified);
+            }
+            self.end_evaluation();
         }
 
         // Allow the island to before any cleanup or group analysis tasks
         self.functions.post_generation_run(&self.individuals);
 
-        // Sort the individuals
+        // Sort the individuals and measure how diverse they are
         self.sort_individuals();
+        self.measure_diversity();
+        self.advance_curriculum();
+    }
+
+    // Sets `good_enough` and starts no more individuals once the callbacks find one that is good enough
+    #[cfg(not(feature = "async"))]
+    fn run_qualified_individuals_on_threads(&mut self, qualified: &[bool], workers: usize, good_enough: &AtomicBool)
+    where
+        T: Send,
+        R: Send,
+    {
+        let remaining = qualified.iter().filter(|q| **q).count();
+        let queue = Mutex::new(
+            self.individuals
+                .iter_mut()
+                .zip(qualified.iter())
+                .filter(|(_, q)| **q)
+                .map(|(individual, _)| individual),
+        );
+        let functions = &self.functions;
+        let queue = &queue;
+        thread::scope(|scope| {
+            for _ in 0..workers.min(remaining) {
+                let mut callbacks = functions.clone();
+                scope.spawn(move || loop {
+                    if good_enough.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    let next = queue.lock().expect("an evaluation thread panicked").next();
+                    let individual = match next {
+                        Some(individual) => individual,
+                        None => break,
+                    };
+                    callbacks.run_individual(individual);
+                    if callbacks.is_good_enough(individual) {
+                        good_enough.store(true, Ordering::Relaxed);
+                    }
+                });
+            }
+        });
     }
 
     /// Uses the specified VM to run one generation of individuals. Calls all of the user-supplied functions from the
     /// `Island` trait.
     #[cfg(feature = "async")]
     pub async fn run_one_generation(&mut self) {
+        self.run_one_generation_concurrently(1, &CancellationToken::new()).await;
+    }
+
+    /// Runs one generation of individuals, evaluating up to `max_in_flight` individuals at the same time. When more
+    /// than one individual is in flight, each evaluation uses its own clone of the island callbacks.
+    ///
+    /// Returns false without running `post_generation_run` or sorting if the token was cancelled before every
+    /// individual had been started.
+    #[cfg(feature = "async")]
+    pub async fn run_one_generation_concurrently(
+        &mut self,
+        max_in_flight: usize,
+        cancellation: &CancellationToken,
+    ) -> bool {
+        self.run_one_generation_scheduled(max_in_flight, false, cancellation)
+            .await
+    }
+
+    /// Runs one generation of individuals in the same way as `run_one_generation_concurrently`, but takes turns between
+    /// the individuals in flight. Each one runs until it yields at the end of its time slice, and then goes to the back
+    /// of the line, so that an individual that runs for a long time only delays the others by one slice at a time.
+    #[cfg(feature = "async")]
+    pub async fn run_one_generation_time_sliced(
+        &mut self,
+        max_in_flight: usize,
+        cancellation: &CancellationToken,
+    ) -> bool {
+        self.run_one_generation_scheduled(max_in_flight, true, cancellation)
+            .await
+    }
+
+    #[cfg(feature = "async")]
+    async fn run_one_generation_scheduled(
+        &mut self,
+        max_in_flight: usize,
+        round_robin: bool,
+        cancellation: &CancellationToken,
+    ) -> bool {
         // Allow the island to set up for all runs
+        self.functions.use_difficulty_level(self.difficulty_level);
         self.functions.pre_generation_run(&self.individuals).await;
 
-        // Run each individual
-        for individual in self.individuals.iter_mut() {
-            self.functions.run_individual(individual).await;
+        // Run each individual, or run each stage on the individuals that qualified for it, until one is good enough
+        let mut qualified = self.begin_evaluation();
+        let good_enough = AtomicBool::new(false);
+        let completed = if self.evaluation_stages.is_empty() {
+            self.run_qualified_individuals(&qualified, max_in_flight, round_robin, &good_enough, cancellation)
+                .await
+        } else {
+            let mut completed = true;
+            for index in 0..self.evaluation_stages.len() {
+                self.begin_stage(index, &qualified);
+                completed = self
+                    .run_qualified_individuals(&qualified, max_in_flight, round_robin, &good_enough, cancellation)
+                    .await;
+                if !completed || good_enough.load(Ordering::Relaxed) {
+                    break;
+                }
+                qualified = self.stage_survivors(index, &qualified);
+            }
+            self.end_evaluation();
+            completed
+        };
+        if !completed {
+            return false;
         }
 
         // Allow the island to before any cleanup or group analysis tasks
         self.functions.post_generation_run(&self.individuals).await;
 
-        // Sort the individuals
+        // Sort the individuals and measure how diverse they are
         self.sort_individuals();
+        self.measure_diversity();
+        self.advance_curriculum();
+        true
+    }
+
+    // Returns false if the token was cancelled before every individual had been started. Sets `good_enough` and starts
+    // no more individuals once the callbacks find one that is good enough.
+    #[cfg(feature = "async")]
+    async fn run_qualified_individuals(
+        &mut self,
+        qualified: &[bool],
+        max_in_flight: usize,
+        round_robin: bool,
+        good_enough: &AtomicBool,
+        cancellation: &CancellationToken,
+    ) -> bool {
+        use futures::StreamExt;
+
+        let individuals = self
+            .individuals
+            .iter_mut()
+            .zip(qualified.iter())
+            .filter(|(_, q)| **q)
+            .map(|(individual, _)| individual);
+        if round_robin {
+            let functions = &self.functions;
+            let runs = individuals.map(|individual| {
+                let mut callbacks = functions.clone();
+                async move {
+                    if cancellation.is_cancelled() {
+                        return false;
+                    }
+                    if !good_enough.load(Ordering::Relaxed) {
+                        callbacks.run_individual(individual).await;
+                        if callbacks.is_good_enough(individual) {
+                            good_enough.store(true, Ordering::Relaxed);
+                        }
+                    }
+                    true
+                }
+            });
+            take_turns(runs, max_in_flight).await
+        } else if max_in_flight <= 1 {
+            for individual in individuals {
+                if cancellation.is_cancelled() {
+                    return false;
+                }
+                self.functions.run_individual(individual).await;
+                if self.functions.is_good_enough(individual) {
+                    good_enough.store(true, Ordering::Relaxed);
+                    break;
+                }
+            }
+            true
+        } else {
+            let functions = &self.functions;
+            let runs: Vec<bool> = futures::stream::iter(individuals)
+                .map(|individual| {
+                    let mut callbacks = functions.clone();
+                    async move {
+                        if cancellation.is_cancelled() {
+                            return false;
+                        }
+                        if !good_enough.load(Ordering::Relaxed) {
+                            callbacks.run_individual(individual).await;
+                            if callbacks.is_good_enough(individual) {
+                                good_enough.store(true, Ordering::Relaxed);
+                            }
+                        }
+                        true
+                    }
+                })
+                .buffer_unordered(max_in_flight)
+                .collect()
+                .await;
+            runs.iter().all(|ran| *ran)
+        }
+    }
+
+    // Puts every individual back at the first stage and returns the mask of individuals that run it: all of them. Each
+    // individual is skipped until it is run.
+    fn begin_evaluation(&mut self) -> Vec<bool> {
+        for individual in self.individuals.iter_mut() {
+            individual.begin_stage(0, None);
+            individual.skip();
+        }
+        vec![true; self.individuals.len()]
+    }
+
+    // Moves the qualified individuals into a stage
+    fn begin_stage(&mut self, index: usize, qualified: &[bool]) {
+        let run_time_ms = self.evaluation_stages[index].run_time_ms;
+        for (individual, _) in self.individuals.iter_mut().zip(qualified.iter()).filter(|(_, q)| **q) {
+            individual.begin_stage(index, run_time_ms);
+        }
+    }
+
+    // Returns the mask of the individuals that go on from a stage to the next: the most fit `survivors` of the ones that
+    // ran it
+    fn stage_survivors(&self, index: usize, qualified: &[bool]) -> Vec<bool> {
+        let mut next = vec![false; qualified.len()];
+        if index + 1 < self.evaluation_stages.len() {
+            let mut ranked: Vec<usize> = (0..qualified.len()).filter(|i| qualified[*i]).collect();
+            ranked.sort_by(|a, b| self.compare_scores(&self.individuals[*b], &self.individuals[*a]));
+            for i in ranked.into_iter().take(self.evaluation_stages[index].survivors) {
+                next[i] = true;
+            }
+        }
+        next
+    }
+
+    // Restores the time limit that each individual was created with
+    fn end_evaluation(&mut self) {
+        for individual in self.individuals.iter_mut() {
+            individual.begin_stage(individual.get_evaluation_stage(), None);
+        }
+    }
+
+    /// Returns the stages that individuals are evaluated in. Empty if every individual is run once.
+    pub fn evaluation_stages(&self) -> &[EvaluationStage] {
+        &self.evaluation_stages[..]
+    }
+
+    /// Evaluates the individuals of this island in stages from the next generation on. Pass an empty list to run every
+    /// individual once again.
+    pub fn set_evaluation_stages(&mut self, stages: Vec<EvaluationStage>) {
+        self.evaluation_stages = stages;
+    }
+
+    /// Returns the number of programs in each team that is created for this island
+    pub fn team_size(&self) -> usize {
+        self.team_size
+    }
+
+    /// Makes every individual created for this island from now on a team of `team_size` programs, which the island's
+    /// callbacks evaluate together to produce one RunResult. Each member of a child team is bred from the members in the
+    /// same position of its parents. A `team_size` of zero is treated as one.
+    pub fn set_team_size(&mut self, team_size: usize) {
+        self.team_size = team_size.max(1);
+    }
+
+    /// Returns the number of individuals this island has been given by the World's `island_sizing`, which it breeds in
+    /// place of `individuals_per_island`. None until the first time the individuals are shared out.
+    pub fn target_size(&self) -> Option<usize> {
+        self.target_size
     }
 
-    /// Sorts the individuals by calling the sorter function.
+    pub(crate) fn set_target_size(&mut self, target_size: Option<usize>) {
+        self.target_size = target_size;
+    }
+
+    /// Borrows the curriculum that raises the island's difficulty level, if it has one
+    pub fn curriculum(&self) -> Option<&Curriculum> {
+        self.curriculum.as_ref()
+    }
+
+    /// Sets the curriculum that raises the island's difficulty level from the next generation on, or removes it. The
+    /// current level is kept.
+    pub fn set_curriculum(&mut self, curriculum: Option<Curriculum>) {
+        self.curriculum = curriculum;
+    }
+
+    /// Returns the difficulty level that the next generation will run at
+    pub fn difficulty_level(&self) -> usize {
+        self.difficulty_level
+    }
+
+    /// Moves the island to a difficulty level, such as one saved from an earlier run
+    pub fn set_difficulty_level(&mut self, level: usize) {
+        self.difficulty_level = level;
+    }
+
+    /// Sorts the individuals by calling the sorter function. Individuals that reached a later evaluation stage are
+    /// always sorted as more fit, and individuals that were skipped are always sorted as less fit.
     pub fn sort_individuals(&mut self) {
         // It is useful to swap the Vec into a local variable to avoid borrow-checking issues during the sort
         let mut local_individuals = vec![];
         std::mem::swap(&mut self.individuals, &mut local_individuals);
-        local_individuals.sort_by(|a, b| self.functions.sort_individuals(a, b));
+        local_individuals.sort_by(|a, b| self.compare_individuals(a, b));
         std::mem::swap(&mut self.individuals, &mut local_individuals);
         self.individuals_are_sorted = true;
     }
 
+    // Orders two individuals from least fit to most fit in the same way as `sort_individuals`
+    pub(crate) fn compare_individuals(&self, a: &Individual<T, R>, b: &Individual<T, R>) -> std::cmp::Ordering {
+        match (a.was_skipped(), b.was_skipped()) {
+            (true, true) => std::cmp::Ordering::Equal,
+            (true, false) => std::cmp::Ordering::Less,
+            (false, true) => std::cmp::Ordering::Greater,
+            (false, false) => a
+                .get_evaluation_stage()
+                .cmp(&b.get_evaluation_stage())
+                .then_with(|| self.compare_scores(a, b)),
+        }
+    }
+
+    // Orders two individuals by the callbacks, or by their penalized scores when the island has a numeric event penalty
+    fn compare_scores(&self, a: &Individual<T, R>, b: &Individual<T, R>) -> std::cmp::Ordering {
+        if self.numeric_event_penalty > 0 {
+            self.score_individual(a).cmp(&self.score_individual(b))
+        } else {
+            self.functions.sort_individuals(a, b)
+        }
+    }
+
+    // Runs an individual that is not part of the generation, such as a variant found by local search
+    #[cfg(not(feature = "async"))]
+    pub(crate) fn evaluate_individual(&mut self, individual: &mut Individual<T, R>) {
+        self.functions.run_individual(individual);
+    }
+
+    // Runs an individual that is not part of the generation, such as a variant found by local search
+    #[cfg(feature = "async")]
+    pub(crate) async fn evaluate_individual(&mut self, individual: &mut Individual<T, R>) {
+        self.functions.run_individual(individual).await;
+    }
+
+    // Passes every individual of the current and future generations to `rebuild`, and replaces it with the result. An
+    // individual is removed when the result is None. Returns the number of individuals that were removed.
+    pub(crate) fn rebuild_individuals<F>(&mut self, mut rebuild: F) -> usize
+    where
+        F: FnMut(&Individual<T, R>) -> Option<Individual<T, R>>,
+    {
+        let before = self.individuals.len() + self.future.len();
+        self.individuals = self.individuals.iter().filter_map(&mut rebuild).collect();
+        self.future = self.future.iter().filter_map(&mut rebuild).collect();
+        self.individuals_are_sorted = false;
+        before - self.individuals.len() - self.future.len()
+    }
+
     /// Returns the current number of individuals on the island.
     pub fn len(&self) -> usize {
         self.individuals.len()
@@ -110,43 +639,234 @@ impl<T, R: RunResult> Island<T, R> {
         self.individuals.clear();
         self.individuals_are_sorted = false;
         std::mem::swap(&mut self.individuals, &mut self.future);
+        self.stats = std::mem::take(&mut self.future_stats);
+        self.generations += 1;
+    }
+
+    // Undoes `advance_generation` for a generation that was abandoned before it was sorted. Its individuals go back to
+    // the future generation, so the next `World::fill_all_islands` makes them current again to be run from the start.
+    #[cfg(feature = "async")]
+    pub(crate) fn abandon_generation(&mut self) {
+        let mut abandoned = std::mem::take(&mut self.individuals);
+        abandoned.append(&mut self.future);
+        self.future = abandoned;
+        self.future_stats = std::mem::take(&mut self.stats);
+        self.individuals_are_sorted = false;
+        self.generations = self.generations.saturating_sub(1);
+    }
+
+    /// Returns the number of generations that have been made current on the island since it was created or cleared.
+    /// The first, random, generation counts as one.
+    pub fn generations(&self) -> usize {
+        self.generations
+    }
+
+    /// Returns how the island breeds during its first generations, or None if the island uses the `warm_up` value of the
+    /// WorldConfiguration
+    pub fn warm_up(&self) -> Option<WarmUp> {
+        self.warm_up
+    }
+
+    /// Overrides how the island breeds during its first generations. Set to `Some(WarmUp::None)` to breed this island
+    /// normally from the start, or to None to use the value from the WorldConfiguration.
+    pub fn set_warm_up(&mut self, warm_up: Option<WarmUp>) {
+        self.warm_up = warm_up;
+    }
+
+    /// Returns the amount subtracted from the score of an individual for each of its numeric events
+    pub fn numeric_event_penalty(&self) -> u64 {
+        self.numeric_event_penalty
+    }
+
+    /// Subtracts `penalty` from the score of each individual for every division by zero and saturated conversion of its
+    /// most recent evaluation, so that the island favors programs that do not lean on the protected operations. The
+    /// events are only counted when `WorldConfiguration::numeric_telemetry` is set. While the penalty is above zero,
+    /// the island sorts by the penalized `score_individual` of its callbacks instead of by `sort_individuals`. The
+    /// default is zero, which turns the penalty off.
+    pub fn set_numeric_event_penalty(&mut self, penalty: u64) {
+        self.numeric_event_penalty = penalty;
+    }
+
+    /// Returns the number of individuals that leave this island during a migration, or None if the island uses the
+    /// `number_of_individuals_migrating` value of the WorldConfiguration
+    pub fn number_of_individuals_migrating(&self) -> Option<usize> {
+        self.number_of_individuals_migrating
+    }
+
+    /// Overrides the number of individuals that leave this island during a migration. Set to `Some(0)` to make the
+    /// island a sink that only receives migrants, or to None to use the value from the WorldConfiguration. An island
+    /// asked for more individuals than it has sends the ones it has.
+    pub fn set_number_of_individuals_migrating(&mut self, number: Option<usize>) {
+        self.number_of_individuals_migrating = number;
+    }
+
+    /// Returns whether individuals leaving this island are cloned, or None if the island uses the
+    /// `clone_migrated_individuals` value of the WorldConfiguration
+    pub fn clone_migrated_individuals(&self) -> Option<bool> {
+        self.clone_migrated_individuals
+    }
+
+    /// Overrides whether individuals leaving this island are cloned (true) or removed from the island (false). Set to
+    /// None to use the value from the WorldConfiguration.
+    pub fn set_clone_migrated_individuals(&mut self, clone: Option<bool>) {
+        self.clone_migrated_individuals = clone;
     }
 
-    /// Select one individual from the island according to the specified SelectionCurve and borrow it.
-    /// Returns the individual borrowed or None if the population is zero or not sorted
-    pub fn select_one_individual<Rnd: rand::Rng>(
+    /// Borrows the island's own archive of subtrees, which only exists when the World has a `subtree_archive` and
+    /// migrates `MigrationPayload::Fragments`. The archive holds the subtrees of the island's elites and the fragments
+    /// that migrated to it.
+    pub fn subtree_archive(&self) -> Option<&SubtreeArchive> {
+        self.subtree_archive.as_ref()
+    }
+
+    // Mutably borrows the island's own archive of subtrees, creating it as a copy of `empty` the first time
+    pub(crate) fn subtree_archive_or(&mut self, empty: &SubtreeArchive) -> &mut SubtreeArchive {
+        self.subtree_archive.get_or_insert_with(|| empty.clone())
+    }
+
+    /// Borrows the statistics for the current generation
+    pub fn generation_stats(&self) -> &GenerationStats {
+        &self.stats
+    }
+
+    // Records the level this generation ran at, and moves up a level if the most fit individual passed it
+    fn advance_curriculum(&mut self) {
+        self.stats.difficulty_level = self.difficulty_level;
+        if let (Some(curriculum), Some(best)) = (&self.curriculum, self.individuals.last()) {
+            let best_score = self.score_individual(best);
+            self.difficulty_level = curriculum.next_level(self.difficulty_level, best_score);
+        }
+    }
+
+    // Fills in the diversity measures and the operator stats of the current generation's stats
+    fn measure_diversity(&mut self) {
+        let scores: Vec<u64> = self
+            .individuals
+            .iter()
+            .map(|individual| self.score_individual(individual))
+            .collect();
+        self.stats.measure_diversity(&self.individuals, &scores);
+        self.stats.operators = operator_stats::count_operators(&self.individuals, &scores);
+    }
+
+    // Records the mean scores this island gave to the elites of every island
+    pub(crate) fn set_cross_evaluation(&mut self, scores: Vec<Option<f64>>) {
+        self.stats.cross_evaluation = scores;
+    }
+
+    // Counts the Code variants and host imports used by the current generation and by its most fit `elites`
+    pub(crate) fn opcode_usage(&self, elites: usize, import_names: &[String]) -> OpcodeUsage {
+        let code = self.individuals.iter().map(|individual| individual.get_code());
+        OpcodeUsage::count(code, self.individuals_are_sorted, elites, import_names)
+    }
+
+    // Records that one individual of the current generation has left for another island
+    pub(crate) fn record_emigrant(&mut self) {
+        self.stats.emigrants += 1;
+    }
+
+    // Records that one individual of the future generation arrived from another island
+    pub(crate) fn record_immigrant(&mut self) {
+        self.future_stats.immigrants += 1;
+    }
+
+    // Records that a child bred for the future generation could not be built
+    pub(crate) fn record_failed_child(&mut self, error: &anyhow::Error) {
+        self.future_stats.failed_children += 1;
+        self.future_stats.last_child_error = Some(format!("{:#}", error));
+    }
+
+    // Records the buffers that breeding the future generation allocated and reused
+    pub(crate) fn record_breeding_buffers(&mut self, counts: BufferCounts) {
+        self.future_stats.breeding_buffers += counts;
+    }
+
+    /// Select one individual from the island according to the specified SelectionStrategy, or a SelectionCurve, and
+    /// borrow it. Returns the individual borrowed or None if the population is zero or not sorted
+    pub fn select_one_individual<S: Into<SelectionStrategy>, Rnd: rand::Rng>(
         &self,
-        curve: SelectionCurve,
+        strategy: S,
         rng: &mut Rnd,
     ) -> Option<&Individual<T, R>> {
         if !self.individuals_are_sorted {
             return None;
         }
 
-        let max = self.individuals.len();
-        if max == 0 {
+        if self.individuals.is_empty() {
             None
         } else {
-            self.individuals.get(curve.pick_one_index(rng, max))
+            self.individuals.get(self.pick_one_index(&strategy.into(), rng))
         }
     }
 
-    /// Select one individual from the island according to the specified SelectionCurve and remove it permanently.
-    /// Returns the individual removed or None if the population is zero or not sorted
-    pub fn select_and_remove_one_individual<Rnd: rand::Rng>(
+    // Selects the index of one individual in the same way as `select_one_individual`
+    pub(crate) fn select_one_index<Rnd: rand::Rng>(
+        &self,
+        strategy: &SelectionStrategy,
+        rng: &mut Rnd,
+    ) -> Option<usize> {
+        if !self.individuals_are_sorted || self.individuals.is_empty() {
+            None
+        } else {
+            Some(self.pick_one_index(strategy, rng))
+        }
+    }
+
+    // Selects an elite according to the strategy, skipping individuals whose code is identical to an elite already
+    // chosen. When the pick is such a duplicate, the next most fit unique individual below it is chosen, and then the
+    // next one above it. The structure of the chosen elite is added to `chosen`. Returns None if every individual is a
+    // duplicate or the island is not sorted.
+    pub(crate) fn select_unique_elite<Rnd: rand::Rng>(
+        &self,
+        strategy: &SelectionStrategy,
+        rng: &mut Rnd,
+        chosen: &mut HashSet<u64>,
+    ) -> Option<&Individual<T, R>> {
+        let picked = self.select_one_index(strategy, rng)?;
+        let below = (0..=picked).rev();
+        let above = picked + 1..self.individuals.len();
+        below.chain(above).find_map(|index| {
+            let individual = &self.individuals[index];
+            if chosen.insert(structural_hash(individual.get_code())) {
+                Some(individual)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Select one individual from the island according to the specified SelectionStrategy, or a SelectionCurve, and
+    /// remove it permanently. Returns the individual removed or None if the population is zero or not sorted
+    pub fn select_and_remove_one_individual<S: Into<SelectionStrategy>, Rnd: rand::Rng>(
         &mut self,
-        curve: SelectionCurve,
+        strategy: S,
         rng: &mut Rnd,
     ) -> Option<Individual<T, R>> {
         if !self.individuals_are_sorted {
             return None;
         }
 
-        let max = self.individuals.len();
-        if max == 0 {
+        if self.individuals.is_empty() {
             None
         } else {
-            Some(self.individuals.remove(curve.pick_one_index(rng, max)))
+            let index = self.pick_one_index(&strategy.into(), rng);
+            Some(self.individuals.remove(index))
+        }
+    }
+
+    // Picks the index of one of the sorted individuals. The temperature of a Boltzmann curve is set by the number of
+    // generations the island has been bred for, so it starts at its initial value when the first generation is bred.
+    fn pick_one_index<Rnd: rand::Rng>(&self, strategy: &SelectionStrategy, rng: &mut Rnd) -> usize {
+        match strategy {
+            SelectionStrategy::Curve(curve) if curve.uses_scores() => {
+                let scores: Vec<u64> = self
+                    .individuals
+                    .iter()
+                    .map(|individual| self.score_individual(individual))
+                    .collect();
+                curve.pick_one_scored_index(rng, &scores[..], self.generations.saturating_sub(1))
+            }
+            strategy => strategy.pick_one_index(rng, self.individuals.len()),
         }
     }
 
@@ -155,12 +875,72 @@ impl<T, R: RunResult> Island<T, R> {
         self.future.push(individual);
     }
 
-    /// Returns the score for the individual specified by index, or None if the index is out of bounds
+    // Scores any individual that has been run by this island, even one that is not part of the generation, less any
+    // numeric event penalty
+    pub(crate) fn score_individual(&self, individual: &Individual<T, R>) -> u64 {
+        let penalty = self
+            .numeric_event_penalty
+            .saturating_mul(individual.get_numeric_events().total() as u64);
+        self.functions.score_individual(individual).saturating_sub(penalty)
+    }
+
+    /// Returns the score for the individual specified by index, or None if the index is out of bounds. The score
+    /// includes any numeric event penalty.
     pub fn score_for_individual(&self, index: usize) -> Option<u64> {
         if let Some(individual) = self.get_one_individual(index) {
-            Some(self.functions.score_individual(individual))
+            Some(self.score_individual(individual))
         } else {
             None
         }
     }
 }
+
+// Runs the evaluations `max_in_flight` at a time, polling each one in flight in turn. An evaluation that yields at the
+// end of its time slice goes to the back of the line. Returns false if any of the evaluations returned false.
+#[cfg(feature = "async")]
+async fn take_turns<F, I>(evaluations: I, max_in_flight: usize) -> bool
+where
+    F: std::future::Future<Output = bool>,
+    I: Iterator<Item = F>,
+{
+    use std::collections::VecDeque;
+    use std::future::Future;
+    use std::task::Poll;
+
+    let mut waiting = evaluations.peekable();
+    let mut in_flight = VecDeque::new();
+    let mut all_ran = true;
+    futures::future::poll_fn(|cx| {
+        loop {
+            while in_flight.len() < max_in_flight.max(1) {
+                match waiting.next() {
+                    Some(evaluation) => in_flight.push_back(Box::pin(evaluation)),
+                    None => break,
+                }
+            }
+
+            // Give every evaluation in flight one turn. Each one that yielded has already woken the task.
+            let mut finished = false;
+            for _ in 0..in_flight.len() {
+                let mut evaluation = in_flight.pop_front().unwrap();
+                match evaluation.as_mut().poll(cx) {
+                    Poll::Ready(ran) => {
+                        all_ran &= ran;
+                        finished = true;
+                    }
+                    Poll::Pending => in_flight.push_back(evaluation),
+                }
+            }
+
+            if in_flight.is_empty() && waiting.peek().is_none() {
+                return Poll::Ready(all_ran);
+            }
+
+            // Start the next evaluations right away in place of the ones that finished
+            if !finished || waiting.peek().is_none() {
+                return Poll::Pending;
+            }
+        }
+    })
+    .await
+}
@@ -0,0 +1,147 @@
+#[cfg(feature = "async")]
+use crate::AsyncIslandCallbacks;
+use crate::{CallbackState, EvaluationContext, Individual, IslandCallbacks, RunResult};
+
+// The callbacks an island was created with. Under the `async` feature an island may use either kind of callbacks and
+// the sync callbacks are simply called from the async generation loop.
+pub(crate) enum IslandFunctions<T, R: RunResult> {
+    Sync(Box<dyn IslandCallbacks<T, R>>),
+    #[cfg(feature = "async")]
+    Async(Box<dyn AsyncIslandCallbacks<T, R>>),
+}
+
+impl<T, R: RunResult> IslandFunctions<T, R> {
+    #[cfg(not(feature = "async"))]
+    pub fn pre_generation_run(&mut self, individuals: &[Individual<T, R>]) {
+        match self {
+            IslandFunctions::Sync(functions) => functions.pre_generation_run(individuals),
+        }
+    }
+
+    #[cfg(not(feature = "async"))]
+    pub fn run_individual(&mut self, individual: &mut Individual<T, R>) {
+        individual.begin_evaluation();
+        match self {
+            IslandFunctions::Sync(functions) => functions.run_individual(individual),
+        }
+        self.report_individual(individual);
+    }
+
+    #[cfg(not(feature = "async"))]
+    pub fn post_generation_run(&mut self, individuals: &[Individual<T, R>]) {
+        match self {
+            IslandFunctions::Sync(functions) => functions.post_generation_run(individuals),
+        }
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn pre_generation_run(&mut self, individuals: &[Individual<T, R>]) {
+        match self {
+            IslandFunctions::Sync(functions) => functions.pre_generation_run(individuals),
+            IslandFunctions::Async(functions) => functions.pre_generation_run(individuals).await,
+        }
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn run_individual(&mut self, individual: &mut Individual<T, R>) {
+        individual.begin_evaluation();
+        match self {
+            IslandFunctions::Sync(functions) => functions.run_individual(individual),
+            IslandFunctions::Async(functions) => functions.run_individual(individual).await,
+        }
+        self.report_individual(individual);
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn post_generation_run(&mut self, individuals: &[Individual<T, R>]) {
+        match self {
+            IslandFunctions::Sync(functions) => functions.post_generation_run(individuals),
+            IslandFunctions::Async(functions) => functions.post_generation_run(individuals).await,
+        }
+    }
+
+    // Passes the result of an individual that was just run to `on_individual_evaluated`
+    fn report_individual(&mut self, individual: &Individual<T, R>) {
+        if let Some(result) = individual.get_run_result() {
+            match self {
+                IslandFunctions::Sync(functions) => functions.on_individual_evaluated(individual, result),
+                #[cfg(feature = "async")]
+                IslandFunctions::Async(functions) => functions.on_individual_evaluated(individual, result),
+            }
+        }
+    }
+
+    pub fn use_fitness_cases(&mut self, cases: &[usize]) {
+        match self {
+            IslandFunctions::Sync(functions) => functions.use_fitness_cases(cases),
+            #[cfg(feature = "async")]
+            IslandFunctions::Async(functions) => functions.use_fitness_cases(cases),
+        }
+    }
+
+    pub fn use_difficulty_level(&mut self, level: usize) {
+        match self {
+            IslandFunctions::Sync(functions) => functions.use_difficulty_level(level),
+            #[cfg(feature = "async")]
+            IslandFunctions::Async(functions) => functions.use_difficulty_level(level),
+        }
+    }
+
+    pub fn use_evaluation_context(&mut self, context: &EvaluationContext) {
+        match self {
+            IslandFunctions::Sync(functions) => functions.use_evaluation_context(context),
+            #[cfg(feature = "async")]
+            IslandFunctions::Async(functions) => functions.use_evaluation_context(context),
+        }
+    }
+
+    pub fn is_good_enough(&self, individual: &Individual<T, R>) -> bool {
+        match self {
+            IslandFunctions::Sync(functions) => functions.is_good_enough(individual),
+            #[cfg(feature = "async")]
+            IslandFunctions::Async(functions) => functions.is_good_enough(individual),
+        }
+    }
+
+    pub fn sort_individuals(&self, a: &Individual<T, R>, b: &Individual<T, R>) -> std::cmp::Ordering {
+        match self {
+            IslandFunctions::Sync(functions) => functions.sort_individuals(a, b),
+            #[cfg(feature = "async")]
+            IslandFunctions::Async(functions) => functions.sort_individuals(a, b),
+        }
+    }
+
+    pub fn score_individual(&self, individual: &Individual<T, R>) -> u64 {
+        match self {
+            IslandFunctions::Sync(functions) => functions.score_individual(individual),
+            #[cfg(feature = "async")]
+            IslandFunctions::Async(functions) => functions.score_individual(individual),
+        }
+    }
+
+    pub fn save_state(&mut self, state: &mut CallbackState<T, R>) {
+        match self {
+            IslandFunctions::Sync(functions) => functions.save_state(state),
+            #[cfg(feature = "async")]
+            IslandFunctions::Async(functions) => functions.save_state(state),
+        }
+    }
+
+    pub fn restore_state(&mut self, state: &mut CallbackState<T, R>) -> anyhow::Result<()> {
+        match self {
+            IslandFunctions::Sync(functions) => functions.restore_state(state),
+            #[cfg(feature = "async")]
+            IslandFunctions::Async(functions) => functions.restore_state(state),
+        }
+    }
+}
+
+impl<T, R: RunResult> Clone for IslandFunctions<T, R> {
+    fn clone(&self) -> Self {
+        match self {
+            IslandFunctions::Sync(functions) => IslandFunctions::Sync(functions.clone()),
+            #[cfg(feature = "async")]
+            IslandFunctions::Async(functions) => IslandFunctions::Async(functions.clone()),
+        }
+    }
+}
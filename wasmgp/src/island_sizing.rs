@@ -0,0 +1,128 @@
+use crate::{IndividualId, IslandId};
+
+/// Moves individuals from islands that rarely produce the best programs to islands that often do. After every
+/// generation the champion of each island is offered to a hall of fame, which keeps the `hall_of_fame_size` highest
+/// scoring champions. Every `every_generations` generations, the `individuals_per_island` of all of the islands are
+/// shared out again: each island keeps `min_individuals`, and the rest go to the islands in proportion to how many of
+/// the hall of fame they produced. The hall of fame then starts over, so only recent champions count. When no island
+/// has a champion in the hall of fame, every island goes back to `individuals_per_island`.
+///
+/// The champions of different islands are compared by the scores from their own callbacks, so the islands should score
+/// individuals in the same way.
+/// ```
+/// use wasmgp::*;
+///
+/// let config = WorldConfiguration {
+///     individuals_per_island: 100,
+///     island_sizing: Some(IslandSizing::new(10, 20, 25)),
+///     ..WorldConfiguration::default()
+/// };
+/// assert!(World::<(), EmptyRunResult>::new(config).is_ok());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IslandSizing {
+    /// The number of generations between each sharing out of the individuals. Must be at least one.
+    pub every_generations: usize,
+
+    /// The number of champions kept in the hall of fame. Must be at least one.
+    pub hall_of_fame_size: usize,
+
+    /// The fewest individuals an island is given. Must be at least one and at most `individuals_per_island`.
+    pub min_individuals: usize,
+}
+
+impl IslandSizing {
+    pub fn new(every_generations: usize, hall_of_fame_size: usize, min_individuals: usize) -> IslandSizing {
+        IslandSizing {
+            every_generations,
+            hall_of_fame_size,
+            min_individuals,
+        }
+    }
+}
+
+// The highest scoring champions of the current sizing period. An individual that stays the champion of its island for
+// several generations is only entered once.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct HallOfFame {
+    entries: Vec<(u64, IndividualId, IslandId)>,
+}
+
+impl HallOfFame {
+    pub fn offer(&mut self, island: IslandId, individual: IndividualId, score: u64, size: usize) {
+        if self.entries.iter().any(|(_, id, _)| *id == individual) {
+            return;
+        }
+        self.entries.push((score, individual, island));
+        self.entries.sort_by(|a, b| b.0.cmp(&a.0));
+        self.entries.truncate(size);
+    }
+
+    // The number of entries each island produced
+    pub fn counts(&self, number_of_islands: usize) -> Vec<usize> {
+        let mut counts = vec![0; number_of_islands];
+        for (_, _, island) in self.entries.iter() {
+            if let Some(count) = counts.get_mut(*island) {
+                *count += 1;
+            }
+        }
+        counts
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    // The score, individual and island of every entry, highest score first
+    pub fn entries(&self) -> &[(u64, IndividualId, IslandId)] {
+        &self.entries[..]
+    }
+
+    pub fn from_entries(entries: Vec<(u64, IndividualId, IslandId)>) -> HallOfFame {
+        HallOfFame { entries }
+    }
+}
+
+// Shares out `per_island` individuals for every island. Each island gets `min_individuals` and a share of the rest in
+// proportion to its count, with the leftovers of the rounding going to the largest remainders. When every count is
+// zero, every island gets `per_island`.
+pub(crate) fn share_individuals(per_island: usize, min_individuals: usize, counts: &[usize]) -> Vec<usize> {
+    let total_count: usize = counts.iter().sum();
+    if total_count == 0 {
+        return vec![per_island; counts.len()];
+    }
+    let extra = (per_island - min_individuals) * counts.len();
+    let mut sizes: Vec<usize> = counts
+        .iter()
+        .map(|count| min_individuals + extra * count / total_count)
+        .collect();
+    let mut by_remainder: Vec<usize> = (0..counts.len()).collect();
+    by_remainder.sort_by_key(|island| std::cmp::Reverse(extra * counts[*island] % total_count));
+    let leftover = min_individuals * counts.len() + extra - sizes.iter().sum::<usize>();
+    for island in by_remainder.into_iter().take(leftover) {
+        sizes[island] += 1;
+    }
+    sizes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn individuals_follow_the_hall_of_fame() {
+        let mut hall = HallOfFame::default();
+        hall.offer(0, 1, 50, 3);
+        hall.offer(1, 2, 70, 3);
+        hall.offer(1, 2, 70, 3);
+        hall.offer(1, 3, 60, 3);
+        hall.offer(2, 4, 10, 3);
+        assert_eq!(vec![1, 2, 0], hall.counts(3));
+
+        // 30 individuals with 4 each leaves 18 to share out, 6 and 12 of them
+        assert_eq!(vec![10, 16, 4], share_individuals(10, 4, &[1, 2, 0]));
+        assert_eq!(vec![10, 10, 10], share_individuals(10, 4, &[0, 0, 0]));
+        let sizes = share_individuals(10, 1, &[1, 1, 1]);
+        assert_eq!(30, sizes.iter().sum::<usize>());
+    }
+}
@@ -0,0 +1,215 @@
+use crate::{CallbackState, Individual, RunResult, WasmgpError};
+use anyhow::Result;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use std::cmp::Ordering;
+
+/// A pool of frozen past champions for two-player domains, where an individual can only be judged by playing against
+/// other programs. Each generation plays against a random sample of the league, and the champion of every
+/// `promote_every` generations joins the league. Once the league is full, each new champion replaces the oldest.
+///
+/// A league is meant to be held by the island callbacks:
+/// - `pre_generation_run` calls `draw_opponents`, so that every individual of the generation meets the same opponents
+/// - `run_individual` calls `play` and keeps the average result in the individual's RunResult
+/// - `post_generation_run` calls `end_generation`, which promotes the champion when the schedule calls for it
+/// - `save_state` and `restore_state` call the league's methods of the same names, so that checkpoints keep the league
+///
+/// Until the first champion is promoted, there is nobody to play and `play` returns None. Use `promote` to seed the
+/// league with a known opponent.
+pub struct League<T, R: RunResult> {
+    capacity: usize,
+    sample_size: usize,
+    promote_every: usize,
+    generations_until_promotion: usize,
+    members: Vec<Individual<T, R>>,
+    opponents: Vec<usize>,
+    rng: SmallRng,
+}
+
+impl<T, R: RunResult> League<T, R> {
+    /// Creates an empty league that holds up to `capacity` champions, samples `sample_size` of them as the opponents of
+    /// each generation, and promotes a champion every `promote_every` generations
+    pub fn new(capacity: usize, sample_size: usize, promote_every: usize) -> League<T, R> {
+        League {
+            capacity,
+            sample_size,
+            promote_every: promote_every.max(1),
+            generations_until_promotion: promote_every.max(1),
+            members: vec![],
+            opponents: vec![],
+            rng: SmallRng::from_entropy(),
+        }
+    }
+
+    /// Captures the state of the random number generator that draws the opponents, and reseeds the generator from it.
+    /// See `World::capture_rng_state`.
+    pub fn capture_rng_state(&mut self) -> u64 {
+        let state = self.rng.gen();
+        self.rng = SmallRng::seed_from_u64(state);
+        state
+    }
+
+    /// Resumes drawing opponents from a state returned by `capture_rng_state`
+    pub fn restore_rng_state(&mut self, state: u64) {
+        self.rng = SmallRng::seed_from_u64(state);
+    }
+
+    /// Saves the members of the league, the opponents drawn for this generation, the generations left until the next
+    /// promotion and the random number generator, for island callbacks that hold a league to call from
+    /// `IslandCallbacks::save_state`, so that a run resumed from a checkpoint plays the same opponents
+    pub fn save_state(&mut self, state: &mut CallbackState<T, R>) {
+        state.push_number(self.capture_rng_state());
+        state.push_number(self.generations_until_promotion as u64);
+        state.push_number(self.members.len() as u64);
+        for member in self.members.iter() {
+            state.push_individual(member);
+        }
+        state.push_number(self.opponents.len() as u64);
+        for opponent in self.opponents.iter() {
+            state.push_number(*opponent as u64);
+        }
+    }
+
+    /// Restores what `save_state` saved, from `IslandCallbacks::restore_state`. The members replace any the league
+    /// already has.
+    pub fn restore_state(&mut self, state: &mut CallbackState<T, R>) -> Result<()> {
+        self.restore_rng_state(state.pop_number()?);
+        self.generations_until_promotion = state.pop_number()? as usize;
+        self.members = (0..state.pop_number()?)
+            .map(|_| state.pop_individual())
+            .collect::<Result<_>>()?;
+        self.opponents = (0..state.pop_number()?)
+            .map(|_| state.pop_number().map(|opponent| opponent as usize))
+            .collect::<Result<_>>()?;
+        if self.opponents.iter().any(|opponent| *opponent >= self.members.len()) {
+            return Err(
+                WasmgpError::InvalidCheckpoint("an opponent of the league is not one of its members".into()).into(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Borrows the champions in the league, oldest first
+    pub fn members(&self) -> &[Individual<T, R>] {
+        &self.members[..]
+    }
+
+    /// Adds a clone of the individual to the league, replacing the oldest member if the league is full. The clone is
+    /// frozen: later changes to the individual do not affect it.
+    pub fn promote(&mut self, champion: &Individual<T, R>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.members.len() >= self.capacity {
+            self.members.remove(0);
+        }
+        self.members.push(champion.clone());
+        self.opponents.clear();
+    }
+
+    /// Picks the opponents for the next generation: a random sample of up to `sample_size` members
+    pub fn draw_opponents(&mut self) {
+        let count = self.sample_size.min(self.members.len());
+        self.opponents = rand::seq::index::sample(&mut self.rng, self.members.len(), count).into_vec();
+    }
+
+    /// Plays the individual against each opponent drawn for this generation and returns the average of the results.
+    /// The `game` is passed the individual and an opponent, and returns the result for the individual. Returns None if
+    /// there are no opponents.
+    pub fn play<F>(&mut self, individual: &mut Individual<T, R>, mut game: F) -> Option<f64>
+    where
+        F: FnMut(&mut Individual<T, R>, &mut Individual<T, R>) -> f64,
+    {
+        if self.opponents.is_empty() {
+            self.draw_opponents();
+        }
+        if self.opponents.is_empty() {
+            return None;
+        }
+        let mut total = 0.0;
+        for index in self.opponents.iter() {
+            total += game(individual, &mut self.members[*index]);
+        }
+        Some(total / self.opponents.len() as f64)
+    }
+
+    /// Counts a generation toward the promotion schedule, promoting the most fit individual when it is due. The
+    /// `compare` function orders individuals from least fit to most fit, as `IslandCallbacks::sort_individuals` does.
+    /// Returns true if a champion was promoted.
+    pub fn end_generation<F>(&mut self, individuals: &[Individual<T, R>], compare: F) -> bool
+    where
+        F: Fn(&Individual<T, R>, &Individual<T, R>) -> Ordering,
+    {
+        self.generations_until_promotion -= 1;
+        if self.generations_until_promotion > 0 {
+            return false;
+        }
+        self.generations_until_promotion = self.promote_every;
+        match individuals.iter().max_by(|a, b| compare(a, b)) {
+            Some(champion) => {
+                self.promote(champion);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl<T, R: RunResult> Clone for League<T, R> {
+    fn clone(&self) -> Self {
+        League {
+            capacity: self.capacity,
+            sample_size: self.sample_size,
+            promote_every: self.promote_every,
+            generations_until_promotion: self.generations_until_promotion,
+            members: self.members.clone(),
+            opponents: self.opponents.clone(),
+            rng: self.rng.clone(),
+        }
+    }
+}
+
+#[cfg(all(test, not(feature = "async")))]
+mod tests {
+    use crate::*;
+
+    #[derive(Clone)]
+    struct DoNothing;
+
+    impl IslandCallbacks<(), EmptyRunResult> for DoNothing {
+        fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+            Box::new(Clone::clone(self))
+        }
+
+        fn run_individual(&mut self, _individual: &mut Individual<(), EmptyRunResult>) {}
+    }
+
+    #[test]
+    fn a_restored_league_plays_the_same_opponents() {
+        let mut world = World::<(), EmptyRunResult>::new(WorldConfiguration::default()).unwrap();
+        let mut league = League::new(10, 3, 4);
+        for _ in 0..10 {
+            let (champion, _) = world.evaluate(&[ConstOne::new(0)], &mut DoNothing).unwrap();
+            league.promote(&champion);
+        }
+        league.draw_opponents();
+        league.end_generation(&[], |_, _| std::cmp::Ordering::Equal);
+        let mut state = CallbackState::default();
+        league.save_state(&mut state);
+
+        let ids = |league: &League<(), EmptyRunResult>| -> Vec<IndividualId> {
+            league.members().iter().map(|member| member.get_id()).collect()
+        };
+        let mut resumed = League::new(10, 3, 4);
+        resumed.restore_state(&mut state).unwrap();
+        assert_eq!(ids(&league), ids(&resumed));
+        assert_eq!(league.opponents, resumed.opponents);
+        assert_eq!(3, resumed.generations_until_promotion);
+        for _ in 0..5 {
+            league.draw_opponents();
+            resumed.draw_opponents();
+            assert_eq!(league.opponents, resumed.opponents);
+        }
+        assert!(resumed.restore_state(&mut state).is_err());
+    }
+}
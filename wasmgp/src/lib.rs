@@ -1,60 +1,181 @@
+#[cfg(feature = "async")]
+mod async_island_callbacks;
+#[cfg(feature = "benchmarks")]
+pub mod benchmarks;
+mod breeding_buffers;
+mod cancellation_token;
+mod champion_listing;
+mod checkpoint;
 mod code;
 mod code_arithmetic;
+#[cfg(feature = "bit_ops")]
 mod code_bit_ops;
 mod code_builder;
 mod code_compare;
 mod code_const;
 mod code_context;
 mod code_control;
+mod code_debugger;
+#[cfg(feature = "float_ops")]
 mod code_float;
+mod code_listing;
+mod code_provenance;
+mod code_reduction;
+mod code_slot_range;
 mod code_stream;
+mod code_weight_adaptation;
+mod conversion_profile;
 mod convert;
+mod cost_model;
+mod crossover_cuts;
+mod curriculum;
+#[cfg(feature = "differential")]
+mod differential;
+mod emission_template;
+mod ensemble;
 mod error;
+mod evaluation_context;
+mod evaluation_error;
+mod evaluation_stage;
+mod fitness_case_schedule;
+mod fitness_domain;
 mod function_signature;
+mod generation_stats;
 mod genetic_engine;
 mod genetic_engine_configuration;
 mod genetic_operation;
+mod holdout_result;
+mod host_call_log;
+mod import_options;
 mod indentation;
 mod individual;
+mod initialization;
+mod instruction_set;
 mod island;
 mod island_callbacks;
+mod island_functions;
+mod island_sizing;
+mod league;
+mod local_search;
 mod migration_algorithm;
+mod migration_event;
+mod migration_payload;
+mod module_metadata;
+mod numeric_events;
+mod opcode_usage;
+mod operator_stats;
+mod parent_pairing;
+mod reference_evaluator;
+mod run_policy;
+mod run_report;
 mod run_result;
+mod security_report;
 mod selection_curve;
+mod selection_strategy;
+mod shared_fixture;
 mod slot;
 mod slot_init;
+mod slot_liveness;
+#[cfg(feature = "sqlite")]
+mod sqlite_archive;
+mod stop_criteria;
+mod subtree_archive;
 mod threading_model;
+mod tournament_runner;
 mod value_type;
+mod warm_up;
+#[cfg(feature = "wasi")]
+mod wasi_command;
 mod wasm_ast_assumptions;
+mod weighted_fitness;
+#[cfg(feature = "worker")]
+mod worker;
 mod world;
 mod world_configuration;
 
+#[cfg(feature = "async")]
+pub use async_island_callbacks::AsyncIslandCallbacks;
+pub use breeding_buffers::BufferCounts;
+pub use cancellation_token::CancellationToken;
+pub use champion_listing::ChampionListing;
+pub use checkpoint::{CallbackState, CheckpointSchedule};
 pub use code::Code;
 pub use code_arithmetic::*;
+#[cfg(feature = "bit_ops")]
 pub use code_bit_ops::*;
 pub use code_builder::CodeBuilder;
 pub use code_compare::*;
 pub use code_const::*;
 pub use code_context::CodeContext;
 pub use code_control::*;
+pub use code_debugger::{CodeDebugger, DebugStep, HostCall, LoopFrame};
+#[cfg(feature = "float_ops")]
 pub use code_float::*;
+pub use code_listing::parse_code_listing;
+pub use code_provenance::*;
+pub use code_reduction::reduce_code;
+pub use code_slot_range::*;
 pub use code_stream::*;
-pub use error::WasmgpError;
+pub use code_weight_adaptation::CodeWeightAdaptation;
+pub use conversion_profile::ConversionProfile;
+pub use cost_model::CostModel;
+pub use crossover_cuts::{CrossoverCuts, CutPredicate};
+pub use curriculum::Curriculum;
+#[cfg(feature = "differential")]
+pub use differential::{DifferentialOptions, DifferentialReport, Divergence};
+pub use ensemble::{majority_vote, mean_output, Ensemble};
+pub use error::{CodeDiagnostic, WasmgpError};
+pub use evaluation_context::EvaluationContext;
+pub use evaluation_error::{EvaluationError, EvaluationLimit};
+pub use evaluation_stage::EvaluationStage;
+pub use fitness_case_schedule::FitnessCaseSchedule;
+pub use fitness_domain::{DomainRegistry, FitnessDomain};
 pub use function_signature::FunctionSignature;
+pub use generation_stats::{GenerationRecord, GenerationStats, EDIT_DISTANCE_SAMPLE_SIZE};
 pub use genetic_engine::GeneticEngine;
 pub use genetic_engine_configuration::*;
 pub use genetic_operation::*;
+pub use holdout_result::HoldoutResult;
+pub use host_call_log::HostCallRecord;
+pub use import_options::{CallLimit, CallLimitPolicy, ImportOptions};
 pub use indentation::Indentation;
-pub use individual::Individual;
+pub use individual::{Individual, IndividualId};
+pub use initialization::Initialization;
+pub use instruction_set::{InstructionSet, INSTRUCTION_SEMANTICS_VERSION};
 pub use island::Island;
 pub use island_callbacks::IslandCallbacks;
+pub use island_sizing::IslandSizing;
+pub use league::League;
+pub use local_search::{simplify_code, LocalSearch};
 pub use migration_algorithm::MigrationAlgorithm;
+pub use migration_event::MigrationEvent;
+pub use migration_payload::MigrationPayload;
+pub use module_metadata::ModuleMetadata;
+pub use numeric_events::NumericEvents;
+pub use opcode_usage::{OpcodeCounts, OpcodeUsage};
+pub use operator_stats::{Operator, OperatorStats};
+pub use parent_pairing::{PairingMeasure, ParentPairing};
+pub use reference_evaluator::{HostFunction, ReferenceEvaluator, SlotValue};
+pub use run_policy::{PolicyAction, PolicyCondition, PolicyRule, RunPolicy};
 pub use run_result::*;
-pub use selection_curve::SelectionCurve;
+pub use security_report::{ImportReport, SecurityReport};
+pub use selection_curve::{CustomCurve, SelectionCurve, TemperatureSchedule};
+pub use selection_strategy::SelectionStrategy;
+pub use shared_fixture::SharedFixture;
 pub use slot::*;
 pub use slot_init::*;
+pub use slot_liveness::{remove_dead_writes, SlotLiveness};
+#[cfg(feature = "sqlite")]
+pub use sqlite_archive::SqliteArchive;
+pub use stop_criteria::{StopCriteria, StopReason};
+pub use subtree_archive::SubtreeArchive;
 pub use threading_model::ThreadingModel;
+pub use tournament_runner::{EntrantComparison, EntrantResult, TournamentReport, TournamentRunner};
 pub use value_type::ValueType;
+pub use warm_up::WarmUp;
+pub use weighted_fitness::{FitnessCombinator, Goal, WeightedFitness};
+#[cfg(feature = "worker")]
+pub use worker::Worker;
 pub use world::*;
 pub use world_configuration::WorldConfiguration;
 
@@ -0,0 +1,209 @@
+use crate::*;
+use rand::Rng;
+
+/// Settings for a memetic step that runs after every generation has been evaluated. The most fit `individuals` of each
+/// island are simplified, and then their constants are tuned by `tuning_steps` rounds of hill climbing: one constant is
+/// nudged, the result is run through the island's `run_individual`, and the change is kept if the island sorts it as
+/// more fit. A variant that ends up at least as fit as the individual it came from, with different code, is added to
+/// the island's next generation.
+///
+/// Every round re-runs an individual, so the cost of a generation goes up by about `individuals * (tuning_steps + 1)`
+/// evaluations on each island.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LocalSearch {
+    pub individuals: usize,
+    pub tuning_steps: usize,
+}
+
+impl LocalSearch {
+    pub fn new(individuals: usize, tuning_steps: usize) -> LocalSearch {
+        LocalSearch {
+            individuals,
+            tuning_steps,
+        }
+    }
+}
+
+/// Removes code that has no effect: blocks without a body, an empty else branch, copies of a slot to itself, and breaks
+/// outside of a loop.
+pub fn simplify_code(code: &[Code]) -> Vec<Code> {
+    simplify_list(code, false)
+}
+
+fn simplify_list(code: &[Code], in_loop: bool) -> Vec<Code> {
+    let mut simplified = vec![];
+    for c in code.iter() {
+        let c = match c {
+            Code::If(c) => If::new(c.if_not_zero(), simplify_list(c.do_this(), in_loop)),
+            Code::IfElse(c) => IfElse::new(
+                c.if_not_zero(),
+                simplify_list(c.do_this(), in_loop),
+                simplify_list(c.else_do_this(), in_loop),
+            ),
+            Code::DoUntil(c) => DoUntil::new(c.until_not_zero(), simplify_list(c.do_this(), true)),
+            Code::DoWhile(c) => DoWhile::new(c.while_not_zero(), simplify_list(c.do_this(), true)),
+            Code::DoFor(c) => DoFor::new(c.times(), simplify_list(c.do_this(), true)),
+            Code::Provenance(c) => Provenance::new(c.tag(), simplify_list(c.do_this(), in_loop)),
+            _ => c.clone(),
+        };
+        match &c {
+            Code::If(c) if c.do_this().is_empty() => {}
+            Code::IfElse(c) if c.do_this().is_empty() && c.else_do_this().is_empty() => {}
+            Code::IfElse(c) if c.else_do_this().is_empty() => {
+                simplified.push(If::new(c.if_not_zero(), c.do_this().to_vec()))
+            }
+            Code::DoFor(c) if c.do_this().is_empty() => {}
+            Code::Provenance(c) if c.do_this().is_empty() => {}
+            Code::CopySlot(c) if c.source() == c.destination() => {}
+            Code::Break(_) | Code::BreakIf(_) if !in_loop => {}
+            _ => simplified.push(c),
+        }
+    }
+    simplified
+}
+
+// Returns a copy of the code with one of its constants nudged up or down, or None if the code has no constants. Integers
+// move by up to an eighth of their value, and floats by a tenth.
+pub(crate) fn tune_constant<Rnd: Rng>(code: &[Code], rng: &mut Rnd) -> Option<Vec<Code>> {
+    let mut constants = 0;
+    for c in code.iter() {
+        c.walk(&mut |c| {
+            if is_constant(c) {
+                constants += 1;
+            }
+        });
+    }
+    if constants == 0 {
+        return None;
+    }
+    let mut target = rng.gen_range(0..constants);
+    Some(nudge_constant(code, &mut target, rng))
+}
+
+fn is_constant(code: &Code) -> bool {
+    matches!(
+        code,
+        Code::ConstI32(_) | Code::ConstI64(_) | Code::ConstF32(_) | Code::ConstF64(_)
+    )
+}
+
+// Rebuilds the code, nudging the constant that is `target` constants further on
+fn nudge_constant<Rnd: Rng>(code: &[Code], target: &mut usize, rng: &mut Rnd) -> Vec<Code> {
+    let mut nudged = vec![];
+    for c in code.iter() {
+        let c = match c {
+            Code::If(c) => If::new(c.if_not_zero(), nudge_constant(c.do_this(), target, rng)),
+            Code::IfElse(c) => {
+                let do_this = nudge_constant(c.do_this(), target, rng);
+                IfElse::new(c.if_not_zero(), do_this, nudge_constant(c.else_do_this(), target, rng))
+            }
+            Code::DoUntil(c) => DoUntil::new(c.until_not_zero(), nudge_constant(c.do_this(), target, rng)),
+            Code::DoWhile(c) => DoWhile::new(c.while_not_zero(), nudge_constant(c.do_this(), target, rng)),
+            Code::DoFor(c) => DoFor::new(c.times(), nudge_constant(c.do_this(), target, rng)),
+            Code::Provenance(c) => Provenance::new(c.tag(), nudge_constant(c.do_this(), target, rng)),
+            c if is_constant(c) && *target > 0 => {
+                *target -= 1;
+                c.clone()
+            }
+            c if is_constant(c) => {
+                // Move the target past every remaining constant so that only this one is nudged
+                *target = usize::MAX;
+                let up = rng.gen_bool(0.5);
+                match c {
+                    Code::ConstI32(c) => {
+                        let step = rng.gen_range(1..=(c.value().unsigned_abs() / 8).max(1)) as i32;
+                        ConstI32::new(
+                            c.slot(),
+                            if up {
+                                c.value().wrapping_add(step)
+                            } else {
+                                c.value().wrapping_sub(step)
+                            },
+                        )
+                    }
+                    Code::ConstI64(c) => {
+                        let step = rng.gen_range(1..=(c.value().unsigned_abs() / 8).max(1)) as i64;
+                        ConstI64::new(
+                            c.slot(),
+                            if up {
+                                c.value().wrapping_add(step)
+                            } else {
+                                c.value().wrapping_sub(step)
+                            },
+                        )
+                    }
+                    Code::ConstF32(c) => {
+                        let step = (c.value().abs() * 0.1).max(0.1);
+                        ConstF32::new(c.slot(), if up { c.value() + step } else { c.value() - step })
+                    }
+                    Code::ConstF64(c) => {
+                        let step = (c.value().abs() * 0.1).max(0.1);
+                        ConstF64::new(c.slot(), if up { c.value() + step } else { c.value() - step })
+                    }
+                    _ => unreachable!(),
+                }
+            }
+            c => c.clone(),
+        };
+        nudged.push(c);
+    }
+    nudged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn simplify_removes_code_without_effect() {
+        let code = [
+            Break::new(),
+            CopySlot::new(1, 1),
+            If::new(0, vec![]),
+            IfElse::new(0, vec![Add::new(0, 1, 2)], vec![BreakIf::new(3)]),
+            DoFor::new(3, vec![If::new(1, vec![CopySlot::new(2, 2)])]),
+            DoUntil::new(0, vec![Break::new(), CopySlot::new(1, 2)]),
+            Return::new(),
+        ];
+        assert_eq!(
+            vec![
+                If::new(0, vec![Add::new(0, 1, 2)]),
+                DoUntil::new(0, vec![Break::new(), CopySlot::new(1, 2)]),
+                Return::new(),
+            ],
+            simplify_code(&code[..])
+        );
+    }
+
+    #[test]
+    fn tuning_nudges_exactly_one_constant() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        assert_eq!(None, tune_constant(&[Add::new(0, 1, 2)], &mut rng));
+
+        let code = [
+            ConstI32::new(0, 100),
+            DoFor::new(2, vec![ConstI64::new(1, 0), ConstF64::new(2, 10.0)]),
+        ];
+        for _ in 0..20 {
+            let tuned = tune_constant(&code[..], &mut rng).unwrap();
+            let changed: Vec<(&Code, &Code)> = code.iter().zip(tuned.iter()).filter(|(a, b)| a != b).collect();
+            assert_eq!(1, changed.len());
+            match changed[0] {
+                (Code::ConstI32(before), Code::ConstI32(after)) => {
+                    assert!((before.value() - after.value()).abs() <= 12)
+                }
+                (Code::DoFor(before), Code::DoFor(after)) => {
+                    let changed = before
+                        .do_this()
+                        .iter()
+                        .zip(after.do_this().iter())
+                        .filter(|(a, b)| a != b);
+                    assert_eq!(1, changed.count());
+                }
+                _ => panic!("the wrong code was tuned"),
+            }
+        }
+    }
+}
@@ -20,4 +20,9 @@ pub enum MigrationAlgorithm {
     /// Every individual selected for migration picks a completely random island that is not its current island and
     /// migrates to that island.
     CompletelyRandom,
+
+    /// The single most fit individual of every island is cloned to every other island. This ignores both
+    /// `number_of_individuals_migrating` and `clone_migrated_individuals`: exactly one individual always leaves each
+    /// island and it is never removed from its home island.
+    BroadcastBest,
 }
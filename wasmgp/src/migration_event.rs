@@ -0,0 +1,19 @@
+use crate::{IndividualId, IslandId};
+
+/// A record of one individual moving from one island to another during a migration. The World keeps a log of every
+/// migration so that changes in fitness on an island can be correlated with the arrival of particular migrants.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MigrationEvent {
+    /// The island the individual left (or was cloned from)
+    pub source: IslandId,
+
+    /// The island the individual was added to. The individual joins the future generation of this island.
+    pub destination: IslandId,
+
+    /// The ID of the migrating individual. Cloned migrants keep the ID of the original.
+    pub individual_id: IndividualId,
+
+    /// The generation that had just completed when the migration took place. The migrant is first evaluated on the
+    /// destination island in the following generation.
+    pub generation: usize,
+}
@@ -0,0 +1,17 @@
+/// What travels between the islands when it is time for a migration. The `migration_algorithm` decides where it goes,
+/// and the `number_of_individuals_migrating` decides how much of it leaves each island.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MigrationPayload {
+    /// Whole individuals move to the future generation of another island (default)
+    #[default]
+    Individuals,
+
+    /// Each island keeps its own copy of the `subtree_archive`, filled from its own elites, and a migration copies
+    /// subtrees from the archive of one island into the archive of another. Nothing else changes on the destination
+    /// island until its archive mutations splice the subtrees into children, which makes this a softer exchange than
+    /// moving individuals: an island can pick up a useful piece of code without being taken over by the island it came
+    /// from. Requires a `subtree_archive`, and has no effect unless the `archive_mutation_rate` is above zero.
+    ///
+    /// Subtrees are picked at random from the source archive, and the `migration_log` does not record them.
+    Fragments,
+}
@@ -0,0 +1,28 @@
+use crate::Code;
+
+/// The shape of the Wasm module compiled for an individual's code, so that a fitness function can weigh how complex a
+/// program is without counting points itself. Individuals created by a World always have it; an individual built
+/// directly from an `InstancePre` has all zeros.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ModuleMetadata {
+    /// The size of the emitted module in bytes, including the imports and exports every module of the World shares
+    pub size: usize,
+
+    /// The locals of the main function: the return and work slots and the temporary locals used by the instructions.
+    /// The parameters are not counted.
+    pub local_count: usize,
+
+    /// The most blocks nested inside of each other in the code, such as two for an If inside of a DoFor. Zero for code
+    /// without blocks.
+    pub max_nesting_depth: usize,
+}
+
+impl ModuleMetadata {
+    pub(crate) fn new(size: usize, local_count: usize, code: &[Code]) -> ModuleMetadata {
+        ModuleMetadata {
+            size,
+            local_count,
+            max_nesting_depth: code.iter().map(|c| c.nesting_depth()).max().unwrap_or(0),
+        }
+    }
+}
@@ -0,0 +1,20 @@
+/// How often the protected numeric operations of an individual's code stepped in during its most recent evaluation.
+/// They are only counted when `WorldConfiguration::numeric_telemetry` is set. A program that leans on them may score
+/// well by accident, such as by dividing by zero to leave a slot unchanged, so an island can subtract a penalty for
+/// each one with `Island::set_numeric_event_penalty`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NumericEvents {
+    /// The Divide and Remainder instructions that skipped their operation because the divisor was zero
+    pub divisions_by_zero: usize,
+
+    /// The conversions from a float to an integer where the float was NaN or out of the range of the integer, and so
+    /// was saturated to zero or to the integer's minimum or maximum
+    pub saturated_conversions: usize,
+}
+
+impl NumericEvents {
+    /// Returns the number of events of every kind
+    pub fn total(&self) -> usize {
+        self.divisions_by_zero + self.saturated_conversions
+    }
+}
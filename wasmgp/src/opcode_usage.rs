@@ -0,0 +1,175 @@
+use crate::Code;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use strum::IntoEnumIterator;
+
+/// How often each Code variant and each host import appears in a group of individuals.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OpcodeCounts {
+    /// The number of individuals that were counted
+    pub individuals: usize,
+
+    /// The number of times each Code variant appears, including those nested inside of blocks. Every variant is
+    /// listed, even the ones that do not appear at all.
+    pub code: BTreeMap<&'static str, usize>,
+
+    /// The number of `Call`s to each host import, by the name it was imported with
+    pub host_imports: BTreeMap<String, usize>,
+}
+
+impl OpcodeCounts {
+    // Starts with a count of zero for every Code variant and import
+    fn new(import_names: &[String]) -> OpcodeCounts {
+        OpcodeCounts {
+            individuals: 0,
+            code: Code::iter().map(|c| (c.name(), 0)).collect(),
+            host_imports: import_names.iter().map(|name| (name.clone(), 0)).collect(),
+        }
+    }
+
+    fn add(&mut self, code: &[Code], import_names: &[String]) {
+        self.individuals += 1;
+        for c in code.iter() {
+            c.walk(&mut |c| {
+                *self.code.entry(c.name()).or_default() += 1;
+                if let Code::Call(call) = c {
+                    if let Some(name) = import_names.get(call.function_index() as usize) {
+                        *self.host_imports.entry(name.clone()).or_default() += 1;
+                    }
+                }
+            });
+        }
+    }
+
+    // Adds the counts of another group of individuals to these
+    pub(crate) fn merge(&mut self, other: &OpcodeCounts) {
+        self.individuals += other.individuals;
+        for (name, count) in other.code.iter() {
+            *self.code.entry(name).or_default() += count;
+        }
+        for (name, count) in other.host_imports.iter() {
+            *self.host_imports.entry(name.clone()).or_default() += count;
+        }
+    }
+
+    /// The total number of code items counted
+    pub fn total(&self) -> usize {
+        self.code.values().sum()
+    }
+
+    /// The share of all counted code items that are the named Code variant, between 0.0 and 1.0
+    pub fn frequency(&self, name: &str) -> f64 {
+        let total = self.total();
+        if total == 0 {
+            0.0
+        } else {
+            self.code.get(name).copied().unwrap_or(0) as f64 / total as f64
+        }
+    }
+}
+
+/// A report of how often each Code variant and host import is used by the current generation of one island, and by its
+/// elites: the most fit `elite_individuals_per_generation` individuals.
+///
+/// The `Display` output is a table with one row for each variant or import that appears, listing its share of the code
+/// in the population and in the elites. Variants that are more common in the elites than in the population are ones
+/// that selection favors.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OpcodeUsage {
+    pub population: OpcodeCounts,
+    pub elites: OpcodeCounts,
+}
+
+impl OpcodeUsage {
+    // Counts the individuals, which are ordered from least fit to most fit when `sorted` is set. The elites are only
+    // known for a sorted population.
+    pub(crate) fn count<'a, I>(individuals: I, sorted: bool, elites: usize, import_names: &[String]) -> OpcodeUsage
+    where
+        I: DoubleEndedIterator<Item = &'a [Code]>,
+    {
+        let mut usage = OpcodeUsage {
+            population: OpcodeCounts::new(import_names),
+            elites: OpcodeCounts::new(import_names),
+        };
+        for (rank, code) in individuals.rev().enumerate() {
+            usage.population.add(code, import_names);
+            if sorted && rank < elites {
+                usage.elites.add(code, import_names);
+            }
+        }
+        usage
+    }
+}
+
+impl Display for OpcodeUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<24} {:>10} {:>10}",
+            format!(
+                "{} / {} individuals",
+                self.population.individuals, self.elites.individuals
+            ),
+            "population",
+            "elites"
+        )?;
+        for (name, count) in self.population.code.iter() {
+            let elite_count = self.elites.code.get(name).copied().unwrap_or(0);
+            if *count > 0 || elite_count > 0 {
+                writeln!(
+                    f,
+                    "{:<24} {:>9.2}% {:>9.2}%",
+                    name,
+                    100.0 * self.population.frequency(name),
+                    100.0 * self.elites.frequency(name)
+                )?;
+            }
+        }
+        for (name, count) in self.population.host_imports.iter() {
+            let elite_count = self.elites.host_imports.get(name).copied().unwrap_or(0);
+            if *count > 0 || elite_count > 0 {
+                writeln!(f, "{:<24} {:>10} {:>10}", format!("host {}", name), count, elite_count)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn counts_nested_code_and_host_imports() {
+        let imports = ["draw".to_string(), "play".to_string()];
+        let least_fit = [ConstOne::new(0), Call::new(1, vec![], vec![])];
+        let most_fit = [DoFor::new(
+            3,
+            vec![Call::new(0, vec![], vec![]), Call::new(0, vec![], vec![])],
+        )];
+        let usage = OpcodeUsage::count([&least_fit[..], &most_fit[..]].into_iter(), true, 1, &imports[..]);
+
+        assert_eq!(2, usage.population.individuals);
+        assert_eq!(3, usage.population.code["Call"]);
+        assert_eq!(1, usage.population.code["DoFor"]);
+        assert_eq!(0, usage.population.code["Add"]);
+        assert_eq!(5, usage.population.total());
+        assert_eq!(2, usage.population.host_imports["draw"]);
+        assert_eq!(1, usage.population.host_imports["play"]);
+
+        assert_eq!(1, usage.elites.individuals);
+        assert_eq!(0, usage.elites.code["ConstOne"]);
+        assert_eq!(2.0 / 3.0, usage.elites.frequency("Call"));
+        assert_eq!(0, usage.elites.host_imports["play"]);
+    }
+
+    #[test]
+    fn an_unsorted_population_has_no_elites() {
+        let code = [ConstOne::new(0)];
+        let usage = OpcodeUsage::count([&code[..]].into_iter(), false, 2, &[]);
+        assert_eq!(1, usage.population.individuals);
+        assert_eq!(0, usage.elites.individuals);
+        assert_eq!(0.0, usage.elites.frequency("ConstOne"));
+    }
+}
@@ -0,0 +1,92 @@
+use crate::{GeneticOperation, Individual, RunResult};
+
+/// How an individual was produced from its parents
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Operator {
+    /// An elite copied unchanged into the next generation
+    Reproduction,
+
+    /// `GeneticOperation::Mutation`, including the mutations of a `WarmUp::MutationOnly`
+    Mutation,
+
+    /// `GeneticOperation::ArchiveMutation`
+    ArchiveMutation,
+
+    /// `GeneticOperation::OperandMutation`
+    OperandMutation,
+
+    /// `GeneticOperation::Crossover`
+    Crossover,
+
+    /// `GeneticOperation::HomologousCrossover`
+    HomologousCrossover,
+}
+
+impl GeneticOperation {
+    /// Returns the operator of the operation, without the number of times it is applied
+    pub fn operator(&self) -> Operator {
+        match self {
+            GeneticOperation::Mutation(_) => Operator::Mutation,
+            GeneticOperation::ArchiveMutation(_) => Operator::ArchiveMutation,
+            GeneticOperation::OperandMutation(_) => Operator::OperandMutation,
+            GeneticOperation::Crossover(_) => Operator::Crossover,
+            GeneticOperation::HomologousCrossover(_) => Operator::HomologousCrossover,
+        }
+    }
+}
+
+/// How well the children produced by one operator did in a generation. A child outperforms its parents when its score
+/// is higher than the score of its most fit parent in the generation it was bred from, so the count is only meaningful
+/// while the scores of one generation can be compared with the next, such as when the fitness cases do not change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OperatorStats {
+    pub operator: Operator,
+
+    /// The number of individuals of the generation produced by the operator
+    pub children: usize,
+
+    /// The number of those children that scored higher than their most fit parent
+    pub improved: usize,
+}
+
+impl OperatorStats {
+    /// Returns the fraction of the children that outperformed their parents
+    pub fn success_rate(&self) -> f64 {
+        if self.children == 0 {
+            0.0
+        } else {
+            self.improved as f64 / self.children as f64
+        }
+    }
+}
+
+// Counts the children of each operator in a generation that has been run, in the order of `Operator`. Individuals that
+// were not bred, such as random ones and immigrants, are left out. The scores are in the same order as the individuals.
+pub(crate) fn count_operators<T, R: RunResult>(individuals: &[Individual<T, R>], scores: &[u64]) -> Vec<OperatorStats> {
+    let mut stats: Vec<OperatorStats> = vec![];
+    for (individual, score) in individuals.iter().zip(scores.iter()) {
+        let (operator, parent_score) = match individual.get_origin() {
+            Some(origin) => origin,
+            None => continue,
+        };
+        let index = match stats.binary_search_by(|s| s.operator.cmp(&operator)) {
+            Ok(index) => index,
+            Err(index) => {
+                stats.insert(
+                    index,
+                    OperatorStats {
+                        operator,
+                        children: 0,
+                        improved: 0,
+                    },
+                );
+                index
+            }
+        };
+        stats[index].children += 1;
+        if *score > parent_score {
+            stats[index].improved += 1;
+        }
+    }
+    stats
+}
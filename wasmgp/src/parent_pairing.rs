@@ -0,0 +1,178 @@
+use crate::generation_stats::structural_distance;
+use crate::{Island, RunResult, SelectionStrategy};
+
+// The number of times a mate is redrawn before a Distinct pairing settles for the first parent
+const DISTINCT_ATTEMPTS: usize = 10;
+
+/// How the second parent of a crossover is chosen once the first has been selected with `select_as_parent`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ParentPairing {
+    /// Both parents are drawn independently with the selection strategy, so an individual is sometimes paired with
+    /// itself (default)
+    #[default]
+    Independent,
+
+    /// The second parent is drawn again until it is a different individual from the first. An island of one individual
+    /// still pairs it with itself.
+    Distinct,
+
+    /// Positive assortative mating: of `candidates` draws with the selection strategy, the one most like the first
+    /// parent becomes the second. Pairing like with like refines the niches an island has found.
+    Similar { measure: PairingMeasure, candidates: usize },
+
+    /// Negative assortative mating: of `candidates` draws with the selection strategy, the one least like the first
+    /// parent becomes the second. Pairing unlike individuals keeps an island diverse.
+    Dissimilar { measure: PairingMeasure, candidates: usize },
+}
+
+/// How alike two parents are, for `ParentPairing::Similar` and `ParentPairing::Dissimilar`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PairingMeasure {
+    /// The difference between the scores from `IslandCallbacks::score_individual`
+    Fitness,
+
+    /// The number of instructions that must be inserted, deleted or substituted to turn one program into the other
+    Structure,
+}
+
+impl ParentPairing {
+    /// Returns the number of draws with the selection strategy made for each second parent, which must be at least one
+    pub fn candidates(&self) -> usize {
+        match *self {
+            ParentPairing::Independent | ParentPairing::Distinct => 1,
+            ParentPairing::Similar { candidates, .. } | ParentPairing::Dissimilar { candidates, .. } => candidates,
+        }
+    }
+
+    // Picks the index of the individual that is paired with the one at `first`
+    pub(crate) fn pick_mate<T, R: RunResult, Rnd: rand::Rng>(
+        &self,
+        island: &Island<T, R>,
+        first: usize,
+        strategy: &SelectionStrategy,
+        rng: &mut Rnd,
+    ) -> usize {
+        let (measure, candidates, similar) = match *self {
+            ParentPairing::Independent => return island.select_one_index(strategy, rng).unwrap(),
+            ParentPairing::Distinct => {
+                for _ in 0..DISTINCT_ATTEMPTS {
+                    let mate = island.select_one_index(strategy, rng).unwrap();
+                    if mate != first {
+                        return mate;
+                    }
+                }
+                return first;
+            }
+            ParentPairing::Similar { measure, candidates } => (measure, candidates, true),
+            ParentPairing::Dissimilar { measure, candidates } => (measure, candidates, false),
+        };
+
+        // The first parent is only a candidate if nothing else was drawn
+        let mut drawn: Vec<usize> = (0..candidates)
+            .map(|_| island.select_one_index(strategy, rng).unwrap())
+            .collect();
+        if drawn.iter().any(|index| *index != first) {
+            drawn.retain(|index| *index != first);
+        }
+        let distance = |index: usize| -> u64 {
+            match measure {
+                PairingMeasure::Fitness => {
+                    let a = island.score_for_individual(first).unwrap();
+                    let b = island.score_for_individual(index).unwrap();
+                    a.abs_diff(b)
+                }
+                PairingMeasure::Structure => {
+                    let a = island.get_one_individual(first).unwrap();
+                    let b = island.get_one_individual(index).unwrap();
+                    structural_distance(a.get_code(), b.get_code()) as u64
+                }
+            }
+        };
+        let closest = drawn.iter().copied().min_by_key(|index| distance(*index));
+        let farthest = drawn.iter().copied().max_by_key(|index| distance(*index));
+        if similar {
+            closest.unwrap()
+        } else {
+            farthest.unwrap()
+        }
+    }
+}
+
+#[cfg(all(test, not(feature = "async")))]
+mod tests {
+    use crate::*;
+    use rand::SeedableRng;
+
+    #[derive(Clone)]
+    struct ScoreTheLength;
+
+    impl IslandCallbacks<(), EmptyRunResult> for ScoreTheLength {
+        fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+            Box::new(Clone::clone(self))
+        }
+
+        fn run_individual(&mut self, _individual: &mut Individual<(), EmptyRunResult>) {}
+
+        fn score_individual(&self, individual: &Individual<(), EmptyRunResult>) -> u64 {
+            individual.get_code().len() as u64
+        }
+    }
+
+    #[test]
+    fn pairings_choose_the_expected_mate() {
+        let config = WorldConfiguration {
+            individuals_per_island: 10,
+            individual_max_points: 20,
+            parent_pairing: ParentPairing::Distinct,
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+        world.create_island(Box::new(ScoreTheLength));
+        world.fill_all_islands().unwrap();
+        world.run_one_generation();
+        let island = world.get_island(0).unwrap();
+        assert_eq!(10, island.len());
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(1234);
+        let strategy = SelectionStrategy::Curve(SelectionCurve::Fair);
+
+        for first in 0..island.len() {
+            assert_ne!(
+                first,
+                ParentPairing::Distinct.pick_mate(island, first, &strategy, &mut rng)
+            );
+        }
+
+        // With this many candidates every individual is drawn, so the mate is the closest or farthest of the others
+        let score = |index: usize| island.score_for_individual(index).unwrap();
+        let differences = |first: usize| {
+            (0..island.len())
+                .filter(move |i| *i != first)
+                .map(move |i| score(first).abs_diff(score(i)))
+        };
+        for first in 0..island.len() {
+            let similar = ParentPairing::Similar {
+                measure: PairingMeasure::Fitness,
+                candidates: 200,
+            };
+            let mate = similar.pick_mate(island, first, &strategy, &mut rng);
+            assert_ne!(first, mate);
+            assert_eq!(differences(first).min().unwrap(), score(first).abs_diff(score(mate)));
+
+            let dissimilar = ParentPairing::Dissimilar {
+                measure: PairingMeasure::Fitness,
+                candidates: 200,
+            };
+            let mate = dissimilar.pick_mate(island, first, &strategy, &mut rng);
+            assert_eq!(differences(first).max().unwrap(), score(first).abs_diff(score(mate)));
+        }
+
+        let config = WorldConfiguration {
+            parent_pairing: ParentPairing::Similar {
+                measure: PairingMeasure::Structure,
+                candidates: 0,
+            },
+            ..WorldConfiguration::default()
+        };
+        assert!(World::<(), EmptyRunResult>::new(config).is_err());
+    }
+}
@@ -0,0 +1,1455 @@
+use crate::code_builder::CodeBuilder;
+use crate::code_slot_range::slot_range;
+use crate::conversion_profile::wrap_float;
+use crate::host_call_log;
+use crate::*;
+use anyhow::Result;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use wasm_ast::FunctionIndex;
+use wasmtime::Val;
+
+/// A value held by one of the slots while the `ReferenceEvaluator` runs some Code.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SlotValue {
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+}
+
+impl SlotValue {
+    /// Returns the zero value for the specified type
+    pub fn zero(value_type: ValueType) -> SlotValue {
+        match value_type {
+            ValueType::I32 => SlotValue::I32(0),
+            ValueType::I64 => SlotValue::I64(0),
+            ValueType::F32 => SlotValue::F32(0.0),
+            ValueType::F64 => SlotValue::F64(0.0),
+        }
+    }
+
+    /// Returns the one value for the specified type
+    pub fn one(value_type: ValueType) -> SlotValue {
+        match value_type {
+            ValueType::I32 => SlotValue::I32(1),
+            ValueType::I64 => SlotValue::I64(1),
+            ValueType::F32 => SlotValue::F32(1.0),
+            ValueType::F64 => SlotValue::F64(1.0),
+        }
+    }
+
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            SlotValue::I32(_) => ValueType::I32,
+            SlotValue::I64(_) => ValueType::I64,
+            SlotValue::F32(_) => ValueType::F32,
+            SlotValue::F64(_) => ValueType::F64,
+        }
+    }
+
+    /// Converts the value to another type using the same rules as the generated Wasm: integers are extended or
+    /// converted to floats using the signedness of the code, I64 is wrapped into I32, and floats are truncated into
+    /// integers with saturation.
+    pub fn convert(self, destination_type: ValueType, is_signed: bool) -> SlotValue {
+        self.convert_with_profile(destination_type, is_signed, ConversionProfile::Saturating)
+    }
+
+    /// Converts the value to another type using the same rules as the Wasm generated with the conversion profile
+    pub fn convert_with_profile(
+        self,
+        destination_type: ValueType,
+        is_signed: bool,
+        profile: ConversionProfile,
+    ) -> SlotValue {
+        match (profile, self, destination_type) {
+            (ConversionProfile::Wrapping, SlotValue::F32(v), ValueType::I32) => {
+                SlotValue::I32(wrap_float(v as f64) as i32)
+            }
+            (ConversionProfile::Wrapping, SlotValue::F32(v), ValueType::I64) => {
+                SlotValue::I64(wrap_float(v as f64) as i64)
+            }
+            (ConversionProfile::Wrapping, SlotValue::F64(v), ValueType::I32) => SlotValue::I32(wrap_float(v) as i32),
+            (ConversionProfile::Wrapping, SlotValue::F64(v), ValueType::I64) => SlotValue::I64(wrap_float(v) as i64),
+
+            (ConversionProfile::Bitcast, SlotValue::F32(v), ValueType::I32 | ValueType::I64) => {
+                SlotValue::I32(v.to_bits() as i32).convert(destination_type, is_signed)
+            }
+            (ConversionProfile::Bitcast, SlotValue::F64(v), ValueType::I32 | ValueType::I64) => {
+                SlotValue::I64(v.to_bits() as i64).convert(destination_type, is_signed)
+            }
+            (ConversionProfile::Bitcast, SlotValue::I32(_) | SlotValue::I64(_), ValueType::F32) => {
+                match self.convert(ValueType::I32, is_signed) {
+                    SlotValue::I32(bits) => SlotValue::F32(f32::from_bits(bits as u32)),
+                    _ => unreachable!(),
+                }
+            }
+            (ConversionProfile::Bitcast, SlotValue::I32(_) | SlotValue::I64(_), ValueType::F64) => {
+                match self.convert(ValueType::I64, is_signed) {
+                    SlotValue::I64(bits) => SlotValue::F64(f64::from_bits(bits as u64)),
+                    _ => unreachable!(),
+                }
+            }
+
+            (_, SlotValue::I32(v), ValueType::I32) => SlotValue::I32(v),
+            (_, SlotValue::I32(v), ValueType::I64) => {
+                SlotValue::I64(if is_signed { v as i64 } else { v as u32 as i64 })
+            }
+            (_, SlotValue::I32(v), ValueType::F32) => {
+                SlotValue::F32(if is_signed { v as f32 } else { v as u32 as f32 })
+            }
+            (_, SlotValue::I32(v), ValueType::F64) => {
+                SlotValue::F64(if is_signed { v as f64 } else { v as u32 as f64 })
+            }
+
+            (_, SlotValue::I64(v), ValueType::I32) => SlotValue::I32(v as i32),
+            (_, SlotValue::I64(v), ValueType::I64) => SlotValue::I64(v),
+            (_, SlotValue::I64(v), ValueType::F32) => {
+                SlotValue::F32(if is_signed { v as f32 } else { v as u64 as f32 })
+            }
+            (_, SlotValue::I64(v), ValueType::F64) => {
+                SlotValue::F64(if is_signed { v as f64 } else { v as u64 as f64 })
+            }
+
+            (_, SlotValue::F32(v), ValueType::I32) => {
+                SlotValue::I32(if is_signed { v as i32 } else { v as u32 as i32 })
+            }
+            (_, SlotValue::F32(v), ValueType::I64) => {
+                SlotValue::I64(if is_signed { v as i64 } else { v as u64 as i64 })
+            }
+            (_, SlotValue::F32(v), ValueType::F32) => SlotValue::F32(v),
+            (_, SlotValue::F32(v), ValueType::F64) => SlotValue::F64(v as f64),
+
+            (_, SlotValue::F64(v), ValueType::I32) => {
+                SlotValue::I32(if is_signed { v as i32 } else { v as u32 as i32 })
+            }
+            (_, SlotValue::F64(v), ValueType::I64) => {
+                SlotValue::I64(if is_signed { v as i64 } else { v as u64 as i64 })
+            }
+            (_, SlotValue::F64(v), ValueType::F32) => SlotValue::F32(v as f32),
+            (_, SlotValue::F64(v), ValueType::F64) => SlotValue::F64(v),
+        }
+    }
+
+    pub(crate) fn to_val(self) -> Val {
+        match self {
+            SlotValue::I32(v) => Val::I32(v),
+            SlotValue::I64(v) => Val::I64(v),
+            SlotValue::F32(v) => Val::F32(v.to_bits()),
+            SlotValue::F64(v) => Val::F64(v.to_bits()),
+        }
+    }
+
+    pub(crate) fn from_val(value: &Val) -> SlotValue {
+        match value {
+            Val::I32(v) => SlotValue::I32(*v),
+            Val::I64(v) => SlotValue::I64(*v),
+            Val::F32(bits) => SlotValue::F32(f32::from_bits(*bits)),
+            Val::F64(bits) => SlotValue::F64(f64::from_bits(*bits)),
+            _ => panic!("unsupported wasmtime::Val {:?}", value),
+        }
+    }
+}
+
+impl From<i32> for SlotValue {
+    fn from(value: i32) -> Self {
+        SlotValue::I32(value)
+    }
+}
+
+impl From<i64> for SlotValue {
+    fn from(value: i64) -> Self {
+        SlotValue::I64(value)
+    }
+}
+
+impl From<f32> for SlotValue {
+    fn from(value: f32) -> Self {
+        SlotValue::F32(value)
+    }
+}
+
+impl From<f64> for SlotValue {
+    fn from(value: f64) -> Self {
+        SlotValue::F64(value)
+    }
+}
+
+/// A host function that the `ReferenceEvaluator` calls in place of a Wasm import. It receives the values of the
+/// parameter slots and returns the values for the result slots.
+pub type HostFunction<'a> = Box<dyn FnMut(&[SlotValue]) -> Result<Vec<SlotValue>> + 'a>;
+
+/// Runs Code directly on an array of slots in Rust, without generating or compiling any Wasm. The results match what
+/// the Wasm built by `CodeContext` would produce for the same Code, which makes the evaluator useful for differential
+/// testing of the Wasm path and as a cheap way to evaluate very small programs.
+///
+/// Function imports are declared in the same order as `World::add_function_import` so that the indices used by
+/// `Call` line up. A call to an import without a host function returns zero for every result.
+///
+/// ```
+/// use wasmgp::*;
+///
+/// let signature = FunctionSignature::new("main", vec![ValueType::I32, ValueType::I32], vec![ValueType::I32]);
+/// let slots = SlotCount { i32: 1, i64: 0, f32: 0, f64: 0 };
+/// let mut evaluator = ReferenceEvaluator::new(&signature, slots, true, SlotInit::Zero).unwrap();
+/// let double = evaluator.add_function_import(FunctionSignature::new(
+///     "double",
+///     vec![ValueType::I32],
+///     vec![ValueType::I32],
+/// ));
+/// evaluator
+///     .set_host_function(double, |params| match params[0] {
+///         SlotValue::I32(v) => Ok(vec![SlotValue::I32(v * 2)]),
+///         _ => unreachable!(),
+///     })
+///     .unwrap();
+///
+/// let code = vec![Add::new(0, 1, 3), Call::new(double, vec![3], vec![2]), Return::new()];
+/// let mut rng = rand::thread_rng();
+/// let results = evaluator.evaluate(&code, &[5.into(), 7.into()], &mut rng).unwrap();
+/// assert_eq!(vec![SlotValue::I32(24)], results);
+/// ```
+pub struct ReferenceEvaluator<'a> {
+    signature: FunctionSignature,
+    slots: SlotCount,
+    is_signed: bool,
+    conversion_profile: ConversionProfile,
+    init: SlotInit,
+    slot_types: Vec<ValueType>,
+    imports: Vec<FunctionSignature>,
+    host_functions: HashMap<FunctionIndex, HostFunction<'a>>,
+    max_steps: u64,
+}
+
+impl<'a> ReferenceEvaluator<'a> {
+    /// Creates an evaluator for the same function that `CodeContext::new` would build with these arguments.
+    pub fn new(
+        signature: &FunctionSignature,
+        slots: SlotCount,
+        is_signed: bool,
+        init: SlotInit,
+    ) -> Result<ReferenceEvaluator<'a>> {
+        let slot_count = signature.params().len() + signature.results().len() + slots.len();
+        if slot_count > 256 {
+            return Err(WasmgpError::SlotCountTooLarge(slot_count).into());
+        }
+
+        let mut slot_types = Vec::with_capacity(slot_count);
+        slot_types.extend_from_slice(signature.params());
+        slot_types.extend_from_slice(signature.results());
+        slot_types.extend(slots.iter());
+
+        Ok(ReferenceEvaluator {
+            signature: signature.clone(),
+            slots,
+            is_signed,
+            conversion_profile: ConversionProfile::Saturating,
+            init,
+            slot_types,
+            imports: vec![],
+            host_functions: HashMap::new(),
+            max_steps: 1_000_000,
+        })
+    }
+
+    /// Converts between integers and floats in the same way as a `CodeContext` with this conversion profile.
+    pub fn set_conversion_profile(&mut self, conversion_profile: ConversionProfile) {
+        self.conversion_profile = conversion_profile;
+    }
+
+    /// Declares the next function import and returns the index that `Call` uses for it.
+    pub fn add_function_import(&mut self, signature: FunctionSignature) -> FunctionIndex {
+        self.imports.push(signature);
+        (self.imports.len() - 1) as FunctionIndex
+    }
+
+    /// Routes calls to the import at `function_index` to the specified function.
+    pub fn set_host_function<F>(&mut self, function_index: FunctionIndex, func: F) -> Result<()>
+    where
+        F: FnMut(&[SlotValue]) -> Result<Vec<SlotValue>> + 'a,
+    {
+        if function_index as usize >= self.imports.len() {
+            return Err(WasmgpError::InvalidFunctionIndex(function_index).into());
+        }
+        self.host_functions.insert(function_index, Box::new(func));
+        Ok(())
+    }
+
+    /// The number of steps (one per Code run and one per loop iteration) allowed before the evaluation is stopped.
+    /// This takes the place of the time limit used when running Wasm.
+    pub fn max_steps(&self) -> u64 {
+        self.max_steps
+    }
+
+    pub fn set_max_steps(&mut self, max_steps: u64) {
+        self.max_steps = max_steps;
+    }
+
+    /// Runs the code with the specified parameters and returns the values of the result slots. The `rng` is only used
+    /// when the work slots are initialized with `SlotInit::Random`, and is consumed in the same order as
+    /// `CodeContext::build` so that a shared seed produces the same slot values on both paths. With
+    /// `SlotInit::RandomPerCall`, a single value is taken from the `rng` to seed the built-in random import.
+    ///
+    /// Code that would fail to build or validate as Wasm returns an error before anything runs. Integer overflow
+    /// during a signed divide returns `WasmgpError::IntegerOverflow`, matching the Wasm trap.
+    pub fn evaluate<R: rand::Rng>(
+        &mut self,
+        code: &[Code],
+        params: &[SlotValue],
+        rng: &mut R,
+    ) -> Result<Vec<SlotValue>> {
+        let mut state = self.start(code, params, rng, false)?;
+        self.finish(&mut state, code)
+    }
+
+    /// Like `evaluate`, but also records a `DebugStep` before each Code runs. Only problems found before anything runs
+    /// are returned as the outer error, so that the steps leading up to a runtime error are still available.
+    pub(crate) fn evaluate_traced<R: rand::Rng>(
+        &mut self,
+        code: &[Code],
+        params: &[SlotValue],
+        rng: &mut R,
+    ) -> Result<(Result<Vec<SlotValue>>, Vec<DebugStep>)> {
+        let mut state = self.start(code, params, rng, true)?;
+        let outcome = self.finish(&mut state, code);
+        Ok((outcome, state.trace.unwrap_or_default()))
+    }
+
+    fn start<R: rand::Rng>(
+        &self,
+        code: &[Code],
+        params: &[SlotValue],
+        rng: &mut R,
+        trace: bool,
+    ) -> Result<EvaluationState> {
+        self.validate(code)?;
+
+        if params.len() != self.signature.params().len()
+            || params
+                .iter()
+                .zip(self.signature.params().iter())
+                .any(|(value, value_type)| value.value_type() != *value_type)
+        {
+            return Err(WasmgpError::ParameterMismatch.into());
+        }
+
+        let mut state = EvaluationState {
+            slots: Vec::with_capacity(self.slot_types.len()),
+            steps: 0,
+            loops: vec![],
+            path: vec![],
+            trace: if trace { Some(vec![]) } else { None },
+        };
+        state.slots.extend_from_slice(params);
+        state
+            .slots
+            .extend(self.signature.results().iter().map(|r| SlotValue::zero(*r)));
+        let mut random_state: u64 = match self.init {
+            SlotInit::RandomPerCall => rng.gen(),
+            _ => 0,
+        };
+        for value_type in self.slots.iter() {
+            state.slots.push(match self.init {
+                SlotInit::Zero => SlotValue::zero(value_type),
+                SlotInit::One => SlotValue::one(value_type),
+                SlotInit::Random => match value_type {
+                    ValueType::I32 => SlotValue::I32(rng.gen()),
+                    ValueType::I64 => SlotValue::I64(rng.gen()),
+                    ValueType::F32 => SlotValue::F32(rng.gen()),
+                    ValueType::F64 => SlotValue::F64(rng.gen()),
+                },
+                SlotInit::RandomPerCall => {
+                    crate::slot_init::random_slot_value(value_type, host_call_log::splitmix64(&mut random_state))
+                }
+            });
+        }
+        Ok(state)
+    }
+
+    fn finish(&mut self, state: &mut EvaluationState, code: &[Code]) -> Result<Vec<SlotValue>> {
+        // The final Return (if any) is where the function ends anyway
+        let body = match code.last() {
+            Some(Code::Return(_)) => &code[..code.len() - 1],
+            _ => code,
+        };
+        self.run_code_list(state, body, None)?;
+
+        let first_result = self.signature.params().len();
+        Ok(state.slots[first_result..first_result + self.signature.results().len()].to_vec())
+    }
+
+    // Reports the same problems that would stop the code from becoming a valid Wasm module: slot errors are found by
+    // building the instructions for each Code, while misplaced returns and mismatched calls are only caught when the
+    // Wasm is validated.
+    fn validate(&self, code: &[Code]) -> Result<()> {
+        let mut context = CodeContext::new(&self.signature, self.slots.clone(), self.is_signed, self.init)?;
+        for import in self.imports.iter() {
+            context.add_function_import(import.clone());
+        }
+        let mut instruction_list = vec![];
+        for (index, c) in code.iter().enumerate() {
+            c.append_code(&context, &mut instruction_list)
+                .map_err(|err| CodeDiagnostic::wrap(err, format!("{}[{}]", c.name(), index)))?;
+        }
+
+        self.validate_top_level(code, true)
+    }
+
+    // A Return at the top level must be the final Code, otherwise the function ends with extra values. The body of a
+    // Provenance is still part of the top level, and it `ends_function` when the Provenance is the final Code.
+    fn validate_top_level(&self, code: &[Code], ends_function: bool) -> Result<()> {
+        let last = code.len().saturating_sub(1);
+        for (index, c) in code.iter().enumerate() {
+            let result = match c {
+                Code::Return(_) if ends_function && index == last => Ok(()),
+                Code::Return(_) => Err(WasmgpError::MisplacedReturn.into()),
+                Code::Provenance(p) => self.validate_top_level(p.do_this(), ends_function && index == last),
+                c => self.validate_nested(c, false),
+            };
+            result.map_err(|err| CodeDiagnostic::wrap(err, format!("{}[{}]", c.name(), index)))?;
+        }
+        Ok(())
+    }
+
+    // `in_loop` is true when the Code is inside of a loop, where a `Break` becomes a branch
+    fn validate_nested(&self, code: &Code, in_loop: bool) -> Result<()> {
+        match code {
+            Code::Call(call) => {
+                let import = self
+                    .imports
+                    .get(call.function_index() as usize)
+                    .ok_or(WasmgpError::InvalidFunctionIndex(call.function_index()))?;
+                // Extra or missing slots are allowed, but each slot that is used must have the type of its value
+                let params_match = call
+                    .params()
+                    .iter()
+                    .zip(import.params().iter())
+                    .all(|(slot, value_type)| self.slot_types[*slot as usize] == *value_type);
+                let results_match = call
+                    .results()
+                    .iter()
+                    .zip(import.results().iter())
+                    .all(|(slot, value_type)| self.slot_types[*slot as usize] == *value_type);
+                if params_match && results_match {
+                    Ok(())
+                } else {
+                    Err(WasmgpError::CallSignatureMismatch(call.function_index()).into())
+                }
+            }
+            Code::If(code) => self.validate_code_list(code.do_this(), "body", false, in_loop),
+            Code::IfElse(code) => {
+                self.validate_code_list(code.do_this(), "body", false, in_loop)?;
+                self.validate_code_list(code.else_do_this(), "else_body", false, in_loop)
+            }
+            Code::DoUntil(code) => self.validate_code_list(code.do_this(), "body", true, true),
+            Code::DoWhile(code) => self.validate_code_list(code.do_this(), "body", true, true),
+            Code::DoFor(code) => self.validate_code_list(code.do_this(), "body", true, true),
+            Code::Provenance(code) => self.validate_code_list(code.do_this(), "body", false, in_loop),
+            _ => Ok(()),
+        }
+    }
+
+    // A nested Return leaves its values on the stack, which is only valid if they are thrown away before the end of
+    // the block. A `Break` does that, because the code after a branch is unreachable, and so does the branch back to
+    // the top of the loop that follows every loop body.
+    fn validate_code_list(&self, list: &[Code], list_name: &str, is_loop_body: bool, in_loop: bool) -> Result<()> {
+        let mut values_left_by = None;
+        for (index, c) in list.iter().enumerate() {
+            match c {
+                Code::Return(_) => values_left_by = values_left_by.or(Some(index)),
+                Code::Break(_) if in_loop => values_left_by = None,
+                c => self
+                    .validate_nested(c, in_loop)
+                    .map_err(|err| CodeDiagnostic::wrap(err, format!("{}[{}]", list_name, index)))?,
+            }
+        }
+
+        match values_left_by {
+            Some(index) if !is_loop_body => Err(CodeDiagnostic::wrap(
+                WasmgpError::MisplacedReturn.into(),
+                format!("{}[{}]", list_name, index),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    // Runs each Code in the list. The `list_name` is only used for the path of a `DebugStep`; the top-level list has
+    // no name and uses the name of each Code instead.
+    fn run_code_list(
+        &mut self,
+        state: &mut EvaluationState,
+        list: &[Code],
+        list_name: Option<&'static str>,
+    ) -> Result<Flow> {
+        for (index, c) in list.iter().enumerate() {
+            if state.trace.is_some() {
+                state.path.push((list_name.unwrap_or_else(|| c.name()), index));
+            }
+            let flow = self.run_code(state, c);
+            if state.trace.is_some() {
+                state.path.pop();
+            }
+            if let Flow::Branch(depth) = flow? {
+                return Ok(Flow::Branch(depth));
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    // Runs the body of a loop. Any `Break` inside of it can now exit the loop.
+    fn run_loop_body(&mut self, state: &mut EvaluationState, frame: LoopFrame, list: &[Code]) -> Result<Flow> {
+        state.loops.push(frame);
+        let flow = self.run_code_list(state, list, Some("body"));
+        state.loops.pop();
+        flow
+    }
+
+    fn run_code(&mut self, state: &mut EvaluationState, code: &Code) -> Result<Flow> {
+        state.step(self.max_steps)?;
+        state.record(code);
+        let is_signed = self.is_signed;
+
+        match code {
+            // Const
+            Code::ConstI32(c) => self.set(state, c.slot(), SlotValue::I32(c.value())),
+            Code::ConstI64(c) => self.set(state, c.slot(), SlotValue::I64(c.value())),
+            Code::ConstF32(c) => self.set(state, c.slot(), SlotValue::F32(c.value())),
+            Code::ConstF64(c) => self.set(state, c.slot(), SlotValue::F64(c.value())),
+            Code::ConstOne(c) => self.set(state, c.destination(), SlotValue::I32(1)),
+            Code::ConstZero(c) => self.set(state, c.destination(), SlotValue::I32(0)),
+
+            // Bitwise
+            #[cfg(feature = "bit_ops")]
+            Code::CountLeadingZeros(c) => {
+                let value = match self.get(state, c.source(), self.integer_type_of(c.source())) {
+                    SlotValue::I32(v) => SlotValue::I32(v.leading_zeros() as i32),
+                    SlotValue::I64(v) => SlotValue::I64(v.leading_zeros() as i64),
+                    _ => unreachable!(),
+                };
+                self.set(state, c.destination(), value)
+            }
+            #[cfg(feature = "bit_ops")]
+            Code::CountTrailingZeros(c) => {
+                let value = match self.get(state, c.source(), self.integer_type_of(c.source())) {
+                    SlotValue::I32(v) => SlotValue::I32(v.trailing_zeros() as i32),
+                    SlotValue::I64(v) => SlotValue::I64(v.trailing_zeros() as i64),
+                    _ => unreachable!(),
+                };
+                self.set(state, c.destination(), value)
+            }
+            #[cfg(feature = "bit_ops")]
+            Code::PopulationCount(c) => {
+                let value = match self.get(state, c.source(), self.integer_type_of(c.source())) {
+                    SlotValue::I32(v) => SlotValue::I32(v.count_ones() as i32),
+                    SlotValue::I64(v) => SlotValue::I64(v.count_ones() as i64),
+                    _ => unreachable!(),
+                };
+                self.set(state, c.destination(), value)
+            }
+            #[cfg(feature = "bit_ops")]
+            Code::PowerOfTwo(c) => {
+                let operate_as = self.integer_type_of(c.destination());
+                let value = match self.get(state, c.exponent(), operate_as) {
+                    SlotValue::I32(e) => SlotValue::I32(1i32.wrapping_shl(e as u32)),
+                    SlotValue::I64(e) => SlotValue::I64(1i64.wrapping_shl(e as u32)),
+                    _ => unreachable!(),
+                };
+                self.set(state, c.destination(), value)
+            }
+            #[cfg(feature = "bit_ops")]
+            Code::IntegerLog2(c) => {
+                let value = match self.get(state, c.source(), self.integer_type_of(c.source())) {
+                    SlotValue::I32(v) => SlotValue::I32(31 - v.leading_zeros() as i32),
+                    SlotValue::I64(v) => SlotValue::I64(63 - v.leading_zeros() as i64),
+                    _ => unreachable!(),
+                };
+                self.set(state, c.destination(), value)
+            }
+            #[cfg(feature = "bit_ops")]
+            Code::And(c) => {
+                let operate_as = self.integer_type_of_pair(c.left(), c.right());
+                let (left, right) = self.operands(state, c.left(), c.right(), operate_as);
+                self.set(
+                    state,
+                    c.destination(),
+                    integer_op(left, right, |l, r| l & r, |l, r| l & r),
+                )
+            }
+            #[cfg(feature = "bit_ops")]
+            Code::Or(c) => {
+                let operate_as = self.integer_type_of_pair(c.left(), c.right());
+                let (left, right) = self.operands(state, c.left(), c.right(), operate_as);
+                self.set(
+                    state,
+                    c.destination(),
+                    integer_op(left, right, |l, r| l | r, |l, r| l | r),
+                )
+            }
+            #[cfg(feature = "bit_ops")]
+            Code::Xor(c) => {
+                let operate_as = self.integer_type_of_pair(c.left(), c.right());
+                let (left, right) = self.operands(state, c.left(), c.right(), operate_as);
+                self.set(
+                    state,
+                    c.destination(),
+                    integer_op(left, right, |l, r| l ^ r, |l, r| l ^ r),
+                )
+            }
+            #[cfg(feature = "bit_ops")]
+            Code::ShiftLeft(c) => {
+                let operate_as = self.integer_type_of(c.source());
+                let (source, bits) = self.operands(state, c.source(), c.bits(), operate_as);
+                let value = integer_op(
+                    source,
+                    bits,
+                    |v, b| v.wrapping_shl(b as u32),
+                    |v, b| v.wrapping_shl(b as u32),
+                );
+                self.set(state, c.destination(), value)
+            }
+            #[cfg(feature = "bit_ops")]
+            Code::ShiftRight(c) => {
+                let operate_as = self.integer_type_of(c.source());
+                let (source, bits) = self.operands(state, c.source(), c.bits(), operate_as);
+                let value = if is_signed {
+                    integer_op(
+                        source,
+                        bits,
+                        |v, b| v.wrapping_shr(b as u32),
+                        |v, b| v.wrapping_shr(b as u32),
+                    )
+                } else {
+                    integer_op(
+                        source,
+                        bits,
+                        |v, b| (v as u32).wrapping_shr(b as u32) as i32,
+                        |v, b| (v as u64).wrapping_shr(b as u32) as i64,
+                    )
+                };
+                self.set(state, c.destination(), value)
+            }
+            #[cfg(feature = "bit_ops")]
+            Code::RotateLeft(c) => {
+                let operate_as = self.integer_type_of(c.source());
+                let (source, bits) = self.operands(state, c.source(), c.bits(), operate_as);
+                let value = integer_op(
+                    source,
+                    bits,
+                    |v, b| v.rotate_left(b as u32 & 31),
+                    |v, b| v.rotate_left(b as u32 & 63),
+                );
+                self.set(state, c.destination(), value)
+            }
+            #[cfg(feature = "bit_ops")]
+            Code::RotateRight(c) => {
+                let operate_as = self.integer_type_of(c.source());
+                let (source, bits) = self.operands(state, c.source(), c.bits(), operate_as);
+                let value = integer_op(
+                    source,
+                    bits,
+                    |v, b| v.rotate_right(b as u32 & 31),
+                    |v, b| v.rotate_right(b as u32 & 63),
+                );
+                self.set(state, c.destination(), value)
+            }
+
+            // Arithmetic
+            Code::Add(c) => {
+                let operate_as = self.slot_type(c.destination());
+                let (left, right) = self.operands(state, c.left(), c.right(), operate_as);
+                let value = arithmetic_op(
+                    left,
+                    right,
+                    i32::wrapping_add,
+                    i64::wrapping_add,
+                    |l, r| l + r,
+                    |l, r| l + r,
+                );
+                self.set(state, c.destination(), value)
+            }
+            Code::Subtract(c) => {
+                let operate_as = self.slot_type(c.destination());
+                let (left, right) = self.operands(state, c.left(), c.right(), operate_as);
+                let value = arithmetic_op(
+                    left,
+                    right,
+                    i32::wrapping_sub,
+                    i64::wrapping_sub,
+                    |l, r| l - r,
+                    |l, r| l - r,
+                );
+                self.set(state, c.destination(), value)
+            }
+            Code::Multiply(c) => {
+                let operate_as = self.slot_type(c.destination());
+                let (left, right) = self.operands(state, c.left(), c.right(), operate_as);
+                let value = arithmetic_op(
+                    left,
+                    right,
+                    i32::wrapping_mul,
+                    i64::wrapping_mul,
+                    |l, r| l * r,
+                    |l, r| l * r,
+                );
+                self.set(state, c.destination(), value)
+            }
+            Code::Divide(c) => {
+                let operate_as = self.slot_type(c.destination());
+                let value = match self.operands(state, c.dividend(), c.divisor(), operate_as) {
+                    // Dividing by zero leaves the destination unchanged
+                    (_, SlotValue::I32(0)) | (_, SlotValue::I64(0)) => return Ok(Flow::Next),
+                    (_, SlotValue::F32(0.0)) => return Ok(Flow::Next),
+                    (_, SlotValue::F64(0.0)) => return Ok(Flow::Next),
+
+                    (SlotValue::I32(l), SlotValue::I32(r)) if is_signed => {
+                        SlotValue::I32(l.checked_div(r).ok_or(WasmgpError::IntegerOverflow)?)
+                    }
+                    (SlotValue::I32(l), SlotValue::I32(r)) => SlotValue::I32((l as u32 / r as u32) as i32),
+                    (SlotValue::I64(l), SlotValue::I64(r)) if is_signed => {
+                        SlotValue::I64(l.checked_div(r).ok_or(WasmgpError::IntegerOverflow)?)
+                    }
+                    (SlotValue::I64(l), SlotValue::I64(r)) => SlotValue::I64((l as u64 / r as u64) as i64),
+                    (SlotValue::F32(l), SlotValue::F32(r)) => SlotValue::F32(l / r),
+                    (SlotValue::F64(l), SlotValue::F64(r)) => SlotValue::F64(l / r),
+                    _ => unreachable!(),
+                };
+                self.set(state, c.destination(), value)
+            }
+            Code::Remainder(c) => {
+                let operate_as = self.integer_type_of_pair(c.dividend(), c.divisor());
+                let value = match self.operands(state, c.dividend(), c.divisor(), operate_as) {
+                    // Dividing by zero leaves the destination unchanged
+                    (_, SlotValue::I32(0)) | (_, SlotValue::I64(0)) => return Ok(Flow::Next),
+
+                    (SlotValue::I32(l), SlotValue::I32(r)) if is_signed => SlotValue::I32(l.wrapping_rem(r)),
+                    (SlotValue::I32(l), SlotValue::I32(r)) => SlotValue::I32((l as u32 % r as u32) as i32),
+                    (SlotValue::I64(l), SlotValue::I64(r)) if is_signed => SlotValue::I64(l.wrapping_rem(r)),
+                    (SlotValue::I64(l), SlotValue::I64(r)) => SlotValue::I64((l as u64 % r as u64) as i64),
+                    _ => unreachable!(),
+                };
+                self.set(state, c.destination(), value)
+            }
+            Code::Power(c) => {
+                let operate_as = self.integer_type_of(c.destination());
+                let value = match self.operands(state, c.base(), c.exponent(), operate_as) {
+                    (SlotValue::I32(b), SlotValue::I32(e)) => {
+                        SlotValue::I32(wrapping_power(b as i64, e as u32 as u64) as i32)
+                    }
+                    (SlotValue::I64(b), SlotValue::I64(e)) => SlotValue::I64(wrapping_power(b, e as u64)),
+                    _ => unreachable!(),
+                };
+                self.set(state, c.destination(), value)
+            }
+
+            // Float
+            #[cfg(feature = "float_ops")]
+            Code::AbsoluteValue(c) => {
+                let value = float_unary_op(self.float_operand(state, c.source()), f32::abs, f64::abs);
+                self.set(state, c.destination(), value)
+            }
+            #[cfg(feature = "float_ops")]
+            Code::Negate(c) => {
+                let value = float_unary_op(self.float_operand(state, c.source()), |v| -v, |v| -v);
+                self.set(state, c.destination(), value)
+            }
+            #[cfg(feature = "float_ops")]
+            Code::SquareRoot(c) => {
+                let value = float_unary_op(
+                    self.float_operand(state, c.source()),
+                    |v| v.abs().sqrt(),
+                    |v| v.abs().sqrt(),
+                );
+                self.set(state, c.destination(), value)
+            }
+            #[cfg(feature = "float_ops")]
+            Code::Ceiling(c) => {
+                let value = float_unary_op(self.float_operand(state, c.source()), f32::ceil, f64::ceil);
+                self.set(state, c.destination(), value)
+            }
+            #[cfg(feature = "float_ops")]
+            Code::Floor(c) => {
+                let value = float_unary_op(self.float_operand(state, c.source()), f32::floor, f64::floor);
+                self.set(state, c.destination(), value)
+            }
+            #[cfg(feature = "float_ops")]
+            Code::Nearest(c) => {
+                let value = float_unary_op(
+                    self.float_operand(state, c.source()),
+                    f32::round_ties_even,
+                    f64::round_ties_even,
+                );
+                self.set(state, c.destination(), value)
+            }
+            #[cfg(feature = "float_ops")]
+            Code::Min(c) => {
+                let (left, right) = self.float_operands(state, c.left(), c.right());
+                self.set(state, c.destination(), float_binary_op(left, right, min_f32, min_f64))
+            }
+            #[cfg(feature = "float_ops")]
+            Code::Max(c) => {
+                let (left, right) = self.float_operands(state, c.left(), c.right());
+                self.set(state, c.destination(), float_binary_op(left, right, max_f32, max_f64))
+            }
+            #[cfg(feature = "float_ops")]
+            Code::CopySign(c) => {
+                let (left, right) = self.float_operands(state, c.left(), c.right());
+                let value = float_binary_op(left, right, f32::copysign, f64::copysign);
+                self.set(state, c.destination(), value)
+            }
+
+            // Comparison
+            Code::IsEqualZero(c) => {
+                let is_zero = match state.slots[c.source() as usize] {
+                    SlotValue::I32(v) => v == 0,
+                    SlotValue::I64(v) => v == 0,
+                    SlotValue::F32(v) => v == 0.0,
+                    SlotValue::F64(v) => v == 0.0,
+                };
+                self.set(state, c.destination(), SlotValue::I32(is_zero as i32))
+            }
+            Code::AreEqual(c) => {
+                let (left, right) = self.compare_operands(state, c.left(), c.right());
+                let result = compare(left, right, is_signed, |o| o.is_eq());
+                self.set(state, c.destination(), SlotValue::I32(result as i32))
+            }
+            Code::AreNotEqual(c) => {
+                // NaN is not equal to anything, so unlike the other comparisons this one is true for NaN
+                let (left, right) = self.compare_operands(state, c.left(), c.right());
+                let result = !compare(left, right, is_signed, |o| o.is_eq());
+                self.set(state, c.destination(), SlotValue::I32(result as i32))
+            }
+            Code::IsLessThan(c) => {
+                let (left, right) = self.compare_operands(state, c.left(), c.right());
+                let result = compare(left, right, is_signed, |o| o.is_lt());
+                self.set(state, c.destination(), SlotValue::I32(result as i32))
+            }
+            Code::IsGreaterThan(c) => {
+                let (left, right) = self.compare_operands(state, c.left(), c.right());
+                let result = compare(left, right, is_signed, |o| o.is_gt());
+                self.set(state, c.destination(), SlotValue::I32(result as i32))
+            }
+            Code::IsLessThanOrEqual(c) => {
+                let (left, right) = self.compare_operands(state, c.left(), c.right());
+                let result = compare(left, right, is_signed, |o| o.is_le());
+                self.set(state, c.destination(), SlotValue::I32(result as i32))
+            }
+            Code::IsGreaterThanOrEqual(c) => {
+                let (left, right) = self.compare_operands(state, c.left(), c.right());
+                let result = compare(left, right, is_signed, |o| o.is_ge());
+                self.set(state, c.destination(), SlotValue::I32(result as i32))
+            }
+
+            // Slot ranges
+            Code::FillSlots(c) => {
+                for slot in slot_range(c.start(), c.count(), self.slot_types.len()) {
+                    self.set(state, slot as Slot, SlotValue::I32(c.value()));
+                }
+            }
+            Code::SumSlots(c) => {
+                let operate_as = self.slot_type(c.destination());
+                let mut sum = SlotValue::zero(operate_as);
+                for slot in slot_range(c.start(), c.count(), self.slot_types.len()) {
+                    let value = self.get(state, slot as Slot, operate_as);
+                    sum = arithmetic_op(
+                        sum,
+                        value,
+                        i32::wrapping_add,
+                        i64::wrapping_add,
+                        |l, r| l + r,
+                        |l, r| l + r,
+                    );
+                }
+                self.set(state, c.destination(), sum)
+            }
+            Code::CopySlots(c) => {
+                let pairs = c.pairs(self.slot_types.len());
+                let values: Vec<SlotValue> = pairs.iter().map(|(source, _)| state.slots[*source as usize]).collect();
+                for ((_, destination), value) in pairs.iter().zip(values) {
+                    self.set(state, *destination, value);
+                }
+            }
+
+            // Control
+            Code::CopySlot(c) => {
+                let value = state.slots[c.source() as usize];
+                self.set(state, c.destination(), value)
+            }
+            // The values a nested Return leaves on the stack do not change any slots
+            Code::Return(_) => {}
+            Code::Call(c) => {
+                // Extra parameter slots are left out and missing parameters are zero
+                let import = &self.imports[c.function_index() as usize];
+                let params: Vec<SlotValue> = import
+                    .params()
+                    .iter()
+                    .enumerate()
+                    .map(|(index, value_type)| match c.params().get(index) {
+                        Some(slot) => state.slots[*slot as usize],
+                        None => SlotValue::zero(*value_type),
+                    })
+                    .collect();
+                let results = match self.host_functions.get_mut(&c.function_index()) {
+                    Some(func) => func(&params[..])?,
+                    None => import.results().iter().map(|r| SlotValue::zero(*r)).collect(),
+                };
+                if results.len() != import.results().len()
+                    || results
+                        .iter()
+                        .zip(import.results().iter())
+                        .any(|(value, value_type)| value.value_type() != *value_type)
+                {
+                    return Err(WasmgpError::CallSignatureMismatch(c.function_index()).into());
+                }
+
+                if let Some(step) = state.trace.as_mut().and_then(|trace| trace.last_mut()) {
+                    step.host_call = Some(HostCall {
+                        function_index: c.function_index(),
+                        params,
+                        results: results.clone(),
+                    });
+                }
+
+                // The result slots are set in reverse, so when a slot is listed twice the first result wins. Extra results
+                // are dropped, and then the return slots without a result are set to zero.
+                let kept = c.results().len().min(results.len());
+                for (slot, value) in c.results()[..kept].iter().zip(results).rev() {
+                    state.slots[*slot as usize] = value;
+                }
+                for slot in c.results()[kept..].iter() {
+                    state.slots[*slot as usize] = SlotValue::zero(self.slot_types[*slot as usize]);
+                }
+            }
+            Code::If(c) => {
+                if self.is_not_zero(state, c.if_not_zero()) {
+                    return Ok(exit_block(self.run_code_list(state, c.do_this(), Some("body"))?));
+                }
+            }
+            Code::IfElse(c) => {
+                let flow = if self.is_not_zero(state, c.if_not_zero()) {
+                    self.run_code_list(state, c.do_this(), Some("body"))?
+                } else {
+                    self.run_code_list(state, c.else_do_this(), Some("else_body"))?
+                };
+                return Ok(exit_block(flow));
+            }
+
+            // Each loop is a Wasm `loop` inside of a `block`: a branch of 0 from the body goes back to the top of the
+            // loop and a branch of 1 exits the block. Going back to the top skips anything that follows the body.
+            Code::DoUntil(c) => {
+                for iteration in 0.. {
+                    state.step(self.max_steps)?;
+                    let frame = LoopFrame::new(code, iteration, None);
+                    match self.run_loop_body(state, frame, c.do_this())? {
+                        Flow::Next => {
+                            if self.is_not_zero(state, c.until_not_zero()) {
+                                break;
+                            }
+                        }
+                        Flow::Branch(0) => {}
+                        Flow::Branch(1) => break,
+                        Flow::Branch(depth) => return Ok(Flow::Branch(depth - 2)),
+                    }
+                }
+            }
+            Code::DoWhile(c) => {
+                for iteration in 0.. {
+                    state.step(self.max_steps)?;
+                    if self.is_not_zero(state, c.while_not_zero()) {
+                        break;
+                    }
+                    let frame = LoopFrame::new(code, iteration, None);
+                    match self.run_loop_body(state, frame, c.do_this())? {
+                        Flow::Next | Flow::Branch(0) => {}
+                        Flow::Branch(1) => break,
+                        Flow::Branch(depth) => return Ok(Flow::Branch(depth - 2)),
+                    }
+                }
+            }
+            Code::DoFor(c) => {
+                let mut remaining = c.times() as i32;
+                for iteration in 0.. {
+                    state.step(self.max_steps)?;
+                    if remaining == 0 {
+                        break;
+                    }
+                    let frame = LoopFrame::new(code, iteration, Some(remaining));
+                    match self.run_loop_body(state, frame, c.do_this())? {
+                        Flow::Next => remaining -= 1,
+                        Flow::Branch(0) => {}
+                        Flow::Branch(1) => break,
+                        Flow::Branch(depth) => return Ok(Flow::Branch(depth - 2)),
+                    }
+                }
+            }
+            Code::Break(_) => {
+                if !state.loops.is_empty() {
+                    return Ok(Flow::Branch(1));
+                }
+            }
+            Code::BreakIf(c) => {
+                if !state.loops.is_empty() && self.is_not_zero(state, c.break_if_not_zero()) {
+                    return Ok(Flow::Branch(1));
+                }
+            }
+
+            // Provenance adds no block, so a branch from inside of it passes straight through
+            Code::Provenance(c) => return self.run_code_list(state, c.do_this(), Some("body")),
+        };
+
+        Ok(Flow::Next)
+    }
+
+    // Slots have already been validated, so they can be indexed directly
+    fn slot_type(&self, slot: Slot) -> ValueType {
+        self.slot_types[slot as usize]
+    }
+
+    fn get(&self, state: &EvaluationState, slot: Slot, operate_as: ValueType) -> SlotValue {
+        state.slots[slot as usize].convert_with_profile(operate_as, self.is_signed, self.conversion_profile)
+    }
+
+    fn set(&self, state: &mut EvaluationState, slot: Slot, value: SlotValue) {
+        state.slots[slot as usize] =
+            value.convert_with_profile(self.slot_type(slot), self.is_signed, self.conversion_profile);
+    }
+
+    fn operands(
+        &self,
+        state: &EvaluationState,
+        left: Slot,
+        right: Slot,
+        operate_as: ValueType,
+    ) -> (SlotValue, SlotValue) {
+        (self.get(state, left, operate_as), self.get(state, right, operate_as))
+    }
+
+    fn is_not_zero(&self, state: &EvaluationState, slot: Slot) -> bool {
+        self.get(state, slot, ValueType::I32) != SlotValue::I32(0)
+    }
+
+    fn integer_type_of(&self, slot: Slot) -> ValueType {
+        match self.slot_type(slot) {
+            ValueType::I32 => ValueType::I32,
+            _ => ValueType::I64,
+        }
+    }
+
+    fn integer_type_of_pair(&self, left: Slot, right: Slot) -> ValueType {
+        match (self.slot_type(left), self.slot_type(right)) {
+            (ValueType::I32, ValueType::I32) => ValueType::I32,
+            _ => ValueType::I64,
+        }
+    }
+
+    fn float_operand(&self, state: &EvaluationState, source: Slot) -> SlotValue {
+        match self.slot_type(source) {
+            ValueType::F32 => self.get(state, source, ValueType::F32),
+            _ => self.get(state, source, ValueType::F64),
+        }
+    }
+
+    fn float_operands(&self, state: &EvaluationState, left: Slot, right: Slot) -> (SlotValue, SlotValue) {
+        let operate_as = match (self.slot_type(left), self.slot_type(right)) {
+            (ValueType::F32, ValueType::F32) => ValueType::F32,
+            _ => ValueType::F64,
+        };
+        self.operands(state, left, right, operate_as)
+    }
+
+    fn compare_operands(&self, state: &EvaluationState, left: Slot, right: Slot) -> (SlotValue, SlotValue) {
+        let operate_as = match (self.slot_type(left), self.slot_type(right)) {
+            (ValueType::I32, ValueType::I32) => ValueType::I32,
+            (ValueType::I32, ValueType::I64) | (ValueType::I64, ValueType::I32) | (ValueType::I64, ValueType::I64) => {
+                ValueType::I64
+            }
+            (ValueType::F32, ValueType::F32) => ValueType::F32,
+            _ => ValueType::F64,
+        };
+        self.operands(state, left, right, operate_as)
+    }
+}
+
+struct EvaluationState {
+    slots: Vec<SlotValue>,
+    steps: u64,
+
+    // The loops that contain the Code being run. `Break` only does something inside of a loop.
+    loops: Vec<LoopFrame>,
+
+    // When tracing, the list name and index of each Code that contains the one being run, and the steps so far
+    path: Vec<(&'static str, usize)>,
+    trace: Option<Vec<DebugStep>>,
+}
+
+impl EvaluationState {
+    fn record(&mut self, code: &Code) {
+        if let Some(trace) = self.trace.as_mut() {
+            trace.push(DebugStep {
+                step: self.steps,
+                path: self
+                    .path
+                    .iter()
+                    .map(|(name, index)| format!("{}[{}]", name, index))
+                    .collect(),
+                code: code.clone(),
+                slots: self.slots.clone(),
+                loops: self.loops.clone(),
+                host_call: None,
+            });
+        }
+    }
+
+    fn step(&mut self, max_steps: u64) -> Result<()> {
+        self.steps += 1;
+        if self.steps > max_steps {
+            Err(WasmgpError::StepLimitExceeded(max_steps).into())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+// What to do after running some Code. A branch names the Wasm label to go to, where 0 is the innermost label.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Flow {
+    Next,
+    Branch(u32),
+}
+
+// `If` and `IfElse` are Wasm blocks: a branch of 0 only leaves the block, and any other branch loses one level
+fn exit_block(flow: Flow) -> Flow {
+    match flow {
+        Flow::Next | Flow::Branch(0) => Flow::Next,
+        Flow::Branch(depth) => Flow::Branch(depth - 1),
+    }
+}
+
+// Raises the base to the power by repeated squaring, wrapping around on overflow in the same way as the Wasm of `Power`
+fn wrapping_power(base: i64, exponent: u64) -> i64 {
+    let (mut result, mut base, mut exponent) = (1i64, base, exponent);
+    while exponent != 0 {
+        if exponent & 1 != 0 {
+            result = result.wrapping_mul(base);
+        }
+        base = base.wrapping_mul(base);
+        exponent >>= 1;
+    }
+    result
+}
+
+fn integer_op(
+    left: SlotValue,
+    right: SlotValue,
+    i32_op: fn(i32, i32) -> i32,
+    i64_op: fn(i64, i64) -> i64,
+) -> SlotValue {
+    match (left, right) {
+        (SlotValue::I32(l), SlotValue::I32(r)) => SlotValue::I32(i32_op(l, r)),
+        (SlotValue::I64(l), SlotValue::I64(r)) => SlotValue::I64(i64_op(l, r)),
+        _ => unreachable!(),
+    }
+}
+
+fn arithmetic_op(
+    left: SlotValue,
+    right: SlotValue,
+    i32_op: fn(i32, i32) -> i32,
+    i64_op: fn(i64, i64) -> i64,
+    f32_op: fn(f32, f32) -> f32,
+    f64_op: fn(f64, f64) -> f64,
+) -> SlotValue {
+    match (left, right) {
+        (SlotValue::I32(l), SlotValue::I32(r)) => SlotValue::I32(i32_op(l, r)),
+        (SlotValue::I64(l), SlotValue::I64(r)) => SlotValue::I64(i64_op(l, r)),
+        (SlotValue::F32(l), SlotValue::F32(r)) => SlotValue::F32(f32_op(l, r)),
+        (SlotValue::F64(l), SlotValue::F64(r)) => SlotValue::F64(f64_op(l, r)),
+        _ => unreachable!(),
+    }
+}
+
+fn float_unary_op(value: SlotValue, f32_op: fn(f32) -> f32, f64_op: fn(f64) -> f64) -> SlotValue {
+    match value {
+        SlotValue::F32(v) => SlotValue::F32(f32_op(v)),
+        SlotValue::F64(v) => SlotValue::F64(f64_op(v)),
+        _ => unreachable!(),
+    }
+}
+
+fn float_binary_op(
+    left: SlotValue,
+    right: SlotValue,
+    f32_op: fn(f32, f32) -> f32,
+    f64_op: fn(f64, f64) -> f64,
+) -> SlotValue {
+    match (left, right) {
+        (SlotValue::F32(l), SlotValue::F32(r)) => SlotValue::F32(f32_op(l, r)),
+        (SlotValue::F64(l), SlotValue::F64(r)) => SlotValue::F64(f64_op(l, r)),
+        _ => unreachable!(),
+    }
+}
+
+// Any comparison involving NaN is false
+fn compare(left: SlotValue, right: SlotValue, is_signed: bool, test: fn(Ordering) -> bool) -> bool {
+    let ordering = match (left, right) {
+        (SlotValue::I32(l), SlotValue::I32(r)) if is_signed => Some(l.cmp(&r)),
+        (SlotValue::I32(l), SlotValue::I32(r)) => Some((l as u32).cmp(&(r as u32))),
+        (SlotValue::I64(l), SlotValue::I64(r)) if is_signed => Some(l.cmp(&r)),
+        (SlotValue::I64(l), SlotValue::I64(r)) => Some((l as u64).cmp(&(r as u64))),
+        (SlotValue::F32(l), SlotValue::F32(r)) => l.partial_cmp(&r),
+        (SlotValue::F64(l), SlotValue::F64(r)) => l.partial_cmp(&r),
+        _ => unreachable!(),
+    };
+    ordering.map(test).unwrap_or(false)
+}
+
+// Wasm `min` and `max` return NaN if either side is NaN and order -0.0 below 0.0
+fn min_f32(l: f32, r: f32) -> f32 {
+    if l.is_nan() || r.is_nan() {
+        f32::NAN
+    } else if l == r {
+        if l.is_sign_negative() {
+            l
+        } else {
+            r
+        }
+    } else {
+        l.min(r)
+    }
+}
+
+fn max_f32(l: f32, r: f32) -> f32 {
+    if l.is_nan() || r.is_nan() {
+        f32::NAN
+    } else if l == r {
+        if l.is_sign_positive() {
+            l
+        } else {
+            r
+        }
+    } else {
+        l.max(r)
+    }
+}
+
+fn min_f64(l: f64, r: f64) -> f64 {
+    if l.is_nan() || r.is_nan() {
+        f64::NAN
+    } else if l == r {
+        if l.is_sign_negative() {
+            l
+        } else {
+            r
+        }
+    } else {
+        l.min(r)
+    }
+}
+
+fn max_f64(l: f64, r: f64) -> f64 {
+    if l.is_nan() || r.is_nan() {
+        f64::NAN
+    } else if l == r {
+        if l.is_sign_positive() {
+            l
+        } else {
+            r
+        }
+    } else {
+        l.max(r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+
+    fn evaluator<'a>(params: Vec<ValueType>, results: Vec<ValueType>, is_signed: bool) -> ReferenceEvaluator<'a> {
+        let signature = FunctionSignature::new("main", params, results);
+        let slots = SlotCount {
+            i32: 2,
+            i64: 0,
+            f32: 0,
+            f64: 1,
+        };
+        ReferenceEvaluator::new(&signature, slots, is_signed, SlotInit::Zero).unwrap()
+    }
+
+    #[test]
+    fn arithmetic_follows_the_destination_type() {
+        let mut evaluator = evaluator(vec![ValueType::F32, ValueType::F32], vec![ValueType::I32], true);
+        let mut rng = SmallRng::seed_from_u64(1);
+        let code = vec![Add::new(0, 1, 2), Return::new()];
+
+        // Fractions are truncated before the operation, as in the `Add` doc-test
+        let results = evaluator
+            .evaluate(&code, &[15.5f32.into(), 15.5f32.into()], &mut rng)
+            .unwrap();
+        assert_eq!(vec![SlotValue::I32(30)], results);
+    }
+
+    #[test]
+    fn floats_become_integers_by_the_conversion_profile() {
+        let mut evaluator = evaluator(vec![ValueType::F32], vec![ValueType::I32], true);
+        let mut rng = SmallRng::seed_from_u64(1);
+        let code = vec![CopySlot::new(0, 1), Return::new()];
+        let mut convert = |profile, value: f32| {
+            evaluator.set_conversion_profile(profile);
+            evaluator.evaluate(&code, &[value.into()], &mut rng).unwrap()
+        };
+
+        assert_eq!(
+            vec![SlotValue::I32(i32::MAX)],
+            convert(ConversionProfile::Saturating, 3.0e9)
+        );
+        assert_eq!(
+            vec![SlotValue::I32(3_000_000_000u32 as i32)],
+            convert(ConversionProfile::Wrapping, 3.0e9)
+        );
+        assert_eq!(vec![SlotValue::I32(-2)], convert(ConversionProfile::Wrapping, -2.5));
+        assert_eq!(
+            vec![SlotValue::I32(0)],
+            convert(ConversionProfile::Wrapping, f32::INFINITY)
+        );
+        assert_eq!(
+            vec![SlotValue::I32(0x3f800000)],
+            convert(ConversionProfile::Bitcast, 1.0)
+        );
+    }
+
+    #[test]
+    fn divide_by_zero_leaves_the_destination_unchanged() {
+        let mut evaluator = evaluator(vec![ValueType::I32, ValueType::I32], vec![ValueType::I32], true);
+        let mut rng = SmallRng::seed_from_u64(1);
+        let code = vec![ConstI32::new(2, 7), Divide::new(0, 1, 2), Return::new()];
+
+        let results = evaluator.evaluate(&code, &[9.into(), 0.into()], &mut rng).unwrap();
+        assert_eq!(vec![SlotValue::I32(7)], results);
+
+        let results = evaluator.evaluate(&code, &[9.into(), 2.into()], &mut rng).unwrap();
+        assert_eq!(vec![SlotValue::I32(4)], results);
+
+        let result = evaluator.evaluate(&code, &[i32::MIN.into(), (-1).into()], &mut rng);
+        assert!(matches!(
+            result.unwrap_err().downcast_ref::<WasmgpError>(),
+            Some(WasmgpError::IntegerOverflow)
+        ));
+    }
+
+    #[test]
+    fn break_inside_an_if_continues_the_loop() {
+        let mut evaluator = evaluator(vec![], vec![ValueType::I32], true);
+        evaluator.set_max_steps(1_000);
+        let mut rng = SmallRng::seed_from_u64(1);
+
+        // A `Break` directly inside of the loop leaves it
+        let code = vec![
+            DoFor::new(3, vec![ConstOne::new(1), Add::new(0, 1, 0), Break::new()]),
+            Return::new(),
+        ];
+        assert_eq!(
+            vec![SlotValue::I32(1)],
+            evaluator.evaluate(&code, &[], &mut rng).unwrap()
+        );
+
+        // Inside of an `If` the same branch goes back to the top of the loop without counting down
+        let code = vec![
+            DoFor::new(
+                3,
+                vec![ConstOne::new(1), Add::new(0, 1, 0), If::new(1, vec![Break::new()])],
+            ),
+            Return::new(),
+        ];
+        let result = evaluator.evaluate(&code, &[], &mut rng);
+        assert!(matches!(
+            result.unwrap_err().downcast_ref::<WasmgpError>(),
+            Some(WasmgpError::StepLimitExceeded(1_000))
+        ));
+    }
+
+    #[test]
+    fn invalid_code_is_rejected_before_running() {
+        let mut evaluator = evaluator(vec![], vec![ValueType::I32], true);
+        let mut rng = SmallRng::seed_from_u64(1);
+
+        let code = vec![ConstI32::new(0, 5), Return::new(), ConstI32::new(0, 7)];
+        let err = evaluator.evaluate(&code, &[], &mut rng).unwrap_err();
+        assert_eq!(
+            "Return[1]: Return must be the final Code or be discarded by a loop",
+            err.to_string()
+        );
+
+        // Inside of a loop body, the values are thrown away when the loop goes back to the top
+        let code = vec![DoFor::new(2, vec![Return::new(), ConstI32::new(0, 5)]), Return::new()];
+        assert_eq!(
+            vec![SlotValue::I32(5)],
+            evaluator.evaluate(&code, &[], &mut rng).unwrap()
+        );
+
+        let code = vec![DoFor::new(2, vec![If::new(0, vec![Return::new()])]), Return::new()];
+        let err = evaluator.evaluate(&code, &[], &mut rng).unwrap_err();
+        assert_eq!(
+            "DoFor[0].body[0].body[0]: Return must be the final Code or be discarded by a loop",
+            err.to_string()
+        );
+
+        let code = vec![If::new(1, vec![ConstOne::new(9)])];
+        let err = evaluator.evaluate(&code, &[], &mut rng).unwrap_err();
+        assert_eq!("If[0].body[0]: slot 9 out of range (4 slots defined)", err.to_string());
+
+        let code = vec![Call::new(0, vec![], vec![])];
+        let err = evaluator.evaluate(&code, &[], &mut rng).unwrap_err();
+        assert_eq!("Call[0]: Invalid function index: 0", err.to_string());
+    }
+
+    #[test]
+    fn matches_the_wasm_for_random_code() {
+        let config = WorldConfiguration {
+            main_entry_point: FunctionSignature::new(
+                "main",
+                vec![ValueType::I32, ValueType::F64],
+                vec![ValueType::I64, ValueType::F32],
+            ),
+            work_slots: SlotCount {
+                i32: 2,
+                i64: 2,
+                f32: 1,
+                f64: 1,
+            },
+            individual_max_points: 12,
+            ..Default::default()
+        };
+        let mut world = World::<(), EmptyRunResult>::new(config.clone()).unwrap();
+        let mut evaluator = world.reference_evaluator().unwrap();
+        evaluator.set_max_steps(10_000);
+
+        let slot_count = (config.work_slots.len() + 4) as Slot;
+        let mut engine = GeneticEngine::new(GeneticEngineConfiguration::new(Some(7), slot_count));
+        let mut rng = SmallRng::seed_from_u64(1);
+        let mut compared = 0;
+        for _ in 0..200 {
+            let code = engine.random_code_list(12);
+            let expected = match evaluator.evaluate(&code, &[(-3).into(), 2.5f64.into()], &mut rng) {
+                Ok(results) => results,
+                // The wasm would run until its time limit, or trap
+                Err(_) => continue,
+            };
+
+            let mut store = world.store(());
+            store.set_epoch_deadline(1_000);
+            let instance = world.instanciate(&mut store, &code[..]).unwrap();
+            let func = instance
+                .get_typed_func::<(i32, f64), (i64, f32)>(&mut store, "main")
+                .unwrap();
+            let (r0, r1) = func.call(&mut store, (-3, 2.5)).unwrap();
+            assert_eq!(expected[0], SlotValue::I64(r0), "{:?}", code);
+            match expected[1] {
+                SlotValue::F32(e) => assert!(e == r1 || (e.is_nan() && r1.is_nan()), "{:?}", code),
+                _ => unreachable!(),
+            }
+            compared += 1;
+        }
+        assert!(compared > 100);
+    }
+}
@@ -0,0 +1,286 @@
+use crate::{IslandId, RunResult, WasmgpError, World};
+use anyhow::Result;
+
+/// Rules that change a run between generations, so that the logic of an experiment can be kept in a text file next to
+/// the rest of its settings instead of in the driver. Give the policy to `World::set_run_policy`, and
+/// `World::run_generations_while` applies it after every generation.
+///
+/// A policy is written one rule per line, as `when <condition>: <action>, <action>, ...`. Blank lines and lines that
+/// start with `#` are skipped. The conditions are:
+/// - `generation <n>`: once the World has completed `n` generations
+/// - `island <id> stagnates <n>`: once the best score of the island has not gone up for `n` generations
+/// - `any island stagnates <n>`: the same, for each island on its own
+///
+/// The actions are:
+/// - `mutation_rate <n>` and `crossover_rate <n>`: change the rate of the operation for every island, as the rates are
+///   shared by the whole World
+/// - `migrate`: migrate individuals between the islands right away, as `World::migrate_now`
+/// - `migrate_every <n>`: change `generations_between_migrations`, where zero turns the automatic migrations off
+/// - `reseed <n>`: restart the stagnant island, keeping its `n` most fit individuals, or every island when the
+///   condition is a generation
+///
+/// A stagnation rule that fires starts the count of its island over, so it fires again after another `n` stagnant
+/// generations.
+/// ```
+/// use wasmgp::*;
+///
+/// let policy: RunPolicy = "
+///     # Shake up an island that has stopped improving
+///     when island 2 stagnates 50: mutation_rate 30, migrate
+///     when generation 200: migrate_every 0
+/// "
+/// .parse()
+/// .unwrap();
+/// assert_eq!(2, policy.rules().len());
+/// assert_eq!(vec![PolicyAction::MutationRate(30), PolicyAction::Migrate], policy.rules()[0].actions);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RunPolicy {
+    rules: Vec<PolicyRule>,
+
+    // The best score of each island so far, and the number of generations since it last went up
+    best_scores: Vec<Option<u64>>,
+    stagnant_generations: Vec<usize>,
+    firings: Vec<(usize, usize)>,
+}
+
+/// One rule of a RunPolicy: the actions to take when the condition is met
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PolicyRule {
+    pub condition: PolicyCondition,
+    pub actions: Vec<PolicyAction>,
+}
+
+/// When a PolicyRule fires
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PolicyCondition {
+    /// Once, after the World completes the generation
+    Generation(usize),
+
+    /// After the best score of the island, or of any island when None, has not gone up for the number of generations
+    Stagnates(Option<IslandId>, usize),
+}
+
+/// What a PolicyRule does when it fires
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PolicyAction {
+    /// Sets the `mutation_rate` of the World
+    MutationRate(u8),
+
+    /// Sets the `crossover_rate` of the World
+    CrossoverRate(u8),
+
+    /// Migrates individuals between the islands right away
+    Migrate,
+
+    /// Sets the `generations_between_migrations` of the World
+    MigrateEvery(usize),
+
+    /// Reseeds the islands of the condition, keeping the number of most fit individuals
+    Reseed(usize),
+}
+
+impl RunPolicy {
+    pub fn new(rules: Vec<PolicyRule>) -> RunPolicy {
+        RunPolicy {
+            rules,
+            ..RunPolicy::default()
+        }
+    }
+
+    /// Borrows the rules, in the order they are applied
+    pub fn rules(&self) -> &[PolicyRule] {
+        &self.rules[..]
+    }
+
+    /// Returns the generation and the index of the rule for every time a rule has fired, oldest first
+    pub fn firings(&self) -> &[(usize, usize)] {
+        &self.firings[..]
+    }
+
+    /// Updates the stagnation counts from the generation that just completed, and takes the actions of every rule that
+    /// fires. Call it exactly once after each generation; `World::run_generations_while` does so for the policy of the
+    /// World.
+    pub fn apply<T: Default, R: RunResult>(&mut self, world: &mut World<T, R>) -> Result<()> {
+        let number_of_islands = world.get_number_of_islands();
+        self.best_scores.resize(number_of_islands, None);
+        self.stagnant_generations.resize(number_of_islands, 0);
+        for id in 0..number_of_islands {
+            let island = world.get_island(id).unwrap();
+            if island.most_fit_individual().is_none() {
+                continue;
+            }
+            let score = Some(island.generation_stats().best_score);
+            if score > self.best_scores[id] {
+                self.best_scores[id] = score;
+                self.stagnant_generations[id] = 0;
+            } else {
+                self.stagnant_generations[id] += 1;
+            }
+        }
+
+        // The counts are only started over once every rule has seen them
+        let generation = world.generations_complete();
+        let mut restarted = vec![false; number_of_islands];
+        let mut fired = vec![];
+        for (index, rule) in self.rules.iter().enumerate() {
+            let islands: Vec<IslandId> = match rule.condition {
+                PolicyCondition::Generation(n) if n == generation => (0..number_of_islands).collect(),
+                PolicyCondition::Generation(_) => continue,
+                PolicyCondition::Stagnates(island, n) => (0..number_of_islands)
+                    .filter(|id| island.map_or(true, |island| island == *id))
+                    .filter(|id| self.stagnant_generations[*id] >= n)
+                    .collect(),
+            };
+            if islands.is_empty() {
+                continue;
+            }
+            if let PolicyCondition::Stagnates(..) = rule.condition {
+                for id in islands.iter() {
+                    restarted[*id] = true;
+                }
+            }
+            for action in rule.actions.iter() {
+                match *action {
+                    PolicyAction::MutationRate(rate) => world.set_mutation_rate(rate)?,
+                    PolicyAction::CrossoverRate(rate) => world.set_crossover_rate(rate)?,
+                    PolicyAction::Migrate => world.migrate_now(),
+                    PolicyAction::MigrateEvery(generations) => world.set_generations_between_migrations(generations),
+                    PolicyAction::Reseed(keep_top_n) => {
+                        for id in islands.iter() {
+                            world.get_island_mut(*id).unwrap().reseed(keep_top_n);
+                        }
+                    }
+                }
+            }
+            fired.push((generation, index));
+        }
+        self.firings.append(&mut fired);
+        for (id, restarted) in restarted.into_iter().enumerate() {
+            if restarted {
+                self.stagnant_generations[id] = 0;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for RunPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<RunPolicy> {
+        let mut rules = vec![];
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            rules.push(line.parse()?);
+        }
+        Ok(RunPolicy::new(rules))
+    }
+}
+
+impl std::str::FromStr for PolicyRule {
+    type Err = anyhow::Error;
+
+    fn from_str(line: &str) -> Result<PolicyRule> {
+        let invalid = |reason: &str| WasmgpError::InvalidRunPolicy(format!("{} in '{}'", reason, line));
+        let (condition, actions) = line
+            .strip_prefix("when ")
+            .and_then(|rule| rule.split_once(':'))
+            .ok_or_else(|| invalid("expected 'when <condition>: <actions>'"))?;
+        let words: Vec<&str> = condition.split_whitespace().collect();
+        let number = |word: &str| word.parse().map_err(|_| invalid("expected a number"));
+        let condition = match words[..] {
+            ["generation", n] => PolicyCondition::Generation(number(n)?),
+            ["island", id, "stagnates", n] => PolicyCondition::Stagnates(Some(number(id)?), number(n)?),
+            ["any", "island", "stagnates", n] => PolicyCondition::Stagnates(None, number(n)?),
+            _ => return Err(invalid("unknown condition").into()),
+        };
+        let actions = actions
+            .split(',')
+            .map(|action| match action.split_whitespace().collect::<Vec<&str>>()[..] {
+                ["mutation_rate", n] => n.parse().map(PolicyAction::MutationRate).ok(),
+                ["crossover_rate", n] => n.parse().map(PolicyAction::CrossoverRate).ok(),
+                ["migrate"] => Some(PolicyAction::Migrate),
+                ["migrate_every", n] => n.parse().map(PolicyAction::MigrateEvery).ok(),
+                ["reseed", n] => n.parse().map(PolicyAction::Reseed).ok(),
+                _ => None,
+            })
+            .collect::<Option<Vec<PolicyAction>>>()
+            .ok_or_else(|| invalid("unknown action"))?;
+        Ok(PolicyRule { condition, actions })
+    }
+}
+
+impl std::fmt::Display for RunPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for rule in self.rules.iter() {
+            writeln!(f, "{}", rule)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for PolicyRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.condition {
+            PolicyCondition::Generation(n) => write!(f, "when generation {}:", n)?,
+            PolicyCondition::Stagnates(Some(id), n) => write!(f, "when island {} stagnates {}:", id, n)?,
+            PolicyCondition::Stagnates(None, n) => write!(f, "when any island stagnates {}:", n)?,
+        }
+        for (index, action) in self.actions.iter().enumerate() {
+            let separator = if index == 0 { " " } else { ", " };
+            match action {
+                PolicyAction::MutationRate(n) => write!(f, "{}mutation_rate {}", separator, n)?,
+                PolicyAction::CrossoverRate(n) => write!(f, "{}crossover_rate {}", separator, n)?,
+                PolicyAction::Migrate => write!(f, "{}migrate", separator)?,
+                PolicyAction::MigrateEvery(n) => write!(f, "{}migrate_every {}", separator, n)?,
+                PolicyAction::Reseed(n) => write!(f, "{}reseed {}", separator, n)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, not(feature = "async")))]
+mod tests {
+    use crate::*;
+
+    #[derive(Clone)]
+    struct DoNothing;
+
+    impl IslandCallbacks<(), EmptyRunResult> for DoNothing {
+        fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+            Box::new(Clone::clone(self))
+        }
+
+        fn run_individual(&mut self, _individual: &mut Individual<(), EmptyRunResult>) {}
+    }
+
+    #[test]
+    fn rules_fire_between_generations() {
+        let text = "when island 0 stagnates 2: mutation_rate 5, migrate_every 0\nwhen generation 4: reseed 1\n";
+        let policy: RunPolicy = text.parse().unwrap();
+        assert_eq!(text, policy.to_string());
+        assert!("when island 0 sleeps 2: migrate".parse::<RunPolicy>().is_err());
+        assert!("when generation 4: reseed all".parse::<RunPolicy>().is_err());
+
+        let config = WorldConfiguration {
+            individuals_per_island: 5,
+            individual_max_points: 10,
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+        world.create_island(Box::new(DoNothing));
+        world.set_run_policy(Some(policy));
+
+        // Every score is zero, so the island stagnates from the second generation on
+        world
+            .run_generations_while(|world| world.generations_complete() < 5)
+            .unwrap();
+        let firings = world.run_policy().unwrap().firings();
+        assert_eq!([(3, 0), (4, 1), (5, 0)], firings[..]);
+    }
+}
@@ -0,0 +1,154 @@
+use crate::{ChampionListing, GenerationRecord, MigrationEvent, WorldConfiguration};
+use std::fmt::Write;
+
+// Everything that goes into the report written by `World::write_run_report`
+pub(crate) struct RunReport<'a> {
+    pub config: &'a WorldConfiguration,
+    pub generations_complete: usize,
+    pub history: &'a [GenerationRecord],
+    pub migrations: &'a [MigrationEvent],
+    pub timeline: &'a [ChampionListing],
+    pub champions: Vec<ChampionListing>,
+}
+
+impl<'a> RunReport<'a> {
+    // Writes the report as Markdown, with one section for each part of the run
+    pub fn write_markdown(&self, f: &mut String) -> std::fmt::Result {
+        writeln!(f, "# Run report")?;
+        writeln!(f)?;
+        writeln!(f, "Generations complete: {}", self.generations_complete)?;
+        writeln!(f)?;
+
+        writeln!(f, "## Configuration")?;
+        writeln!(f)?;
+        writeln!(f, "```")?;
+        writeln!(f, "{:#?}", self.config)?;
+        writeln!(f, "```")?;
+        writeln!(f)?;
+
+        writeln!(f, "## Fitness and diversity")?;
+        writeln!(f)?;
+        if self.history.is_empty() {
+            writeln!(f, "No generations were recorded.")?;
+            writeln!(f)?;
+        } else {
+            writeln!(
+                f,
+                "| Generation | Island | Best score | Mean score | Fitness variance | Unique structures | Mean edit distance | Opcode entropy | Immigrants | Emigrants |"
+            )?;
+            writeln!(f, "|---|---|---|---|---|---|---|---|---|---|")?;
+            for record in self.history.iter() {
+                let stats = &record.stats;
+                writeln!(
+                    f,
+                    "| {} | {} | {} | {:.2} | {:.2} | {} | {:.2} | {:.3} | {} | {} |",
+                    record.generation,
+                    record.island,
+                    stats.best_score,
+                    stats.mean_score,
+                    stats.fitness_variance,
+                    stats.unique_structures,
+                    stats.mean_edit_distance,
+                    stats.opcode_entropy,
+                    stats.immigrants,
+                    stats.emigrants
+                )?;
+            }
+            writeln!(f)?;
+        }
+
+        self.write_cross_evaluation(f)?;
+
+        writeln!(f, "## Migrations")?;
+        writeln!(f)?;
+        if self.migrations.is_empty() {
+            writeln!(f, "No individuals migrated.")?;
+            writeln!(f)?;
+        } else {
+            writeln!(f, "| Generation | Individual | From island | To island |")?;
+            writeln!(f, "|---|---|---|---|")?;
+            for event in self.migrations.iter() {
+                writeln!(
+                    f,
+                    "| {} | {} | {} | {} |",
+                    event.generation, event.individual_id, event.source, event.destination
+                )?;
+            }
+            writeln!(f)?;
+        }
+
+        if !self.timeline.is_empty() {
+            writeln!(f, "## Champion timeline")?;
+            writeln!(f)?;
+            for listing in self.timeline.iter() {
+                write_listing(f, listing)?;
+            }
+        }
+
+        writeln!(f, "## Final champions")?;
+        writeln!(f)?;
+        if self.champions.is_empty() {
+            writeln!(f, "No island has a sorted generation.")?;
+        }
+        for listing in self.champions.iter() {
+            write_listing(f, listing)?;
+        }
+
+        Ok(())
+    }
+
+    // Writes the cross evaluation of the last recorded generation, if it had one
+    fn write_cross_evaluation(&self, f: &mut String) -> std::fmt::Result {
+        let generation = match self.history.last() {
+            Some(last) => last.generation,
+            None => return Ok(()),
+        };
+        let rows: Vec<&GenerationRecord> = self
+            .history
+            .iter()
+            .filter(|record| record.generation == generation && !record.stats.cross_evaluation.is_empty())
+            .collect();
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(f, "## Cross evaluation")?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "Mean scores of the elites of each island (columns) under the callbacks of each island (rows) in generation {}.",
+            generation
+        )?;
+        writeln!(f)?;
+        write!(f, "| Evaluator |")?;
+        for source in 0..rows[0].stats.cross_evaluation.len() {
+            write!(f, " Island {} |", source)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "|---|{}", "---|".repeat(rows[0].stats.cross_evaluation.len()))?;
+        for record in rows {
+            write!(f, "| Island {} |", record.island)?;
+            for score in record.stats.cross_evaluation.iter() {
+                match score {
+                    Some(score) => write!(f, " {:.2} |", score)?,
+                    None => write!(f, " - |")?,
+                }
+            }
+            writeln!(f)?;
+        }
+        writeln!(f)
+    }
+}
+
+fn write_listing(f: &mut String, listing: &ChampionListing) -> std::fmt::Result {
+    writeln!(
+        f,
+        "### Island {}, generation {}: individual {} (score {})",
+        listing.island, listing.generation, listing.individual_id, listing.score
+    )?;
+    writeln!(f)?;
+    writeln!(f, "```rust")?;
+    write!(f, "{}", listing.listing)?;
+    writeln!(f, "```")?;
+    writeln!(f)
+}
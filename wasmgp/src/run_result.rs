@@ -1,10 +1,31 @@
 use std::fmt::Debug;
 
-/// This trait is a alias to avoid typing all the restrictions everytime we need to reference them
-pub trait RunResult: Clone + Debug + PartialEq + 'static {}
+/// This trait is a alias to avoid typing all the restrictions everytime we need to reference them. It also lets a
+/// checkpoint keep the results of the generation it was written after, so that a resumed run does not have to run that
+/// generation again to know how fit each individual was.
+pub trait RunResult: Clone + Debug + PartialEq + 'static {
+    /// Writes the result as one line of text for a checkpoint. The default returns None: the result cannot be saved,
+    /// and a run resumed from the checkpoint runs the individuals of the last generation again.
+    fn save_result(&self) -> Option<String> {
+        None
+    }
+
+    /// Reads a result written by `save_result`, or returns None if the text cannot be read
+    fn restore_result(_text: &str) -> Option<Self> {
+        None
+    }
+}
 
 /// This empty struct can be used when run results are not needed. Some tests and doctests make use of this
 #[derive(Clone, Debug, PartialEq)]
 pub struct EmptyRunResult {}
 
-impl RunResult for EmptyRunResult {}
+impl RunResult for EmptyRunResult {
+    fn save_result(&self) -> Option<String> {
+        Some(String::new())
+    }
+
+    fn restore_result(_text: &str) -> Option<Self> {
+        Some(EmptyRunResult {})
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{CallLimit, FunctionSignature};
+
+/// What the runtime enforces while the World evaluates evolved code, for auditing a service that runs untrusted
+/// programs. Returned by `World::security_report`, and printed as Markdown by its `Display` implementation.
+///
+/// Evolved modules declare no memory, tables or globals, and import only the functions listed here. No WASI functions
+/// are ever linked, so evolved code cannot reach files, clocks, the network or the environment except through the
+/// imports that were added to the World.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SecurityReport {
+    /// The milliseconds an individual may run before wasmtime interrupts it, measured by the epoch of the Engine
+    pub run_time_ms: u64,
+
+    /// How often the epoch of the Engine ticks. A run may end up to one tick early.
+    pub epoch_ticks_per_ms: u64,
+
+    /// The fuel an individual may use before it is stopped, from `WorldConfiguration::fuel_per_evaluation`
+    pub fuel_limit: Option<u64>,
+
+    /// The bytes of native stack the code may use, from `WorldConfiguration::max_wasm_stack`. None keeps the limit of
+    /// wasmtime.
+    pub max_wasm_stack: Option<usize>,
+
+    /// The bytes of linear memory an evolved module can reach, which is zero because the modules declare no memory
+    pub memory_bytes: usize,
+
+    /// True when evaluations yield to the async executor every millisecond instead of blocking the thread
+    pub async_epoch_yielding: bool,
+
+    /// The limit on the calls to all imports together during one evaluation
+    pub host_call_budget: Option<CallLimit>,
+
+    /// The functions added to the World with `World::add_function_import` or `World::add_function_import_with_options`
+    pub imports: Vec<ImportReport>,
+
+    /// The imports that the World links for its own use, such as to supply random work slots or to count numeric
+    /// events. Each has a fixed behavior and cannot reach the state of the host.
+    pub built_in_imports: Vec<String>,
+}
+
+/// One function that evolved code may call, and the limits placed on it
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImportReport {
+    /// The name, parameters and results of the import
+    pub signature: FunctionSignature,
+
+    /// The limit on the calls to this import during one evaluation
+    pub call_limit: Option<CallLimit>,
+
+    /// True when repeated calls with the same arguments return the first results without calling the host
+    pub memoize: bool,
+}
+
+impl std::fmt::Display for SecurityReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "# Security report")?;
+        writeln!(f)?;
+        writeln!(f, "## Limits")?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "Run time: {} ms, at {} epoch ticks per ms",
+            self.run_time_ms, self.epoch_ticks_per_ms
+        )?;
+        match self.fuel_limit {
+            Some(fuel) => writeln!(f, "Fuel: {}", fuel)?,
+            None => writeln!(f, "Fuel: not metered")?,
+        }
+        match self.max_wasm_stack {
+            Some(bytes) => writeln!(f, "Wasm stack: {} bytes", bytes)?,
+            None => writeln!(f, "Wasm stack: the wasmtime default")?,
+        }
+        writeln!(f, "Linear memory: {} bytes", self.memory_bytes)?;
+        if self.async_epoch_yielding {
+            writeln!(f, "Evaluations yield to the executor every millisecond")?;
+        }
+        match self.host_call_budget {
+            Some(budget) => writeln!(
+                f,
+                "Host call budget: {} calls, then {:?}",
+                budget.max_calls, budget.policy
+            )?,
+            None => writeln!(f, "Host call budget: none")?,
+        }
+        writeln!(f)?;
+
+        writeln!(f, "## Imports")?;
+        writeln!(f)?;
+        if self.imports.is_empty() && self.built_in_imports.is_empty() {
+            writeln!(f, "No functions are imported.")?;
+        }
+        for import in self.imports.iter() {
+            write!(
+                f,
+                "- {}({:?}) -> {:?}",
+                import.signature.name(),
+                import.signature.params(),
+                import.signature.results()
+            )?;
+            if let Some(limit) = import.call_limit {
+                write!(f, ", at most {} calls, then {:?}", limit.max_calls, limit.policy)?;
+            }
+            if import.memoize {
+                write!(f, ", memoized")?;
+            }
+            writeln!(f)?;
+        }
+        for name in self.built_in_imports.iter() {
+            writeln!(f, "- {} (built in)", name)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "WASI: not linked")
+    }
+}
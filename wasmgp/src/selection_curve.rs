@@ -1,9 +1,12 @@
+use std::fmt;
+use std::sync::Arc;
+
 const NOT_QUITE_ONE: f64 = 0.9999999999f64;
 
 /// Defines the algorithm used when a random individual is needed from a pool of individuals that has been sorted by a
 /// fitness function. The sorting algorithm defines the greatest fitness as being sorted at the end of a vector where
 /// `pool.sort_by(fitness_fn)` has been called.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum SelectionCurve {
     // All individuals are as likely as any other to be selected
     Fair,
@@ -25,18 +28,125 @@ pub enum SelectionCurve {
 
     // The less fit individuals will appear much more often
     StrongPreferenceForUnfit,
+
+    // Each individual is selected in proportion to a user supplied density of its rank. See `SelectionCurve::custom`
+    Custom(CustomCurve),
+
+    // Each individual is selected in proportion to `exp(score / temperature)`, where the score comes from
+    // `IslandCallbacks::score_individual` and the temperature falls as the island ages. A high temperature selects
+    // almost fairly (exploration) and a low one almost always selects the best (exploitation).
+    Boltzmann(TemperatureSchedule),
+
+    // The population is split into a group of the fittest `top_fraction` and the rest. The fit group is chosen with a
+    // probability of `top_probability`, and then an individual is chosen fairly from within the group. See
+    // `SelectionCurve::koza_over_selection`
+    OverSelection { top_fraction: f64, top_probability: f64 },
+}
+
+/// How the temperature of `SelectionCurve::Boltzmann` changes over the generations of an island. Temperatures are in
+/// the same units as the scores of the individuals: two individuals whose scores differ by the temperature differ in
+/// how likely they are to be selected by a factor of e.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TemperatureSchedule {
+    /// The temperature never changes
+    Constant(f64),
+
+    /// The temperature starts at `initial` and is multiplied by `rate` after each generation, until it reaches
+    /// `minimum`
+    Exponential { initial: f64, rate: f64, minimum: f64 },
+
+    /// The temperature starts at `initial` and is reduced by `step` after each generation, until it reaches `minimum`
+    Linear { initial: f64, step: f64, minimum: f64 },
+}
+
+impl TemperatureSchedule {
+    /// Returns the temperature after the given number of generations
+    pub fn temperature(&self, generation: usize) -> f64 {
+        match *self {
+            TemperatureSchedule::Constant(temperature) => temperature,
+            TemperatureSchedule::Exponential { initial, rate, minimum } => {
+                f64::max(minimum, initial * rate.powi(generation.min(i32::MAX as usize) as i32))
+            }
+            TemperatureSchedule::Linear { initial, step, minimum } => {
+                f64::max(minimum, initial - step * generation as f64)
+            }
+        }
+    }
 }
 
 impl SelectionCurve {
-    /// Randomly selects a value in the range [0 .. number_of_individuals] according to the SelectionCurve properties
+    /// Creates a curve from a function that maps the rank of an individual to how likely it is to be selected. The rank
+    /// is a fraction in [0.0, 1.0], where 0.0 is the least fit individual and 1.0 the most fit. The values returned are
+    /// relative densities: they do not need to sum to one, and an individual whose density is zero or less is never
+    /// selected.
+    ///
+    /// ```
+    /// use wasmgp::SelectionCurve;
+    ///
+    /// // Never select the least fit half, and select the rest in proportion to their rank
+    /// let curve = SelectionCurve::custom(|rank| if rank < 0.5 { 0.0 } else { rank });
+    /// let mut rng = rand::thread_rng();
+    /// assert!(curve.pick_one_index(&mut rng, 10) >= 5);
+    /// ```
+    pub fn custom<F: Fn(f64) -> f64 + Send + Sync + 'static>(density: F) -> SelectionCurve {
+        SelectionCurve::Custom(CustomCurve(Arc::new(density)))
+    }
+
+    /// Creates the over-selection used by Koza for large populations: 80% of the selections come from a fit group of
+    /// 320 individuals, which is 32% of a population of 1000 and a smaller fraction of larger populations. Smaller
+    /// populations use a fit group of 32%.
+    pub fn koza_over_selection(individuals_per_island: usize) -> SelectionCurve {
+        SelectionCurve::OverSelection {
+            top_fraction: 320.0 / usize::max(1000, individuals_per_island) as f64,
+            top_probability: 0.8,
+        }
+    }
+
+    /// Returns true if the curve selects by the scores of the individuals rather than only by their order, in which case
+    /// `pick_one_scored_index` should be used.
+    pub fn uses_scores(&self) -> bool {
+        matches!(self, SelectionCurve::Boltzmann(_))
+    }
+
+    /// Randomly selects a value in the range [0 .. number_of_individuals] according to the SelectionCurve properties.
+    ///
+    /// There are no scores to use, so a Boltzmann curve uses the rank of each individual as its score: 0 for the least
+    /// fit, 1 for the next, and so on. It is treated as the first generation.
     pub fn pick_one_index<R: rand::Rng>(&self, rng: &mut R, number_of_individuals: usize) -> usize {
+        match self {
+            SelectionCurve::Custom(custom) => return custom.pick_one_index(rng, number_of_individuals),
+            SelectionCurve::Boltzmann(_) => {
+                let ranks: Vec<u64> = (0..number_of_individuals as u64).collect();
+                return self.pick_one_scored_index(rng, &ranks[..], 0);
+            }
+            SelectionCurve::OverSelection {
+                top_fraction,
+                top_probability,
+            } => {
+                // The fit group always has at least one individual, and the rest of the population may be empty
+                let top_size = (number_of_individuals as f64 * top_fraction.clamp(0.0, 1.0)).ceil() as usize;
+                let top_size = top_size.clamp(1, usize::max(1, number_of_individuals));
+                let rest_size = number_of_individuals.saturating_sub(top_size);
+                return if rest_size == 0 || rng.gen_bool(top_probability.clamp(0.0, 1.0)) {
+                    rest_size + rng.gen_range(0..top_size)
+                } else {
+                    rng.gen_range(0..rest_size)
+                };
+            }
+            _ => {}
+        }
+
         // Pick a value in the range of (0.0 .. 1.0] (includes zero, but not one). This behavior is part of the
         // guarantee of the rand::distributions::Standard spec
         let pick: f64 = rng.gen();
 
         // Use exponential scaling for the preferences
         let pick = match &self {
-            SelectionCurve::Fair => pick,
+            // The other curves have already returned their pick
+            SelectionCurve::Fair
+            | SelectionCurve::Custom(_)
+            | SelectionCurve::Boltzmann(_)
+            | SelectionCurve::OverSelection { .. } => pick,
             SelectionCurve::SlightPreferenceForFit | SelectionCurve::SlightPreferenceForUnfit => pick * pick,
             SelectionCurve::PreferenceForFit | SelectionCurve::PreferenceForUnfit => pick * pick * pick,
             SelectionCurve::StrongPreferenceForFit | SelectionCurve::StrongPreferenceForUnfit => {
@@ -58,6 +168,83 @@ impl SelectionCurve {
         // Multiply the pick by the number of individuals and turn it into an integer
         (pick * number_of_individuals as f64).floor() as usize
     }
+
+    /// Randomly selects an index of `scores`, which are the scores of individuals sorted from least to most fit. The
+    /// `generation` is the number of generations the population has been bred for, and sets the temperature of a
+    /// Boltzmann curve. Every other curve ignores the scores and uses `pick_one_index`.
+    ///
+    /// When the temperature is zero or less, the individual with the best score is always selected.
+    pub fn pick_one_scored_index<R: rand::Rng>(&self, rng: &mut R, scores: &[u64], generation: usize) -> usize {
+        let schedule = match self {
+            SelectionCurve::Boltzmann(schedule) => schedule,
+            _ => return self.pick_one_index(rng, scores.len()),
+        };
+
+        // Subtracting the best score keeps every exponent at zero or less, so that nothing overflows
+        let best = scores.iter().copied().max().unwrap_or_default();
+        let temperature = schedule.temperature(generation);
+        if temperature.is_nan() || temperature <= 0.0 {
+            return scores.iter().rposition(|score| *score == best).unwrap_or_default();
+        }
+        let weights: Vec<f64> = scores
+            .iter()
+            .map(|score| f64::exp(-((best - score) as f64) / temperature))
+            .collect();
+        pick_weighted_index(rng, &weights[..])
+    }
+}
+
+// Picks an index with a probability in proportion to its weight. Weights that are not finite or are zero or less are
+// never picked. When no weight can be picked, every index is equally likely.
+fn pick_weighted_index<R: rand::Rng>(rng: &mut R, weights: &[f64]) -> usize {
+    let usable = |weight: f64| weight.is_finite() && weight > 0.0;
+    let total: f64 = weights.iter().copied().filter(|weight| usable(*weight)).sum();
+    if !(total > 0.0 && total.is_finite()) {
+        return rng.gen_range(0..weights.len());
+    }
+
+    let mut pick = rng.gen::<f64>() * total;
+    for (index, weight) in weights
+        .iter()
+        .copied()
+        .enumerate()
+        .filter(|(_, weight)| usable(*weight))
+    {
+        if pick < weight {
+            return index;
+        }
+        pick -= weight;
+    }
+
+    // Rounding error can leave a little of the pick over, which belongs to the last index that can be picked
+    weights.iter().rposition(|weight| usable(*weight)).unwrap()
+}
+
+/// The density function of a `SelectionCurve::Custom`. Two custom curves are only equal if they share the same function.
+#[derive(Clone)]
+pub struct CustomCurve(Arc<dyn Fn(f64) -> f64 + Send + Sync>);
+
+impl CustomCurve {
+    // Each individual is weighted by the density at the middle of its rank. When no individual has a usable weight,
+    // they are all equally likely.
+    fn pick_one_index<R: rand::Rng>(&self, rng: &mut R, number_of_individuals: usize) -> usize {
+        let weights: Vec<f64> = (0..number_of_individuals)
+            .map(|index| (self.0)((index as f64 + 0.5) / number_of_individuals as f64))
+            .collect();
+        pick_weighted_index(rng, &weights[..])
+    }
+}
+
+impl fmt::Debug for CustomCurve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CustomCurve")
+    }
+}
+
+impl PartialEq for CustomCurve {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
 }
 
 #[cfg(test)]
@@ -253,4 +440,139 @@ mod tests {
             last_bucket_count = bucket;
         }
     }
+
+    #[test]
+    fn custom_selection_curve() {
+        // A density that rises linearly with rank selects the last bucket about twice as often as the middle one
+        let buckets = pick_100_000_times(SelectionCurve::custom(|rank| rank));
+        let middle: usize = buckets[45..55].iter().sum();
+        let last: usize = buckets[90..100].iter().sum();
+        assert!(
+            last > middle * 17 / 10 && last < middle * 23 / 10,
+            "middle {}, last {}",
+            middle,
+            last
+        );
+
+        // Individuals with no density are never chosen
+        let buckets = pick_100_000_times(SelectionCurve::custom(|rank| if rank < 0.25 { 0.0 } else { 1.0 }));
+        assert!(buckets[..25].iter().all(|&bucket| bucket == 0));
+        assert!(buckets[25..].iter().all(|&bucket| bucket > 1200 && bucket < 1470));
+
+        // Unusable densities fall back to a fair selection
+        let buckets = pick_100_000_times(SelectionCurve::custom(|_| f64::NAN));
+        assert!(buckets.iter().all(|&bucket| bucket > 850 && bucket < 1150));
+    }
+
+    #[test]
+    fn custom_curves_are_equal_only_to_their_clones() {
+        let curve = SelectionCurve::custom(|rank| rank);
+        assert_eq!(curve, curve.clone());
+        assert_ne!(curve, SelectionCurve::custom(|rank| rank));
+        assert_ne!(curve, SelectionCurve::Fair);
+    }
+
+    #[test]
+    fn temperature_schedules() {
+        assert_eq!(5.0, TemperatureSchedule::Constant(5.0).temperature(100));
+
+        let exponential = TemperatureSchedule::Exponential {
+            initial: 100.0,
+            rate: 0.5,
+            minimum: 10.0,
+        };
+        assert_eq!(100.0, exponential.temperature(0));
+        assert_eq!(25.0, exponential.temperature(2));
+        assert_eq!(10.0, exponential.temperature(4));
+        assert_eq!(10.0, exponential.temperature(usize::MAX));
+
+        let linear = TemperatureSchedule::Linear {
+            initial: 100.0,
+            step: 30.0,
+            minimum: 0.0,
+        };
+        assert_eq!(70.0, linear.temperature(1));
+        assert_eq!(0.0, linear.temperature(4));
+    }
+
+    #[test]
+    fn boltzmann_selection_cools_toward_the_best() {
+        let curve = SelectionCurve::Boltzmann(TemperatureSchedule::Exponential {
+            initial: 1000.0,
+            rate: 0.1,
+            minimum: 0.0,
+        });
+        assert!(curve.uses_scores());
+        let scores: Vec<u64> = (0..10).map(|score| score * 10).collect();
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(1234);
+        let mut pick_10_000_times = |generation: usize| {
+            let mut buckets = [0usize; 10];
+            for _ in 0..10_000 {
+                buckets[curve.pick_one_scored_index(&mut rng, &scores[..], generation)] += 1;
+            }
+            buckets
+        };
+
+        // While hot, the scores barely matter
+        let buckets = pick_10_000_times(0);
+        assert!(
+            buckets.iter().all(|&bucket| bucket > 800 && bucket < 1200),
+            "{:?}",
+            buckets
+        );
+
+        // At a temperature of 10, each step of 10 points is e times as likely as the one below it
+        let buckets = pick_10_000_times(2);
+        assert!(
+            buckets[9] > 6000 && buckets[8] > 2000 && buckets[0] == 0,
+            "{:?}",
+            buckets
+        );
+
+        // Once frozen, only the best is selected
+        let buckets = pick_10_000_times(20);
+        assert_eq!(10_000, buckets[9]);
+    }
+
+    #[test]
+    fn over_selection_curve() {
+        let buckets = pick_100_000_times(SelectionCurve::OverSelection {
+            top_fraction: 0.2,
+            top_probability: 0.8,
+        });
+
+        // 80% of the picks are shared by the fittest 20 individuals, and the other 20% by the remaining 80
+        assert!(
+            buckets[80..].iter().all(|&bucket| bucket > 3700 && bucket < 4300),
+            "{:?}",
+            buckets
+        );
+        assert!(
+            buckets[..80].iter().all(|&bucket| bucket > 200 && bucket < 300),
+            "{:?}",
+            buckets
+        );
+
+        // A fit group of everyone is a fair selection
+        let buckets = pick_100_000_times(SelectionCurve::OverSelection {
+            top_fraction: 1.0,
+            top_probability: 0.8,
+        });
+        assert!(buckets.iter().all(|&bucket| (900..=1100).contains(&bucket)));
+    }
+
+    #[test]
+    fn koza_over_selection_keeps_a_fit_group_of_320() {
+        let fraction = |curve: SelectionCurve| match curve {
+            SelectionCurve::OverSelection { top_fraction, .. } => top_fraction,
+            _ => panic!("not an over-selection"),
+        };
+        assert_eq!(0.32, fraction(SelectionCurve::koza_over_selection(500)));
+        assert_eq!(0.32, fraction(SelectionCurve::koza_over_selection(1000)));
+        assert_eq!(0.08, fraction(SelectionCurve::koza_over_selection(4000)));
+
+        // A single individual is always selected
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(1234);
+        assert_eq!(0, SelectionCurve::koza_over_selection(1).pick_one_index(&mut rng, 1));
+    }
 }
@@ -0,0 +1,101 @@
+use crate::SelectionCurve;
+
+/// How one individual is selected from an island whose individuals have been sorted from the least fit to the most
+/// fit. The World selects parents, elites and migrants with a strategy each, so that, for example, parents can be
+/// chosen by tournament while the elites still follow a curve.
+///
+/// Those fields of the `WorldConfiguration` held a `SelectionCurve` before there were strategies. A curve converts into
+/// a strategy, so a configuration that set `select_as_parent: SelectionCurve::Fair` now sets
+/// `select_as_parent: SelectionCurve::Fair.into()`, and a strategy still compares equal to the curve it holds.
+/// ```
+/// use wasmgp::*;
+///
+/// let config = WorldConfiguration {
+///     select_as_parent: SelectionStrategy::tournament(4),
+///     select_for_migration: SelectionCurve::Fair.into(),
+///     ..WorldConfiguration::default()
+/// };
+///
+/// // The most fit of four draws is rarely from the least fit half
+/// let mut rng = rand::thread_rng();
+/// let picks: Vec<usize> = (0..1000).map(|_| config.select_as_parent.pick_one_index(&mut rng, 10)).collect();
+/// assert!(picks.iter().filter(|pick| **pick < 5).count() < 200);
+/// assert!(config.select_for_migration == SelectionCurve::Fair);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum SelectionStrategy {
+    /// The individual is picked by its rank, or by its score, according to the curve
+    Curve(SelectionCurve),
+
+    /// `size` individuals are drawn at random, and the most fit of them is picked. An individual may be drawn more
+    /// than once. Larger tournaments favor the fit more strongly, and a tournament of one selects fairly. The size
+    /// must be at least one.
+    Tournament { size: usize },
+}
+
+impl SelectionStrategy {
+    /// Creates a `SelectionStrategy::Tournament` of `size` individuals
+    pub fn tournament(size: usize) -> SelectionStrategy {
+        SelectionStrategy::Tournament { size }
+    }
+
+    /// Randomly selects a value in the range [0 .. number_of_individuals] according to the strategy. A curve picks with
+    /// `SelectionCurve::pick_one_index`, so it does not see any scores.
+    pub fn pick_one_index<R: rand::Rng>(&self, rng: &mut R, number_of_individuals: usize) -> usize {
+        match self {
+            SelectionStrategy::Curve(curve) => curve.pick_one_index(rng, number_of_individuals),
+            SelectionStrategy::Tournament { size } => {
+                // The individuals are sorted, so the most fit of the draws is the one with the highest index
+                (0..usize::max(1, *size))
+                    .map(|_| rng.gen_range(0..usize::max(1, number_of_individuals)))
+                    .max()
+                    .unwrap()
+            }
+        }
+    }
+}
+
+impl From<SelectionCurve> for SelectionStrategy {
+    fn from(curve: SelectionCurve) -> Self {
+        SelectionStrategy::Curve(curve)
+    }
+}
+
+impl PartialEq<SelectionCurve> for SelectionStrategy {
+    fn eq(&self, other: &SelectionCurve) -> bool {
+        matches!(self, SelectionStrategy::Curve(curve) if curve == other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn larger_tournaments_favor_the_fit_more_strongly() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let mut mean_pick = |strategy: SelectionStrategy| -> f64 {
+            let picks: Vec<usize> = (0..10_000).map(|_| strategy.pick_one_index(&mut rng, 100)).collect();
+            assert!(picks.iter().all(|pick| *pick < 100));
+            picks.iter().sum::<usize>() as f64 / picks.len() as f64
+        };
+
+        // A tournament of k picks the maximum of k draws, whose mean is about k / (k + 1) of the way up
+        let fair = mean_pick(SelectionStrategy::tournament(1));
+        let pairs = mean_pick(SelectionStrategy::tournament(2));
+        let sevens = mean_pick(SelectionStrategy::tournament(7));
+        assert!((fair - 49.5).abs() < 2.0);
+        assert!((pairs - 66.0).abs() < 2.0);
+        assert!((sevens - 87.0).abs() < 2.0);
+
+        assert_eq!(0, SelectionStrategy::tournament(3).pick_one_index(&mut rng, 1));
+        assert_eq!(
+            SelectionStrategy::Curve(SelectionCurve::Fair),
+            SelectionCurve::Fair.into()
+        );
+        assert!(SelectionStrategy::Curve(SelectionCurve::Fair) == SelectionCurve::Fair);
+        assert!(SelectionStrategy::tournament(1) != SelectionCurve::Fair);
+    }
+}
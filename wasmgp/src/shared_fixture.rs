@@ -0,0 +1,110 @@
+use crate::EvaluationContext;
+use std::sync::Arc;
+
+/// Holds data that every individual of a generation is evaluated against, such as a pre-shuffled set of decks, so that
+/// it is built once per generation instead of once per individual. Keep it in the island's callbacks, call `refresh`
+/// from `IslandCallbacks::use_evaluation_context`, and borrow it with `get` while building the state for each
+/// individual in `run_individual`. Every clone refers to the same fixture, so the clones of the callbacks that run
+/// individuals concurrently share it instead of copying it.
+/// ```
+/// use wasmgp::*;
+///
+/// let mut decks: SharedFixture<Vec<u64>> = SharedFixture::new();
+/// let context = EvaluationContext {
+///     generation: 3,
+///     ..EvaluationContext::default()
+/// };
+/// decks.refresh(&context, |context| (0..100).map(|deck| context.seed ^ deck).collect());
+///
+/// // Refreshing again in the same generation keeps the fixture that was built
+/// decks.refresh(&context, |_| panic!("the fixture is only built once per generation"));
+/// let clone = decks.clone();
+/// assert_eq!(100, clone.get().unwrap().len());
+/// ```
+pub struct SharedFixture<F> {
+    generation: Option<usize>,
+    fixture: Option<Arc<F>>,
+}
+
+impl<F> SharedFixture<F> {
+    pub fn new() -> SharedFixture<F> {
+        SharedFixture {
+            generation: None,
+            fixture: None,
+        }
+    }
+
+    /// Builds the fixture for the generation of the context with `create`, unless it was already built for that
+    /// generation
+    pub fn refresh<C: FnOnce(&EvaluationContext) -> F>(&mut self, context: &EvaluationContext, create: C) {
+        if self.generation != Some(context.generation) || self.fixture.is_none() {
+            self.fixture = Some(Arc::new(create(context)));
+            self.generation = Some(context.generation);
+        }
+    }
+
+    /// Borrows the fixture of the most recent generation, or returns None if `refresh` has never been called, such as
+    /// when an individual is run by `World::evaluate` before the first generation
+    pub fn get(&self) -> Option<&F> {
+        self.fixture.as_deref()
+    }
+
+    /// Returns the generation the fixture was built for
+    pub fn generation(&self) -> Option<usize> {
+        self.generation
+    }
+}
+
+impl<F> Clone for SharedFixture<F> {
+    fn clone(&self) -> Self {
+        SharedFixture {
+            generation: self.generation,
+            fixture: self.fixture.clone(),
+        }
+    }
+}
+
+impl<F> Default for SharedFixture<F> {
+    fn default() -> Self {
+        SharedFixture::new()
+    }
+}
+
+impl<F> std::fmt::Debug for SharedFixture<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SharedFixture")
+            .field("generation", &self.generation)
+            .field("is_built", &self.fixture.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn fixtures_are_built_once_per_generation_and_shared_by_clones() {
+        let mut fixture: SharedFixture<Vec<u64>> = SharedFixture::new();
+        assert!(fixture.get().is_none());
+
+        let mut built = 0;
+        for generation in [0, 0, 1, 1, 1] {
+            let context = EvaluationContext {
+                generation,
+                seed: generation as u64,
+                ..EvaluationContext::default()
+            };
+            fixture.refresh(&context, |context| {
+                built += 1;
+                vec![context.seed; 10]
+            });
+        }
+        assert_eq!(2, built);
+        assert_eq!(Some(1), fixture.generation());
+
+        let clone = fixture.clone();
+        assert!(std::ptr::eq(fixture.get().unwrap(), clone.get().unwrap()));
+        assert_eq!(vec![1; 10], *clone.get().unwrap());
+    }
+}
@@ -1,3 +1,5 @@
+use crate::{SlotValue, ValueType};
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum SlotInit {
     /// Initialize all slots to zero (default)
@@ -6,6 +8,31 @@ pub enum SlotInit {
     /// Initialize all slots to one
     One,
 
-    /// Initialize all slots to a random value
+    /// Initialize all slots to a random value. The values are chosen when the individual is compiled, so every call to
+    /// the same individual starts with the same values.
     Random,
+
+    /// Initialize all slots to new random values at the start of every call. Programs evolved this way cannot rely on
+    /// the starting value of a work slot, which makes them robust to uninitialized state. Integer slots may hold any
+    /// value and float slots a value from zero to one.
+    ///
+    /// The values come from the generator behind `WorldConfiguration::random_import`, which is seeded from the
+    /// World's `seed` and the ID of the individual. It restarts when each evaluation begins and continues from one call
+    /// to the next within it, so an individual sees the same values every time it is evaluated.
+    RandomPerCall,
 }
+
+// Turns 64 random bits into a value for a slot of the specified type, in the same way as the code emitted for
+// `SlotInit::RandomPerCall`: integers keep the low bits, and floats scale the bits as an unsigned integer into 0..=1.
+pub(crate) fn random_slot_value(value_type: ValueType, bits: u64) -> SlotValue {
+    match value_type {
+        ValueType::I32 => SlotValue::I32(bits as i64 as i32),
+        ValueType::I64 => SlotValue::I64(bits as i64),
+        ValueType::F32 => SlotValue::F32(bits as f32 * RANDOM_F32_SCALE),
+        ValueType::F64 => SlotValue::F64(bits as f64 * RANDOM_F64_SCALE),
+    }
+}
+
+// The multipliers that scale an unsigned 64-bit integer into 0..=1 (2^-64)
+pub(crate) const RANDOM_F32_SCALE: f32 = 1.0 / 18446744073709551616.0;
+pub(crate) const RANDOM_F64_SCALE: f64 = 1.0 / 18446744073709551616.0;
@@ -0,0 +1,376 @@
+use crate::*;
+use std::collections::BTreeSet;
+
+/// Which slots a list of code reads and writes, found by following the code backwards from its end. Useful to see
+/// which inputs a program really uses, or how many work slots a domain needs. The slots in `live_out` are the ones read
+/// once the code is done, which for a function are its result slots.
+///
+/// Loops and branches are followed along every path, so a slot listed in `read_before_written` is one that *may* be
+/// read before it is written, and a write listed in `dead_writes` is one that is never read on any path.
+/// ```
+/// use wasmgp::*;
+///
+/// // Two params in slots 0 and 1, one result in slot 2, and work slots 3 and 4
+/// let code = vec![
+///     ConstOne::new(3),
+///     Add::new(0, 3, 4),
+///     Multiply::new(0, 0, 2),
+/// ];
+/// let liveness = SlotLiveness::analyze(&code, &[2]);
+/// assert_eq!(vec![0], liveness.read_before_written);
+/// assert_eq!(vec![0, 3], liveness.read);
+/// assert_eq!(vec![2, 3, 4], liveness.written);
+///
+/// // Slot 4 is never read, so the Add does nothing, and then neither does the ConstOne
+/// assert_eq!(vec![1], liveness.dead_writes);
+/// assert_eq!(vec![Multiply::new(0, 0, 2)], remove_dead_writes(&code, &[2]));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SlotLiveness {
+    /// The slots that may be read before the code writes them, in order. These are the inputs of the code, and include
+    /// any slot of `live_out` that is not always written.
+    pub read_before_written: Vec<Slot>,
+
+    /// Every slot that the code reads, in order
+    pub read: Vec<Slot>,
+
+    /// Every slot that the code writes, in order
+    pub written: Vec<Slot>,
+
+    /// The code items whose results are never read, numbered in the order that `Code::walk` visits them across the
+    /// whole list. A `Call` is never listed, since the host function may have other effects.
+    pub dead_writes: Vec<usize>,
+}
+
+impl SlotLiveness {
+    pub fn analyze(code: &[Code], live_out: &[Slot]) -> SlotLiveness {
+        let mut read = BTreeSet::new();
+        let mut written = BTreeSet::new();
+        for c in code.iter() {
+            c.walk(&mut |c| {
+                let (reads, writes, _) = effects(c);
+                read.extend(reads);
+                written.extend(writes);
+            });
+        }
+
+        let mut analysis = Analysis {
+            live_out: live_out.iter().copied().collect(),
+            dead_writes: BTreeSet::new(),
+        };
+        let live_in = analysis.list(code, 0, analysis.live_out.clone(), None, true);
+        SlotLiveness {
+            read_before_written: live_in.into_iter().collect(),
+            read: read.into_iter().collect(),
+            written: written.into_iter().collect(),
+            dead_writes: analysis.dead_writes.into_iter().collect(),
+        }
+    }
+
+    /// Returns the highest slot that the code reads or writes, or None if it uses no slots
+    pub fn highest_slot(&self) -> Option<Slot> {
+        self.read.last().copied().max(self.written.last().copied())
+    }
+}
+
+/// Removes every code item whose results are never read, as found by `SlotLiveness::analyze`, until none are left.
+/// Removing one dead write can make the writes that fed it dead as well.
+pub fn remove_dead_writes(code: &[Code], live_out: &[Slot]) -> Vec<Code> {
+    let mut code = code.to_vec();
+    loop {
+        let dead: BTreeSet<usize> = SlotLiveness::analyze(&code, live_out).dead_writes.into_iter().collect();
+        if dead.is_empty() {
+            return code;
+        }
+        code = remove_items(&code, 0, &dead);
+    }
+}
+
+type Live = BTreeSet<Slot>;
+
+struct Analysis {
+    live_out: Live,
+    dead_writes: BTreeSet<usize>,
+}
+
+impl Analysis {
+    // Returns the slots live before the list, given the slots live after it. The first item is numbered `first`, and
+    // `on_break` holds the slots live after the innermost loop. Dead writes are only recorded when `record` is set, so
+    // that the passes that search for the slots live around a loop do not record writes that a later pass finds live.
+    fn list(&mut self, code: &[Code], first: usize, live_after: Live, on_break: Option<&Live>, record: bool) -> Live {
+        let mut starts = vec![];
+        let mut index = first;
+        for c in code.iter() {
+            starts.push(index);
+            index += c.points();
+        }
+
+        let mut live = live_after;
+        for (c, index) in code.iter().zip(starts).rev() {
+            live = self.item(c, index, live, on_break, record);
+        }
+        live
+    }
+
+    fn item(&mut self, code: &Code, index: usize, live_after: Live, on_break: Option<&Live>, record: bool) -> Live {
+        match code {
+            Code::Return(_) => self.live_out.clone(),
+
+            // A break outside of a loop does nothing
+            Code::Break(_) => on_break.cloned().unwrap_or(live_after),
+            Code::BreakIf(c) => {
+                let mut live = live_after;
+                live.extend(on_break.into_iter().flatten());
+                live.insert(c.break_if_not_zero());
+                live
+            }
+            Code::If(c) => {
+                let mut live = self.list(c.do_this(), index + 1, live_after.clone(), on_break, record);
+                live.extend(live_after);
+                live.insert(c.if_not_zero());
+                live
+            }
+            Code::IfElse(c) => {
+                let else_first = index + 1 + c.do_this().iter().map(|c| c.points()).sum::<usize>();
+                let mut live = self.list(c.do_this(), index + 1, live_after.clone(), on_break, record);
+                live.extend(self.list(c.else_do_this(), else_first, live_after, on_break, record));
+                live.insert(c.if_not_zero());
+                live
+            }
+            Code::DoUntil(c) => {
+                let condition = Some(c.until_not_zero());
+                self.loop_body(c.do_this(), index + 1, condition, false, live_after, record)
+            }
+            Code::DoWhile(c) => {
+                let condition = Some(c.while_not_zero());
+                self.loop_body(c.do_this(), index + 1, condition, true, live_after, record)
+            }
+            Code::DoFor(c) if c.times() == 0 => live_after,
+            Code::DoFor(c) => self.loop_body(c.do_this(), index + 1, None, false, live_after, record),
+            Code::Provenance(c) => self.list(c.do_this(), index + 1, live_after, on_break, record),
+            c => {
+                let (reads, writes, has_side_effects) = effects(c);
+                if record && !has_side_effects && !writes.is_empty() && writes.iter().all(|s| !live_after.contains(s)) {
+                    self.dead_writes.insert(index);
+                }
+                let mut live = live_after;
+                for slot in writes {
+                    live.remove(&slot);
+                }
+                live.extend(reads);
+                live
+            }
+        }
+    }
+
+    // Returns the slots live before a loop, which reads its `condition` after each pass, or before each pass when
+    // `check_first` is set. The body may run again after each pass, so the slots live at the start of the body are
+    // found by repeating the body until they stop growing.
+    fn loop_body(
+        &mut self,
+        body: &[Code],
+        first: usize,
+        condition: Option<Slot>,
+        check_first: bool,
+        live_after: Live,
+        record: bool,
+    ) -> Live {
+        let after_pass = |head: &Live| -> Live {
+            let mut live = live_after.clone();
+            live.extend(head.iter().copied());
+            live.extend(condition);
+            live
+        };
+        let mut head = Live::new();
+        loop {
+            let next = self.list(body, first, after_pass(&head), Some(&live_after), false);
+            if next == head {
+                break;
+            }
+            head = next;
+        }
+        if record {
+            self.list(body, first, after_pass(&head), Some(&live_after), true);
+        }
+        if check_first {
+            after_pass(&head)
+        } else {
+            head
+        }
+    }
+}
+
+// Returns the slots that a code item without a body reads and writes, and whether it may have effects other than
+// writing its slots. Items with a body return nothing for themselves.
+fn effects(code: &Code) -> (Vec<Slot>, Vec<Slot>, bool) {
+    let unary = |source: Slot, destination: Slot| (vec![source], vec![destination], false);
+    let binary = |left: Slot, right: Slot, destination: Slot| (vec![left, right], vec![destination], false);
+    match code {
+        Code::ConstI32(c) => (vec![], vec![c.slot()], false),
+        Code::ConstI64(c) => (vec![], vec![c.slot()], false),
+        Code::ConstF32(c) => (vec![], vec![c.slot()], false),
+        Code::ConstF64(c) => (vec![], vec![c.slot()], false),
+        Code::ConstOne(c) => (vec![], vec![c.destination()], false),
+        Code::ConstZero(c) => (vec![], vec![c.destination()], false),
+        #[cfg(feature = "bit_ops")]
+        Code::CountLeadingZeros(c) => unary(c.source(), c.destination()),
+        #[cfg(feature = "bit_ops")]
+        Code::CountTrailingZeros(c) => unary(c.source(), c.destination()),
+        #[cfg(feature = "bit_ops")]
+        Code::PopulationCount(c) => unary(c.source(), c.destination()),
+        #[cfg(feature = "bit_ops")]
+        Code::PowerOfTwo(c) => unary(c.exponent(), c.destination()),
+        #[cfg(feature = "bit_ops")]
+        Code::IntegerLog2(c) => unary(c.source(), c.destination()),
+        #[cfg(feature = "bit_ops")]
+        Code::And(c) => binary(c.left(), c.right(), c.destination()),
+        #[cfg(feature = "bit_ops")]
+        Code::Or(c) => binary(c.left(), c.right(), c.destination()),
+        #[cfg(feature = "bit_ops")]
+        Code::Xor(c) => binary(c.left(), c.right(), c.destination()),
+        #[cfg(feature = "bit_ops")]
+        Code::ShiftLeft(c) => binary(c.source(), c.bits(), c.destination()),
+        #[cfg(feature = "bit_ops")]
+        Code::ShiftRight(c) => binary(c.source(), c.bits(), c.destination()),
+        #[cfg(feature = "bit_ops")]
+        Code::RotateLeft(c) => binary(c.source(), c.bits(), c.destination()),
+        #[cfg(feature = "bit_ops")]
+        Code::RotateRight(c) => binary(c.source(), c.bits(), c.destination()),
+        Code::Add(c) => binary(c.left(), c.right(), c.destination()),
+        Code::Subtract(c) => binary(c.left(), c.right(), c.destination()),
+        Code::Multiply(c) => binary(c.left(), c.right(), c.destination()),
+        Code::Divide(c) => binary(c.dividend(), c.divisor(), c.destination()),
+        Code::Remainder(c) => binary(c.dividend(), c.divisor(), c.destination()),
+        Code::Power(c) => binary(c.base(), c.exponent(), c.destination()),
+        #[cfg(feature = "float_ops")]
+        Code::AbsoluteValue(c) => unary(c.source(), c.destination()),
+        #[cfg(feature = "float_ops")]
+        Code::Negate(c) => unary(c.source(), c.destination()),
+        #[cfg(feature = "float_ops")]
+        Code::SquareRoot(c) => unary(c.source(), c.destination()),
+        #[cfg(feature = "float_ops")]
+        Code::Ceiling(c) => unary(c.source(), c.destination()),
+        #[cfg(feature = "float_ops")]
+        Code::Floor(c) => unary(c.source(), c.destination()),
+        #[cfg(feature = "float_ops")]
+        Code::Nearest(c) => unary(c.source(), c.destination()),
+        #[cfg(feature = "float_ops")]
+        Code::Min(c) => binary(c.left(), c.right(), c.destination()),
+        #[cfg(feature = "float_ops")]
+        Code::Max(c) => binary(c.left(), c.right(), c.destination()),
+        #[cfg(feature = "float_ops")]
+        Code::CopySign(c) => binary(c.left(), c.right(), c.destination()),
+        Code::IsEqualZero(c) => unary(c.source(), c.destination()),
+        Code::AreEqual(c) => binary(c.left(), c.right(), c.destination()),
+        Code::AreNotEqual(c) => binary(c.left(), c.right(), c.destination()),
+        Code::IsLessThan(c) => binary(c.left(), c.right(), c.destination()),
+        Code::IsGreaterThan(c) => binary(c.left(), c.right(), c.destination()),
+        Code::IsLessThanOrEqual(c) => binary(c.left(), c.right(), c.destination()),
+        Code::IsGreaterThanOrEqual(c) => binary(c.left(), c.right(), c.destination()),
+        Code::FillSlots(c) => (vec![], slot_range(c.start(), c.count()), false),
+        Code::SumSlots(c) => (slot_range(c.start(), c.count()), vec![c.destination()], false),
+        Code::CopySlots(c) => (
+            slot_range(c.source_start(), c.count()),
+            slot_range(c.destination_start(), c.count()),
+            false,
+        ),
+        Code::CopySlot(c) => unary(c.source(), c.destination()),
+        Code::Call(c) => (c.params().to_vec(), c.results().to_vec(), true),
+        Code::Return(_)
+        | Code::Break(_)
+        | Code::BreakIf(_)
+        | Code::If(_)
+        | Code::IfElse(_)
+        | Code::DoUntil(_)
+        | Code::DoWhile(_)
+        | Code::DoFor(_)
+        | Code::Provenance(_) => (vec![], vec![], true),
+    }
+}
+
+// The `count` slots beginning with `start`, stopping at the last slot there can be
+fn slot_range(start: Slot, count: u8) -> Vec<Slot> {
+    (start as usize..(start as usize + count as usize).min(Slot::MAX as usize + 1))
+        .map(|slot| slot as Slot)
+        .collect()
+}
+
+// Rebuilds the code without the items numbered in `remove`. The first item is numbered `first`.
+fn remove_items(code: &[Code], first: usize, remove: &BTreeSet<usize>) -> Vec<Code> {
+    let mut kept = vec![];
+    let mut index = first;
+    for c in code.iter() {
+        let c_index = index;
+        index += c.points();
+        if remove.contains(&c_index) {
+            continue;
+        }
+        let body = c_index + 1;
+        let c = match c {
+            Code::If(c) => If::new(c.if_not_zero(), remove_items(c.do_this(), body, remove)),
+            Code::IfElse(c) => {
+                let else_first = body + c.do_this().iter().map(|c| c.points()).sum::<usize>();
+                IfElse::new(
+                    c.if_not_zero(),
+                    remove_items(c.do_this(), body, remove),
+                    remove_items(c.else_do_this(), else_first, remove),
+                )
+            }
+            Code::DoUntil(c) => DoUntil::new(c.until_not_zero(), remove_items(c.do_this(), body, remove)),
+            Code::DoWhile(c) => DoWhile::new(c.while_not_zero(), remove_items(c.do_this(), body, remove)),
+            Code::DoFor(c) => DoFor::new(c.times(), remove_items(c.do_this(), body, remove)),
+            Code::Provenance(c) => Provenance::new(c.tag(), remove_items(c.do_this(), body, remove)),
+            c => c.clone(),
+        };
+        kept.push(c);
+    }
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn a_write_read_by_the_next_pass_of_a_loop_is_live() {
+        // Slot 3 is only read at the top of the loop, by the pass after the one that writes it
+        let code = vec![DoFor::new(
+            3,
+            vec![Add::new(2, 3, 2), ConstOne::new(3), ConstZero::new(4)],
+        )];
+        let liveness = SlotLiveness::analyze(&code, &[2]);
+        assert_eq!(vec![2, 3], liveness.read_before_written);
+        assert_eq!(vec![3], liveness.dead_writes);
+    }
+
+    #[test]
+    fn writes_before_a_return_or_break_are_read_where_they_jump() {
+        let code = vec![
+            DoUntil::new(0, vec![ConstOne::new(3), BreakIf::new(1), ConstOne::new(2)]),
+            CopySlot::new(3, 2),
+            ConstOne::new(4),
+            Return::new(),
+        ];
+        let liveness = SlotLiveness::analyze(&code, &[2]);
+
+        // Slot 3 is read after the loop whether it ends at the BreakIf or at its condition. The write to slot 2 in the
+        // loop is always replaced by the copy, and slot 4 is never read before the Return.
+        assert_eq!(vec![0, 1], liveness.read_before_written);
+        assert_eq!(vec![3, 5], liveness.dead_writes);
+        assert_eq!(Some(4), liveness.highest_slot());
+    }
+
+    #[test]
+    fn a_while_loop_may_never_run() {
+        let code = vec![ConstOne::new(2), DoWhile::new(0, vec![ConstZero::new(2)])];
+        assert_eq!(0, SlotLiveness::analyze(&code, &[2]).dead_writes.len());
+        let code = vec![ConstOne::new(2), DoUntil::new(0, vec![ConstZero::new(2)])];
+        assert_eq!(vec![0], SlotLiveness::analyze(&code, &[2]).dead_writes);
+    }
+
+    #[test]
+    fn calls_are_never_dead() {
+        let code = vec![Call::new(0, vec![0], vec![3])];
+        assert_eq!(code, remove_dead_writes(&code, &[2]));
+    }
+}
@@ -0,0 +1,219 @@
+use crate::{RunResult, WasmgpError, World};
+use anyhow::Result;
+use rusqlite::{params, Connection};
+use std::path::Path;
+
+// The version of the schema, kept in the `user_version` of the database. Columns are only ever added in a new version.
+const SCHEMA_VERSION: i64 = 1;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS individuals (
+    generation INTEGER NOT NULL,
+    island INTEGER NOT NULL,
+    individual_id INTEGER NOT NULL,
+    fitness_rank INTEGER NOT NULL,
+    score INTEGER NOT NULL,
+    points INTEGER NOT NULL,
+    skipped INTEGER NOT NULL,
+    code TEXT NOT NULL,
+    PRIMARY KEY (generation, island, individual_id)
+);
+CREATE TABLE IF NOT EXISTS lineage (
+    individual_id INTEGER NOT NULL,
+    parent_id INTEGER NOT NULL,
+    PRIMARY KEY (individual_id, parent_id)
+);
+CREATE TABLE IF NOT EXISTS generation_stats (
+    generation INTEGER NOT NULL,
+    island INTEGER NOT NULL,
+    immigrants INTEGER NOT NULL,
+    emigrants INTEGER NOT NULL,
+    difficulty_level INTEGER NOT NULL,
+    unique_structures INTEGER NOT NULL,
+    mean_edit_distance REAL NOT NULL,
+    opcode_entropy REAL NOT NULL,
+    best_score INTEGER NOT NULL,
+    mean_score REAL NOT NULL,
+    fitness_variance REAL NOT NULL,
+    failed_children INTEGER NOT NULL,
+    PRIMARY KEY (generation, island)
+);
+";
+
+/// Saves the individuals, scores, lineage and stats of every generation to a SQLite file, so that a run can be
+/// analyzed with SQL once it is over. Call `record_generation` after each generation, such as from the function passed
+/// to `World::run_generations_while`. Requires the `sqlite` feature.
+///
+/// The file has three tables, and its schema only changes by adding columns:
+/// - `individuals` has one row for each individual of each generation, with its `fitness_rank` (zero for the most fit),
+///   its `score` from `IslandCallbacks::score_individual`, its `points`, whether it was `skipped`, and its `code` as
+///   printed by `Individual::get_code_string`. Only the lead of a team is saved.
+/// - `lineage` has one row for each parent of each individual, from `Individual::get_parents`
+/// - `generation_stats` has the `GenerationStats` of each island for each generation, except for the cross evaluation
+///   and the last child error
+///
+/// Scores are stored as signed integers, so a score above `i64::MAX` is saved as `i64::MAX`.
+/// ```no_run
+/// use wasmgp::*;
+///
+/// let mut world = World::<(), EmptyRunResult>::new(WorldConfiguration::default()).unwrap();
+/// // ... create the islands ...
+/// let mut archive = SqliteArchive::open("run.sqlite").unwrap();
+/// world
+///     .run_generations_while(|world| {
+///         archive.record_generation(world).unwrap();
+///         world.generations_complete() < 100
+///     })
+///     .unwrap();
+/// ```
+pub struct SqliteArchive {
+    connection: Connection,
+}
+
+impl SqliteArchive {
+    /// Opens the SQLite file, creating it and its tables if they do not exist. Returns an error if the file was written
+    /// with a newer schema.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<SqliteArchive> {
+        SqliteArchive::with_connection(Connection::open(path)?)
+    }
+
+    /// Creates an archive that is only kept in memory, such as for tests
+    pub fn open_in_memory() -> Result<SqliteArchive> {
+        SqliteArchive::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(connection: Connection) -> Result<SqliteArchive> {
+        let version: i64 = connection.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version > SCHEMA_VERSION {
+            return Err(WasmgpError::InvalidConfiguration(format!(
+                "the SQLite archive has schema version {}, but only version {} is supported",
+                version, SCHEMA_VERSION
+            ))
+            .into());
+        }
+        connection.execute_batch(SCHEMA)?;
+        connection.execute_batch(&format!("PRAGMA user_version = {}", SCHEMA_VERSION))?;
+        Ok(SqliteArchive { connection })
+    }
+
+    /// Borrows the connection to the database, so that it can be queried while the run is going
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    /// Saves the current generation of every island, which should have been run and sorted. A generation that is
+    /// recorded again replaces the rows saved for it before.
+    pub fn record_generation<T, R: RunResult>(&mut self, world: &World<T, R>) -> Result<()> {
+        let generation = world.generations_complete() as i64;
+        let transaction = self.connection.transaction()?;
+        {
+            let mut insert_individual = transaction.prepare_cached(
+                "INSERT OR REPLACE INTO individuals
+                    (generation, island, individual_id, fitness_rank, score, points, skipped, code)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?;
+            let mut insert_parent = transaction
+                .prepare_cached("INSERT OR IGNORE INTO lineage (individual_id, parent_id) VALUES (?1, ?2)")?;
+            let mut insert_stats = transaction.prepare_cached(
+                "INSERT OR REPLACE INTO generation_stats
+                    (generation, island, immigrants, emigrants, difficulty_level, unique_structures, mean_edit_distance,
+                    opcode_entropy, best_score, mean_score, fitness_variance, failed_children)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            )?;
+
+            for id in 0..world.get_number_of_islands() {
+                let island = world.get_island(id).unwrap();
+                for index in 0..island.len() {
+                    let individual = island.get_one_individual(index).unwrap();
+                    let points: usize = individual.get_code().iter().map(|code| code.points()).sum();
+                    insert_individual.execute(params![
+                        generation,
+                        id as i64,
+                        individual.get_id() as i64,
+                        (island.len() - 1 - index) as i64,
+                        to_sql_integer(island.score_for_individual(index).unwrap()),
+                        points as i64,
+                        individual.was_skipped(),
+                        individual.get_code_string(),
+                    ])?;
+                    for parent in individual.get_parents() {
+                        insert_parent.execute(params![individual.get_id() as i64, *parent as i64])?;
+                    }
+                }
+
+                let stats = island.generation_stats();
+                insert_stats.execute(params![
+                    generation,
+                    id as i64,
+                    stats.immigrants as i64,
+                    stats.emigrants as i64,
+                    stats.difficulty_level as i64,
+                    stats.unique_structures as i64,
+                    stats.mean_edit_distance,
+                    stats.opcode_entropy,
+                    to_sql_integer(stats.best_score),
+                    stats.mean_score,
+                    stats.fitness_variance,
+                    stats.failed_children as i64,
+                ])?;
+            }
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+}
+
+fn to_sql_integer(value: u64) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
+
+#[cfg(all(test, not(feature = "async")))]
+mod tests {
+    use crate::*;
+
+    #[derive(Clone)]
+    struct ScoreById;
+    impl IslandCallbacks<(), EmptyRunResult> for ScoreById {
+        fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+            Box::new(Clone::clone(self))
+        }
+        fn run_individual(&mut self, _individual: &mut Individual<(), EmptyRunResult>) {}
+        fn score_individual(&self, individual: &Individual<(), EmptyRunResult>) -> u64 {
+            individual.get_id()
+        }
+    }
+
+    #[test]
+    fn every_generation_is_recorded_with_its_lineage() {
+        let config = WorldConfiguration {
+            individuals_per_island: 4,
+            individual_max_points: 10,
+            elite_individuals_per_generation: 0,
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+        world.create_island(Box::new(ScoreById));
+        let mut archive = SqliteArchive::open_in_memory().unwrap();
+        world
+            .run_generations_while(|world| {
+                archive.record_generation(world).unwrap();
+                world.generations_complete() < 2
+            })
+            .unwrap();
+
+        let count = |sql: &str| -> i64 { archive.connection().query_row(sql, [], |row| row.get(0)).unwrap() };
+        assert_eq!(8, count("SELECT COUNT(*) FROM individuals"));
+        assert_eq!(2, count("SELECT COUNT(*) FROM generation_stats"));
+        assert_eq!(
+            3,
+            count("SELECT individual_id FROM individuals WHERE generation = 1 AND fitness_rank = 0")
+        );
+
+        // Every individual of the second generation was bred from the first
+        assert_eq!(0, count("SELECT COUNT(*) FROM lineage WHERE individual_id < 4"));
+        assert_eq!(
+            4,
+            count("SELECT COUNT(DISTINCT individual_id) FROM lineage WHERE parent_id < 4 AND individual_id >= 4")
+        );
+    }
+}
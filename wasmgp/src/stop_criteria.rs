@@ -0,0 +1,169 @@
+use crate::{IslandId, RunResult, World};
+
+/// Decides when a run is over. Pass `keep_running` the World after each generation, such as from the function given
+/// to `World::run_generations_while`:
+/// ```
+/// use wasmgp::*;
+///
+/// #[derive(Clone)]
+/// struct DoNothing;
+/// impl IslandCallbacks<(), EmptyRunResult> for DoNothing {
+///     fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+///         Box::new(Clone::clone(self))
+///     }
+///     fn run_individual(&mut self, _individual: &mut Individual<(), EmptyRunResult>) {}
+/// }
+///
+/// let config = WorldConfiguration {
+///     individuals_per_island: 5,
+///     ..WorldConfiguration::default()
+/// };
+/// let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+/// world.create_island(Box::new(DoNothing));
+///
+/// // Every score is zero, so the run ends once the score has stood still for three generations
+/// let mut stop = StopCriteria::new(100);
+/// stop.set_max_stagnant_generations(3);
+/// # #[cfg(not(feature = "async"))]
+/// # {
+/// world.run_generations_while(|world| stop.keep_running(world)).unwrap();
+/// assert_eq!(Some(StopReason::Stagnation), stop.reason());
+/// assert_eq!(4, world.generations_complete());
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StopCriteria {
+    max_generations: usize,
+    target_score: Option<u64>,
+    max_stagnant_generations: Option<usize>,
+
+    // The best score seen on any island so far, and the number of generations since it last went up
+    best_score: Option<u64>,
+    stagnant_generations: usize,
+    reason: Option<StopReason>,
+}
+
+/// Why `StopCriteria::keep_running` ended a run
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StopReason {
+    /// The World completed the maximum number of generations
+    MaxGenerations,
+
+    /// The most fit individual of the island reached the target score
+    TargetScore(IslandId),
+
+    /// No island beat the best score for the maximum number of stagnant generations
+    Stagnation,
+}
+
+impl StopCriteria {
+    /// Creates criteria that stop the run once the World has completed `max_generations`
+    pub fn new(max_generations: usize) -> StopCriteria {
+        StopCriteria {
+            max_generations,
+            target_score: None,
+            max_stagnant_generations: None,
+            best_score: None,
+            stagnant_generations: 0,
+            reason: None,
+        }
+    }
+
+    /// Also stops the run once the most fit individual of any island has a score (from
+    /// `IslandCallbacks::score_individual`) of at least `score`
+    pub fn set_target_score(&mut self, score: u64) {
+        self.target_score = Some(score);
+    }
+
+    /// Also stops the run once `generations` have passed without any island beating the best score so far
+    pub fn set_max_stagnant_generations(&mut self, generations: usize) {
+        self.max_stagnant_generations = Some(generations);
+    }
+
+    /// Returns false once the run should stop, and remembers why. Call it exactly once after each generation.
+    pub fn keep_running<T: Default, R: RunResult>(&mut self, world: &World<T, R>) -> bool {
+        let mut best_this_generation = None;
+        for id in 0..world.get_number_of_islands() {
+            let island = world.get_island(id).unwrap();
+            if island.most_fit_individual().is_none() {
+                continue;
+            }
+            let score = island.generation_stats().best_score;
+            if matches!(self.target_score, Some(target) if score >= target) {
+                self.reason = Some(StopReason::TargetScore(id));
+                return false;
+            }
+            best_this_generation = best_this_generation.max(Some(score));
+        }
+
+        if best_this_generation > self.best_score {
+            self.best_score = best_this_generation;
+            self.stagnant_generations = 0;
+        } else {
+            self.stagnant_generations += 1;
+        }
+        if matches!(self.max_stagnant_generations, Some(max) if self.stagnant_generations >= max) {
+            self.reason = Some(StopReason::Stagnation);
+            return false;
+        }
+
+        if world.generations_complete() >= self.max_generations {
+            self.reason = Some(StopReason::MaxGenerations);
+            return false;
+        }
+        true
+    }
+
+    /// Returns why the run was stopped, or None if `keep_running` has not stopped it
+    pub fn reason(&self) -> Option<StopReason> {
+        self.reason
+    }
+}
+
+#[cfg(all(test, not(feature = "async")))]
+mod tests {
+    use crate::*;
+
+    #[derive(Clone)]
+    struct ScoreTheLength;
+
+    impl IslandCallbacks<(), EmptyRunResult> for ScoreTheLength {
+        fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+            Box::new(Clone::clone(self))
+        }
+
+        fn run_individual(&mut self, _individual: &mut Individual<(), EmptyRunResult>) {}
+
+        fn score_individual(&self, individual: &Individual<(), EmptyRunResult>) -> u64 {
+            individual.get_code().len() as u64
+        }
+    }
+
+    fn world() -> World<(), EmptyRunResult> {
+        let config = WorldConfiguration {
+            individuals_per_island: 10,
+            individual_max_points: 10,
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::new(config).unwrap();
+        world.create_island(Box::new(ScoreTheLength));
+        world
+    }
+
+    #[test]
+    fn stops_at_the_target_score_or_the_generation_limit() {
+        let mut world = world();
+        let mut stop = StopCriteria::new(50);
+        stop.set_target_score(1);
+        world.run_generations_while(|world| stop.keep_running(world)).unwrap();
+        assert_eq!(Some(StopReason::TargetScore(0)), stop.reason());
+        assert!(world.get_island(0).unwrap().generation_stats().best_score >= 1);
+
+        let mut world = self::world();
+        let mut stop = StopCriteria::new(3);
+        stop.set_target_score(1000);
+        world.run_generations_while(|world| stop.keep_running(world)).unwrap();
+        assert_eq!(Some(StopReason::MaxGenerations), stop.reason());
+        assert_eq!(3, world.generations_complete());
+    }
+}
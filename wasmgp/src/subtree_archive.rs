@@ -0,0 +1,126 @@
+use crate::Code;
+use rand::Rng;
+
+/// A library of pieces of code taken from the most fit individuals, which archive mutations insert into children in
+/// place of purely random code. Programs for structured problems are often built from a few useful pieces, such as a
+/// loop that scans a range of slots, and reusing the pieces that the elites already found spreads them faster than
+/// waiting for mutation to discover them again.
+///
+/// Every piece of code with at least `min_points` points, at any depth, is a subtree. The newest subtrees are kept, up
+/// to the `capacity` of the archive, and a subtree that is seen again counts as new.
+/// ```
+/// use wasmgp::*;
+///
+/// let mut archive = SubtreeArchive::new(2, 2);
+/// archive.add_subtrees(&[
+///     ConstOne::new(0),
+///     DoFor::new(3, vec![If::new(1, vec![Add::new(0, 1, 2)])]),
+/// ]);
+///
+/// // The If and the DoFor that holds it are big enough, but the Add and the ConstOne are not
+/// assert_eq!(
+///     vec![
+///         DoFor::new(3, vec![If::new(1, vec![Add::new(0, 1, 2)])]),
+///         If::new(1, vec![Add::new(0, 1, 2)]),
+///     ],
+///     archive.subtrees()
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SubtreeArchive {
+    capacity: usize,
+    min_points: usize,
+
+    // Oldest first
+    subtrees: Vec<Code>,
+}
+
+impl SubtreeArchive {
+    /// Creates an empty archive that holds up to `capacity` subtrees of at least `min_points` points each
+    pub fn new(capacity: usize, min_points: usize) -> SubtreeArchive {
+        SubtreeArchive {
+            capacity,
+            min_points,
+            subtrees: vec![],
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn min_points(&self) -> usize {
+        self.min_points
+    }
+
+    /// Borrows the subtrees, oldest first
+    pub fn subtrees(&self) -> &[Code] {
+        &self.subtrees[..]
+    }
+
+    // An archive with the same capacity and minimum points that holds the subtrees, oldest first, such as the ones
+    // saved in a checkpoint
+    pub(crate) fn with_subtrees(&self, subtrees: Vec<Code>) -> SubtreeArchive {
+        SubtreeArchive {
+            capacity: self.capacity,
+            min_points: self.min_points,
+            subtrees,
+        }
+    }
+
+    /// Adds every subtree of the code that has at least `min_points` points. The oldest subtrees are removed once the
+    /// archive is over its capacity.
+    pub fn add_subtrees(&mut self, code: &[Code]) {
+        let min_points = self.min_points;
+        let mut found = vec![];
+        for c in code.iter() {
+            c.walk(&mut |subtree| {
+                if subtree.points() >= min_points {
+                    found.push(subtree.clone());
+                }
+            });
+        }
+        for subtree in found {
+            self.subtrees.retain(|existing| *existing != subtree);
+            self.subtrees.push(subtree);
+        }
+        let excess = self.subtrees.len().saturating_sub(self.capacity);
+        self.subtrees.drain(..excess);
+    }
+
+    /// Picks one of the subtrees with no more than `max_points` points, or returns None if there are none
+    pub fn pick<Rnd: Rng>(&self, rng: &mut Rnd, max_points: usize) -> Option<Code> {
+        let fits: Vec<&Code> = self.subtrees.iter().filter(|c| c.points() <= max_points).collect();
+        if fits.is_empty() {
+            None
+        } else {
+            Some(fits[rng.gen_range(0..fits.len())].clone())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn the_newest_subtrees_are_kept() {
+        let mut archive = SubtreeArchive::new(2, 1);
+        archive.add_subtrees(&[ConstOne::new(0), ConstOne::new(1)]);
+        archive.add_subtrees(&[ConstOne::new(0), ConstOne::new(2)]);
+        assert_eq!(&[ConstOne::new(0), ConstOne::new(2)], archive.subtrees());
+    }
+
+    #[test]
+    fn only_subtrees_that_fit_are_picked() {
+        let mut archive = SubtreeArchive::new(10, 1);
+        archive.add_subtrees(&[DoFor::new(2, vec![ConstOne::new(0), ConstOne::new(1)])]);
+        let mut rng = SmallRng::seed_from_u64(3);
+        for _ in 0..10 {
+            assert!(archive.pick(&mut rng, 2).unwrap().points() <= 2);
+        }
+        assert_eq!(None, SubtreeArchive::new(10, 1).pick(&mut rng, 5));
+    }
+}
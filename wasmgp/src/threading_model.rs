@@ -1,14 +1,20 @@
+/// How `World::run_one_generation_threaded` and `World::run_generations_while_threaded` spread the evaluation of
+/// individuals over threads. The threads share the World's wasmtime Engine and the compiled modules, and every
+/// evaluation makes its own Store. `World::run_one_generation` always evaluates on the calling thread, so it does not
+/// need `T` and `R` to be `Send`. Only used without the `async` feature, which evaluates individuals concurrently with
+/// `max_in_flight` instead.
 #[derive(Clone, Debug, PartialEq)]
 pub enum ThreadingModel {
     /// Do not use multi-threading when executing the world
     None,
-    
-    // TODO: The threading models below require significant planning and work to safely mutate different parts of a
-    // world at the same time.
 
-    // Each Island will execute in its own thread. The parameter is the total number of islands to execute at once
-    // PerIsland(usize),
+    /// Each Island runs its generation on a worker thread. The parameter is the number of worker threads, which take
+    /// the islands one at a time until every island has run. The island's own callbacks are used, so nothing about
+    /// them changes, but different islands are run at the same time.
+    PerIsland(usize),
 
-    // Each Individual will execute in its own thread. The parameter is the total number of individuals to run at once.
-    // PerIndividual(usize),
+    /// The individuals of each island are evaluated on worker threads, one island after another. The parameter is the
+    /// number of worker threads. Each thread evaluates with its own clone of the island's callbacks, made after
+    /// `pre_generation_run`, in the same way as `max_in_flight` does under the `async` feature.
+    PerIndividual(usize),
 }
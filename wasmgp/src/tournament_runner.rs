@@ -0,0 +1,335 @@
+#[cfg(not(feature = "async"))]
+use crate::{Individual, WasmgpError};
+use crate::{IslandCallbacks, RunResult, World, WorldConfiguration};
+use anyhow::Result;
+
+/// Compares several configurations of the same experiment. Each entrant is a named WorldConfiguration. The runner
+/// builds a World for each one, gives it its imports and islands with the `setup` function, and runs `trials`
+/// independent forks of it with `World::run_forks`. The champion of each trial is the most fit individual of any of
+/// its islands on the shared `holdout` callbacks, scored by its mean over `holdout_runs` runs of
+/// `World::evaluate_on_holdout`.
+///
+/// Each pair of entrants is then compared with a Mann-Whitney U test on the scores of their champions. The winner is
+/// the entrant that scores higher than every other entrant, with a p-value below the `significance`. When no entrant
+/// does, there is no winner, and more trials may be needed.
+/// ```
+/// use wasmgp::*;
+///
+/// #[derive(Clone)]
+/// struct DoNothing;
+/// impl IslandCallbacks<(), EmptyRunResult> for DoNothing {
+///     fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+///         Box::new(Clone::clone(self))
+///     }
+///     fn run_individual(&mut self, _individual: &mut Individual<(), EmptyRunResult>) {}
+/// }
+///
+/// let small = WorldConfiguration {
+///     individuals_per_island: 5,
+///     ..WorldConfiguration::default()
+/// };
+/// let mutation_only = WorldConfiguration {
+///     crossover_rate: 0,
+///     ..small.clone()
+/// };
+///
+/// // Each World gets one island, and the champions are scored by the same callbacks
+/// let setup = |world: &mut World<(), EmptyRunResult>| -> anyhow::Result<()> {
+///     world.create_island(Box::new(DoNothing));
+///     Ok(())
+/// };
+/// let mut runner = TournamentRunner::new(3, 2, setup, Box::new(DoNothing));
+/// runner.add_entrant("crossover", small);
+/// runner.add_entrant("mutation only", mutation_only);
+/// let report = runner.run(|world| world.generations_complete() < 2).unwrap();
+///
+/// // Every program scores zero, so neither configuration wins
+/// assert_eq!(3, report.entrants[0].champion_scores.len());
+/// assert_eq!(None, report.winner);
+/// ```
+pub struct TournamentRunner<T, R: RunResult> {
+    entrants: Vec<(String, WorldConfiguration)>,
+    trials: usize,
+    holdout_runs: usize,
+    significance: f64,
+    setup: Box<dyn Fn(&mut World<T, R>) -> Result<()>>,
+    holdout: Box<dyn IslandCallbacks<T, R>>,
+}
+
+/// The outcome of `TournamentRunner::run`
+#[derive(Clone, Debug, PartialEq)]
+pub struct TournamentReport {
+    /// The entrants, in the order they were added
+    pub entrants: Vec<EntrantResult>,
+
+    /// A comparison of every pair of entrants, with the `first` entrant before the `second`
+    pub comparisons: Vec<EntrantComparison>,
+
+    /// The index of the entrant that beat every other entrant, if any
+    pub winner: Option<usize>,
+}
+
+/// The champions of one entrant of a tournament
+#[derive(Clone, Debug, PartialEq)]
+pub struct EntrantResult {
+    pub name: String,
+
+    /// The mean holdout score of the champion of each trial, in the order of the trials
+    pub champion_scores: Vec<f64>,
+}
+
+/// A Mann-Whitney U test of the champion scores of two entrants
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EntrantComparison {
+    pub first: usize,
+    pub second: usize,
+
+    /// The number of pairs of trials in which the champion of the first entrant scored higher than that of the second,
+    /// with ties counted as half
+    pub u: f64,
+
+    /// The two-sided p-value of the test, from the normal approximation with a correction for ties
+    pub p_value: f64,
+}
+
+impl<T, R: RunResult> TournamentRunner<T, R> {
+    /// Creates a runner with no entrants. `setup` is called on the new World of each entrant to add its imports and
+    /// islands, and the `holdout` callbacks score the champions. The significance starts at 0.05.
+    pub fn new<S>(
+        trials: usize,
+        holdout_runs: usize,
+        setup: S,
+        holdout: Box<dyn IslandCallbacks<T, R>>,
+    ) -> TournamentRunner<T, R>
+    where
+        S: Fn(&mut World<T, R>) -> Result<()> + 'static,
+    {
+        TournamentRunner {
+            entrants: vec![],
+            trials,
+            holdout_runs,
+            significance: 0.05,
+            setup: Box::new(setup),
+            holdout,
+        }
+    }
+
+    /// Adds a configuration to the tournament under the name
+    pub fn add_entrant<S: Into<String>>(&mut self, name: S, config: WorldConfiguration) {
+        self.entrants.push((name.into(), config));
+    }
+
+    /// Sets the p-value that the winner must beat every other entrant by
+    pub fn set_significance(&mut self, significance: f64) {
+        self.significance = significance;
+    }
+}
+
+#[cfg(not(feature = "async"))]
+impl<T: Default + Send + 'static, R: RunResult + Send> TournamentRunner<T, R> {
+    /// Runs the trials of every entrant with `run_generations_while` and the same `while_fn`, one entrant at a time
+    /// with its trials on threads of their own, and compares the entrants. Fails if there are fewer than two entrants
+    /// or trials, or if any trial fails.
+    pub fn run<While>(&mut self, while_fn: While) -> Result<TournamentReport>
+    where
+        While: Fn(&mut World<T, R>) -> bool + Sync,
+    {
+        if self.entrants.len() < 2 || self.trials < 2 {
+            return Err(WasmgpError::InvalidConfiguration(
+                "a tournament needs at least two entrants and two trials".into(),
+            )
+            .into());
+        }
+
+        let mut entrants = vec![];
+        for (name, config) in self.entrants.iter() {
+            let mut world = World::new(config.clone())?;
+            (self.setup)(&mut world)?;
+            let mut trials = world.fork(self.trials, true);
+            for result in World::run_forks(&mut trials, &while_fn) {
+                result?;
+            }
+            entrants.push(EntrantResult {
+                name: name.clone(),
+                champion_scores: trials.iter().map(|trial| self.champion_score(trial)).collect(),
+            });
+        }
+
+        let mut comparisons = vec![];
+        for first in 0..entrants.len() {
+            for second in first + 1..entrants.len() {
+                let (u, p_value) = mann_whitney(&entrants[first].champion_scores, &entrants[second].champion_scores);
+                comparisons.push(EntrantComparison {
+                    first,
+                    second,
+                    u,
+                    p_value,
+                });
+            }
+        }
+        let winner = choose_winner(&entrants, &comparisons, self.significance);
+        Ok(TournamentReport {
+            entrants,
+            comparisons,
+            winner,
+        })
+    }
+
+    // The best mean holdout score of the most fit individuals of the islands
+    fn champion_score(&self, world: &World<T, R>) -> f64 {
+        let champions: Vec<Individual<T, R>> = (0..world.get_number_of_islands())
+            .filter_map(|id| world.get_island(id).and_then(|island| island.most_fit_individual()))
+            .cloned()
+            .collect();
+        world
+            .evaluate_on_holdout(&champions, self.holdout.clone(), self.holdout_runs)
+            .iter()
+            .map(|result| result.mean_score())
+            .fold(0.0, f64::max)
+    }
+}
+
+impl std::fmt::Display for TournamentReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for entrant in self.entrants.iter() {
+            let scores = &entrant.champion_scores;
+            let mean = scores.iter().sum::<f64>() / scores.len().max(1) as f64;
+            writeln!(
+                f,
+                "{}: mean champion score {:.3} over {} trials",
+                entrant.name,
+                mean,
+                scores.len()
+            )?;
+        }
+        for comparison in self.comparisons.iter() {
+            writeln!(
+                f,
+                "{} vs {}: U = {}, p = {:.4}",
+                self.entrants[comparison.first].name,
+                self.entrants[comparison.second].name,
+                comparison.u,
+                comparison.p_value
+            )?;
+        }
+        match self.winner {
+            Some(winner) => writeln!(f, "Winner: {}", self.entrants[winner].name),
+            None => writeln!(f, "Winner: none"),
+        }
+    }
+}
+
+// The entrant whose champions score higher than those of every other entrant with a p-value below the significance
+#[cfg(not(feature = "async"))]
+fn choose_winner(entrants: &[EntrantResult], comparisons: &[EntrantComparison], significance: f64) -> Option<usize> {
+    (0..entrants.len()).find(|candidate| {
+        comparisons
+            .iter()
+            .filter(|comparison| comparison.first == *candidate || comparison.second == *candidate)
+            .all(|comparison| {
+                let pairs = (entrants[comparison.first].champion_scores.len()
+                    * entrants[comparison.second].champion_scores.len()) as f64;
+                let first_is_higher = comparison.u > pairs / 2.0;
+                let candidate_is_higher = first_is_higher == (comparison.first == *candidate);
+                candidate_is_higher && comparison.p_value < significance
+            })
+    })
+}
+
+// Returns the U statistic of the first sample and the two-sided p-value of the Mann-Whitney U test
+#[cfg(not(feature = "async"))]
+fn mann_whitney(first: &[f64], second: &[f64]) -> (f64, f64) {
+    let (n1, n2) = (first.len() as f64, second.len() as f64);
+    if first.is_empty() || second.is_empty() {
+        return (0.0, 1.0);
+    }
+
+    // Rank both samples together, giving tied values the mean of their ranks
+    let mut values: Vec<(f64, bool)> = first
+        .iter()
+        .map(|v| (*v, true))
+        .chain(second.iter().map(|v| (*v, false)))
+        .collect();
+    values.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut first_rank_sum = 0.0;
+    let mut tie_term = 0.0;
+    let mut start = 0;
+    while start < values.len() {
+        let mut end = start + 1;
+        while end < values.len() && values[end].0 == values[start].0 {
+            end += 1;
+        }
+        let rank = (start + end + 1) as f64 / 2.0;
+        first_rank_sum += rank * values[start..end].iter().filter(|(_, is_first)| *is_first).count() as f64;
+        let tied = (end - start) as f64;
+        tie_term += tied * tied * tied - tied;
+        start = end;
+    }
+
+    let u = first_rank_sum - n1 * (n1 + 1.0) / 2.0;
+    let n = n1 + n2;
+    let variance = n1 * n2 / 12.0 * ((n + 1.0) - tie_term / (n * (n - 1.0)));
+    if variance <= 0.0 {
+        return (u, 1.0);
+    }
+    let z = ((u - n1 * n2 / 2.0).abs() - 0.5).max(0.0) / variance.sqrt();
+    (u, erfc(z / std::f64::consts::SQRT_2).min(1.0))
+}
+
+// The complementary error function, with a fractional error below 1.2e-7 (Numerical Recipes' erfcc)
+#[cfg(not(feature = "async"))]
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let polynomial = -z * z - 1.26551223
+        + t * (1.00002368
+            + t * (0.37409196
+                + t * (0.09678418
+                    + t * (-0.18628806
+                        + t * (0.27886807
+                            + t * (-1.13520398 + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277))))))));
+    let result = t * polynomial.exp();
+    if x >= 0.0 {
+        result
+    } else {
+        2.0 - result
+    }
+}
+
+#[cfg(all(test, not(feature = "async")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_entrant_with_higher_champions_wins() {
+        let low = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let high = [6.0, 7.0, 8.0, 9.0, 10.0];
+        let (u, p_value) = mann_whitney(&low, &high);
+        assert_eq!(0.0, u);
+        assert!((p_value - 0.0122).abs() < 0.001);
+        assert_eq!((4.5, 1.0), mann_whitney(&[1.0, 2.0, 3.0], &[1.0, 2.0, 3.0]));
+        assert_eq!(1.0, mann_whitney(&[2.0, 2.0], &[2.0, 2.0]).1);
+
+        let entrants: Vec<EntrantResult> = [("low", &low), ("high", &high), ("also low", &low)]
+            .into_iter()
+            .map(|(name, scores)| EntrantResult {
+                name: name.into(),
+                champion_scores: scores.to_vec(),
+            })
+            .collect();
+        let comparisons: Vec<EntrantComparison> = [(0, 1), (0, 2), (1, 2)]
+            .into_iter()
+            .map(|(first, second)| {
+                let (u, p_value) = mann_whitney(&entrants[first].champion_scores, &entrants[second].champion_scores);
+                EntrantComparison {
+                    first,
+                    second,
+                    u,
+                    p_value,
+                }
+            })
+            .collect();
+        assert_eq!(Some(1), choose_winner(&entrants, &comparisons, 0.05));
+        assert_eq!(None, choose_winner(&entrants, &comparisons, 0.01));
+    }
+}
@@ -0,0 +1,25 @@
+/// How an island breeds during its first generations. Crossover between the random code of a young island mostly
+/// produces more random code, so a warm-up can leave crossover out until the population has found some structure.
+///
+/// The warm-up lasts while an island is bred for the first `generations` times. The elites are carried over as usual.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WarmUp {
+    /// The island is bred normally from the start
+    None,
+
+    /// Every child is a mutation of a single parent
+    MutationOnly { generations: usize },
+
+    /// Every child is new random code, as in the first generation
+    RandomImmigrants { generations: usize },
+}
+
+impl WarmUp {
+    /// Returns the number of times an island is bred before the warm-up ends
+    pub fn generations(&self) -> usize {
+        match *self {
+            WarmUp::None => 0,
+            WarmUp::MutationOnly { generations } | WarmUp::RandomImmigrants { generations } => generations,
+        }
+    }
+}
@@ -0,0 +1,293 @@
+use crate::{FunctionSignature, ValueType};
+use anyhow::Result;
+use wasm_ast::{
+    BlockType, ControlInstruction, Export, Expression, Function, FunctionIndex, FunctionType, Import, Instruction,
+    IntegerType, Limit, Memory, MemoryArgument, MemoryInstruction, ModuleBuilder, Name, NumberType, NumericInstruction,
+    ParametricInstruction, ResultType, SignExtension, VariableInstruction,
+};
+
+const WASI_MODULE: &str = "wasi_snapshot_preview1";
+
+// Where the shim keeps its data in the one page of memory. The arguments are read into the rest of the page, so a
+// command can be given up to 255 arguments.
+const ARGC: i32 = 0;
+const ARGV_BUF_SIZE: i32 = 4;
+const IOVEC: i32 = 8;
+const NWRITTEN: i32 = 16;
+const DIGITS_END: i32 = 64;
+const ARGV: i32 = 1024;
+const ARGV_BUF: i32 = 2048;
+
+// The WASI functions used by the shim
+pub(crate) struct WasiImports {
+    args_sizes_get: FunctionIndex,
+    args_get: FunctionIndex,
+    fd_write: FunctionIndex,
+}
+
+// Imports the WASI functions used by the shim. Imports must be added before any function of the module, or the indices
+// of the functions would move.
+pub(crate) fn add_wasi_imports(builder: &mut ModuleBuilder) -> Result<WasiImports> {
+    let mut import_function = |name: &str, params: usize| -> Result<FunctionIndex> {
+        let params = ResultType::from(vec![wasm_ast::ValueType::I32; params]);
+        let results = ResultType::from(vec![wasm_ast::ValueType::I32]);
+        let type_index = builder.add_function_type(FunctionType::new(params, results))?;
+        let import = Import::function(
+            Name::new(String::from(WASI_MODULE)),
+            Name::new(String::from(name)),
+            type_index,
+        );
+        Ok(builder.add_import(import)?)
+    };
+    Ok(WasiImports {
+        args_sizes_get: import_function("args_sizes_get", 2)?,
+        args_get: import_function("args_get", 2)?,
+        fd_write: import_function("fd_write", 4)?,
+    })
+}
+
+// Adds the memory and the exported `_start` function of a WASI command. `_start` parses each parameter of the main
+// function from the argument in the same position (after the name of the program), calls the main function, and writes
+// each of its results to stdout as a decimal number on a line of its own. A missing argument is zero, and an argument
+// is read up to its first character that is not a digit. The parameters and results must be integers.
+pub(crate) fn add_start_function(
+    builder: &mut ModuleBuilder,
+    imports: &WasiImports,
+    main: &FunctionSignature,
+    main_index: FunctionIndex,
+    is_signed: bool,
+) -> Result<()> {
+    let memory_index = builder.add_memory(Memory::from(Limit::unbounded(1)))?;
+    builder.add_export(Export::memory("memory".into(), memory_index));
+    let parse_index = add_parse_function(builder)?;
+    let print_index = add_print_function(builder, imports, is_signed)?;
+
+    // Each parameter is parsed into an i64 local, and each result is kept in a local of its own type
+    let param_count = main.params().len() as u32;
+    let mut locals = vec![wasm_ast::ValueType::I64; main.params().len()];
+    locals.extend(main.results_ast());
+    let mut instructions: Vec<Instruction> = vec![
+        NumericInstruction::I32Constant(ARGC).into(),
+        NumericInstruction::I32Constant(ARGV_BUF_SIZE).into(),
+        ControlInstruction::Call(imports.args_sizes_get).into(),
+        ParametricInstruction::Drop.into(),
+        NumericInstruction::I32Constant(ARGV).into(),
+        NumericInstruction::I32Constant(ARGV_BUF).into(),
+        ControlInstruction::Call(imports.args_get).into(),
+        ParametricInstruction::Drop.into(),
+    ];
+    for param in 0..param_count {
+        // The first argument is the name of the program
+        let argument = param as i32 + 1;
+        let parse: Vec<Instruction> = vec![
+            NumericInstruction::I32Constant(ARGV + 4 * argument).into(),
+            MemoryInstruction::Load(NumberType::I32, word_argument()).into(),
+            ControlInstruction::Call(parse_index).into(),
+            VariableInstruction::LocalSet(param).into(),
+        ];
+        instructions.push(NumericInstruction::I32Constant(ARGC).into());
+        instructions.push(MemoryInstruction::Load(NumberType::I32, word_argument()).into());
+        instructions.push(NumericInstruction::I32Constant(argument).into());
+        instructions.push(NumericInstruction::GreaterThanInteger(IntegerType::I32, SignExtension::Unsigned).into());
+        instructions.push(ControlInstruction::If(BlockType::None, Expression::new(parse), None).into());
+    }
+    for (param, value_type) in main.params().iter().enumerate() {
+        instructions.push(VariableInstruction::LocalGet(param as u32).into());
+        if *value_type == ValueType::I32 {
+            instructions.push(NumericInstruction::Wrap.into());
+        }
+    }
+    instructions.push(ControlInstruction::Call(main_index).into());
+
+    // The last result is on the top of the stack
+    let result_local = |result: usize| param_count + result as u32;
+    for result in (0..main.results().len()).rev() {
+        instructions.push(VariableInstruction::LocalSet(result_local(result)).into());
+    }
+    for (result, value_type) in main.results().iter().enumerate() {
+        instructions.push(VariableInstruction::LocalGet(result_local(result)).into());
+        if *value_type == ValueType::I32 {
+            instructions.push(NumericInstruction::ExtendWithSignExtension(sign_extension(is_signed)).into());
+        }
+        instructions.push(ControlInstruction::Call(print_index).into());
+    }
+
+    let type_index = builder.add_function_type(FunctionType::new(ResultType::empty(), ResultType::empty()))?;
+    let function = Function::new(type_index, ResultType::from(locals), instructions.into());
+    let start_index = builder.add_function(function)?;
+    builder.add_export(Export::function("_start".into(), start_index));
+    Ok(())
+}
+
+// Adds `parse(pointer: i32) -> i64`, which reads an optional minus sign and then decimal digits from the string at the
+// pointer
+fn add_parse_function(builder: &mut ModuleBuilder) -> Result<FunctionIndex> {
+    let (pointer, negative, value, digit) = (0, 1, 2, 3);
+    let skip_sign: Vec<Instruction> = vec![
+        NumericInstruction::I32Constant(1).into(),
+        VariableInstruction::LocalSet(negative).into(),
+        VariableInstruction::LocalGet(pointer).into(),
+        NumericInstruction::I32Constant(1).into(),
+        NumericInstruction::Add(NumberType::I32).into(),
+        VariableInstruction::LocalSet(pointer).into(),
+    ];
+    let read_digit: Vec<Instruction> = vec![
+        VariableInstruction::LocalGet(pointer).into(),
+        MemoryInstruction::Load8(IntegerType::I32, SignExtension::Unsigned, byte_argument()).into(),
+        NumericInstruction::I32Constant(b'0' as i32).into(),
+        NumericInstruction::Subtract(NumberType::I32).into(),
+        VariableInstruction::LocalSet(digit).into(),
+        // Anything but a digit, including the NUL at the end, ends the number
+        VariableInstruction::LocalGet(digit).into(),
+        NumericInstruction::I32Constant(10).into(),
+        NumericInstruction::GreaterThanOrEqualToInteger(IntegerType::I32, SignExtension::Unsigned).into(),
+        ControlInstruction::BranchIf(1).into(),
+        VariableInstruction::LocalGet(value).into(),
+        NumericInstruction::I64Constant(10).into(),
+        NumericInstruction::Multiply(NumberType::I64).into(),
+        VariableInstruction::LocalGet(digit).into(),
+        NumericInstruction::ExtendWithSignExtension(SignExtension::Unsigned).into(),
+        NumericInstruction::Add(NumberType::I64).into(),
+        VariableInstruction::LocalSet(value).into(),
+        VariableInstruction::LocalGet(pointer).into(),
+        NumericInstruction::I32Constant(1).into(),
+        NumericInstruction::Add(NumberType::I32).into(),
+        VariableInstruction::LocalSet(pointer).into(),
+        ControlInstruction::Branch(0).into(),
+    ];
+    let read_digits = vec![ControlInstruction::Loop(BlockType::None, Expression::new(read_digit)).into()];
+    let negate: Vec<Instruction> = vec![
+        NumericInstruction::I64Constant(0).into(),
+        VariableInstruction::LocalGet(value).into(),
+        NumericInstruction::Subtract(NumberType::I64).into(),
+        VariableInstruction::LocalSet(value).into(),
+    ];
+    let instructions: Vec<Instruction> = vec![
+        VariableInstruction::LocalGet(pointer).into(),
+        MemoryInstruction::Load8(IntegerType::I32, SignExtension::Unsigned, byte_argument()).into(),
+        NumericInstruction::I32Constant(b'-' as i32).into(),
+        NumericInstruction::Equal(NumberType::I32).into(),
+        ControlInstruction::If(BlockType::None, Expression::new(skip_sign), None).into(),
+        ControlInstruction::Block(BlockType::None, Expression::new(read_digits)).into(),
+        VariableInstruction::LocalGet(negative).into(),
+        ControlInstruction::If(BlockType::None, Expression::new(negate), None).into(),
+        VariableInstruction::LocalGet(value).into(),
+    ];
+
+    let function_type = FunctionType::new(
+        ResultType::from(vec![wasm_ast::ValueType::I32]),
+        ResultType::from(vec![wasm_ast::ValueType::I64]),
+    );
+    let type_index = builder.add_function_type(function_type)?;
+    let locals = ResultType::from(vec![
+        wasm_ast::ValueType::I32,
+        wasm_ast::ValueType::I64,
+        wasm_ast::ValueType::I32,
+    ]);
+    Ok(builder.add_function(Function::new(type_index, locals, instructions.into()))?)
+}
+
+// Adds `print(value: i64)`, which writes the value and a newline to stdout. The digits are written backwards from the
+// end of their buffer.
+fn add_print_function(builder: &mut ModuleBuilder, imports: &WasiImports, is_signed: bool) -> Result<FunctionIndex> {
+    let (value, position, negative) = (0, 1, 2);
+    let write_byte = |byte: Vec<Instruction>| -> Vec<Instruction> {
+        let mut instructions: Vec<Instruction> = vec![
+            VariableInstruction::LocalGet(position).into(),
+            NumericInstruction::I32Constant(1).into(),
+            NumericInstruction::Subtract(NumberType::I32).into(),
+            VariableInstruction::LocalSet(position).into(),
+            VariableInstruction::LocalGet(position).into(),
+        ];
+        instructions.extend(byte);
+        instructions.push(MemoryInstruction::Store8(IntegerType::I32, byte_argument()).into());
+        instructions
+    };
+
+    let mut instructions: Vec<Instruction> = vec![
+        NumericInstruction::I32Constant(DIGITS_END).into(),
+        VariableInstruction::LocalSet(position).into(),
+    ];
+    instructions.extend(write_byte(vec![NumericInstruction::I32Constant(b'\n' as i32).into()]));
+    if is_signed {
+        // The digits of the smallest i64 come out right, because they are divided out as an unsigned number
+        let negate: Vec<Instruction> = vec![
+            NumericInstruction::I64Constant(0).into(),
+            VariableInstruction::LocalGet(value).into(),
+            NumericInstruction::Subtract(NumberType::I64).into(),
+            VariableInstruction::LocalSet(value).into(),
+        ];
+        instructions.extend(vec![
+            VariableInstruction::LocalGet(value).into(),
+            NumericInstruction::I64Constant(0).into(),
+            NumericInstruction::LessThanInteger(IntegerType::I64, SignExtension::Signed).into(),
+            VariableInstruction::LocalSet(negative).into(),
+            VariableInstruction::LocalGet(negative).into(),
+            ControlInstruction::If(BlockType::None, Expression::new(negate), None).into(),
+        ]);
+    }
+    let mut write_digit = write_byte(vec![
+        VariableInstruction::LocalGet(value).into(),
+        NumericInstruction::I64Constant(10).into(),
+        NumericInstruction::Remainder(IntegerType::I64, SignExtension::Unsigned).into(),
+        NumericInstruction::Wrap.into(),
+        NumericInstruction::I32Constant(b'0' as i32).into(),
+        NumericInstruction::Add(NumberType::I32).into(),
+    ]);
+    write_digit.extend(vec![
+        VariableInstruction::LocalGet(value).into(),
+        NumericInstruction::I64Constant(10).into(),
+        NumericInstruction::DivideInteger(IntegerType::I64, SignExtension::Unsigned).into(),
+        VariableInstruction::LocalSet(value).into(),
+        VariableInstruction::LocalGet(value).into(),
+        NumericInstruction::EqualToZero(IntegerType::I64).into(),
+        NumericInstruction::EqualToZero(IntegerType::I32).into(),
+        ControlInstruction::BranchIf(0).into(),
+    ]);
+    instructions.push(ControlInstruction::Loop(BlockType::None, Expression::new(write_digit)).into());
+    if is_signed {
+        let minus = write_byte(vec![NumericInstruction::I32Constant(b'-' as i32).into()]);
+        instructions.push(VariableInstruction::LocalGet(negative).into());
+        instructions.push(ControlInstruction::If(BlockType::None, Expression::new(minus), None).into());
+    }
+
+    // Write the bytes from the position to the end of the buffer with one iovec
+    instructions.extend(vec![
+        NumericInstruction::I32Constant(IOVEC).into(),
+        VariableInstruction::LocalGet(position).into(),
+        MemoryInstruction::Store(NumberType::I32, word_argument()).into(),
+        NumericInstruction::I32Constant(IOVEC + 4).into(),
+        NumericInstruction::I32Constant(DIGITS_END).into(),
+        VariableInstruction::LocalGet(position).into(),
+        NumericInstruction::Subtract(NumberType::I32).into(),
+        MemoryInstruction::Store(NumberType::I32, word_argument()).into(),
+        NumericInstruction::I32Constant(1).into(),
+        NumericInstruction::I32Constant(IOVEC).into(),
+        NumericInstruction::I32Constant(1).into(),
+        NumericInstruction::I32Constant(NWRITTEN).into(),
+        ControlInstruction::Call(imports.fd_write).into(),
+        ParametricInstruction::Drop.into(),
+    ]);
+
+    let function_type = FunctionType::new(ResultType::from(vec![wasm_ast::ValueType::I64]), ResultType::empty());
+    let type_index = builder.add_function_type(function_type)?;
+    let locals = ResultType::from(vec![wasm_ast::ValueType::I32, wasm_ast::ValueType::I32]);
+    Ok(builder.add_function(Function::new(type_index, locals, instructions.into()))?)
+}
+
+// The alignment of a byte and of a 32-bit word, as powers of two
+fn byte_argument() -> MemoryArgument {
+    MemoryArgument::default_offset(0)
+}
+
+fn word_argument() -> MemoryArgument {
+    MemoryArgument::default_offset(2)
+}
+
+fn sign_extension(is_signed: bool) -> SignExtension {
+    if is_signed {
+        SignExtension::Signed
+    } else {
+        SignExtension::Unsigned
+    }
+}
@@ -0,0 +1,256 @@
+use crate::{Individual, RunResult};
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+/// Whether a larger or a smaller value of a metric is more fit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Goal {
+    Maximize,
+    Minimize,
+}
+
+/// How the normalized metrics of a `WeightedFitness` are combined into a single fitness.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FitnessCombinator {
+    /// The weighted mean of the metrics. A weak metric can be made up for by a strong one.
+    WeightedSum,
+
+    /// The product of each metric raised to the power of its weight. An individual that does badly on any one metric
+    /// has a low fitness.
+    Product,
+
+    /// The worst of the metrics. The weights are ignored.
+    Min,
+}
+
+/// Combines several metrics of a RunResult into a single fitness between 0.0 and 1.0.
+///
+/// Raw metrics rarely share a scale (games won out of 100 versus cards left out of 5200), so each metric is normalized
+/// by the lowest and highest value seen in the population when `learn_normalization` was last called. Call it from
+/// `IslandCallbacks::post_generation_run`, which runs after every individual has a RunResult and before they are
+/// sorted, and then use `compare` or `score` to sort.
+///
+/// ```
+/// use wasmgp::*;
+///
+/// #[derive(Clone, Debug, PartialEq)]
+/// struct Outcome {
+///     wins: u32,
+///     moves: u32,
+/// }
+/// impl RunResult for Outcome {}
+///
+/// let mut fitness = WeightedFitness::new(FitnessCombinator::WeightedSum);
+/// fitness.add_metric("wins", 3.0, Goal::Maximize, |r: &Outcome| r.wins as f64);
+/// fitness.add_metric("moves", 1.0, Goal::Minimize, |r: &Outcome| r.moves as f64);
+/// fitness.learn_normalization_from_results(&[Outcome { wins: 0, moves: 100 }, Outcome { wins: 10, moves: 200 }]);
+///
+/// // Winning every game with the most moves beats winning nothing with the fewest moves
+/// assert_eq!(0.75, fitness.fitness(&Outcome { wins: 10, moves: 200 }));
+/// assert_eq!(0.25, fitness.fitness(&Outcome { wins: 0, moves: 100 }));
+/// ```
+pub struct WeightedFitness<R: RunResult> {
+    combinator: FitnessCombinator,
+    metrics: Vec<Metric<R>>,
+}
+
+struct Metric<R> {
+    name: String,
+    weight: f64,
+    goal: Goal,
+    measure: Arc<dyn Fn(&R) -> f64 + Send + Sync>,
+    range: Option<(f64, f64)>,
+}
+
+impl<R: RunResult> WeightedFitness<R> {
+    pub fn new(combinator: FitnessCombinator) -> WeightedFitness<R> {
+        WeightedFitness {
+            combinator,
+            metrics: vec![],
+        }
+    }
+
+    /// Adds a metric that is measured from each RunResult. Metrics with a weight of zero have no effect on the
+    /// `WeightedSum` or `Product` of the metrics.
+    pub fn add_metric<F>(&mut self, name: &str, weight: f64, goal: Goal, measure: F)
+    where
+        F: Fn(&R) -> f64 + Send + Sync + 'static,
+    {
+        self.metrics.push(Metric {
+            name: name.to_string(),
+            weight,
+            goal,
+            measure: Arc::new(measure),
+            range: None,
+        });
+    }
+
+    /// Learns the range of every metric from the individuals that have a RunResult
+    pub fn learn_normalization<T>(&mut self, individuals: &[Individual<T, R>]) {
+        let results: Vec<&R> = individuals.iter().filter_map(|i| i.get_run_result()).collect();
+        self.learn_ranges(&results[..]);
+    }
+
+    /// Learns the range of every metric from a set of RunResults
+    pub fn learn_normalization_from_results(&mut self, results: &[R]) {
+        let results: Vec<&R> = results.iter().collect();
+        self.learn_ranges(&results[..]);
+    }
+
+    fn learn_ranges(&mut self, results: &[&R]) {
+        for metric in self.metrics.iter_mut() {
+            metric.range = results
+                .iter()
+                .map(|result| (metric.measure)(result))
+                .filter(|value| !value.is_nan())
+                .fold(None, |range, value| match range {
+                    None => Some((value, value)),
+                    Some((low, high)) => Some((f64::min(low, value), f64::max(high, value))),
+                });
+        }
+    }
+
+    /// Returns the lowest and highest value of the named metric from the last call to `learn_normalization`, or None
+    /// if there was nothing to learn from
+    pub fn metric_range(&self, name: &str) -> Option<(f64, f64)> {
+        self.metrics.iter().find(|m| m.name == name).and_then(|m| m.range)
+    }
+
+    /// Returns the value of each metric for the RunResult, scaled to 0.0 for the least fit value in the population and
+    /// 1.0 for the most fit. Values outside of the learned range are clamped. A metric that had the same value for the
+    /// whole population, or that has not learned a range yet, is 1.0 for every RunResult.
+    pub fn normalized_metrics(&self, run_result: &R) -> Vec<f64> {
+        self.metrics
+            .iter()
+            .map(|metric| {
+                let value = (metric.measure)(run_result);
+                let normalized = match metric.range {
+                    Some((low, high)) if high > low => ((value - low) / (high - low)).clamp(0.0, 1.0),
+                    _ => 1.0,
+                };
+                match metric.goal {
+                    Goal::Maximize => normalized,
+                    Goal::Minimize => 1.0 - normalized,
+                }
+            })
+            .collect()
+    }
+
+    /// Combines the normalized metrics of the RunResult into a fitness between 0.0 and 1.0
+    pub fn fitness(&self, run_result: &R) -> f64 {
+        let normalized = self.normalized_metrics(run_result);
+        let weighted = normalized.iter().zip(self.metrics.iter().map(|m| m.weight));
+        match self.combinator {
+            FitnessCombinator::WeightedSum => {
+                let total_weight: f64 = self.metrics.iter().map(|m| m.weight).sum();
+                if total_weight > 0.0 {
+                    weighted.map(|(value, weight)| value * weight).sum::<f64>() / total_weight
+                } else {
+                    0.0
+                }
+            }
+            FitnessCombinator::Product => weighted.map(|(value, weight)| value.powf(weight)).product(),
+            FitnessCombinator::Min => normalized.iter().copied().fold(1.0, f64::min),
+        }
+    }
+
+    /// The fitness scaled up to a whole number, for use by `IslandCallbacks::score_individual`. An individual without
+    /// a RunResult scores zero.
+    pub fn score<T>(&self, individual: &Individual<T, R>) -> u64 {
+        individual
+            .get_run_result()
+            .map_or(0, |result| (self.fitness(result) * 1_000_000.0).round() as u64)
+    }
+
+    /// Orders two individuals from least fit to most fit, for use by `IslandCallbacks::sort_individuals`. Individuals
+    /// without a RunResult are the least fit.
+    pub fn compare<T>(&self, a: &Individual<T, R>, b: &Individual<T, R>) -> Ordering {
+        let a = a.get_run_result().map(|result| self.fitness(result));
+        let b = b.get_run_result().map(|result| self.fitness(result));
+        match (a, b) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            (a, b) => a.is_some().cmp(&b.is_some()),
+        }
+    }
+}
+
+impl<R: RunResult> Clone for WeightedFitness<R> {
+    fn clone(&self) -> Self {
+        WeightedFitness {
+            combinator: self.combinator,
+            metrics: self
+                .metrics
+                .iter()
+                .map(|metric| Metric {
+                    name: metric.name.clone(),
+                    weight: metric.weight,
+                    goal: metric.goal,
+                    measure: metric.measure.clone(),
+                    range: metric.range,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Outcome {
+        wins: u32,
+        cards_left: u32,
+    }
+
+    impl RunResult for Outcome {}
+
+    fn outcome(wins: u32, cards_left: u32) -> Outcome {
+        Outcome { wins, cards_left }
+    }
+
+    fn fitness_for(combinator: FitnessCombinator) -> WeightedFitness<Outcome> {
+        let mut fitness = WeightedFitness::new(combinator);
+        fitness.add_metric("wins", 1.0, Goal::Maximize, |r: &Outcome| r.wins as f64);
+        fitness.add_metric("cards_left", 2.0, Goal::Minimize, |r: &Outcome| r.cards_left as f64);
+        fitness.learn_normalization_from_results(&[outcome(0, 52), outcome(4, 12), outcome(2, 0)]);
+        fitness
+    }
+
+    #[test]
+    fn metrics_are_normalized_against_the_population() {
+        let fitness = fitness_for(FitnessCombinator::WeightedSum);
+        assert_eq!(Some((0.0, 4.0)), fitness.metric_range("wins"));
+        assert_eq!(Some((0.0, 52.0)), fitness.metric_range("cards_left"));
+        assert_eq!(None, fitness.metric_range("unknown"));
+
+        assert_eq!(vec![0.5, 0.75], fitness.normalized_metrics(&outcome(2, 13)));
+
+        // Values outside of the learned range are clamped
+        assert_eq!(vec![1.0, 0.0], fitness.normalized_metrics(&outcome(8, 60)));
+    }
+
+    #[test]
+    fn combinators() {
+        let result = outcome(2, 13);
+        assert_eq!(2.0 / 3.0, fitness_for(FitnessCombinator::WeightedSum).fitness(&result));
+        assert_eq!(
+            0.5 * 0.75 * 0.75,
+            fitness_for(FitnessCombinator::Product).fitness(&result)
+        );
+        assert_eq!(0.5, fitness_for(FitnessCombinator::Min).fitness(&result));
+
+        // One bad metric sinks the product but not the sum
+        let result = outcome(0, 0);
+        assert_eq!(2.0 / 3.0, fitness_for(FitnessCombinator::WeightedSum).fitness(&result));
+        assert_eq!(0.0, fitness_for(FitnessCombinator::Product).fitness(&result));
+    }
+
+    #[test]
+    fn a_metric_without_spread_does_not_discriminate() {
+        let mut fitness = fitness_for(FitnessCombinator::Product);
+        fitness.learn_normalization_from_results(&[outcome(3, 10), outcome(3, 20)]);
+        assert_eq!(vec![1.0, 0.5], fitness.normalized_metrics(&outcome(3, 15)));
+        assert_eq!(0.25, fitness.fitness(&outcome(3, 15)));
+    }
+}
@@ -0,0 +1,191 @@
+use crate::{parse_code_listing, IslandCallbacks, RunResult, World};
+use anyhow::Result;
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+
+/// Evaluates individuals on behalf of another process, so that the islands of a run can hand their evaluations to
+/// other machines. The worker owns a `World` that defines the entry point and host imports, and any number of named
+/// fitness functions. Each fitness function is an `IslandCallbacks` that runs and scores one individual at a time.
+///
+/// The protocol is plain text with one message per line. The worker answers every `evaluate` with exactly one line:
+/// - `fitness <name>` chooses the registered fitness function for the evaluations that follow
+/// - `cases <index> <index> ...` sets the fitness cases passed to `use_fitness_cases` before each evaluation. A bare
+///   `cases` clears them.
+/// - `evaluate <request>` is followed by the code listing of the individual, as printed by `Individual::get_code_string`
+///   and ending with an unindented `]` line. The answer is `score <request> <score>`, or `error <request> <message>`
+///   if the individual could not be evaluated.
+/// - `quit` stops the worker. The worker also stops at the end of the input.
+///
+/// The `wasmgp-worker` binary (built with the `worker` feature) serves a fitness function that only rewards small
+/// programs, which is enough to test a cluster. Real domains build their own binary that registers their callbacks and
+/// calls `serve_stdio`. The World of the worker should leave `WorldConfiguration::fitness_cases` at zero, since the
+/// cases come from the messages instead.
+/// ```
+/// use wasmgp::*;
+///
+/// // Scores each individual by its number of points
+/// #[derive(Clone)]
+/// struct CountPoints;
+/// impl IslandCallbacks<(), EmptyRunResult> for CountPoints {
+///     fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+///         Box::new(Clone::clone(self))
+///     }
+///     fn run_individual(&mut self, _individual: &mut Individual<(), EmptyRunResult>) {}
+///     fn score_individual(&self, individual: &Individual<(), EmptyRunResult>) -> u64 {
+///         individual.get_code().iter().map(|code| code.points() as u64).sum()
+///     }
+/// }
+///
+/// let world = World::<(), EmptyRunResult>::new(WorldConfiguration::default()).unwrap();
+/// let mut worker = Worker::new(world);
+/// worker.register_fitness("points", Box::new(CountPoints));
+///
+/// let input = "fitness points\nevaluate 7\n[\n    ConstOne::new(0),\n]\n";
+/// let mut output = vec![];
+/// worker.serve(input.as_bytes(), &mut output).unwrap();
+/// assert_eq!("score 7 1\n", String::from_utf8(output).unwrap());
+/// ```
+pub struct Worker<T, R: RunResult> {
+    world: World<T, R>,
+    fitness: HashMap<String, Box<dyn IslandCallbacks<T, R>>>,
+    current_fitness: Option<String>,
+    cases: Vec<usize>,
+}
+
+impl<T: Default, R: RunResult> Worker<T, R> {
+    pub fn new(world: World<T, R>) -> Worker<T, R> {
+        Worker {
+            world,
+            fitness: HashMap::new(),
+            current_fitness: None,
+            cases: vec![],
+        }
+    }
+
+    /// Registers a fitness function under the name that `fitness` messages use. A second registration of the same name
+    /// replaces the first.
+    pub fn register_fitness(&mut self, name: &str, callbacks: Box<dyn IslandCallbacks<T, R>>) {
+        self.fitness.insert(name.to_owned(), callbacks);
+    }
+
+    /// Answers the messages read from `input` until it ends or a `quit` message arrives. An individual that cannot be
+    /// evaluated is answered with an error line and the worker keeps going, but a failure to read or write is returned.
+    pub fn serve<I: BufRead, O: Write>(&mut self, input: I, mut output: O) -> Result<()> {
+        let mut lines = input.lines();
+        while let Some(line) = lines.next() {
+            let line = line?;
+            let (command, argument) = line.split_once(' ').unwrap_or((line.as_str(), ""));
+            match command {
+                "fitness" => self.current_fitness = Some(argument.to_owned()),
+                "cases" => {
+                    self.cases = argument
+                        .split_whitespace()
+                        .filter_map(|index| index.parse().ok())
+                        .collect()
+                }
+                "evaluate" => {
+                    let mut listing = String::new();
+                    for listing_line in lines.by_ref() {
+                        let listing_line = listing_line?;
+                        listing.push_str(&listing_line);
+                        listing.push('\n');
+                        if listing_line == "]" {
+                            break;
+                        }
+                    }
+                    match self.evaluate(&listing) {
+                        Ok(score) => writeln!(output, "score {} {}", argument, score)?,
+                        Err(error) => writeln!(output, "error {} {:#}", argument, error)?,
+                    }
+                    output.flush()?;
+                }
+                "quit" => break,
+                "" => {}
+                _ => {
+                    writeln!(output, "error - unknown message '{}'", command)?;
+                    output.flush()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Answers the messages read from standard input on standard output
+    pub fn serve_stdio(&mut self) -> Result<()> {
+        self.serve(std::io::stdin().lock(), std::io::stdout().lock())
+    }
+
+    fn evaluate(&mut self, listing: &str) -> Result<u64> {
+        let code = parse_code_listing(listing)?;
+        let name = self
+            .current_fitness
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("no fitness function was chosen"))?;
+        let callbacks = self
+            .fitness
+            .get_mut(name)
+            .ok_or_else(|| anyhow::anyhow!("there is no fitness function named '{}'", name))?;
+        if !self.cases.is_empty() {
+            callbacks.use_fitness_cases(&self.cases[..]);
+        }
+        let (_, score) = self.world.evaluate(&code, callbacks.as_mut())?;
+        Ok(score)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    // Scores each individual by the sum of its fitness cases
+    #[derive(Clone)]
+    struct SumTheCases(u64);
+    impl IslandCallbacks<(), EmptyRunResult> for SumTheCases {
+        fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+            Box::new(Clone::clone(self))
+        }
+        fn use_fitness_cases(&mut self, cases: &[usize]) {
+            self.0 = cases.iter().sum::<usize>() as u64;
+        }
+        fn run_individual(&mut self, _individual: &mut Individual<(), EmptyRunResult>) {}
+        fn score_individual(&self, _individual: &Individual<(), EmptyRunResult>) -> u64 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn every_evaluation_gets_one_answer() {
+        let world = World::<(), EmptyRunResult>::new(WorldConfiguration::default()).unwrap();
+        let mut worker = Worker::new(world);
+        worker.register_fitness("cases", Box::new(SumTheCases(0)));
+
+        let input = "evaluate 1
+[
+]
+fitness cases
+cases 2 3
+evaluate 2
+[
+    ConstOne::new(0),
+]
+bogus
+evaluate 3
+[
+    NotACode::new(),
+]
+quit
+evaluate 4
+[
+]
+";
+        let mut output = vec![];
+        worker.serve(input.as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let answers: Vec<&str> = output.lines().collect();
+        assert_eq!(4, answers.len());
+        assert_eq!("error 1 no fitness function was chosen", answers[0]);
+        assert_eq!("score 2 5", answers[1]);
+        assert_eq!("error - unknown message 'bogus'", answers[2]);
+        assert!(answers[3].starts_with("error 3 "));
+    }
+}
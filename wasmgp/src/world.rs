@@ -1,20 +1,59 @@
+use crate::checkpoint::{Checkpoint, CheckpointIsland, CheckpointTeam, FitnessCaseCountdown};
+#[cfg(feature = "differential")]
+use crate::differential::{self, DifferentialOptions, DifferentialReport};
+use crate::emission_template::EmissionTemplate;
+use crate::host_call_log;
+use crate::island_functions::IslandFunctions;
+use crate::island_sizing::{self, HallOfFame};
+use crate::local_search;
+use crate::run_report::RunReport;
+#[cfg(feature = "wasi")]
+use crate::wasi_command;
+#[cfg(feature = "async")]
+use crate::AsyncIslandCallbacks;
 use crate::{
-    Code, CodeContext, FunctionSignature, GeneticEngine, GeneticEngineConfiguration, Individual, Island,
-    IslandCallbacks, MigrationAlgorithm, RunResult, WasmgpError, WorldConfiguration,
+    reduce_code, simplify_code, CallLimitPolicy, CallbackState, CancellationToken, ChampionListing, Code, CodeContext,
+    Ensemble, EvaluationContext, FitnessCaseSchedule, FitnessDomain, FunctionSignature, GenerationRecord,
+    GenerationStats, GeneticEngine, GeneticEngineConfiguration, HoldoutResult, HostCallRecord, ImportOptions,
+    ImportReport, Individual, IndividualId, Initialization, InstructionSet, Island, IslandCallbacks,
+    MigrationAlgorithm, MigrationEvent, MigrationPayload, ModuleMetadata, OpcodeCounts, OpcodeUsage, Operator,
+    ParentPairing, ReferenceEvaluator, RunPolicy, RunResult, SecurityReport, SelectionStrategy, SlotInit, SlotValue,
+    SubtreeArchive, ThreadingModel, ValueType, WarmUp, WasmgpError, WorldConfiguration,
 };
-use anyhow::Result;
+use anyhow::{Context, Result};
 use rand::seq::SliceRandom;
 use rand::Rng;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::path::Path;
+#[cfg(not(feature = "async"))]
+use std::sync::Mutex;
+use std::sync::OnceLock;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::vec;
-use wasm_ast::{FunctionIndex, Import, ModuleBuilder, Name};
-use wasmtime::{AsContextMut, Config, Engine, Extern, Func, Instance, InstancePre, IntoFunc, Linker, Store};
+use wasm_ast::{
+    ControlInstruction, Export, Function, FunctionIndex, Import, Instruction, ModuleBuilder, Name, ResultType,
+    VariableInstruction,
+};
+use wasmtime::{AsContextMut, Config, Engine, Extern, Func, Instance, InstancePre, IntoFunc, Linker, Store, Val};
 
 pub type IslandId = usize;
 
 pub const MODULE_NAME: &'static str = "host";
 
+/// The name of the random number import that is added when `WorldConfiguration::random_import` is set. It takes no
+/// parameters and returns an i32.
+pub const RANDOM_IMPORT: &str = "rand_u32";
+
+// The import that supplies the values of work slots initialized with `SlotInit::RandomPerCall`
+pub(crate) const RANDOM_SLOT_IMPORT: &str = "wasmgp_random_slot";
+
+// The imports that count numeric events when `WorldConfiguration::numeric_telemetry` is set
+pub(crate) const DIVISION_BY_ZERO_IMPORT: &str = "wasmgp_division_by_zero";
+pub(crate) const CHECK_F32_CONVERSION_IMPORT: &str = "wasmgp_check_f32_conversion";
+pub(crate) const CHECK_F64_CONVERSION_IMPORT: &str = "wasmgp_check_f64_conversion";
+
 /// A WasmGP world holds the islands where individuals live. It contains the logic behind how individuals are tested,
 /// how to progress from generation to generation, how to alter future generations, etc.
 ///
@@ -34,33 +73,296 @@ pub struct World<T, R: RunResult> {
     genetic_engine: GeneticEngine,
     linker: Linker<T>,
     imported_functions: Vec<FunctionSignature>,
+    import_options: Vec<ImportOptions>,
     module_builder: ModuleBuilder,
+    emission_template: OnceLock<Option<EmissionTemplate>>,
     islands: Vec<Island<T, R>>,
     generations_remaining_before_migration: usize,
+    generations_complete: usize,
+    next_individual_id: IndividualId,
+    migration_log: Vec<MigrationEvent>,
+    champion_listings: Vec<ChampionListing>,
+    generation_history: Vec<GenerationRecord>,
+    last_generation_stats: Vec<GenerationStats>,
+    cancellation: CancellationToken,
+    fitness_cases: Vec<usize>,
+    generations_until_new_fitness_cases: usize,
+    last_checkpoint: Instant,
+    hall_of_fame: HallOfFame,
+    run_policy: Option<RunPolicy>,
 }
 
 impl<T: Default, R: RunResult> World<T, R> {
-    pub fn new(config: WorldConfiguration) -> Result<World<T, R>> {
-        if config.slot_count() > u8::MAX as usize {
-            return Err(WasmgpError::SlotCountTooLarge(config.slot_count()).into());
-        }
-        let total_slots = config.slot_count() as u8;
+    pub fn new(config: WorldConfiguration) -> Result<World<T, R>>
+    where
+        T: 'static,
+    {
+        // Check first, so that a bad configuration does not leave an epoch thread behind
+        Self::check_configuration(&config)?;
+        let engine = Self::create_engine(&config)?;
+        Self::with_engine(engine, config)
+    }
 
+    /// Creates a wasmtime Engine that can run the individuals of a World with the configuration, and starts the thread
+    /// that advances its epoch `epoch_ticks_per_ms` times every millisecond. `World::new` creates one of these for every
+    /// World.
+    pub fn create_engine(config: &WorldConfiguration) -> Result<Engine> {
         let mut engine_config = Config::default();
         engine_config.epoch_interruption(true);
+        #[cfg(feature = "async")]
+        engine_config.async_support(config.async_epoch_yielding);
+        engine_config.consume_fuel(config.fuel_per_evaluation.is_some());
+        if let Some(bytes) = config.max_wasm_stack {
+            engine_config.max_wasm_stack(bytes);
+        }
         let engine = Engine::new(&engine_config)?;
-        let linker = Linker::new(&engine);
 
-        // Advance the engine's epoch once every millisecond
+        // Advance the engine's epoch once each time the thread wakes up. Sleeping often runs long, and a late tick is
+        // not made up for with a burst of ticks, which would end the runs in progress early. A run may sometimes be
+        // given more time than it asked for, but never less.
         let engine_for_timer = engine.clone();
+        let tick = Duration::from_micros(1000 / config.epoch_ticks_per_ms.clamp(1, 1000));
         thread::spawn(move || loop {
-            thread::sleep(Duration::from_millis(1));
+            thread::sleep(tick);
             engine_for_timer.increment_epoch();
         });
 
+        Ok(engine)
+    }
+
+    /// Creates a World that runs its individuals on an existing wasmtime Engine. Several Worlds, such as the
+    /// populations of a co-evolution or the runs of a hyperparameter search, can then share one Engine and its epoch
+    /// thread instead of each starting their own.
+    ///
+    /// The Engine should come from `World::create_engine` or `World::wasm_engine`. An Engine created any other way must
+    /// have epoch interruption enabled, must have its epoch advanced `epoch_ticks_per_ms` times every millisecond, and
+    /// must have async support exactly when `async_epoch_yielding` is set, must consume fuel exactly when
+    /// `fuel_per_evaluation` is set, and must have the `max_wasm_stack` of the configuration.
+    /// ```
+    /// use wasmgp::*;
+    ///
+    /// let first = World::<(), EmptyRunResult>::new(WorldConfiguration::default()).unwrap();
+    /// let engine = first.wasm_engine().clone();
+    /// let second = World::<(), EmptyRunResult>::with_engine(engine, WorldConfiguration::default()).unwrap();
+    /// assert!(wasmtime::Engine::same(first.wasm_engine(), second.wasm_engine()));
+    /// ```
+    pub fn with_engine(engine: Engine, config: WorldConfiguration) -> Result<World<T, R>>
+    where
+        T: 'static,
+    {
+        let genetic_config = Self::check_configuration(&config)?;
+        let mut linker = Linker::new(&engine);
+        if config.work_slot_initialization == SlotInit::RandomPerCall {
+            linker.func_wrap(MODULE_NAME, RANDOM_SLOT_IMPORT, host_call_log::next_random)?;
+        }
+        if config.numeric_telemetry {
+            linker.func_wrap(
+                MODULE_NAME,
+                DIVISION_BY_ZERO_IMPORT,
+                host_call_log::count_division_by_zero,
+            )?;
+            linker.func_wrap(
+                MODULE_NAME,
+                CHECK_F32_CONVERSION_IMPORT,
+                |value: f32, min: f64, max: f64| {
+                    host_call_log::check_conversion(value as f64, min, max);
+                    value
+                },
+            )?;
+            linker.func_wrap(
+                MODULE_NAME,
+                CHECK_F64_CONVERSION_IMPORT,
+                |value: f64, min: f64, max: f64| {
+                    host_call_log::check_conversion(value, min, max);
+                    value
+                },
+            )?;
+        }
         let generations_remaining_before_migration = config.generations_between_migrations;
-        let mut genetic_config = GeneticEngineConfiguration::new(None, total_slots);
+        let mut world = World {
+            config,
+            wasm_engine: engine,
+            genetic_engine: GeneticEngine::new(genetic_config),
+            linker: linker,
+            imported_functions: vec![],
+            import_options: vec![],
+            module_builder: ModuleBuilder::new(),
+            emission_template: OnceLock::new(),
+            islands: vec![],
+            generations_remaining_before_migration,
+            generations_complete: 0,
+            next_individual_id: 0,
+            migration_log: vec![],
+            champion_listings: vec![],
+            generation_history: vec![],
+            last_generation_stats: vec![],
+            cancellation: CancellationToken::new(),
+            fitness_cases: vec![],
+            generations_until_new_fitness_cases: 0,
+            last_checkpoint: Instant::now(),
+            hall_of_fame: HallOfFame::default(),
+            run_policy: None,
+        };
+        if let Some(archive) = world.config.subtree_archive.clone() {
+            world.genetic_engine.set_subtree_archive(archive);
+        }
+        if world.config.random_import {
+            world.add_function_import(RANDOM_IMPORT, || (host_call_log::next_random() >> 32) as u32)?;
+        }
+        Ok(world)
+    }
+
+    // Validates the configuration and returns the matching configuration for the GeneticEngine
+    fn check_configuration(config: &WorldConfiguration) -> Result<GeneticEngineConfiguration> {
+        if config.slot_count() > u8::MAX as usize {
+            return Err(WasmgpError::SlotCountTooLarge(config.slot_count()).into());
+        }
+        let total_slots = config.slot_count() as u8;
+
+        if config.individual_max_cost == Some(0) {
+            return Err(WasmgpError::InvalidConfiguration("individual_max_cost must be at least one".into()).into());
+        }
+        if config.initial_max_points == Some(0) {
+            return Err(WasmgpError::InvalidConfiguration("initial_max_points must be at least one".into()).into());
+        }
+        if let Initialization::RampedHalfAndHalf { min_points } = config.initialization {
+            if min_points == 0 || min_points > config.random_max_points() {
+                return Err(WasmgpError::InvalidConfiguration(
+                    "a RampedHalfAndHalf initialization must have between one and initial_max_points min_points".into(),
+                )
+                .into());
+            }
+        }
+        if config.epoch_ticks_per_ms == 0 || config.epoch_ticks_per_ms > 1000 {
+            return Err(
+                WasmgpError::InvalidConfiguration("epoch_ticks_per_ms must be between 1 and 1000".into()).into(),
+            );
+        }
+        if config.max_in_flight == 0 {
+            return Err(WasmgpError::InvalidConfiguration("max_in_flight must be at least one".into()).into());
+        }
+        if config.parent_pairing.candidates() == 0 {
+            return Err(
+                WasmgpError::InvalidConfiguration("parent_pairing must draw at least one candidate".into()).into(),
+            );
+        }
+        if config.breeding_threads == 0 {
+            return Err(WasmgpError::InvalidConfiguration("breeding_threads must be at least one".into()).into());
+        }
+        for (name, strategy) in [
+            ("select_for_migration", &config.select_for_migration),
+            ("select_as_parent", &config.select_as_parent),
+            ("select_as_elite", &config.select_as_elite),
+        ] {
+            if let SelectionStrategy::Tournament { size: 0 } = strategy {
+                return Err(WasmgpError::InvalidConfiguration(format!(
+                    "the tournament of {} must have at least one individual",
+                    name
+                ))
+                .into());
+            }
+        }
+        match config.threading_model {
+            ThreadingModel::None => {}
+            ThreadingModel::PerIsland(0) | ThreadingModel::PerIndividual(0) => {
+                return Err(WasmgpError::InvalidConfiguration(
+                    "the threading_model must have at least one worker".into(),
+                )
+                .into());
+            }
+            _ if cfg!(feature = "async") => {
+                return Err(WasmgpError::InvalidConfiguration(
+                    "the threading_model cannot be used with the async feature, which uses max_in_flight".into(),
+                )
+                .into());
+            }
+            _ => {}
+        }
+        if config.trace_host_calls && config.async_epoch_yielding {
+            return Err(WasmgpError::InvalidConfiguration(
+                "trace_host_calls cannot be combined with async_epoch_yielding".into(),
+            )
+            .into());
+        }
+        if config.host_call_budget.is_some() && config.async_epoch_yielding {
+            return Err(WasmgpError::InvalidConfiguration(
+                "host_call_budget cannot be combined with async_epoch_yielding".into(),
+            )
+            .into());
+        }
+        if config.fuel_per_evaluation == Some(0) {
+            return Err(WasmgpError::InvalidConfiguration("fuel_per_evaluation must not be zero".into()).into());
+        }
+        if let Some(bytes) = config.max_wasm_stack {
+            if bytes == 0 || (config.async_epoch_yielding && bytes >= 2 << 20) {
+                return Err(WasmgpError::InvalidConfiguration(
+                    "max_wasm_stack must not be zero, and must be less than 2 MiB with async_epoch_yielding".into(),
+                )
+                .into());
+            }
+        }
+        if config.max_turns == 0 {
+            return Err(WasmgpError::InvalidConfiguration("max_turns must be at least one".into()).into());
+        }
+        if let Some(slice_ms) = config.evaluation_slice_ms {
+            if slice_ms == 0 || !config.async_epoch_yielding {
+                return Err(WasmgpError::InvalidConfiguration(
+                    "evaluation_slice_ms must be at least one, and requires async_epoch_yielding".into(),
+                )
+                .into());
+            }
+        }
+        if let FitnessCaseSchedule::RandomSubset { size, generations } = config.fitness_case_schedule {
+            if size == 0 || size > config.fitness_cases || generations == 0 {
+                return Err(WasmgpError::InvalidConfiguration(
+                    "a RandomSubset must have between one and fitness_cases cases, redrawn at least every generation"
+                        .into(),
+                )
+                .into());
+            }
+        }
+        if let Some(adaptation) = config.code_weight_adaptation {
+            if !(adaptation.rate > 0.0 && adaptation.rate <= 1.0) || adaptation.floor == 0 {
+                return Err(WasmgpError::InvalidConfiguration(
+                    "code_weight_adaptation must have a rate above zero and up to one, and a floor of at least one"
+                        .into(),
+                )
+                .into());
+            }
+        }
+        if let Some(sizing) = config.island_sizing {
+            if sizing.every_generations == 0
+                || sizing.hall_of_fame_size == 0
+                || sizing.min_individuals == 0
+                || sizing.min_individuals > config.individuals_per_island
+            {
+                return Err(WasmgpError::InvalidConfiguration(
+                    "island_sizing must share out at least every generation from a hall of fame of at least one, with \
+                     between one and individuals_per_island min_individuals"
+                        .into(),
+                )
+                .into());
+            }
+        }
+        if let Some(schedule) = &config.checkpoints {
+            if schedule.keep == 0 || (schedule.every_generations == 0 && schedule.every_minutes == 0) {
+                return Err(WasmgpError::InvalidConfiguration(
+                    "checkpoints must keep at least one checkpoint, and be written by generation or by time".into(),
+                )
+                .into());
+            }
+        }
+        #[cfg(not(feature = "async"))]
+        if config.async_epoch_yielding {
+            return Err(
+                WasmgpError::InvalidConfiguration("async_epoch_yielding requires the 'async' feature".into()).into(),
+            );
+        }
+
+        let mut genetic_config = GeneticEngineConfiguration::new(config.seed, total_slots);
         genetic_config.individual_max_points = config.individual_max_points;
+        genetic_config.individual_max_cost = config.individual_max_cost;
+        genetic_config.cost_model = config.cost_model.clone();
         genetic_config.mutation_rate = config.mutation_rate;
         genetic_config.max_mutation_points = config.max_mutation_points;
         if genetic_config.mutation_rate > 0 && genetic_config.max_mutation_points == 0 {
@@ -69,25 +371,59 @@ impl<T: Default, R: RunResult> World<T, R> {
             )
             .into());
         }
+        genetic_config.archive_mutation_rate = config.archive_mutation_rate;
+        match &config.subtree_archive {
+            Some(archive) if archive.capacity() == 0 => {
+                return Err(WasmgpError::InvalidConfiguration(
+                    "the subtree_archive must have a capacity of at least one".into(),
+                )
+                .into());
+            }
+            None if config.archive_mutation_rate > 0 => {
+                return Err(WasmgpError::InvalidConfiguration(
+                    "must set a subtree_archive if archive_mutation_rate is greater than zero".into(),
+                )
+                .into());
+            }
+            None if config.migration_payload == MigrationPayload::Fragments => {
+                return Err(WasmgpError::InvalidConfiguration(
+                    "must set a subtree_archive to migrate fragments".into(),
+                )
+                .into());
+            }
+            _ => {}
+        }
+        if genetic_config.archive_mutation_rate > 0 && genetic_config.max_mutation_points == 0 {
+            return Err(WasmgpError::InvalidConfiguration(
+                "must set max_mutation_points if archive_mutation_rate is greater than zero".into(),
+            )
+            .into());
+        }
+        genetic_config.operand_mutation_rate = config.operand_mutation_rate;
+        if genetic_config.operand_mutation_rate > 0 && genetic_config.max_mutation_points == 0 {
+            return Err(WasmgpError::InvalidConfiguration(
+                "must set max_mutation_points if operand_mutation_rate is greater than zero".into(),
+            )
+            .into());
+        }
         genetic_config.crossover_rate = config.crossover_rate;
         genetic_config.max_crossover_points = config.max_crossover_points;
+        genetic_config.crossover_cuts = config.crossover_cuts.clone();
         if genetic_config.crossover_rate > 0 && genetic_config.max_crossover_points == 0 {
             return Err(WasmgpError::InvalidConfiguration(
                 "must set max_crossover_points if crossover_rate is greater than zero".into(),
             )
             .into());
         }
+        genetic_config.homologous_crossover_rate = config.homologous_crossover_rate;
+        if genetic_config.homologous_crossover_rate > 0 && genetic_config.max_crossover_points == 0 {
+            return Err(WasmgpError::InvalidConfiguration(
+                "must set max_crossover_points if homologous_crossover_rate is greater than zero".into(),
+            )
+            .into());
+        }
 
-        Ok(World {
-            config,
-            wasm_engine: engine,
-            genetic_engine: GeneticEngine::new(genetic_config),
-            linker: linker,
-            imported_functions: vec![],
-            module_builder: ModuleBuilder::new(),
-            islands: vec![],
-            generations_remaining_before_migration,
-        })
+        Ok(genetic_config)
     }
 
     /// Defines a named function that will be available to every individual
@@ -109,9 +445,55 @@ impl<T: Default, R: RunResult> World<T, R> {
         &mut self,
         name: &str,
         func: impl IntoFunc<T, Params, Args>,
-    ) -> Result<FunctionIndex> {
+    ) -> Result<FunctionIndex>
+    where
+        T: 'static,
+    {
+        self.add_function_import_with_options(name, func, ImportOptions::default())
+    }
+
+    /// Defines a named function that will be available to every individual, with options that apply while each
+    /// individual runs
+    /// ```
+    /// use wasmgp::*;
+    /// use wasmtime::*;
+    ///
+    /// fn draw_card(mut caller: Caller<'_, u64>) -> u64 {
+    ///     let drawn: &mut u64 = caller.data_mut();
+    ///     *drawn += 1;
+    ///     *drawn
+    /// }
+    ///
+    /// let config = WorldConfiguration::default();
+    /// let mut world = World::<u64, EmptyRunResult>::new(config).unwrap();
+    /// let options = ImportOptions {
+    ///     call_limit: Some(CallLimit::new(52, CallLimitPolicy::Trap)),
+    ///     ..Default::default()
+    /// };
+    /// world.add_function_import_with_options("draw_card", draw_card, options).unwrap();
+    /// ```
+    pub fn add_function_import_with_options<Params, Args>(
+        &mut self,
+        name: &str,
+        func: impl IntoFunc<T, Params, Args>,
+        options: ImportOptions,
+    ) -> Result<FunctionIndex>
+    where
+        T: 'static,
+    {
         // Add the function to the linker
-        self.linker.func_wrap(MODULE_NAME, name, func)?;
+        if options != ImportOptions::default() && self.config.async_epoch_yielding {
+            return Err(WasmgpError::InvalidConfiguration(
+                "import options cannot be combined with async_epoch_yielding".into(),
+            )
+            .into());
+        }
+        if self.config.trace_host_calls || self.config.host_call_budget.is_some() || options != ImportOptions::default()
+        {
+            self.add_shimmed_function(name, func, options)?;
+        } else {
+            self.linker.func_wrap(MODULE_NAME, name, func)?;
+        }
 
         // Get the type information about the function so that we know how to call it later
         let mut store = Store::new(&self.wasm_engine, T::default());
@@ -121,6 +503,7 @@ impl<T: Default, R: RunResult> World<T, R> {
             // Add the signature of the function to our import list and also to the module builder
             let signature = FunctionSignature::new_from_func_type(name, func_type);
             self.imported_functions.push(signature.clone());
+            self.import_options.push(options);
             let type_index = self.module_builder.add_function_type(signature.clone().into())?;
 
             // Define an import in the module for this function type
@@ -130,6 +513,7 @@ impl<T: Default, R: RunResult> World<T, R> {
                 type_index,
             );
             let function_index = self.module_builder.add_import(import)?;
+            self.emission_template = OnceLock::new();
 
             // Add this function to the weight table so that it may be randomly selected
             self.genetic_engine.set_host_call_weight(
@@ -217,6 +601,96 @@ impl<T: Default, R: RunResult> World<T, R> {
         Ok(())
     }
 
+    // Defines the function in a linker of its own, and then defines a function of the same type in the World's linker
+    // that counts, memoizes and traces each call before passing it through.
+    fn add_shimmed_function<Params, Args>(
+        &mut self,
+        name: &str,
+        func: impl IntoFunc<T, Params, Args>,
+        options: ImportOptions,
+    ) -> Result<()>
+    where
+        T: 'static,
+    {
+        let mut inner = Linker::new(&self.wasm_engine);
+        inner.func_wrap(MODULE_NAME, name, func)?;
+        let mut store = Store::new(&self.wasm_engine, T::default());
+        let func_type = match inner.get(&mut store, MODULE_NAME, name) {
+            Some(Extern::Func(f)) => f.ty(&store),
+            _ => return Ok(()),
+        };
+        let zeros: Vec<Val> = FunctionSignature::new_from_func_type(name, func_type.clone())
+            .results()
+            .iter()
+            .map(|r| SlotValue::zero(*r).to_val())
+            .collect();
+
+        let import_name = name.to_string();
+        let trace = self.config.trace_host_calls;
+        let budget = self.config.host_call_budget;
+        self.linker
+            .func_new(MODULE_NAME, name, func_type, move |mut caller, params, results| {
+                let within_limit = match options.call_limit {
+                    Some(limit) => host_call_log::count_call(&import_name, limit.max_calls),
+                    None => true,
+                };
+                let within_budget = match budget {
+                    Some(budget) => host_call_log::count_budgeted_call(budget.max_calls),
+                    None => true,
+                };
+
+                // The import's own limit takes precedence over the budget
+                let exceeded = if !within_limit {
+                    options
+                        .call_limit
+                        .map(|limit| (limit.policy, WasmgpError::CallLimitExceeded(import_name.clone())))
+                } else if !within_budget {
+                    budget.map(|budget| (budget.policy, WasmgpError::HostCallBudgetExceeded(budget.max_calls)))
+                } else {
+                    None
+                };
+                let memo = match options.memoize {
+                    true => host_call_log::memoized(&import_name, params),
+                    false => None,
+                };
+                let outcome = match exceeded {
+                    Some((CallLimitPolicy::Trap, err)) => Err(err.into()),
+                    Some((CallLimitPolicy::NoOp, _)) => {
+                        results.clone_from_slice(&zeros[..]);
+                        Ok(())
+                    }
+                    _ => match memo {
+                        Some(memo) => {
+                            results.clone_from_slice(&memo[..]);
+                            Ok(())
+                        }
+                        None => {
+                            let outcome = match inner.get(&mut caller, MODULE_NAME, &import_name) {
+                                Some(Extern::Func(f)) => f.call(&mut caller, params, results),
+                                _ => unreachable!("the shimmed function is always defined"),
+                            };
+                            if options.memoize && outcome.is_ok() {
+                                host_call_log::memoize(&import_name, params, results);
+                            }
+                            outcome
+                        }
+                    },
+                };
+                if trace {
+                    host_call_log::record(HostCallRecord {
+                        name: import_name.clone(),
+                        params: params.iter().map(SlotValue::from_val).collect(),
+                        results: match &outcome {
+                            Ok(()) => Ok(results.iter().map(SlotValue::from_val).collect()),
+                            Err(err) => Err(format!("{:#}", err)),
+                        },
+                    });
+                }
+                outcome
+            })?;
+        Ok(())
+    }
+
     fn get_extern_func_from_linker(&self, store: impl AsContextMut<Data = T>, name: &str) -> Option<Func> {
         if let Some(ext) = self.linker.get(store, MODULE_NAME, name) {
             match ext {
@@ -228,334 +702,5615 @@ impl<T: Default, R: RunResult> World<T, R> {
         }
     }
 
+    /// Returns the wasmtime Engine that the individuals of the World run on, so that it can be shared with another World
+    /// through `World::with_engine`
+    pub fn wasm_engine(&self) -> &Engine {
+        &self.wasm_engine
+    }
+
+    /// Creates a Store for the World's Engine, holding the `fuel_per_evaluation` when it is set
     pub fn store(&self, data: T) -> Store<T> {
-        Store::new(&self.wasm_engine, data)
+        let mut store = Store::new(&self.wasm_engine, data);
+        if let Some(fuel) = self.config.fuel_per_evaluation {
+            store.add_fuel(fuel).expect("the engine of the World consumes fuel");
+        }
+        store
     }
 
     /// Creates a wasmtime Instance for the specified Code
     pub fn instanciate(&mut self, store: impl AsContextMut<Data = T>, code: &[Code]) -> Result<Instance> {
-        let mut builder = self.module_builder.clone();
-        let context = CodeContext::new(
-            &self.config.main_entry_point,
-            self.config.work_slots.clone(),
-            self.config.is_signed,
-            self.config.work_slot_initialization,
-        )?;
-        context.build(&mut builder, &code[..], self.genetic_engine.rng())?;
-        let module_ast = builder.build();
-        let mut buffer = Vec::new();
-        wasm_ast::emit_binary(&module_ast, &mut buffer)?;
+        let buffer = self.wasm_binary(code)?;
         let module = wasmtime::Module::new(&self.wasm_engine, &buffer[..])?;
         self.linker.instantiate(store, &module)
     }
 
     /// Creates a wasmtime InstancePre for the specified Code
     pub fn instanciate_pre(&mut self, code: &[Code]) -> Result<InstancePre<T>> {
-        let mut builder = self.module_builder.clone();
-        let context = CodeContext::new(
-            &self.config.main_entry_point,
-            self.config.work_slots.clone(),
-            self.config.is_signed,
-            self.config.work_slot_initialization,
-        )?;
-        context.build(&mut builder, &code[..], self.genetic_engine.rng())?;
-        let module_ast = builder.build();
-        let mut buffer = Vec::new();
-        wasm_ast::emit_binary(&module_ast, &mut buffer)?;
-        let module = wasmtime::Module::new(&self.wasm_engine, &buffer[..])?;
-        self.linker.instantiate_pre(&module)
+        let compiler = CodeCompiler {
+            config: &self.config,
+            wasm_engine: &self.wasm_engine,
+            linker: &self.linker,
+            module_builder: &self.module_builder,
+            imports: &self.imported_functions,
+            emission_template: &self.emission_template,
+        };
+        let (instance_pre, _) = compiler.compile(code, self.genetic_engine.rng())?;
+        Ok(instance_pre)
     }
 
-    /// Returns a copy of the ModuleBuilder. This builder includes any imports that were previously defined with
-    /// `add_function_import`
-    pub fn module_builder(&self) -> ModuleBuilder {
-        self.module_builder.clone()
+    // Compiles the code, keeping the metadata of the module for the individual that will run it
+    fn compile(&mut self, code: &[Code]) -> Result<(InstancePre<T>, ModuleMetadata)> {
+        let compiler = CodeCompiler {
+            config: &self.config,
+            wasm_engine: &self.wasm_engine,
+            linker: &self.linker,
+            module_builder: &self.module_builder,
+            imports: &self.imported_functions,
+            emission_template: &self.emission_template,
+        };
+        compiler.compile(code, self.genetic_engine.rng())
     }
 
-    /// Adds a new island to the World that will use the specified callbacks to perform the various individual
-    /// processing tasks required during its lifetime
-    pub fn create_island(&mut self, callbacks: Box<dyn IslandCallbacks<T, R>>) -> IslandId {
-        let id = self.islands.len();
-        self.islands.push(Island::new(callbacks));
-
-        id
+    /// Compiles the Code into the binary of a Wasm module, such as to save a champion as a `.wasm` file. The module
+    /// exports the main entry point, and imports every function added to the World from the `MODULE_NAME` module, so
+    /// whatever runs it must supply functions with the same names and signatures.
+    /// ```
+    /// use wasmgp::*;
+    ///
+    /// let mut world = World::<(), EmptyRunResult>::new(WorldConfiguration::default()).unwrap();
+    /// let binary = world.wasm_binary(&[ConstOne::new(0)]).unwrap();
+    /// assert_eq!(b"\0asm", &binary[..4]);
+    /// ```
+    pub fn wasm_binary(&mut self, code: &[Code]) -> Result<Vec<u8>> {
+        let compiler = CodeCompiler {
+            config: &self.config,
+            wasm_engine: &self.wasm_engine,
+            linker: &self.linker,
+            module_builder: &self.module_builder,
+            imports: &self.imported_functions,
+            emission_template: &self.emission_template,
+        };
+        compiler.wasm_binary(code, self.genetic_engine.rng())
     }
 
-    /// Returns the total number of islands
-    pub fn get_number_of_islands(&self) -> usize {
-        self.islands.len()
+    /// Builds a single Wasm module that answers as the ensemble does. The module exports the main entry point, which
+    /// calls every member with its parameters and then returns the result of the `combiner`. The combiner is Code for a
+    /// function whose parameters are the results of the main entry point repeated once per member, in order, and whose
+    /// results are those of the main entry point. It has the world's work slots, like any other program. Each member
+    /// is also exported as `<main>_member_<index>`, and the combiner as `<main>_combiner`.
+    ///
+    /// The module imports the same host functions as the individuals. Worlds whose work slots are initialized with
+    /// `SlotInit::RandomPerCall` cannot build ensemble modules.
+    /// ```
+    /// use wasmgp::*;
+    ///
+    /// #[derive(Clone)]
+    /// struct DoNothing;
+    /// impl IslandCallbacks<(), EmptyRunResult> for DoNothing {
+    ///     fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+    ///         Box::new(Clone::clone(self))
+    ///     }
+    ///     fn run_individual(&mut self, _individual: &mut Individual<(), EmptyRunResult>) {}
+    /// }
+    ///
+    /// let config = WorldConfiguration {
+    ///     main_entry_point: FunctionSignature::new("main", vec![ValueType::I32, ValueType::I32], vec![ValueType::I32]),
+    ///     ..WorldConfiguration::default()
+    /// };
+    /// let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+    /// let mut members = vec![];
+    /// for code in [Add::new(0, 1, 2), Subtract::new(0, 1, 2)] {
+    ///     members.push(world.evaluate(&[code], &mut DoNothing).unwrap().0);
+    /// }
+    /// let ensemble = Ensemble::new(members);
+    ///
+    /// // The combiner takes the two answers as its parameters and returns their sum
+    /// let binary = world.ensemble_wasm_binary(&ensemble, &[Add::new(0, 1, 2)]).unwrap();
+    /// assert_eq!(b"\0asm", &binary[..4]);
+    /// ```
+    pub fn ensemble_wasm_binary(&mut self, ensemble: &Ensemble<T, R>, combiner: &[Code]) -> Result<Vec<u8>> {
+        let members: Vec<&[Code]> = ensemble.members().iter().map(|member| member.get_code()).collect();
+        let compiler = CodeCompiler {
+            config: &self.config,
+            wasm_engine: &self.wasm_engine,
+            linker: &self.linker,
+            module_builder: &self.module_builder,
+            imports: &self.imported_functions,
+            emission_template: &self.emission_template,
+        };
+        compiler.ensemble_wasm_binary(&members[..], combiner, self.genetic_engine.rng())
     }
 
-    /// Borrows an island by the specified ID
-    pub fn get_island(&self, id: IslandId) -> Option<&Island<T, R>> {
-        self.islands.get(id)
+    /// Builds a WASI command module from the code, so that a champion can be run outside of the World with any WASI
+    /// runtime, such as `wasmtime run champion.wasm 3 4`. The module exports `_start`, which reads one integer for each
+    /// parameter of the main entry point from the command line arguments, calls the code, and writes each result to
+    /// standard output in decimal on its own line. A missing argument is read as zero, and an argument is read up to
+    /// its first character that is not a digit. The module needs only `args_sizes_get`, `args_get` and `fd_write` from
+    /// `wasi_snapshot_preview1`.
+    ///
+    /// Only worlds whose main entry point has integer parameters and results can build commands, and the World must not
+    /// have imported functions, `numeric_telemetry` or work slots initialized with `SlotInit::RandomPerCall`, as there
+    /// is nothing to provide them outside of the World.
+    #[cfg(feature = "wasi")]
+    pub fn wasi_command_binary(&mut self, code: &[Code]) -> Result<Vec<u8>> {
+        let compiler = CodeCompiler {
+            config: &self.config,
+            wasm_engine: &self.wasm_engine,
+            linker: &self.linker,
+            module_builder: &self.module_builder,
+            imports: &self.imported_functions,
+            emission_template: &self.emission_template,
+        };
+        compiler.wasi_command_binary(code, self.genetic_engine.rng())
     }
 
-    /// Mutably borrows an island by the specified ID
-    pub fn get_island_mut(&mut self, id: IslandId) -> Option<&mut Island<T, R>> {
-        self.islands.get_mut(id)
+    /// Builds an individual from the code and runs it once through the `run_individual` of the callbacks, with the
+    /// world's imports and configuration, exactly as an island would run it. This is a quick way to check a
+    /// hand-written or saved program. The callbacks are not added to an island. When the world uses fitness cases, they
+    /// are first given the cases of the most recent generation, or every case if no generation has run.
+    ///
+    /// Returns the individual, which holds the run result, and its score from `score_individual`.
+    /// ```
+    /// use wasmgp::*;
+    ///
+    /// #[derive(Clone)]
+    /// struct ReturnsTheSum;
+    /// impl IslandCallbacks<(), EmptyRunResult> for ReturnsTheSum {
+    ///     fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+    ///         Box::new(Clone::clone(self))
+    ///     }
+    ///     fn run_individual(&mut self, individual: &mut Individual<(), EmptyRunResult>) {
+    ///         let ((), result) = individual.execute::<(u32, u32), u32>((), (2, 3));
+    ///         if result.unwrap() == 5 {
+    ///             individual.set_run_result(Some(EmptyRunResult {}));
+    ///         }
+    ///     }
+    ///     fn score_individual(&self, individual: &Individual<(), EmptyRunResult>) -> u64 {
+    ///         individual.get_run_result().map_or(0, |_| 1)
+    ///     }
+    /// }
+    ///
+    /// let config = WorldConfiguration {
+    ///     main_entry_point: FunctionSignature::new("main", vec![ValueType::I32, ValueType::I32], vec![ValueType::I32]),
+    ///     ..WorldConfiguration::default()
+    /// };
+    /// let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+    /// let (individual, score) = world.evaluate(&[Add::new(0, 1, 2)], &mut ReturnsTheSum).unwrap();
+    /// assert_eq!(1, score);
+    /// assert!(individual.get_run_result().is_some());
+    /// ```
+    pub fn evaluate(
+        &mut self,
+        code: &[Code],
+        callbacks: &mut dyn IslandCallbacks<T, R>,
+    ) -> Result<(Individual<T, R>, u64)> {
+        if self.config.fitness_cases > 0 {
+            if self.fitness_cases.is_empty() {
+                let cases: Vec<usize> = (0..self.config.fitness_cases).collect();
+                callbacks.use_fitness_cases(&cases[..]);
+            } else {
+                callbacks.use_fitness_cases(&self.fitness_cases[..]);
+            }
+        }
+        let mut individual = self.new_individual(code.to_vec())?;
+        callbacks.run_individual(&mut individual);
+        let score = callbacks.score_individual(&individual);
+        Ok((individual, score))
     }
 
-    /// Removes all individuals from all islands
-    pub fn reset_all_islands(&mut self) {
-        for island in self.islands.iter_mut() {
-            island.clear();
-        }
+    /// Shrinks the code with `reduce_code`, running each smaller candidate through `evaluate` with the callbacks. A
+    /// candidate is kept when `keep` accepts the individual and its score, such as when the score shows that a champion
+    /// still wins often enough. A candidate that cannot be built is not kept.
+    pub fn reduce_champion<K>(
+        &mut self,
+        code: &[Code],
+        callbacks: &mut dyn IslandCallbacks<T, R>,
+        mut keep: K,
+    ) -> Result<Vec<Code>>
+    where
+        K: FnMut(&Individual<T, R>, u64) -> bool,
+    {
+        reduce_code(code, |candidate| match self.evaluate(candidate, callbacks) {
+            Ok((individual, score)) => keep(&individual, score),
+            Err(_) => false,
+        })
     }
 
-    /// Runs the next generation across all islands.
-    #[cfg(not(feature = "async"))]
-    pub fn run_one_generation(&mut self) {
-        for island in self.islands.iter_mut() {
-            island.run_one_generation();
+    /// Runs each of the individuals `runs` times with a separate set of callbacks, and returns how each one did in the
+    /// same order. This scores final champions on seeds or cases that they never saw during evolution, which shows
+    /// whether they learned the problem or only the fitness cases of their islands.
+    ///
+    /// The runs are numbered from zero, and the callbacks are told the number of each run through `use_fitness_cases`
+    /// (with a single case) before `pre_generation_run`. The callbacks decide what each run number means, such as the
+    /// seed of a game from a range that the islands do not use. Every individual is run from the start of a full
+    /// evaluation, without the RunResult it had on its island, and the individuals passed in are not changed.
+    /// ```
+    /// use wasmgp::*;
+    ///
+    /// #[derive(Clone)]
+    /// struct DoNothing;
+    /// impl IslandCallbacks<(), EmptyRunResult> for DoNothing {
+    ///     fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+    ///         Box::new(Clone::clone(self))
+    ///     }
+    ///     fn run_individual(&mut self, _individual: &mut Individual<(), EmptyRunResult>) {}
+    /// }
+    ///
+    /// // The holdout scores each individual by the number of the run
+    /// #[derive(Clone)]
+    /// struct ScoreTheRun(u64);
+    /// impl IslandCallbacks<(), EmptyRunResult> for ScoreTheRun {
+    ///     fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+    ///         Box::new(Clone::clone(self))
+    ///     }
+    ///     fn use_fitness_cases(&mut self, cases: &[usize]) {
+    ///         self.0 = cases[0] as u64;
+    ///     }
+    ///     fn run_individual(&mut self, _individual: &mut Individual<(), EmptyRunResult>) {}
+    ///     fn score_individual(&self, _individual: &Individual<(), EmptyRunResult>) -> u64 {
+    ///         self.0
+    ///     }
+    /// }
+    ///
+    /// let config = WorldConfiguration {
+    ///     individuals_per_island: 5,
+    ///     ..WorldConfiguration::default()
+    /// };
+    /// let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+    /// world.create_island(Box::new(DoNothing));
+    /// world.fill_all_islands().unwrap();
+    /// let champion = world.get_island(0).unwrap().get_one_individual(4).unwrap().clone();
+    ///
+    /// let results = world.evaluate_on_holdout(&[champion], Box::new(ScoreTheRun(0)), 4);
+    /// assert_eq!(vec![0, 1, 2, 3], results[0].scores);
+    /// assert_eq!(1.5, results[0].mean_score());
+    /// ```
+    pub fn evaluate_on_holdout(
+        &self,
+        individuals: &[Individual<T, R>],
+        mut callbacks: Box<dyn IslandCallbacks<T, R>>,
+        runs: usize,
+    ) -> Vec<HoldoutResult<R>> {
+        let mut results: Vec<HoldoutResult<R>> = individuals
+            .iter()
+            .map(|individual| HoldoutResult {
+                individual_id: individual.get_id(),
+                run_results: vec![],
+                scores: vec![],
+            })
+            .collect();
+        let mut guests = individuals.to_vec();
+        for run in 0..runs {
+            callbacks.use_fitness_cases(&[run]);
+            for guest in guests.iter_mut() {
+                Self::prepare_guest(guest);
+            }
+            callbacks.pre_generation_run(&guests);
+            for guest in guests.iter_mut() {
+                guest.begin_evaluation();
+                callbacks.run_individual(guest);
+            }
+            callbacks.post_generation_run(&guests);
+            for (result, guest) in results.iter_mut().zip(guests.iter()) {
+                result.run_results.push(guest.get_run_result().cloned());
+                result.scores.push(callbacks.score_individual(guest));
+            }
         }
+        results
+    }
 
-        // See if it is time for a migration
-        if self.config.generations_between_migrations > 0 {
-            self.generations_remaining_before_migration -= 1;
-            if self.generations_remaining_before_migration == 0 {
+    /// Tunes the numeric constants of an individual without changing the shape of its code, and returns the most fit
+    /// variant found. Only the code of the team lead is tuned. This squeezes extra fitness out of a champion that structural search can no longer improve.
+    ///
+    /// The `objective` runs an individual and returns its fitness, where larger is better. It is called once for the
+    /// individual as given and then once per iteration. Each iteration nudges one constant up or down, and the change is
+    /// kept if it is more fit. A less fit change may also be kept while the search is young (simulated annealing), which
+    /// lets it climb out of a shallow local optimum: the temperature starts at one percent of the starting fitness and
+    /// cools to zero by the last iteration.
+    ///
+    /// The returned individual has a new ID unless no variant was more fit than the original, in which case it is a
+    /// clone of the original. Code without constants is returned unchanged.
+    /// ```
+    /// use wasmgp::*;
+    ///
+    /// struct Unscored;
+    /// impl IslandCallbacks<(), EmptyRunResult> for Unscored {
+    ///     fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+    ///         Box::new(Unscored)
+    ///     }
+    ///     fn run_individual(&mut self, _individual: &mut Individual<(), EmptyRunResult>) {}
+    /// }
+    ///
+    /// let config = WorldConfiguration::default();
+    /// let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+    /// world.reset_all_code_weights(0);
+    /// world.set_code_weight(Code::ConstI32(ConstI32::default()), 1);
+    /// world.create_island(Box::new(Unscored));
+    /// world.fill_all_islands().unwrap();
+    /// let champion = world.get_island(0).unwrap().get_one_individual(0).unwrap().clone();
+    ///
+    /// // Larger constants are more fit
+    /// let sum_of_constants = |individual: &mut Individual<(), EmptyRunResult>| -> f64 {
+    ///     let constants = individual.get_code().iter().map(|code| match code {
+    ///         Code::ConstI32(c) => c.value() as f64,
+    ///         _ => 0.0,
+    ///     });
+    ///     constants.sum()
+    /// };
+    /// let mut tuned = world.tune_constants(&champion, sum_of_constants, 50).unwrap();
+    /// assert_eq!(champion.get_code().len(), tuned.get_code().len());
+    /// assert!(sum_of_constants(&mut tuned) > sum_of_constants(&mut champion.clone()));
+    /// ```
+    pub fn tune_constants<F>(
+        &mut self,
+        individual: &Individual<T, R>,
+        mut objective: F,
+        iterations: usize,
+    ) -> Result<Individual<T, R>>
+    where
+        F: FnMut(&mut Individual<T, R>) -> f64,
+    {
+        let mut best = individual.clone();
+        let mut best_fitness = objective(&mut best);
+        let mut current_code = best.get_code().to_vec();
+        let mut current_fitness = best_fitness;
+        let starting_temperature = best_fitness.abs() * 0.01;
+        for iteration in 0..iterations {
+            let code = match local_search::tune_constant(&current_code[..], self.genetic_engine.rng()) {
+                Some(code) => code,
+                None => break,
+            };
+            let mut candidate = self.new_individual(code)?;
+            candidate.set_teammates(individual.teammates().to_vec());
+            let fitness = objective(&mut candidate);
+
+            let temperature = starting_temperature * (1.0 - iteration as f64 / iterations as f64);
+            let accept = fitness > current_fitness
+                || (temperature > 0.0
+                    && !fitness.is_nan()
+                    && self
+                        .genetic_engine
+                        .rng()
+                        .gen_bool(((fitness - current_fitness) / temperature).exp().clamp(0.0, 1.0)));
+            if accept {
+                current_code = candidate.get_code().to_vec();
+                current_fitness = fitness;
+            }
+            if fitness > best_fitness {
+                best_fitness = fitness;
+                best = candidate;
+            }
+        }
+
+        Ok(best)
+    }
+
+    /// Creates a `ReferenceEvaluator` for the main entry point of this World. Every function import is declared in the
+    /// order it was added, but calls to them return zeros until `ReferenceEvaluator::set_host_function` is used.
+    pub fn reference_evaluator<'a>(&self) -> Result<ReferenceEvaluator<'a>> {
+        let mut evaluator = ReferenceEvaluator::new(
+            &self.config.main_entry_point,
+            self.config.work_slots.clone(),
+            self.config.is_signed,
+            self.config.work_slot_initialization,
+        )?;
+        evaluator.set_conversion_profile(self.config.conversion_profile);
+        for signature in self.imported_functions.iter() {
+            evaluator.add_function_import(signature.clone());
+        }
+        Ok(evaluator)
+    }
+
+    /// Generates random Code with the World's GeneticEngine and runs it with random parameters, both as Wasm and with
+    /// the `evaluator`, reporting every run where the two disagree. The evaluator should come from
+    /// `reference_evaluator` and have a host function set for each import that behaves like the one in the World.
+    ///
+    /// Each Wasm run has the usual `individual_run_time_ms` to finish, and runs that time out (or run out of steps in
+    /// the evaluator) are counted as inconclusive rather than as divergences.
+    #[cfg(feature = "differential")]
+    pub fn differential_test(
+        &mut self,
+        evaluator: &mut ReferenceEvaluator,
+        options: &DifferentialOptions,
+    ) -> Result<DifferentialReport> {
+        use rand::rngs::SmallRng;
+        use rand::SeedableRng;
+
+        let mut rng = match options.seed {
+            Some(seed) => SmallRng::seed_from_u64(seed),
+            None => SmallRng::from_entropy(),
+        };
+        let mut report = DifferentialReport::default();
+        for _ in 0..options.programs {
+            let code = self.genetic_engine.random_code_list(options.max_points);
+            for _ in 0..options.inputs_per_program {
+                let params = differential::random_params(self.config.main_entry_point.params(), &mut rng);
+
+                // Both paths initialize random slots from the same seed
+                let slot_seed = rng.gen();
+                let reference = evaluator.evaluate(&code, &params, &mut SmallRng::seed_from_u64(slot_seed));
+                let wasm = self.run_differential_wasm(&code, &params, &mut SmallRng::seed_from_u64(slot_seed));
+                differential::compare(&mut report, &code, params, wasm, reference);
+            }
+        }
+
+        Ok(report)
+    }
+
+    #[cfg(feature = "differential")]
+    fn run_differential_wasm<Rn: Rng>(
+        &self,
+        code: &[Code],
+        params: &[SlotValue],
+        rng: &mut Rn,
+    ) -> Result<Vec<SlotValue>> {
+        let compiler = CodeCompiler {
+            config: &self.config,
+            wasm_engine: &self.wasm_engine,
+            linker: &self.linker,
+            module_builder: &self.module_builder,
+            imports: &self.imported_functions,
+            emission_template: &self.emission_template,
+        };
+        let buffer = compiler.wasm_binary(code, rng)?;
+        let module = wasmtime::Module::new(&self.wasm_engine, &buffer[..])?;
+
+        let mut store = self.store(T::default());
+        store.set_epoch_deadline(self.config.individual_run_time_ms * self.config.epoch_ticks_per_ms + 1);
+        let instance = self.linker.instantiate(&mut store, &module)?;
+        let func = instance
+            .get_func(&mut store, self.config.main_entry_point.name())
+            .ok_or(WasmgpError::InvalidConfiguration(
+                "the main entry point was not exported".into(),
+            ))?;
+        let params: Vec<wasmtime::Val> = params.iter().map(|p| p.to_val()).collect();
+        let mut results: Vec<wasmtime::Val> = self
+            .config
+            .main_entry_point
+            .results()
+            .iter()
+            .map(|r| SlotValue::zero(*r).to_val())
+            .collect();
+        // Slots randomized on every call are seeded from the rng, just as they are by the ReferenceEvaluator
+        let mut host_calls = host_call_log::HostCallLog {
+            random_state: rng.gen(),
+            ..Default::default()
+        };
+        host_call_log::with_log(&mut host_calls, || func.call(&mut store, &params[..], &mut results[..]))?;
+
+        Ok(results.iter().map(SlotValue::from_val).collect())
+    }
+
+    /// Returns a copy of the ModuleBuilder. This builder includes any imports that were previously defined with
+    /// `add_function_import`
+    pub fn module_builder(&self) -> ModuleBuilder {
+        self.module_builder.clone()
+    }
+
+    /// Adds a new island to the World that will use the specified callbacks to perform the various individual
+    /// processing tasks required during its lifetime
+    pub fn create_island(&mut self, callbacks: Box<dyn IslandCallbacks<T, R>>) -> IslandId {
+        let id = self.islands.len();
+        self.islands.push(Island::new(IslandFunctions::Sync(callbacks)));
+
+        id
+    }
+
+    /// Adds the host functions and islands of a domain to the World, and returns the IDs of the new islands. The
+    /// domain's entry point must match `WorldConfiguration::main_entry_point`, which `FitnessDomain::configure` sets.
+    pub fn attach_domain<D: FitnessDomain<T, R>>(&mut self, domain: D) -> Result<Vec<IslandId>> {
+        self.attach_domain_ref(&domain)
+    }
+
+    pub(crate) fn attach_domain_ref(&mut self, domain: &dyn FitnessDomain<T, R>) -> Result<Vec<IslandId>> {
+        if domain.entry_point() != self.config.main_entry_point {
+            return Err(WasmgpError::InvalidConfiguration(format!(
+                "the '{}' domain needs the entry point {:?}, but the world has {:?}",
+                domain.name(),
+                domain.entry_point(),
+                self.config.main_entry_point
+            ))
+            .into());
+        }
+        domain.add_imports(self)?;
+        Ok(domain
+            .create_islands()
+            .into_iter()
+            .map(|callbacks| self.create_island(callbacks))
+            .collect())
+    }
+
+    /// Adds a new island to the World that will await the specified async callbacks to perform the various individual
+    /// processing tasks required during its lifetime
+    #[cfg(feature = "async")]
+    pub fn create_async_island(&mut self, callbacks: Box<dyn AsyncIslandCallbacks<T, R>>) -> IslandId {
+        let id = self.islands.len();
+        self.islands.push(Island::new(IslandFunctions::Async(callbacks)));
+
+        id
+    }
+
+    /// Returns the total number of islands
+    pub fn get_number_of_islands(&self) -> usize {
+        self.islands.len()
+    }
+
+    /// Borrows an island by the specified ID
+    pub fn get_island(&self, id: IslandId) -> Option<&Island<T, R>> {
+        self.islands.get(id)
+    }
+
+    /// Reports how often each Code variant and host import is used by the current generation of an island and by its
+    /// `elite_individuals_per_generation` most fit individuals. The elites are only counted once the generation has
+    /// been run and sorted. Returns None if there is no island with the ID.
+    pub fn opcode_usage(&self, id: IslandId) -> Option<OpcodeUsage> {
+        let import_names: Vec<String> = self.imported_functions.iter().map(|f| f.name().clone()).collect();
+        self.islands
+            .get(id)
+            .map(|island| island.opcode_usage(self.config.elite_individuals_per_generation, &import_names[..]))
+    }
+
+    /// Mutably borrows an island by the specified ID
+    pub fn get_island_mut(&mut self, id: IslandId) -> Option<&mut Island<T, R>> {
+        self.islands.get_mut(id)
+    }
+
+    /// Removes all individuals from all islands
+    pub fn reset_all_islands(&mut self) {
+        for island in self.islands.iter_mut() {
+            island.clear();
+        }
+    }
+
+    /// Restarts every island with random individuals, keeping the `keep_top_n` most fit individuals of each island (see
+    /// `Island::reseed`). The champion listings, migration log and generation history are kept, as are the code
+    /// weights. Call this between generations, such as from the function passed to `run_generations_while` once the
+    /// islands have converged.
+    pub fn reseed_all_islands(&mut self, keep_top_n: usize) {
+        for island in self.islands.iter_mut() {
+            island.reseed(keep_top_n);
+        }
+    }
+
+    /// Switches a world that has already been running to a new configuration, such as when the problem gains an entry
+    /// point parameter or needs more work slots, so that the run can continue instead of starting over from random
+    /// code. The islands, imported functions and code weights are kept.
+    ///
+    /// Every individual in the current and future generations is rebuilt for the new configuration. If its code no
+    /// longer builds (for example because it uses a slot that no longer exists), the `adapter` is passed the code and
+    /// may return a repaired copy (for example by calling `Code::wrap_slots` on each item), or None to give up on it.
+    /// Individuals whose code still does not build are removed, as are teams with any such member. Individuals that are
+    /// carried over keep their IDs, but lose their run results.
+    /// Returns the number of individuals that were removed.
+    ///
+    /// The new configuration is checked in the same way as by `World::new`, and may not change `async_epoch_yielding`
+    /// or `epoch_ticks_per_ms`.
+    /// ```
+    /// use wasmgp::*;
+    ///
+    /// let mut world = World::<(), EmptyRunResult>::new(WorldConfiguration::default()).unwrap();
+    /// let config = WorldConfiguration {
+    ///     work_slots: SlotCount {
+    ///         i32: 4,
+    ///         i64: 0,
+    ///         f32: 0,
+    ///         f64: 0,
+    ///     },
+    ///     ..WorldConfiguration::default()
+    /// };
+    /// let removed = world.migrate_configuration(config, |_code| None).unwrap();
+    /// assert_eq!(0, removed);
+    /// ```
+    pub fn migrate_configuration<A>(&mut self, config: WorldConfiguration, mut adapter: A) -> Result<usize>
+    where
+        A: FnMut(&[Code]) -> Option<Vec<Code>>,
+    {
+        let genetic_config = Self::check_configuration(&config)?;
+        if config.async_epoch_yielding != self.config.async_epoch_yielding {
+            return Err(WasmgpError::InvalidConfiguration(
+                "async_epoch_yielding cannot be changed when migrating the configuration".into(),
+            )
+            .into());
+        }
+        if config.epoch_ticks_per_ms != self.config.epoch_ticks_per_ms {
+            return Err(WasmgpError::InvalidConfiguration(
+                "epoch_ticks_per_ms cannot be changed when migrating the configuration".into(),
+            )
+            .into());
+        }
+        if config.fuel_per_evaluation.is_some() != self.config.fuel_per_evaluation.is_some() {
+            return Err(WasmgpError::InvalidConfiguration(
+                "fuel_per_evaluation cannot be turned on or off when migrating the configuration".into(),
+            )
+            .into());
+        }
+        if config.max_wasm_stack != self.config.max_wasm_stack {
+            return Err(WasmgpError::InvalidConfiguration(
+                "max_wasm_stack cannot be changed when migrating the configuration".into(),
+            )
+            .into());
+        }
+        if config.random_import != self.config.random_import {
+            return Err(WasmgpError::InvalidConfiguration(
+                "random_import cannot be changed when migrating the configuration".into(),
+            )
+            .into());
+        }
+        if config.numeric_telemetry != self.config.numeric_telemetry {
+            return Err(WasmgpError::InvalidConfiguration(
+                "numeric_telemetry cannot be changed when migrating the configuration".into(),
+            )
+            .into());
+        }
+        self.generations_remaining_before_migration = self
+            .generations_remaining_before_migration
+            .min(config.generations_between_migrations);
+        self.fitness_cases.clear();
+        self.generations_until_new_fitness_cases = 0;
+        self.config = config;
+        self.emission_template = OnceLock::new();
+
+        // The shares of the individuals were for the old configuration, so every island starts over from it
+        self.hall_of_fame.clear();
+        for island in self.islands.iter_mut() {
+            island.set_target_size(None);
+        }
+        self.genetic_engine.reconfigure(genetic_config);
+
+        // The islands are moved out so that the world can build the replacement individuals
+        let mut islands = std::mem::take(&mut self.islands);
+        let mut removed = 0;
+        for island in islands.iter_mut() {
+            removed += island.rebuild_individuals(|individual| self.carry_over_individual(individual, &mut adapter));
+        }
+        self.islands = islands;
+
+        Ok(removed)
+    }
+
+    // Rebuilds an individual and its teammates for the current configuration. Returns None if any member cannot be
+    // rebuilt, even after the adapter has been applied.
+    fn carry_over_individual<A>(&mut self, individual: &Individual<T, R>, adapter: &mut A) -> Option<Individual<T, R>>
+    where
+        A: FnMut(&[Code]) -> Option<Vec<Code>>,
+    {
+        let mut lead = self.carry_over_member(individual, adapter)?;
+        let mut teammates = vec![];
+        for member in individual.teammates() {
+            teammates.push(self.carry_over_member(member, adapter)?);
+        }
+        lead.set_teammates(teammates);
+        Some(lead)
+    }
+
+    fn carry_over_member<A>(&mut self, member: &Individual<T, R>, adapter: &mut A) -> Option<Individual<T, R>>
+    where
+        A: FnMut(&[Code]) -> Option<Vec<Code>>,
+    {
+        let code = member.get_code().to_vec();
+        let (code, (instance_pre, module_metadata)) = match self.compile(&code[..]) {
+            Ok(compiled) => (code, compiled),
+            Err(_) => {
+                let code = adapter(&code[..])?;
+                let compiled = self.compile(&code[..]).ok()?;
+                (code, compiled)
+            }
+        };
+        let mut individual = Individual::new(
+            member.get_id(),
+            code,
+            self.config.main_entry_point.name().clone(),
+            instance_pre,
+            self.config.individual_run_time_ms,
+            self.config.epoch_ticks_per_ms,
+            self.config.evaluation_retries,
+        );
+        individual.set_time_slice(self.config.evaluation_slice_ms.unwrap_or(1));
+        individual.set_max_turns(self.config.max_turns);
+        individual.set_fuel(self.config.fuel_per_evaluation);
+        individual.set_module_metadata(module_metadata);
+        Some(individual)
+    }
+
+    /// Runs the next generation across all islands, evaluating every individual on the calling thread. The
+    /// configuration's `threading_model` is used by `run_one_generation_threaded` instead.
+    #[cfg(not(feature = "async"))]
+    pub fn run_one_generation(&mut self) {
+        self.run_generation(Self::run_islands);
+    }
+
+    /// Runs the next generation across all islands, on worker threads when the configuration has a `threading_model`.
+    /// The worker threads are why `T` and `R` must be `Send`.
+    #[cfg(not(feature = "async"))]
+    pub fn run_one_generation_threaded(&mut self)
+    where
+        T: Send,
+        R: Send,
+    {
+        self.run_generation(Self::run_islands_threaded);
+    }
+
+    // Runs a generation, in which `run_islands` evaluates the individuals of every island
+    #[cfg(not(feature = "async"))]
+    fn run_generation(&mut self, run_islands: fn(&mut Self)) {
+        self.choose_fitness_cases();
+        self.share_evaluation_context();
+        run_islands(self);
+        self.run_cross_evaluation();
+
+        // Improve on the most fit individuals, and then learn from them
+        self.run_local_search();
+        self.adapt_code_weights();
+        self.update_subtree_archive();
+        self.record_champion_listings();
+        self.resize_islands();
+
+        // See if it is time for a migration
+        if self.config.generations_between_migrations > 0 {
+            self.generations_remaining_before_migration -= 1;
+            if self.generations_remaining_before_migration == 0 {
+                self.migrate_individuals_between_islands();
+                self.generations_remaining_before_migration = self.config.generations_between_migrations;
+            }
+        }
+        self.record_generation_history();
+        self.generations_complete += 1;
+    }
+
+    /// Runs the next generation across all islands.
+    ///
+    /// Up to `max_in_flight` individuals of each island are evaluated at the same time, in time slices when the
+    /// configuration has an `evaluation_slice_ms`. If the world's cancellation token is cancelled part way through, no
+    /// further individuals are started and the generation is abandoned without sorting or migrating. Every island puts
+    /// the individuals of the abandoned generation back into its next generation, so once a new token is set with
+    /// `set_cancellation_token`, the next generation runs them again from the start.
+    #[cfg(feature = "async")]
+    pub async fn run_one_generation(&mut self) {
+        let generations_until_new_fitness_cases = self.generations_until_new_fitness_cases;
+        self.choose_fitness_cases();
+        self.share_evaluation_context();
+        let mut completed = true;
+        for island in self.islands.iter_mut() {
+            completed = match self.config.evaluation_slice_ms {
+                Some(_) => {
+                    island
+                        .run_one_generation_time_sliced(self.config.max_in_flight, &self.cancellation)
+                        .await
+                }
+                None => {
+                    island
+                        .run_one_generation_concurrently(self.config.max_in_flight, &self.cancellation)
+                        .await
+                }
+            };
+            if !completed {
+                break;
+            }
+        }
+        if !completed {
+            for island in self.islands.iter_mut() {
+                island.abandon_generation();
+            }
+            self.generations_until_new_fitness_cases = generations_until_new_fitness_cases;
+            return;
+        }
+        self.run_cross_evaluation().await;
+
+        // Improve on the most fit individuals, and then learn from them
+        self.run_local_search().await;
+        self.adapt_code_weights();
+        self.update_subtree_archive();
+        self.record_champion_listings();
+        self.resize_islands();
+
+        // See if it is time for a migration
+        if self.config.generations_between_migrations > 0 {
+            self.generations_remaining_before_migration -= 1;
+            if self.generations_remaining_before_migration == 0 {
                 self.migrate_individuals_between_islands();
                 self.generations_remaining_before_migration = self.config.generations_between_migrations;
             }
         }
+        self.record_generation_history();
+        self.generations_complete += 1;
+    }
+
+    // Evaluates the individuals of every island, one island after another on the calling thread
+    #[cfg(not(feature = "async"))]
+    fn run_islands(&mut self) {
+        for island in self.islands.iter_mut() {
+            island.run_one_generation();
+        }
+    }
+
+    // Evaluates the individuals of every island with the configuration's threading model. With
+    // `ThreadingModel::PerIsland` each worker takes the next island that has not been run until there are none left.
+    // Every individual makes its own Store, so the threads only share the Engine and the compiled modules.
+    #[cfg(not(feature = "async"))]
+    fn run_islands_threaded(&mut self)
+    where
+        T: Send,
+        R: Send,
+    {
+        match self.config.threading_model {
+            ThreadingModel::None => self.run_islands(),
+            ThreadingModel::PerIndividual(workers) => {
+                for island in self.islands.iter_mut() {
+                    island.run_one_generation_on_threads(workers);
+                }
+            }
+            ThreadingModel::PerIsland(workers) => {
+                let workers = workers.min(self.islands.len());
+                let queue = Mutex::new(self.islands.iter_mut());
+                let queue = &queue;
+                thread::scope(|scope| {
+                    for _ in 0..workers {
+                        scope.spawn(move || loop {
+                            let next = queue.lock().expect("an island thread panicked").next();
+                            match next {
+                                Some(island) => island.run_one_generation(),
+                                None => break,
+                            }
+                        });
+                    }
+                });
+            }
+        }
+    }
+
+    // Records the most fit individual of every island, when the configuration asks for it this generation
+    fn record_champion_listings(&mut self) {
+        let interval = self.config.champion_listing_interval;
+        if interval == 0 || self.generations_complete % interval != interval - 1 {
+            return;
+        }
+        let mut champions = self.champions(self.generations_complete);
+        self.champion_listings.append(&mut champions);
+    }
+
+    // Enters the champion of every island into the hall of fame, and at the end of each period of `island_sizing`
+    // shares out the individuals of all the islands by how many of the hall of fame each produced
+    fn resize_islands(&mut self) {
+        let sizing = match self.config.island_sizing {
+            Some(sizing) => sizing,
+            None => return,
+        };
+        for (id, island) in self.islands.iter().enumerate() {
+            if let Some(champion) = island.most_fit_individual() {
+                let score = island.score_for_individual(island.len() - 1).unwrap_or_default();
+                self.hall_of_fame
+                    .offer(id, champion.get_id(), score, sizing.hall_of_fame_size);
+            }
+        }
+        if (self.generations_complete + 1) % sizing.every_generations != 0 {
+            return;
+        }
+        let counts = self.hall_of_fame.counts(self.islands.len());
+        let sizes =
+            island_sizing::share_individuals(self.config.individuals_per_island, sizing.min_individuals, &counts);
+        for (island, size) in self.islands.iter_mut().zip(sizes) {
+            island.set_target_size(Some(size));
+        }
+        self.hall_of_fame.clear();
+    }
+
+    // The number of individuals the island is filled to
+    fn island_size(&self, id: IslandId) -> usize {
+        self.islands
+            .get(id)
+            .and_then(|island| island.target_size())
+            .unwrap_or(self.config.individuals_per_island)
+    }
+
+    // Lists the most fit individual of every island that has a sorted generation
+    fn champions(&self, generation: usize) -> Vec<ChampionListing> {
+        let mut champions = vec![];
+        for (id, island) in self.islands.iter().enumerate() {
+            if let Some(champion) = island.most_fit_individual() {
+                champions.push(ChampionListing {
+                    island: id,
+                    generation,
+                    individual_id: champion.get_id(),
+                    score: island.score_for_individual(island.len() - 1).unwrap_or_default(),
+                    listing: champion.get_code_string(),
+                });
+            }
+        }
+        champions
+    }
+
+    // Keeps the stats of every island for the generation that just completed
+    fn record_generation_history(&mut self) {
+        self.last_generation_stats.clear();
+        for (id, island) in self.islands.iter().enumerate() {
+            self.generation_history.push(GenerationRecord {
+                island: id,
+                generation: self.generations_complete,
+                stats: island.generation_stats().clone(),
+            });
+            self.last_generation_stats.push(island.generation_stats().clone());
+        }
+    }
+
+    // Has every island run the elites of the other islands and score them with its own callbacks
+    #[cfg(not(feature = "async"))]
+    fn run_cross_evaluation(&mut self) {
+        let elites = self.cross_evaluation_elites();
+        for id in 0..self.islands.len() {
+            let mut scores = vec![];
+            for (source, elites) in elites.iter().enumerate() {
+                let mut total = 0.0;
+                for elite in elites.iter() {
+                    let mut guest = elite.clone();
+                    if source != id {
+                        Self::prepare_guest(&mut guest);
+                        self.islands[id].evaluate_individual(&mut guest);
+                    }
+                    total += self.islands[id].score_individual(&guest) as f64;
+                }
+                scores.push(Self::mean_score(total, elites.len()));
+            }
+            self.islands[id].set_cross_evaluation(scores);
+        }
+    }
+
+    // Has every island run the elites of the other islands and score them with its own callbacks. Stops early if the
+    // world is cancelled.
+    #[cfg(feature = "async")]
+    async fn run_cross_evaluation(&mut self) {
+        let elites = self.cross_evaluation_elites();
+        for id in 0..self.islands.len() {
+            let mut scores = vec![];
+            for (source, elites) in elites.iter().enumerate() {
+                let mut total = 0.0;
+                for elite in elites.iter() {
+                    if self.cancellation.is_cancelled() {
+                        return;
+                    }
+                    let mut guest = elite.clone();
+                    if source != id {
+                        Self::prepare_guest(&mut guest);
+                        self.islands[id].evaluate_individual(&mut guest).await;
+                    }
+                    total += self.islands[id].score_individual(&guest) as f64;
+                }
+                scores.push(Self::mean_score(total, elites.len()));
+            }
+            self.islands[id].set_cross_evaluation(scores);
+        }
+    }
+
+    // Clones the individuals of every island that the others evaluate, or none if cross evaluation is turned off
+    fn cross_evaluation_elites(&self) -> Vec<Vec<Individual<T, R>>> {
+        let count = self.config.cross_evaluation_elites;
+        if count == 0 {
+            return vec![];
+        }
+        (0..self.islands.len())
+            .map(|id| self.clone_most_fit(id, count))
+            .collect()
+    }
+
+    // Forgets how an individual did on its home island, so that it is run from the start of a full evaluation
+    fn prepare_guest(guest: &mut Individual<T, R>) {
+        guest.set_run_result(None);
+        guest.begin_stage(0, None);
+    }
+
+    fn mean_score(total: f64, count: usize) -> Option<f64> {
+        if count == 0 {
+            None
+        } else {
+            Some(total / count as f64)
+        }
+    }
+
+    // Simplifies and tunes the constants of the most fit individuals of every island, and adds the variants that are at
+    // least as fit to the next generation
+    #[cfg(not(feature = "async"))]
+    fn run_local_search(&mut self) {
+        let search = match self.config.local_search {
+            Some(search) => search,
+            None => return,
+        };
+        for id in 0..self.islands.len() {
+            for parent in self.clone_most_fit(id, search.individuals) {
+                let mut best = parent.clone();
+                if let Some(mut candidate) = self.local_search_candidate(&best, simplify_code(best.get_code())) {
+                    self.islands[id].evaluate_individual(&mut candidate);
+                    if self.islands[id].compare_individuals(&candidate, &best) != Ordering::Less {
+                        best = candidate;
+                    }
+                }
+                for _ in 0..search.tuning_steps {
+                    let code = match local_search::tune_constant(best.get_code(), self.genetic_engine.rng()) {
+                        Some(code) => code,
+                        None => break,
+                    };
+                    if let Some(mut candidate) = self.local_search_candidate(&best, code) {
+                        self.islands[id].evaluate_individual(&mut candidate);
+                        if self.islands[id].compare_individuals(&candidate, &best) == Ordering::Greater {
+                            best = candidate;
+                        }
+                    }
+                }
+                if best.get_code() != parent.get_code() {
+                    self.islands[id].add_individual_to_future_generation(best);
+                }
+            }
+        }
+    }
+
+    // Simplifies and tunes the constants of the most fit individuals of every island, and adds the variants that are at
+    // least as fit to the next generation. Stops early if the world is cancelled.
+    #[cfg(feature = "async")]
+    async fn run_local_search(&mut self) {
+        let search = match self.config.local_search {
+            Some(search) => search,
+            None => return,
+        };
+        for id in 0..self.islands.len() {
+            for parent in self.clone_most_fit(id, search.individuals) {
+                if self.cancellation.is_cancelled() {
+                    return;
+                }
+                let mut best = parent.clone();
+                if let Some(mut candidate) = self.local_search_candidate(&best, simplify_code(best.get_code())) {
+                    self.islands[id].evaluate_individual(&mut candidate).await;
+                    if self.islands[id].compare_individuals(&candidate, &best) != Ordering::Less {
+                        best = candidate;
+                    }
+                }
+                for _ in 0..search.tuning_steps {
+                    let code = match local_search::tune_constant(best.get_code(), self.genetic_engine.rng()) {
+                        Some(code) => code,
+                        None => break,
+                    };
+                    if let Some(mut candidate) = self.local_search_candidate(&best, code) {
+                        self.islands[id].evaluate_individual(&mut candidate).await;
+                        if self.islands[id].compare_individuals(&candidate, &best) == Ordering::Greater {
+                            best = candidate;
+                        }
+                    }
+                }
+                if best.get_code() != parent.get_code() {
+                    self.islands[id].add_individual_to_future_generation(best);
+                }
+            }
+        }
+    }
+
+    // Clones the most fit individuals of a sorted island, most fit first. An unsorted island has none.
+    fn clone_most_fit(&self, id: IslandId, count: usize) -> Vec<Individual<T, R>> {
+        let island = &self.islands[id];
+        if island.most_fit_individual().is_none() {
+            return vec![];
+        }
+        (0..island.len())
+            .rev()
+            .take(count)
+            .filter_map(|index| island.get_one_individual(index).cloned())
+            .collect()
+    }
+
+    // Creates a new individual from the code of a local search step, which is evaluated in the same stage and with the
+    // same teammates as the individual it came from. Returns None if the code is unchanged or cannot be compiled.
+    fn local_search_candidate(&mut self, from: &Individual<T, R>, code: Vec<Code>) -> Option<Individual<T, R>> {
+        if code == from.get_code() {
+            return None;
+        }
+        let mut candidate = self.new_individual(code).ok()?;
+        candidate.set_teammates(from.teammates().to_vec());
+        candidate.begin_stage(from.get_evaluation_stage(), None);
+        Some(candidate)
+    }
+
+    // Moves the code weights toward the composition of the elites of every island, when the configuration asks for it
+    fn adapt_code_weights(&mut self) {
+        let adaptation = match self.config.code_weight_adaptation {
+            Some(adaptation) => adaptation,
+            None => return,
+        };
+        let import_names: Vec<String> = self.imported_functions.iter().map(|f| f.name().clone()).collect();
+        let mut elites = OpcodeCounts::default();
+        for island in self.islands.iter() {
+            let usage = island.opcode_usage(self.config.elite_individuals_per_generation, &import_names[..]);
+            elites.merge(&usage.elites);
+        }
+        let total = elites.total();
+        if total == 0 {
+            return;
+        }
+
+        let share = |code: &Code| -> f64 {
+            let count = match code {
+                Code::Call(call) => import_names
+                    .get(call.function_index() as usize)
+                    .and_then(|name| elites.host_imports.get(name)),
+                _ => elites.code.get(code.name()),
+            };
+            count.copied().unwrap_or(0) as f64 / total as f64
+        };
+        self.genetic_engine
+            .adapt_code_weights(share, adaptation.rate, adaptation.floor);
+    }
+
+    // Adds the subtrees of the elites of every island to the archive, when the configuration has one. When fragments
+    // migrate, each island adds its elites to its own archive instead.
+    fn update_subtree_archive(&mut self) {
+        let empty = match &self.config.subtree_archive {
+            Some(archive) => archive,
+            None => return,
+        };
+        if self.config.migration_payload == MigrationPayload::Fragments {
+            for island in self.islands.iter_mut() {
+                let mut archive = island.subtree_archive_or(empty).clone();
+                for elite in island.elites(self.config.elite_individuals_per_generation) {
+                    archive.add_subtrees(elite.get_code());
+                }
+                *island.subtree_archive_or(empty) = archive;
+            }
+            return;
+        }
+        let mut archive = self.genetic_engine.subtree_archive().clone();
+        for island in self.islands.iter() {
+            for elite in island.elites(self.config.elite_individuals_per_generation) {
+                archive.add_subtrees(elite.get_code());
+            }
+        }
+        self.genetic_engine.set_subtree_archive(archive);
+    }
+
+    // Has the GeneticEngine insert the subtrees of the island's own archive while it breeds the island, when fragments
+    // migrate
+    fn use_island_subtree_archive(&mut self, id: IslandId) {
+        if self.config.migration_payload != MigrationPayload::Fragments {
+            return;
+        }
+        let island_archive = self.islands.get(id).and_then(|island| island.subtree_archive());
+        if let Some(archive) = island_archive.or(self.config.subtree_archive.as_ref()) {
+            self.genetic_engine.set_subtree_archive(archive.clone());
+        }
+    }
+
+    /// Borrows the archive of subtrees taken from the elites, which is empty unless the configuration has a
+    /// `subtree_archive`. When `MigrationPayload::Fragments` migrate, each island has its own archive in
+    /// `Island::subtree_archive`, and this is the one used to breed the island that was filled last.
+    pub fn subtree_archive(&self) -> &SubtreeArchive {
+        self.genetic_engine.subtree_archive()
+    }
+
+    /// The indices of the fitness cases that the most recent generation was evaluated on. Empty until the first
+    /// generation runs, or if `WorldConfiguration::fitness_cases` is zero.
+    pub fn current_fitness_cases(&self) -> &[usize] {
+        &self.fitness_cases[..]
+    }
+
+    // Draws the fitness cases for the next generation when the schedule calls for it, and passes them to every island
+    fn choose_fitness_cases(&mut self) {
+        if self.config.fitness_cases == 0 {
+            return;
+        }
+        if self.generations_until_new_fitness_cases == 0 {
+            self.fitness_cases = match self.config.fitness_case_schedule {
+                FitnessCaseSchedule::All => (0..self.config.fitness_cases).collect(),
+                FitnessCaseSchedule::RandomSubset { size, generations } => {
+                    self.generations_until_new_fitness_cases = generations;
+                    let mut cases =
+                        rand::seq::index::sample(self.genetic_engine.rng(), self.config.fitness_cases, size).into_vec();
+                    cases.sort_unstable();
+                    cases
+                }
+            };
+        }
+        self.generations_until_new_fitness_cases = self.generations_until_new_fitness_cases.saturating_sub(1);
+        for island in self.islands.iter_mut() {
+            island.use_fitness_cases(&self.fitness_cases[..]);
+        }
+    }
+
+    // Passes the generation, its seed and the stats of the last generation to every island
+    fn share_evaluation_context(&mut self) {
+        let mut generation = self.generations_complete as u64;
+        let mut context = EvaluationContext {
+            generation: self.generations_complete,
+            island: 0,
+            seed: self.config.seed.unwrap_or_default() ^ host_call_log::splitmix64(&mut generation),
+            stats: self.last_generation_stats.clone(),
+        };
+        for (id, island) in self.islands.iter_mut().enumerate() {
+            context.island = id;
+            island.use_evaluation_context(&context);
+        }
+    }
+
+    /// Fills all islands with the children of the genetic algorithm, or with random individuals if there was no
+    /// previous generation from which to draw upon.
+    ///
+    /// When `breeding_threads` is greater than one, the children of each island are bred and compiled on that many
+    /// threads.
+    pub fn fill_all_islands(&mut self) -> Result<()> {
+        for id in 0..self.islands.len() {
+            self.use_island_subtree_archive(id);
+            if self.config.breeding_threads > 1 {
+                self.fill_island_in_parallel(id)?;
+                self.advance_island_generation(id);
+                continue;
+            }
+
+            let mut elite_remaining = self.config.elite_individuals_per_generation;
+            let mut elite_structures = HashSet::new();
+            let mut failures_in_a_row = 0;
+            while self.len_island_future_generation(id) < self.island_size(id) {
+                let island = self.islands.get(id).unwrap();
+                let pick_elite = if elite_remaining > 0 {
+                    elite_remaining -= 1;
+                    true
+                } else {
+                    false
+                };
+                let team_size = island.team_size();
+                let next = if island.len() == 0 {
+                    let breeding = Self::initial_breeding(&self.config, island.len_future_generation());
+                    let codes: Result<Vec<Vec<Code>>> = (0..team_size)
+                        .map(|_| {
+                            let (code, _) =
+                                breeding.breed(&mut self.genetic_engine, self.config.random_max_points())?;
+                            Ok(code)
+                        })
+                        .collect();
+                    codes.and_then(|codes| self.new_team(codes))
+                } else {
+                    // An elite whose code is already among the elites is skipped in favor of the next unique one. If
+                    // there is no unique one left, a child is bred in its place.
+                    let elite = if pick_elite {
+                        island.select_unique_elite(
+                            &self.config.select_as_elite,
+                            self.genetic_engine.rng(),
+                            &mut elite_structures,
+                        )
+                    } else {
+                        None
+                    };
+                    if let Some(elite) = elite {
+                        let mut elite_copy = elite.clone();
+                        elite_copy.set_origin(Some((Operator::Reproduction, island.score_individual(elite))));
+                        Ok(elite_copy)
+                    } else {
+                        let (plan, parents, parent_score) = Self::plan_team(
+                            island,
+                            Self::active_warm_up(&self.config, island),
+                            &self.config.select_as_parent,
+                            &self.config.parent_pairing,
+                            self.genetic_engine.rng(),
+                        );
+                        let bred: Result<Vec<(Vec<Code>, Option<Operator>)>> = plan
+                            .into_iter()
+                            .map(|breeding| breeding.breed(&mut self.genetic_engine, self.config.random_max_points()))
+                            .collect();
+                        bred.and_then(|bred| {
+                            let operator = bred[0].1;
+                            let mut team = self.new_team(bred.into_iter().map(|(code, _)| code).collect())?;
+                            team.set_parents(parents);
+                            team.set_origin(operator.map(|operator| (operator, parent_score)));
+                            Ok(team)
+                        })
+                    }
+                };
+                match next {
+                    Ok(next) => {
+                        failures_in_a_row = 0;
+                        self.add_individual_to_island_future_generation(id, next);
+                    }
+                    Err(error) => {
+                        failures_in_a_row += 1;
+                        self.record_failed_child(id, error, failures_in_a_row)?;
+                    }
+                }
+            }
+
+            // Now that the future generation is full, make it the current generation
+            self.advance_island_generation(id);
+        }
+        self.genetic_engine.clear_buffers();
+
+        Ok(())
+    }
+
+    // Chooses how the individual at `index` of the first generation of an island is generated
+    fn initial_breeding(config: &WorldConfiguration, index: usize) -> Breeding<'static> {
+        match config.initialization {
+            Initialization::Uniform => Breeding::Random,
+            initialization => {
+                match initialization.target(index, config.individuals_per_island, config.random_max_points()) {
+                    (points, true) => Breeding::Full(points),
+                    (points, false) => Breeding::Grow(points),
+                }
+            }
+        }
+    }
+
+    // Returns the warm-up the island is in, or WarmUp::None once it has been bred for all of the warm-up generations
+    fn active_warm_up(config: &WorldConfiguration, island: &Island<T, R>) -> WarmUp {
+        let warm_up = island.warm_up().unwrap_or(config.warm_up);
+        if island.generations() <= warm_up.generations() {
+            warm_up
+        } else {
+            WarmUp::None
+        }
+    }
+
+    // Chooses the parents of a child team from the island, and returns how each member is bred along with the IDs of
+    // the parents and the score of the most fit of them. Each member of the team is bred from the members in the same
+    // position of the parent teams, or from random code when a parent team is too small. The second parent is paired
+    // with the first according to the ParentPairing.
+    fn plan_team<'a, Rnd: Rng>(
+        island: &'a Island<T, R>,
+        warm_up: WarmUp,
+        strategy: &SelectionStrategy,
+        pairing: &ParentPairing,
+        rng: &mut Rnd,
+    ) -> (Vec<Breeding<'a>>, Vec<IndividualId>, u64) {
+        let team_size = island.team_size();
+        if let WarmUp::RandomImmigrants { .. } = warm_up {
+            return (vec![Breeding::Random; team_size], vec![], 0);
+        }
+
+        let first = island.select_one_index(strategy, rng).unwrap();
+        let left = island.get_one_individual(first).unwrap();
+        if let WarmUp::MutationOnly { .. } = warm_up {
+            let plan = (0..team_size)
+                .map(|member| match left.get_team_member(member) {
+                    Some(left) => Breeding::Mutation(left.get_code()),
+                    None => Breeding::Random,
+                })
+                .collect();
+            return (plan, vec![left.get_id()], island.score_individual(left));
+        }
+
+        let right = island
+            .get_one_individual(pairing.pick_mate(island, first, strategy, rng))
+            .unwrap();
+        let plan = (0..team_size)
+            .map(
+                |member| match (left.get_team_member(member), right.get_team_member(member)) {
+                    (Some(left), Some(right)) => Breeding::Child(left.get_code(), right.get_code()),
+                    _ => Breeding::Random,
+                },
+            )
+            .collect();
+        let parent_score = island.score_individual(left).max(island.score_individual(right));
+        (plan, vec![left.get_id(), right.get_id()], parent_score)
+    }
+
+    // Fills the future generation of one island the same way as `fill_all_islands`, except that the children are bred
+    // and compiled on `breeding_threads` threads. The elites and parents are chosen up front on this thread, and the
+    // new individuals get their IDs in order once every thread has finished.
+    fn fill_island_in_parallel(&mut self, id: IslandId) -> Result<()> {
+        // Children that fail to build leave the future generation short, so more are bred until it is full
+        let mut elite_remaining = self.config.elite_individuals_per_generation;
+        let mut failures_in_a_row = 0;
+        while self.len_island_future_generation(id) < self.island_size(id) {
+            failures_in_a_row = self.breed_island_in_parallel(id, elite_remaining, failures_in_a_row)?;
+            elite_remaining = 0;
+        }
+
+        Ok(())
+    }
+
+    // Breeds the children that the future generation of an island is short of, starting with up to `elite_count` elites.
+    // Returns the number of children in a row that have failed to build.
+    fn breed_island_in_parallel(
+        &mut self,
+        id: IslandId,
+        elite_count: usize,
+        mut failures_in_a_row: usize,
+    ) -> Result<usize> {
+        let needed = self
+            .island_size(id)
+            .saturating_sub(self.len_island_future_generation(id));
+        let island = self.islands.get(id).unwrap();
+        let team_size = island.team_size();
+
+        // Each plan has an entry for how every member of the team is bred
+        let warm_up = Self::active_warm_up(&self.config, island);
+        let mut elites = vec![];
+        let mut elite_structures = HashSet::new();
+        let mut plans: Vec<Vec<Breeding>> = vec![];
+        let mut lineages: Vec<(Vec<IndividualId>, u64)> = vec![];
+        for index in 0..needed {
+            let elite = if island.len() > 0 && index < elite_count {
+                island.select_unique_elite(
+                    &self.config.select_as_elite,
+                    self.genetic_engine.rng(),
+                    &mut elite_structures,
+                )
+            } else {
+                None
+            };
+            if island.len() == 0 {
+                let breeding = Self::initial_breeding(&self.config, island.len_future_generation() + index);
+                plans.push(vec![breeding; team_size]);
+                lineages.push((vec![], 0));
+            } else if let Some(elite) = elite {
+                let mut elite_copy = elite.clone();
+                elite_copy.set_origin(Some((Operator::Reproduction, island.score_individual(elite))));
+                elites.push(elite_copy);
+            } else {
+                let (plan, parents, parent_score) = Self::plan_team(
+                    island,
+                    warm_up,
+                    &self.config.select_as_parent,
+                    &self.config.parent_pairing,
+                    self.genetic_engine.rng(),
+                );
+                plans.push(plan);
+                lineages.push((parents, parent_score));
+            }
+        }
+
+        let compiler = CodeCompiler {
+            config: &self.config,
+            wasm_engine: &self.wasm_engine,
+            linker: &self.linker,
+            module_builder: &self.module_builder,
+            imports: &self.imported_functions,
+            emission_template: &self.emission_template,
+        };
+        let max_points = self.config.random_max_points();
+        let chunk_size = usize::max(1, plans.len().div_ceil(self.config.breeding_threads));
+        let mut engines: Vec<GeneticEngine> = plans.chunks(chunk_size).map(|_| self.genetic_engine.handle()).collect();
+        let bred = thread::scope(|scope| {
+            let workers: Vec<_> = plans
+                .chunks(chunk_size)
+                .zip(engines.iter_mut())
+                .map(|(chunk, engine)| {
+                    let compiler = &compiler;
+                    scope.spawn(move || -> Vec<Result<BredTeam<T>>> {
+                        let mut breed_member = |breeding: &Breeding| -> Result<BredMember<T>> {
+                            let (code, operator) = breeding.breed(engine, max_points)?;
+                            let (instance_pre, module_metadata) = compiler.compile(&code[..], engine.rng())?;
+                            Ok((code, instance_pre, module_metadata, operator))
+                        };
+                        chunk
+                            .iter()
+                            .map(|plan| plan.iter().map(&mut breed_member).collect())
+                            .collect()
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().expect("a breeding thread panicked"))
+                .collect::<Vec<_>>()
+        });
+        for engine in engines.iter_mut() {
+            let counts = engine.take_buffer_counts();
+            self.islands.get_mut(id).unwrap().record_breeding_buffers(counts);
+        }
+
+        for elite in elites {
+            self.add_individual_to_island_future_generation(id, elite);
+        }
+        for (members, (parents, parent_score)) in bred.into_iter().zip(lineages) {
+            match members {
+                Ok(members) => {
+                    failures_in_a_row = 0;
+                    let operator = members[0].3;
+                    let mut members: Vec<Individual<T, R>> = members
+                        .into_iter()
+                        .map(|(code, instance_pre, module_metadata, _)| {
+                            self.individual_from_instance_pre(code, instance_pre, module_metadata)
+                        })
+                        .collect();
+                    let mut lead = members.remove(0);
+                    lead.set_teammates(members);
+                    lead.set_parents(parents);
+                    lead.set_origin(operator.map(|operator| (operator, parent_score)));
+                    self.add_individual_to_island_future_generation(id, lead);
+                }
+                Err(error) => {
+                    failures_in_a_row += 1;
+                    self.record_failed_child(id, error, failures_in_a_row)?;
+                }
+            }
+        }
+
+        Ok(failures_in_a_row)
+    }
+
+    // Counts a child that could not be built in the stats of its island. Returns the error once more than
+    // `max_child_build_retries` children in a row have failed.
+    fn record_failed_child(&mut self, id: IslandId, error: anyhow::Error, failures_in_a_row: usize) -> Result<()> {
+        self.islands.get_mut(id).unwrap().record_failed_child(&error);
+        if failures_in_a_row > self.config.max_child_build_retries {
+            return Err(error.context(format!("children failed to build {} times in a row", failures_in_a_row)));
+        }
+        Ok(())
+    }
+
+    // Creates an individual with a new ID from the code
+    fn new_individual(&mut self, code: Vec<Code>) -> Result<Individual<T, R>> {
+        let (instance_pre, module_metadata) = self.compile(&code[..])?;
+        Ok(self.individual_from_instance_pre(code, instance_pre, module_metadata))
+    }
+
+    // Creates an individual with a new ID from code that has already been compiled
+    fn individual_from_instance_pre(
+        &mut self,
+        code: Vec<Code>,
+        instance_pre: InstancePre<T>,
+        module_metadata: ModuleMetadata,
+    ) -> Individual<T, R> {
+        let id = self.next_individual_id();
+        self.individual_with_id(id, code, instance_pre, module_metadata)
+    }
+
+    // Creates an individual with the ID from code that has already been compiled. The random values of the individual
+    // come from the ID.
+    fn individual_with_id(
+        &self,
+        id: IndividualId,
+        code: Vec<Code>,
+        instance_pre: InstancePre<T>,
+        module_metadata: ModuleMetadata,
+    ) -> Individual<T, R> {
+        let mut individual = Individual::new(
+            id,
+            code,
+            self.config.main_entry_point.name().clone(),
+            instance_pre,
+            self.config.individual_run_time_ms,
+            self.config.epoch_ticks_per_ms,
+            self.config.evaluation_retries,
+        );
+        individual.seed_random(self.config.seed.unwrap_or_default());
+        individual.set_time_slice(self.config.evaluation_slice_ms.unwrap_or(1));
+        individual.set_max_turns(self.config.max_turns);
+        individual.set_fuel(self.config.fuel_per_evaluation);
+        individual.set_module_metadata(module_metadata);
+        individual
+    }
+
+    // Creates a team with one member for each code. The first member leads the team.
+    fn new_team(&mut self, codes: Vec<Vec<Code>>) -> Result<Individual<T, R>> {
+        let mut members = vec![];
+        for code in codes {
+            members.push(self.new_individual(code)?);
+        }
+        let mut lead = members.remove(0);
+        lead.set_teammates(members);
+        Ok(lead)
+    }
+
+    // Rebuilds the teams from a checkpoint with the IDs their members had, so that their random values are the same as
+    // before, and with the results of their last evaluation when those were saved. Members from a checkpoint that has
+    // no IDs are given new ones.
+    fn restore_teams(
+        &mut self,
+        teams: Vec<CheckpointTeam>,
+        next_individual_id: &mut IndividualId,
+    ) -> Result<Vec<Individual<T, R>>> {
+        let mut restored = vec![];
+        for team in teams {
+            let mut individuals = vec![];
+            for (id, code) in team.members {
+                let (instance_pre, module_metadata) = self.compile(&code[..])?;
+                let id = id.unwrap_or_else(|| {
+                    let id = *next_individual_id;
+                    *next_individual_id += 1;
+                    id
+                });
+                individuals.push(self.individual_with_id(id, code, instance_pre, module_metadata));
+            }
+            let mut lead = individuals.remove(0);
+            lead.set_teammates(individuals);
+            if let Some(evaluation) = team.evaluation {
+                let run_result = match evaluation.run_result {
+                    Some(text) => Some(R::restore_result(&text).ok_or_else(|| {
+                        WasmgpError::InvalidCheckpoint(format!("the run result '{}' could not be read", text))
+                    })?),
+                    None => None,
+                };
+                lead.restore_evaluation(
+                    run_result,
+                    evaluation.stage,
+                    evaluation.skipped,
+                    evaluation.numeric_events,
+                );
+            }
+            restored.push(lead);
+        }
+        Ok(restored)
+    }
+
+    fn next_individual_id(&mut self) -> IndividualId {
+        let id = self.next_individual_id;
+        self.next_individual_id += 1;
+        id
+    }
+
+    fn len_island_future_generation(&self, id: IslandId) -> usize {
+        self.islands.get(id).unwrap().len_future_generation()
+    }
+
+    fn add_individual_to_island_future_generation(&mut self, id: IslandId, individual: Individual<T, R>) {
+        self.islands
+            .get_mut(id)
+            .unwrap()
+            .add_individual_to_future_generation(individual)
+    }
+
+    fn advance_island_generation(&mut self, id: IslandId) {
+        let island = self.islands.get_mut(id).unwrap();
+        island.record_breeding_buffers(self.genetic_engine.take_buffer_counts());
+        island.advance_generation()
+    }
+
+    /// Runs generations until the specified function returns false. The function is called after each generation and
+    /// may change the world before the next one, such as by calling `migrate_now`. Returns `WasmgpError::Cancelled` if
+    /// the world's cancellation token is cancelled before a generation starts. Any run policy is applied before the
+    /// function is called. Each generation is run with `run_one_generation`, on the calling thread.
+    #[cfg(not(feature = "async"))]
+    pub fn run_generations_while<While>(&mut self, while_fn: While) -> Result<()>
+    where
+        While: FnMut(&mut World<T, R>) -> bool,
+    {
+        self.run_generations(while_fn, Self::run_one_generation)
+    }
+
+    /// Runs generations in the same way as `run_generations_while`, except that each generation is run with
+    /// `run_one_generation_threaded`, on worker threads when the configuration has a `threading_model`.
+    #[cfg(not(feature = "async"))]
+    pub fn run_generations_while_threaded<While>(&mut self, while_fn: While) -> Result<()>
+    where
+        T: Send,
+        R: Send,
+        While: FnMut(&mut World<T, R>) -> bool,
+    {
+        self.run_generations(while_fn, Self::run_one_generation_threaded)
+    }
+
+    // Runs generations with `run_one` until `while_fn` returns false
+    #[cfg(not(feature = "async"))]
+    fn run_generations<While>(&mut self, mut while_fn: While, run_one: fn(&mut Self)) -> Result<()>
+    where
+        While: FnMut(&mut World<T, R>) -> bool,
+    {
+        // Always run at least one generation
+        let mut running = true;
+        while running {
+            if self.cancellation.is_cancelled() {
+                return Err(WasmgpError::Cancelled.into());
+            }
+            self.fill_all_islands()?;
+            run_one(self);
+            self.checkpoint_if_due()?;
+            self.apply_run_policy()?;
+            running = while_fn(self);
+        }
+
+        Ok(())
+    }
+
+    /// Runs generations until the specified function returns false. The function is called after each generation and
+    /// may change the world before the next one, such as by calling `migrate_now`. Returns `WasmgpError::Cancelled` if
+    /// the world's cancellation token is cancelled, which may happen from another task while a generation is running.
+    /// Any run policy is applied before the function is called.
+    #[cfg(feature = "async")]
+    pub async fn run_generations_while<While>(&mut self, mut while_fn: While) -> Result<()>
+    where
+        While: FnMut(&mut World<T, R>) -> bool,
+    {
+        // Always run at least one generation
+        let mut running = true;
+        while running {
+            if self.cancellation.is_cancelled() {
+                return Err(WasmgpError::Cancelled.into());
+            }
+            self.fill_all_islands()?;
+            self.run_one_generation().await;
+            if self.cancellation.is_cancelled() {
+                return Err(WasmgpError::Cancelled.into());
+            }
+            self.checkpoint_if_due()?;
+            self.apply_run_policy()?;
+            running = while_fn(self);
+        }
+
+        Ok(())
+    }
+
+    /// Captures the state of the World's random number generator, which chooses parents, breeds children, picks
+    /// migrants and draws fitness cases. Save it with a checkpoint and pass it to `restore_rng_state` after loading, so
+    /// that the resumed run makes the same random choices as this one does from here on. The generator is reseeded
+    /// from the returned state. The random values of each individual come from the World's seed and the individual's ID,
+    /// so they need no state of their own, but a `League` has its own generator, which island callbacks save with
+    /// `IslandCallbacks::save_state`.
+    pub fn capture_rng_state(&mut self) -> u64 {
+        self.genetic_engine.capture_rng_state()
+    }
+
+    /// Resumes the random choices of the World from a state returned by `capture_rng_state`
+    pub fn restore_rng_state(&mut self, state: u64) {
+        self.genetic_engine.restore_rng_state(state);
+    }
+
+    /// Creates `n` Worlds for independent trials of the same experiment. Each has the configuration, imports, code
+    /// weights and islands of this World, but its own stream of random numbers and its own seed, drawn from this
+    /// World's even when this World has no seed, so that the random values of individuals and the seeds of evaluation
+    /// contexts differ between trials. A seeded World forks the same trials every time. When `reseed` is true the forks
+    /// start with empty islands and a generation count of zero; otherwise each starts from a copy of the current
+    /// population. The history, logs and listings start out empty, the forks write no checkpoints, and each fork has a
+    /// cancellation token of its own, so that one trial can be stopped without the others. A reseeded fork also starts
+    /// its migration countdown over.
+    ///
+    /// `World::run_forks` runs the trials on threads of their own.
+    pub fn fork(&mut self, n: usize, reseed: bool) -> Vec<World<T, R>> {
+        (0..n)
+            .map(|_| {
+                let mut config = self.config.clone();
+                config.checkpoints = None;
+                config.seed = Some(self.genetic_engine.rng().gen());
+                let mut islands = self.islands.clone();
+                if reseed {
+                    for island in islands.iter_mut() {
+                        island.clear();
+                    }
+                }
+                World {
+                    config,
+                    wasm_engine: self.wasm_engine.clone(),
+                    genetic_engine: self.genetic_engine.handle(),
+                    linker: self.linker.clone(),
+                    imported_functions: self.imported_functions.clone(),
+                    import_options: self.import_options.clone(),
+                    module_builder: self.module_builder.clone(),
+                    emission_template: self.emission_template.clone(),
+                    islands,
+                    generations_remaining_before_migration: if reseed {
+                        self.config.generations_between_migrations
+                    } else {
+                        self.generations_remaining_before_migration
+                    },
+                    generations_complete: if reseed { 0 } else { self.generations_complete },
+                    next_individual_id: self.next_individual_id,
+                    migration_log: vec![],
+                    champion_listings: vec![],
+                    generation_history: vec![],
+                    last_generation_stats: vec![],
+                    cancellation: CancellationToken::new(),
+                    fitness_cases: self.fitness_cases.clone(),
+                    generations_until_new_fitness_cases: self.generations_until_new_fitness_cases,
+                    last_checkpoint: Instant::now(),
+                    hall_of_fame: if reseed {
+                        HallOfFame::default()
+                    } else {
+                        self.hall_of_fame.clone()
+                    },
+                    run_policy: self.run_policy.clone(),
+                }
+            })
+            .collect()
+    }
+
+    /// Runs every fork from `World::fork` on a thread of its own, each with `run_generations_while_threaded` and the
+    /// same `while_fn`, and returns the result of each fork in order. The forks keep their generation history, so the
+    /// stats of the trials can be gathered from them afterwards.
+    /// ```
+    /// use wasmgp::*;
+    ///
+    /// #[derive(Clone)]
+    /// struct DoNothing;
+    /// impl IslandCallbacks<(), EmptyRunResult> for DoNothing {
+    ///     fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+    ///         Box::new(Clone::clone(self))
+    ///     }
+    ///     fn run_individual(&mut self, _individual: &mut Individual<(), EmptyRunResult>) {}
+    /// }
+    ///
+    /// let config = WorldConfiguration {
+    ///     individuals_per_island: 5,
+    ///     seed: Some(7),
+    ///     ..WorldConfiguration::default()
+    /// };
+    /// let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+    /// world.create_island(Box::new(DoNothing));
+    ///
+    /// let mut trials = world.fork(3, true);
+    /// let results = World::run_forks(&mut trials, |trial| trial.generations_complete() < 2);
+    /// assert!(results.iter().all(|result| result.is_ok()));
+    /// assert!(trials.iter().all(|trial| trial.generation_history().len() == 2));
+    /// ```
+    #[cfg(not(feature = "async"))]
+    pub fn run_forks<While>(forks: &mut [World<T, R>], while_fn: While) -> Vec<Result<()>>
+    where
+        T: Send,
+        R: Send,
+        While: Fn(&mut World<T, R>) -> bool + Sync,
+    {
+        let while_fn = &while_fn;
+        thread::scope(|scope| {
+            let workers: Vec<_> = forks
+                .iter_mut()
+                .map(|world| scope.spawn(move || world.run_generations_while_threaded(while_fn)))
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().expect("a trial thread panicked"))
+                .collect()
+        })
+    }
+
+    /// Writes a checkpoint of the run to the file at `path`, from which `restore_checkpoint` continues the run just as
+    /// this world would. It holds:
+    /// - the number of generations complete, the state of the random number generator (see `capture_rng_state`) and
+    ///   the next individual ID
+    /// - the generations left until the next migration, and the fitness cases in use with the generations left until
+    ///   new ones are drawn
+    /// - the code weights, as adapted by `code_weight_adaptation`, the subtree archive and the hall of fame of
+    ///   `island_sizing`
+    /// - for every island, its generation count, difficulty level, target size and subtree archive, whatever its
+    ///   callbacks save with `IslandCallbacks::save_state`, and the ID and code of each of its individuals
+    ///
+    /// The sorted current generation of an island is saved along with the fitness of its individuals when every one of
+    /// their RunResults can be written by `RunResult::save_result`. Otherwise it is saved as part of the next
+    /// generation, to be run again. The file is first written next to `path` and then renamed, so a crash while
+    /// writing leaves any earlier file at `path` whole. Call this between generations;
+    /// `WorldConfiguration::checkpoints` writes checkpoints on a schedule instead.
+    pub fn write_checkpoint<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let mut islands = vec![];
+        for island in self.islands.iter_mut() {
+            let callback_state = island.save_callback_state();
+            let current: Vec<CheckpointTeam> = island.individuals().map(CheckpointTeam::from_individual).collect();
+            let future = island.future_individuals().map(CheckpointTeam::from_individual);
+            let (current, future) =
+                if island.most_fit_individual().is_some() && current.iter().all(|team| team.evaluation.is_some()) {
+                    (current, future.collect())
+                } else {
+                    (vec![], current.into_iter().chain(future).collect())
+                };
+            islands.push(CheckpointIsland {
+                difficulty_level: Some(island.difficulty_level()),
+                generations: Some(island.generations()),
+                target_size: island.target_size(),
+                subtree_archive: island.subtree_archive().map(|archive| archive.subtrees().to_vec()),
+                callback_numbers: callback_state.numbers(),
+                callback_teams: callback_state
+                    .individuals()
+                    .map(CheckpointTeam::from_individual)
+                    .collect(),
+                current,
+                future,
+            });
+        }
+        let checkpoint = Checkpoint {
+            instruction_set: Some(InstructionSet::current()),
+            generations_complete: self.generations_complete,
+            rng_state: self.capture_rng_state(),
+            next_individual_id: self.next_individual_id,
+            generations_remaining_before_migration: Some(self.generations_remaining_before_migration),
+            fitness_cases: Some(FitnessCaseCountdown {
+                generations_until_new_cases: self.generations_until_new_fitness_cases,
+                cases: self.fitness_cases.clone(),
+            }),
+            code_weight_levels: Some(self.genetic_engine.code_weight_levels()),
+            subtree_archive: self
+                .config
+                .subtree_archive
+                .as_ref()
+                .map(|_| self.genetic_engine.subtree_archive().subtrees().to_vec()),
+            hall_of_fame: self.hall_of_fame.entries().to_vec(),
+            islands,
+        };
+        let mut text = String::new();
+        checkpoint.write_text(&mut text)?;
+
+        let path = path.as_ref();
+        let partial = path.with_extension("partial");
+        std::fs::write(&partial, text)
+            .with_context(|| format!("failed to write the checkpoint to {}", partial.display()))?;
+        std::fs::rename(&partial, path)
+            .with_context(|| format!("failed to move the checkpoint to {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Resumes a run from a checkpoint written by `write_checkpoint`. The world must have the same configuration and
+    /// islands as the world that wrote it, since neither is saved. Everything that `write_checkpoint` lists is
+    /// restored. What the callbacks of each island saved is passed to `IslandCallbacks::restore_state` of a clone of
+    /// them, which replaces them once every island has been read. The individuals keep their IDs, and with them their
+    /// random values. An island whose current generation was saved with its fitness breeds the next generation from it
+    /// without running it again, so a seeded run continues exactly as it would have without stopping, as long as the
+    /// callbacks restore all of their state.
+    ///
+    /// The history, logs, listings and generation stats of the earlier run are not restored, so the
+    /// `EvaluationContext` of the first generation after resuming has no stats, and neither are the state of a
+    /// `RunPolicy` or any changes made to the configuration while the run went on. Checkpoints written by earlier
+    /// versions, which only saved the individuals, give every island those individuals as its next generation.
+    ///
+    /// A checkpoint written by a build with another `InstructionSet` is refused with
+    /// `WasmgpError::InstructionSetMismatch`, because its code could mean something else in this build. A checkpoint
+    /// that is refused for any reason, including by the callbacks, leaves the world as it was.
+    pub fn restore_checkpoint<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read the checkpoint at {}", path.display()))?;
+        let checkpoint = Checkpoint::from_text(&text)
+            .with_context(|| format!("failed to read the checkpoint at {}", path.display()))?;
+        if let Some(instruction_set) = checkpoint.instruction_set {
+            instruction_set.check()?;
+        }
+        if checkpoint.islands.len() != self.islands.len() {
+            return Err(WasmgpError::InvalidCheckpoint(format!(
+                "the checkpoint has {} islands but the world has {}",
+                checkpoint.islands.len(),
+                self.islands.len()
+            ))
+            .into());
+        }
+
+        if let Some(levels) = &checkpoint.code_weight_levels {
+            let table_size = self.genetic_engine.code_weight_levels().len();
+            if levels.len() != table_size {
+                return Err(WasmgpError::InvalidCheckpoint(format!(
+                    "the checkpoint has {} code weights but the world has {}",
+                    levels.len(),
+                    table_size
+                ))
+                .into());
+            }
+        }
+
+        // Read every island before changing any of them, so that a checkpoint found to be invalid part way through
+        // leaves the world as it was. Compiling the code draws random numbers, so the generator is put back as well.
+        let rng = self.genetic_engine.rng().clone();
+        let mut next_individual_id = checkpoint.next_individual_id;
+        let mut islands = vec![];
+        for (id, island) in checkpoint.islands.into_iter().enumerate() {
+            match self.read_checkpoint_island(id, island, &mut next_individual_id) {
+                Ok(restored) => islands.push(restored),
+                Err(err) => {
+                    *self.genetic_engine.rng() = rng;
+                    return Err(err);
+                }
+            }
+        }
+
+        if let Some(levels) = &checkpoint.code_weight_levels {
+            self.genetic_engine.set_code_weight_levels(levels);
+        }
+        self.next_individual_id = next_individual_id;
+        for (id, (island, current, future, functions)) in islands.into_iter().enumerate() {
+            let restored = &mut self.islands[id];
+            restored.restore_individuals(current, future, island.generations);
+            restored.set_functions(functions);
+            if let Some(level) = island.difficulty_level {
+                restored.set_difficulty_level(level);
+            }
+            restored.set_target_size(island.target_size);
+            if let (Some(subtrees), Some(empty)) = (island.subtree_archive, &self.config.subtree_archive) {
+                *restored.subtree_archive_or(empty) = empty.with_subtrees(subtrees);
+            }
+        }
+        if let (Some(subtrees), Some(empty)) = (checkpoint.subtree_archive, &self.config.subtree_archive) {
+            self.genetic_engine.set_subtree_archive(empty.with_subtrees(subtrees));
+        }
+        self.hall_of_fame = HallOfFame::from_entries(checkpoint.hall_of_fame);
+        self.generations_complete = checkpoint.generations_complete;
+        if let Some(generations) = checkpoint.generations_remaining_before_migration {
+            self.generations_remaining_before_migration = generations;
+        }
+        if let Some(countdown) = checkpoint.fitness_cases {
+            self.generations_until_new_fitness_cases = countdown.generations_until_new_cases;
+            self.fitness_cases = countdown.cases;
+        }
+        self.restore_rng_state(checkpoint.rng_state);
+        self.last_checkpoint = Instant::now();
+        Ok(())
+    }
+
+    // Compiles the teams of an island in a checkpoint, and restores the state its callbacks saved into a copy of the
+    // island's callbacks. The island itself is left as it is.
+    fn read_checkpoint_island(
+        &mut self,
+        id: IslandId,
+        mut island: CheckpointIsland,
+        next_individual_id: &mut IndividualId,
+    ) -> Result<RestoredIsland<T, R>> {
+        let current = self.restore_teams(std::mem::take(&mut island.current), next_individual_id)?;
+        let future = self.restore_teams(std::mem::take(&mut island.future), next_individual_id)?;
+        let callback_individuals =
+            self.restore_teams(std::mem::take(&mut island.callback_teams), next_individual_id)?;
+        let state = CallbackState::from_parts(std::mem::take(&mut island.callback_numbers), callback_individuals);
+        let functions = self.islands[id].restored_functions(state)?;
+        Ok((island, current, future, functions))
+    }
+
+    /// Creates a world with the configuration, adds an island for each of the callbacks, and resumes the run from the
+    /// checkpoint at `path`. The configuration and callbacks must match those of the world that wrote the checkpoint,
+    /// as neither is saved. See `restore_checkpoint` for what is restored.
+    pub fn resume<P: AsRef<Path>>(
+        path: P,
+        config: WorldConfiguration,
+        islands: Vec<Box<dyn IslandCallbacks<T, R>>>,
+    ) -> Result<World<T, R>>
+    where
+        T: 'static,
+    {
+        let mut world = World::new(config)?;
+        for callbacks in islands {
+            world.create_island(callbacks);
+        }
+        world.restore_checkpoint(path)?;
+        Ok(world)
+    }
+
+    // Writes a checkpoint when the `checkpoints` schedule says one is due, and then removes the ones it no longer keeps
+    fn checkpoint_if_due(&mut self) -> Result<()> {
+        let schedule = match &self.config.checkpoints {
+            Some(schedule) => schedule.clone(),
+            None => return Ok(()),
+        };
+        if !schedule.is_due(self.generations_complete, self.last_checkpoint.elapsed()) {
+            return Ok(());
+        }
+        std::fs::create_dir_all(&schedule.directory)
+            .with_context(|| format!("failed to create {}", schedule.directory.display()))?;
+        self.write_checkpoint(schedule.checkpoint_path(self.generations_complete))?;
+        self.last_checkpoint = Instant::now();
+        schedule.remove_old_checkpoints()
+    }
+
+    /// Returns a clone of the token that stops `run_generations_while`. Cancel it from any thread or task.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancellation.clone()
+    }
+
+    /// Replaces the cancellation token. Once a token has been cancelled it stays cancelled, so set a new token before
+    /// running more generations after a cancellation.
+    pub fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.cancellation = token;
+    }
+
+    /// Returns the number of generations that have been run so far
+    pub fn generations_complete(&self) -> usize {
+        self.generations_complete
+    }
+
+    /// Borrows the log of every migration that has taken place, in the order they occurred
+    pub fn migration_log(&self) -> &[MigrationEvent] {
+        &self.migration_log[..]
+    }
+
+    /// Returns the migrations that brought individuals to the specified island
+    pub fn migrations_to_island(&self, id: IslandId) -> impl Iterator<Item = &MigrationEvent> {
+        self.migration_log.iter().filter(move |event| event.destination == id)
+    }
+
+    /// Returns the migrations that took individuals away from the specified island
+    pub fn migrations_from_island(&self, id: IslandId) -> impl Iterator<Item = &MigrationEvent> {
+        self.migration_log.iter().filter(move |event| event.source == id)
+    }
+
+    /// Removes all entries from the migration log. Long runs may want to periodically save and clear the log.
+    pub fn clear_migration_log(&mut self) {
+        self.migration_log.clear();
+    }
+
+    /// Borrows the listings of the most fit individuals recorded every `champion_listing_interval` generations, in the
+    /// order they were recorded
+    pub fn champion_listings(&self) -> &[ChampionListing] {
+        &self.champion_listings[..]
+    }
+
+    /// Removes all of the recorded champion listings
+    pub fn clear_champion_listings(&mut self) {
+        self.champion_listings.clear();
+    }
+
+    /// Returns the mean scores from the latest cross evaluation, where the score of island `source`'s elites under the
+    /// callbacks of island `evaluator` is at `[evaluator][source]`. The diagonal holds each island's scores for its own
+    /// elites. Empty rows mean `cross_evaluation_elites` is zero or the generation has not been run.
+    pub fn cross_evaluation_matrix(&self) -> Vec<Vec<Option<f64>>> {
+        self.islands
+            .iter()
+            .map(|island| island.generation_stats().cross_evaluation.clone())
+            .collect()
+    }
+
+    /// Borrows the stats of every island for every generation that has been run, in the order they were run
+    pub fn generation_history(&self) -> &[GenerationRecord] {
+        &self.generation_history[..]
+    }
+
+    /// Removes all of the recorded generation stats. Long runs may want to periodically save and clear the history.
+    pub fn clear_generation_history(&mut self) {
+        self.generation_history.clear();
+    }
+
+    /// Writes a Markdown report of the run so far to the file at `path`: the configuration, the fitness and diversity of
+    /// every island in every generation, the migrations, the `champion_listings`, and the code of the current most fit
+    /// individual of every island. Clearing the generation history, migration log or champion listings leaves those
+    /// entries out of the report.
+    pub fn write_run_report<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let report = RunReport {
+            config: &self.config,
+            generations_complete: self.generations_complete,
+            history: &self.generation_history[..],
+            migrations: &self.migration_log[..],
+            timeline: &self.champion_listings[..],
+            champions: self.champions(self.generations_complete.saturating_sub(1)),
+        };
+        let mut markdown = String::new();
+        report.write_markdown(&mut markdown)?;
+        std::fs::write(path.as_ref(), markdown)
+            .with_context(|| format!("failed to write the run report to {}", path.as_ref().display()))?;
+        Ok(())
+    }
+
+    /// Describes the limits that the runtime places on every evaluation and the functions that evolved code can call,
+    /// for auditing a service that runs the code of untrusted individuals.
+    /// ```
+    /// use wasmgp::*;
+    ///
+    /// let mut world = World::<(), EmptyRunResult>::new(WorldConfiguration::default()).unwrap();
+    /// let options = ImportOptions {
+    ///     call_limit: Some(CallLimit::new(10, CallLimitPolicy::Trap)),
+    ///     ..Default::default()
+    /// };
+    /// world.add_function_import_with_options("half", |value: u32| value / 2, options).unwrap();
+    ///
+    /// let report = world.security_report();
+    /// assert_eq!(None, report.fuel_limit);
+    /// assert_eq!("half", report.imports[0].signature.name());
+    /// assert_eq!(Some(10), report.imports[0].call_limit.map(|limit| limit.max_calls));
+    /// ```
+    pub fn security_report(&self) -> SecurityReport {
+        let imports = self
+            .imported_functions
+            .iter()
+            .zip(self.import_options.iter())
+            .map(|(signature, options)| ImportReport {
+                signature: signature.clone(),
+                call_limit: options.call_limit,
+                memoize: options.memoize,
+            })
+            .collect();
+        let mut built_in_imports = vec![];
+        if self.config.work_slot_initialization == SlotInit::RandomPerCall {
+            built_in_imports.push(RANDOM_SLOT_IMPORT.to_string());
+        }
+        if self.config.numeric_telemetry {
+            for name in [
+                DIVISION_BY_ZERO_IMPORT,
+                CHECK_F32_CONVERSION_IMPORT,
+                CHECK_F64_CONVERSION_IMPORT,
+            ] {
+                built_in_imports.push(name.to_string());
+            }
+        }
+        SecurityReport {
+            run_time_ms: self.config.individual_run_time_ms,
+            epoch_ticks_per_ms: self.config.epoch_ticks_per_ms,
+            fuel_limit: self.config.fuel_per_evaluation,
+            max_wasm_stack: self.config.max_wasm_stack,
+            memory_bytes: 0,
+            async_epoch_yielding: self.config.async_epoch_yielding,
+            host_call_budget: self.config.host_call_budget,
+            imports,
+            built_in_imports,
+        }
+    }
+
+    /// Migrates individuals between the islands right away, using the `migration_algorithm` just as the automatic
+    /// migrations do. Drivers can call this from the function passed to `run_generations_while`, such as when the
+    /// islands have stopped improving. Set `generations_between_migrations` to zero to leave every migration to the
+    /// driver. The countdown to the next automatic migration is not changed.
+    pub fn migrate_now(&mut self) {
+        self.migrate_individuals_between_islands();
+    }
+
+    /// Changes how many generations pass between automatic migrations, starting the countdown over. Zero turns the
+    /// automatic migrations off, so that they only happen through `migrate_now`.
+    pub fn set_generations_between_migrations(&mut self, generations: usize) {
+        self.config.generations_between_migrations = generations;
+        self.generations_remaining_before_migration = generations;
+    }
+
+    /// Changes the `mutation_rate` used to breed the next generations. The configuration is checked in the same way as
+    /// by `World::new`.
+    pub fn set_mutation_rate(&mut self, rate: u8) -> Result<()> {
+        self.change_genetic_configuration(|config| config.mutation_rate = rate)
+    }
+
+    /// Changes the `crossover_rate` used to breed the next generations. The configuration is checked in the same way
+    /// as by `World::new`.
+    pub fn set_crossover_rate(&mut self, rate: u8) -> Result<()> {
+        self.change_genetic_configuration(|config| config.crossover_rate = rate)
+    }
+
+    // Changes a part of the configuration that only affects breeding, keeping the code weights and random numbers
+    fn change_genetic_configuration<F: FnOnce(&mut WorldConfiguration)>(&mut self, change: F) -> Result<()> {
+        let mut config = self.config.clone();
+        change(&mut config);
+        let genetic_config = Self::check_configuration(&config)?;
+        self.config = config;
+        self.emission_template = OnceLock::new();
+        self.genetic_engine.reconfigure(genetic_config);
+        Ok(())
+    }
+
+    /// Sets the policy that `run_generations_while` applies after every generation, or removes it with None
+    pub fn set_run_policy(&mut self, policy: Option<RunPolicy>) {
+        self.run_policy = policy;
+    }
+
+    /// Borrows the run policy, such as to see which of its rules have fired
+    pub fn run_policy(&self) -> Option<&RunPolicy> {
+        self.run_policy.as_ref()
+    }
+
+    // Applies the run policy to the generation that just completed. The policy is taken out of the world while its
+    // actions change the world.
+    fn apply_run_policy(&mut self) -> Result<()> {
+        if let Some(mut policy) = self.run_policy.take() {
+            let applied = policy.apply(self);
+            self.run_policy = Some(policy);
+            applied?;
+        }
+        Ok(())
+    }
+
+    fn migrate_individuals_between_islands(&mut self) {
+        let island_len = self.islands.len();
+
+        // It only makes sense to migrate if there are at least two islands
+        if island_len > 1 {
+            match self.config.migration_algorithm {
+                MigrationAlgorithm::Circular => self.migrate_all_islands_circular_n(1),
+                MigrationAlgorithm::Cyclical(n) => self.migrate_all_islands_circular_n(n),
+                MigrationAlgorithm::Incremental(n) => {
+                    self.migrate_all_islands_circular_n(n);
+
+                    // Increment 'n'. An 'n' of zero makes no sense, so when it gets there use '1' instead.
+                    let mut next_n = self.island_at_distance(0, n + 1);
+                    if next_n == 0 {
+                        next_n = 1
+                    }
+                    self.config.migration_algorithm = MigrationAlgorithm::Incremental(next_n);
+                }
+                MigrationAlgorithm::RandomCircular => {
+                    // Define a new order of islands and calculate the distance to the next island in this new order.
+                    // For example, if there are 7 islands and the order starts with 2, 3: the first distance is 1.
+                    // However if the order starts with 3, 2: the first distance is 6
+                    //
+                    // This algorithm achieves the desired goal of having individuals from each island migrate together
+                    // to another random island, and each island is the source and destination exactly once.
+                    let island_order = self.random_island_order();
+                    let distances = World::<T, R>::distances_to_next_island(&island_order[..]);
+                    for (source_id, n) in std::iter::zip(island_order, distances) {
+                        self.migrate_one_island_circular_n(source_id, n);
+                    }
+                }
+                MigrationAlgorithm::CompletelyRandom => {
+                    let len = self.islands.len();
+
+                    // For each migrating individual on each island, pick a random destination that is not the same
+                    // island and migrate there.
+                    for source_island_id in 0..len {
+                        for _ in 0..self.number_of_individuals_migrating_from(source_island_id) {
+                            let destination_island_id = World::<T, R>::random_destination_island(
+                                source_island_id,
+                                len,
+                                self.genetic_engine.rng(),
+                            );
+                            self.migrate_one_individual_from_island_to_island(source_island_id, destination_island_id);
+                        }
+                    }
+                }
+                MigrationAlgorithm::BroadcastBest => {
+                    for source_island_id in 0..island_len {
+                        for distance in 1..island_len {
+                            let destination_island_id = self.island_at_distance(source_island_id, distance);
+                            self.migrate_best_individual_from_island_to_island(source_island_id, destination_island_id);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn migrate_one_individual_from_island_to_island(
+        &mut self,
+        source_island_id: IslandId,
+        destination_island_id: IslandId,
+    ) {
+        if self.config.migration_payload == MigrationPayload::Fragments {
+            self.migrate_fragment_from_island_to_island(source_island_id, destination_island_id);
+            return;
+        }
+        let strategy = self.config.select_for_migration.clone();
+        let clone = self.clone_individuals_migrating_from(source_island_id);
+
+        // Get the migrating individual from the source island. An island that was asked to send more individuals than
+        // it has stops sending once it is empty.
+        let source_island = self.islands.get_mut(source_island_id).unwrap();
+        let selected = if clone {
+            source_island
+                .select_one_individual(strategy, self.genetic_engine.rng())
+                .cloned()
+        } else {
+            source_island.select_and_remove_one_individual(strategy, self.genetic_engine.rng())
+        };
+        let mut migrating: Individual<T, R> = match selected {
+            Some(individual) => individual,
+            None => return,
+        };
+        source_island.record_emigrant();
+        self.migration_log.push(MigrationEvent {
+            source: source_island_id,
+            destination: destination_island_id,
+            individual_id: migrating.get_id(),
+            generation: self.generations_complete,
+        });
+
+        // Add it to the destination island
+        let destination_island = self.islands.get_mut(destination_island_id).unwrap();
+        destination_island.record_immigrant();
+        migrating.set_origin(None);
+        destination_island.add_individual_to_future_generation(migrating);
+    }
+
+    // The number of individuals leaving the island in each migration, taking any per-island override into account
+    fn number_of_individuals_migrating_from(&self, island_id: IslandId) -> usize {
+        self.islands
+            .get(island_id)
+            .and_then(|island| island.number_of_individuals_migrating())
+            .unwrap_or(self.config.number_of_individuals_migrating)
+    }
+
+    // Whether individuals leaving the island are cloned, taking any per-island override into account
+    fn clone_individuals_migrating_from(&self, island_id: IslandId) -> bool {
+        self.islands
+            .get(island_id)
+            .and_then(|island| island.clone_migrated_individuals())
+            .unwrap_or(self.config.clone_migrated_individuals)
+    }
+
+    // Clones the most fit individual of the source island into the future generation of the destination island
+    fn migrate_best_individual_from_island_to_island(
+        &mut self,
+        source_island_id: IslandId,
+        destination_island_id: IslandId,
+    ) {
+        if self.config.migration_payload == MigrationPayload::Fragments {
+            self.migrate_fragment_from_island_to_island(source_island_id, destination_island_id);
+            return;
+        }
+        let source_island = self.islands.get_mut(source_island_id).unwrap();
+        let mut migrating = match source_island.most_fit_individual() {
+            Some(best) => best.clone(),
+            None => return,
+        };
+        source_island.record_emigrant();
+        self.migration_log.push(MigrationEvent {
+            source: source_island_id,
+            destination: destination_island_id,
+            individual_id: migrating.get_id(),
+            generation: self.generations_complete,
+        });
+
+        let destination_island = self.islands.get_mut(destination_island_id).unwrap();
+        destination_island.record_immigrant();
+        migrating.set_origin(None);
+        destination_island.add_individual_to_future_generation(migrating);
+    }
+
+    // Copies a random subtree from the archive of the source island into the archive of the destination island. Nothing
+    // is sent by an island whose archive is still empty.
+    fn migrate_fragment_from_island_to_island(&mut self, source_island_id: IslandId, destination_island_id: IslandId) {
+        let fragment = match self.islands.get(source_island_id).unwrap().subtree_archive() {
+            Some(archive) => archive.pick(self.genetic_engine.rng(), usize::MAX),
+            None => None,
+        };
+        if let (Some(fragment), Some(empty)) = (fragment, self.config.subtree_archive.as_ref()) {
+            let destination_island = self.islands.get_mut(destination_island_id).unwrap();
+            destination_island.subtree_archive_or(empty).add_subtrees(&[fragment]);
+        }
+    }
+
+    // Calculates the ID of the island at a specific distance from the source. Wraps around when we get to the end of
+    // the list.
+    fn island_at_distance(&self, source_id: IslandId, distance: usize) -> IslandId {
+        (source_id + distance) % self.islands.len()
+    }
+
+    fn migrate_all_islands_circular_n(&mut self, n: usize) {
+        for source_island_id in 0..self.islands.len() {
+            self.migrate_one_island_circular_n(source_island_id, n);
+        }
+    }
+
+    fn migrate_one_island_circular_n(&mut self, source_island_id: IslandId, n: usize) {
+        let destination_island_id = self.island_at_distance(source_island_id, n);
+        for _ in 0..self.number_of_individuals_migrating_from(source_island_id) {
+            self.migrate_one_individual_from_island_to_island(source_island_id, destination_island_id);
+        }
+    }
+
+    // Picks a random island that is not the source island. There must be at least two islands. Picking from one fewer
+    // island and skipping over the source keeps every other island equally likely without needing to retry.
+    fn random_destination_island<Rnd: Rng>(source_id: IslandId, len: usize, rng: &mut Rnd) -> IslandId {
+        let destination_id = rng.gen_range(0..len - 1);
+        if destination_id >= source_id {
+            destination_id + 1
+        } else {
+            destination_id
+        }
+    }
+
+    // Creates a Vec containing the source_id of each island exactly one time
+    fn random_island_order(&mut self) -> Vec<IslandId> {
+        let mut island_ids: Vec<IslandId> = (0..self.islands.len()).collect();
+        island_ids.shuffle(self.genetic_engine.rng());
+
+        island_ids
+    }
+
+    // Creates a Vec containing the distance to the previous island in the list for every entry in the parameter. The
+    // distance for the first entry wraps around to the last item.
+    fn distances_to_next_island(island_id: &[IslandId]) -> Vec<IslandId> {
+        let len = island_id.len();
+        let mut distances = Vec::with_capacity(len);
+        let mut previous_source_id = island_id.last().unwrap();
+        for source_id in island_id.iter() {
+            let distance = ((previous_source_id + len) - source_id) % len;
+            distances.push(distance);
+            previous_source_id = source_id;
+        }
+
+        distances
+    }
+}
+
+// How one member of a child team is bred, with the code of its parents
+#[derive(Clone, Copy)]
+enum Breeding<'a> {
+    Random,
+    Full(usize),
+    Grow(usize),
+    Mutation(&'a [Code]),
+    Child(&'a [Code], &'a [Code]),
+}
+
+impl<'a> Breeding<'a> {
+    // Random code has up to `max_points` points. Bred code is limited by the configuration of the engine. Returns the
+    // operator that bred the code, or None for random code.
+    fn breed(self, engine: &mut GeneticEngine, max_points: usize) -> Result<(Vec<Code>, Option<Operator>)> {
+        match self {
+            Breeding::Random => Ok((engine.random_code_list(max_points), None)),
+            Breeding::Full(points) => Ok((engine.full_code_list(points), None)),
+            Breeding::Grow(points) => Ok((engine.random_code_list(points), None)),
+            Breeding::Mutation(parent) => Ok((engine.rand_mutation(parent)?, Some(Operator::Mutation))),
+            Breeding::Child(left, right) => {
+                let operation = engine.select_genetic_operation();
+                let code = engine.apply_genetic_operation(&operation, left, right)?;
+                Ok((code, Some(operation.operator())))
+            }
+        }
+    }
+}
+
+// An island read from a checkpoint: the rest of the checkpoint once its teams have been compiled into the current and
+// future generations, and a copy of the island's callbacks holding the state they saved
+type RestoredIsland<T, R> = (
+    CheckpointIsland,
+    Vec<Individual<T, R>>,
+    Vec<Individual<T, R>>,
+    IslandFunctions<T, R>,
+);
+
+// The code, compiled module, module metadata and operator of one member of a team bred by `fill_island_in_parallel`
+type BredMember<T> = (Vec<Code>, InstancePre<T>, ModuleMetadata, Option<Operator>);
+type BredTeam<T> = Vec<BredMember<T>>;
+
+// The parts of the world needed to compile code, borrowed separately from the genetic engine so that several threads
+// can compile at the same time
+struct CodeCompiler<'a, T> {
+    config: &'a WorldConfiguration,
+    wasm_engine: &'a Engine,
+    linker: &'a Linker<T>,
+    module_builder: &'a ModuleBuilder,
+    imports: &'a [FunctionSignature],
+    emission_template: &'a OnceLock<Option<EmissionTemplate>>,
+}
+
+impl<'a, T> CodeCompiler<'a, T> {
+    fn compile<Rnd: Rng>(&self, code: &[Code], rng: &mut Rnd) -> Result<(InstancePre<T>, ModuleMetadata)> {
+        let (buffer, local_count) = self.emit(code, rng)?;
+        let module = wasmtime::Module::new(self.wasm_engine, &buffer[..])?;
+        let instance_pre = self.linker.instantiate_pre(&module)?;
+        Ok((instance_pre, ModuleMetadata::new(buffer.len(), local_count, code)))
+    }
+
+    // Builds one module with a function for each member, the combiner, and a main function that calls the members and
+    // passes all of their results to the combiner
+    fn ensemble_wasm_binary<Rnd: Rng>(&self, members: &[&[Code]], combiner: &[Code], rng: &mut Rnd) -> Result<Vec<u8>> {
+        if members.is_empty() {
+            return Err(WasmgpError::EmptyEnsemble.into());
+        }
+        if self.config.work_slot_initialization == SlotInit::RandomPerCall && self.config.work_slots.len() > 0 {
+            // Each function would add its own random import after the functions before it, moving their indices
+            return Err(WasmgpError::InvalidConfiguration(
+                "an ensemble module cannot use SlotInit::RandomPerCall".into(),
+            )
+            .into());
+        }
+        if self.config.numeric_telemetry {
+            // The telemetry imports have the same problem
+            return Err(
+                WasmgpError::InvalidConfiguration("an ensemble module cannot use numeric_telemetry".into()).into(),
+            );
+        }
+
+        let main = &self.config.main_entry_point;
+        let mut builder = self.module_builder.clone();
+        let mut member_indices = vec![];
+        for (index, code) in members.iter().enumerate() {
+            let signature = FunctionSignature::new(
+                format!("{}_member_{}", main.name(), index),
+                main.params().to_vec(),
+                main.results().to_vec(),
+            );
+            let context = self.code_context(&signature)?;
+            let function_index = context
+                .build(&mut builder, code, rng)
+                .with_context(|| format!("member {} could not be built", index))?;
+            member_indices.push(function_index);
+        }
+
+        let combiner_params: Vec<ValueType> = members.iter().flat_map(|_| main.results().iter().copied()).collect();
+        let signature = FunctionSignature::new(
+            format!("{}_combiner", main.name()),
+            combiner_params,
+            main.results().to_vec(),
+        );
+        let context = self.code_context(&signature)?;
+        let combiner_index = context
+            .build(&mut builder, combiner, rng)
+            .context("the combiner could not be built")?;
+
+        let mut instructions: Vec<Instruction> = vec![];
+        for member_index in member_indices {
+            for param in 0..main.params().len() {
+                instructions.push(VariableInstruction::LocalGet(param as u32).into());
+            }
+            instructions.push(ControlInstruction::Call(member_index).into());
+        }
+        instructions.push(ControlInstruction::Call(combiner_index).into());
+        let type_index = builder.add_function_type(main.clone().into())?;
+        let function = Function::new(type_index, ResultType::empty(), instructions.into());
+        let function_index = builder.add_function(function)?;
+        builder.add_export(Export::function(main.name().clone().into(), function_index));
+
+        let module_ast = builder.build();
+        let mut buffer = Vec::new();
+        wasm_ast::emit_binary(&module_ast, &mut buffer).context("failed to emit the Wasm binary for the ensemble")?;
+        Ok(buffer)
+    }
+
+    // Creates the context for a function of the module, which knows the signature of every function import
+    fn code_context(&self, signature: &FunctionSignature) -> Result<CodeContext> {
+        let mut context = CodeContext::new(
+            signature,
+            self.config.work_slots.clone(),
+            self.config.is_signed,
+            self.config.work_slot_initialization,
+        )?;
+        context.set_numeric_telemetry(self.config.numeric_telemetry);
+        context.set_conversion_profile(self.config.conversion_profile);
+        for import in self.imports.iter() {
+            context.add_function_import(import.clone());
+        }
+        Ok(context)
+    }
+
+    fn wasm_binary<Rnd: Rng>(&self, code: &[Code], rng: &mut Rnd) -> Result<Vec<u8>> {
+        let (buffer, _) = self.emit(code, rng)?;
+        Ok(buffer)
+    }
+
+    #[cfg(feature = "wasi")]
+    fn wasi_command_binary<Rnd: Rng>(&self, code: &[Code], rng: &mut Rnd) -> Result<Vec<u8>> {
+        // A command only has the WASI functions to call, which the imports of the World are not
+        let main = &self.config.main_entry_point;
+        if !self.imports.is_empty()
+            || self.config.numeric_telemetry
+            || (self.config.work_slot_initialization == SlotInit::RandomPerCall && self.config.work_slots.len() > 0)
+        {
+            return Err(WasmgpError::InvalidConfiguration(
+                "a WASI command cannot call function imports, numeric_telemetry or SlotInit::RandomPerCall".into(),
+            )
+            .into());
+        }
+        if main.params().iter().chain(main.results().iter()).any(|t| t.is_float()) {
+            return Err(WasmgpError::InvalidConfiguration(
+                "a WASI command can only read and write integer parameters and results".into(),
+            )
+            .into());
+        }
+
+        let mut builder = self.module_builder.clone();
+        let imports = wasi_command::add_wasi_imports(&mut builder)?;
+        let context = self.code_context(main)?;
+        let main_index = context.build(&mut builder, code, rng)?;
+        wasi_command::add_start_function(&mut builder, &imports, main, main_index, self.config.is_signed)?;
+        let module_ast = builder.build();
+        let mut buffer = Vec::new();
+        wasm_ast::emit_binary(&module_ast, &mut buffer)
+            .context("failed to emit the Wasm binary for the WASI command")?;
+        Ok(buffer)
+    }
+
+    // Emits the binary of the module along with the number of locals of its main function
+    fn emit<Rnd: Rng>(&self, code: &[Code], rng: &mut Rnd) -> Result<(Vec<u8>, usize)> {
+        let context = self.code_context(&self.config.main_entry_point)?;
+        if let Some(template) = self.emission_template() {
+            // Only the main function is built, in a module of its own, and its body is moved into the template
+            let mut builder = ModuleBuilder::new();
+            context.build(&mut builder, code, rng)?;
+            let mut buffer = Vec::new();
+            wasm_ast::emit_binary(&builder.build(), &mut buffer)
+                .context("failed to emit the Wasm binary for the Code")?;
+            return Ok((template.assemble(&buffer)?, context.local_types().len()));
+        }
+
+        let mut builder = self.module_builder.clone();
+        context.build(&mut builder, code, rng)?;
+        let local_count = context.local_types().len();
+        let module_ast = builder.build();
+        let mut buffer = Vec::new();
+        wasm_ast::emit_binary(&module_ast, &mut buffer).context("failed to emit the Wasm binary for the Code")?;
+        Ok((buffer, local_count))
+    }
+
+    // Returns the template of the World's module, emitting it the first time it is needed. There is none when
+    // `incremental_emission` is off, or when the main function calls imports that `CodeContext::build` adds to the
+    // module, because their indices depend on the module the function is built into.
+    fn emission_template(&self) -> Option<&EmissionTemplate> {
+        use rand::rngs::SmallRng;
+        use rand::SeedableRng;
+
+        self.emission_template
+            .get_or_init(|| {
+                let uses_random_per_call =
+                    self.config.work_slot_initialization == SlotInit::RandomPerCall && self.config.work_slots.len() > 0;
+                if !self.config.incremental_emission || uses_random_per_call || self.config.numeric_telemetry {
+                    return None;
+                }
+
+                // The body of the template is cut out, so the slots it initializes do not matter
+                let mut builder = self.module_builder.clone();
+                let context = self.code_context(&self.config.main_entry_point).ok()?;
+                context.build(&mut builder, &[], &mut SmallRng::seed_from_u64(0)).ok()?;
+                let mut buffer = Vec::new();
+                wasm_ast::emit_binary(&builder.build(), &mut buffer).ok()?;
+                EmissionTemplate::new(&buffer).ok()
+            })
+            .as_ref()
+    }
+}
+
+#[cfg(all(test, not(feature = "async")))]
+mod tests {
+    use crate::*;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    #[derive(Clone)]
+    struct DoNothing {}
+
+    impl IslandCallbacks<(), EmptyRunResult> for DoNothing {
+        fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+            Box::new(DoNothing {})
+        }
+
+        fn run_individual(&mut self, _individual: &mut Individual<(), EmptyRunResult>) {}
+    }
+
+    fn world_with_islands(config: WorldConfiguration, number_of_islands: usize) -> World<(), EmptyRunResult> {
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+        for _ in 0..number_of_islands {
+            world.create_island(Box::new(DoNothing {}));
+        }
+        world
+    }
+
+    #[test]
+    fn champion_listings_are_recorded_every_interval() {
+        let config = WorldConfiguration {
+            individuals_per_island: 5,
+            individual_max_points: 10,
+            champion_listing_interval: 2,
+            ..WorldConfiguration::default()
+        };
+        let mut world = world_with_islands(config, 2);
+        world
+            .run_generations_while(|world| world.generations_complete() < 4)
+            .unwrap();
+
+        let listings = world.champion_listings();
+        let recorded: Vec<(IslandId, usize)> = listings.iter().map(|l| (l.island, l.generation)).collect();
+        assert_eq!([(0, 1), (1, 1), (0, 3), (1, 3)], recorded[..]);
+        let champion = world.get_island(1).unwrap().most_fit_individual().unwrap();
+        assert_eq!(champion.get_id(), listings[3].individual_id);
+        assert_eq!(champion.get_code_string(), listings[3].listing);
+
+        world.clear_champion_listings();
+        assert!(world.champion_listings().is_empty());
+    }
+
+    #[test]
+    fn run_report_covers_the_whole_run() {
+        let config = WorldConfiguration {
+            individuals_per_island: 5,
+            individual_max_points: 10,
+            generations_between_migrations: 1,
+            number_of_individuals_migrating: 1,
+            champion_listing_interval: 2,
+            ..WorldConfiguration::default()
+        };
+        let mut world = world_with_islands(config, 2);
+        world
+            .run_generations_while(|world| world.generations_complete() < 3)
+            .unwrap();
+
+        let history = world.generation_history();
+        assert_eq!(6, history.len());
+        assert_eq!((1, 1), (history[3].island, history[3].generation));
+        assert_eq!(1, history[3].stats.emigrants);
+
+        let path = std::env::temp_dir().join(format!("wasmgp-run-report-{}.md", std::process::id()));
+        world.write_run_report(&path).unwrap();
+        let report = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        for section in [
+            "## Configuration",
+            "## Fitness and diversity",
+            "## Migrations",
+            "## Champion timeline",
+            "## Final champions",
+        ] {
+            assert!(report.contains(section), "{}", section);
+        }
+        let champion = world.get_island(1).unwrap().most_fit_individual().unwrap();
+        assert!(report.contains(&format!(
+            "### Island 1, generation 2: individual {} ",
+            champion.get_id()
+        )));
+
+        world.clear_generation_history();
+        assert!(world.generation_history().is_empty());
+    }
+
+    #[derive(Clone)]
+    struct ScoreTheLengthTimes {
+        factor: u64,
+        runs: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl IslandCallbacks<(), EmptyRunResult> for ScoreTheLengthTimes {
+        fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+            Box::new(Clone::clone(self))
+        }
+
+        fn run_individual(&mut self, _individual: &mut Individual<(), EmptyRunResult>) {
+            self.runs.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        }
+
+        fn score_individual(&self, individual: &Individual<(), EmptyRunResult>) -> u64 {
+            self.factor * individual.get_code().len() as u64
+        }
+    }
+
+    #[test]
+    fn islands_score_each_others_elites() {
+        let config = WorldConfiguration {
+            individuals_per_island: 5,
+            individual_max_points: 10,
+            cross_evaluation_elites: 2,
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+        let runs = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        for factor in [1, 3] {
+            world.create_island(Box::new(ScoreTheLengthTimes {
+                factor,
+                runs: runs.clone(),
+            }));
+        }
+        world.fill_all_islands().unwrap();
+        world.run_one_generation();
+
+        // Each island runs its own five individuals and the two elites of the other island
+        assert_eq!(14, runs.load(std::sync::atomic::Ordering::SeqCst));
+        let matrix = world.cross_evaluation_matrix();
+        for (source, (once, thrice)) in matrix[0].iter().zip(matrix[1].iter()).enumerate() {
+            let island = world.get_island(source).unwrap();
+            let total: usize = (3..5)
+                .map(|index| island.get_one_individual(index).unwrap().get_code().len())
+                .sum();
+            assert_eq!(Some(total as f64 / 2.0), *once);
+            assert_eq!(Some(3.0 * total as f64 / 2.0), *thrice);
+        }
+        assert_eq!(matrix[1], world.generation_history()[1].stats.cross_evaluation);
+
+        let path = std::env::temp_dir().join(format!("wasmgp-cross-evaluation-{}.md", std::process::id()));
+        world.write_run_report(&path).unwrap();
+        let report = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(report.contains("## Cross evaluation"));
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct RunNumber(usize);
+
+    impl RunResult for RunNumber {}
+
+    #[derive(Clone, Default)]
+    struct RecordTheRun {
+        run: usize,
+        generations: usize,
+    }
+
+    impl IslandCallbacks<(), RunNumber> for RecordTheRun {
+        fn clone(&self) -> Box<dyn IslandCallbacks<(), RunNumber>> {
+            Box::new(Clone::clone(self))
+        }
+
+        fn use_fitness_cases(&mut self, cases: &[usize]) {
+            self.run = cases[0];
+        }
+
+        fn pre_generation_run(&mut self, _individuals: &[Individual<(), RunNumber>]) {
+            self.generations += 1;
+        }
+
+        fn run_individual(&mut self, individual: &mut Individual<(), RunNumber>) {
+            assert_eq!(None, individual.get_run_result());
+            individual.set_run_result(Some(RunNumber(self.run)));
+        }
+
+        fn score_individual(&self, individual: &Individual<(), RunNumber>) -> u64 {
+            (individual.get_run_result().unwrap().0 * self.generations) as u64
+        }
+    }
+
+    // Sets a RunResult on the individuals with even IDs, and records each result that is streamed back
+    #[derive(Clone)]
+    struct StreamEvenResults {
+        streamed: std::sync::Arc<std::sync::Mutex<Vec<(IndividualId, usize)>>>,
+    }
+
+    impl IslandCallbacks<(), RunNumber> for StreamEvenResults {
+        fn clone(&self) -> Box<dyn IslandCallbacks<(), RunNumber>> {
+            Box::new(Clone::clone(self))
+        }
+
+        fn run_individual(&mut self, individual: &mut Individual<(), RunNumber>) {
+            let id = individual.get_id();
+            individual.set_run_result(if id % 2 == 0 {
+                Some(RunNumber(id as usize))
+            } else {
+                None
+            });
+        }
+
+        fn on_individual_evaluated(&mut self, individual: &Individual<(), RunNumber>, result: &RunNumber) {
+            self.streamed.lock().unwrap().push((individual.get_id(), result.0));
+        }
+
+        fn post_generation_run(&mut self, _individuals: &[Individual<(), RunNumber>]) {
+            assert_eq!(3, self.streamed.lock().unwrap().len());
+        }
+    }
+
+    #[test]
+    fn results_are_streamed_before_the_generation_ends() {
+        let config = WorldConfiguration {
+            individuals_per_island: 6,
+            individual_max_points: 10,
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<(), RunNumber>::new(config).unwrap();
+        let streamed = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        world.create_island(Box::new(StreamEvenResults {
+            streamed: streamed.clone(),
+        }));
+        world
+            .run_generations_while(|world| world.generations_complete() < 1)
+            .unwrap();
+        assert_eq!(vec![(0, 0), (2, 2), (4, 4)], *streamed.lock().unwrap());
+    }
+
+    #[test]
+    fn threading_models_evaluate_every_individual() {
+        for threading_model in [ThreadingModel::PerIsland(2), ThreadingModel::PerIndividual(4)] {
+            let config = WorldConfiguration {
+                individuals_per_island: 6,
+                individual_max_points: 10,
+                threading_model,
+                ..WorldConfiguration::default()
+            };
+            let mut world = World::<(), RunNumber>::new(config).unwrap();
+            let islands: Vec<_> = (0..3)
+                .map(|_| std::sync::Arc::new(std::sync::Mutex::new(vec![])))
+                .collect();
+            for streamed in islands.iter() {
+                world.create_island(Box::new(StreamEvenResults {
+                    streamed: streamed.clone(),
+                }));
+            }
+            world
+                .run_generations_while_threaded(|world| world.generations_complete() < 1)
+                .unwrap();
+
+            // Every island ran its own individuals, in whatever order the threads got to them
+            let mut streamed: Vec<(IndividualId, usize)> = islands
+                .iter()
+                .flat_map(|streamed| streamed.lock().unwrap().clone())
+                .collect();
+            streamed.sort();
+            let expected: Vec<(IndividualId, usize)> = (0..18).step_by(2).map(|id| (id, id as usize)).collect();
+            assert_eq!(expected, streamed);
+        }
+
+        let config = WorldConfiguration {
+            threading_model: ThreadingModel::PerIndividual(0),
+            ..WorldConfiguration::default()
+        };
+        assert!(World::<(), RunNumber>::new(config).is_err());
+    }
+
+    #[derive(Clone)]
+    struct UsesSharedState {}
+
+    impl IslandCallbacks<std::rc::Rc<()>, EmptyRunResult> for UsesSharedState {
+        fn clone(&self) -> Box<dyn IslandCallbacks<std::rc::Rc<()>, EmptyRunResult>> {
+            Box::new(UsesSharedState {})
+        }
+
+        fn run_individual(&mut self, _individual: &mut Individual<std::rc::Rc<()>, EmptyRunResult>) {}
+    }
+
+    #[test]
+    fn worlds_whose_state_is_not_send_run_on_the_calling_thread() {
+        let config = WorldConfiguration {
+            individuals_per_island: 5,
+            individual_max_points: 10,
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<std::rc::Rc<()>, EmptyRunResult>::new(config).unwrap();
+        world.create_island(Box::new(UsesSharedState {}));
+        world.run_one_generation();
+        world
+            .run_generations_while(|world| world.generations_complete() < 2)
+            .unwrap();
+        assert_eq!(2, world.generations_complete());
+    }
+
+    #[test]
+    fn parents_elites_and_migrants_may_be_chosen_by_tournament() {
+        let config = WorldConfiguration {
+            individuals_per_island: 8,
+            individual_max_points: 10,
+            elite_individuals_per_generation: 2,
+            generations_between_migrations: 1,
+            number_of_individuals_migrating: 2,
+            select_as_parent: SelectionStrategy::tournament(3),
+            select_as_elite: SelectionStrategy::tournament(8),
+            select_for_migration: SelectionStrategy::tournament(2),
+            ..WorldConfiguration::default()
+        };
+        let mut world = world_with_islands(config, 2);
+        world
+            .run_generations_while(|world| world.generations_complete() < 3)
+            .unwrap();
+        assert_eq!(3, world.generation_history().len() / 2);
+        assert!(!world.migration_log().is_empty());
+
+        // Each tournament picks the fittest of its own draws from the sorted island, which the curve it replaces
+        // does not do from the same random numbers
+        let island = world.get_island(0).unwrap();
+        let mut rng = SmallRng::seed_from_u64(5);
+        let mut draws = rng.clone();
+        let mut curve_rng = rng.clone();
+        let mut tournament_picks = vec![];
+        for _ in 0..20 {
+            let fittest_drawn = (0..3).map(|_| draws.gen_range(0..island.len())).max().unwrap();
+            let picked = island
+                .select_one_individual(SelectionStrategy::tournament(3), &mut rng)
+                .unwrap();
+            assert!(std::ptr::eq(island.get_one_individual(fittest_drawn).unwrap(), picked));
+            tournament_picks.push(fittest_drawn);
+        }
+        let curve_picks: Vec<usize> = (0..20)
+            .map(|_| SelectionCurve::PreferenceForFit.pick_one_index(&mut curve_rng, island.len()))
+            .collect();
+        assert_ne!(curve_picks, tournament_picks);
+
+        let config = WorldConfiguration {
+            select_as_elite: SelectionStrategy::tournament(0),
+            ..WorldConfiguration::default()
+        };
+        let error = World::<(), EmptyRunResult>::new(config).err().unwrap();
+        assert!(error.to_string().contains("select_as_elite"));
+    }
+
+    #[test]
+    fn holdout_runs_each_individual_with_the_holdout_callbacks() {
+        let config = WorldConfiguration {
+            individuals_per_island: 5,
+            individual_max_points: 10,
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<(), RunNumber>::new(config).unwrap();
+        world.create_island(Box::new(RecordTheRun::default()));
+        world.fill_all_islands().unwrap();
+        world.run_one_generation();
+        let island = world.get_island(0).unwrap();
+        let champions = [
+            island.get_one_individual(4).unwrap().clone(),
+            island.get_one_individual(3).unwrap().clone(),
+        ];
+
+        let results = world.evaluate_on_holdout(&champions, Box::new(RecordTheRun::default()), 3);
+        assert_eq!(2, results.len());
+        for (result, champion) in results.iter().zip(champions.iter()) {
+            assert_eq!(champion.get_id(), result.individual_id);
+            assert_eq!(Some(&RunNumber(0)), champion.get_run_result());
+            assert_eq!(
+                vec![Some(RunNumber(0)), Some(RunNumber(1)), Some(RunNumber(2))],
+                result.run_results
+            );
+            assert_eq!(vec![0, 2, 6], result.scores);
+            assert_eq!((Some(0), Some(6)), (result.min_score(), result.max_score()));
+        }
+    }
+
+    #[test]
+    fn ensemble_module_combines_every_member() {
+        let config = WorldConfiguration {
+            main_entry_point: FunctionSignature::new(
+                "main",
+                vec![ValueType::I32, ValueType::I32],
+                vec![ValueType::I32],
+            ),
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+        let mut members = vec![];
+        for code in [Add::new(0, 1, 2), Subtract::new(0, 1, 2), Multiply::new(0, 1, 2)] {
+            members.push(world.evaluate(&[code], &mut DoNothing {}).unwrap().0);
+        }
+        let ensemble = Ensemble::new(members);
+
+        // The combiner adds the second and third answers to the first
+        let combiner = [Add::new(0, 1, 3), Add::new(3, 2, 3)];
+        let binary = world.ensemble_wasm_binary(&ensemble, &combiner).unwrap();
+        let module = wasmtime::Module::new(world.wasm_engine(), &binary[..]).unwrap();
+        let mut store = world.store(());
+        store.set_epoch_deadline(1_000);
+        let instance = wasmtime::Instance::new(&mut store, &module, &[]).unwrap();
+        let main = instance.get_typed_func::<(i32, i32), i32>(&mut store, "main").unwrap();
+        assert_eq!(8 + 2 + 15, main.call(&mut store, (5, 3)).unwrap());
+        let member = instance
+            .get_typed_func::<(i32, i32), i32>(&mut store, "main_member_1")
+            .unwrap();
+        assert_eq!(2, member.call(&mut store, (5, 3)).unwrap());
+
+        assert!(world.ensemble_wasm_binary(&Ensemble::new(vec![]), &combiner).is_err());
+    }
+
+    #[test]
+    fn generation_stats_count_the_children_of_each_operator() {
+        let config = WorldConfiguration {
+            individuals_per_island: 6,
+            individual_max_points: 10,
+            ..WorldConfiguration::default()
+        };
+        let mut world = world_with_islands(config, 1);
+        world
+            .run_generations_while(|world| world.generations_complete() < 1)
+            .unwrap();
+        assert!(world.get_island(0).unwrap().generation_stats().operators.is_empty());
+
+        // Every individual of the second generation is an elite or a bred child, and none can beat a parent when every
+        // score is the same
+        world
+            .run_generations_while(|world| world.generations_complete() < 2)
+            .unwrap();
+        let island = world.get_island(0).unwrap();
+        let operators = &island.generation_stats().operators;
+        assert_eq!(6, operators.iter().map(|stats| stats.children).sum::<usize>());
+        assert!(operators
+            .iter()
+            .all(|stats| stats.improved == 0 && stats.success_rate() == 0.0));
+        assert!(operators.windows(2).all(|pair| pair[0].operator < pair[1].operator));
+        let elites = (0..island.len())
+            .filter(|index| island.get_one_individual(*index).unwrap().get_operator() == Some(Operator::Reproduction))
+            .count();
+        let reproduced = operators
+            .iter()
+            .find(|stats| stats.operator == Operator::Reproduction)
+            .map_or(0, |stats| stats.children);
+        assert!(elites > 0);
+        assert_eq!(elites, reproduced);
+    }
+
+    #[test]
+    fn islands_in_the_hall_of_fame_are_given_more_individuals() {
+        let config = WorldConfiguration {
+            individuals_per_island: 5,
+            individual_max_points: 10,
+            island_sizing: Some(IslandSizing::new(2, 1, 2)),
+            ..WorldConfiguration::default()
+        };
+        let mut world = world_with_islands(config, 2);
+        world
+            .run_generations_while(|world| world.generations_complete() < 1)
+            .unwrap();
+        assert_eq!(None, world.get_island(0).unwrap().target_size());
+
+        // Every champion scores the same, so the first one entered keeps the only place in the hall of fame
+        world
+            .run_generations_while(|world| world.generations_complete() < 3)
+            .unwrap();
+        let first = world.get_island(0).unwrap();
+        let second = world.get_island(1).unwrap();
+        assert_eq!((Some(8), Some(2)), (first.target_size(), second.target_size()));
+        assert_eq!((8, 2), (first.len(), second.len()));
+
+        let invalid = WorldConfiguration {
+            island_sizing: Some(IslandSizing::new(2, 1, 101)),
+            ..WorldConfiguration::default()
+        };
+        assert!(World::<(), EmptyRunResult>::new(invalid).is_err());
+    }
+
+    #[test]
+    fn restoring_the_rng_state_repeats_the_run() {
+        let config = WorldConfiguration {
+            individuals_per_island: 5,
+            individual_max_points: 10,
+            ..WorldConfiguration::default()
+        };
+        let mut world = world_with_islands(config.clone(), 1);
+        let state = world.capture_rng_state();
+        world.fill_all_islands().unwrap();
+        let island = world.get_island(0).unwrap();
+        let first: Vec<String> = (0..island.len())
+            .map(|index| island.get_one_individual(index).unwrap().get_code_string())
+            .collect();
+        assert_eq!(5, first.len());
+
+        let mut resumed = world_with_islands(config, 1);
+        resumed.restore_rng_state(state);
+        resumed.fill_all_islands().unwrap();
+        for (index, code) in first.iter().enumerate() {
+            let individual = resumed.get_island(0).unwrap().get_one_individual(index).unwrap();
+            assert_eq!(*code, individual.get_code_string());
+        }
+    }
+
+    #[test]
+    fn forks_copy_the_population_but_not_the_random_numbers() {
+        let config = WorldConfiguration {
+            individuals_per_island: 5,
+            individual_max_points: 10,
+            seed: Some(11),
+            ..WorldConfiguration::default()
+        };
+        let codes = |world: &World<(), EmptyRunResult>| -> Vec<String> {
+            let island = world.get_island(0).unwrap();
+            (0..island.len())
+                .map(|index| island.get_one_individual(index).unwrap().get_code_string())
+                .collect()
+        };
+        let mut world = world_with_islands(config.clone(), 1);
+        world.fill_all_islands().unwrap();
+        world.run_one_generation();
+
+        let forks = world.fork(2, false);
+        assert!(forks.iter().all(|fork| codes(fork) == codes(&world)));
+
+        // Each fork is stopped on its own
+        forks[0].cancellation_token().cancel();
+        assert!(!world.cancellation_token().is_cancelled());
+        assert!(!forks[1].cancellation_token().is_cancelled());
+
+        // Started over, the trials breed different populations, but the same ones from the same seed
+        let mut fresh = world.fork(2, true);
+        assert_eq!(0, fresh[0].get_island(0).unwrap().len());
+        assert_eq!(
+            config.generations_between_migrations,
+            fresh[0].generations_remaining_before_migration
+        );
+        for fork in fresh.iter_mut() {
+            fork.fill_all_islands().unwrap();
+        }
+        assert_ne!(codes(&fresh[0]), codes(&fresh[1]));
+
+        let mut again = world_with_islands(config, 1);
+        again.fill_all_islands().unwrap();
+        again.run_one_generation();
+        again.fork(2, false);
+        let mut repeated = again.fork(1, true).remove(0);
+        repeated.fill_all_islands().unwrap();
+        assert_eq!(codes(&fresh[0]), codes(&repeated));
+    }
+
+    #[test]
+    fn forks_of_an_unseeded_world_have_seeds_of_their_own() {
+        let config = WorldConfiguration {
+            individuals_per_island: 5,
+            individual_max_points: 10,
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+        let contexts = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        world.create_island(Box::new(RecordContexts {
+            contexts: contexts.clone(),
+        }));
+
+        let mut forks = world.fork(2, true);
+        assert!(forks[0].config.seed.is_some());
+        assert_ne!(forks[0].config.seed, forks[1].config.seed);
+        for fork in forks.iter_mut() {
+            fork.fill_all_islands().unwrap();
+            fork.run_one_generation();
+        }
+        let contexts = contexts.lock().unwrap();
+        assert_eq!(2, contexts.len());
+        assert_ne!(contexts[0].seed, contexts[1].seed);
+    }
+
+    // A RunResult that checkpoints can save
+    #[derive(Clone, Debug, PartialEq)]
+    struct Points(u64);
+
+    impl RunResult for Points {
+        fn save_result(&self) -> Option<String> {
+            Some(self.0.to_string())
+        }
+
+        fn restore_result(text: &str) -> Option<Points> {
+            text.parse().ok().map(Points)
+        }
+    }
+
+    // Scores the points of the code on each fitness case, plus the IDs of the league opponents it plays
+    #[derive(Clone)]
+    struct ScoreAgainstTheLeague {
+        league: League<(), Points>,
+        cases: Vec<usize>,
+    }
+
+    impl IslandCallbacks<(), Points> for ScoreAgainstTheLeague {
+        fn clone(&self) -> Box<dyn IslandCallbacks<(), Points>> {
+            Box::new(Clone::clone(self))
+        }
+
+        fn use_fitness_cases(&mut self, cases: &[usize]) {
+            self.cases = cases.to_vec();
+        }
+
+        fn pre_generation_run(&mut self, _individuals: &[Individual<(), Points>]) {
+            self.league.draw_opponents();
+        }
+
+        fn run_individual(&mut self, individual: &mut Individual<(), Points>) {
+            let against = self
+                .league
+                .play(individual, |_, opponent| opponent.get_id() as f64)
+                .unwrap_or_default();
+            let points: usize = individual.get_code().iter().map(|code| code.points()).sum();
+            let cases: usize = self.cases.iter().sum();
+            individual.set_run_result(Some(Points((points * cases) as u64 + against as u64)));
+        }
+
+        fn post_generation_run(&mut self, individuals: &[Individual<(), Points>]) {
+            let score = |individual: &Individual<(), Points>| individual.get_run_result().map_or(0, |points| points.0);
+            self.league.end_generation(individuals, |a, b| score(a).cmp(&score(b)));
+        }
+
+        fn score_individual(&self, individual: &Individual<(), Points>) -> u64 {
+            individual.get_run_result().map_or(0, |points| points.0)
+        }
+
+        fn save_state(&mut self, state: &mut CallbackState<(), Points>) {
+            self.league.save_state(state);
+        }
+
+        fn restore_state(&mut self, state: &mut CallbackState<(), Points>) -> anyhow::Result<()> {
+            self.league.restore_state(state)
+        }
+    }
+
+    // The ID, code and score of every individual of every island, with the generation count and size of the island
+    fn snapshot_islands(world: &World<(), Points>) -> Vec<(usize, Option<usize>, Vec<(IndividualId, String, u64)>)> {
+        (0..world.get_number_of_islands())
+            .map(|id| {
+                let island = world.get_island(id).unwrap();
+                let individuals = (0..island.len())
+                    .map(|index| {
+                        let individual = island.get_one_individual(index).unwrap();
+                        let score = island.score_for_individual(index).unwrap();
+                        (individual.get_id(), individual.get_code_string(), score)
+                    })
+                    .collect();
+                (island.generations(), island.target_size(), individuals)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn a_resumed_run_continues_exactly_as_the_run_it_was_saved_from() {
+        let directory = std::env::temp_dir().join(format!("wasmgp-checkpoints-{}", std::process::id()));
+        let schedule = CheckpointSchedule::new(&directory, 2, 2);
+        let config = WorldConfiguration {
+            individuals_per_island: 6,
+            individual_max_points: 10,
+            elite_individuals_per_generation: 1,
+            seed: Some(5),
+            checkpoints: Some(schedule.clone()),
+            generations_between_migrations: 3,
+            fitness_cases: 10,
+            fitness_case_schedule: FitnessCaseSchedule::RandomSubset {
+                size: 4,
+                generations: 3,
+            },
+            island_sizing: Some(IslandSizing::new(2, 3, 2)),
+            code_weight_adaptation: Some(CodeWeightAdaptation::new(0.5, 1)),
+            subtree_archive: Some(SubtreeArchive::new(20, 2)),
+            archive_mutation_rate: 2,
+            ..WorldConfiguration::default()
+        };
+        let islands = || -> Vec<Box<dyn IslandCallbacks<(), Points>>> {
+            (0..2)
+                .map(|_| {
+                    Box::new(ScoreAgainstTheLeague {
+                        league: League::new(3, 2, 2),
+                        cases: vec![],
+                    }) as Box<dyn IslandCallbacks<(), Points>>
+                })
+                .collect()
+        };
+
+        // The uninterrupted run keeps the last two checkpoints
+        let mut world = World::new(config.clone()).unwrap();
+        for callbacks in islands() {
+            world.create_island(callbacks);
+        }
+        let mut after_five = None;
+        world
+            .run_generations_while(|world| {
+                if world.generations_complete() == 5 {
+                    after_five = Some(snapshot_islands(world));
+                }
+                world.generations_complete() < 7
+            })
+            .unwrap();
+        let files = schedule.checkpoint_files().unwrap();
+        assert_eq!(vec![schedule.checkpoint_path(4), schedule.checkpoint_path(6)], files);
+        let checkpoint_six = std::fs::read_to_string(&files[1]).unwrap();
+        assert!(checkpoint_six.contains("\ncurrent\n") && checkpoint_six.contains("\ncallback_individuals\n"));
+
+        // Resumed from the earlier checkpoint, the run breeds, scores and saves the same generations
+        let mut resumed = World::resume(&files[0], config, islands()).unwrap();
+        assert_eq!(4, resumed.generations_complete());
+        resumed
+            .run_generations_while(|world| world.generations_complete() < 5)
+            .unwrap();
+        assert_eq!(after_five.unwrap(), snapshot_islands(&resumed));
+        resumed
+            .run_generations_while(|world| world.generations_complete() < 7)
+            .unwrap();
+        assert_eq!(snapshot_islands(&world), snapshot_islands(&resumed));
+        assert_eq!(world.subtree_archive(), resumed.subtree_archive());
+        assert_eq!(checkpoint_six, std::fs::read_to_string(&files[1]).unwrap());
+
+        // A checkpoint that is found to be invalid at its last island leaves the world as it was
+        let last_result = checkpoint_six.rfind("\nrun_result ").unwrap() + 1;
+        let line_end = last_result + checkpoint_six[last_result..].find('\n').unwrap();
+        let broken = directory.join("broken.txt");
+        std::fs::write(
+            &broken,
+            format!(
+                "{}run_result many{}",
+                &checkpoint_six[..last_result],
+                &checkpoint_six[line_end..]
+            ),
+        )
+        .unwrap();
+        let islands_before = snapshot_islands(&resumed);
+        let weights_before = resumed.genetic_engine.code_weight_levels();
+        let next_id_before = resumed.next_individual_id;
+        let mut rng_before = resumed.genetic_engine.rng().clone();
+        let error = resumed.restore_checkpoint(&broken).err().unwrap();
+        assert!(matches!(
+            error.downcast_ref::<WasmgpError>(),
+            Some(WasmgpError::InvalidCheckpoint(..))
+        ));
+        assert_eq!(islands_before, snapshot_islands(&resumed));
+        assert_eq!(weights_before, resumed.genetic_engine.code_weight_levels());
+        assert_eq!(next_id_before, resumed.next_individual_id);
+        assert_eq!(rng_before.gen::<u64>(), resumed.genetic_engine.rng().gen::<u64>());
+
+        // A checkpoint from a build with other instructions is refused
+        let text = std::fs::read_to_string(&files[0]).unwrap();
+        std::fs::write(
+            &files[0],
+            text.replace(&InstructionSet::current().to_string(), "0:0000000000000000"),
+        )
+        .unwrap();
+        let error = World::resume(&files[0], WorldConfiguration::default(), islands())
+            .err()
+            .unwrap();
+        assert!(matches!(
+            error.downcast_ref::<WasmgpError>(),
+            Some(WasmgpError::InstructionSetMismatch(..))
+        ));
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    struct DoNothingDomain(FunctionSignature);
+
+    impl FitnessDomain<(), EmptyRunResult> for DoNothingDomain {
+        fn name(&self) -> &str {
+            "nothing"
+        }
+
+        fn entry_point(&self) -> FunctionSignature {
+            self.0.clone()
+        }
+
+        fn add_imports(&self, _world: &mut World<(), EmptyRunResult>) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn create_islands(&self) -> Vec<Box<dyn IslandCallbacks<(), EmptyRunResult>>> {
+            vec![Box::new(DoNothing {}), Box::new(DoNothing {}), Box::new(DoNothing {})]
+        }
+    }
+
+    #[test]
+    fn domains_must_match_the_entry_point() {
+        let mut world = world_with_islands(WorldConfiguration::default(), 1);
+        let other = FunctionSignature::new("other", vec![ValueType::I32], vec![]);
+        assert!(world.attach_domain(DoNothingDomain(other)).is_err());
+        assert_eq!(1, world.get_number_of_islands());
+
+        let main = WorldConfiguration::default().main_entry_point;
+        assert_eq!(vec![1, 2, 3], world.attach_domain(DoNothingDomain(main)).unwrap());
+    }
+
+    #[test]
+    fn reseeding_keeps_the_most_fit_individuals() {
+        let config = WorldConfiguration {
+            individuals_per_island: 6,
+            individual_max_points: 10,
+            generations_between_migrations: 0,
+            ..WorldConfiguration::default()
+        };
+        let mut world = world_with_islands(config, 2);
+        world
+            .run_generations_while(|world| world.generations_complete() < 2)
+            .unwrap();
+        let island = world.get_island(0).unwrap();
+        let kept: Vec<IndividualId> = (4..6)
+            .map(|index| island.get_one_individual(index).unwrap().get_id())
+            .collect();
+        let last_id = (0..2)
+            .flat_map(|id| {
+                let island = world.get_island(id).unwrap();
+                (0..island.len()).map(move |index| island.get_one_individual(index).unwrap().get_id())
+            })
+            .max()
+            .unwrap();
+
+        world.reseed_all_islands(2);
+        assert_eq!(0, world.get_island(0).unwrap().len());
+        assert_eq!(2, world.get_island(1).unwrap().len_future_generation());
+        world.fill_all_islands().unwrap();
+
+        let island = world.get_island(0).unwrap();
+        let ids: Vec<IndividualId> = (0..island.len())
+            .map(|index| island.get_one_individual(index).unwrap().get_id())
+            .collect();
+        assert_eq!(6, ids.len());
+        assert_eq!(kept[1], ids[0]);
+        assert_eq!(kept[0], ids[1]);
+        assert!(ids[2..].iter().all(|id| *id > last_id));
+    }
+
+    #[test]
+    fn elites_skip_duplicate_code() {
+        for breeding_threads in [1, 2] {
+            let config = WorldConfiguration {
+                individuals_per_island: 6,
+                individual_max_points: 1,
+                elite_individuals_per_generation: 3,
+                breeding_threads,
+                ..WorldConfiguration::default()
+            };
+            let mut world = world_with_islands(config, 1);
+
+            // Every individual is the same single instruction, so only one of them can be an elite
+            world.reset_all_code_weights(0);
+            world.set_code_weight(Code::Return(Return::default()), 1);
+            world
+                .run_generations_while(|world| world.generations_complete() < 1)
+                .unwrap();
+            let island = world.get_island(0).unwrap();
+            let before: Vec<IndividualId> = (0..island.len())
+                .map(|index| island.get_one_individual(index).unwrap().get_id())
+                .collect();
+
+            world.fill_all_islands().unwrap();
+            let island = world.get_island(0).unwrap();
+            let carried = (0..island.len())
+                .filter(|index| before.contains(&island.get_one_individual(*index).unwrap().get_id()))
+                .count();
+            assert_eq!(6, island.len());
+            assert_eq!(1, carried);
+        }
+    }
+
+    #[test]
+    fn drivers_can_migrate_between_generations() {
+        let config = WorldConfiguration {
+            individuals_per_island: 5,
+            individual_max_points: 10,
+            generations_between_migrations: 0,
+            number_of_individuals_migrating: 2,
+            ..WorldConfiguration::default()
+        };
+        let mut world = world_with_islands(config, 2);
+        world
+            .run_generations_while(|world| {
+                if world.generations_complete() == 2 {
+                    world.migrate_now();
+                }
+                world.generations_complete() < 3
+            })
+            .unwrap();
+        assert_eq!(4, world.migration_log().len());
+        assert!(world.migration_log().iter().all(|event| event.generation == 2));
+
+        // Turning the automatic migrations back on migrates after every generation
+        world.set_generations_between_migrations(1);
+        world
+            .run_generations_while(|world| world.generations_complete() < 5)
+            .unwrap();
+        assert_eq!(12, world.migration_log().len());
+    }
+
+    #[test]
+    fn migration_log_records_every_migrant() {
+        let config = WorldConfiguration {
+            individuals_per_island: 5,
+            individual_max_points: 10,
+            generations_between_migrations: 1,
+            number_of_individuals_migrating: 2,
+            ..WorldConfiguration::default()
+        };
+        let mut world = world_with_islands(config, 3);
+
+        world.fill_all_islands().unwrap();
+        world.run_one_generation();
+        assert_eq!(1, world.generations_complete());
+
+        let log = world.migration_log();
+        assert_eq!(6, log.len());
+        for event in log.iter() {
+            assert_eq!((event.source + 1) % 3, event.destination);
+            assert_eq!(0, event.generation);
+        }
+        assert_eq!(2, world.migrations_to_island(0).count());
+        assert!(world.migrations_to_island(0).all(|event| event.source == 2));
+        assert_eq!(2, world.migrations_from_island(0).count());
+
+        // The migrated individual keeps its ID
+        let migrant_id = world.migrations_to_island(1).next().unwrap().individual_id;
+        let source = world.get_island(0).unwrap();
+        assert!((0..source.len()).any(|i| source.get_one_individual(i).unwrap().get_id() == migrant_id));
+
+        for id in 0..3 {
+            assert_eq!(2, world.get_island(id).unwrap().generation_stats().emigrants);
+        }
+
+        // The immigrants are counted in the generation they join
+        world.fill_all_islands().unwrap();
+        for id in 0..3 {
+            let stats = world.get_island(id).unwrap().generation_stats();
+            assert_eq!(2, stats.immigrants);
+            assert_eq!(0, stats.emigrants);
+        }
+
+        world.clear_migration_log();
+        assert!(world.migration_log().is_empty());
+    }
+
+    #[test]
+    fn fragments_migrate_between_the_archives_of_the_islands() {
+        let config = WorldConfiguration {
+            individuals_per_island: 5,
+            individual_max_points: 10,
+            generations_between_migrations: 0,
+            number_of_individuals_migrating: 3,
+            migration_payload: MigrationPayload::Fragments,
+            archive_mutation_rate: 1,
+            subtree_archive: Some(SubtreeArchive::new(100, 1)),
+            ..WorldConfiguration::default()
+        };
+        let mut world = world_with_islands(config.clone(), 2);
+        world.fill_all_islands().unwrap();
+        world.run_one_generation();
+
+        // Each island archived the subtrees of its own elites
+        let archive = |world: &World<(), EmptyRunResult>, id| world.get_island(id).unwrap().subtree_archive().cloned();
+        let before: Vec<SubtreeArchive> = (0..2).map(|id| archive(&world, id).unwrap()).collect();
+        assert!(before.iter().all(|archive| !archive.subtrees().is_empty()));
+
+        // Only subtrees moved, and each island now has some of the subtrees of the other
+        world.migrate_now();
+        assert!(world.migration_log().is_empty());
+        for id in 0..2 {
+            assert_eq!(0, world.get_island(id).unwrap().len_future_generation());
+            let after = archive(&world, id).unwrap();
+            let other = &before[1 - id];
+            assert!(after
+                .subtrees()
+                .iter()
+                .any(|subtree| other.subtrees().contains(subtree)));
+        }
+
+        let no_archive = WorldConfiguration {
+            subtree_archive: None,
+            archive_mutation_rate: 0,
+            ..config
+        };
+        assert!(World::<(), EmptyRunResult>::new(no_archive).is_err());
+    }
+
+    #[test]
+    fn islands_can_override_migration_size_and_cloning() {
+        let config = WorldConfiguration {
+            individuals_per_island: 5,
+            individual_max_points: 10,
+            generations_between_migrations: 1,
+            number_of_individuals_migrating: 2,
+            clone_migrated_individuals: true,
+            ..WorldConfiguration::default()
+        };
+        let mut world = world_with_islands(config, 3);
+        world
+            .get_island_mut(0)
+            .unwrap()
+            .set_number_of_individuals_migrating(Some(4));
+        world
+            .get_island_mut(0)
+            .unwrap()
+            .set_clone_migrated_individuals(Some(false));
+        world
+            .get_island_mut(1)
+            .unwrap()
+            .set_number_of_individuals_migrating(Some(0));
+
+        world.fill_all_islands().unwrap();
+        world.run_one_generation();
+
+        // Island 0 exports aggressively and loses the individuals it exports
+        assert_eq!(4, world.migrations_from_island(0).count());
+        assert_eq!(1, world.get_island(0).unwrap().len());
+
+        // Island 1 is a sink
+        assert_eq!(0, world.migrations_from_island(1).count());
+        assert_eq!(4, world.migrations_to_island(1).count());
+
+        // Island 2 uses the world configuration
+        assert_eq!(2, world.migrations_from_island(2).count());
+        assert_eq!(5, world.get_island(2).unwrap().len());
+    }
+
+    #[test]
+    fn an_island_stops_sending_migrants_once_it_is_empty() {
+        let config = WorldConfiguration {
+            individuals_per_island: 5,
+            individual_max_points: 10,
+            generations_between_migrations: 1,
+            number_of_individuals_migrating: 2,
+            clone_migrated_individuals: false,
+            ..WorldConfiguration::default()
+        };
+        let mut world = world_with_islands(config, 2);
+        world
+            .get_island_mut(0)
+            .unwrap()
+            .set_number_of_individuals_migrating(Some(7));
+
+        world.fill_all_islands().unwrap();
+        world.run_one_generation();
+
+        assert_eq!(5, world.migrations_from_island(0).count());
+        assert_eq!(0, world.get_island(0).unwrap().len());
+        assert_eq!(2, world.migrations_from_island(1).count());
+    }
+
+    #[test]
+    fn random_destination_island_is_never_the_source() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        for len in 2..8 {
+            let mut seen = vec![0; len];
+            for source_id in 0..len {
+                for _ in 0..100 {
+                    let destination_id =
+                        World::<(), EmptyRunResult>::random_destination_island(source_id, len, &mut rng);
+                    assert_ne!(source_id, destination_id);
+                    assert!(destination_id < len);
+                    seen[destination_id] += 1;
+                }
+            }
+
+            // Every island is picked as a destination from the other islands
+            assert!(seen.iter().all(|count| *count > 0));
+        }
+
+        // With two islands there is only one possible destination
+        assert_eq!(
+            1,
+            World::<(), EmptyRunResult>::random_destination_island(0, 2, &mut rng)
+        );
+        assert_eq!(
+            0,
+            World::<(), EmptyRunResult>::random_destination_island(1, 2, &mut rng)
+        );
+    }
+
+    #[test]
+    fn completely_random_migrants_leave_their_island() {
+        let config = WorldConfiguration {
+            individuals_per_island: 5,
+            individual_max_points: 10,
+            generations_between_migrations: 1,
+            number_of_individuals_migrating: 3,
+            migration_algorithm: MigrationAlgorithm::CompletelyRandom,
+            ..WorldConfiguration::default()
+        };
+        let mut world = world_with_islands(config, 4);
+
+        world.fill_all_islands().unwrap();
+        world.run_one_generation();
+
+        assert_eq!(12, world.migration_log().len());
+        assert!(world
+            .migration_log()
+            .iter()
+            .all(|event| event.source != event.destination));
+        let arrivals: usize = (0..4)
+            .map(|id| world.get_island(id).unwrap().len_future_generation())
+            .sum();
+        assert_eq!(12, arrivals);
+    }
+
+    #[derive(Clone)]
+    struct RecordCases {
+        cases: std::sync::Arc<std::sync::Mutex<Vec<Vec<usize>>>>,
+    }
+
+    impl IslandCallbacks<(), EmptyRunResult> for RecordCases {
+        fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+            Box::new(Clone::clone(self))
+        }
+
+        fn use_fitness_cases(&mut self, cases: &[usize]) {
+            self.cases.lock().unwrap().push(cases.to_vec());
+        }
+
+        fn run_individual(&mut self, _individual: &mut Individual<(), EmptyRunResult>) {}
+    }
+
+    #[test]
+    fn fitness_case_subsets_rotate_on_schedule() {
+        let config = WorldConfiguration {
+            individuals_per_island: 5,
+            individual_max_points: 10,
+            fitness_cases: 50,
+            fitness_case_schedule: FitnessCaseSchedule::RandomSubset {
+                size: 8,
+                generations: 2,
+            },
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+        let cases = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        world.create_island(Box::new(RecordCases { cases: cases.clone() }));
+        assert!(world.current_fitness_cases().is_empty());
+        world
+            .run_generations_while(|world| world.generations_complete() < 4)
+            .unwrap();
+
+        let cases = cases.lock().unwrap();
+        assert_eq!(4, cases.len());
+        assert_eq!(cases[3], world.current_fitness_cases());
+        for subset in cases.iter() {
+            assert_eq!(8, subset.len());
+            assert!(subset.windows(2).all(|pair| pair[0] < pair[1]));
+            assert!(subset.iter().all(|case| *case < 50));
+        }
+        assert_eq!(cases[0], cases[1]);
+        assert_eq!(cases[2], cases[3]);
+        assert_ne!(cases[1], cases[2]);
+    }
+
+    #[derive(Clone)]
+    struct RecordStages {
+        runs: std::sync::Arc<std::sync::Mutex<Vec<(usize, IndividualId)>>>,
+    }
+
+    impl IslandCallbacks<(), EmptyRunResult> for RecordStages {
+        fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+            Box::new(Clone::clone(self))
+        }
+
+        fn run_individual(&mut self, individual: &mut Individual<(), EmptyRunResult>) {
+            let run = (individual.get_evaluation_stage(), individual.get_id());
+            self.runs.lock().unwrap().push(run);
+        }
+
+        // The oldest individuals are the least fit, so that the ones that pass each stage are easy to predict
+        fn score_individual(&self, i: &Individual<(), EmptyRunResult>) -> u64 {
+            i.get_id()
+        }
+    }
+
+    #[test]
+    fn only_the_survivors_of_a_stage_run_the_next() {
+        let config = WorldConfiguration {
+            individuals_per_island: 6,
+            individual_max_points: 10,
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+        let runs = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        let id = world.create_island(Box::new(RecordStages { runs: runs.clone() }));
+        world.get_island_mut(id).unwrap().set_evaluation_stages(vec![
+            EvaluationStage::new("screen", 3, Some(1)),
+            EvaluationStage::new("shortlist", 1, None),
+            EvaluationStage::new("full", 0, None),
+        ]);
+        world
+            .run_generations_while(|world| world.generations_complete() < 1)
+            .unwrap();
+
+        let runs = runs.lock().unwrap();
+        let stage_runs =
+            |stage: usize| -> Vec<IndividualId> { runs.iter().filter(|run| run.0 == stage).map(|run| run.1).collect() };
+        assert_eq!(vec![0, 1, 2, 3, 4, 5], stage_runs(0));
+        assert_eq!(vec![3, 4, 5], stage_runs(1));
+        assert_eq!(vec![5], stage_runs(2));
+
+        let island = world.get_island(id).unwrap();
+        let stages: Vec<usize> = (0..6)
+            .map(|i| island.get_one_individual(i).unwrap().get_evaluation_stage())
+            .collect();
+        assert_eq!(vec![0, 0, 0, 1, 1, 2], stages);
+        assert_eq!(5, island.most_fit_individual().unwrap().get_id());
+    }
+
+    // Records the individuals it runs, and ends the generation once it runs the `good_enough` individual
+    #[derive(Clone)]
+    struct StopAfter {
+        good_enough: IndividualId,
+        runs: std::sync::Arc<std::sync::Mutex<Vec<IndividualId>>>,
+    }
+
+    impl IslandCallbacks<(), EmptyRunResult> for StopAfter {
+        fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+            Box::new(Clone::clone(self))
+        }
+
+        fn run_individual(&mut self, individual: &mut Individual<(), EmptyRunResult>) {
+            self.runs.lock().unwrap().push(individual.get_id());
+        }
+
+        fn is_good_enough(&self, individual: &Individual<(), EmptyRunResult>) -> bool {
+            individual.get_id() == self.good_enough
+        }
+
+        fn score_individual(&self, i: &Individual<(), EmptyRunResult>) -> u64 {
+            i.get_id()
+        }
+    }
+
+    #[test]
+    fn a_good_enough_individual_ends_the_generation() {
+        let config = WorldConfiguration {
+            individuals_per_island: 6,
+            individual_max_points: 10,
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+        let runs = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        let id = world.create_island(Box::new(StopAfter {
+            good_enough: 2,
+            runs: runs.clone(),
+        }));
+        world
+            .run_generations_while(|world| world.generations_complete() < 1)
+            .unwrap();
+
+        // The individuals after the good enough one are skipped, and sort as less fit even though their IDs are higher
+        assert_eq!(vec![0, 1, 2], *runs.lock().unwrap());
+        let island = world.get_island(id).unwrap();
+        let skipped: Vec<bool> = (0..6)
+            .map(|i| island.get_one_individual(i).unwrap().was_skipped())
+            .collect();
+        assert_eq!(vec![true, true, true, false, false, false], skipped);
+        assert_eq!(2, island.most_fit_individual().unwrap().get_id());
+    }
+
+    #[test]
+    fn diversity_is_measured_after_each_generation() {
+        let config = WorldConfiguration {
+            individuals_per_island: 6,
+            individual_max_points: 10,
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+        let runs = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        let id = world.create_island(Box::new(RecordStages { runs }));
+        world
+            .run_generations_while(|world| world.generations_complete() < 1)
+            .unwrap();
+
+        let island = world.get_island(id).unwrap();
+        let codes: Vec<String> = (0..6)
+            .map(|i| format!("{:?}", island.get_one_individual(i).unwrap().get_code()))
+            .collect();
+        let unique: std::collections::HashSet<&String> = codes.iter().collect();
+        let stats = island.generation_stats();
+        assert_eq!(unique.len(), stats.unique_structures);
+        assert!(stats.mean_edit_distance >= 0.0);
+        assert!(stats.opcode_entropy >= 0.0);
+
+        // The scores are the IDs 0 through 5
+        assert_eq!(35.0 / 12.0, stats.fitness_variance);
+    }
+
+    #[test]
+    fn opcode_usage_counts_the_population_and_the_elites() {
+        let config = WorldConfiguration {
+            individuals_per_island: 6,
+            individual_max_points: 10,
+            elite_individuals_per_generation: 2,
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+        let runs = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        let id = world.create_island(Box::new(RecordStages { runs }));
+        assert!(world.opcode_usage(id + 1).is_none());
+        world
+            .run_generations_while(|world| world.generations_complete() < 1)
+            .unwrap();
+
+        let usage = world.opcode_usage(id).unwrap();
+        let island = world.get_island(id).unwrap();
+        let points = |range: std::ops::Range<usize>| -> usize {
+            range
+                .map(|i| island.get_one_individual(i).unwrap().get_code())
+                .map(|code| code.iter().map(|c| c.points()).sum::<usize>())
+                .sum()
+        };
+        assert_eq!(6, usage.population.individuals);
+        assert_eq!(points(0..6), usage.population.total());
+        assert_eq!(2, usage.elites.individuals);
+        assert_eq!(points(4..6), usage.elites.total());
+    }
+
+    #[test]
+    fn code_weights_adapt_toward_the_elites() {
+        let config = WorldConfiguration {
+            individuals_per_island: 6,
+            individual_max_points: 10,
+            elite_individuals_per_generation: 2,
+            code_weight_adaptation: Some(CodeWeightAdaptation::new(0.5, 1)),
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+        world.reset_all_code_weights(0);
+        world.set_code_weight(Code::Add(Add::default()), 10);
+        world.set_code_weight(Code::Subtract(Subtract::default()), 10);
+        let runs = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        let id = world.create_island(Box::new(RecordStages { runs }));
+        world
+            .run_generations_while(|world| world.generations_complete() < 1)
+            .unwrap();
+
+        let elites = world.opcode_usage(id).unwrap().elites;
+        for code in [Code::Add(Add::default()), Code::Subtract(Subtract::default())] {
+            let target = f64::max(1.0, elites.frequency(code.name()) * 20.0);
+            let expected = (10.0 + 0.5 * (target - 10.0)).round() as u8;
+            assert_eq!(expected, world.genetic_engine.get_code_weight(&code));
+        }
+    }
+
+    // Larger constants are more fit
+    #[derive(Clone)]
+    struct SumOfConstants {
+        runs: std::sync::Arc<std::sync::Mutex<Vec<(IndividualId, u64)>>>,
+    }
+
+    impl SumOfConstants {
+        fn score(code: &[Code]) -> u64 {
+            let mut sum = 1i64 << 40;
+            for c in code.iter() {
+                c.walk(&mut |c| {
+                    if let Code::ConstI32(c) = c {
+                        sum += c.value() as i64;
+                    }
+                });
+            }
+            sum as u64
+        }
+    }
+
+    impl IslandCallbacks<(), EmptyRunResult> for SumOfConstants {
+        fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+            Box::new(Clone::clone(self))
+        }
+
+        fn run_individual(&mut self, individual: &mut Individual<(), EmptyRunResult>) {
+            let run = (individual.get_id(), SumOfConstants::score(individual.get_code()));
+            self.runs.lock().unwrap().push(run);
+        }
+
+        fn score_individual(&self, i: &Individual<(), EmptyRunResult>) -> u64 {
+            SumOfConstants::score(i.get_code())
+        }
+    }
+
+    #[test]
+    fn local_search_adds_improved_variants_to_the_next_generation() {
+        let config = WorldConfiguration {
+            individuals_per_island: 6,
+            individual_max_points: 10,
+            local_search: Some(LocalSearch::new(1, 20)),
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+        world.reset_all_code_weights(0);
+        world.set_code_weight(Code::ConstI32(ConstI32::default()), 1);
+        let runs = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        let id = world.create_island(Box::new(SumOfConstants { runs: runs.clone() }));
+        world.fill_all_islands().unwrap();
+        world.run_one_generation();
+
+        // The generation was run, then the most fit individual was tuned
+        let runs = runs.lock().unwrap();
+        assert_eq!(6 + 20, runs.len());
+        let island = world.get_island(id).unwrap();
+        let most_fit_score = island.score_for_individual(5).unwrap();
+        let best_tuned_score = runs[6..].iter().map(|run| run.1).max().unwrap();
+        assert!(best_tuned_score > most_fit_score);
+        assert_eq!(1, island.len_future_generation());
+    }
+
+    // Runs every member of each team and records the team sizes
+    #[derive(Clone)]
+    struct RunTeams {
+        team_sizes: std::sync::Arc<std::sync::Mutex<Vec<usize>>>,
+    }
+
+    impl IslandCallbacks<(), EmptyRunResult> for RunTeams {
+        fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+            Box::new(Clone::clone(self))
+        }
+
+        fn run_individual(&mut self, individual: &mut Individual<(), EmptyRunResult>) {
+            for member in 0..individual.team_size() {
+                let member = individual.get_team_member_mut(member).unwrap();
+                let ((), result) = member.execute::<(), ()>((), ());
+                result.unwrap();
+            }
+            individual.set_run_result(Some(EmptyRunResult {}));
+            self.team_sizes.lock().unwrap().push(individual.team_size());
+        }
+    }
+
+    #[test]
+    fn teams_are_created_and_bred_member_by_member() {
+        let config = WorldConfiguration {
+            individuals_per_island: 5,
+            individual_max_points: 10,
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+        world.reset_all_code_weights(0);
+        world.set_code_weight(Code::ConstOne(ConstOne::default()), 1);
+        world.set_code_weight(Code::Add(Add::default()), 1);
+        let team_sizes = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        let id = world.create_island(Box::new(RunTeams {
+            team_sizes: team_sizes.clone(),
+        }));
+        world.get_island_mut(id).unwrap().set_team_size(3);
+        world
+            .run_generations_while(|world| world.generations_complete() < 2)
+            .unwrap();
+
+        assert_eq!(vec![3; 10], *team_sizes.lock().unwrap());
+        let team = world.get_island(id).unwrap().most_fit_individual().unwrap();
+        assert!(team.get_team_member(2).is_some());
+        assert!(team.get_team_member(3).is_none());
+        assert!(team.get_team_member(1).unwrap().get_team_member(1).is_none());
+    }
+
+    #[derive(Clone)]
+    struct RecordLevels {
+        levels: std::sync::Arc<std::sync::Mutex<Vec<usize>>>,
+    }
+
+    impl IslandCallbacks<(), EmptyRunResult> for RecordLevels {
+        fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+            Box::new(Clone::clone(self))
+        }
+
+        fn use_difficulty_level(&mut self, level: usize) {
+            self.levels.lock().unwrap().push(level);
+        }
+
+        fn run_individual(&mut self, _individual: &mut Individual<(), EmptyRunResult>) {}
+
+        fn score_individual(&self, i: &Individual<(), EmptyRunResult>) -> u64 {
+            i.get_id()
+        }
+    }
+
+    #[derive(Clone)]
+    struct RecordContexts {
+        contexts: std::sync::Arc<std::sync::Mutex<Vec<EvaluationContext>>>,
+    }
+
+    impl IslandCallbacks<(), EmptyRunResult> for RecordContexts {
+        fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+            Box::new(Clone::clone(self))
+        }
+
+        fn use_evaluation_context(&mut self, context: &EvaluationContext) {
+            self.contexts.lock().unwrap().push(context.clone());
+        }
+
+        fn run_individual(&mut self, _individual: &mut Individual<(), EmptyRunResult>) {}
+    }
+
+    #[test]
+    fn islands_receive_the_evaluation_context() {
+        let config = WorldConfiguration {
+            individuals_per_island: 5,
+            individual_max_points: 10,
+            seed: Some(3),
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+        let contexts = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        world.create_island(Box::new(RecordContexts {
+            contexts: contexts.clone(),
+        }));
+        world.create_island(Box::new(RecordContexts {
+            contexts: contexts.clone(),
+        }));
+        world
+            .run_generations_while(|world| world.generations_complete() < 2)
+            .unwrap();
+
+        let contexts = contexts.lock().unwrap();
+        assert_eq!(4, contexts.len());
+        assert_eq!((0, 0), (contexts[0].generation, contexts[0].island));
+        assert_eq!((0, 1), (contexts[1].generation, contexts[1].island));
+        assert_eq!((1, 1), (contexts[3].generation, contexts[3].island));
+        assert_eq!(contexts[0].seed, contexts[1].seed);
+        assert_ne!(contexts[1].seed, contexts[2].seed);
+        assert!(contexts[0].stats.is_empty());
+        assert_eq!(2, contexts[2].stats.len());
+        assert_eq!(world.generation_history()[0].stats, contexts[2].stats[0]);
+    }
+
+    #[test]
+    fn curriculum_levels_rise_when_the_best_passes_the_threshold() {
+        let config = WorldConfiguration {
+            individuals_per_island: 5,
+            individual_max_points: 10,
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+        let levels = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        let id = world.create_island(Box::new(RecordLevels { levels: levels.clone() }));
+
+        // The most fit individual of the first generation scores 4, which passes level zero but not level one
+        world
+            .get_island_mut(id)
+            .unwrap()
+            .set_curriculum(Some(Curriculum::new(vec![4, 5, u64::MAX])));
+        world
+            .run_generations_while(|world| world.generations_complete() < 4)
+            .unwrap();
+
+        assert_eq!(vec![0, 1, 2, 2], *levels.lock().unwrap());
+        let island = world.get_island(id).unwrap();
+        assert_eq!(2, island.generation_stats().difficulty_level);
+        assert_eq!(2, island.difficulty_level());
+    }
+
+    // Plays against a league of past champions, where the higher id wins. Records the league size at the start of each
+    // generation and the result of every individual.
+    #[derive(Clone)]
+    struct PlayLeague {
+        league: League<(), EmptyRunResult>,
+        league_sizes: std::sync::Arc<std::sync::Mutex<Vec<usize>>>,
+        results: std::sync::Arc<std::sync::Mutex<Vec<Option<f64>>>>,
+    }
+
+    impl IslandCallbacks<(), EmptyRunResult> for PlayLeague {
+        fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+            Box::new(Clone::clone(self))
+        }
+
+        fn pre_generation_run(&mut self, _individuals: &[Individual<(), EmptyRunResult>]) {
+            self.league_sizes.lock().unwrap().push(self.league.members().len());
+            self.league.draw_opponents();
+        }
+
+        fn post_generation_run(&mut self, individuals: &[Individual<(), EmptyRunResult>]) {
+            self.league
+                .end_generation(individuals, |a, b| a.get_id().cmp(&b.get_id()));
+        }
+
+        fn run_individual(&mut self, individual: &mut Individual<(), EmptyRunResult>) {
+            let result = self.league.play(
+                individual,
+                |me, opponent| {
+                    if me.get_id() > opponent.get_id() {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                },
+            );
+            self.results.lock().unwrap().push(result);
+        }
+
+        fn score_individual(&self, i: &Individual<(), EmptyRunResult>) -> u64 {
+            i.get_id()
+        }
+    }
+
+    #[test]
+    fn champions_are_promoted_into_the_league_and_played_against() {
+        let config = WorldConfiguration {
+            individuals_per_island: 5,
+            individual_max_points: 10,
+            elite_individuals_per_generation: 1,
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+        let league_sizes = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        let results = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        world.create_island(Box::new(PlayLeague {
+            league: League::new(2, 2, 1),
+            league_sizes: league_sizes.clone(),
+            results: results.clone(),
+        }));
+        world
+            .run_generations_while(|world| world.generations_complete() < 4)
+            .unwrap();
+
+        // The league holds at most two champions, and is empty during the first generation
+        assert_eq!(vec![0, 1, 2, 2], *league_sizes.lock().unwrap());
+        let results = results.lock().unwrap();
+        assert_eq!(20, results.len());
+        assert!(results[..5].iter().all(|r| r.is_none()));
+        assert!(results[5..].iter().all(|r| r.is_some()));
+
+        // The only champion survives as an elite and cannot beat itself, but every new individual is newer than it
+        let wins = results[5..10].iter().filter(|r| **r == Some(1.0)).count();
+        let losses = results[5..10].iter().filter(|r| **r == Some(0.0)).count();
+        assert_eq!((4, 1), (wins, losses));
+    }
+
+    #[test]
+    fn migrating_the_configuration_keeps_the_individuals_that_still_build() {
+        let config = WorldConfiguration {
+            individuals_per_island: 20,
+            individual_max_points: 1,
+            ..WorldConfiguration::default()
+        };
+        let mut world = world_with_islands(config.clone(), 1);
+        world.reset_all_code_weights(0);
+        world.set_code_weight(Code::ConstOne(ConstOne::default()), 1);
+        world.fill_all_islands().unwrap();
+
+        // Each individual is a single ConstOne, which no longer builds if its slot is beyond the new work slots
+        let slot_of = |individual: &Individual<(), EmptyRunResult>| match &individual.get_code()[0] {
+            Code::ConstOne(c) => c.destination(),
+            _ => panic!("only ConstOne has a weight"),
+        };
+        let island = world.get_island(0).unwrap();
+        let before: Vec<(IndividualId, Slot)> = (0..island.len())
+            .map(|i| island.get_one_individual(i).unwrap())
+            .map(|individual| (individual.get_id(), slot_of(individual)))
+            .collect();
+        let out_of_range = before.iter().filter(|(_, slot)| *slot >= 4).count();
+
+        // Repair the code of even IDs and give up on the rest
+        let mut adapted = 0;
+        let smaller = WorldConfiguration {
+            work_slots: SlotCount {
+                i32: 4,
+                i64: 0,
+                f32: 0,
+                f64: 0,
+            },
+            ..config
+        };
+        let removed = world
+            .migrate_configuration(smaller, |code| {
+                adapted += 1;
+                match &code[0] {
+                    Code::ConstOne(c) if c.destination() % 2 == 0 => Some(vec![ConstOne::new(c.destination() % 4)]),
+                    _ => None,
+                }
+            })
+            .unwrap();
+
+        assert_eq!(out_of_range, adapted);
+        let expected: Vec<(IndividualId, Slot)> = before
+            .iter()
+            .filter(|(_, slot)| *slot < 4 || *slot % 2 == 0)
+            .map(|(id, slot)| (*id, *slot % 4))
+            .collect();
+        assert_eq!(before.len() - expected.len(), removed);
+        let island = world.get_island(0).unwrap();
+        let after: Vec<(IndividualId, Slot)> = (0..island.len())
+            .map(|i| island.get_one_individual(i).unwrap())
+            .map(|individual| (individual.get_id(), slot_of(individual)))
+            .collect();
+        assert_eq!(expected, after);
+        world.run_one_generation();
+    }
+
+    #[test]
+    fn migrating_the_configuration_cannot_switch_async_yielding() {
+        let mut world = world_with_islands(WorldConfiguration::default(), 1);
+        let config = WorldConfiguration {
+            async_epoch_yielding: true,
+            ..WorldConfiguration::default()
+        };
+        assert!(world.migrate_configuration(config, |_| None).is_err());
+    }
+
+    #[test]
+    fn code_weight_adaptation_needs_a_floor() {
+        let config = WorldConfiguration {
+            code_weight_adaptation: Some(CodeWeightAdaptation::new(0.5, 0)),
+            ..WorldConfiguration::default()
+        };
+        assert!(World::<(), EmptyRunResult>::new(config).is_err());
+    }
+
+    #[test]
+    fn fitness_case_subsets_must_fit_in_the_cases() {
+        let config = WorldConfiguration {
+            fitness_cases: 5,
+            fitness_case_schedule: FitnessCaseSchedule::RandomSubset {
+                size: 6,
+                generations: 1,
+            },
+            ..WorldConfiguration::default()
+        };
+        assert!(World::<(), EmptyRunResult>::new(config).is_err());
+    }
+
+    #[test]
+    fn cancelling_stops_run_generations_while() {
+        let config = WorldConfiguration {
+            individuals_per_island: 5,
+            individual_max_points: 10,
+            ..WorldConfiguration::default()
+        };
+        let mut world = world_with_islands(config, 2);
+        let token = world.cancellation_token();
+
+        let result = world.run_generations_while(|world| {
+            if world.generations_complete() == 3 {
+                token.cancel();
+            }
+            true
+        });
+        assert!(matches!(
+            result.unwrap_err().downcast_ref::<WasmgpError>(),
+            Some(WasmgpError::Cancelled)
+        ));
+        assert_eq!(3, world.generations_complete());
+
+        // A fresh token allows the world to run again
+        world.set_cancellation_token(CancellationToken::new());
+        world
+            .run_generations_while(|world| world.generations_complete() < 5)
+            .unwrap();
+        assert_eq!(5, world.generations_complete());
+    }
+
+    fn fail_every_time(mut caller: Caller<'_, u32>) -> anyhow::Result<()> {
+        *caller.data_mut() += 1;
+        anyhow::bail!("the host refused")
+    }
+
+    #[test]
+    fn failed_evaluations_are_captured_without_running_again() {
+        let config = WorldConfiguration {
+            evaluation_retries: 2,
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<u32, EmptyRunResult>::new(config).unwrap();
+        let fail = world.add_function_import("fail", fail_every_time).unwrap();
+
+        let code = vec![Call::new(fail, vec![], vec![])];
+        let instance_pre = world.instanciate_pre(&code[..]).unwrap();
+        let mut individual = Individual::<u32, EmptyRunResult>::new(0, code, "main".into(), instance_pre, 250, 1, 2);
+        assert!(individual.get_error().is_none());
+
+        // The host function has already run, so the evaluation is not tried again even though retries are allowed
+        let (calls, result) = individual.execute::<(), ()>(0, ());
+        assert!(result.is_err());
+        assert_eq!(1, calls);
+        let error = individual.get_error().unwrap();
+        assert_eq!(1, error.attempts);
+        assert!(error.message.contains("the host refused"));
+
+        // Successful evaluations leave no error behind
+        let code = vec![Return::new()];
+        let instance_pre = world.instanciate_pre(&code[..]).unwrap();
+        let mut individual = Individual::<u32, EmptyRunResult>::new(1, code, "main".into(), instance_pre, 250, 1, 2);
+        let (_, result) = individual.execute::<(), ()>(0, ());
+        assert!(result.is_ok());
+        assert!(individual.get_error().is_none());
+    }
+
+    fn add_to_state(mut caller: Caller<'_, u32>, amount: u32) -> u32 {
+        *caller.data_mut() += amount;
+        *caller.data()
+    }
+
+    #[test]
+    fn host_calls_are_traced_when_configured() {
+        let config = WorldConfiguration {
+            main_entry_point: FunctionSignature::new("main", vec![ValueType::I32], vec![]),
+            trace_host_calls: true,
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<u32, EmptyRunResult>::new(config).unwrap();
+        let add = world.add_function_import("add_to_state", add_to_state).unwrap();
+        let fail = world.add_function_import("fail", fail_every_time).unwrap();
+
+        let code = vec![
+            Call::new(add, vec![0], vec![1]),
+            Call::new(add, vec![1], vec![1]),
+            Call::new(fail, vec![], vec![]),
+        ];
+        let instance_pre = world.instanciate_pre(&code[..]).unwrap();
+        let mut individual = Individual::<u32, EmptyRunResult>::new(0, code, "main".into(), instance_pre, 250, 1, 0);
+        let (state, result) = individual.execute::<i32, ()>(5, 3);
+        assert!(result.is_err());
+        assert_eq!(17, state);
+
+        let trace = individual.get_host_call_trace();
+        assert_eq!(3, trace.len());
+        assert_eq!("add_to_state", trace[0].name);
+        assert_eq!(vec![SlotValue::I32(3)], trace[0].params);
+        assert_eq!(Ok(vec![SlotValue::I32(8)]), trace[0].results);
+        assert_eq!(vec![SlotValue::I32(8)], trace[1].params);
+        assert_eq!(Ok(vec![SlotValue::I32(16)]), trace[1].results);
+        assert_eq!("fail", trace[2].name);
+        assert!(trace[2].results.as_ref().unwrap_err().contains("the host refused"));
+
+        // Each evaluation starts a new trace
+        let (_, result) = individual.execute::<i32, ()>(0, 0);
+        assert!(result.is_err());
+        assert_eq!(3, individual.get_host_call_trace().len());
+        assert_eq!(Ok(vec![SlotValue::I32(0)]), individual.get_host_call_trace()[0].results);
+    }
+
+    fn run_with_call_limit(policy: CallLimitPolicy) -> (Individual<u32, EmptyRunResult>, u32, anyhow::Result<()>) {
+        let config = WorldConfiguration {
+            main_entry_point: FunctionSignature::new("main", vec![ValueType::I32], vec![]),
+            evaluation_retries: 2,
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<u32, EmptyRunResult>::new(config).unwrap();
+        let options = ImportOptions {
+            call_limit: Some(CallLimit::new(2, policy)),
+            ..ImportOptions::default()
+        };
+        let add = world
+            .add_function_import_with_options("add_to_state", add_to_state, options)
+            .unwrap();
+
+        let code = vec![
+            Call::new(add, vec![0], vec![1]),
+            Call::new(add, vec![0], vec![1]),
+            Call::new(add, vec![0], vec![1]),
+            Call::new(add, vec![0], vec![1]),
+        ];
+        let instance_pre = world.instanciate_pre(&code[..]).unwrap();
+        let mut individual = Individual::<u32, EmptyRunResult>::new(0, code, "main".into(), instance_pre, 250, 1, 2);
+        let (state, result) = individual.execute::<i32, ()>(0, 1);
+        (individual, state, result)
+    }
+
+    #[test]
+    fn call_limits_apply_their_policy() {
+        // Trapping ends the evaluation on the third call, and is not retried
+        let (individual, state, result) = run_with_call_limit(CallLimitPolicy::Trap);
+        assert!(matches!(
+            result.unwrap_err().downcast_ref::<WasmgpError>(),
+            Some(WasmgpError::CallLimitExceeded(name)) if name == "add_to_state"
+        ));
+        assert_eq!(2, state);
+        assert_eq!(1, individual.get_error().unwrap().attempts);
+        assert_eq!(["add_to_state"], individual.get_exceeded_call_limits());
+
+        // The calls over the limit are skipped
+        let (individual, state, result) = run_with_call_limit(CallLimitPolicy::NoOp);
+        assert!(result.is_ok());
+        assert_eq!(2, state);
+        assert_eq!(["add_to_state"], individual.get_exceeded_call_limits());
+
+        // Every call is made, but the individual is flagged
+        let (individual, state, result) = run_with_call_limit(CallLimitPolicy::Flag);
+        assert!(result.is_ok());
+        assert_eq!(4, state);
+        assert_eq!(["add_to_state"], individual.get_exceeded_call_limits());
+    }
+
+    fn run_with_budget(policy: CallLimitPolicy) -> (Individual<u32, EmptyRunResult>, u32, anyhow::Result<()>) {
+        let config = WorldConfiguration {
+            main_entry_point: FunctionSignature::new("main", vec![ValueType::I32], vec![]),
+            host_call_budget: Some(CallLimit::new(3, policy)),
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<u32, EmptyRunResult>::new(config).unwrap();
+        let first = world.add_function_import("add_first", add_to_state).unwrap();
+        let second = world.add_function_import("add_second", add_to_state).unwrap();
+
+        let code = vec![
+            Call::new(first, vec![0], vec![1]),
+            Call::new(second, vec![0], vec![1]),
+            Call::new(first, vec![0], vec![1]),
+            Call::new(second, vec![0], vec![1]),
+        ];
+        let instance_pre = world.instanciate_pre(&code[..]).unwrap();
+        let mut individual = Individual::<u32, EmptyRunResult>::new(0, code, "main".into(), instance_pre, 250, 1, 0);
+        let (state, result) = individual.execute::<i32, ()>(0, 1);
+        (individual, state, result)
+    }
+
+    #[test]
+    fn the_host_call_budget_is_shared_by_every_import() {
+        let (individual, state, result) = run_with_budget(CallLimitPolicy::Trap);
+        assert!(matches!(
+            result.unwrap_err().downcast_ref::<WasmgpError>(),
+            Some(WasmgpError::HostCallBudgetExceeded(3))
+        ));
+        assert_eq!(3, state);
+        assert!(individual.exceeded_host_call_budget());
+        assert!(individual.get_exceeded_call_limits().is_empty());
+
+        let (individual, state, result) = run_with_budget(CallLimitPolicy::NoOp);
+        assert!(result.is_ok());
+        assert_eq!(3, state);
+        assert!(individual.exceeded_host_call_budget());
+
+        let (individual, state, result) = run_with_budget(CallLimitPolicy::Flag);
+        assert!(result.is_ok());
+        assert_eq!(4, state);
+        assert!(individual.exceeded_host_call_budget());
+    }
+
+    #[test]
+    fn memoized_imports_are_called_once_per_argument() {
+        let config = WorldConfiguration {
+            main_entry_point: FunctionSignature::new("main", vec![ValueType::I32], vec![]),
+            trace_host_calls: true,
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<u32, EmptyRunResult>::new(config).unwrap();
+        let options = ImportOptions {
+            memoize: true,
+            ..ImportOptions::default()
+        };
+        let add = world
+            .add_function_import_with_options("add_to_state", add_to_state, options)
+            .unwrap();
+
+        let code = vec![
+            Call::new(add, vec![0], vec![1]),
+            Call::new(add, vec![0], vec![2]),
+            Call::new(add, vec![1], vec![3]),
+            Call::new(add, vec![0], vec![4]),
+        ];
+        let instance_pre = world.instanciate_pre(&code[..]).unwrap();
+        let mut individual = Individual::<u32, EmptyRunResult>::new(0, code, "main".into(), instance_pre, 250, 1, 0);
+
+        // The second and last calls repeat the arguments of the first, so they return its result without reaching the
+        // host
+        let (state, result) = individual.execute::<i32, ()>(1, 5);
+        assert!(result.is_ok());
+        assert_eq!(12, state);
+        let results: Vec<_> = individual
+            .get_host_call_trace()
+            .iter()
+            .map(|call| call.results.clone().unwrap())
+            .collect();
+        assert_eq!(
+            vec![
+                vec![SlotValue::I32(6)],
+                vec![SlotValue::I32(6)],
+                vec![SlotValue::I32(12)],
+                vec![SlotValue::I32(6)]
+            ],
+            results
+        );
+
+        // The memo does not outlive the evaluation
+        let (state, result) = individual.execute::<i32, ()>(100, 5);
+        assert!(result.is_ok());
+        assert_eq!(210, state);
+    }
+
+    #[test]
+    fn host_calls_are_not_traced_by_default() {
+        let config = WorldConfiguration {
+            main_entry_point: FunctionSignature::new("main", vec![ValueType::I32], vec![]),
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<u32, EmptyRunResult>::new(config).unwrap();
+        let add = world.add_function_import("add_to_state", add_to_state).unwrap();
+
+        let code = vec![Call::new(add, vec![0], vec![1])];
+        let instance_pre = world.instanciate_pre(&code[..]).unwrap();
+        let mut individual = Individual::<u32, EmptyRunResult>::new(0, code, "main".into(), instance_pre, 250, 1, 0);
+        let (state, result) = individual.execute::<i32, ()>(5, 3);
+        assert!(result.is_ok());
+        assert_eq!(8, state);
+        assert!(individual.get_host_call_trace().is_empty());
+    }
+
+    #[test]
+    fn broadcast_best_sends_the_best_of_each_island_to_every_other_island() {
+        let config = WorldConfiguration {
+            individuals_per_island: 5,
+            individual_max_points: 10,
+            generations_between_migrations: 1,
+            number_of_individuals_migrating: 2,
+            clone_migrated_individuals: false,
+            migration_algorithm: MigrationAlgorithm::BroadcastBest,
+            ..WorldConfiguration::default()
+        };
+        let mut world = world_with_islands(config, 4);
+
+        world.fill_all_islands().unwrap();
+        world.run_one_generation();
+
+        assert_eq!(12, world.migration_log().len());
+        for id in 0..4 {
+            let island = world.get_island(id).unwrap();
+            let best_id = island.most_fit_individual().unwrap().get_id();
+
+            // The best individual is never removed from its home island
+            assert_eq!(5, island.len());
+            assert_eq!(3, island.generation_stats().emigrants);
+            assert_eq!(3, world.migrations_from_island(id).count());
+            assert!(world
+                .migrations_from_island(id)
+                .all(|event| event.individual_id == best_id));
+
+            let mut sources: Vec<IslandId> = world.migrations_to_island(id).map(|event| event.source).collect();
+            sources.sort();
+            let expected: Vec<IslandId> = (0..4).filter(|source| *source != id).collect();
+            assert_eq!(expected, sources);
+        }
+    }
+
+    // An engine that only writes constants, to slots that are out of range half of the time
+    fn engine_with_unbuildable_children() -> GeneticEngine {
+        let mut engine = GeneticEngine::new(GeneticEngineConfiguration::new(Some(1), 20));
+        engine.reset_all_code_weights(0);
+        engine.set_code_weight(Code::ConstOne(ConstOne::default()), 1);
+        engine
+    }
+
+    #[test]
+    fn children_that_fail_to_build_are_replaced() {
+        for breeding_threads in [1, 3] {
+            let config = WorldConfiguration {
+                individuals_per_island: 30,
+                individual_max_points: 1,
+                breeding_threads,
+                max_child_build_retries: 30,
+                ..WorldConfiguration::default()
+            };
+            let mut world = world_with_islands(config, 1);
+            world.genetic_engine = engine_with_unbuildable_children();
+            world.fill_all_islands().unwrap();
+
+            let island = world.get_island(0).unwrap();
+            assert_eq!(30, island.len());
+            let stats = island.generation_stats();
+            assert!(stats.failed_children > 0);
+            assert!(stats.last_child_error.as_ref().unwrap().contains("out of range"));
+        }
+    }
+
+    #[test]
+    fn filling_fails_after_too_many_children_in_a_row_fail_to_build() {
+        let config = WorldConfiguration {
+            individuals_per_island: 30,
+            individual_max_points: 1,
+            max_child_build_retries: 0,
+            ..WorldConfiguration::default()
+        };
+        let mut world = world_with_islands(config, 1);
+        world.genetic_engine = engine_with_unbuildable_children();
+        let error = world.fill_all_islands().unwrap_err();
+        assert!(error.downcast_ref::<crate::CodeDiagnostic>().is_some());
+        assert!(error.to_string().contains("children failed to build 1 times in a row"));
+    }
+
+    // Records how long the run took and whether it completed or was stopped
+    #[derive(Clone, Default)]
+    struct TimeTheRun {
+        elapsed: std::time::Duration,
+        completed: bool,
+    }
+
+    impl IslandCallbacks<(), EmptyRunResult> for TimeTheRun {
+        fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+            Box::new(Clone::clone(self))
+        }
+
+        fn run_individual(&mut self, individual: &mut Individual<(), EmptyRunResult>) {
+            let started = std::time::Instant::now();
+            let ((), result) = individual.execute::<(), ()>((), ());
+            self.elapsed = started.elapsed();
+            self.completed = result.is_ok();
+        }
+    }
+
+    #[test]
+    fn runs_inside_the_time_limit_complete_and_runs_over_it_are_cut_off() {
+        let config = WorldConfiguration {
+            individual_run_time_ms: 2,
+            epoch_ticks_per_ms: 20,
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+        let quick = [ConstZero::new(0)];
+        let forever = [DoUntil::new(0, vec![ConstZero::new(0)])];
+        for _ in 0..20 {
+            let mut timer = TimeTheRun::default();
+            world.evaluate(&quick[..], &mut timer).unwrap();
+            assert!(timer.completed);
+
+            // A late tick only gives the run more time, so no run is stopped before its two milliseconds are up
+            let mut timer = TimeTheRun::default();
+            world.evaluate(&forever[..], &mut timer).unwrap();
+            assert!(!timer.completed);
+            assert!(
+                timer.elapsed >= std::time::Duration::from_millis(2),
+                "{:?}",
+                timer.elapsed
+            );
+            assert!(timer.elapsed < std::time::Duration::from_secs(1), "{:?}", timer.elapsed);
+        }
+
+        let config = WorldConfiguration {
+            epoch_ticks_per_ms: 0,
+            ..WorldConfiguration::default()
+        };
+        assert!(World::<(), EmptyRunResult>::new(config).is_err());
+    }
+
+    #[test]
+    fn fuel_and_run_time_limits_report_which_one_stopped_the_code() {
+        let forever = [DoUntil::new(0, vec![ConstZero::new(0)])];
+        let run_forever = |config: WorldConfiguration| {
+            let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+            let (mut individual, _) = world.evaluate(&forever[..], &mut DoNothing {}).unwrap();
+            let ((), result) = individual.execute::<(), ()>((), ());
+            assert!(result.is_err());
+            individual.get_exceeded_limit()
+        };
+
+        // Fuel runs out long before the time does
+        let fuel = WorldConfiguration {
+            individual_run_time_ms: 10_000,
+            fuel_per_evaluation: Some(10_000),
+            ..WorldConfiguration::default()
+        };
+        assert_eq!(Some(EvaluationLimit::Fuel), run_forever(fuel.clone()));
+        let world = World::<(), EmptyRunResult>::new(fuel).unwrap();
+        assert_eq!(Some(10_000), world.security_report().fuel_limit);
+
+        // The time runs out long before the fuel does
+        let time = WorldConfiguration {
+            individual_run_time_ms: 5,
+            fuel_per_evaluation: Some(1 << 40),
+            ..WorldConfiguration::default()
+        };
+        assert_eq!(Some(EvaluationLimit::RunTime), run_forever(time));
+
+        let config = WorldConfiguration {
+            fuel_per_evaluation: Some(0),
+            ..WorldConfiguration::default()
+        };
+        assert!(World::<(), EmptyRunResult>::new(config).is_err());
+    }
+
+    #[test]
+    fn the_wasm_stack_limit_reaches_the_engine() {
+        let config = WorldConfiguration {
+            max_wasm_stack: Some(64 << 10),
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<(), EmptyRunResult>::new(config.clone()).unwrap();
+        assert_eq!(Some(64 << 10), world.security_report().max_wasm_stack);
+        let (mut individual, _) = world.evaluate(&[ConstZero::new(0)], &mut DoNothing {}).unwrap();
+        let ((), result) = individual.execute::<(), ()>((), ());
+        assert!(result.is_ok());
+
+        let bigger = WorldConfiguration {
+            max_wasm_stack: Some(128 << 10),
+            ..config
+        };
+        assert!(world.migrate_configuration(bigger, |_| None).is_err());
+        let zero = WorldConfiguration {
+            max_wasm_stack: Some(0),
+            ..WorldConfiguration::default()
+        };
+        assert!(World::<(), EmptyRunResult>::new(zero).is_err());
+    }
+
+    #[cfg(feature = "wasi")]
+    #[test]
+    fn wasi_commands_read_arguments_and_print_results() {
+        use wasmtime::{Caller, Linker, Memory, Store};
+
+        let config = WorldConfiguration {
+            main_entry_point: FunctionSignature::new(
+                "main",
+                vec![ValueType::I32, ValueType::I32],
+                vec![ValueType::I32],
+            ),
+            is_signed: true,
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+        let binary = world.wasi_command_binary(&[Add::new(0, 1, 2)]).unwrap();
+
+        // Just enough of WASI to run the command, with stdout collected in the store
+        let memory = |caller: &mut Caller<'_, Vec<u8>>| -> Memory {
+            caller.get_export("memory").unwrap().into_memory().unwrap()
+        };
+        const ARGUMENTS: &[&[u8]] = &[b"prog\0", b"3\0", b"-4\0"];
+        let mut linker = Linker::<Vec<u8>>::new(world.wasm_engine());
+        linker
+            .func_wrap(
+                "wasi_snapshot_preview1",
+                "args_sizes_get",
+                move |mut caller: Caller<'_, Vec<u8>>, argc: i32, size: i32| -> i32 {
+                    let memory = memory(&mut caller);
+                    let total: usize = ARGUMENTS.iter().map(|a| a.len()).sum();
+                    memory
+                        .write(&mut caller, argc as usize, &(ARGUMENTS.len() as u32).to_le_bytes())
+                        .unwrap();
+                    memory
+                        .write(&mut caller, size as usize, &(total as u32).to_le_bytes())
+                        .unwrap();
+                    0
+                },
+            )
+            .unwrap();
+        linker
+            .func_wrap(
+                "wasi_snapshot_preview1",
+                "args_get",
+                move |mut caller: Caller<'_, Vec<u8>>, argv: i32, buffer: i32| -> i32 {
+                    let memory = memory(&mut caller);
+                    let mut offset = buffer as usize;
+                    for (index, argument) in ARGUMENTS.iter().enumerate() {
+                        let pointer = (argv as usize) + 4 * index;
+                        memory
+                            .write(&mut caller, pointer, &(offset as u32).to_le_bytes())
+                            .unwrap();
+                        memory.write(&mut caller, offset, argument).unwrap();
+                        offset += argument.len();
+                    }
+                    0
+                },
+            )
+            .unwrap();
+        linker
+            .func_wrap(
+                "wasi_snapshot_preview1",
+                "fd_write",
+                move |mut caller: Caller<'_, Vec<u8>>, fd: i32, iovs: i32, iovs_len: i32, written: i32| -> i32 {
+                    assert_eq!(1, fd);
+                    let memory = memory(&mut caller);
+                    let mut total = 0;
+                    for index in 0..iovs_len as usize {
+                        let mut iovec = [0u8; 8];
+                        memory.read(&caller, iovs as usize + 8 * index, &mut iovec).unwrap();
+                        let pointer = u32::from_le_bytes(iovec[..4].try_into().unwrap()) as usize;
+                        let length = u32::from_le_bytes(iovec[4..].try_into().unwrap()) as usize;
+                        let mut bytes = vec![0u8; length];
+                        memory.read(&caller, pointer, &mut bytes).unwrap();
+                        caller.data_mut().extend(bytes);
+                        total += length as u32;
+                    }
+                    memory
+                        .write(&mut caller, written as usize, &total.to_le_bytes())
+                        .unwrap();
+                    0
+                },
+            )
+            .unwrap();
+
+        let module = wasmtime::Module::new(world.wasm_engine(), &binary[..]).unwrap();
+        let mut store = Store::new(world.wasm_engine(), vec![]);
+        store.set_epoch_deadline(1000);
+        let instance = linker.instantiate(&mut store, &module).unwrap();
+        let start = instance.get_typed_func::<(), ()>(&mut store, "_start").unwrap();
+        start.call(&mut store, ()).unwrap();
+        assert_eq!(b"-1\n", &store.data()[..]);
+
+        let floats = WorldConfiguration {
+            main_entry_point: FunctionSignature::new("main", vec![ValueType::F32], vec![ValueType::F32]),
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<(), EmptyRunResult>::new(floats).unwrap();
+        assert!(world.wasi_command_binary(&[]).is_err());
+    }
+
+    #[test]
+    fn individuals_know_the_shape_of_their_module() {
+        let config = WorldConfiguration {
+            main_entry_point: FunctionSignature::new("main", vec![], vec![ValueType::I32]),
+            work_slots: SlotCount {
+                i32: 1,
+                i64: 0,
+                f32: 0,
+                f64: 0,
+            },
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+        let code = [DoFor::new(3, vec![If::new(1, vec![ConstOne::new(0)])])];
+        let (individual, _) = world.evaluate(&code[..], &mut DoNothing {}).unwrap();
+
+        // The return slot, the work slot and the counter of the loop
+        let metadata = individual.get_module_metadata();
+        assert_eq!(world.wasm_binary(&code[..]).unwrap().len(), metadata.size);
+        assert_eq!(3, metadata.local_count);
+        assert_eq!(2, metadata.max_nesting_depth);
+    }
+
+    #[test]
+    fn incremental_emission_matches_the_whole_module() {
+        let config = |incremental_emission| WorldConfiguration {
+            main_entry_point: FunctionSignature::new("main", vec![ValueType::I32], vec![ValueType::I32]),
+            work_slots: SlotCount {
+                i32: 1,
+                i64: 0,
+                f32: 0,
+                f64: 0,
+            },
+            incremental_emission,
+            ..WorldConfiguration::default()
+        };
+        let mut whole = World::<u32, EmptyRunResult>::new(config(false)).unwrap();
+        let mut incremental = World::<u32, EmptyRunResult>::new(config(true)).unwrap();
+        let add = whole.add_function_import("add_to_state", add_to_state).unwrap();
+        incremental.add_function_import("add_to_state", add_to_state).unwrap();
+
+        // The parameter is slot 0, the result is slot 1 and the work slot is 2
+        let code = vec![Call::new(add, vec![0], vec![2]), Add::new(2, 0, 1)];
+        assert_eq!(
+            whole.wasm_binary(&code[..]).unwrap(),
+            incremental.wasm_binary(&code[..]).unwrap()
+        );
+
+        // Another import changes the rest of the module, so the template is emitted again
+        whole.add_function_import("fail", fail_every_time).unwrap();
+        incremental.add_function_import("fail", fail_every_time).unwrap();
+        assert_eq!(
+            whole.wasm_binary(&code[..]).unwrap(),
+            incremental.wasm_binary(&code[..]).unwrap()
+        );
+
+        let instance_pre = incremental.instanciate_pre(&code[..]).unwrap();
+        let mut individual = Individual::<u32, EmptyRunResult>::new(0, code, "main".into(), instance_pre, 250, 1, 0);
+        let (state, result) = individual.execute::<i32, i32>(5, 3);
+        assert_eq!(8, state);
+        assert_eq!(11, result.unwrap());
+    }
+
+    #[test]
+    fn execute_turns_calls_one_instance_until_the_episode_ends() {
+        let config = WorldConfiguration {
+            main_entry_point: FunctionSignature::new("main", vec![ValueType::I32], vec![ValueType::I32]),
+            work_slots: SlotCount {
+                i32: 1,
+                i64: 0,
+                f32: 0,
+                f64: 0,
+            },
+            max_turns: 3,
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+        let (mut individual, _) = world
+            .evaluate(&[ConstOne::new(2), Add::new(0, 2, 1)], &mut DoNothing {})
+            .unwrap();
+
+        // Each call adds one to the result of the last, and the episode ends at two
+        let mut seen = vec![];
+        let (_, calls) = individual.execute_turns::<u32, u32, _>((), |_, results| {
+            seen.push(results);
+            match results {
+                Some(2) => None,
+                last => Some(last.unwrap_or(0)),
+            }
+        });
+        assert_eq!(2, calls.unwrap());
+        assert_eq!(vec![None, Some(1), Some(2)], seen);
+
+        // An episode that never ends is stopped after `max_turns` calls
+        let (_, calls) = individual.execute_turns::<u32, u32, _>((), |_, results| Some(results.unwrap_or(0)));
+        assert_eq!(3, calls.unwrap());
+        assert!(individual.get_error().is_none());
+    }
+
+    #[test]
+    fn work_slots_can_be_randomized_on_every_call() {
+        let config = WorldConfiguration {
+            main_entry_point: FunctionSignature::new("main", vec![], vec![ValueType::I32, ValueType::F64]),
+            work_slots: SlotCount {
+                i32: 1,
+                i64: 0,
+                f32: 0,
+                f64: 1,
+            },
+            work_slot_initialization: SlotInit::RandomPerCall,
+            ..WorldConfiguration::default()
+        };
+
+        // Copy each work slot into a result
+        let code = [Add::new(2, 0, 0), Add::new(3, 1, 1)];
+        let run = |world: &mut World<(), EmptyRunResult>| {
+            let (mut individual, _) = world.evaluate(&code[..], &mut DoNothing {}).unwrap();
+            (0..4)
+                .map(|_| individual.execute::<(), (u32, f64)>((), ()).1.unwrap())
+                .collect::<Vec<(u32, f64)>>()
+        };
+        let values = run(&mut World::new(config.clone()).unwrap());
+        assert!(values.windows(2).all(|pair| pair[0].0 != pair[1].0));
+        assert!(values.iter().all(|(_, f)| (0.0..=1.0).contains(f)));
+
+        // The same individuals in the same order see the same values
+        assert_eq!(values, run(&mut World::new(config).unwrap()));
+    }
+
+    #[derive(Clone, Default)]
+    struct RecordTwoCalls {
+        values: Vec<u32>,
+    }
+
+    impl IslandCallbacks<(), EmptyRunResult> for RecordTwoCalls {
+        fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+            Box::new(Clone::clone(self))
+        }
+
+        fn run_individual(&mut self, individual: &mut Individual<(), EmptyRunResult>) {
+            self.values.clear();
+            for _ in 0..2 {
+                self.values.push(individual.execute::<(), u32>((), ()).1.unwrap());
+            }
+        }
     }
 
-    /// Runs the next generation across all islands.
-    #[cfg(feature = "async")]
-    pub async fn run_one_generation(&mut self) {
-        for island in self.islands.iter_mut() {
-            island.run_one_generation().await;
+    // Runs the individual with a divisor of zero, one and zero again, and a float too large for an i32 each time
+    #[derive(Clone)]
+    struct DivideByZeroTwice;
+
+    impl IslandCallbacks<(), EmptyRunResult> for DivideByZeroTwice {
+        fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+            Box::new(Clone::clone(self))
         }
 
-        // See if it is time for a migration
-        if self.config.generations_between_migrations > 0 {
-            self.generations_remaining_before_migration -= 1;
-            if self.generations_remaining_before_migration == 0 {
-                self.migrate_individuals_between_islands();
-                self.generations_remaining_before_migration = self.config.generations_between_migrations;
+        fn run_individual(&mut self, individual: &mut Individual<(), EmptyRunResult>) {
+            for divisor in [0, 1, 0] {
+                let (_, result) = individual.execute::<(i32, f32), i32>((), (divisor, 1e20));
+                result.unwrap();
             }
         }
+
+        fn score_individual(&self, _individual: &Individual<(), EmptyRunResult>) -> u64 {
+            100
+        }
     }
 
-    /// Fills all islands with the children of the genetic algorithm, or with random individuals if there was no
-    /// previous generation from which to draw upon.
-    pub fn fill_all_islands(&mut self) -> Result<()> {
-        for id in 0..self.islands.len() {
-            let mut elite_remaining = self.config.elite_individuals_per_generation;
-            while self.len_island_future_generation(id) < self.config.individuals_per_island {
-                let island = self.islands.get(id).unwrap();
-                let pick_elite = if elite_remaining > 0 {
-                    elite_remaining -= 1;
-                    true
-                } else {
-                    false
-                };
-                let next = if island.len() == 0 {
-                    let code = self.genetic_engine.random_code_list(self.config.individual_max_points);
-                    let instance_pre = self.instanciate_pre(&code[..])?;
-                    Individual::new(
-                        code,
-                        self.config.main_entry_point.name().clone(),
-                        instance_pre,
-                        self.config.individual_run_time_ms,
-                    )
-                } else {
-                    if pick_elite {
-                        let elite = island
-                            .select_one_individual(self.config.select_as_elite, self.genetic_engine.rng())
-                            .unwrap();
+    // Scores one when the code adds its parameters
+    #[derive(Clone)]
+    struct AddsTwoAndThree;
 
-                        elite.clone()
-                    } else {
-                        let left = island
-                            .select_one_individual(self.config.select_as_parent, self.genetic_engine.rng())
-                            .unwrap();
-                        let right = island
-                            .select_one_individual(self.config.select_as_parent, self.genetic_engine.rng())
-                            .unwrap();
-                        let code = self.genetic_engine.rand_child(left.get_code(), right.get_code())?;
-                        let instance_pre = self.instanciate_pre(&code[..])?;
-                        Individual::new(
-                            code,
-                            self.config.main_entry_point.name().clone(),
-                            instance_pre,
-                            self.config.individual_run_time_ms,
-                        )
-                    }
-                };
-                self.add_individual_to_island_future_generation(id, next);
+    impl IslandCallbacks<(), EmptyRunResult> for AddsTwoAndThree {
+        fn clone(&self) -> Box<dyn IslandCallbacks<(), EmptyRunResult>> {
+            Box::new(Clone::clone(self))
+        }
+
+        fn run_individual(&mut self, individual: &mut Individual<(), EmptyRunResult>) {
+            let ((), result) = individual.execute::<(i32, i32), i32>((), (2, 3));
+            if result.ok() == Some(5) {
+                individual.set_run_result(Some(EmptyRunResult {}));
             }
+        }
 
-            // Now that the future generation is full, make it the current generation
-            self.advance_island_generation(id);
+        fn score_individual(&self, individual: &Individual<(), EmptyRunResult>) -> u64 {
+            individual.get_run_result().map_or(0, |_| 1)
         }
+    }
 
-        Ok(())
+    #[test]
+    fn champions_are_reduced_to_the_code_that_scores() {
+        let config = WorldConfiguration {
+            main_entry_point: FunctionSignature::new(
+                "main",
+                vec![ValueType::I32, ValueType::I32],
+                vec![ValueType::I32],
+            ),
+            ..WorldConfiguration::default()
+        };
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+        let champion = [
+            DoFor::new(2, vec![Add::new(0, 1, 2)]),
+            ConstOne::new(0),
+            ConstOne::new(1),
+        ];
+        let reduced = world
+            .reduce_champion(&champion, &mut AddsTwoAndThree, |_, score| score == 1)
+            .unwrap();
+        assert_eq!(vec![Add::new(0, 1, 2)], reduced);
+        assert!(world
+            .reduce_champion(&[ConstOne::new(2)], &mut AddsTwoAndThree, |_, score| score == 1)
+            .is_err());
     }
 
-    fn len_island_future_generation(&self, id: IslandId) -> usize {
-        self.islands.get(id).unwrap().len_future_generation()
+    #[test]
+    fn numeric_events_are_counted_for_each_evaluation() {
+        let config = WorldConfiguration {
+            main_entry_point: FunctionSignature::new(
+                "main",
+                vec![ValueType::I32, ValueType::F32],
+                vec![ValueType::I32],
+            ),
+            numeric_telemetry: true,
+            ..WorldConfiguration::default()
+        };
+        // Both operands of the Add are saturated when the float is converted to an i32
+        let code = [Divide::new(2, 0, 2), Add::new(1, 1, 2)];
+        let mut world = World::<(), EmptyRunResult>::new(config.clone()).unwrap();
+        let (mut individual, _) = world.evaluate(&code[..], &mut DivideByZeroTwice).unwrap();
+        let expected = NumericEvents {
+            divisions_by_zero: 2,
+            saturated_conversions: 6,
+        };
+        assert_eq!(expected, individual.get_numeric_events());
+
+        let id = world.create_island(Box::new(DivideByZeroTwice));
+        world.get_island_mut(id).unwrap().set_numeric_event_penalty(10);
+        assert_eq!(20, world.get_island(id).unwrap().score_individual(&individual));
+
+        individual.begin_evaluation();
+        assert_eq!(NumericEvents::default(), individual.get_numeric_events());
+
+        let mut quiet = World::<(), EmptyRunResult>::new(WorldConfiguration {
+            numeric_telemetry: false,
+            ..config
+        })
+        .unwrap();
+        let (individual, _) = quiet.evaluate(&code[..], &mut DivideByZeroTwice).unwrap();
+        assert_eq!(0, individual.get_numeric_events().total());
     }
 
-    fn add_individual_to_island_future_generation(&mut self, id: IslandId, individual: Individual<T, R>) {
-        self.islands
-            .get_mut(id)
-            .unwrap()
-            .add_individual_to_future_generation(individual)
+    #[test]
+    fn random_import_repeats_for_each_evaluation() {
+        let config = WorldConfiguration {
+            main_entry_point: FunctionSignature::new("main", vec![], vec![ValueType::I32]),
+            seed: Some(5),
+            random_import: true,
+            ..WorldConfiguration::default()
+        };
+        let code = [Call::new(0, vec![], vec![0])];
+        let run = |config: WorldConfiguration| {
+            let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+            let mut callbacks = RecordTwoCalls::default();
+            let (mut individual, _) = world.evaluate(&code[..], &mut callbacks).unwrap();
+            let first = callbacks.values.clone();
+            individual.begin_evaluation();
+            callbacks.run_individual(&mut individual);
+            assert_eq!(first, callbacks.values);
+            first
+        };
+
+        let values = run(config.clone());
+        assert_ne!(values[0], values[1]);
+        assert_eq!(values, run(config.clone()));
+        assert_ne!(
+            values,
+            run(WorldConfiguration {
+                seed: Some(6),
+                ..config
+            })
+        );
     }
 
-    fn advance_island_generation(&mut self, id: IslandId) {
-        self.islands.get_mut(id).unwrap().advance_generation()
+    #[test]
+    fn worlds_can_share_an_engine() {
+        let config = WorldConfiguration {
+            individuals_per_island: 5,
+            individual_max_points: 10,
+            ..WorldConfiguration::default()
+        };
+        let mut first = world_with_islands(config.clone(), 1);
+        let mut second = World::<(), EmptyRunResult>::with_engine(first.wasm_engine().clone(), config).unwrap();
+        second.create_island(Box::new(DoNothing {}));
+        for world in [&mut first, &mut second] {
+            world
+                .run_generations_while(|world| world.generations_complete() < 2)
+                .unwrap();
+        }
+        assert!(wasmtime::Engine::same(first.wasm_engine(), second.wasm_engine()));
     }
 
-    /// Runs generations until the specified function returns false
-    #[cfg(not(feature = "async"))]
-    pub fn run_generations_while<While>(&mut self, mut while_fn: While) -> Result<()>
-    where
-        While: FnMut(&World<T, R>) -> bool,
-    {
-        // Always run at least one generation
-        let mut running = true;
-        while running {
-            self.fill_all_islands()?;
-            self.run_one_generation();
-            running = while_fn(self);
+    #[test]
+    fn random_individuals_use_the_initial_max_points() {
+        let config = WorldConfiguration {
+            individuals_per_island: 20,
+            individual_max_points: 50,
+            initial_max_points: Some(3),
+            ..WorldConfiguration::default()
+        };
+        let mut world = world_with_islands(config, 1);
+        world.fill_all_islands().unwrap();
+
+        let island = world.get_island(0).unwrap();
+        for index in 0..island.len() {
+            let points: usize = island
+                .get_one_individual(index)
+                .unwrap()
+                .get_code()
+                .iter()
+                .map(|c| c.points())
+                .sum();
+            assert!(points <= 3);
         }
 
-        Ok(())
+        let config = WorldConfiguration {
+            initial_max_points: Some(0),
+            ..WorldConfiguration::default()
+        };
+        assert!(World::<(), EmptyRunResult>::new(config).is_err());
     }
 
-    /// Runs generations until the specified function returns false
-    #[cfg(feature = "async")]
-    pub async fn run_generations_while<While>(&mut self, mut while_fn: While) -> Result<()>
-    where
-        While: FnMut(&World<T, R>) -> bool,
-    {
-        // Always run at least one generation
-        let mut running = true;
-        while running {
-            self.fill_all_islands()?;
-            self.run_one_generation().await;
-            running = while_fn(self);
-        }
+    #[test]
+    fn ramped_half_and_half_alternates_full_and_grown_programs() {
+        for breeding_threads in [1, 3] {
+            let config = WorldConfiguration {
+                individuals_per_island: 10,
+                initial_max_points: Some(20),
+                initialization: Initialization::RampedHalfAndHalf { min_points: 2 },
+                breeding_threads,
+                ..WorldConfiguration::default()
+            };
+            let mut world = world_with_islands(config, 1);
+            world.fill_all_islands().unwrap();
 
-        Ok(())
+            // The first generation has not been sorted, so the individuals are in the order they were made
+            let island = world.get_island(0).unwrap();
+            for index in 0..island.len() {
+                let code = island.get_one_individual(index).unwrap().get_code();
+                let points: usize = code.iter().map(|c| c.points()).sum();
+                let target = 2 + 18 * index / 9;
+                if index % 2 == 0 {
+                    assert_eq!(target, points);
+                } else {
+                    assert!(points <= target);
+                }
+            }
+        }
     }
 
-    pub fn migrate_individuals_between_islands(&mut self) {
-        let island_len = self.islands.len();
+    #[test]
+    fn warm_up_generations_breed_without_crossover() {
+        let config = WorldConfiguration {
+            individuals_per_island: 10,
+            individual_max_points: 5,
+            mutation_rate: 0,
+            crossover_rate: 1,
+            ..WorldConfiguration::default()
+        };
+        let mut world = world_with_islands(config, 3);
+        world
+            .get_island_mut(0)
+            .unwrap()
+            .set_warm_up(Some(WarmUp::MutationOnly { generations: 1 }));
+        world
+            .get_island_mut(1)
+            .unwrap()
+            .set_warm_up(Some(WarmUp::RandomImmigrants { generations: 1 }));
 
-        // It only makes sense to migrate if there are at least two islands
-        if island_len > 1 {
-            match self.config.migration_algorithm {
-                MigrationAlgorithm::Circular => self.migrate_all_islands_circular_n(1),
-                MigrationAlgorithm::Cyclical(n) => self.migrate_all_islands_circular_n(n),
-                MigrationAlgorithm::Incremental(n) => {
-                    self.migrate_all_islands_circular_n(n);
+        // The first generation only writes ones, and everything bred after it only writes zeros
+        world.reset_all_code_weights(0);
+        world.set_code_weight(Code::ConstOne(ConstOne::default()), 1);
+        world.fill_all_islands().unwrap();
+        world.run_one_generation();
+        world.reset_all_code_weights(0);
+        world.set_code_weight(Code::ConstZero(ConstZero::default()), 1);
+        let first_new_id = world.next_individual_id;
+        world.fill_all_islands().unwrap();
 
-                    // Increment 'n'. An 'n' of zero makes no sense, so when it gets there use '1' instead.
-                    let mut next_n = self.island_at_distance(0, n + 1);
-                    if next_n == 0 {
-                        next_n = 1
-                    }
-                    self.config.migration_algorithm = MigrationAlgorithm::Incremental(next_n);
-                }
-                MigrationAlgorithm::RandomCircular => {
-                    // Define a new order of islands and calculate the distance to the next island in this new order.
-                    // For example, if there are 7 islands and the order starts with 2, 3: the first distance is 1.
-                    // However if the order starts with 3, 2: the first distance is 6
-                    //
-                    // This algorithm achieves the desired goal of having individuals from each island migrate together
-                    // to another random island, and each island is the source and destination exactly once.
-                    let island_order = self.random_island_order();
-                    let distances = World::<T, R>::distances_to_next_island(&island_order[..]);
-                    for (source_id, n) in std::iter::zip(island_order, distances) {
-                        self.migrate_one_island_circular_n(source_id, n);
-                    }
-                }
-                MigrationAlgorithm::CompletelyRandom => {
-                    let len = self.islands.len();
+        let zeros_in_children = |island: &Island<(), EmptyRunResult>| -> Vec<(usize, usize)> {
+            (0..island.len())
+                .map(|index| island.get_one_individual(index).unwrap())
+                .filter(|child| child.get_id() >= first_new_id)
+                .map(|child| {
+                    let zeros = child
+                        .get_code()
+                        .iter()
+                        .filter(|code| code.name() == "ConstZero")
+                        .count();
+                    (zeros, child.get_code().len())
+                })
+                .collect()
+        };
 
-                    // For each migrating individual on each island, pick a random destination that is not the same
-                    // island and migrate there.
-                    for source_island_id in 0..len {
-                        for _ in 0..self.config.number_of_individuals_migrating {
-                            let mut destination_island_id = source_island_id;
-                            while source_island_id != destination_island_id {
-                                destination_island_id = self.genetic_engine.rng().gen_range(0..len);
-                            }
-                            self.migrate_one_individual_from_island_to_island(source_island_id, destination_island_id);
-                        }
-                    }
+        // A mutation of one point, a whole new program, and a crossover of two programs of ones
+        assert!(zeros_in_children(world.get_island(0).unwrap())
+            .iter()
+            .all(|(zeros, _)| *zeros == 1));
+        assert!(zeros_in_children(world.get_island(1).unwrap())
+            .iter()
+            .all(|(zeros, len)| zeros == len));
+        assert!(zeros_in_children(world.get_island(2).unwrap())
+            .iter()
+            .all(|(zeros, _)| *zeros == 0));
+
+        // The warm-up is over once the island has been bred for its generations
+        let island = world.get_island(0).unwrap();
+        assert_eq!(WarmUp::None, World::active_warm_up(&world.config, island));
+    }
+
+    #[test]
+    fn islands_can_be_filled_on_several_threads() {
+        let config = WorldConfiguration {
+            individuals_per_island: 10,
+            individual_max_points: 10,
+            breeding_threads: 3,
+            ..WorldConfiguration::default()
+        };
+        let mut world = world_with_islands(config, 2);
+        world.get_island_mut(1).unwrap().set_team_size(2);
+
+        for generation in 0..3 {
+            let first_new_id = world.next_individual_id;
+            world.fill_all_islands().unwrap();
+            let mut ids = vec![];
+            for id in 0..2 {
+                let island = world.get_island(id).unwrap();
+                assert_eq!(10, island.len());
+                for index in 0..island.len() {
+                    let individual = island.get_one_individual(index).unwrap();
+                    assert_eq!(id + 1, individual.team_size());
+                    ids.push(individual.get_id());
                 }
             }
+
+            // The elites are carried over, but every other individual is new and has its own ID
+            let mut new_ids: Vec<IndividualId> = ids.into_iter().filter(|id| *id >= first_new_id).collect();
+            new_ids.sort();
+            new_ids.dedup();
+            assert_eq!(if generation == 0 { 20 } else { 16 }, new_ids.len());
+            world.run_one_generation();
         }
     }
 
-    fn migrate_one_individual_from_island_to_island(
-        &mut self,
-        source_island_id: IslandId,
-        destination_island_id: IslandId,
-    ) {
-        let curve = self.config.select_for_migration;
-
-        // Get the migrating individual from the source island
-        let source_island = self.islands.get_mut(source_island_id).unwrap();
-        let migrating: Individual<T, R> = if self.config.clone_migrated_individuals {
-            source_island
-                .select_one_individual(curve, self.genetic_engine.rng())
-                .unwrap()
-                .clone()
-        } else {
-            source_island
-                .select_and_remove_one_individual(curve, self.genetic_engine.rng())
-                .unwrap()
+    #[test]
+    fn every_individual_stays_within_the_max_cost() {
+        let config = WorldConfiguration {
+            individuals_per_island: 20,
+            individual_max_points: 30,
+            individual_max_cost: Some(40),
+            ..WorldConfiguration::default()
         };
+        let model = config.cost_model.clone();
+        let mut world = world_with_islands(config, 1);
+        world.fill_all_islands().unwrap();
+        for _ in 0..5 {
+            world.run_one_generation();
+            let island = world.get_island(0).unwrap();
+            for index in 0..island.len() {
+                let code = island.get_one_individual(index).unwrap().get_code();
+                assert!(model.code_cost(code) <= 40);
+            }
+        }
 
-        // Add it to the destination island
-        let destination_island = self.islands.get_mut(destination_island_id).unwrap();
-        destination_island.add_individual_to_future_generation(migrating);
+        let config = WorldConfiguration {
+            individual_max_cost: Some(0),
+            ..WorldConfiguration::default()
+        };
+        assert!(World::<(), EmptyRunResult>::new(config).is_err());
     }
+}
 
-    // Calculates the ID of the island at a specific distance from the source. Wraps around when we get to the end of
-    // the list.
-    fn island_at_distance(&self, source_id: IslandId, distance: usize) -> IslandId {
-        (source_id + distance) % self.islands.len()
+#[cfg(all(test, feature = "async"))]
+mod async_tests {
+    use crate::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[derive(Clone)]
+    struct CountRuns {
+        runs: Arc<AtomicUsize>,
     }
 
-    fn migrate_all_islands_circular_n(&mut self, n: usize) {
-        for source_island_id in 0..self.islands.len() {
-            self.migrate_one_island_circular_n(source_island_id, n);
+    #[async_trait::async_trait]
+    impl AsyncIslandCallbacks<(), EmptyRunResult> for CountRuns {
+        fn clone(&self) -> Box<dyn AsyncIslandCallbacks<(), EmptyRunResult>> {
+            Box::new(CountRuns {
+                runs: self.runs.clone(),
+            })
         }
-    }
 
-    fn migrate_one_island_circular_n(&mut self, source_island_id: IslandId, n: usize) {
-        let destination_island_id = self.island_at_distance(source_island_id, n);
-        for _ in 0..self.config.number_of_individuals_migrating {
-            self.migrate_one_individual_from_island_to_island(source_island_id, destination_island_id);
+        async fn run_individual(&mut self, individual: &mut Individual<(), EmptyRunResult>) {
+            // Random code may well loop until it runs out of time, so only the fact that it finished matters here
+            let _ = individual.execute_async::<(), ()>((), ()).await;
+            self.runs.fetch_add(1, Ordering::SeqCst);
         }
     }
 
-    // Creates a Vec containing the source_id of each island exactly one time
-    fn random_island_order(&mut self) -> Vec<IslandId> {
-        let mut island_ids: Vec<IslandId> = (0..self.islands.len()).collect();
-        island_ids.shuffle(self.genetic_engine.rng());
+    #[test]
+    fn evaluates_concurrently_until_cancelled() {
+        let config = WorldConfiguration {
+            individuals_per_island: 6,
+            individual_max_points: 10,
+            individual_run_time_ms: 20,
+            max_in_flight: 4,
+            async_epoch_yielding: true,
+            ..WorldConfiguration::default()
+        };
+        let runs = Arc::new(AtomicUsize::new(0));
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+        world.create_async_island(Box::new(CountRuns { runs: runs.clone() }));
+        world.create_async_island(Box::new(CountRuns { runs: runs.clone() }));
+        let token = world.cancellation_token();
 
-        island_ids
+        let result = futures::executor::block_on(world.run_generations_while(|world| {
+            if world.generations_complete() == 2 {
+                token.cancel();
+            }
+            true
+        }));
+        assert!(result.is_err());
+        assert_eq!(2, world.generations_complete());
+        assert_eq!(24, runs.load(Ordering::SeqCst));
     }
 
-    // Creates a Vec containing the distance to the previous island in the list for every entry in the parameter. The
-    // distance for the first entry wraps around to the last item.
-    fn distances_to_next_island(island_id: &[IslandId]) -> Vec<IslandId> {
-        let len = island_id.len();
-        let mut distances = Vec::with_capacity(len);
-        let mut previous_source_id = island_id.last().unwrap();
-        for source_id in island_id.iter() {
-            let distance = ((previous_source_id + len) - source_id) % len;
-            distances.push(distance);
-            previous_source_id = source_id;
+    #[derive(Clone)]
+    struct CancelOnRun {
+        runs: Arc<AtomicUsize>,
+        cancel_on: usize,
+        token: CancellationToken,
+    }
+
+    #[async_trait::async_trait]
+    impl AsyncIslandCallbacks<(), EmptyRunResult> for CancelOnRun {
+        fn clone(&self) -> Box<dyn AsyncIslandCallbacks<(), EmptyRunResult>> {
+            Box::new(Clone::clone(self))
         }
 
-        distances
+        async fn run_individual(&mut self, individual: &mut Individual<(), EmptyRunResult>) {
+            let _ = individual.execute_async::<(), ()>((), ()).await;
+            if self.runs.fetch_add(1, Ordering::SeqCst) + 1 == self.cancel_on {
+                self.token.cancel();
+            }
+        }
+    }
+
+    #[test]
+    fn a_generation_cancelled_part_way_is_run_again_after_resuming() {
+        let config = WorldConfiguration {
+            individuals_per_island: 6,
+            individual_max_points: 10,
+            individual_run_time_ms: 20,
+            async_epoch_yielding: true,
+            ..WorldConfiguration::default()
+        };
+        let runs = Arc::new(AtomicUsize::new(0));
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+        for _ in 0..2 {
+            world.create_async_island(Box::new(CancelOnRun {
+                runs: runs.clone(),
+                cancel_on: 15,
+                token: world.cancellation_token(),
+            }));
+        }
+
+        // The second generation is cancelled after the third individual of the first island, before the second island
+        // has started
+        let result = futures::executor::block_on(world.run_generations_while(|_| true));
+        assert!(result.is_err());
+        assert_eq!(1, world.generations_complete());
+        assert_eq!(15, runs.load(Ordering::SeqCst));
+        for id in 0..2 {
+            let island = world.get_island(id).unwrap();
+            assert_eq!(1, island.generations());
+            assert_eq!(6, island.len_future_generation());
+        }
+
+        // The abandoned generation is run again in full before the one after it
+        world.set_cancellation_token(CancellationToken::new());
+        futures::executor::block_on(world.run_generations_while(|world| world.generations_complete() < 3)).unwrap();
+        assert_eq!(3, world.generations_complete());
+        assert_eq!(15 + 24, runs.load(Ordering::SeqCst));
+        for id in 0..2 {
+            assert_eq!(3, world.get_island(id).unwrap().generations());
+            assert!(world.get_island(id).unwrap().most_fit_individual().is_some());
+        }
+    }
+
+    #[test]
+    fn evaluates_in_time_slices() {
+        let config = WorldConfiguration {
+            individuals_per_island: 8,
+            individual_max_points: 10,
+            individual_run_time_ms: 20,
+            max_in_flight: 3,
+            async_epoch_yielding: true,
+            evaluation_slice_ms: Some(2),
+            ..WorldConfiguration::default()
+        };
+        let runs = Arc::new(AtomicUsize::new(0));
+        let mut world = World::<(), EmptyRunResult>::new(config).unwrap();
+        world.create_async_island(Box::new(CountRuns { runs: runs.clone() }));
+        futures::executor::block_on(world.run_generations_while(|world| world.generations_complete() < 2)).unwrap();
+        assert_eq!(2, world.generations_complete());
+        assert_eq!(16, runs.load(Ordering::SeqCst));
+
+        let config = WorldConfiguration {
+            evaluation_slice_ms: Some(2),
+            ..WorldConfiguration::default()
+        };
+        assert!(World::<(), EmptyRunResult>::new(config).is_err());
     }
 }
@@ -1,4 +1,9 @@
-use crate::{FunctionSignature, MigrationAlgorithm, SelectionCurve, SlotCount, SlotInit, ThreadingModel};
+use crate::{
+    CallLimit, CheckpointSchedule, CodeWeightAdaptation, ConversionProfile, CostModel, CrossoverCuts,
+    FitnessCaseSchedule, FunctionSignature, Initialization, IslandSizing, LocalSearch, MigrationAlgorithm,
+    MigrationPayload, ParentPairing, SelectionCurve, SelectionStrategy, SlotCount, SlotInit, SubtreeArchive,
+    ThreadingModel, WarmUp,
+};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct WorldConfiguration {
@@ -34,11 +39,123 @@ pub struct WorldConfiguration {
     /// The default is 250ms
     pub individual_run_time_ms: u64,
 
+    /// The number of times per millisecond that the world's clock ticks. Run times are counted in ticks, and a run is
+    /// given one tick more than its run time, because the first tick may come just after it starts, so a run may last
+    /// up to one tick longer than `individual_run_time_ms` but never ends before it. With one tick per millisecond a
+    /// run time of 1 or 2 may be nearly doubled; ten ticks per millisecond make it accurate to a tenth of a
+    /// millisecond, at the cost of a busier clock thread. Must be between 1 and 1000.
+    ///
+    /// The default is 1
+    pub epoch_ticks_per_ms: u64,
+
+    /// When set, each evaluation may also use at most this much fuel, which wasmtime burns at roughly one unit for
+    /// every instruction it runs. The run time keeps the host safe from code that runs too long, while fuel stops the
+    /// code after the same amount of work on every machine, so that fitness comparisons do not depend on how fast or
+    /// busy the machine is. Whichever limit is reached first stops the code, and `EvaluationError::exceeded_limit`
+    /// tells which one it was. Metering fuel slows down the code a little. Must not be zero.
+    ///
+    /// The default is None
+    pub fuel_per_evaluation: Option<u64>,
+
+    /// When set, the bytes of native stack that the Wasm code of an evaluation may use. Code that calls deeper than the
+    /// stack allows is stopped with `Trap::StackOverflow`, and `EvaluationError::exceeded_limit` reports it as the
+    /// call depth, instead of overflowing the stack of the thread. Evolved code can only call the imports of the World
+    /// for now, so its call depth is bounded, but this is the guard for code that calls itself. Must not be zero, and
+    /// must be less than the 2 MiB stack that async evaluations run on when `async_epoch_yielding` is set.
+    ///
+    /// The default is None, which keeps the limit of wasmtime
+    pub max_wasm_stack: Option<usize>,
+
+    /// The number of times a failed instantiation of an individual's code, such as when the memory of the instance
+    /// cannot be allocated, is tried again before the error is captured on the individual. A failure while the code
+    /// runs (a trap, an error from a host function, running out of time or going over a `CallLimit` or the
+    /// `host_call_budget`) is never retried, because it would happen again and the host functions may already have
+    /// changed the state.
+    ///
+    /// The default is 0
+    pub evaluation_retries: usize,
+
+    /// The most times that `Individual::execute_turns` calls the main entry point during one evaluation, such as the
+    /// most moves of a turn-based game. Must be at least one.
+    ///
+    /// The default is 1000
+    pub max_turns: usize,
+
+    /// When true, every call to a function import is recorded with its arguments and results, and can be read back
+    /// from `Individual::get_host_call_trace` after the individual has run. Tracing slows down every host call, so it
+    /// is best used on a World created just to replay the most fit individuals. Cannot be combined with
+    /// `async_epoch_yielding`.
+    ///
+    /// The default is false
+    pub trace_host_calls: bool,
+
+    /// Limits the total number of calls to all function imports during one evaluation of an individual, such as the
+    /// number of moves a player may make in a game. Going over the budget applies its `CallLimitPolicy` in the same
+    /// way as going over an import's own `CallLimit`, and `Individual::exceeded_host_call_budget` reports it to the
+    /// fitness function. The budget is applied to the imports added after the World is created, so it must be set in
+    /// the configuration passed to `World::new`. Cannot be combined with `async_epoch_yielding`.
+    ///
+    /// The default is None
+    pub host_call_budget: Option<CallLimit>,
+
+    /// Seeds the random numbers of the World: the GeneticEngine that writes and breeds the code, and the built-in
+    /// `rand_u32` import. Only the seed passed to `World::new` is used.
+    ///
+    /// The default is None, which seeds the GeneticEngine randomly and the `rand_u32` import with zero
+    pub seed: Option<u64>,
+
+    /// When true, the World adds a `rand_u32` function import (see `RANDOM_IMPORT`) before any other import, so that
+    /// the evolved code can use random numbers. Its values come from a generator that is restarted at the beginning of
+    /// each evaluation of an individual from the `seed` and the individual's ID, so an individual sees the same values
+    /// every time it is evaluated, and a run can be repeated exactly.
+    ///
+    /// The default is false
+    pub random_import: bool,
+
+    /// When true, the Divide and Remainder instructions count each time their divisor is zero, and every conversion
+    /// from a float to an integer counts each time the float is NaN or out of range and has to be saturated by the
+    /// `ConversionProfile::Saturating`. The counts of an individual's most recent evaluation are returned by
+    /// `Individual::get_numeric_events`. Counting calls a host function for each event and each conversion, so the
+    /// code runs more slowly. Cannot be used with ensembles.
+    ///
+    /// The default is false
+    pub numeric_telemetry: bool,
+
+    /// How instructions convert values between integers and floats when they read or write a slot of another type. This
+    /// applies to every instruction, and to the `ReferenceEvaluator` made by `World::reference_evaluator`.
+    ///
+    /// The default is `ConversionProfile::Saturating`
+    pub conversion_profile: ConversionProfile,
+
     /// The maximum amount of code that any individual may have
     ///
     /// The default is 100
     pub individual_max_points: usize,
 
+    /// The most that the code of any new individual may cost to run, as estimated by the `cost_model`. Points count
+    /// the amount of code, while cost counts the work it does, so this keeps a few points of nested loops from being
+    /// bred. Random code, mutations and crossovers are all held to it.
+    ///
+    /// The default is None, for no limit
+    pub individual_max_cost: Option<u64>,
+
+    /// How the cost of code is estimated for `individual_max_cost`
+    ///
+    /// The default is `CostModel::default()`
+    pub cost_model: CostModel,
+
+    /// The maximum amount of code of a new random individual, such as those of the first generation. Keeping this
+    /// below `individual_max_points` starts the islands with small programs, which are left room to grow through
+    /// mutation.
+    ///
+    /// The default is None, which uses `individual_max_points`
+    pub initial_max_points: Option<usize>,
+
+    /// How the sizes of the programs of the first generation of each island are chosen
+    ///
+    /// The default is Initialization::Uniform
+    pub initialization: Initialization,
+
     /// The number of individuals on each island. Before running a generation, the island will be filled with the
     /// children of genetic selection if there was a previous generation, or new random individuals if there was no
     /// previous generation.
@@ -46,39 +163,138 @@ pub struct WorldConfiguration {
 
     /// The number of individuals whose code will be copied as-is to the next generation. This can help preserve highly
     /// fit code. Set to zero to disable elitism. ref https://en.wikipedia.org/wiki/Genetic_algorithm#Elitism
+    ///
+    /// The elites all have different code: a pick that is identical to an elite already chosen is replaced by the next
+    /// most fit unique individual, so that one dominant program cannot take every elite slot. When there are fewer
+    /// unique programs than elites, the rest of the slots are filled with bred children.
     pub elite_individuals_per_generation: usize,
 
+    /// Every this many generations, the World records the code of the most fit individual of each island in its
+    /// `champion_listings`. Set to zero to record nothing.
+    ///
+    /// The default is 0
+    pub champion_listing_interval: usize,
+
+    /// After each generation, every island runs this many of the most fit individuals of each other island and scores
+    /// them with its own callbacks. The mean scores fill in the `cross_evaluation` of each island's GenerationStats,
+    /// which together form the matrix returned by `World::cross_evaluation_matrix`. Set to zero to skip the extra runs.
+    ///
+    /// The default is 0
+    pub cross_evaluation_elites: usize,
+
     /// After this many generations across all islands, some of the individual will migrate to new islands. Set to zero
-    /// to disable automatic migrations.
+    /// to disable automatic migrations, and migrate with `World::migrate_now` instead.
     pub generations_between_migrations: usize,
 
-    /// The number of individuals that will migrate from one island to another.
+    /// The number of individuals that will migrate from one island to another. Individual islands may override this
+    /// with `Island::set_number_of_individuals_migrating`.
     pub number_of_individuals_migrating: usize,
 
     /// When it is time for a migration, a new island will be selected for the individual according to the specified
     /// algorithm.
     pub migration_algorithm: MigrationAlgorithm,
 
+    /// Whether whole individuals or subtrees of code move between the islands during a migration. With
+    /// `MigrationPayload::Fragments`, `number_of_individuals_migrating` is the number of subtrees sent by each island.
+    ///
+    /// The default is `MigrationPayload::Individuals`
+    pub migration_payload: MigrationPayload,
+
     /// If false, individuals selected for migration are removed from their home island. If true, the selected
-    /// individuals are cloned and the clone is moved. The default is true
+    /// individuals are cloned and the clone is moved. Individual islands may override this with
+    /// `Island::set_clone_migrated_individuals`. The default is true
     pub clone_migrated_individuals: bool,
 
-    /// The SelectionCurve that will be used when choosing which individual will participate in migration. The default
-    /// is PreferenceForFit.
-    pub select_for_migration: SelectionCurve,
+    /// How the individuals that will participate in migration are chosen. The default is the PreferenceForFit curve.
+    pub select_for_migration: SelectionStrategy,
+
+    /// How a fit parent is chosen for genetic operations. The default is the PreferenceForFit curve. Islands of a
+    /// thousand or more individuals may breed better with `SelectionCurve::koza_over_selection`, and
+    /// `SelectionStrategy::tournament` sets the selection pressure by the size of the tournament.
+    pub select_as_parent: SelectionStrategy,
+
+    /// How the second parent of each crossover is chosen once the first has been selected with `select_as_parent`.
+    /// `ParentPairing::Distinct` keeps an individual from being crossed with itself, and the assortative pairings
+    /// match parents that are alike or unlike in fitness or structure.
+    ///
+    /// The default is ParentPairing::Independent
+    pub parent_pairing: ParentPairing,
+
+    /// How an elite individual is chosen to preserve for the next generation. The default is the
+    /// StrongPreferenceForFit curve.
+    pub select_as_elite: SelectionStrategy,
 
-    /// The SelectionCurve that will be used when choosing a fit parent for genetic operations. The default is
-    /// PreferenceForFit.
-    pub select_as_parent: SelectionCurve,
+    /// How the islands breed during their first generations. Each island may override this with `Island::set_warm_up`.
+    ///
+    /// The default is WarmUp::None
+    pub warm_up: WarmUp,
+
+    /// The number of fitness cases (games, seeds, data points) that the islands can evaluate individuals on. The World
+    /// only counts the cases; the callbacks decide what each case index means. Zero turns off the fitness case
+    /// schedule and `IslandCallbacks::use_fitness_cases` is never called.
+    ///
+    /// The default is 0
+    pub fitness_cases: usize,
 
-    /// The SelectionCurve used when choosing an elite individual to preserve for the next generation. The default is
-    /// StrongPreferenceForFit.
-    pub select_as_elite: SelectionCurve,
+    /// Which of the `fitness_cases` each generation is evaluated on. Evaluating on a rotating random subset cuts the
+    /// cost of each generation on domains with hundreds of cases, while every case still gets used over time.
+    ///
+    /// The default is FitnessCaseSchedule::All
+    pub fitness_case_schedule: FitnessCaseSchedule,
 
-    /// Determine how the world runs with regards to multi-threading. Placeholder: currently multi-threading is not
-    /// implemented
+    /// Determine how the world runs with regards to multi-threading: whether the individuals of a generation are
+    /// evaluated on the calling thread, with each island on a worker thread, or with the individuals of each island
+    /// spread over worker threads. Only used by `World::run_one_generation_threaded` and
+    /// `World::run_generations_while_threaded`. Cannot be used with the `async` feature.
+    ///
+    /// The default is ThreadingModel::None
     pub threading_model: ThreadingModel,
 
+    /// The number of individuals on an island that may be evaluated at the same time. Only used with the `async`
+    /// feature. When greater than one, each evaluation runs on its own clone of the island's callbacks, made after
+    /// `pre_generation_run`.
+    ///
+    /// The default is 1
+    pub max_in_flight: usize,
+
+    /// The number of threads that breed and compile the next generation of each island in `fill_all_islands`. Each
+    /// thread uses its own handle of the genetic engine, so the parents are still chosen on the calling thread but the
+    /// children are created and compiled in parallel. Compiling is the most expensive part of filling an island.
+    ///
+    /// The default is 1, which fills the islands on the calling thread
+    pub breeding_threads: usize,
+
+    /// The number of children in a row that may fail to build into a Wasm module before `fill_all_islands` gives up and
+    /// returns the error. Each child that fails is replaced by a newly bred one and counted in the
+    /// `GenerationStats::failed_children` of its island. Set to zero to return the first error.
+    ///
+    /// The default is 10
+    pub max_child_build_retries: usize,
+
+    /// When true, the parts of the module that are the same for every individual (the types, the imports and the
+    /// export) are emitted once and kept, and only the body of each individual's main function is emitted after that.
+    /// This saves most of the emission work for a World with many imports. Each module is still compiled and validated
+    /// by wasmtime in full. `SlotInit::RandomPerCall` and `numeric_telemetry` add imports of their own to each module,
+    /// so they always emit the whole module.
+    ///
+    /// The default is false
+    pub incremental_emission: bool,
+
+    /// When true, the wasm engine is created with async support. Individuals must then be run with
+    /// `Individual::execute_async`, which yields back to the executor every millisecond instead of blocking the
+    /// thread until the code finishes. Requires the `async` feature.
+    ///
+    /// The default is false
+    pub async_epoch_yielding: bool,
+
+    /// When set, the individuals of an island are evaluated in time slices of this many milliseconds. Up to
+    /// `max_in_flight` evaluations are started, and each one runs for a slice before it yields and the next one in line
+    /// runs, round-robin, so that a few long-running individuals do not hold up the rest of the generation. Requires
+    /// `async_epoch_yielding`.
+    ///
+    /// The default is None, which yields every millisecond and runs whichever evaluation the executor polls next
+    pub evaluation_slice_ms: Option<u64>,
+
     /// The average number of times the 'Mutation' genetic operation will be chosen. The `mutation_rate` and
     /// `crossover_rate` are summed and then a random value is picked in that range to the final rate is dependant upon
     /// both values.
@@ -97,6 +313,30 @@ pub struct WorldConfiguration {
     /// The default value is 9. If the default `mutation_rate` is also used, this equates to 90%
     pub crossover_rate: u8,
 
+    /// The average number of times the 'ArchiveMutation' genetic operation will be chosen, relative to the
+    /// `mutation_rate` and `crossover_rate`. An archive mutation replaces points of the code with subtrees from the
+    /// `subtree_archive`, and it uses `max_mutation_points` in the same way as a mutation. Requires a `subtree_archive`
+    /// when above zero.
+    ///
+    /// The default value is 0
+    pub archive_mutation_rate: u8,
+
+    /// The average number of times the 'OperandMutation' genetic operation will be chosen, relative to the
+    /// `mutation_rate` and `crossover_rate`. An operand mutation keeps every instruction and gives one of their slots
+    /// or constants a new random value, which is a much finer step than replacing an instruction. It uses
+    /// `max_mutation_points` for the number of operands changed.
+    ///
+    /// The default value is 0
+    pub operand_mutation_rate: u8,
+
+    /// The average number of times the 'HomologousCrossover' genetic operation will be chosen, relative to the
+    /// `mutation_rate` and `crossover_rate`. A homologous crossover replaces segments of the first parent with segments
+    /// of the second that are about the same size and in about the same place, so the children stay close to the size
+    /// of their parents and the code does not bloat. It uses `max_crossover_points` for the number of segments.
+    ///
+    /// The default value is 0
+    pub homologous_crossover_rate: u8,
+
     /// The maximum number of code items that will be mutated (replaced with new random code) when the 'Mutation'
     /// operation is chosen. The actual value is random between one and this number. Must be at least one if mutation
     /// is used at all.
@@ -104,17 +344,62 @@ pub struct WorldConfiguration {
     /// The default value is 1
     pub max_mutation_points: u8,
 
-    /// The maximum number of times the code streams will be swapped during the Crossover operation. The actual value is
-    /// random between one and this number. Must be at least one if crossover is used at all.
+    /// The maximum number of times the code streams will be swapped during the Crossover operation, or the maximum
+    /// number of segments replaced by the HomologousCrossover operation. The actual value is random between one and
+    /// this number. Must be at least one if either crossover is used at all.
     ///
     /// The default value is 2
     pub max_crossover_points: u8,
+
+    /// Where the Crossover and HomologousCrossover operations may cut the code of the parents, such as never inside a
+    /// loop, so that seeded scaffolding survives crossovers
+    ///
+    /// The default is `CrossoverCuts::Anywhere`
+    pub crossover_cuts: CrossoverCuts,
+
+    /// When set, the code weights are adapted toward the composition of the elites after every generation. The elites
+    /// are the `elite_individuals_per_generation` most fit individuals of each island.
+    ///
+    /// The default is None, which leaves the code weights where they were set
+    pub code_weight_adaptation: Option<CodeWeightAdaptation>,
+
+    /// When set, the subtrees of the elites of every island are added to the archive after every generation, and the
+    /// 'ArchiveMutation' operation inserts them into children. The elites are the `elite_individuals_per_generation`
+    /// most fit individuals of each island. The archive may start with subtrees in it, such as ones saved from an
+    /// earlier run, but only the archive passed to `World::new` is used. Its capacity must be at least one.
+    ///
+    /// The default is None
+    pub subtree_archive: Option<SubtreeArchive>,
+
+    /// When set, the most fit individuals of each island are simplified and have their constants tuned after every
+    /// generation, and the variants that are at least as fit join the next generation.
+    ///
+    /// The default is None
+    pub local_search: Option<LocalSearch>,
+
+    /// When set, `World::run_generations_while` writes checkpoints on this schedule and removes the old ones. A
+    /// checkpoint that cannot be written stops the run with the error.
+    ///
+    /// The default is None, which only writes checkpoints through `World::write_checkpoint`
+    pub checkpoints: Option<CheckpointSchedule>,
+
+    /// When set, the individuals of all the islands are shared out again every few generations, with more going to the
+    /// islands whose champions made the World's hall of fame. An island then breeds its own share instead of
+    /// `individuals_per_island`, and the total stays the same.
+    ///
+    /// The default is None, which keeps `individuals_per_island` on every island
+    pub island_sizing: Option<IslandSizing>,
 }
 
 impl WorldConfiguration {
     pub fn slot_count(&self) -> usize {
         self.main_entry_point.params().len() + self.main_entry_point.results().len() + self.work_slots.len()
     }
+
+    // The maximum amount of code of a new random individual
+    pub(crate) fn random_max_points(&self) -> usize {
+        self.initial_max_points.unwrap_or(self.individual_max_points)
+    }
 }
 
 impl Default for WorldConfiguration {
@@ -131,21 +416,58 @@ impl Default for WorldConfiguration {
             is_signed: false,
             memory_size: 0,
             individual_run_time_ms: 250,
+            epoch_ticks_per_ms: 1,
+            fuel_per_evaluation: None,
+            max_wasm_stack: None,
+            evaluation_retries: 0,
+            max_turns: 1000,
+            trace_host_calls: false,
+            host_call_budget: None,
+            seed: None,
+            random_import: false,
+            numeric_telemetry: false,
+            conversion_profile: ConversionProfile::Saturating,
             individual_max_points: 100,
+            individual_max_cost: None,
+            cost_model: CostModel::default(),
+            initial_max_points: None,
+            initialization: Initialization::Uniform,
             individuals_per_island: 100,
             elite_individuals_per_generation: 2,
+            champion_listing_interval: 0,
+            cross_evaluation_elites: 0,
             generations_between_migrations: 10,
             number_of_individuals_migrating: 10,
             migration_algorithm: MigrationAlgorithm::Circular,
+            migration_payload: MigrationPayload::Individuals,
             clone_migrated_individuals: true,
-            select_for_migration: SelectionCurve::PreferenceForFit,
-            select_as_parent: SelectionCurve::PreferenceForFit,
-            select_as_elite: SelectionCurve::StrongPreferenceForFit,
+            select_for_migration: SelectionStrategy::Curve(SelectionCurve::PreferenceForFit),
+            select_as_parent: SelectionStrategy::Curve(SelectionCurve::PreferenceForFit),
+            parent_pairing: ParentPairing::Independent,
+            select_as_elite: SelectionStrategy::Curve(SelectionCurve::StrongPreferenceForFit),
+            warm_up: WarmUp::None,
+            fitness_cases: 0,
+            fitness_case_schedule: FitnessCaseSchedule::All,
             threading_model: ThreadingModel::None,
+            max_in_flight: 1,
+            breeding_threads: 1,
+            max_child_build_retries: 10,
+            incremental_emission: false,
+            async_epoch_yielding: false,
+            evaluation_slice_ms: None,
             mutation_rate: 1,
             crossover_rate: 9,
+            archive_mutation_rate: 0,
+            operand_mutation_rate: 0,
+            homologous_crossover_rate: 0,
             max_mutation_points: 1,
             max_crossover_points: 2,
+            crossover_cuts: CrossoverCuts::Anywhere,
+            code_weight_adaptation: None,
+            subtree_archive: None,
+            local_search: None,
+            checkpoints: None,
+            island_sizing: None,
         }
     }
 }